@@ -0,0 +1,30 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform int skybox;                  // 1 = sample the cubemap, 0 = gradient
+uniform vec3 topColor;               // gradient, top edge of the screen
+uniform vec3 bottomColor;            // and bottom edge
+uniform samplerCube cubemap;         // srgb texture, sampling decodes it already
+uniform mat4 inverseViewProjection;  // rotation-only view, so directions come out
+
+// srgb in, linear out, see phong.vert
+vec3 srgbToLinear(vec3 c)
+{
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main()
+{
+    if (skybox == 0) {
+        // blended in linear, so the middle of the gradient does not dip darker
+        FragColor = vec4(mix(srgbToLinear(bottomColor), srgbToLinear(topColor), vUv.y), 1.0);
+        return;
+    }
+
+    // screen position -> point on the far plane -> direction from the camera
+    vec4 far = inverseViewProjection * vec4(vUv * 2.0 - 1.0, 1.0, 1.0);
+    FragColor = vec4(texture(cubemap, normalize(far.xyz / far.w)).rgb, 1.0);
+}
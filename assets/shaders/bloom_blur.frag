@@ -0,0 +1,22 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D image;
+uniform vec2 direction; // one texel along x or y, the blur is separable
+
+// 9 tap gaussian done as 5 linear fetches: the hardware filter blends pairs of taps
+const float offsets[3] = float[](0.0, 1.3846153846, 3.2307692308);
+const float weights[3] = float[](0.2270270270, 0.3162162162, 0.0702702703);
+
+void main()
+{
+    vec3 color = texture(image, vUv).rgb * weights[0];
+    for (int i = 1; i < 3; i++) {
+        color += texture(image, vUv + direction * offsets[i]).rgb * weights[i];
+        color += texture(image, vUv - direction * offsets[i]).rgb * weights[i];
+    }
+    FragColor = vec4(color, 1.0);
+}
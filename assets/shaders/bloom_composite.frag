@@ -0,0 +1,16 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D scene; // the frame as drawn
+uniform sampler2D bloom; // blurred bright parts, half resolution
+uniform float intensity;
+
+// glow is light, so it is added on top, never blended over
+void main()
+{
+    vec3 color = texture(scene, vUv).rgb + texture(bloom, vUv).rgb * intensity;
+    FragColor = vec4(color, 1.0);
+}
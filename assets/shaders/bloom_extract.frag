@@ -0,0 +1,21 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D scene;  // hdr frame, values above 1.0 survive in here
+uniform float threshold;  // brightness where glow starts
+
+// keep only what is brighter than threshold, with a soft knee so the glow fades in
+// instead of switching on at one exact brightness
+void main()
+{
+    vec3 color = texture(scene, vUv).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    float knee = threshold * 0.5;
+    float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 1e-4);
+    float weight = max(soft, brightness - threshold) / max(brightness, 1e-4);
+    FragColor = vec4(color * weight, 1.0);
+}
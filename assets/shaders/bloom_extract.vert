@@ -0,0 +1,12 @@
+#version 330 core
+
+// fullscreen triangle, no vertex buffer needed
+// vertex 0, 1, 2 -> uv (0,0), (2,0), (0,2): one triangle that covers the whole screen
+out vec2 vUv;
+
+void main()
+{
+    vec2 uv = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    vUv = uv;
+    gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
@@ -0,0 +1,58 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D image;        // the frame, or the first pass's result with the blur in alpha
+uniform sampler2D depth;        // the scene's depth texture
+uniform mat4 inverseProjection; // window depth back to a distance, ortho works too
+uniform vec2 direction;         // one texel along x or y, the blur is separable
+uniform float focus;            // view distance that stays sharp
+uniform float scale;            // blur in pixels per unit of |z - focus| / z, from the aperture
+uniform float maxRadius;        // pixels, the blur never grows past this
+uniform int first;              // 1 = work the blur out from depth, 0 = it is in alpha
+
+float distanceAt(vec2 uv)
+{
+    vec4 ndc = vec4(uv * 2.0 - 1.0, texture(depth, uv).r * 2.0 - 1.0, 1.0);
+    vec4 view = inverseProjection * ndc;
+    return -view.z / view.w;
+}
+
+float blurAt(vec2 uv)
+{
+    // circle of confusion, radius in pixels
+    if (first == 0) {
+        return texture(image, uv).a;
+    }
+    float z = max(distanceAt(uv), 1e-4);
+    return min(abs(z - focus) / z * scale, maxRadius);
+}
+
+// gather along one direction: every neighbour whose own blur reaches this pixel adds to
+// it, so a blurry foreground spreads over what is behind it; a neighbour further away
+// than this pixel only reaches as far as this pixel's blur, the background does not
+// bleed over something sharp in front of it
+void main()
+{
+    float radius = blurAt(vUv);
+    float z = distanceAt(vUv);
+    vec3 sum = texture(image, vUv).rgb;
+    float weights = 1.0;
+    int reach = int(ceil(maxRadius));
+    for (int i = -reach; i <= reach; i++) {
+        if (i == 0) {
+            continue;
+        }
+        vec2 uv = vUv + direction * float(i);
+        float other = blurAt(uv);
+        if (distanceAt(uv) > z) {
+            other = min(other, radius);
+        }
+        float weight = clamp(other - abs(float(i)) + 1.0, 0.0, 1.0);
+        sum += texture(image, uv).rgb * weight;
+        weights += weight;
+    }
+    FragColor = vec4(sum / weights, radius);
+}
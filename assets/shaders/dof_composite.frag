@@ -0,0 +1,14 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D image; // blurred both ways, the blur radius in alpha
+uniform sampler2D depth; // the scene's, passed on like the ssao composite does
+
+void main()
+{
+    FragColor = vec4(texture(image, vUv).rgb, 1.0);
+    gl_FragDepth = texture(depth, vUv).r;
+}
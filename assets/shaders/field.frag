@@ -0,0 +1,12 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D potential; // colored on the cpu, an srgb texture so it reads back linear
+
+void main()
+{
+    FragColor = texture(potential, vUv);
+}
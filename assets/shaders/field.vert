@@ -0,0 +1,29 @@
+#version 330 core
+
+// one quad from gl_VertexID through an atom's nucleus, no vertex buffer
+// drawn as a 4 vertex triangle strip, see field.rs
+
+// camera, see phong.vert
+layout (std140) uniform Frame {
+    mat4 view;
+    mat4 projection;
+    vec4 viewPos;           // camera position, w unused
+    vec4 clipPlane;         // cross-section, see phong.vert
+    vec4 lightPositions[4]; // world space point lights, w unused
+    vec4 lightColor;        // rgb, w unused
+    int lightCount;
+};
+
+uniform vec3 center; // the nucleus, world space
+uniform vec3 right;  // half the quad's width along it
+uniform vec3 up;     // half its height
+
+out vec2 vUv;
+
+void main()
+{
+    vec2 corner = vec2(gl_VertexID & 1, (gl_VertexID >> 1) & 1);
+    vUv = corner;
+    vec3 world = center + right * (corner.x * 2.0 - 1.0) + up * (corner.y * 2.0 - 1.0);
+    gl_Position = projection * view * vec4(world, 1.0);
+}
@@ -4,9 +4,9 @@ out vec4 FragColor;
 // this is the final color output of the fragment shader
 // every pixel that survives rasterization ends up here
 
-uniform vec4 ourColor;
-// uniform = constant per draw call
-// cpu sends this in before drawing each sphere
+in vec4 vColor;
+// per-instance color from the vertex shader
+// cpu fills this into the instance buffer once per sphere
 // this is literally the particle’s color
 
 void main()
@@ -18,7 +18,7 @@ void main()
     // no reflections
     // just raw color
 
-    FragColor = ourColor;
+    FragColor = vColor;
 
     // every fragment of the sphere gets the same color
     // which means spheres are flat-colored blobs
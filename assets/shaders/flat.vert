@@ -0,0 +1,60 @@
+#version 330 core
+
+layout (location = 0) in vec3 aPos;
+// vertex attribute at location 0
+// this comes from your vao
+// each vertex is just a 3d position
+// no normals
+// no texture coords
+// just geometry
+
+layout (location = 2) in vec4 aColor;
+layout (location = 3) in mat4 aModel;
+// per-instance attributes (divisor = 1)
+// these advance once per sphere, not once per vertex
+// aModel moves tiny unit sphere to particle position and scales it down
+// a mat4 attribute eats 4 locations, so 3, 4, 5, 6 are all taken
+
+// camera + cross-section, shared by every world space shader (FrameUniforms in render.rs)
+// view: camera transform
+// converts world space to camera space
+// basically where you are looking from
+// projection: perspective matrix
+// converts 3d to clip space
+// adds depth illusion
+layout (std140) uniform Frame {
+    mat4 view;
+    mat4 projection;
+    vec4 viewPos;           // camera position, w unused
+    vec4 clipPlane;         // cross-section, see phong.vert
+    vec4 lightPositions[4]; // world space point lights, w unused
+    vec4 lightColor;        // rgb, w unused
+    int lightCount;
+};
+
+out vec4 vColor;
+// handed to the fragment shader, converted to linear
+
+// srgb in, linear out, see phong.vert
+vec3 srgbToLinear(vec3 c)
+{
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main()
+{
+    // classic mvp chain
+    // model -> view -> projection
+    // rightmost applies first
+
+    vec4 world = aModel * vec4(aPos, 1.0);
+    gl_Position = projection * view * world;
+    gl_ClipDistance[0] = dot(clipPlane, world);
+
+    // convert local sphere vertex into world position
+    // then into camera space
+    // then into clip space
+    // then rasterizer handles the rest
+
+    vColor = vec4(srgbToLinear(aColor.rgb), aColor.a);
+}
@@ -0,0 +1,53 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D screen; // the frame as drawn, no antialiasing
+uniform vec2 texelSize;   // 1 / resolution
+
+// classic "fxaa lite": find edges by luma contrast, blur along the edge direction only
+#define FXAA_SPAN_MAX 8.0
+#define FXAA_REDUCE_MUL (1.0 / 8.0)
+#define FXAA_REDUCE_MIN (1.0 / 128.0)
+
+void main()
+{
+    const vec3 luma = vec3(0.299, 0.587, 0.114);
+
+    vec3 rgbNW = texture(screen, vUv + vec2(-1.0, -1.0) * texelSize).rgb;
+    vec3 rgbNE = texture(screen, vUv + vec2( 1.0, -1.0) * texelSize).rgb;
+    vec3 rgbSW = texture(screen, vUv + vec2(-1.0,  1.0) * texelSize).rgb;
+    vec3 rgbSE = texture(screen, vUv + vec2( 1.0,  1.0) * texelSize).rgb;
+    vec3 rgbM  = texture(screen, vUv).rgb;
+
+    float lumaNW = dot(rgbNW, luma);
+    float lumaNE = dot(rgbNE, luma);
+    float lumaSW = dot(rgbSW, luma);
+    float lumaSE = dot(rgbSE, luma);
+    float lumaM  = dot(rgbM,  luma);
+
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    // gradient across the edge -> blur direction along it
+    vec2 dir;
+    dir.x = -((lumaNW + lumaNE) - (lumaSW + lumaSE));
+    dir.y =  ((lumaNW + lumaSW) - (lumaNE + lumaSE));
+
+    float dirReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * (0.25 * FXAA_REDUCE_MUL), FXAA_REDUCE_MIN);
+    float rcpDirMin = 1.0 / (min(abs(dir.x), abs(dir.y)) + dirReduce);
+    dir = clamp(dir * rcpDirMin, vec2(-FXAA_SPAN_MAX), vec2(FXAA_SPAN_MAX)) * texelSize;
+
+    vec3 rgbA = 0.5 * (
+        texture(screen, vUv + dir * (1.0 / 3.0 - 0.5)).rgb +
+        texture(screen, vUv + dir * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 rgbB = rgbA * 0.5 + 0.25 * (
+        texture(screen, vUv + dir * -0.5).rgb +
+        texture(screen, vUv + dir *  0.5).rgb);
+
+    // the wide blur overshot into a different surface, fall back to the narrow one
+    float lumaB = dot(rgbB, luma);
+    FragColor = vec4((lumaB < lumaMin || lumaB > lumaMax) ? rgbA : rgbB, 1.0);
+}
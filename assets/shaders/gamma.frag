@@ -0,0 +1,20 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D screen; // srgb texture, comes out of texture() linear
+
+// for windows without an srgb framebuffer: encode by hand what the hardware would have
+vec3 linearToSrgb(vec3 c)
+{
+    c = clamp(c, 0.0, 1.0);
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+void main()
+{
+    vec4 color = texture(screen, vUv);
+    FragColor = vec4(linearToSrgb(color.rgb), color.a);
+}
@@ -0,0 +1,10 @@
+#version 330 core
+
+out vec4 FragColor;
+
+uniform vec4 color; // one color per draw, alpha < 1 for the guide rings
+
+void main()
+{
+    FragColor = color;
+}
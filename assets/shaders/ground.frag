@@ -0,0 +1,107 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+// the same block as in phong.vert, for the camera and the key light
+layout (std140) uniform Frame {
+    mat4 view;
+    mat4 projection;
+    vec4 viewPos;           // camera position, w unused
+    vec4 clipPlane;         // cross-section, the ground is not cut
+    vec4 lightPositions[4]; // world space point lights, w unused
+    vec4 lightColor;        // rgb, w unused
+    int lightCount;
+};
+uniform mat4 inverseViewProjection; // clip space back to the world, ortho works too
+uniform float height;               // world y of the plane
+uniform vec3 fadeCenter;            // under the camera target, where the ground is fullest
+uniform float fade;                 // world units from there to where it is gone
+uniform int grid;                   // 1 = lines over a faint fill, 0 = a solid plane
+// the key light's shadow map, see shadow.rs and phong.frag
+uniform sampler2DShadow shadowMap;
+uniform mat4 lightSpace;
+uniform float normalOffset;
+uniform int shadows;
+
+const vec3 PLANE = vec3(0.18);     // linear, the matte grey of the plane
+const vec3 LINE = vec3(0.55);      // and of the grid lines
+const float MINOR = 1.0;           // world units between lines
+const float MAJOR = 10.0;          // every tenth one stronger
+const float FILL = 0.2;            // opacity of the grid's fill, enough to catch shadows
+
+vec3 unproject(vec2 ndc, float z)
+{
+    vec4 p = inverseViewProjection * vec4(ndc, z, 1.0);
+    return p.xyz / p.w;
+}
+
+float lines(vec2 p, float spacing)
+{
+    // 1 on a line, 0 off it, a pixel wide at any distance; lines closer together than a
+    // few pixels fade out before they turn into moire
+    vec2 cell = p / spacing;
+    vec2 width = fwidth(cell);
+    vec2 distance = abs(fract(cell - 0.5) - 0.5) / max(width, vec2(1e-6));
+    float line = 1.0 - min(min(distance.x, distance.y), 1.0);
+    return line * (1.0 - smoothstep(0.15, 0.4, max(width.x, width.y)));
+}
+
+float keyLight(vec3 p)
+{
+    if (shadows == 0) {
+        return 1.0;
+    }
+    vec4 s = lightSpace * vec4(p + vec3(0.0, normalOffset, 0.0), 1.0);
+    vec3 coords = s.xyz / s.w * 0.5 + 0.5;
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+    vec2 texel = 1.0 / vec2(textureSize(shadowMap, 0));
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            lit += texture(shadowMap, vec3(coords.xy + vec2(x, y) * texel, coords.z));
+        }
+    }
+    return lit / 9.0;
+}
+
+// a fullscreen triangle, every pixel casts its ray at the plane y = height; the point it
+// hits is drawn with its real depth, so atoms stand on the ground and sink into it
+void main()
+{
+    vec2 ndc = vUv * 2.0 - 1.0;
+    vec3 near = unproject(ndc, -1.0);
+    vec3 far = unproject(ndc, 1.0);
+    vec3 ray = far - near;
+    if (abs(ray.y) < 1e-6) {
+        discard;
+    }
+    float t = (height - near.y) / ray.y;
+    if (t < 0.0 || t > 1.0) {
+        discard; // behind the camera or past the far plane
+    }
+    vec3 p = near + ray * t;
+
+    float alpha = 1.0 - smoothstep(0.5 * fade, fade, distance(p.xz, fadeCenter.xz));
+    vec3 color = PLANE;
+    if (grid != 0) {
+        float line = max(lines(p.xz, MINOR) * 0.5, lines(p.xz, MAJOR));
+        color = mix(PLANE, LINE, line);
+        alpha *= mix(FILL, 1.0, line);
+    }
+    if (alpha < 0.01) {
+        discard; // nothing to see, and no depth to hide what is behind
+    }
+
+    // lit from above like the atoms, the key light shadowed
+    vec3 toLight = normalize(lightPositions[0].xyz - p);
+    float diffuse = max(toLight.y, 0.0) * keyLight(p);
+    color *= (0.35 + 0.65 * diffuse) * lightColor.rgb;
+
+    vec4 clip = projection * view * vec4(p, 1.0);
+    gl_FragDepth = clip.z / clip.w * 0.5 + 0.5;
+    FragColor = vec4(color, alpha);
+}
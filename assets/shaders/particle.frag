@@ -0,0 +1,19 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec4 vColor;
+in vec2 vCorner;
+
+void main()
+{
+    // round soft blob instead of a square
+    // bright in the middle, nothing at the rim
+    float d = length(vCorner);
+    if (d > 1.0)
+        discard;
+    float falloff = 1.0 - d * d;
+
+    // drawn with additive blending, so overlapping sparks pile up into a glow
+    FragColor = vec4(vColor.rgb, vColor.a * falloff * falloff);
+}
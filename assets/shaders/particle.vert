@@ -0,0 +1,40 @@
+#version 330 core
+
+layout (location = 0) in vec3 aPos;
+// corner of the unit quad, xy in -1..1, z unused
+
+layout (location = 2) in vec4 aColor;
+layout (location = 3) in mat4 aModel;
+// same instance layout as every other batch
+// only translation + uniform scale are used: where the particle is and how big
+
+// camera, see phong.vert
+layout (std140) uniform Frame {
+    mat4 view;
+    mat4 projection;
+    vec4 viewPos;           // camera position, w unused
+    vec4 clipPlane;         // cross-section, see phong.vert
+    vec4 lightPositions[4]; // world space point lights, w unused
+    vec4 lightColor;        // rgb, w unused
+    int lightCount;
+};
+
+out vec4 vColor;
+out vec2 vCorner;
+
+// srgb in, linear out, see phong.vert
+vec3 srgbToLinear(vec3 c)
+{
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main()
+{
+    // billboard: push the corner out in view space, so the quad always faces the camera
+    vec4 center = view * aModel[3];
+    float size = length(aModel[0].xyz);
+    gl_Position = projection * (center + vec4(aPos.xy * size, 0.0, 0.0));
+
+    vColor = vec4(srgbToLinear(aColor.rgb), aColor.a);
+    vCorner = aPos.xy;
+}
@@ -0,0 +1,94 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec3 vFragPos;
+in vec3 vNormal;
+in vec4 vColor;
+in vec2 vUv;
+
+#define MAX_LIGHTS 4
+// camera + lights, the same block as in phong.vert
+// lightCount = how many lightPositions are actually set, lightColor 1.0 = white
+layout (std140) uniform Frame {
+    mat4 view;
+    mat4 projection;
+    vec4 viewPos;           // camera position, w unused
+    vec4 clipPlane;         // cross-section, see phong.vert
+    vec4 lightPositions[4]; // world space point lights, w unused
+    vec4 lightColor;        // rgb, w unused
+    int lightCount;
+};
+// the bound material, see material.rs
+uniform vec3 materialColor; // tint multiplied onto the per-instance color, linear
+uniform float shininess;    // blinn-phong exponent
+uniform float specularStrength;
+uniform float opacity;      // multiplied onto the instance alpha
+uniform sampler2D surface;  // the material's texture, srgb so it samples linear
+uniform int textured;       // 1 = multiply it in
+uniform int unlit;          // 1 = skip lighting: unlit materials, lines and points
+// the key light's shadow map, see shadow.rs
+uniform sampler2DShadow shadowMap;
+uniform mat4 lightSpace;    // world -> the map
+uniform float normalOffset; // world units off the surface for the lookup, against acne
+uniform int shadows;        // 1 = the map holds this frame
+
+float keyLight(vec3 normal)
+{
+    // 1 = lit, 0 = fully in shadow; 3 x 3 comparisons, each one blended by the hardware
+    if (shadows == 0) {
+        return 1.0;
+    }
+    vec4 p = lightSpace * vec4(vFragPos + normal * normalOffset, 1.0);
+    vec3 coords = p.xyz / p.w * 0.5 + 0.5;
+    if (coords.z > 1.0) {
+        return 1.0; // beyond the far plane, nothing there to cast
+    }
+    vec2 texel = 1.0 / vec2(textureSize(shadowMap, 0));
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            lit += texture(shadowMap, vec3(coords.xy + vec2(x, y) * texel, coords.z));
+        }
+    }
+    return lit / 9.0;
+}
+
+void main()
+{
+    // blinn-phong
+    // ambient + diffuse + specular, nothing more
+    // enough to make spheres look round instead of like flat discs
+
+    vec3 base = vColor.rgb * materialColor;
+    if (textured != 0) {
+        base *= texture(surface, vUv).rgb;
+    }
+
+    if (unlit != 0) {
+        FragColor = vec4(base, vColor.a * opacity);
+        return;
+    }
+
+    vec3 normal = normalize(vNormal);
+    vec3 viewDir = normalize(viewPos.xyz - vFragPos);
+
+    float ambient = 0.15;
+    float diffuse = 0.0;
+    float specular = 0.0;
+
+    // every extra light is weaker than the first so the key light stays dominant
+    // and only the key light casts shadows
+    float shadow = keyLight(normal);
+    for (int i = 0; i < lightCount && i < MAX_LIGHTS; i++) {
+        float weight = (i == 0) ? shadow : 0.35;
+        vec3 lightDir = normalize(lightPositions[i].xyz - vFragPos);
+        vec3 halfway = normalize(lightDir + viewDir);
+
+        diffuse += weight * max(dot(normal, lightDir), 0.0);
+        specular += weight * pow(max(dot(normal, halfway), 0.0), shininess) * specularStrength;
+    }
+
+    vec3 color = (ambient + diffuse) * lightColor.rgb * base + specular * lightColor.rgb;
+    FragColor = vec4(color, vColor.a * opacity);
+}
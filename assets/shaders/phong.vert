@@ -0,0 +1,56 @@
+#version 330 core
+
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+// per-vertex data: position + normal
+// for a unit sphere the normal is literally the position
+// but the mesh ships it anyway so other shapes work too
+
+layout (location = 2) in vec4 aColor;
+layout (location = 3) in mat4 aModel;
+// per-instance attributes, same layout as the flat shader
+
+layout (location = 7) in vec2 aUv;
+// texture coordinates, only spheres have them (0, 0 for everything else)
+
+// one block for the whole frame, uploaded once and shared by every world space shader
+// (FrameUniforms in render.rs), the light half is for phong.frag
+// clipPlane: cross-section plane (slice.rs), world positions with dot(clipPlane, p) < 0
+// are cut away; only takes effect while GL_CLIP_DISTANCE0 is enabled
+layout (std140) uniform Frame {
+    mat4 view;
+    mat4 projection;
+    vec4 viewPos;           // camera position, w unused
+    vec4 clipPlane;         // cross-section, see below
+    vec4 lightPositions[4]; // world space point lights, w unused
+    vec4 lightColor;        // rgb, w unused
+    int lightCount;
+};
+
+out vec3 vFragPos; // world space position for lighting
+out vec3 vNormal;  // world space normal
+out vec4 vColor;
+out vec2 vUv;
+
+// colors arrive as srgb, like everything a color picker or atomsim.toml hands out
+// lighting and blending happen in linear, the framebuffer encodes back on write
+vec3 srgbToLinear(vec3 c)
+{
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main()
+{
+    vec4 world = aModel * vec4(aPos, 1.0);
+    vFragPos = world.xyz;
+
+    // mat3(model) is only correct for uniform scale
+    // spheres only ever get uniform scale so we skip inverse-transpose
+    // (which would be an inverse per vertex, no thanks)
+    vNormal = mat3(aModel) * aNormal;
+
+    vColor = vec4(srgbToLinear(aColor.rgb), aColor.a);
+    vUv = aUv;
+    gl_Position = projection * view * world;
+    gl_ClipDistance[0] = dot(clipPlane, world);
+}
@@ -0,0 +1,18 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec4 vColor;
+
+void main()
+{
+    // points are squares by default
+    // fade alpha toward the edge so every sample is a soft round dot
+    vec2 offset = gl_PointCoord - vec2(0.5);
+    float falloff = 1.0 - smoothstep(0.2, 0.5, length(offset));
+
+    if (falloff <= 0.0)
+        discard;
+
+    FragColor = vec4(vColor.rgb, vColor.a * falloff);
+}
@@ -0,0 +1,38 @@
+#version 330 core
+
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec4 aColor;
+// one vertex = one sample of |psi|^2
+// no mesh, no instancing, the point IS the particle
+
+// camera + cross-section, see phong.vert
+layout (std140) uniform Frame {
+    mat4 view;
+    mat4 projection;
+    vec4 viewPos;           // camera position, w unused
+    vec4 clipPlane;         // cross-section, see phong.vert
+    vec4 lightPositions[4]; // world space point lights, w unused
+    vec4 lightColor;        // rgb, w unused
+    int lightCount;
+};
+
+uniform float pointSize; // size in pixels at distance 1, shrinks with depth
+
+out vec4 vColor;
+
+// srgb in, linear out, see phong.vert
+vec3 srgbToLinear(vec3 c)
+{
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main()
+{
+    gl_Position = projection * view * vec4(aPos, 1.0);
+    gl_ClipDistance[0] = dot(clipPlane, vec4(aPos, 1.0));
+
+    // perspective-ish point size so the cloud does not look flat
+    gl_PointSize = max(pointSize / gl_Position.w, 1.0);
+
+    vColor = vec4(srgbToLinear(aColor.rgb), aColor.a);
+}
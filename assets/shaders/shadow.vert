@@ -0,0 +1,12 @@
+#version 330 core
+
+layout (location = 0) in vec3 aPos;
+layout (location = 3) in mat4 aModel;
+// the instanced meshes as they are, only position and model matter here
+
+uniform mat4 lightSpace; // world -> the shadow map, see shadow.rs
+
+void main()
+{
+    gl_Position = lightSpace * aModel * vec4(aPos, 1.0);
+}
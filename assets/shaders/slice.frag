@@ -0,0 +1,12 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D density; // already colored on the cpu, srgb written straight through
+
+void main()
+{
+    FragColor = vec4(texture(density, vUv).rgb, 1.0);
+}
@@ -0,0 +1,15 @@
+#version 330 core
+
+// one screen-aligned quad from gl_VertexID, no vertex buffer
+// drawn as a 4 vertex triangle strip
+uniform mat4 projection; // pixels -> clip space, y down like the text overlay
+uniform vec4 rect;       // x, y, width, height in pixels
+
+out vec2 vUv;
+
+void main()
+{
+    vec2 corner = vec2(gl_VertexID & 1, (gl_VertexID >> 1) & 1);
+    vUv = vec2(corner.x, 1.0 - corner.y);
+    gl_Position = projection * vec4(rect.xy + corner * rect.zw, 0.0, 1.0);
+}
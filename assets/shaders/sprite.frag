@@ -0,0 +1,19 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec4 vColor;
+in vec2 vUv;
+
+uniform sampler2D sprite; // r = shading of a lit ball, a = its soft round edge
+uniform bool shaded;      // false for things that glow on their own (photons)
+
+void main()
+{
+    vec4 texel = texture(sprite, vUv);
+    // the corners of the quad: nothing there, and no depth written for them either
+    if (texel.a < 0.1)
+        discard;
+    float light = shaded ? texel.r : 1.0;
+    FragColor = vec4(vColor.rgb * light, vColor.a * texel.a);
+}
@@ -0,0 +1,86 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D depth; // the scene's depth texture
+uniform mat4 projection;
+uniform mat4 inverseProjection; // back from window depth to view space, ortho works too
+uniform vec2 texel;             // one pixel in uv
+uniform float radius;           // how far to look for occluders, times the distance
+uniform int samples;
+
+vec3 viewPosition(vec2 uv)
+{
+    vec4 ndc = vec4(uv * 2.0 - 1.0, texture(depth, uv).r * 2.0 - 1.0, 1.0);
+    vec4 view = inverseProjection * ndc;
+    return view.xyz / view.w;
+}
+
+float hash(vec2 p)
+{
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// how much of the hemisphere above each pixel is blocked by nearby geometry:
+// points around the surface, pushed along its normal, that end up behind what the depth
+// buffer holds are inside something; the blur pass smooths the noise out
+void main()
+{
+    if (texture(depth, vUv).r >= 1.0) {
+        FragColor = vec4(1.0); // background, nothing to shade
+        return;
+    }
+    vec3 p = viewPosition(vUv);
+
+    // normal from the neighbours, on each axis the side closer in depth so silhouettes
+    // do not get a normal bent over the edge
+    vec3 left = p - viewPosition(vUv - vec2(texel.x, 0.0));
+    vec3 right = viewPosition(vUv + vec2(texel.x, 0.0)) - p;
+    vec3 down = p - viewPosition(vUv - vec2(0.0, texel.y));
+    vec3 up = viewPosition(vUv + vec2(0.0, texel.y)) - p;
+    vec3 dx = abs(left.z) < abs(right.z) ? left : right;
+    vec3 dy = abs(down.z) < abs(up.z) ? down : up;
+    vec3 normal = normalize(cross(dx, dy));
+    if (dot(normal, p) > 0.0) {
+        normal = -normal; // towards the camera
+    }
+
+    // the pattern turned per pixel, banding becomes noise
+    float angle = hash(gl_FragCoord.xy) * 6.2831853;
+    vec3 random = vec3(cos(angle), sin(angle), 0.0);
+    vec3 tangent = random - normal * dot(random, normal);
+    tangent = length(tangent) < 1e-3 ? vec3(1.0, 0.0, 0.0) : normalize(tangent);
+    vec3 bitangent = cross(normal, tangent);
+
+    float reach = radius * length(p);
+    float bias = 0.02 * reach;
+    float occlusion = 0.0;
+    for (int i = 0; i < samples; i++) {
+        // spiral over the hemisphere, lengths spread on their own sequence and packed
+        // towards the surface, close occluders matter most
+        float f = (float(i) + 0.5) / float(samples);
+        float z = 1.0 - f;
+        float ring = sqrt(1.0 - z * z);
+        float phi = float(i) * 2.3999632; // golden angle
+        vec3 direction = vec3(cos(phi) * ring, sin(phi) * ring, z);
+        float along = fract(float(i) * 0.618034 + 0.5);
+        along = mix(0.1, 1.0, along * along);
+        vec3 s = p + (tangent * direction.x + bitangent * direction.y + normal * direction.z)
+            * (reach * along);
+
+        vec4 clip = projection * vec4(s, 1.0);
+        vec2 uv = clip.xy / clip.w * 0.5 + 0.5;
+        if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0)))) {
+            continue;
+        }
+        float sceneZ = viewPosition(uv).z;
+        // something far in front (a nucleus between the camera and a distant shell) is
+        // not touching this surface, it fades out past the reach
+        float range = smoothstep(0.0, 1.0, reach / max(abs(p.z - sceneZ), 1e-4));
+        occlusion += (sceneZ >= s.z + bias ? 1.0 : 0.0) * range;
+    }
+    float ao = 1.0 - occlusion / float(max(samples, 1));
+    FragColor = vec4(ao, ao, ao, 1.0);
+}
@@ -0,0 +1,35 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D occlusion;
+uniform sampler2D depth;
+uniform vec2 direction; // one texel along x or y, the blur is separable
+uniform mat4 inverseProjection; // depths compared as distances, not window depth
+
+float linear(vec2 uv)
+{
+    vec4 ndc = vec4(uv * 2.0 - 1.0, texture(depth, uv).r * 2.0 - 1.0, 1.0);
+    vec4 view = inverseProjection * ndc;
+    return -view.z / view.w;
+}
+
+// wide enough to hide the per pixel rotation, but only over pixels at about the same
+// depth, the shading of a nucleus does not bleed onto the shell behind it
+void main()
+{
+    float center = linear(vUv);
+    float sum = 0.0;
+    float weights = 0.0;
+    for (int i = -4; i <= 4; i++) {
+        vec2 uv = vUv + direction * float(i);
+        float apart = abs(linear(uv) - center) / max(center, 1e-4);
+        float weight = exp(-float(i * i) / 18.0) * (1.0 - smoothstep(0.0, 0.05, apart));
+        sum += texture(occlusion, uv).r * weight;
+        weights += weight;
+    }
+    float ao = sum / max(weights, 1e-4);
+    FragColor = vec4(ao, ao, ao, 1.0);
+}
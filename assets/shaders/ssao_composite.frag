@@ -0,0 +1,20 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D scene;     // the frame as drawn
+uniform sampler2D occlusion; // blurred, 1 = open, 0 = fully blocked
+uniform sampler2D depth;     // the scene's, passed on for depth of field
+uniform float strength;
+
+// ambient light is what gets blocked, but the frame is already lit, so the whole color is
+// darkened; strength 0 leaves it as it was
+void main()
+{
+    vec4 color = texture(scene, vUv);
+    float ao = texture(occlusion, vUv).r;
+    FragColor = vec4(color.rgb * mix(1.0, ao, strength), color.a);
+    gl_FragDepth = texture(depth, vUv).r;
+}
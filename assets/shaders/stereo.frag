@@ -0,0 +1,26 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D left; // srgb textures, come out of texture() linear
+uniform sampler2D right;
+uniform bool sideBySide; // otherwise red-cyan anaglyph
+
+void main()
+{
+    if (sideBySide) {
+        // each eye squeezed into its half
+        FragColor = vUv.x < 0.5
+            ? texture(left, vec2(vUv.x * 2.0, vUv.y))
+            : texture(right, vec2(vUv.x * 2.0 - 1.0, vUv.y));
+        return;
+    }
+    // half-color: the left eye gets the brightness through its red filter, so green and
+    // blue things still reach it; a color anaglyph would leave it seeing only red ones
+    vec3 l = texture(left, vUv).rgb;
+    vec3 r = texture(right, vUv).rgb;
+    float brightness = dot(l, vec3(0.2126, 0.7152, 0.0722));
+    FragColor = vec4(brightness, r.g, r.b, 1.0);
+}
@@ -0,0 +1,18 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+in vec4 vColor;
+
+uniform sampler2D glyphs; // single channel atlas, 1 = ink, 0 = empty
+
+void main()
+{
+    float ink = texture(glyphs, vUv).r;
+
+    if (ink <= 0.0)
+        discard;
+
+    FragColor = vec4(vColor.rgb, vColor.a * ink);
+}
@@ -0,0 +1,17 @@
+#version 330 core
+
+layout (location = 0) in vec2 aPos;   // screen position in pixels, origin top-left
+layout (location = 1) in vec2 aUv;    // where the glyph lives in the font atlas
+layout (location = 2) in vec4 aColor; // per-vertex so one batch can mix colors
+
+uniform mat4 projection; // pixels -> clip space
+
+out vec2 vUv;
+out vec4 vColor;
+
+void main()
+{
+    gl_Position = projection * vec4(aPos, 0.0, 1.0);
+    vUv = aUv;
+    vColor = aColor;
+}
@@ -0,0 +1,31 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in vec2 vUv;
+
+uniform sampler2D scene; // the whole hdr frame, glow included
+uniform float exposure;  // multiplied on first, 1 = as lit
+uniform int mode;        // 1 = reinhard, 2 = aces, see tonemap.rs
+
+vec3 reinhard(vec3 c)
+{
+    // on luminance, so bright colors keep their hue instead of going white per channel
+    float luminance = dot(c, vec3(0.2126, 0.7152, 0.0722));
+    return c / (1.0 + luminance);
+}
+
+vec3 aces(vec3 c)
+{
+    // Narkowicz's fit of the ACES filmic curve: a toe, a shoulder, brights roll off to white
+    return clamp((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14), 0.0, 1.0);
+}
+
+// brightness above 1.0 is squeezed under it instead of cut off; still linear afterwards,
+// the target encodes to srgb like before
+void main()
+{
+    vec3 color = texture(scene, vUv).rgb * exposure;
+    color = mode == 2 ? aces(color) : reinhard(color);
+    FragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,19 @@
+#version 330 core
+
+out vec4 FragColor;
+
+in float vAlpha;
+
+uniform vec3 color; // per shell
+
+// srgb in, linear out, see phong.vert
+vec3 srgbToLinear(vec3 c)
+{
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+void main()
+{
+    // old points fade to nothing so the tail does not end in a hard cut
+    FragColor = vec4(srgbToLinear(color), vAlpha * vAlpha);
+}
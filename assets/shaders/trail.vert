@@ -0,0 +1,31 @@
+#version 330 core
+
+layout (location = 0) in vec3 aPos;
+// one vertex = one recorded electron position
+// the buffer is a ring, so age has to be worked out from the vertex index
+
+// camera, see phong.vert
+layout (std140) uniform Frame {
+    mat4 view;
+    mat4 projection;
+    vec4 viewPos;           // camera position, w unused
+    vec4 clipPlane;         // cross-section, see phong.vert
+    vec4 lightPositions[4]; // world space point lights, w unused
+    vec4 lightColor;        // rgb, w unused
+    int lightCount;
+};
+
+uniform int head;     // next slot to be written = one past the newest point
+uniform int count;    // how many slots hold real data
+uniform int capacity; // ring size, slot `capacity` mirrors slot 0
+
+out float vAlpha;
+
+void main()
+{
+    gl_Position = projection * view * vec4(aPos, 1.0);
+
+    int slot = gl_VertexID % capacity;
+    int age = (head - 1 - slot + capacity) % capacity; // 0 = newest
+    vAlpha = 1.0 - float(age) / float(max(count, 1));
+}
@@ -0,0 +1,142 @@
+use crate::console::Command; // photon command
+use crate::photon::Photon; // the same photons the atoms give off, flying the other way
+use crate::scene::MAX_SHELLS; // highest shell an electron can be sent to
+use crate::simulation::{Atom, HC_EV_NM};
+use nalgebra_glm as glm;
+
+// absorption (Shift+Enter): light of a chosen wavelength fired at the selected atom, or the
+// main one, from the left of the screen
+// an atom only takes a photon whose energy is exactly what lifts one of its electrons to a
+// higher shell; then the electron jumps, the photon is gone, and a moment later the
+// electron falls back and gives the same light off again
+// any other photon flies straight through, however close it comes: absorption is quantized
+// energies are the bohr levels with the same screening as the emitted photons, so a photon
+// caught on its way out of an atom is always one the same atom can absorb again
+// "exactly" is within TOLERANCE, real lines have a width too, just a much smaller one
+
+pub const MIN_WAVELENGTH: f32 = 0.01; // nm, inner shells of heavy atoms are x-rays
+pub const MAX_WAVELENGTH: f32 = 20000.0; // high outer shells are far infrared
+pub const WAVELENGTH_STEP: f32 = 1.05; // factor per key press, wider than a line
+const TOLERANCE: f32 = 0.02; // relative energy mismatch still absorbed
+const APPROACH: f32 = 10.0; // how far past the outermost shell a photon starts
+
+#[derive(Clone, Copy)]
+pub struct Transition {
+    pub electron: (usize, usize), // shell index, index in that shell
+    pub to: usize,                // shell index it is lifted to
+    pub energy: f32,              // eV
+}
+
+impl Transition {
+    pub fn wavelength(&self) -> f32 {
+        HC_EV_NM / self.energy
+    }
+}
+
+pub fn transitions(atom: &Atom) -> Vec<Transition> {
+    // from every occupied shell to every shell above it, deepest first
+    // electrons of a shell are alike here, the first one stands for all of them
+    atom.shells
+        .iter()
+        .enumerate()
+        .filter(|(_, shell)| shell.electron_count() > 0)
+        .flat_map(|(from, _)| {
+            (from + 1..MAX_SHELLS).map(move |to| Transition {
+                electron: (from, 0),
+                to,
+                energy: atom.electron_energy(to) - atom.electron_energy(from),
+            })
+        })
+        .collect()
+}
+
+pub fn matching(atom: &Atom, wavelength: f32) -> Option<Transition> {
+    // the transition a photon of this wavelength would drive, None if it drives none
+    let energy = HC_EV_NM / wavelength;
+    let mismatch = |transition: &Transition| (energy / transition.energy - 1.0).abs();
+    transitions(atom)
+        .into_iter()
+        .filter(|transition| mismatch(transition) <= TOLERANCE)
+        .min_by(|a, b| mismatch(a).total_cmp(&mismatch(b)))
+}
+
+pub fn next_line(atom: &Atom, wavelength: f32, longer: bool) -> Option<f32> {
+    // the nearest absorption line past this wavelength, for tuning straight onto one
+    let lines = transitions(atom).into_iter().map(|t| t.wavelength());
+    let range = MIN_WAVELENGTH..=MAX_WAVELENGTH;
+    if longer {
+        lines
+            .filter(|&line| line > wavelength * (1.0 + TOLERANCE) && range.contains(&line))
+            .min_by(f32::total_cmp)
+    } else {
+        lines
+            .filter(|&line| line < wavelength / (1.0 + TOLERANCE) && range.contains(&line))
+            .max_by(f32::total_cmp)
+    }
+}
+
+pub fn wavelength_label(nm: f32) -> String {
+    // four significant digits, from x-rays at hundredths of a nm to the far infrared
+    let decimals = (3.0 - nm.log10().floor()).max(0.0) as usize;
+    format!("{:.*} nm", decimals, nm)
+}
+
+pub fn fire(
+    atom: &Atom,
+    position: glm::Vec3,
+    index: usize,
+    right: &glm::Vec3,
+    wavelength: f32,
+) -> Photon {
+    // index: the atom's place in Scene::atoms
+    // right: the screen's right in the world, the photon flies along it through the nucleus
+    let start = position - right * (atom.outer_radius() + APPROACH);
+    Photon::incoming(start, *right, wavelength, index)
+}
+
+pub fn absorb(photon: &Photon, atom: &Atom, position: glm::Vec3) -> Option<Transition> {
+    // what an incoming photon does to its atom this step: Some once it has reached the
+    // orbit of an electron it can lift, None while it is still on its way or passes by
+    let transition = matching(atom, photon.wavelength)?;
+    let radius = atom.shells[transition.electron.0].radius as f32;
+    (glm::distance(&photon.position, &position) <= radius).then_some(transition)
+}
+
+pub fn commands() -> Vec<Command> {
+    // for the console: tune the lamp and fire in one go
+    vec![Command {
+        name: "photon",
+        usage: "<nm>",
+        help: "fire light of this wavelength at the selected (or main) atom",
+        run: |context, args| {
+            let [word] = args else {
+                return Err("one wavelength".to_string());
+            };
+            let wavelength = word
+                .parse::<f32>()
+                .ok()
+                .filter(|nm| (MIN_WAVELENGTH..=MAX_WAVELENGTH).contains(nm))
+                .ok_or_else(|| {
+                    format!(
+                        "the wavelength has to be between {} and {} nm",
+                        MIN_WAVELENGTH, MAX_WAVELENGTH
+                    )
+                })?;
+            let scene = &mut *context.scene;
+            scene.lamp_wavelength = wavelength;
+            if !scene.fire_photon() {
+                return Err("there is no atom".to_string());
+            }
+            let target = &scene.atoms[scene.target_atom()].atom;
+            Ok(match matching(target, wavelength) {
+                Some(transition) => format!(
+                    "{}, absorbed n={} -> n={}",
+                    wavelength_label(wavelength),
+                    transition.electron.0 + 1,
+                    transition.to + 1
+                ),
+                None => format!("{}, passes through", wavelength_label(wavelength)),
+            })
+        },
+    }]
+}
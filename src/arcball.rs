@@ -0,0 +1,74 @@
+use crate::camera::Camera; // the ball is in screen space
+use nalgebra_glm as glm;
+
+// turning one atom by hand (right mouse button), independent of the camera: an arcball,
+// as if the atom sat in a glass ball as big as the window and the cursor rolled the ball
+// a point under the cursor at the press stays under it while dragging; past the rim of the
+// ball the cursor slides round its edge, which rolls the atom about the line of sight
+// the camera does not move, so with several atoms laid out only the grabbed one turns,
+// and everything it carries turns with it: nucleus, shells, electrons, rings, field lines
+// Shift+C puts the selected atom (or the main one) back the way it was built
+
+pub struct AtomDrag {
+    pub atom: usize,     // in Scene::atoms
+    center: glm::Vec2,   // the atom on screen, window units, y down
+    radius: f32,         // of the ball, window units
+    from: glm::Vec3,     // ball point of the press, camera space
+    start: glm::Quat,    // the atom's orientation at the press
+    to_world: glm::Mat3, // camera space -> world, as the camera was at the press
+}
+
+impl AtomDrag {
+    pub fn new(
+        atom: usize,
+        position: &glm::Vec3,
+        orientation: glm::Quat,
+        camera: &Camera,
+        cursor: glm::Vec2,
+        window: glm::Vec2,
+    ) -> Self {
+        // cursor and window size in window units, like glfw's cursor position
+        let view = camera.get_view_matrix();
+        let clip = camera.get_projection_matrix()
+            * view
+            * glm::vec4(position.x, position.y, position.z, 1.0);
+        let ndc = clip.xy() / clip.w.max(1e-6);
+        let center = glm::vec2(
+            (ndc.x + 1.0) * 0.5 * window.x,
+            (1.0 - ndc.y) * 0.5 * window.y,
+        );
+        let radius = 0.5 * window.x.min(window.y).max(1.0);
+        Self {
+            atom,
+            center,
+            radius,
+            from: ball_point(cursor, center, radius),
+            start: orientation,
+            to_world: glm::mat4_to_mat3(&view).transpose(),
+        }
+    }
+
+    pub fn orientation(&self, cursor: glm::Vec2) -> glm::Quat {
+        // where the drag has turned the atom to, with the cursor here now
+        let to = ball_point(cursor, self.center, self.radius);
+        let axis = glm::cross(&self.from, &to);
+        if glm::length(&axis) < 1e-6 {
+            return self.start;
+        }
+        let angle = glm::dot(&self.from, &to).clamp(-1.0, 1.0).acos();
+        let turn = glm::quat_angle_axis(angle, &glm::normalize(&(self.to_world * axis)));
+        glm::quat_normalize(&(turn * self.start))
+    }
+}
+
+fn ball_point(cursor: glm::Vec2, center: glm::Vec2, radius: f32) -> glm::Vec3 {
+    // the point of the ball under the cursor, camera space (x right, y up, z to the viewer)
+    let flat = glm::vec2(cursor.x - center.x, center.y - cursor.y) / radius;
+    let squared = glm::dot(&flat, &flat);
+    if squared <= 1.0 {
+        glm::vec3(flat.x, flat.y, (1.0 - squared).sqrt())
+    } else {
+        let rim = flat / squared.sqrt();
+        glm::vec3(rim.x, rim.y, 0.0)
+    }
+}
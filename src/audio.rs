@@ -0,0 +1,213 @@
+use crate::config::AudioConfig; // volumes per kind of sound
+use crate::simulation::HC_EV_NM; // photon energy sets its pitch
+
+// short sound effects (build with --features audio, Ctrl+M mutes)
+// every sound is a tone made up on the spot, a sine sliding from one pitch to another under
+// a quick fade, so there are no sound files to ship:
+//   transition  electron lifted to a higher shell, a rising blip
+//   emission    photon given off, a falling ping, higher for more energetic light
+//   decay       nucleus decaying, a low thump
+//   interface   key presses, a short click
+// the simulation queues what happened into Scene::sounds, see Scene::step
+// without the feature, or without an output device, everything here runs but stays silent
+
+const MIN_PITCH: f32 = 200.0; // Hz, infrared photons
+const MAX_PITCH: f32 = 3000.0; // x-rays, anything above would just hurt
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Sound {
+    Transition,
+    Emission { wavelength: f32 }, // nm
+    Decay,
+    Interface,
+}
+
+impl Sound {
+    fn category(self) -> usize {
+        // index into the volumes, and at most one sound of each a frame
+        match self {
+            Sound::Transition => 0,
+            Sound::Emission { .. } => 1,
+            Sound::Decay => 2,
+            Sound::Interface => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(not(feature = "audio"), allow(dead_code))] // only a real speaker listens to it
+pub struct Tone {
+    pub from: f32,    // Hz
+    pub to: f32,      // Hz
+    pub seconds: f32, // until it has faded out
+    pub volume: f32,  // 0..1
+}
+
+fn tone(sound: Sound, volume: f32) -> Tone {
+    let (from, to, seconds) = match sound {
+        Sound::Transition => (520.0, 780.0, 0.12),
+        Sound::Emission { wavelength } => {
+            // sqrt of the photon energy, so visible light and x-rays are both in earshot
+            let energy = HC_EV_NM / wavelength;
+            let pitch = (300.0 * energy.sqrt()).clamp(MIN_PITCH, MAX_PITCH);
+            (pitch, pitch * 0.8, 0.15)
+        }
+        Sound::Decay => (90.0, 55.0, 0.4),
+        Sound::Interface => (1200.0, 1200.0, 0.03),
+    };
+    Tone {
+        from,
+        to,
+        seconds,
+        volume,
+    }
+}
+
+pub struct Audio {
+    muted: bool,
+    volumes: [f32; 4], // by Sound::category
+    speaker: Option<speaker::Speaker>,
+}
+
+impl Audio {
+    pub fn new(config: &AudioConfig) -> Self {
+        Self {
+            muted: config.muted,
+            volumes: [
+                config.transitions,
+                config.emission,
+                config.decay,
+                config.interface,
+            ],
+            speaker: speaker::Speaker::open(),
+        }
+    }
+
+    pub fn toggle_mute(&mut self) -> bool {
+        // true = now muted
+        self.muted = !self.muted;
+        self.muted
+    }
+
+    pub fn play(&self, sounds: &[Sound]) {
+        // everything that happened this frame, a burst of ten photons is one ping
+        let Some(speaker) = &self.speaker else {
+            return;
+        };
+        if self.muted {
+            return;
+        }
+        let mut played = [false; 4];
+        for &sound in sounds {
+            let category = sound.category();
+            let volume = self.volumes[category];
+            if played[category] || volume <= 0.0 {
+                continue;
+            }
+            played[category] = true;
+            speaker.play(tone(sound, volume));
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+mod speaker {
+    use super::Tone;
+    use rodio::{OutputStream, OutputStreamHandle, Source};
+    use std::f32::consts::PI;
+    use std::time::Duration;
+
+    const SAMPLE_RATE: u32 = 44100;
+    const ATTACK: f32 = 0.005; // seconds of fade in, no click at the start
+
+    pub struct Speaker {
+        _stream: OutputStream, // the sound stops when this goes
+        handle: OutputStreamHandle,
+    }
+
+    impl Speaker {
+        pub fn open() -> Option<Self> {
+            match OutputStream::try_default() {
+                Ok((stream, handle)) => Some(Self {
+                    _stream: stream,
+                    handle,
+                }),
+                Err(err) => {
+                    log::warn!("no sound: {}", err);
+                    None
+                }
+            }
+        }
+
+        pub fn play(&self, tone: Tone) {
+            let chirp = Chirp {
+                tone,
+                sample: 0,
+                phase: 0.0,
+            };
+            if let Err(err) = self.handle.play_raw(chirp) {
+                log::warn!("could not play a sound: {}", err);
+            }
+        }
+    }
+
+    struct Chirp {
+        // one tone as mono samples
+        tone: Tone,
+        sample: u32,
+        phase: f32, // radians, summed up so the slide does not jump
+    }
+
+    impl Iterator for Chirp {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            let t = self.sample as f32 / SAMPLE_RATE as f32;
+            let tone = &self.tone;
+            if t >= tone.seconds {
+                return None;
+            }
+            self.sample += 1;
+            let along = t / tone.seconds;
+            let frequency = tone.from + (tone.to - tone.from) * along;
+            self.phase = (self.phase + 2.0 * PI * frequency / SAMPLE_RATE as f32) % (2.0 * PI);
+            // quick fade in, then down to nothing by the end
+            let envelope = (t / ATTACK).min(1.0) * (1.0 - along) * (1.0 - along);
+            Some(self.phase.sin() * envelope * tone.volume)
+        }
+    }
+
+    impl Source for Chirp {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            SAMPLE_RATE
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            Some(Duration::from_secs_f32(self.tone.seconds))
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod speaker {
+    use super::Tone;
+
+    // built without sound, there is never a speaker to play on
+    pub struct Speaker;
+
+    impl Speaker {
+        pub fn open() -> Option<Self> {
+            None
+        }
+
+        pub fn play(&self, _tone: Tone) {}
+    }
+}
@@ -0,0 +1,302 @@
+use crate::elements::{AUFBAU_ORDER, SUBSHELL_LETTERS}; // filling order, see electron_configuration
+use crate::levels; // spin arrows drawn the way the level diagram draws them
+use crate::simulation::{ring_direction, Atom, Shell};
+use crate::text::TextRenderer; // the panel is drawn with the hud's rectangles
+use nalgebra_glm as glm;
+use std::f64::consts::PI;
+
+// builder mode (Z): the main atom starts over as a bare nucleus and gets its electrons back
+// one at a time, Right sends the next one in and Left takes the last one away again
+// each electron flies in from outside and lands on its shell, and a panel in the corner
+// fills in the orbital boxes as it goes, so electron configuration can be taught step by step
+// the filling order is madelung's rule: subshells by n + l, ties to the lower n, which is
+// why 4s fills before 3d; inside a subshell every box gets an up arrow before any gets a
+// down arrow (hund), the same as hund_filling hands out spins
+// real atoms break the rule here and there (chromium is 3d5 4s1, copper 3d10 4s1), the
+// builder shows the rule; leaving builder mode gives the element table's ground state back
+
+const FLIGHT_TIME: f64 = 1.0; // seconds of simulated time from outside to the shell
+const APPROACH: f64 = 15.0; // how far outside its shell an electron starts
+
+// panel layout in pixels, bottom left corner like the radial plot (which only the cloud
+// views have, the builder is a bohr view thing)
+const PANEL_WIDTH: f32 = 340.0;
+const MARGIN: f32 = 10.0;
+const SPECTRUM_SPACE: f32 = 76.0; // kept clear for the spectrum bar along the bottom
+const HEADER_HEIGHT: f32 = 40.0;
+const LINE_HEIGHT: f32 = 10.0; // configuration text, scale 1
+const ROW_HEIGHT: f32 = 22.0; // one shell of boxes
+const LABEL_WIDTH: f32 = 16.0; // "3d" in front of its boxes
+
+const BOX_WIDTH: f32 = 14.0;
+const BOX_HEIGHT: f32 = 14.0;
+const SUBSHELL_GAP: f32 = 6.0;
+
+const PLACED_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 1.0, 1.0, 1.0);
+const NEWEST_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 0.9, 0.3, 1.0); // also the subshell filling now
+
+#[derive(Clone, Copy)]
+pub struct Placement {
+    // where one electron of the filling order goes
+    pub n: u32,
+    pub l: usize,
+    pub orbital: usize, // box within the subshell
+    pub spin_up: bool,
+}
+
+pub fn placement(index: usize) -> Option<Placement> {
+    // electron number index (from 0) of the filling order, None past the last subshell
+    let mut before = 0;
+    for (n, l) in AUFBAU_ORDER {
+        let (l, orbitals) = (l as usize, 2 * l as usize + 1);
+        if index < before + 2 * orbitals {
+            let i = index - before;
+            return Some(Placement {
+                n,
+                l,
+                orbital: i % orbitals,
+                spin_up: i < orbitals,
+            });
+        }
+        before += 2 * orbitals;
+    }
+    None
+}
+
+pub fn subshell_name(n: u32, l: usize) -> String {
+    format!("{}{}", n, SUBSHELL_LETTERS[l])
+}
+
+pub fn configuration(count: usize) -> String {
+    // "1s2 2s2 2p6 3s1" for the first count electrons, in filling order, without the noble
+    // gas core electron_configuration shortens it to, the point is to watch it grow
+    let mut parts = Vec::new();
+    let mut remaining = count;
+    for (n, l) in AUFBAU_ORDER {
+        if remaining == 0 {
+            break;
+        }
+        let here = remaining.min(2 * (2 * l as usize + 1));
+        remaining -= here;
+        parts.push(format!("{}{}", subshell_name(n, l as usize), here));
+    }
+    parts.join(" ")
+}
+
+struct Incoming {
+    // an electron on its way in, atom space
+    n: u32,
+    phase: f64, // along the ring it is heading for, keeps up with the shell's turning
+    progress: f64,
+    position: glm::Vec3,
+    previous: glm::Vec3, // one simulation step ago
+}
+
+pub struct Builder {
+    incoming: Option<Incoming>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self { incoming: None }
+    }
+
+    pub fn next(&mut self, atom: &mut Atom) -> bool {
+        // send in the next electron; one still on its way lands right away, so holding the
+        // key does not queue anything up
+        // false once the atom is neutral
+        self.land(atom);
+        let index = atom.electron_count();
+        if index >= atom.protons as usize {
+            return false;
+        }
+        let Some(place) = placement(index) else {
+            return false;
+        };
+        let phase = rand::random::<f64>() * 2.0 * PI;
+        let position = flight_position(place.n, phase, 0.0);
+        self.incoming = Some(Incoming {
+            n: place.n,
+            phase,
+            progress: 0.0,
+            position,
+            previous: position,
+        });
+        true
+    }
+
+    pub fn previous(&mut self, atom: &mut Atom) -> bool {
+        // call back the one on its way, or take the last one off its shell again
+        if self.incoming.take().is_some() {
+            return true;
+        }
+        let Some(index) = atom.electron_count().checked_sub(1) else {
+            return false;
+        };
+        placement(index).is_some_and(|place| atom.remove_electron(place.n))
+    }
+
+    pub fn step(&mut self, atom: &mut Atom, dt: f64) -> Option<(u32, glm::Vec3)> {
+        // shell and atom space position of an electron that landed this step
+        let incoming = self.incoming.as_mut()?;
+        incoming.progress += dt / FLIGHT_TIME;
+        incoming.phase += Shell::new(incoming.n, 0).angular_velocity * dt;
+        incoming.previous = incoming.position;
+        incoming.position = flight_position(incoming.n, incoming.phase, incoming.progress);
+        if incoming.progress >= 1.0 {
+            return self.land(atom);
+        }
+        None
+    }
+
+    fn land(&mut self, atom: &mut Atom) -> Option<(u32, glm::Vec3)> {
+        let incoming = self.incoming.take()?;
+        atom.land_electron(incoming.n, incoming.phase);
+        Some((incoming.n, flight_position(incoming.n, incoming.phase, 1.0)))
+    }
+
+    pub fn incoming(&self, alpha: f32) -> Option<(u32, glm::Vec3)> {
+        // shell it is heading for and atom space position, blended like everything else
+        self.incoming.as_ref().map(|incoming| {
+            (
+                incoming.n,
+                glm::lerp(&incoming.previous, &incoming.position, alpha),
+            )
+        })
+    }
+}
+
+fn flight_position(n: u32, phase: f64, progress: f64) -> glm::Vec3 {
+    // spirals in from above the ring's plane and settles onto the ring
+    let shell = Shell::new(n, 0);
+    let t = progress.clamp(0.0, 1.0);
+    let t = t * t * (3.0 - 2.0 * t); // same easing as the jumps between shells
+    let away = APPROACH * (1.0 - t);
+    let local = ring_direction(phase) * (shell.radius + away) + glm::vec3(0.0, 0.5 * away, 0.0);
+    let local: glm::Vec3 = local.cast();
+    (shell.plane_rotation() * glm::vec4(local.x, local.y, local.z, 1.0)).xyz()
+}
+
+pub fn draw(text: &mut TextRenderer, atom: &Atom, builder: &Builder, height: u32) {
+    // queued into the text batch, the caller flushes
+    let grey = glm::vec4(0.8, 0.8, 0.8, 1.0);
+    let placed = atom.electron_count();
+    let incoming = builder.incoming.as_ref();
+    let filling = placement(placed); // where the next one goes, or the one on its way
+
+    // the configuration wrapped to the panel
+    let mut lines: Vec<String> = Vec::new();
+    for part in configuration(placed).split(' ') {
+        match lines.last_mut() {
+            Some(line)
+                if TextRenderer::text_width(&format!("{} {}", line, part), 1.0)
+                    <= PANEL_WIDTH - 12.0 =>
+            {
+                line.push(' ');
+                line.push_str(part);
+            }
+            _ => lines.push(part.to_string()),
+        }
+    }
+    // every shell with an electron, and the one the next electron goes to
+    let rows = (0..placed)
+        .chain(filling.map(|_| placed))
+        .filter_map(placement)
+        .map(|place| place.n)
+        .max()
+        .unwrap_or(1);
+
+    let panel_height =
+        HEADER_HEIGHT + lines.len() as f32 * LINE_HEIGHT + 6.0 + rows as f32 * ROW_HEIGHT;
+    let x0 = MARGIN;
+    let y0 = height as f32 - SPECTRUM_SPACE - panel_height;
+    text.draw_rect(
+        x0,
+        y0,
+        PANEL_WIDTH,
+        panel_height,
+        &glm::vec4(0.0, 0.0, 0.0, 0.85),
+    );
+    let caption = format!("building {}", atom.element().symbol);
+    text.draw_text(&caption, x0 + 6.0, y0 + 6.0, 2.0, &grey);
+    let status = match filling {
+        _ if placed >= atom.protons as usize => {
+            format!("{} of {} electrons, neutral", placed, atom.protons)
+        }
+        Some(place) => format!(
+            "{} of {} electrons, next in {}",
+            placed,
+            atom.protons,
+            subshell_name(place.n, place.l)
+        ),
+        None => format!("{} of {} electrons", placed, atom.protons),
+    };
+    text.draw_text(&status, x0 + 6.0, y0 + 24.0, 1.0, &grey);
+    for (i, line) in lines.iter().enumerate() {
+        let y = y0 + HEADER_HEIGHT + i as f32 * LINE_HEIGHT;
+        text.draw_text(line, x0 + 6.0, y, 1.0, &PLACED_COLOR);
+    }
+
+    // one row of boxes per shell, s to f from the left
+    let rows_top = y0 + HEADER_HEIGHT + lines.len() as f32 * LINE_HEIGHT + 6.0;
+    let row_y = |n: u32| rows_top + (n - 1) as f32 * ROW_HEIGHT + ROW_HEIGHT / 2.0;
+    let subshell_x = |l: usize| {
+        x0 + 6.0
+            + (0..l)
+                .map(|below| {
+                    LABEL_WIDTH + (2 * below + 1) as f32 * (BOX_WIDTH + 1.0) + SUBSHELL_GAP
+                })
+                .sum::<f32>()
+    };
+    let box_x =
+        |l: usize, orbital: usize| subshell_x(l) + LABEL_WIDTH + orbital as f32 * (BOX_WIDTH + 1.0);
+    for n in 1..=rows {
+        let y = row_y(n);
+        for l in 0..(n as usize).min(SUBSHELL_LETTERS.len()) {
+            let current = filling.is_some_and(|place| (place.n, place.l) == (n, l));
+            let label_color = if current { &NEWEST_COLOR } else { &grey };
+            text.draw_text(
+                &subshell_name(n, l),
+                subshell_x(l),
+                y - 3.0,
+                1.0,
+                label_color,
+            );
+            for orbital in 0..2 * l + 1 {
+                let x = box_x(l, orbital);
+                text.draw_rect(x, y - BOX_HEIGHT / 2.0, BOX_WIDTH, BOX_HEIGHT, label_color);
+                text.draw_rect(
+                    x + 1.0,
+                    y - BOX_HEIGHT / 2.0 + 1.0,
+                    BOX_WIDTH - 2.0,
+                    BOX_HEIGHT - 2.0,
+                    &glm::vec4(0.1, 0.1, 0.1, 1.0),
+                );
+            }
+        }
+    }
+    let mut spin_arrow = |place: Placement, color: &glm::Vec4| {
+        let (x, y) = (box_x(place.l, place.orbital), row_y(place.n));
+        let (x, tail, tip) = if place.spin_up {
+            (x + 4.5, y + 5.0, y - 5.0)
+        } else {
+            (x + 9.5, y - 5.0, y + 5.0)
+        };
+        levels::arrow(text, x, tail, tip, color);
+    };
+    for index in 0..placed {
+        if let Some(place) = placement(index) {
+            let color = if index + 1 == placed {
+                &NEWEST_COLOR
+            } else {
+                &PLACED_COLOR
+            };
+            spin_arrow(place, color);
+        }
+    }
+    // the one on its way fades into its box
+    if let (Some(incoming), Some(place)) = (incoming, filling) {
+        let fade = incoming.progress.clamp(0.0, 1.0) as f32;
+        spin_arrow(place, &glm::vec4(1.0, 0.9, 0.3, fade));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::camera::Camera;
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::scene::{BackgroundMode, DisplaySettings};
+use crate::stats;
+use nalgebra_glm as glm;
+use std::path::Path;
+
+// what is drawn behind the scene when the background is not a flat clear color
+// one fullscreen triangle at the far plane, either a vertical gradient or a cubemap
+// looked up with the camera's viewing direction (rotation only, the sky never gets closer)
+
+// cubemap faces in gl order: +x, -x, +y, -y, +z, -z
+const FACES: [&str; 6] = ["px", "nx", "py", "ny", "pz", "nz"];
+const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+pub struct Background {
+    program: WatchedProgram,
+    empty_vao: gl::types::GLuint, // core profile refuses to draw without a vao, even an empty one
+    cubemap: Option<gl::types::GLuint>, // None = no skybox loaded, Skybox mode draws the gradient
+}
+
+impl Background {
+    pub unsafe fn new(skybox: Option<&str>) -> Result<Self, ShaderError> {
+        // a skybox that does not load is not fatal, there is still the gradient
+        let program = WatchedProgram::new("background", ShaderProgram::background)?;
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+
+        let cubemap = skybox.and_then(|dir| match load_cubemap(Path::new(dir)) {
+            Ok(texture) => {
+                log::info!("loaded skybox from {}", dir);
+                Some(texture)
+            }
+            Err(message) => {
+                log::warn!("{}\nskybox background falls back to the gradient", message);
+                None
+            }
+        });
+
+        Ok(Self {
+            program,
+            empty_vao,
+            cubemap,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn draw(&self, display: &DisplaySettings, camera: &Camera) {
+        // call right after clearing, everything else is drawn over it
+        let skybox = match display.background_mode {
+            BackgroundMode::Solid => return,
+            BackgroundMode::Gradient => None,
+            BackgroundMode::Skybox => self.cubemap,
+        };
+
+        self.program.use_program();
+        self.program
+            .set_uniform_vec3("topColor", &display.theme.gradient_top);
+        self.program
+            .set_uniform_vec3("bottomColor", &display.theme.gradient_bottom);
+        self.program
+            .set_uniform_i32("skybox", skybox.is_some() as i32);
+        if let Some(texture) = skybox {
+            // always a perspective lookup, an orthographic sky would be one flat color
+            let mut view = camera.get_view_matrix();
+            view.set_column(3, &glm::vec4(0.0, 0.0, 0.0, 1.0));
+            let fov = glm::radians(&glm::vec1(camera.projection.fov))[0];
+            let projection = glm::perspective(camera.projection.aspect, fov, 0.1, 10.0);
+            self.program
+                .set_uniform_mat4("inverseViewProjection", &glm::inverse(&(projection * view)));
+            self.program.set_uniform_i32("cubemap", 0);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture);
+        }
+
+        // behind everything, and nothing should be hidden behind it
+        gl::Disable(gl::DEPTH_TEST);
+        gl::DepthMask(gl::FALSE);
+        gl::BindVertexArray(self.empty_vao);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::BindVertexArray(0);
+        gl::DepthMask(gl::TRUE);
+        gl::Enable(gl::DEPTH_TEST);
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+    }
+}
+
+unsafe fn load_cubemap(dir: &Path) -> Result<gl::types::GLuint, String> {
+    // six square images named after their face, px.png, nx.png, ... in `dir`
+    // everything is decoded before any gl call, so a missing face leaves nothing behind
+    let mut faces = Vec::new();
+    for face in FACES {
+        let path = EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", face, ext)))
+            .find(|path| path.exists())
+            .ok_or_else(|| format!("skybox face '{}' not found in {}", face, dir.display()))?;
+        let image = image::open(&path)
+            .map_err(|err| format!("could not load skybox face {}: {}", path.display(), err))?
+            .to_rgba8();
+        faces.push(image);
+    }
+    let size = faces[0].dimensions();
+    if faces.iter().any(|face| face.dimensions() != size) || size.0 != size.1 {
+        return Err(format!(
+            "skybox faces in {} must all be square and the same size",
+            dir.display()
+        ));
+    }
+
+    let mut texture = 0;
+    gl::GenTextures(1, &mut texture);
+    gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture);
+    for (i, face) in faces.iter().enumerate() {
+        gl::TexImage2D(
+            gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
+            0,
+            gl::SRGB8_ALPHA8 as i32, // images are srgb, sampling hands back linear
+            size.0 as i32,
+            size.1 as i32,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            face.as_raw().as_ptr() as *const _,
+        );
+    }
+    // linear filtering, edges clamped or the seams between faces show
+    for (parameter, value) in [
+        (gl::TEXTURE_MIN_FILTER, gl::LINEAR),
+        (gl::TEXTURE_MAG_FILTER, gl::LINEAR),
+        (gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE),
+        (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE),
+        (gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE),
+    ] {
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, parameter, value as i32);
+    }
+    gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+    check_gl_error!("skybox upload");
+    Ok(texture)
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(texture) = self.cubemap {
+                gl::DeleteTextures(1, &texture);
+            }
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
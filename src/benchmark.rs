@@ -0,0 +1,152 @@
+use crate::camera::Camera;
+use crate::elements; // the stress scene's element
+use crate::scene::{Scene, ViewMode};
+use crate::simulation::Atom; // spacing between the atoms
+use crate::stats::{self, DrawStats};
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+use std::time::Instant;
+
+// --benchmark: a fixed heavy scene and a fixed camera path, vsync off, every frame timed
+// run it before and after a renderer change and compare the reports
+// the path flies around the whole block, then dives into it and out again, so culling and
+// the level of detail get exercised as well as raw throughput
+// decays still happen on their random clock, so two runs are close but never identical
+
+const ELEMENT: &str = "U";
+const GRID: [usize; 3] = [5, 2, 5]; // 50 atoms, x / y / z
+const WARMUP_FRAMES: usize = 30; // shader compiles and first uploads, not measured
+
+pub fn stress_scene(scene: &mut Scene) {
+    // a block of uranium atoms, shells just clear of each other
+    let element = elements::by_symbol(ELEMENT).unwrap();
+    let spacing = 2.0
+        * Atom::from_atomic_number(element.atomic_number)
+            .unwrap()
+            .outer_radius()
+        + 2.0;
+    for x in 0..GRID[0] {
+        for y in 0..GRID[1] {
+            for z in 0..GRID[2] {
+                let position = glm::vec3(x as f32, y as f32, z as f32) * spacing;
+                scene.add_atom(element, position);
+            }
+        }
+    }
+    scene.view_mode = ViewMode::Bohr;
+}
+
+pub struct Benchmark {
+    duration: f32,         // seconds of camera path, after the warmup
+    center: glm::Vec3,     // what the path circles
+    extent: f32,           // camera distance that fits the whole block
+    warmup: usize,         // frames left before measuring starts
+    start: Instant,        // of the measured part
+    last: Instant,         // previous frame_done
+    frame_times: Vec<f32>, // seconds
+    draws: Vec<DrawStats>, // per measured frame
+}
+
+impl Benchmark {
+    pub fn new(duration: f32, scene: &Scene) -> Self {
+        let center = scene.center();
+        let extent = scene
+            .atoms
+            .iter()
+            .map(|scene_atom| {
+                glm::distance(&scene_atom.position, &center) + scene_atom.atom.outer_radius()
+            })
+            .fold(0.0, f32::max);
+        let now = Instant::now();
+        Self {
+            duration,
+            center,
+            extent: extent * 2.0,
+            warmup: WARMUP_FRAMES,
+            start: now,
+            last: now,
+            frame_times: Vec::new(),
+            draws: Vec::new(),
+        }
+    }
+
+    // put the camera where the path is right now; the warmup holds the starting point
+    pub fn place_camera(&self, camera: &mut Camera) {
+        let t = if self.warmup > 0 {
+            0.0
+        } else {
+            (self.start.elapsed().as_secs_f32() / self.duration).min(1.0)
+        };
+        let turn = 2.0 * PI * t;
+        // from the fitting distance into the middle of the block and back out
+        let radius = self.extent * (0.55 + 0.45 * turn.cos());
+        let elevation = PI / 2.0 - 0.5 * turn.sin();
+        camera.set_pose(self.center, radius, turn, elevation);
+    }
+
+    // after the swap; false once the path is done
+    pub fn frame_done(&mut self) -> bool {
+        let now = Instant::now();
+        let draws = stats::take();
+        if self.warmup > 0 {
+            self.warmup -= 1;
+            if self.warmup == 0 {
+                self.start = now;
+            }
+        } else {
+            self.frame_times
+                .push(now.duration_since(self.last).as_secs_f32());
+            self.draws.push(draws);
+        }
+        self.last = now;
+        self.warmup > 0 || self.start.elapsed().as_secs_f32() < self.duration
+    }
+
+    pub fn print_report(&self, scene: &Scene, width: i32, height: i32) {
+        if self.frame_times.is_empty() {
+            println!("Benchmark stopped before any frame was measured.");
+            return;
+        }
+        let mut sorted = self.frame_times.clone();
+        sorted.sort_by(f32::total_cmp);
+        let frames = sorted.len();
+        let total: f32 = sorted.iter().sum();
+        let average = total / frames as f32;
+        // nearest rank: the frame time 99% of frames stayed under
+        let p99 = sorted[((frames as f32 * 0.99).ceil() as usize).clamp(1, frames) - 1];
+        let ms = |seconds: f32| seconds * 1000.0;
+
+        let calls = self.draws.iter().map(|draws| draws.draw_calls);
+        let triangles = self.draws.iter().map(|draws| draws.triangles);
+        let mean = |total: u64| total as f64 / frames as f64;
+
+        println!();
+        println!(
+            "Benchmark: {} atoms ({}), {:.1} s at {}x{}, {} frames",
+            scene.atoms.len(),
+            ELEMENT,
+            total,
+            width,
+            height,
+            frames
+        );
+        println!(
+            "  frame time  min {:.2} ms  avg {:.2} ms  99th {:.2} ms  max {:.2} ms  ({:.0} fps)",
+            ms(sorted[0]),
+            ms(average),
+            ms(p99),
+            ms(sorted[frames - 1]),
+            1.0 / average
+        );
+        println!(
+            "  draw calls  avg {:.0}  max {} per frame",
+            mean(calls.clone().sum()),
+            calls.max().unwrap_or(0)
+        );
+        println!(
+            "  triangles   avg {:.0}  max {} per frame",
+            mean(triangles.clone().sum()),
+            triangles.max().unwrap_or(0)
+        );
+    }
+}
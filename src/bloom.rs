@@ -0,0 +1,117 @@
+use crate::framebuffer::{self, ColorFormat, Framebuffer};
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::stats;
+use nalgebra_glm as glm;
+
+// glow around bright things (electrons, photons, sparks)
+// the scene is drawn into a half float target so brightness above 1.0 is not lost, then:
+//   1. extract: keep what is brighter than the threshold, at half resolution
+//   2. blur: separable gaussian, horizontal then vertical, a few times over
+//   3. composite: frame + blur * intensity into whatever was bound before begin()
+// same begin / finish shape as Fxaa, the two nest (bloom inside, fxaa outside)
+
+const BLUR_PASSES: usize = 3; // each pass = one horizontal + one vertical blur, wider glow
+
+pub struct Bloom {
+    extract: WatchedProgram,
+    blur: WatchedProgram,
+    composite: WatchedProgram,
+    empty_vao: gl::types::GLuint, // fullscreen triangle, generated in the vertex shader
+    scene: Framebuffer,           // full resolution hdr frame
+    ping: Framebuffer,            // half resolution, blur goes back and forth between these
+    pong: Framebuffer,
+    previous: gl::types::GLuint, // framebuffer to composite into, remembered in begin()
+}
+
+impl Bloom {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        Ok(Self {
+            extract: WatchedProgram::new("bloom_extract", ShaderProgram::bloom_extract)?,
+            blur: WatchedProgram::new("bloom_blur", ShaderProgram::bloom_blur)?,
+            composite: WatchedProgram::new("bloom_composite", ShaderProgram::bloom_composite)?,
+            empty_vao,
+            scene: Framebuffer::new(ColorFormat::Rgba16F, true),
+            ping: Framebuffer::new(ColorFormat::Rgba16F, false),
+            pong: Framebuffer::new(ColorFormat::Rgba16F, false),
+            previous: 0,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.extract.poll();
+        self.blur.poll();
+        self.composite.poll();
+    }
+
+    pub unsafe fn begin(&mut self, width: u32, height: u32) {
+        // redirect drawing into the hdr target
+        self.previous = framebuffer::current_binding();
+        self.scene.resize(width, height);
+        self.ping.resize(width / 2, height / 2);
+        self.pong.resize(width / 2, height / 2);
+        self.scene.bind();
+    }
+
+    pub unsafe fn finish(&mut self, threshold: f32, intensity: f32) {
+        gl::Disable(gl::DEPTH_TEST);
+        gl::BindVertexArray(self.empty_vao);
+
+        self.ping.bind();
+        self.extract.use_program();
+        self.extract.set_uniform_i32("scene", 0);
+        self.extract.set_uniform_f32("threshold", threshold);
+        self.scene.bind_texture(0);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+        let (width, height) = self.ping.size();
+        let texel = glm::vec2(1.0 / width as f32, 1.0 / height as f32);
+        self.blur.use_program();
+        self.blur.set_uniform_i32("image", 0);
+        for _ in 0..BLUR_PASSES {
+            self.pong.bind();
+            self.blur
+                .set_uniform_vec2("direction", &glm::vec2(texel.x, 0.0));
+            self.ping.bind_texture(0);
+            stats::count(gl::TRIANGLES, 3, 1);
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+            self.ping.bind();
+            self.blur
+                .set_uniform_vec2("direction", &glm::vec2(0.0, texel.y));
+            self.pong.bind_texture(0);
+            stats::count(gl::TRIANGLES, 3, 1);
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        }
+
+        // back to the original target at full size
+        let (width, height) = self.scene.size();
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous);
+        gl::Viewport(0, 0, width as i32, height as i32);
+        self.composite.use_program();
+        self.composite.set_uniform_i32("scene", 0);
+        self.composite.set_uniform_i32("bloom", 1);
+        self.composite.set_uniform_f32("intensity", intensity);
+        self.scene.bind_texture(0);
+        self.ping.bind_texture(1);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+        gl::BindVertexArray(0);
+        gl::Enable(gl::DEPTH_TEST);
+        check_gl_error!("bloom");
+    }
+}
+
+impl Drop for Bloom {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
@@ -1,6 +1,92 @@
+use crate::trackball::TrackballCamera; // camera.mode = "trackball"
 use nalgebra_glm as glm; // linear algebra backbone, without this we are just staring at numbers
 use std::f32::consts::PI; // pi because spherical coordinates refuse to die
 
+// slots for Ctrl+1..9 / 1..9
+pub const BOOKMARKS: usize = 9;
+
+// everything that says where the camera is and which way it looks
+// the angles are always filled in; orientation only when it came from a trackball, so a
+// pose taken in one mode still means something in the other
+#[derive(Clone, Copy)]
+pub struct CameraPose {
+    pub target: glm::Vec3,
+    pub radius: f32,
+    pub azimuth: f32,
+    pub elevation: f32,
+    pub orientation: Option<glm::Quat>,
+}
+
+// a scripted camera move, see Camera::fly_to
+struct Flight {
+    from_target: glm::Vec3,
+    from_radius: f32,
+    to_target: glm::Vec3,
+    to_radius: f32,
+    turn: Option<Turn>, // fly_to_pose turns as well, fly_to keeps the viewing angle
+    elapsed: f32,
+    duration: f32,
+}
+
+struct Turn {
+    from_azimuth: f32,
+    from_elevation: f32,
+    to_azimuth: f32, // unwrapped, so lerping goes the short way round
+    to_elevation: f32,
+    from_orientation: glm::Quat, // trackball mode
+    to_orientation: glm::Quat,
+}
+
+// how the camera turns view space into clip space
+// perspective for the normal view, orthographic for flat diagram-style renders
+pub struct Projection {
+    pub fov: f32,  // vertical field of view in degrees (perspective only)
+    pub near: f32, // clip planes, distance from the camera
+    pub far: f32,
+    pub aspect: f32, // width / height of the framebuffer
+    pub orthographic: bool,
+}
+
+impl Projection {
+    pub fn new() -> Self {
+        Self {
+            fov: 45.0,
+            near: 0.1,
+            far: 100.0,
+            aspect: 16.0 / 9.0,
+            orthographic: false,
+        }
+    }
+
+    // framebuffer changed size, keep circles round
+    pub fn resize(&mut self, width: i32, height: i32) {
+        self.aspect = width.max(1) as f32 / height.max(1) as f32;
+        log::debug!("projection resized to {}x{}", width, height);
+    }
+
+    // distance is how far away the orbit target is
+    // ortho has no perspective to shrink things, so its view volume is sized to show
+    // exactly what the perspective view would show at that distance
+    // that way toggling keeps the atom framed and scrolling still zooms
+    pub fn matrix(&self, distance: f32) -> glm::Mat4 {
+        let fov = glm::radians(&glm::vec1(self.fov))[0];
+        if self.orthographic {
+            let half_height = distance * (fov * 0.5).tan();
+            let half_width = half_height * self.aspect;
+            glm::ortho(
+                -half_width,
+                half_width,
+                -half_height,
+                half_height,
+                self.near,
+                self.far,
+            )
+        } else {
+            glm::perspective(self.aspect, fov, self.near, self.far)
+        }
+    }
+}
+
 // this camera is an orbit camera
 // meaning: it does NOT move freely in xyz space
 // it rotates around a target point at a certain radius
@@ -12,9 +98,34 @@ pub struct Camera {
     pub elevation: f32,    // vertical angle (tilt up/down)
     pub orbit_speed: f32,  // how sensitive mouse movement is
     pub zoom_speed: f32,   // how aggressive scroll zoom is
+    pub pan_speed: f32,    // how far target slides per pixel, scaled by radius
     pub dragging: bool,    // are we currently holding mouse button?
+    pub panning: bool,     // pan button held? (middle or shift + left by default)
     pub last_x: f64,       // previous mouse x (to compute delta)
     pub last_y: f64,       // previous mouse y
+
+    // input does not move the camera directly anymore
+    // it moves these goals, and update(dt) eases the real values toward them
+    // that is what makes it feel smooth instead of twitchy
+    pub goal_target: glm::Vec3,
+    pub goal_radius: f32,
+    pub goal_azimuth: f32,
+    pub goal_elevation: f32,
+    pub smoothing: f32, // 1/s, higher = snappier, lower = floatier
+
+    pub projection: Projection,
+
+    // set in trackball mode: turning goes through its quaternion and azimuth / elevation
+    // are left alone, target, radius, panning and flights work the same either way
+    pub trackball: Option<TrackballCamera>,
+
+    pub bookmarks: [Option<CameraPose>; BOOKMARKS],
+
+    // stereo: how far the eye sits to the right of the camera, in units of radius
+    // (negative = left eye), only ever set while the renderer draws one eye, see stereo.rs
+    pub eye: f32,
+
+    flight: Option<Flight>, // active fly_to animation, if any
 }
 
 impl Camera {
@@ -30,16 +141,210 @@ impl Camera {
 
             orbit_speed: 0.01, // small multiplier so mouse doesn’t fling camera into orbit
             zoom_speed: 1.0,   // scroll multiplier
+            pan_speed: 0.001,  // per pixel per unit of radius, so far away pans faster
 
             dragging: false, // not dragging at start
+            panning: false,
             last_x: 0.0,
             last_y: 0.0,
+
+            // goals start where we are, so nothing moves until input arrives
+            goal_target: target,
+            goal_radius: radius,
+            goal_azimuth: 0.0,
+            goal_elevation: PI / 2.0,
+            smoothing: 12.0,
+
+            projection: Projection::new(),
+
+            trackball: None,
+
+            bookmarks: [None; BOOKMARKS],
+
+            eye: 0.0,
+
+            flight: None,
+        }
+    }
+
+    // advance smoothing and any running flight by dt seconds
+    // call once per frame before get_view_matrix
+    pub fn update(&mut self, dt: f32) {
+        if let Some(flight) = &mut self.flight {
+            flight.elapsed += dt;
+            let t = (flight.elapsed / flight.duration).min(1.0);
+
+            // smoothstep: slow start, slow stop, looks cinematic for free
+            let t = t * t * (3.0 - 2.0 * t);
+
+            self.goal_target = glm::lerp(&flight.from_target, &flight.to_target, t);
+            self.goal_radius = flight.from_radius + (flight.to_radius - flight.from_radius) * t;
+
+            // during a flight the path itself is the easing, so follow it exactly
+            self.target = self.goal_target;
+            self.radius = self.goal_radius;
+
+            if let Some(turn) = &flight.turn {
+                self.goal_azimuth = turn.from_azimuth + (turn.to_azimuth - turn.from_azimuth) * t;
+                self.goal_elevation =
+                    turn.from_elevation + (turn.to_elevation - turn.from_elevation) * t;
+                self.azimuth = self.goal_azimuth;
+                self.elevation = self.goal_elevation;
+                if let Some(trackball) = &mut self.trackball {
+                    trackball.set(glm::quat_slerp(
+                        &turn.from_orientation,
+                        &turn.to_orientation,
+                        t,
+                    ));
+                }
+            }
+
+            if flight.elapsed >= flight.duration {
+                self.flight = None;
+            }
+        }
+
+        // exponential smoothing
+        // frame-rate independent: same feel at 30 fps and 240 fps
+        let k = 1.0 - (-self.smoothing * dt).exp();
+
+        self.target += (self.goal_target - self.target) * k;
+        self.radius += (self.goal_radius - self.radius) * k;
+        self.azimuth += (self.goal_azimuth - self.azimuth) * k;
+        self.elevation += (self.goal_elevation - self.elevation) * k;
+        if let Some(trackball) = &mut self.trackball {
+            trackball.update(k);
+        }
+    }
+
+    // animate target and radius to new values over duration seconds
+    // orientation is left alone so the user keeps their viewing angle
+    pub fn fly_to(&mut self, target: glm::Vec3, radius: f32, duration: f32) {
+        log::debug!(
+            "camera flying to {:?} at radius {:.1} over {:.2}s",
+            target.as_slice(),
+            radius,
+            duration
+        );
+        self.flight = Some(Flight {
+            from_target: self.target,
+            from_radius: self.radius,
+            to_target: target,
+            to_radius: radius.max(1.0),
+            turn: None,
+            elapsed: 0.0,
+            duration: duration.max(0.001), // zero duration = jump, but dont divide by zero
+        });
+    }
+
+    // where the camera is headed right now (the goals, not the eased values), to come back
+    // to later with fly_to_pose
+    pub fn pose(&self) -> CameraPose {
+        let (azimuth, elevation) = match &self.trackball {
+            Some(trackball) => trackball.goal_angles(),
+            None => (self.goal_azimuth, self.goal_elevation),
+        };
+        CameraPose {
+            target: self.goal_target,
+            radius: self.goal_radius,
+            azimuth,
+            elevation,
+            orientation: self.trackball.as_ref().map(|t| t.goal_orientation),
+        }
+    }
+
+    // like fly_to, but turns to the pose's viewing angle on the way
+    pub fn fly_to_pose(&mut self, pose: &CameraPose, duration: f32) {
+        self.fly_to(pose.target, pose.radius, duration);
+        // azimuth wraps, take whichever way round is shorter
+        let from_azimuth = self.goal_azimuth;
+        let mut to_azimuth = pose.azimuth;
+        while to_azimuth - from_azimuth > PI {
+            to_azimuth -= 2.0 * PI;
+        }
+        while to_azimuth - from_azimuth < -PI {
+            to_azimuth += 2.0 * PI;
+        }
+        let (from_orientation, to_orientation) = match &self.trackball {
+            Some(trackball) => (
+                trackball.goal_orientation,
+                pose.orientation.unwrap_or_else(|| {
+                    TrackballCamera::from_angles(pose.azimuth, pose.elevation).orientation
+                }),
+            ),
+            None => (glm::quat_identity(), glm::quat_identity()),
+        };
+        if let Some(flight) = &mut self.flight {
+            flight.turn = Some(Turn {
+                from_azimuth,
+                from_elevation: self.goal_elevation,
+                to_azimuth,
+                to_elevation: pose.elevation.clamp(0.01, PI - 0.01),
+                from_orientation,
+                to_orientation,
+            });
+        }
+    }
+
+    // jump straight to a pose, no easing and no flight, e.g. when a save file is loaded
+    pub fn set_pose(&mut self, target: glm::Vec3, radius: f32, azimuth: f32, elevation: f32) {
+        self.target = target;
+        self.radius = radius.max(1.0);
+        self.azimuth = azimuth;
+        self.elevation = elevation;
+        self.goal_target = self.target;
+        self.goal_radius = self.radius;
+        self.goal_azimuth = self.azimuth;
+        self.goal_elevation = self.elevation;
+        if let Some(trackball) = &mut self.trackball {
+            *trackball = TrackballCamera::from_angles(azimuth, elevation);
+        }
+        self.flight = None;
+    }
+
+    // set_pose with everything a pose has, a trackball keeps its roll
+    pub fn jump_to(&mut self, pose: &CameraPose) {
+        self.set_pose(pose.target, pose.radius, pose.azimuth, pose.elevation);
+        if let (Some(trackball), Some(orientation)) = (&mut self.trackball, pose.orientation) {
+            trackball.set(orientation);
+        }
+    }
+
+    // trackball on or off, keeping the view: the trackball starts from the orbit angles,
+    // and going back the orbit camera looks from the same side (minus any roll)
+    pub fn set_trackball(&mut self, enabled: bool) {
+        match (enabled, &self.trackball) {
+            (true, None) => {
+                self.trackball = Some(TrackballCamera::from_angles(self.azimuth, self.elevation))
+            }
+            (false, Some(trackball)) => {
+                let (azimuth, elevation) = trackball.angles();
+                self.azimuth = azimuth;
+                self.elevation = elevation.clamp(0.01, PI - 0.01);
+                self.goal_azimuth = self.azimuth;
+                self.goal_elevation = self.elevation;
+                self.trackball = None;
+            }
+            _ => {}
+        }
+    }
+
+    // swing around the vertical axis by angle radians, eased like a drag
+    // positive goes the same way as dragging right
+    pub fn orbit_by(&mut self, angle: f32) {
+        match &mut self.trackball {
+            Some(trackball) => trackball.spin(angle),
+            None => self.goal_azimuth += angle,
         }
     }
 
     // convert spherical coordinates into cartesian position
     // this is the actual "where is the camera in 3d space?" function
     pub fn get_position(&self) -> glm::Vec3 {
+        if let Some(trackball) = &self.trackball {
+            return self.target + trackball.axis(glm::vec3(0.0, 0.0, self.radius));
+        }
+
         // clamp elevation so we never reach exactly 0 or pi
         // because at poles sin goes to zero and weird flipping can happen
         // also prevents camera from inverting upside down
@@ -52,41 +357,64 @@ impl Camera {
         // except here:
         // elevation = theta
         // azimuth = phi
-        glm::vec3(
-            self.radius * elevation.sin() * self.azimuth.cos(),
-            self.radius * elevation.cos(),
-            self.radius * elevation.sin() * self.azimuth.sin(),
-        )
+        // offset is relative to target, so panning drags the whole orbit along
+        self.target
+            + glm::vec3(
+                self.radius * elevation.sin() * self.azimuth.cos(),
+                self.radius * elevation.cos(),
+                self.radius * elevation.sin() * self.azimuth.sin(),
+            )
+    }
+
+    // camera's screen-plane axes in world space
+    // right = screen x, up = screen y (pointing up, not down like pixels)
+    fn screen_axes(&self) -> (glm::Vec3, glm::Vec3) {
+        if let Some(trackball) = &self.trackball {
+            return (
+                trackball.axis(glm::vec3(1.0, 0.0, 0.0)),
+                trackball.axis(glm::vec3(0.0, 1.0, 0.0)),
+            );
+        }
+        let forward = glm::normalize(&(self.target - self.get_position()));
+        let right = glm::normalize(&glm::cross(&forward, &glm::vec3(0.0, 1.0, 0.0)));
+        let up = glm::cross(&right, &forward);
+        (right, up)
     }
 
     // build view matrix from camera position
     // look_at basically says:
     // "camera is here, looking at target, up direction is (0,1,0)"
+    // the trackball has its own up, which is what lets it look straight down and roll
     pub fn get_view_matrix(&self) -> glm::Mat4 {
-        glm::look_at(
-            &self.get_position(),
-            &self.target,
-            &glm::vec3(0.0, 1.0, 0.0),
-        )
+        let up = match &self.trackball {
+            Some(trackball) => trackball.axis(glm::vec3(0.0, 1.0, 0.0)),
+            None => glm::vec3(0.0, 1.0, 0.0),
+        };
+        // a stereo eye is the same camera slid sideways, still looking the same way
+        let eye = glm::translation(&glm::vec3(-self.eye * self.radius, 0.0, 0.0));
+        eye * glm::look_at(&self.get_position(), &self.target, &up)
+    }
+
+    // projection matrix for the current zoom level
+    // a stereo eye's window is sheared back over the middle, so both eyes see the target at
+    // the same place on screen (off-axis frustum, no toe-in and so no vertical mismatch)
+    pub fn get_projection_matrix(&self) -> glm::Mat4 {
+        let mut projection = self.projection.matrix(self.radius);
+        projection[(0, 2)] -= projection[(0, 0)] * self.eye;
+        projection
     }
 
     // called whenever mouse moves
     // if dragging is true, we rotate camera
     pub fn process_mouse_move(&mut self, x: f64, y: f64) {
         // compute how much mouse moved
-        let dx = x - self.last_x;
-        let dy = y - self.last_y;
+        let dx = (x - self.last_x) as f32;
+        let dy = (y - self.last_y) as f32;
 
         if self.dragging {
-            // horizontal mouse → change azimuth
-            self.azimuth += dx as f32 * self.orbit_speed;
-
-            // vertical mouse → change elevation
-            // subtract because screen y increases downward
-            self.elevation -= dy as f32 * self.orbit_speed;
-
-            // clamp again so we don’t flip through poles
-            self.elevation = glm::clamp_scalar(self.elevation, 0.01, PI - 0.01);
+            self.orbit(dx, dy);
+        } else if self.panning {
+            self.pan(dx, dy);
         }
 
         // update last known mouse position
@@ -94,29 +422,65 @@ impl Camera {
         self.last_y = y;
     }
 
-    // called when mouse button pressed or released
-    // we only care about left mouse button
-    pub fn process_mouse_button(
-        &mut self,
-        button: glfw::MouseButton,
-        action: glfw::Action,
-        x: f64,
-        y: f64,
-    ) {
-        if button == glfw::MouseButtonLeft {
-            if action == glfw::Action::Press {
-                // start dragging
-                self.dragging = true;
-
-                // record mouse position at click
-                // so first movement delta is correct
-                self.last_x = x;
-                self.last_y = y;
-            } else if action == glfw::Action::Release {
-                // stop dragging
-                self.dragging = false;
-            }
+    // turn around the target as if the mouse had been dragged by dx, dy pixels
+    // the gamepad sticks come in here too, already turned into pixels
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        if self.flight.as_ref().is_some_and(|f| f.turn.is_some()) {
+            // grabbing the view takes it over from a bookmark flight
+            self.flight = None;
+        }
+        if let Some(trackball) = &mut self.trackball {
+            // no poles to stop at, see trackball.rs
+            trackball.drag(dx, dy, self.orbit_speed);
+            return;
         }
+        // horizontal mouse → change azimuth
+        self.goal_azimuth += dx * self.orbit_speed;
+
+        // vertical mouse → change elevation
+        // subtract because screen y increases downward
+        self.goal_elevation -= dy * self.orbit_speed;
+
+        // clamp again so we don’t flip through poles
+        self.goal_elevation = glm::clamp_scalar(self.goal_elevation, 0.01, PI - 0.01);
+    }
+
+    // slide the target in the screen plane, same pixels as orbit
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        // minus on x so the scene follows the mouse instead of running away
+        // manual pan cancels any running flight
+        let (right, up) = self.screen_axes();
+        let scale = self.pan_speed * self.radius;
+
+        self.flight = None;
+        self.goal_target -= right * (dx * scale);
+        self.goal_target += up * (dy * scale);
+    }
+
+    // start rotating around the target, the bound mouse button went down at (x, y)
+    // which button that is lives in input.rs, the camera only knows about drags
+    pub fn start_orbit(&mut self, x: f64, y: f64) {
+        self.dragging = true;
+        self.panning = false;
+
+        // record mouse position at click
+        // so first movement delta is correct
+        self.last_x = x;
+        self.last_y = y;
+    }
+
+    // same, but slide the target in the screen plane instead
+    pub fn start_pan(&mut self, x: f64, y: f64) {
+        self.panning = true;
+        self.dragging = false;
+        self.last_x = x;
+        self.last_y = y;
+    }
+
+    // button released: stop whatever drag was going on
+    pub fn stop_drag(&mut self) {
+        self.dragging = false;
+        self.panning = false;
     }
 
     // scroll wheel zoom
@@ -124,11 +488,12 @@ impl Camera {
     pub fn process_scroll(&mut self, y_offset: f64) {
         // decrease radius when scrolling up
         // increase when scrolling down
-        self.radius -= y_offset as f32 * self.zoom_speed;
+        self.flight = None;
+        self.goal_radius -= y_offset as f32 * self.zoom_speed;
 
         // prevent camera from entering the singularity at radius 0
-        if self.radius < 1.0 {
-            self.radius = 1.0;
+        if self.goal_radius < 1.0 {
+            self.goal_radius = 1.0;
         }
     }
 }
@@ -0,0 +1,42 @@
+use image::RgbaImage; // owned rgba pixel buffer + png encoder
+use std::os::raw::c_void; // raw pointer for glReadPixels
+
+// grabbing pixels back from the gpu
+// glReadPixels hands us rows bottom-to-top (opengl origin is bottom-left)
+// images want top-to-bottom, so every capture gets flipped
+
+pub unsafe fn read_framebuffer(width: u32, height: u32) -> RgbaImage {
+    // read whatever framebuffer is currently bound for reading
+    // for the default framebuffer that is the back buffer, so call BEFORE swap_buffers
+
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+
+    // rgba rows are always 4-byte aligned, but be explicit anyway
+    gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+    gl::ReadPixels(
+        0,
+        0,
+        width as i32,
+        height as i32,
+        gl::RGBA,
+        gl::UNSIGNED_BYTE,
+        pixels.as_mut_ptr() as *mut c_void,
+    );
+
+    let image = RgbaImage::from_raw(width, height, pixels).expect("pixel buffer size mismatch");
+    image::imageops::flip_vertical(&image)
+}
+
+pub unsafe fn save_screenshot(path: &str, width: u32, height: u32) -> image::ImageResult<()> {
+    // read the back buffer and write it as png
+    read_framebuffer(width, height).save(path)
+}
+
+pub fn screenshot_filename() -> String {
+    // unique-ish name so repeated presses dont overwrite each other
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    format!("screenshot_{}.png", stamp)
+}
@@ -0,0 +1,154 @@
+use crate::elements::Element;
+use crate::isotopes; // --element lookup, isotopes included
+use crate::scene::ViewMode;
+use clap::{Parser, ValueEnum};
+
+// command line flags, `atom-sim --help` lists them
+// anything given here wins over atomsim.toml
+// and every value that is given skips its interactive prompt, so
+//   atom-sim --orbital 3,2,0 --element Fe --mode bohr
+// starts straight away without asking anything
+
+#[derive(Parser)]
+#[command(
+    name = "atom-sim",
+    version,
+    about = "Electron clouds, real orbitals and animated Bohr atoms in OpenGL"
+)]
+pub struct Cli {
+    #[arg(long, value_parser = isotopes::parse, help = "Main atom, by symbol, atomic number or isotope (Fe, 26, C-14)")]
+    pub element: Option<(&'static Element, Option<u32>)>,
+
+    #[arg(
+        long,
+        requires = "element",
+        help = "Extra atoms next to the main one, e.g. \"H H-2\" (needs --element)"
+    )]
+    pub extra: Option<String>,
+
+    #[arg(
+        long,
+        requires = "element",
+        help = "Bonds by atom number, e.g. \"1-2 1=3\" (needs --element)"
+    )]
+    pub bonds: Option<String>,
+
+    #[arg(
+        long,
+        requires = "element",
+        help = "Show the main atom next to the first extra one, or a partner picked for it, with a table comparing them"
+    )]
+    pub compare: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "element",
+        help = "Open a molecule from an .xyz or .pdb file, bonds are inferred from the distances"
+    )]
+    pub open: Option<String>,
+
+    #[arg(long, value_name = "N,L,M", value_parser = parse_orbital, help = "Quantum numbers for the cloud and orbital views")]
+    pub orbital: Option<(i32, i32, i32)>,
+
+    #[arg(long, value_enum, help = "View to start in")]
+    pub mode: Option<Mode>,
+
+    #[arg(long, help = "Simulation speed multiplier")]
+    pub speed: Option<f32>,
+
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_resolution, help = "Window (or headless image) size")]
+    pub resolution: Option<(u32, u32)>,
+
+    #[arg(long, help = "Open fullscreen on the primary monitor")]
+    pub fullscreen: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Play a timed demo script (.ron file), Ctrl+R starts it over"
+    )]
+    pub script: Option<String>,
+
+    #[arg(long, value_name = "FRAMES", num_args = 0..=1, default_missing_value = "1", help = "Render FRAMES frames to png files without showing a window")]
+    pub headless: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "10",
+        value_parser = parse_seconds,
+        conflicts_with_all = ["headless", "script", "element", "open"],
+        help = "Fly a fixed camera path through 50 uranium atoms with vsync off, then print frame times, draw calls and triangles"
+    )]
+    pub benchmark: Option<f32>,
+
+    #[arg(long, default_value_t = 4, value_parser = parse_msaa, help = "MSAA samples to ask the driver for: 0, 2, 4 or 8")]
+    pub msaa: u32,
+
+    #[arg(
+        long,
+        help = "Log OpenGL errors and driver debug messages (always on in debug builds)"
+    )]
+    pub gl_debug: bool,
+}
+
+// --mode values, lowercase on the command line
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Mode {
+    Cloud,
+    Bohr,
+    Orbital,
+}
+
+impl From<Mode> for ViewMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Cloud => ViewMode::Cloud,
+            Mode::Bohr => ViewMode::Bohr,
+            Mode::Orbital => ViewMode::Orbital,
+        }
+    }
+}
+
+fn parse_orbital(value: &str) -> Result<(i32, i32, i32), String> {
+    // "3,2,0" -> (3, 2, 0), same rules the orbital sampler uses
+    let numbers: Vec<i32> = value
+        .split(',')
+        .map(|part| part.trim().parse::<i32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| "expected three integers like 3,2,0".to_string())?;
+    let [n, l, m] = numbers[..] else {
+        return Err("expected three integers like 3,2,0".to_string());
+    };
+    if n < 1 || l < 0 || l >= n || m.abs() > l {
+        return Err("need n >= 1, 0 <= l < n and |m| <= l".to_string());
+    }
+    Ok((n, l, m))
+}
+
+fn parse_resolution(value: &str) -> Result<(u32, u32), String> {
+    // "1920x1080"
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| "expected WIDTHxHEIGHT like 1920x1080".to_string())?;
+    match (width.trim().parse::<u32>(), height.trim().parse::<u32>()) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err("expected WIDTHxHEIGHT like 1920x1080".to_string()),
+    }
+}
+
+fn parse_seconds(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(seconds),
+        _ => Err("must be a positive number of seconds".to_string()),
+    }
+}
+
+fn parse_msaa(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(samples) if [0, 2, 4, 8].contains(&samples) => Ok(samples),
+        _ => Err("must be 0, 2, 4 or 8".to_string()),
+    }
+}
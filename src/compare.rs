@@ -0,0 +1,130 @@
+use crate::elements::{self, Element}; // the measured numbers in the table
+use crate::simulation::Atom;
+use crate::text::TextRenderer; // the table is drawn with the hud's rectangles
+use nalgebra_glm as glm;
+
+// comparison mode (Q): the main atom and a partner side by side, with a table of what sets
+// them apart across the top of the screen, for contrasting e.g. Na with Cl
+// both sit in the one scene under the one camera, so turning, zooming and the time controls
+// always apply to the two of them alike
+// an atom already next to the main one (--extra) is the partner, otherwise one is brought
+// in for the comparison and taken away again after it
+// the radii and ionization energies are measured values from the element table, not the
+// bohr model's, whose shells are spaced for looking at
+
+const GAP: f32 = 4.0; // between the outermost shells of the two
+const PERIOD_ENDS: [u32; 7] = [2, 10, 18, 36, 54, 86, 118]; // the noble gases
+
+// table layout in pixels
+const LABEL_WIDTH: f32 = 84.0;
+const COLUMN_WIDTH: f32 = 150.0;
+const ROW_HEIGHT: f32 = 12.0;
+const TOP: f32 = 10.0;
+
+pub struct Comparison {
+    pub partner: usize, // index into Scene::atoms
+    pub added: bool,    // brought in by the comparison, goes when it ends
+}
+
+pub fn default_partner(element: &Element) -> &'static Element {
+    // the other end of the same period: alkali metals get the halogen, everything else
+    // gets the alkali metal, so Na meets Cl and Cl meets Na
+    let z = element.atomic_number;
+    let end = PERIOD_ENDS.into_iter().find(|&end| end >= z).unwrap_or(z);
+    let start = PERIOD_ENDS
+        .into_iter()
+        .rev()
+        .find(|&end| end < z)
+        .map_or(1, |end| end + 1);
+    let partner = if z != start {
+        start
+    } else if end - 1 > start {
+        end - 1 // the halogen
+    } else {
+        end // hydrogen, whose period has no halogen
+    };
+    elements::by_atomic_number(partner).unwrap()
+}
+
+pub fn partner_position(main: &Atom, main_position: glm::Vec3, partner: &Atom) -> glm::Vec3 {
+    // to the right of the main atom, shells just clear of each other
+    main_position + glm::vec3(main.outer_radius() + partner.outer_radius() + GAP, 0.0, 0.0)
+}
+
+pub fn draw(text: &mut TextRenderer, atoms: [&Atom; 2], width: u32) {
+    // queued into the text batch, the caller flushes
+    let grey = glm::vec4(0.8, 0.8, 0.8, 1.0);
+    let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+    let elements = atoms.map(Atom::element);
+    let shells = |atom: &Atom| {
+        atom.shells
+            .iter()
+            .map(|shell| shell.electron_count().to_string())
+            .collect::<Vec<_>>()
+            .join("-")
+    };
+    let rows: [(&str, [String; 2]); 7] = [
+        (
+            "Z",
+            elements.map(|element| element.atomic_number.to_string()),
+        ),
+        (
+            "mass",
+            elements.map(|element| format!("{:.2} u", element.atomic_mass)),
+        ),
+        (
+            "configuration",
+            elements.map(|element| elements::electron_configuration(element.atomic_number)),
+        ),
+        ("shells", atoms.map(shells)),
+        (
+            "radius",
+            elements.map(|element| match element.covalent_radius() {
+                Some(radius) => format!("{} pm", radius),
+                None => "-".to_string(),
+            }),
+        ),
+        (
+            "ionization",
+            elements.map(|element| match element.ionization_energy() {
+                Some(energy) => format!("{:.2} eV", energy),
+                None => "-".to_string(),
+            }),
+        ),
+        (
+            "charge",
+            atoms.map(|atom| atom.ion_label().unwrap_or_else(|| "neutral".to_string())),
+        ),
+    ];
+
+    let table_width = LABEL_WIDTH + 2.0 * COLUMN_WIDTH;
+    let table_height = 30.0 + rows.len() as f32 * ROW_HEIGHT;
+    let x0 = (width as f32 - table_width) / 2.0;
+    text.draw_rect(
+        x0,
+        TOP,
+        table_width,
+        table_height,
+        &glm::vec4(0.0, 0.0, 0.0, 0.85),
+    );
+    for (column, element) in elements.iter().enumerate() {
+        let x = x0 + LABEL_WIDTH + column as f32 * COLUMN_WIDTH;
+        text.draw_text(element.symbol, x, TOP + 6.0, 2.0, &white);
+        let symbol_width = TextRenderer::text_width(element.symbol, 2.0);
+        text.draw_text(
+            element.local_name(),
+            x + symbol_width + 6.0,
+            TOP + 12.0,
+            1.0,
+            &grey,
+        );
+    }
+    for (i, (label, values)) in rows.iter().enumerate() {
+        let y = TOP + 26.0 + i as f32 * ROW_HEIGHT;
+        text.draw_text(label, x0 + 6.0, y, 1.0, &grey);
+        for (column, value) in values.iter().enumerate() {
+            let x = x0 + LABEL_WIDTH + column as f32 * COLUMN_WIDTH;
+            text.draw_text(value, x, y, 1.0, &white);
+        }
+    }
+}
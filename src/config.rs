@@ -0,0 +1,694 @@
+use crate::camera::Camera;
+use crate::elements::{self, Element}; // default element lookup
+use crate::ground::GroundMode; // ground.mode
+use crate::quality::Quality; // render.quality
+use crate::scene::{BackgroundMode, DisplaySettings, MAX_SHELLS};
+use crate::stereo::{StereoMode, MAX_EYE_SEPARATION}; // [stereo]
+use crate::style::MoleculeStyle; // render.style
+use crate::texture::{Filter, TextureFiles}; // [textures]
+use crate::theme::{Theme, PRESETS}; // colors.theme
+use crate::thermal; // temperature range
+use crate::tonemap::ToneMapping; // tone_mapping.mode
+use nalgebra_glm as glm;
+use serde::Deserialize; // config file format
+use std::fmt; // pretty errors
+use std::fs; // config file
+
+// startup settings from atomsim.toml in the working directory
+// every key is optional, anything left out keeps the default below
+//
+//   [window]
+//   width = 1920
+//   height = 1080
+//   vsync = false
+//
+//   [simulation]
+//   element = "Fe"
+//   time_scale = 2.0
+//   repulsion = true
+//   temperature = 300.0
+//
+//   [camera]
+//   orbit_speed = 0.005
+//   mode = "trackball"
+//
+//   [colors]
+//   theme = "dark"
+//   background = [0.05, 0.05, 0.1]
+//   electron = [1.0, 1.0, 0.3]
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io { path: String, message: String },
+    Parse { path: String, message: String }, // not valid toml, unknown key, wrong type
+    Invalid { path: String, message: String }, // parses fine, but the value makes no sense
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, message } => {
+                write!(f, "could not read config '{}': {}", path, message)
+            }
+            // toml errors already point at the line and show it, keep them as they are
+            ConfigError::Parse { path, message } => {
+                write!(f, "invalid config file '{}':\n{}", path, message)
+            }
+            ConfigError::Invalid { path, message } => {
+                write!(f, "invalid config file '{}': {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: u32,
+    pub height: u32,
+    pub vsync: bool,
+    pub ui_scale: f32, // hud, overlays and control panel, times the display's own scale
+    pub language: String, // "auto" = from the system, "en", or a file in assets/locales
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            vsync: true,
+            ui_scale: 1.0,
+            language: "auto".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    pub element: String,        // symbol, offered as the default answer at startup
+    pub time_scale: f32,        // simulated seconds per real second
+    pub cloud_particles: usize, // monte carlo samples for the probability cloud
+    pub orbital_samples: usize, // points in the real orbital view
+    pub gpu_sampling: bool,     // sample the real orbital in a compute shader when gl 4.3 is there
+    pub electron_scale: f32,
+    pub trail_length: usize,
+    pub repulsion: bool, // electrons spread over their shell spheres instead of sitting on rings
+    pub vibration: bool, // nucleons move under their own forces instead of sitting packed
+    pub temperature: f32, // kelvin, 0 = perfectly still
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            element: "C".to_string(),
+            time_scale: 1.0,
+            cloud_particles: 100000,
+            orbital_samples: 50000,
+            gpu_sampling: true,
+            electron_scale: 0.2,
+            trail_length: 90,
+            repulsion: false,
+            vibration: false,
+            temperature: 0.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CameraConfig {
+    // same meaning as the fields of the same name on Camera
+    pub orbit_speed: f32,
+    pub zoom_speed: f32,
+    pub pan_speed: f32,
+    pub smoothing: f32,
+    pub fov: f32,
+    pub mode: String, // "orbit" (azimuth / elevation, stops at the poles) or "trackball"
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            orbit_speed: 0.01,
+            zoom_speed: 1.0,
+            pan_speed: 0.001,
+            smoothing: 12.0,
+            fov: 45.0,
+            mode: "orbit".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorConfig {
+    pub theme: String, // preset the colors start from, see theme.rs
+    // srgb in 0..1, each one replaces the theme's color when given
+    pub background: Option<[f32; 3]>,
+    pub proton: Option<[f32; 3]>,
+    pub neutron: Option<[f32; 3]>,
+    pub electron: Option<[f32; 3]>, // every shell
+    pub highlight: Option<[f32; 3]>,
+}
+
+impl Default for ColorConfig {
+    fn default() -> Self {
+        Self {
+            theme: "classic".to_string(),
+            background: None,
+            proton: None,
+            neutron: None,
+            electron: None,
+            highlight: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BloomConfig {
+    pub enabled: bool,
+    pub threshold: f32, // brightness where the glow starts, 1.0 = full white
+    pub intensity: f32, // how strongly the blurred glow is added back
+}
+
+impl Default for BloomConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            intensity: 0.8,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SsaoConfig {
+    pub enabled: bool, // off for weak gpus, three fullscreen passes a frame
+    pub strength: f32, // 0 = no darkening, 1 = fully blocked surfaces go black
+    pub radius: f32,   // how far occluders count, times the distance from the camera
+}
+
+impl Default for SsaoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strength: 0.8,
+            radius: 0.05,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShadowConfig {
+    pub enabled: bool, // key light shadows in the bohr view, Shift+H toggles
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DepthOfFieldConfig {
+    pub enabled: bool, // Ctrl+F toggles
+    pub aperture: f32, // 0 = everything sharp, 1 = blurs quickly away from the focus
+}
+
+impl Default for DepthOfFieldConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            aperture: 0.3,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToneMappingConfig {
+    pub mode: String,  // "off", "reinhard" or "aces", Ctrl+T cycles
+    pub exposure: f32, // multiplies the frame before it is mapped, 1 = as lit
+}
+
+impl Default for ToneMappingConfig {
+    fn default() -> Self {
+        Self {
+            mode: "aces".to_string(),
+            exposure: 1.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GroundConfig {
+    pub mode: String, // "off", "plane" or "grid", Shift+G cycles
+    pub height: f32,  // from the bottom of the lowest shell, negative is further down
+    pub fade: f32,    // world units around the camera target until it has faded out
+}
+
+impl Default for GroundConfig {
+    fn default() -> Self {
+        Self {
+            mode: "off".to_string(),
+            height: 0.0,
+            fade: 80.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderConfig {
+    pub quality: String, // "low", "medium", "high" or "ultra", Shift+W cycles
+    pub style: String,   // "shells", "ball-and-stick", "space-filling" or "wireframe"
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            quality: "high".to_string(),
+            style: "shells".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StereoConfig {
+    pub mode: String,    // "off", "anaglyph" or "side by side", Shift+V cycles
+    pub separation: f32, // between the eyes, times the distance to the target
+}
+
+impl Default for StereoConfig {
+    fn default() -> Self {
+        Self {
+            mode: "off".to_string(),
+            separation: 0.03,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackgroundConfig {
+    pub mode: String,          // "solid" (colors.background), "gradient" or "skybox"
+    pub top: Option<[f32; 3]>, // gradient colors, the theme's unless given
+    pub bottom: Option<[f32; 3]>,
+    pub skybox: Option<String>, // directory with px/nx/py/ny/pz/nz images
+}
+
+impl Default for BackgroundConfig {
+    fn default() -> Self {
+        Self {
+            mode: "solid".to_string(),
+            top: None,
+            bottom: None,
+            skybox: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SelectionConfig {
+    pub outline: f32, // width of the outline in pixels, colored colors.highlight
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self { outline: 3.0 }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TextureConfig {
+    pub enabled: bool,
+    pub filter: String, // "trilinear", "linear" or "nearest"
+    // png files wrapped around the spheres instead of the generated ones
+    pub proton: Option<String>,
+    pub neutron: Option<String>,
+    pub electron: Option<String>,
+}
+
+impl Default for TextureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            filter: "trilinear".to_string(),
+            proton: None,
+            neutron: None,
+            electron: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordingConfig {
+    pub every: u32,        // capture every Nth rendered frame
+    pub fps: u32,          // frame rate of the ffmpeg video
+    pub directory: String, // where recordings go, created when needed
+    pub ffmpeg: bool,      // pipe into ffmpeg for an mp4 instead of writing pngs
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            every: 2,
+            fps: 30,
+            directory: "recordings".to_string(),
+            ffmpeg: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioConfig {
+    // volumes 0..1 per kind of sound, see audio.rs; ignored in builds without the feature
+    pub muted: bool, // start silent, Ctrl+M toggles
+    pub transitions: f32,
+    pub emission: f32,
+    pub decay: f32,
+    pub interface: f32, // key presses
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            transitions: 0.4,
+            emission: 0.3,
+            decay: 0.6,
+            interface: 0.15,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub simulation: SimulationConfig,
+    pub camera: CameraConfig,
+    pub colors: ColorConfig,
+    pub bloom: BloomConfig,
+    pub ssao: SsaoConfig,
+    pub shadows: ShadowConfig,
+    pub ground: GroundConfig,
+    pub depth_of_field: DepthOfFieldConfig,
+    pub tone_mapping: ToneMappingConfig,
+    pub render: RenderConfig,
+    pub background: BackgroundConfig,
+    pub stereo: StereoConfig,
+    pub selection: SelectionConfig,
+    pub textures: TextureConfig,
+    pub recording: RecordingConfig,
+    pub audio: AudioConfig,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self, ConfigError> {
+        let source = fs::read_to_string(path).map_err(|err| ConfigError::Io {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+        let config: Config = toml::from_str(&source).map_err(|err| ConfigError::Parse {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+
+        config.validate().map_err(|message| ConfigError::Invalid {
+            path: path.to_string(),
+            message,
+        })?;
+        log::info!("loaded settings from {}", path);
+        Ok(config)
+    }
+
+    pub fn load_or_default(path: &str) -> Self {
+        // same deal as bindings.toml: missing = defaults, broken = warning + defaults
+        if !std::path::Path::new(path).exists() {
+            return Self::default();
+        }
+        Self::load(path).unwrap_or_else(|err| {
+            log::warn!("{}\nusing default settings", err);
+            Self::default()
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        // catch values that would crash or hang later, with the key name in the message
+        if self.window.width == 0 || self.window.height == 0 {
+            return Err("window.width and window.height must be at least 1".to_string());
+        }
+        if !(0.25..=8.0).contains(&self.window.ui_scale) {
+            return Err("window.ui_scale must be between 0.25 and 8".to_string());
+        }
+        // it names a file, so nothing that could climb out of assets/locales
+        let language = &self.window.language;
+        if language.is_empty()
+            || !language
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "window.language '{}' must be \"auto\" or a language code like \"de\"",
+                language
+            ));
+        }
+        if elements::by_symbol(&self.simulation.element).is_none() {
+            return Err(format!(
+                "simulation.element '{}' is not an element symbol",
+                self.simulation.element
+            ));
+        }
+        if self.simulation.time_scale <= 0.0 {
+            return Err("simulation.time_scale must be positive".to_string());
+        }
+        if !(0.0..=thermal::MAX_TEMPERATURE).contains(&self.simulation.temperature) {
+            return Err(format!(
+                "simulation.temperature must be between 0 and {} K",
+                thermal::MAX_TEMPERATURE
+            ));
+        }
+        if self.simulation.electron_scale <= 0.0 {
+            return Err("simulation.electron_scale must be positive".to_string());
+        }
+        if !(1.0..=170.0).contains(&self.camera.fov) {
+            return Err("camera.fov must be between 1 and 170 degrees".to_string());
+        }
+        if !["orbit", "trackball"].contains(&self.camera.mode.as_str()) {
+            return Err(format!(
+                "camera.mode '{}' must be orbit or trackball",
+                self.camera.mode
+            ));
+        }
+        if self.bloom.threshold <= 0.0 {
+            return Err("bloom.threshold must be positive".to_string());
+        }
+        if self.bloom.intensity < 0.0 {
+            return Err("bloom.intensity must not be negative".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.ssao.strength) {
+            return Err("ssao.strength must be between 0 and 1".to_string());
+        }
+        if self.ssao.radius <= 0.0 {
+            return Err("ssao.radius must be positive".to_string());
+        }
+        if GroundMode::from_name(&self.ground.mode).is_none() {
+            return Err(format!(
+                "ground.mode '{}' must be off, plane or grid",
+                self.ground.mode
+            ));
+        }
+        if !self.ground.height.is_finite() {
+            return Err("ground.height must be a number".to_string());
+        }
+        if self.ground.fade <= 0.0 {
+            return Err("ground.fade must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.depth_of_field.aperture) {
+            return Err("depth_of_field.aperture must be between 0 and 1".to_string());
+        }
+        if ToneMapping::from_name(&self.tone_mapping.mode).is_none() {
+            return Err(format!(
+                "tone_mapping.mode '{}' must be off, reinhard or aces",
+                self.tone_mapping.mode
+            ));
+        }
+        if !(self.tone_mapping.exposure > 0.0 && self.tone_mapping.exposure.is_finite()) {
+            return Err("tone_mapping.exposure must be positive".to_string());
+        }
+        if Quality::from_name(&self.render.quality).is_none() {
+            return Err(format!(
+                "render.quality '{}' must be low, medium, high or ultra",
+                self.render.quality
+            ));
+        }
+        if MoleculeStyle::from_name(&self.render.style).is_none() {
+            return Err(format!(
+                "render.style '{}' must be shells, ball-and-stick, space-filling or wireframe",
+                self.render.style
+            ));
+        }
+        if BackgroundMode::from_name(&self.background.mode).is_none() {
+            return Err(format!(
+                "background.mode '{}' must be solid, gradient or skybox",
+                self.background.mode
+            ));
+        }
+        if StereoMode::from_name(&self.stereo.mode).is_none() {
+            return Err(format!(
+                "stereo.mode '{}' must be off, anaglyph or side by side",
+                self.stereo.mode
+            ));
+        }
+        if !(0.0..=MAX_EYE_SEPARATION).contains(&self.stereo.separation) {
+            return Err(format!(
+                "stereo.separation must be between 0 and {}",
+                MAX_EYE_SEPARATION
+            ));
+        }
+        if self.selection.outline <= 0.0 {
+            return Err("selection.outline must be positive".to_string());
+        }
+        if Filter::from_name(&self.textures.filter).is_none() {
+            return Err(format!(
+                "textures.filter '{}' must be trilinear, linear or nearest",
+                self.textures.filter
+            ));
+        }
+        if self.recording.every == 0 || self.recording.fps == 0 {
+            return Err("recording.every and recording.fps must be at least 1".to_string());
+        }
+        let audio = &self.audio;
+        let volumes = [
+            audio.transitions,
+            audio.emission,
+            audio.decay,
+            audio.interface,
+        ];
+        if volumes.iter().any(|volume| !(0.0..=1.0).contains(volume)) {
+            return Err("audio volumes must be between 0 and 1".to_string());
+        }
+        if Theme::preset(&self.colors.theme).is_none() {
+            return Err(format!(
+                "colors.theme '{}' must be one of {}",
+                self.colors.theme,
+                PRESETS.join(", ")
+            ));
+        }
+        let colors = [
+            ("colors.background", self.colors.background),
+            ("colors.proton", self.colors.proton),
+            ("colors.neutron", self.colors.neutron),
+            ("colors.electron", self.colors.electron),
+            ("colors.highlight", self.colors.highlight),
+            ("background.top", self.background.top),
+            ("background.bottom", self.background.bottom),
+        ];
+        for (name, rgb) in colors
+            .into_iter()
+            .filter_map(|(name, rgb)| Some((name, rgb?)))
+        {
+            if rgb.iter().any(|c| !(0.0..=1.0).contains(c)) {
+                return Err(format!("{} components must be between 0 and 1", name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn element(&self) -> &'static Element {
+        // checked in validate, and the default is carbon
+        elements::by_symbol(&self.simulation.element).unwrap()
+    }
+
+    pub fn apply_camera(&self, camera: &mut Camera) {
+        camera.orbit_speed = self.camera.orbit_speed;
+        camera.zoom_speed = self.camera.zoom_speed;
+        camera.pan_speed = self.camera.pan_speed;
+        camera.smoothing = self.camera.smoothing;
+        camera.projection.fov = self.camera.fov;
+        camera.set_trackball(self.camera.mode == "trackball");
+    }
+
+    pub fn apply_display(&self, display: &mut DisplaySettings) {
+        // has to happen before atoms are added, their colors are baked in when built
+        display.electron_scale = self.simulation.electron_scale;
+        display.trail_length = self.simulation.trail_length;
+        // checked in validate
+        display.theme = self.theme();
+        display.background_mode = BackgroundMode::from_name(&self.background.mode).unwrap();
+        display.bloom = self.bloom.enabled;
+        display.bloom_threshold = self.bloom.threshold;
+        display.bloom_intensity = self.bloom.intensity;
+        display.ssao = self.ssao.enabled;
+        display.ssao_strength = self.ssao.strength;
+        display.ssao_radius = self.ssao.radius;
+        display.shadows = self.shadows.enabled;
+        display.ground = GroundMode::from_name(&self.ground.mode).unwrap();
+        display.ground_height = self.ground.height;
+        display.ground_fade = self.ground.fade;
+        display.dof = self.depth_of_field.enabled;
+        display.dof_aperture = self.depth_of_field.aperture;
+        display.tone_mapping = ToneMapping::from_name(&self.tone_mapping.mode).unwrap();
+        display.exposure = self.tone_mapping.exposure;
+        display.stereo = StereoMode::from_name(&self.stereo.mode).unwrap(); // validated too
+        display.eye_separation = self.stereo.separation;
+        display.quality = Quality::from_name(&self.render.quality).unwrap();
+        display.molecule_style = MoleculeStyle::from_name(&self.render.style).unwrap();
+        display.textures = self.textures.enabled;
+        display.outline_width = self.selection.outline;
+        display.ui_scale = self.window.ui_scale;
+    }
+
+    pub fn texture_files(&self) -> TextureFiles<'_> {
+        TextureFiles {
+            // checked in validate
+            filter: Filter::from_name(&self.textures.filter).unwrap(),
+            proton: self.textures.proton.as_deref(),
+            neutron: self.textures.neutron.as_deref(),
+            electron: self.textures.electron.as_deref(),
+        }
+    }
+}
+
+impl Config {
+    fn theme(&self) -> Theme {
+        // the preset, with whatever single colors the file gives on top
+        // theme name checked in validate
+        let mut theme = Theme::preset(&self.colors.theme).unwrap();
+        let overrides = [
+            (&mut theme.background, self.colors.background),
+            (&mut theme.proton, self.colors.proton),
+            (&mut theme.neutron, self.colors.neutron),
+            (&mut theme.highlight, self.colors.highlight),
+            (&mut theme.gradient_top, self.background.top),
+            (&mut theme.gradient_bottom, self.background.bottom),
+        ];
+        for (target, rgb) in overrides {
+            if let Some(rgb) = rgb {
+                *target = color(rgb);
+            }
+        }
+        if let Some(rgb) = self.colors.electron {
+            theme.electrons = [color(rgb); MAX_SHELLS];
+        }
+        theme
+    }
+}
+
+fn color(rgb: [f32; 3]) -> glm::Vec3 {
+    glm::vec3(rgb[0], rgb[1], rgb[2])
+}
@@ -0,0 +1,739 @@
+use crate::absorption; // photon command
+use crate::ground::GroundMode; // ground command
+use crate::history; // undo / redo commands
+use crate::import; // open command
+use crate::input::{Action, Bindings}; // the toggle key, and commands that press keys
+use crate::isotopes; // element names like C-14
+use crate::quality::Quality; // quality command
+use crate::scene::{BackgroundMode, BondOrder, Scene, ViewMode, MAX_SHELLS};
+use crate::script; // element command sets the main atom the way scripts do
+use crate::simulation::{Atom, MAX_TIME_SCALE, MIN_TIME_SCALE};
+use crate::smiles; // smiles command
+use crate::style::MoleculeStyle; // style command
+use crate::text::TextRenderer; // drawn with the hud text batch
+use crate::thermal::MAX_TEMPERATURE; // temperature command
+use crate::tonemap::ToneMapping; // tonemap command
+use glfw::{Action as KeyAction, Key};
+use nalgebra_glm as glm;
+use serde::de::IntoDeserializer; // action names are parsed like bindings.toml
+use serde::Deserialize;
+
+// drop-down command console (~), for typing what would take many key presses:
+//
+//   element Fe       main atom becomes iron (Fe-56, 26 or Fe-54 work too)
+//   speed 0.5        time scale
+//   excite 2 3       an electron of the target atom from n=2 up to n=3
+//   add O, bond 1 2  atoms and bonds, numbered from 1 like on the command line; undo, redo
+//   open water.xyz   a molecule from an .xyz or .pdb file, or smiles CC(=O)O from SMILES
+//   screenshot       any bound action by its name: action ToggleSpin
+//
+// commands live in a registry, every one a name, a usage line, a help text and a function;
+// a module adds its own by handing a list to Commands::register, see absorption::commands
+// while it is open the console takes every key, the mouse still moves the camera
+// Up / Down bring back earlier lines, Tab completes a command name, Escape closes it
+// replies go to the console itself; what the actions print still goes to the terminal
+
+const LOG_LINES: usize = 200; // older lines fall off the top
+const HEIGHT: f32 = 0.4; // of the window, the console drops down this far
+
+pub struct Context<'a> {
+    pub scene: &'a mut Scene,
+    pub actions: Vec<Action>, // run after the command, the same as pressing their keys
+}
+
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str, // the arguments, for help and for errors
+    pub help: &'static str,
+    pub run: fn(&mut Context, &[&str]) -> Result<String, String>, // reply or what went wrong
+}
+
+pub struct Commands {
+    list: Vec<Command>, // sorted by name
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        // the console's own, then every module's
+        let mut commands = Self { list: Vec::new() };
+        commands.register(builtins());
+        commands.register(absorption::commands());
+        commands
+    }
+
+    pub fn register(&mut self, commands: Vec<Command>) {
+        // a name that is there already is replaced, so a module can override a builtin
+        for command in commands {
+            self.list.retain(|existing| existing.name != command.name);
+            self.list.push(command);
+        }
+        self.list.sort_by_key(|command| command.name);
+    }
+
+    fn find(&self, name: &str) -> Option<&Command> {
+        self.list.iter().find(|command| command.name == name)
+    }
+
+    fn complete(&self, prefix: &str) -> Vec<&'static str> {
+        self.list
+            .iter()
+            .map(|command| command.name)
+            .filter(|name| name.starts_with(prefix))
+            .collect()
+    }
+}
+
+pub struct Console {
+    pub open: bool,
+    input: String,
+    log: Vec<String>,
+    history: Vec<String>,  // lines entered, oldest first
+    recall: Option<usize>, // where Up / Down are in history
+    swallow: bool,         // the ` or ~ typed by the key that just opened the console
+    actions: Vec<Action>,  // waiting for take_actions
+    commands: Commands,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            log: vec!["type help for a list of commands".to_string()],
+            history: Vec::new(),
+            recall: None,
+            swallow: false,
+            actions: Vec::new(),
+            commands: Commands::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.swallow = self.open;
+    }
+
+    pub fn take_actions(&mut self) -> Vec<Action> {
+        // what the commands entered since the last call asked for, for run_action
+        std::mem::take(&mut self.actions)
+    }
+
+    pub fn handle_event(
+        &mut self,
+        event: &glfw::WindowEvent,
+        scene: &mut Scene,
+        bindings: &Bindings,
+    ) -> bool {
+        // returns true if the console took the event and nobody else should see it
+        if !self.open {
+            return false;
+        }
+        match event {
+            glfw::WindowEvent::Char(c) => {
+                let swallow = std::mem::take(&mut self.swallow);
+                if !(swallow && matches!(c, '`' | '~')) && (' '..='~').contains(c) {
+                    self.input.push(*c);
+                }
+                true
+            }
+            glfw::WindowEvent::Key(key, _, KeyAction::Press | KeyAction::Repeat, mods) => {
+                match key {
+                    Key::Escape => self.open = false,
+                    Key::Enter | Key::KpEnter => self.submit(scene),
+                    Key::Backspace => {
+                        self.input.pop();
+                    }
+                    Key::Up => self.recall(true),
+                    Key::Down => self.recall(false),
+                    Key::Tab => self.complete(),
+                    _ if bindings.key_action(*key, *mods) == Some(Action::ToggleConsole) => {
+                        self.open = false
+                    }
+                    _ => {}
+                }
+                true
+            }
+            // letting go of a key that was pressed before the console opened
+            glfw::WindowEvent::Key(..) => true,
+            _ => false,
+        }
+    }
+
+    fn submit(&mut self, scene: &mut Scene) {
+        let line = std::mem::take(&mut self.input);
+        let line = line.trim();
+        self.recall = None;
+        if line.is_empty() {
+            return;
+        }
+        self.print(format!("> {}", line));
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+        }
+        let reply = self.run(line, scene);
+        match reply {
+            Ok(reply) => self.print(reply),
+            Err(err) => self.print(format!("error: {}", err)),
+        }
+    }
+
+    pub fn run(&mut self, line: &str, scene: &mut Scene) -> Result<String, String> {
+        // one line of text, the same as typing it and pressing Enter
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default().to_ascii_lowercase();
+        let args: Vec<&str> = words.collect();
+        if name == "help" {
+            return self.help(&args);
+        }
+        let command = self.commands.find(&name).ok_or_else(|| {
+            format!(
+                "unknown command '{}', type help for a list of commands",
+                name
+            )
+        })?;
+        let mut context = Context {
+            scene,
+            actions: Vec::new(),
+        };
+        let reply = (command.run)(&mut context, &args)
+            .map_err(|err| format!("{}\nusage: {} {}", err, command.name, command.usage))
+            .map_err(|err| err.trim_end().to_string());
+        self.actions.extend(context.actions);
+        reply
+    }
+
+    fn help(&self, args: &[&str]) -> Result<String, String> {
+        // every command on one line, or one command in full
+        let usage = |command: &Command| format!("{} {}", command.name, command.usage);
+        let width = self
+            .commands
+            .list
+            .iter()
+            .map(|command| usage(command).len())
+            .max()
+            .unwrap_or(0);
+        let line = |usage: &str, help: &str| format!("{:<width$}  {}", usage, help);
+        match args {
+            [] => {
+                let mut lines = vec![line("help [command]", "this list, or one command")];
+                for command in &self.commands.list {
+                    lines.push(line(&usage(command), command.help));
+                }
+                Ok(lines.join("\n"))
+            }
+            [name] => self
+                .commands
+                .find(&name.to_ascii_lowercase())
+                .map(|command| format!("{}\n  {}", usage(command), command.help))
+                .ok_or_else(|| format!("unknown command '{}'", name)),
+            _ => Err("usage: help [command]".to_string()),
+        }
+    }
+
+    fn print(&mut self, text: String) {
+        self.log.extend(text.lines().map(str::to_string));
+        let excess = self.log.len().saturating_sub(LOG_LINES);
+        self.log.drain(..excess);
+    }
+
+    fn recall(&mut self, older: bool) {
+        // walk through what was entered before, past the newest = an empty line again
+        let index = match (self.recall, older) {
+            (None, true) => self.history.len().checked_sub(1),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) => Some(i + 1).filter(|&i| i < self.history.len()),
+        };
+        self.recall = index;
+        self.input = index.map_or_else(String::new, |i| self.history[i].clone());
+    }
+
+    fn complete(&mut self) {
+        // the command name as far as it is unambiguous, the choices listed when it is not
+        if self.input.contains(' ') {
+            return;
+        }
+        let matches = self.commands.complete(&self.input.to_ascii_lowercase());
+        match matches.as_slice() {
+            [] => {}
+            [name] => self.input = format!("{} ", name),
+            names => {
+                let common = names[1..].iter().fold(names[0], |common, name| {
+                    let shared = common
+                        .chars()
+                        .zip(name.chars())
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    &common[..shared]
+                });
+                self.input = common.to_string();
+                self.print(names.join("  "));
+            }
+        }
+    }
+
+    pub fn draw(&self, text: &mut TextRenderer, width: u32, height: u32) {
+        // queue the console into the text batch, caller flushes
+        // the log fills upwards from the input line, as much of it as fits
+        let scale = 2.0;
+        let line_height = 10.0 * scale;
+        let padding = 8.0;
+        let bottom = (height as f32 * HEIGHT).max(3.0 * line_height);
+        text.draw_rect(
+            0.0,
+            0.0,
+            width as f32,
+            bottom,
+            &glm::vec4(0.0, 0.0, 0.0, 0.8),
+        );
+        text.draw_rect(
+            0.0,
+            bottom,
+            width as f32,
+            2.0,
+            &glm::vec4(0.4, 0.7, 1.0, 1.0),
+        );
+
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        let gray = glm::vec4(0.7, 0.7, 0.7, 1.0);
+        let red = glm::vec4(1.0, 0.45, 0.4, 1.0);
+        let input_y = bottom - padding - 7.0 * scale;
+        text.draw_text(
+            &format!("> {}_", self.input),
+            padding,
+            input_y,
+            scale,
+            &white,
+        );
+        let mut y = input_y - line_height;
+        for line in self.log.iter().rev() {
+            if y < padding {
+                break;
+            }
+            let color = if line.starts_with("> ") {
+                &white
+            } else if line.starts_with("error: ") {
+                &red
+            } else {
+                &gray
+            };
+            text.draw_text(line, padding, y, scale, color);
+            y -= line_height;
+        }
+    }
+}
+
+fn builtins() -> Vec<Command> {
+    vec![
+        Command {
+            name: "element",
+            usage: "<symbol | Z | symbol-A>",
+            help: "main atom becomes this element, or this isotope of it",
+            run: |context, args| {
+                let [name] = args else {
+                    return Err("one element".to_string());
+                };
+                let (element, mass_number) = isotopes::parse(name)?;
+                script::set_main_atom(context.scene, element, mass_number);
+                match context.scene.atoms.first() {
+                    Some(main) => Ok(format!("main atom: {}", main.atom.isotope().name())),
+                    None => Err("there is no atom".to_string()),
+                }
+            },
+        },
+        Command {
+            name: "add",
+            usage: "<symbol | Z | symbol-A> [x y z]",
+            help: "place another atom, beside the others if no position is given",
+            run: |context, args| {
+                let (name, at) = args.split_first().ok_or("one element")?;
+                let (element, mass_number) = isotopes::parse(name)?;
+                let scene = &mut *context.scene;
+                let position = match at {
+                    [] => {
+                        // right of the rightmost atom, with the gap main.rs leaves between them
+                        let radius = Atom::from_atomic_number(element.atomic_number)
+                            .map_or(0.0, |atom| atom.outer_radius());
+                        let edge = scene
+                            .atoms
+                            .iter()
+                            .map(|other| other.position.x + other.atom.outer_radius() + 2.0)
+                            .reduce(f32::max);
+                        glm::vec3(edge.map_or(0.0, |edge| edge + radius), 0.0, 0.0)
+                    }
+                    [x, y, z] => glm::vec3(number(&[x])?, number(&[y])?, number(&[z])?),
+                    _ => return Err("a position is three numbers".to_string()),
+                };
+                let index = scene.place_atom(element, mass_number, position);
+                Ok(format!(
+                    "atom {}: {}",
+                    index + 1,
+                    scene.atoms[index].atom.isotope().name()
+                ))
+            },
+        },
+        Command {
+            name: "remove",
+            usage: "<atom>",
+            help: "take an atom and its bonds out, any but the main atom (1)",
+            run: |context, args| {
+                let [index] = atoms(context.scene, args)?[..] else {
+                    return Err("one atom".to_string());
+                };
+                if index == 0 {
+                    return Err("the main atom stays".to_string());
+                }
+                if !context.scene.delete_atom(index) {
+                    return Err("not while comparing".to_string());
+                }
+                Ok(format!("removed atom {}", index + 1))
+            },
+        },
+        Command {
+            name: "bond",
+            usage: "<atom> <atom> [- | = | #]",
+            help: "single, double or triple bond between two atoms",
+            run: |context, args| {
+                let (pair, order) = match args {
+                    [a, b] => ([*a, *b], BondOrder::Single),
+                    [a, b, symbol] => (
+                        [*a, *b],
+                        symbol
+                            .chars()
+                            .next()
+                            .and_then(BondOrder::from_symbol)
+                            .filter(|_| symbol.len() == 1)
+                            .ok_or_else(|| format!("'{}' is not - = or #", symbol))?,
+                    ),
+                    _ => return Err("two atoms".to_string()),
+                };
+                let [a, b] = atoms(context.scene, &pair)?[..] else {
+                    unreachable!("two words give two atoms")
+                };
+                if !context.scene.connect(a, b, order) {
+                    return Err(format!("{} and {} cannot be bonded again", a + 1, b + 1));
+                }
+                Ok(format!("bond {}-{}", a + 1, b + 1))
+            },
+        },
+        Command {
+            name: "unbond",
+            usage: "<atom> <atom>",
+            help: "remove the bond between two atoms",
+            run: |context, args| {
+                let [a, b] = atoms(context.scene, args)?[..] else {
+                    return Err("two atoms".to_string());
+                };
+                if !context.scene.disconnect(a, b) {
+                    return Err(format!("{} and {} are not bonded", a + 1, b + 1));
+                }
+                Ok(format!("removed bond {}-{}", a + 1, b + 1))
+            },
+        },
+        Command {
+            name: "undo",
+            usage: "",
+            help: "take back the last scene edit, like Ctrl+Z",
+            run: |context, _| {
+                history::undo(context.scene)
+                    .map(|edit| format!("undid {}", edit))
+                    .ok_or_else(|| "nothing to undo".to_string())
+            },
+        },
+        Command {
+            name: "redo",
+            usage: "",
+            help: "do the last undone edit again, like Ctrl+Shift+Z",
+            run: |context, _| {
+                history::redo(context.scene)
+                    .map(|edit| format!("redid {}", edit))
+                    .ok_or_else(|| "nothing to redo".to_string())
+            },
+        },
+        Command {
+            name: "open",
+            usage: "<file.xyz | file.pdb>",
+            help: "a molecule from a file instead of the scene, like dropping it on the window",
+            run: |context, args| {
+                let [path] = args else {
+                    return Err("one file, no spaces in its path".to_string());
+                };
+                let count = import::open(context.scene, path).map_err(|err| err.to_string())?;
+                Ok(format!("opened {} atoms", count))
+            },
+        },
+        Command {
+            name: "smiles",
+            usage: "<SMILES>",
+            help: "a molecule written as SMILES instead of the scene, O=C=O or CC(=O)O",
+            run: |context, args| {
+                let [smiles] = args else {
+                    return Err("one SMILES string, without spaces".to_string());
+                };
+                let count = smiles::open(context.scene, smiles).map_err(|err| err.to_string())?;
+                Ok(format!("built {} atoms", count))
+            },
+        },
+        Command {
+            name: "speed",
+            usage: "<scale>",
+            help: "simulated seconds per real second",
+            run: |context, args| {
+                let scale = number(args)?;
+                if !(MIN_TIME_SCALE..=MAX_TIME_SCALE).contains(&scale) {
+                    return Err(format!(
+                        "the speed has to be between {} and {}",
+                        MIN_TIME_SCALE, MAX_TIME_SCALE
+                    ));
+                }
+                context.scene.time.set_scale(scale);
+                Ok(format!("speed x{}", scale))
+            },
+        },
+        Command {
+            name: "excite",
+            usage: "<from n> <to n>",
+            help: "lift an electron of the selected (or main) atom from one shell to a higher one",
+            run: |context, args| {
+                let [from, to] = args else {
+                    return Err("two shells".to_string());
+                };
+                let shell = |word: &str| {
+                    word.parse::<usize>()
+                        .ok()
+                        .filter(|n| (1..=MAX_SHELLS).contains(n))
+                        .ok_or_else(|| {
+                            format!("'{}' is not a shell from 1 to {}", word, MAX_SHELLS)
+                        })
+                };
+                let (from, to) = (shell(from)?, shell(to)?);
+                if to <= from {
+                    return Err("electrons are only lifted to a higher shell".to_string());
+                }
+                let scene = &mut *context.scene;
+                let target = scene.target_atom();
+                let atom = &mut scene
+                    .atoms
+                    .get_mut(target)
+                    .ok_or_else(|| "there is no atom".to_string())?
+                    .atom;
+                let index = atom.shells.get(from - 1).and_then(|shell| {
+                    shell
+                        .electrons
+                        .iter()
+                        .position(|electron| electron.decay_to.is_none())
+                });
+                match index {
+                    Some(index) if atom.excite((from - 1, index), to - 1) => {
+                        Ok(format!("n={} -> n={}", from, to))
+                    }
+                    _ => Err(format!("no electron in n={} to lift", from)),
+                }
+            },
+        },
+        Command {
+            name: "view",
+            usage: "<cloud | bohr | orbital>",
+            help: "what the main atom is drawn as",
+            run: |context, args| {
+                let mode = match args {
+                    ["cloud"] => ViewMode::Cloud,
+                    ["bohr"] => ViewMode::Bohr,
+                    ["orbital"] => ViewMode::Orbital,
+                    _ => return Err("cloud, bohr or orbital".to_string()),
+                };
+                context.scene.view_mode = mode;
+                Ok(format!("view: {}", args[0]))
+            },
+        },
+        Command {
+            name: "temperature",
+            usage: "<kelvin>",
+            help: "how much the atoms shake, 0 = perfectly still",
+            run: |context, args| {
+                let kelvin = number(args)?;
+                if !(0.0..=MAX_TEMPERATURE).contains(&kelvin) {
+                    return Err(format!(
+                        "the temperature has to be between 0 and {} K",
+                        MAX_TEMPERATURE
+                    ));
+                }
+                context.scene.temperature = kelvin;
+                Ok(format!("temperature {} K", kelvin))
+            },
+        },
+        Command {
+            name: "quality",
+            usage: "<low | medium | high | ultra>",
+            help: "render quality, see Shift+W",
+            run: |context, args| {
+                let quality = match args {
+                    [name] => Quality::from_name(name),
+                    _ => None,
+                }
+                .ok_or_else(|| "low, medium, high or ultra".to_string())?;
+                context.scene.display.quality = quality;
+                Ok(format!("render quality: {}", quality.name()))
+            },
+        },
+        Command {
+            name: "style",
+            usage: "<shells | ball-and-stick | space-filling | wireframe>",
+            help: "how the bohr view draws its atoms, see Ctrl+Shift+B",
+            run: |context, args| {
+                let style = match args {
+                    [name] => MoleculeStyle::from_name(name),
+                    _ => None,
+                }
+                .ok_or_else(|| "shells, ball-and-stick, space-filling or wireframe".to_string())?;
+                context.scene.display.molecule_style = style;
+                Ok(format!("molecule style: {}", style.name()))
+            },
+        },
+        Command {
+            name: "ground",
+            usage: "<off | plane | grid> [height]",
+            help: "the floor under the atoms, height from their lowest shell",
+            run: |context, args| {
+                let (mode, height) = match args {
+                    [name] => (GroundMode::from_name(name), None),
+                    [name, height] => (
+                        GroundMode::from_name(name),
+                        Some(
+                            height
+                                .parse::<f32>()
+                                .ok()
+                                .filter(|height| height.is_finite())
+                                .ok_or_else(|| format!("'{}' is not a height", height))?,
+                        ),
+                    ),
+                    _ => (None, None),
+                };
+                let mode = mode.ok_or_else(|| "off, plane or grid".to_string())?;
+                let display = &mut context.scene.display;
+                display.ground = mode;
+                if let Some(height) = height {
+                    display.ground_height = height;
+                }
+                Ok(format!(
+                    "ground: {}, height {}",
+                    mode.name(),
+                    display.ground_height
+                ))
+            },
+        },
+        Command {
+            name: "tonemap",
+            usage: "<off | reinhard | aces> [exposure]",
+            help: "how the hdr frame is brought to the screen, see Ctrl+T",
+            run: |context, args| {
+                let (mode, exposure) = match args {
+                    [name] => (ToneMapping::from_name(name), None),
+                    [name, exposure] => (
+                        ToneMapping::from_name(name),
+                        Some(
+                            exposure
+                                .parse::<f32>()
+                                .ok()
+                                .filter(|exposure| *exposure > 0.0 && exposure.is_finite())
+                                .ok_or_else(|| format!("'{}' is not an exposure", exposure))?,
+                        ),
+                    ),
+                    _ => (None, None),
+                };
+                let mode = mode.ok_or_else(|| "off, reinhard or aces".to_string())?;
+                let display = &mut context.scene.display;
+                display.tone_mapping = mode;
+                if let Some(exposure) = exposure {
+                    display.exposure = exposure;
+                }
+                Ok(format!(
+                    "tone mapping: {}, exposure {}",
+                    mode.name(),
+                    display.exposure
+                ))
+            },
+        },
+        Command {
+            name: "background",
+            usage: "<solid | gradient | skybox>",
+            help: "what is behind the atoms",
+            run: |context, args| {
+                let mode = match args {
+                    [name] => BackgroundMode::from_name(name),
+                    _ => None,
+                }
+                .ok_or_else(|| "solid, gradient or skybox".to_string())?;
+                context.scene.display.background_mode = mode;
+                Ok(format!("background: {}", mode.name()))
+            },
+        },
+        Command {
+            name: "zoom",
+            usage: "<radius> [seconds]",
+            help: "camera flies to this distance from its target",
+            run: |context, args| {
+                let (radius, seconds) = match args {
+                    [radius] => (radius, "1"),
+                    [radius, seconds] => (radius, *seconds),
+                    _ => return Err("a radius".to_string()),
+                };
+                let radius = number(&[*radius])?;
+                let seconds = number(&[seconds])?;
+                if radius <= 0.0 || seconds <= 0.0 {
+                    return Err("radius and seconds have to be positive".to_string());
+                }
+                let camera = &mut context.scene.camera;
+                camera.fly_to(camera.goal_target, radius, seconds);
+                Ok(format!("zooming to {}", radius))
+            },
+        },
+        Command {
+            name: "screenshot",
+            usage: "",
+            help: "save the next frame as a png, like F12",
+            run: |context, _| {
+                context.actions.push(Action::Screenshot);
+                Ok("screenshot of the next frame".to_string())
+            },
+        },
+        Command {
+            name: "action",
+            usage: "<name>",
+            help: "anything a key does, by its name in bindings.toml (Pause, Excite, ...)",
+            run: |context, args| {
+                let [name] = args else {
+                    return Err("one action".to_string());
+                };
+                let action = Action::deserialize(name.into_deserializer())
+                    .map_err(|_: serde::de::value::Error| format!("no action '{}'", name))?;
+                context.actions.push(action);
+                Ok(format!("{:?}", action))
+            },
+        },
+    ]
+}
+
+fn atoms(scene: &Scene, args: &[&str]) -> Result<Vec<usize>, String> {
+    // atom numbers as people count them, from 1, to indices into Scene::atoms
+    args.iter()
+        .map(|word| {
+            word.parse::<usize>()
+                .ok()
+                .filter(|number| (1..=scene.atoms.len()).contains(number))
+                .map(|number| number - 1)
+                .ok_or_else(|| format!("'{}' is not an atom from 1 to {}", word, scene.atoms.len()))
+        })
+        .collect()
+}
+
+fn number(args: &[&str]) -> Result<f32, String> {
+    // the one argument of a command that takes a number
+    let [word] = args else {
+        return Err("one number".to_string());
+    };
+    word.parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| format!("'{}' is not a number", word))
+}
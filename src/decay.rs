@@ -0,0 +1,198 @@
+use crate::isotopes::DecayMode;
+use crate::nucleus::NUCLEON_RADIUS; // alpha particles are drawn at nucleon size
+use crate::scene::DisplaySettings; // proton / neutron / electron colors
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+
+// radioactive decay, the visible part
+// Atom::decay swaps the nucleus for its daughter, this is what flies off when it does
+// plus a short flash at the nucleus and a hud line saying what turned into what
+//
+// real half-lives go from microseconds (Po-212) to ten billion years (Th-232),
+// no time scale shows both, so decay runs on its own didactic clock:
+// the shortest ones take SHORTEST_HALF_LIFE on screen and every factor of ten
+// in real half-life adds SECONDS_PER_DECADE, so U-238 still outlasts Po-214, just not by 10^20
+
+const SHORTEST_HALF_LIFE: f32 = 3.0; // simulated seconds, for REFERENCE_HALF_LIFE and below
+const REFERENCE_HALF_LIFE: f64 = 1e-6; // real seconds
+const SECONDS_PER_DECADE: f32 = 0.25;
+
+const ALPHA_SPEED: f32 = 4.0; // world units per simulated second, heavy and slow
+const BETA_SPEED: f32 = 15.0; // light and fast
+const EJECTA_LIFETIME: f32 = 4.0;
+const EJECTA_SPIN: f32 = 3.0; // rad/s, alpha particles tumble as they go
+pub const ALPHA_TRAIL_COLOR: glm::Vec3 = glm::Vec3::new(1.0, 0.7, 0.3);
+
+const FLASH_DURATION: f32 = 0.5;
+const FLASH_RADIUS: f32 = 4.0; // world units at full size
+const LABEL_DURATION: f32 = 4.0; // how long the hud keeps saying what decayed
+
+pub fn screen_half_life(half_life: f64) -> f32 {
+    // real half-life in seconds -> simulated seconds on the didactic clock
+    let decades = (half_life / REFERENCE_HALF_LIFE).log10().max(0.0) as f32;
+    SHORTEST_HALF_LIFE + SECONDS_PER_DECADE * decades
+}
+
+pub fn random_direction() -> glm::Vec3 {
+    // uniform on the unit sphere: uniform height, uniform angle around
+    let z: f32 = rand::random::<f32>() * 2.0 - 1.0;
+    let angle = rand::random::<f32>() * 2.0 * PI;
+    let ring = (1.0 - z * z).sqrt();
+    glm::vec3(ring * angle.cos(), ring * angle.sin(), z)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EjectaKind {
+    Alpha,    // helium-4 nucleus, 2 protons + 2 neutrons
+    Electron, // beta-
+    Positron, // beta+
+}
+
+impl EjectaKind {
+    pub fn from_mode(mode: DecayMode) -> Option<Self> {
+        // electron capture only sends out a neutrino, nothing to draw
+        match mode {
+            DecayMode::Alpha => Some(EjectaKind::Alpha),
+            DecayMode::BetaMinus => Some(EjectaKind::Electron),
+            DecayMode::BetaPlus => Some(EjectaKind::Positron),
+            DecayMode::ElectronCapture => None,
+        }
+    }
+}
+
+pub struct Ejecta {
+    // particle thrown out of a decaying nucleus, flies straight out and disappears
+    pub position: glm::Vec3,
+    previous: glm::Vec3, // position one simulation step ago
+    pub velocity: glm::Vec3,
+    pub kind: EjectaKind,
+    pub source: usize, // index of the atom it came out of, it cannot hit that one
+    age: f32,
+}
+
+impl Ejecta {
+    pub fn new(position: glm::Vec3, direction: glm::Vec3, kind: EjectaKind, source: usize) -> Self {
+        let speed = match kind {
+            EjectaKind::Alpha => ALPHA_SPEED,
+            EjectaKind::Electron | EjectaKind::Positron => BETA_SPEED,
+        };
+        Self {
+            position,
+            previous: position,
+            velocity: direction * speed,
+            kind,
+            source,
+            age: 0.0,
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        self.previous = self.position;
+        self.position += self.velocity * dt;
+        self.age += dt;
+    }
+
+    pub fn alive(&self) -> bool {
+        self.age < EJECTA_LIFETIME
+    }
+
+    pub fn spheres(
+        &self,
+        display: &DisplaySettings,
+        alpha: f32,
+    ) -> Vec<(glm::Vec3, f32, glm::Vec3)> {
+        // (world center, radius, color) of everything this particle is drawn with
+        // alpha = Simulation::alpha, blends between the last two steps
+        let position = glm::lerp(&self.previous, &self.position, alpha);
+        match self.kind {
+            EjectaKind::Alpha => alpha_spheres(position, self.age, display),
+            EjectaKind::Electron => {
+                vec![(
+                    position,
+                    display.electron_scale,
+                    free_electron_color(display),
+                )]
+            }
+            EjectaKind::Positron => {
+                vec![(position, display.electron_scale, positron_color(display))]
+            }
+        }
+    }
+
+    pub fn trail_color(&self, display: &DisplaySettings) -> glm::Vec3 {
+        // color of the streak it leaves behind
+        match self.kind {
+            EjectaKind::Alpha => ALPHA_TRAIL_COLOR,
+            EjectaKind::Electron => free_electron_color(display),
+            EjectaKind::Positron => positron_color(display),
+        }
+    }
+}
+
+pub fn alpha_spheres(
+    position: glm::Vec3,
+    age: f32,
+    display: &DisplaySettings,
+) -> Vec<(glm::Vec3, f32, glm::Vec3)> {
+    // tetrahedron of touching nucleons, protons on one edge, neutrons on the other
+    // age in simulated seconds, it tumbles as it goes
+    let spin = glm::rotation(age * EJECTA_SPIN, &glm::vec3(0.3, 1.0, 0.2));
+    let d = NUCLEON_RADIUS / 2f32.sqrt();
+    [
+        (glm::vec3(d, d, d), display.theme.proton),
+        (glm::vec3(-d, -d, d), display.theme.proton),
+        (glm::vec3(-d, d, -d), display.theme.neutron),
+        (glm::vec3(d, -d, -d), display.theme.neutron),
+    ]
+    .into_iter()
+    .map(|(offset, color)| {
+        let rotated = (spin * glm::vec4(offset.x, offset.y, offset.z, 0.0)).xyz();
+        (position + rotated, NUCLEON_RADIUS, color)
+    })
+    .collect()
+}
+
+fn free_electron_color(display: &DisplaySettings) -> glm::Vec3 {
+    // not on any shell, so it borrows the innermost one's color
+    display.theme.electron(1)
+}
+
+fn positron_color(display: &DisplaySettings) -> glm::Vec3 {
+    // antimatter gets the opposite hue of the electrons
+    glm::vec3(1.0, 1.0, 1.0) - free_electron_color(display)
+}
+
+pub struct Flash {
+    // burst of light where a nucleus just decayed, and the hud line that goes with it
+    pub position: glm::Vec3,
+    pub label: String, // "C-14 -> N-14 (beta-)"
+    age: f32,
+}
+
+impl Flash {
+    pub fn new(position: glm::Vec3, label: String) -> Self {
+        Self {
+            position,
+            label,
+            age: 0.0,
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        self.age += dt;
+    }
+
+    pub fn alive(&self) -> bool {
+        self.age < LABEL_DURATION
+    }
+
+    pub fn glow(&self) -> Option<(f32, f32)> {
+        // (radius, opacity) while the flash is visible, None once only the label is left
+        // grows fast then slows down, fades the whole time
+        if self.age >= FLASH_DURATION {
+            return None;
+        }
+        let t = self.age / FLASH_DURATION;
+        Some((FLASH_RADIUS * (t * PI * 0.5).sin(), 1.0 - t))
+    }
+}
@@ -0,0 +1,123 @@
+use crate::framebuffer::{self, ColorFormat, Framebuffer};
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::stats;
+use nalgebra_glm as glm;
+
+// depth of field (Ctrl+F), for screenshots and recordings: sharp at the focus, blurrier
+// the further something is in front of it or behind it, like a camera lens
+// the focus is the selected atom, or the camera target without a selection; the aperture
+// says how quickly it blurs away from there, 0 keeps everything sharp
+// the scene is drawn into a target whose depth is a texture (or ssao hands its depth on),
+// then two passes, horizontal and vertical, each gathering the neighbours whose blur
+// reaches the pixel; the blur radius is worked out from depth in the first and carried
+// in alpha to the second
+// the blur is capped at a share of the picture height, so it costs the same at any size
+// nested outside ssao and inside bloom: the occlusion is blurred with the frame, and a
+// blurred electron still glows
+
+const SCALE: f32 = 0.05; // blur per unit of relative distance at aperture 1, of the height
+const MAX_RADIUS: f32 = 0.012; // the biggest blur, of the picture height
+
+pub struct DepthOfField {
+    blur: WatchedProgram,
+    composite: WatchedProgram,
+    empty_vao: gl::types::GLuint, // fullscreen triangle, generated in the vertex shader
+    scene: Framebuffer,           // full resolution hdr frame, depth readable
+    ping: Framebuffer,            // after the horizontal pass, blur radius in alpha
+    pong: Framebuffer,            // after the vertical one
+    previous: gl::types::GLuint,  // framebuffer to composite into, remembered in begin()
+}
+
+impl DepthOfField {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        Ok(Self {
+            blur: WatchedProgram::new("dof", ShaderProgram::dof)?,
+            composite: WatchedProgram::new("dof_composite", ShaderProgram::dof_composite)?,
+            empty_vao,
+            scene: Framebuffer::with_depth_texture(ColorFormat::Rgba16F),
+            ping: Framebuffer::new(ColorFormat::Rgba16F, false),
+            pong: Framebuffer::new(ColorFormat::Rgba16F, false),
+            previous: 0,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.blur.poll();
+        self.composite.poll();
+    }
+
+    pub unsafe fn begin(&mut self, width: u32, height: u32) {
+        // redirect drawing into the target with the depth texture
+        self.previous = framebuffer::current_binding();
+        self.scene.resize(width, height);
+        self.ping.resize(width, height);
+        self.pong.resize(width, height);
+        self.scene.bind();
+    }
+
+    pub unsafe fn finish(&mut self, projection: &glm::Mat4, focus: f32, aperture: f32) {
+        // focus = view distance of what stays sharp
+        gl::Disable(gl::DEPTH_TEST);
+        gl::BindVertexArray(self.empty_vao);
+        let (width, height) = self.scene.size();
+        let texel = glm::vec2(1.0 / width as f32, 1.0 / height as f32);
+
+        self.blur.use_program();
+        self.blur.set_uniform_i32("image", 0);
+        self.blur.set_uniform_i32("depth", 1);
+        self.blur
+            .set_uniform_mat4("inverseProjection", &glm::inverse(projection));
+        self.blur.set_uniform_f32("focus", focus);
+        self.blur
+            .set_uniform_f32("scale", aperture * SCALE * height as f32);
+        self.blur
+            .set_uniform_f32("maxRadius", (MAX_RADIUS * height as f32).max(1.0));
+        self.scene.bind_depth_texture(1);
+
+        self.ping.bind();
+        self.blur.set_uniform_i32("first", 1);
+        self.blur
+            .set_uniform_vec2("direction", &glm::vec2(texel.x, 0.0));
+        self.scene.bind_texture(0);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+        self.pong.bind();
+        self.blur.set_uniform_i32("first", 0);
+        self.blur
+            .set_uniform_vec2("direction", &glm::vec2(0.0, texel.y));
+        self.ping.bind_texture(0);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+        // back into the original target, depth passed on like ssao does
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous);
+        gl::Viewport(0, 0, width as i32, height as i32);
+        gl::Enable(gl::DEPTH_TEST);
+        gl::DepthFunc(gl::ALWAYS);
+        self.composite.use_program();
+        self.composite.set_uniform_i32("image", 0);
+        self.composite.set_uniform_i32("depth", 1);
+        self.pong.bind_texture(0);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::DepthFunc(gl::LESS);
+
+        for unit in [1, 0] {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        gl::BindVertexArray(0);
+        check_gl_error!("depth of field");
+    }
+}
+
+impl Drop for DepthOfField {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::material::{Material, MaterialId, MaterialLibrary}; // how spheres look
+use crate::scene::EntityId; // what picking reports
+use nalgebra_glm as glm;
+
+// the bohr view's objects as entities with components, instead of one node type that
+// has to know about everything
+// an entity is just an index; each kind of component lives in its own storage, indexed
+// by entity, and a system is a plain function (or loop) over the storages it cares about:
+//   physics   - Scene::step and simulation.rs, moves the atoms themselves
+//   animation - scene.rs copies that state into the transforms, then
+//               World::update_transforms turns them into world matrices
+//   rendering - renderer.rs / export.rs walk the spheres() query
+// hand rolled on purpose: a few hundred entities, rebuilt whenever the structure of an
+// atom changes, do not need archetypes or a dependency
+// short lived things flying through world space (photons, decay ejecta, sparks) stay in
+// their own lists on Scene, they have no parents and come and go every frame
+
+pub type Entity = usize;
+
+// one kind of component, None for entities that do not have it
+pub struct Storage<T> {
+    items: Vec<Option<T>>,
+}
+
+impl<T> Storage<T> {
+    fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    pub fn insert(&mut self, entity: Entity, component: T) {
+        if self.items.len() <= entity {
+            self.items.resize_with(entity + 1, || None);
+        }
+        self.items[entity] = Some(component);
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.items.get(entity).and_then(Option::as_ref)
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.items.get_mut(entity).and_then(Option::as_mut)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(entity, item)| Some((entity, item.as_ref()?)))
+    }
+}
+
+pub struct Transform {
+    pub local: glm::Mat4,       // relative to the parent
+    previous: glm::Mat4,        // local one simulation step ago, for interpolation
+    pub parent: Option<Entity>, // moving a parent moves everything below it
+    pub visible: bool,          // false hides this entity and everything below it
+    world: glm::Mat4,           // from update_transforms, blended between the last two steps
+    shown: bool,                // visible, and so is every parent
+}
+
+// what shape to draw; only spheres so far, unit sphere scaled by radius
+// the radius applies to this entity only, NOT to its children
+pub enum Mesh {
+    Sphere { radius: f32 },
+}
+
+// a proton or neutron of Scene::atoms[atom], index into nucleus::packed
+pub struct NucleonState {
+    pub atom: usize,
+    pub index: usize,
+    pub proton: bool,
+}
+
+// an electron of Scene::atoms[atom]
+pub struct ElectronState {
+    pub atom: usize,
+    pub place: ElectronPlace,
+}
+
+pub enum ElectronPlace {
+    Shell { shell: usize, index: usize }, // index into Atom::shells[shell].electrons
+    Jumping,                              // between two shells, one of Atom::jumps
+}
+
+// leaves a trail colored for shell n, see trail.rs
+pub struct Trail {
+    pub n: u32,
+}
+
+pub struct World {
+    count: usize,
+    pub transforms: Storage<Transform>,
+    pub meshes: Storage<Mesh>,
+    pub materials: Storage<MaterialId>, // into library
+    pub nucleons: Storage<NucleonState>,
+    pub electrons: Storage<ElectronState>,
+    pub trails: Storage<Trail>,
+    pub library: MaterialLibrary,
+    pub blend: f32, // 0 = draw the previous step, 1 = the latest one
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            count: 0,
+            transforms: Storage::new(),
+            meshes: Storage::new(),
+            materials: Storage::new(),
+            nucleons: Storage::new(),
+            electrons: Storage::new(),
+            trails: Storage::new(),
+            library: MaterialLibrary::new(),
+            blend: 1.0,
+        }
+    }
+
+    pub fn spawn(&mut self, parent: Option<Entity>, local: glm::Mat4) -> Entity {
+        // every entity has a transform, the rest is added on with the storages' insert
+        // parents always exist before their children, which is what lets update_transforms
+        // go through the list once, front to back
+        let entity = self.count;
+        self.count += 1;
+        let (parent_world, parent_shown) = parent
+            .and_then(|parent| self.transforms.get(parent))
+            .map_or((glm::identity(), true), |parent| {
+                (parent.world, parent.shown)
+            });
+        self.transforms.insert(
+            entity,
+            Transform {
+                local,
+                previous: local,
+                parent,
+                visible: true,
+                world: parent_world * local,
+                shown: parent_shown,
+            },
+        );
+        entity
+    }
+
+    pub fn set_local(&mut self, entity: Entity, local: glm::Mat4) {
+        if let Some(transform) = self.transforms.get_mut(entity) {
+            transform.local = local;
+        }
+    }
+
+    pub fn place(&mut self, entity: Entity, local: glm::Mat4) {
+        // set_local for a move that does not come from a simulation step: drawn there right
+        // away, not blended in from where the last step left it (which, while paused, would
+        // never catch up)
+        if let Some(transform) = self.transforms.get_mut(entity) {
+            transform.local = local;
+            transform.previous = local;
+        }
+    }
+
+    pub fn set_visible(&mut self, entity: Entity, visible: bool) {
+        if let Some(transform) = self.transforms.get_mut(entity) {
+            transform.visible = visible;
+        }
+    }
+
+    pub fn save_previous(&mut self) {
+        // call right before a simulation step changes the locals
+        for transform in self.transforms.items.iter_mut().flatten() {
+            transform.previous = transform.local;
+        }
+    }
+
+    pub fn update_transforms(&mut self) {
+        // world matrix of everything, blended by self.blend
+        for entity in 0..self.count {
+            let Some(transform) = self.transforms.get(entity) else {
+                continue;
+            };
+            let (parent_world, parent_shown) = transform
+                .parent
+                .and_then(|parent| self.transforms.get(parent))
+                .map_or((glm::identity(), true), |parent| {
+                    (parent.world, parent.shown)
+                });
+            // plain component-wise lerp: the locals that change between steps are
+            // translations (electrons on their orbits); atoms moved or turned outside a step
+            // go through place(), so everything else is the same matrix twice
+            let local = transform.previous + (transform.local - transform.previous) * self.blend;
+            let transform = self.transforms.get_mut(entity).unwrap();
+            transform.world = parent_world * local;
+            transform.shown = parent_shown && transform.visible;
+        }
+    }
+
+    pub fn position(&self, entity: Entity) -> glm::Vec3 {
+        // world space origin of an entity, as of the last update_transforms
+        self.transforms
+            .get(entity)
+            .map_or(glm::vec3(0.0, 0.0, 0.0), |transform| {
+                transform.world.column(3).xyz()
+            })
+    }
+
+    pub fn pick_id(&self, entity: Entity) -> Option<EntityId> {
+        // what selecting this entity means for the rest of the app; None for electrons
+        // between shells, they are not clickable mid-flight
+        if let Some(nucleon) = self.nucleons.get(entity) {
+            return Some(EntityId::Nucleon {
+                atom: nucleon.atom,
+                index: nucleon.index,
+            });
+        }
+        match self.electrons.get(entity)? {
+            ElectronState {
+                atom,
+                place: ElectronPlace::Shell { shell, index },
+            } => Some(EntityId::Electron {
+                atom: *atom,
+                shell: *shell,
+                index: *index,
+            }),
+            ElectronState {
+                place: ElectronPlace::Jumping,
+                ..
+            } => None,
+        }
+    }
+
+    // query: every shown sphere with its material, model = world matrix scaled to the sphere
+    pub fn spheres(&self) -> impl Iterator<Item = Sphere<'_>> {
+        self.meshes.iter().filter_map(|(entity, mesh)| {
+            let transform = self.transforms.get(entity).filter(|t| t.shown)?;
+            let &material_id = self.materials.get(entity)?;
+            let Mesh::Sphere { radius } = *mesh;
+            Some(Sphere {
+                entity,
+                world: transform.world,
+                model: glm::scale(&transform.world, &glm::vec3(radius, radius, radius)),
+                radius,
+                material_id,
+                material: self.library.get(material_id),
+            })
+        })
+    }
+}
+
+pub struct Sphere<'a> {
+    pub entity: Entity,
+    pub world: glm::Mat4, // without the radius
+    pub model: glm::Mat4,
+    pub radius: f32,
+    pub material_id: MaterialId,
+    pub material: &'a Material,
+}
@@ -0,0 +1,314 @@
+use nalgebra_glm as glm; // cpk colors
+
+// periodic table data
+// one entry per element, index = atomic number - 1
+// shell occupancy is the bohr-style electrons-per-shell list (2-8-18-...)
+// masses are standard atomic weights, or the most stable isotope
+// mass number for elements that only exist in reactors and accelerators
+
+pub struct Element {
+    pub atomic_number: u32,
+    pub symbol: &'static str,
+    pub name: &'static str,
+    pub atomic_mass: f64,       // in u (daltons)
+    pub shells: &'static [u32], // electrons per shell, innermost first
+}
+
+impl Element {
+    pub fn local_name(&self) -> &'static str {
+        // for the screen, in the language of the control panel, see locale.rs
+        crate::locale::element_name(self)
+    }
+
+    pub fn neutron_count(&self) -> u32 {
+        // most common isotope approximated as round(mass) - Z
+        // good enough for drawing nucleons, not for nuclear physics
+        (self.atomic_mass.round() as u32).saturating_sub(self.atomic_number)
+    }
+
+    pub fn ionization_energy(&self) -> Option<f32> {
+        // eV to pull the first electron off, None where nobody has measured it
+        IONIZATION_ENERGIES
+            .get(self.atomic_number as usize - 1)
+            .copied()
+    }
+
+    pub fn covalent_radius(&self) -> Option<u32> {
+        // pm, None past curium
+        COVALENT_RADII.get(self.atomic_number as usize - 1).copied()
+    }
+
+    pub fn van_der_waals_radius(&self) -> Option<u32> {
+        // pm, None past lawrencium
+        VAN_DER_WAALS_RADII
+            .get(self.atomic_number as usize - 1)
+            .copied()
+    }
+
+    pub fn cpk_color(&self) -> glm::Vec3 {
+        // srgb, the usual molecule colors: white hydrogen, grey carbon, blue nitrogen, red
+        // oxygen; deep pink past meitnerium like jmol
+        let rgb = CPK_COLORS
+            .get(self.atomic_number as usize - 1)
+            .copied()
+            .unwrap_or(0xff1493);
+        glm::vec3(
+            (rgb >> 16) as f32 / 255.0,
+            (rgb >> 8 & 0xff) as f32 / 255.0,
+            (rgb & 0xff) as f32 / 255.0,
+        )
+    }
+}
+
+// first ionization energies in eV (nist), measured up to lawrencium
+#[rustfmt::skip]
+const IONIZATION_ENERGIES: [f32; 103] = [
+    13.598, 24.587,
+    5.392, 9.323, 8.298, 11.260, 14.534, 13.618, 17.423, 21.565,
+    5.139, 7.646, 5.986, 8.152, 10.487, 10.360, 12.968, 15.760,
+    4.341, 6.113, 6.561, 6.828, 6.746, 6.767, 7.434, 7.902, 7.881, 7.640, 7.726, 9.394,
+    5.999, 7.900, 9.789, 9.752, 11.814, 14.000,
+    4.177, 5.695, 6.217, 6.634, 6.759, 7.092, 7.119, 7.360, 7.459, 8.337, 7.576, 8.994,
+    5.786, 7.344, 8.608, 9.010, 10.451, 12.130,
+    3.894, 5.212, 5.577, 5.539, 5.473, 5.525, 5.582, 5.644, 5.670, 6.150, 5.864, 5.939,
+    6.022, 6.108, 6.184, 6.254, 5.426, 6.825, 7.550, 7.864, 7.834, 8.438, 8.967, 8.959,
+    9.226, 10.438, 6.108, 7.417, 7.286, 8.414, 9.318, 10.749,
+    4.073, 5.278, 5.380, 6.307, 5.890, 6.194, 6.266, 6.026, 5.974, 5.991, 6.198, 6.282,
+    6.368, 6.500, 6.580, 6.626, 4.960,
+];
+
+// the size of an atom is not a sharp thing, the electron cloud just fades out; the covalent
+// radius (half the length of a single bond to its own kind) is the one measured for nearly
+// every element, in pm (cordero et al. 2008, low spin for Mn, Fe, Co), up to curium
+#[rustfmt::skip]
+const COVALENT_RADII: [u32; 96] = [
+    31, 28,
+    128, 96, 84, 76, 71, 66, 57, 58,
+    166, 141, 121, 111, 107, 105, 102, 106,
+    203, 176, 170, 160, 153, 139, 139, 132, 126, 124, 132, 122, 122, 120, 119, 120, 120, 116,
+    220, 195, 190, 175, 164, 154, 147, 146, 142, 139, 145, 144, 142, 139, 139, 138, 139, 140,
+    244, 215, 207, 204, 203, 201, 199, 198, 198, 196, 194, 192, 192, 189, 190, 187, 187, 175,
+    170, 162, 151, 144, 141, 136, 136, 132, 145, 146, 148, 140, 150, 150,
+    260, 221, 215, 206, 200, 196, 190, 187, 180, 169,
+];
+
+// van der waals radius: how close two atoms of different molecules come before they
+// push back, the size of the sphere in a space-filling model; in pm (bondi 1964, mantina et
+// al. 2009 for the main group, alvarez 2013 style estimates for the rest, as open babel has
+// them), up to lawrencium
+#[rustfmt::skip]
+const VAN_DER_WAALS_RADII: [u32; 103] = [
+    110, 140,
+    181, 153, 192, 170, 155, 152, 147, 154,
+    227, 173, 184, 210, 180, 180, 175, 188,
+    275, 231, 215, 211, 207, 206, 205, 204, 200, 197, 196, 201, 187, 211, 185, 190, 183, 202,
+    303, 249, 232, 223, 218, 217, 216, 213, 210, 210, 211, 218, 193, 217, 206, 206, 198, 216,
+    343, 268, 243, 242, 240, 239, 238, 236, 235, 234, 233, 231, 230, 229, 227, 226, 224, 223,
+    222, 218, 216, 216, 213, 213, 214, 223, 196, 202, 207, 197, 202, 220,
+    348, 283, 247, 245, 243, 241, 239, 243, 244, 245, 244, 245, 245, 245, 246, 246, 246,
+];
+
+// cpk colors (corey, pauling, koltun) the way jmol extends them over the table, as 0xrrggbb
+// srgb, up to meitnerium
+#[rustfmt::skip]
+const CPK_COLORS: [u32; 109] = [
+    0xffffff, 0xd9ffff,
+    0xcc80ff, 0xc2ff00, 0xffb5b5, 0x909090, 0x3050f8, 0xff0d0d, 0x90e050, 0xb3e3f5,
+    0xab5cf2, 0x8aff00, 0xbfa6a6, 0xf0c8a0, 0xff8000, 0xffff30, 0x1ff01f, 0x80d1e3,
+    0x8f40d4, 0x3dff00, 0xe6e6e6, 0xbfc2c7, 0xa6a6ab, 0x8a99c7, 0x9c7ac7, 0xe06633, 0xf090a0,
+    0x50d050, 0xc88033, 0x7d80b0, 0xc28f8f, 0x668f8f, 0xbd80e3, 0xffa100, 0xa62929, 0x5cb8d1,
+    0x702eb0, 0x00ff00, 0x94ffff, 0x94e0e0, 0x73c2c9, 0x54b5b5, 0x3b9e9e, 0x248f8f, 0x0a7d8c,
+    0x006985, 0xc0c0c0, 0xffd98f, 0xa67573, 0x668080, 0x9e63b5, 0xd47a00, 0x940094, 0x429eb0,
+    0x57178f, 0x00c900, 0x70d4ff, 0xffffc7, 0xd9ffc7, 0xc7ffc7, 0xa3ffc7, 0x8fffc7, 0x61ffc7,
+    0x45ffc7, 0x30ffc7, 0x1fffc7, 0x00ff9c, 0x00e675, 0x00d452, 0x00bf38, 0x00ab24, 0x4dc2ff,
+    0x4da6ff, 0x2194d6, 0x267dab, 0x266696, 0x175487, 0xd0d0e0, 0xffd123, 0xb8b8d0, 0xa6544d,
+    0x575961, 0x9e4fb5, 0xab5c00, 0x754f45, 0x428296,
+    0x420066, 0x007d00, 0x70abfa, 0x00baff, 0x00a1ff, 0x008fff, 0x0080ff, 0x006bff, 0x545cf2,
+    0x785ce3, 0x8a4fe3, 0xa136d4, 0xb31fd4, 0xb31fba, 0xb30da6, 0xbd0d87, 0xc70066, 0xcc0059,
+    0xd1004f, 0xd90045, 0xe00038, 0xe6002e, 0xeb0026,
+];
+
+pub fn by_atomic_number(z: u32) -> Option<&'static Element> {
+    // table is ordered so this is just an index
+    if z == 0 {
+        return None;
+    }
+    ELEMENTS.get((z - 1) as usize)
+}
+
+pub fn by_symbol(symbol: &str) -> Option<&'static Element> {
+    // case-insensitive so "fe", "Fe" and "FE" all work
+    ELEMENTS
+        .iter()
+        .find(|element| element.symbol.eq_ignore_ascii_case(symbol))
+}
+
+// subshells in aufbau (madelung) filling order: by n + l, then by n
+#[rustfmt::skip]
+pub const AUFBAU_ORDER: [(u32, u32); 19] = [
+    (1, 0), (2, 0), (2, 1), (3, 0), (3, 1), (4, 0), (3, 2), (4, 1), (5, 0), (4, 2),
+    (5, 1), (6, 0), (4, 3), (5, 2), (6, 1), (7, 0), (5, 3), (6, 2), (7, 1),
+];
+pub const SUBSHELL_LETTERS: [char; 4] = ['s', 'p', 'd', 'f'];
+const NOBLE_GASES: [u32; 6] = [86, 54, 36, 18, 10, 2]; // heaviest first
+
+pub fn electron_configuration(z: u32) -> String {
+    // ground state configuration like "[Ne] 3s2 3p4"
+    // plain aufbau filling, so the handful of exceptions (Cr, Cu, ...) come out textbook-wrong
+    // the core is abbreviated with the biggest noble gas that fits
+    let core = NOBLE_GASES.iter().copied().find(|&gas| gas < z);
+
+    let mut parts = Vec::new();
+    if let Some(gas) = core {
+        parts.push(format!("[{}]", ELEMENTS[(gas - 1) as usize].symbol));
+    }
+
+    let mut remaining = z;
+    let mut skip = core.unwrap_or(0); // electrons already covered by the core
+    for (n, l) in AUFBAU_ORDER {
+        if remaining == 0 {
+            break;
+        }
+        let count = remaining.min(2 * (2 * l + 1));
+        remaining -= count;
+        if skip >= count {
+            skip -= count;
+            continue;
+        }
+        parts.push(format!("{}{}{}", n, SUBSHELL_LETTERS[l as usize], count));
+    }
+    parts.join(" ")
+}
+
+macro_rules! element {
+    ($z:expr, $symbol:expr, $name:expr, $mass:expr, [$($shell:expr),*]) => {
+        Element {
+            atomic_number: $z,
+            symbol: $symbol,
+            name: $name,
+            atomic_mass: $mass,
+            shells: &[$($shell),*],
+        }
+    };
+}
+
+pub static ELEMENTS: [Element; 118] = [
+    element!(1, "H", "Hydrogen", 1.008, [1]),
+    element!(2, "He", "Helium", 4.0026, [2]),
+    element!(3, "Li", "Lithium", 6.94, [2, 1]),
+    element!(4, "Be", "Beryllium", 9.0122, [2, 2]),
+    element!(5, "B", "Boron", 10.81, [2, 3]),
+    element!(6, "C", "Carbon", 12.011, [2, 4]),
+    element!(7, "N", "Nitrogen", 14.007, [2, 5]),
+    element!(8, "O", "Oxygen", 15.999, [2, 6]),
+    element!(9, "F", "Fluorine", 18.998, [2, 7]),
+    element!(10, "Ne", "Neon", 20.180, [2, 8]),
+    element!(11, "Na", "Sodium", 22.990, [2, 8, 1]),
+    element!(12, "Mg", "Magnesium", 24.305, [2, 8, 2]),
+    element!(13, "Al", "Aluminium", 26.982, [2, 8, 3]),
+    element!(14, "Si", "Silicon", 28.085, [2, 8, 4]),
+    element!(15, "P", "Phosphorus", 30.974, [2, 8, 5]),
+    element!(16, "S", "Sulfur", 32.06, [2, 8, 6]),
+    element!(17, "Cl", "Chlorine", 35.45, [2, 8, 7]),
+    element!(18, "Ar", "Argon", 39.948, [2, 8, 8]),
+    element!(19, "K", "Potassium", 39.098, [2, 8, 8, 1]),
+    element!(20, "Ca", "Calcium", 40.078, [2, 8, 8, 2]),
+    element!(21, "Sc", "Scandium", 44.956, [2, 8, 9, 2]),
+    element!(22, "Ti", "Titanium", 47.867, [2, 8, 10, 2]),
+    element!(23, "V", "Vanadium", 50.942, [2, 8, 11, 2]),
+    element!(24, "Cr", "Chromium", 51.996, [2, 8, 13, 1]),
+    element!(25, "Mn", "Manganese", 54.938, [2, 8, 13, 2]),
+    element!(26, "Fe", "Iron", 55.845, [2, 8, 14, 2]),
+    element!(27, "Co", "Cobalt", 58.933, [2, 8, 15, 2]),
+    element!(28, "Ni", "Nickel", 58.693, [2, 8, 16, 2]),
+    element!(29, "Cu", "Copper", 63.546, [2, 8, 18, 1]),
+    element!(30, "Zn", "Zinc", 65.38, [2, 8, 18, 2]),
+    element!(31, "Ga", "Gallium", 69.723, [2, 8, 18, 3]),
+    element!(32, "Ge", "Germanium", 72.630, [2, 8, 18, 4]),
+    element!(33, "As", "Arsenic", 74.922, [2, 8, 18, 5]),
+    element!(34, "Se", "Selenium", 78.971, [2, 8, 18, 6]),
+    element!(35, "Br", "Bromine", 79.904, [2, 8, 18, 7]),
+    element!(36, "Kr", "Krypton", 83.798, [2, 8, 18, 8]),
+    element!(37, "Rb", "Rubidium", 85.468, [2, 8, 18, 8, 1]),
+    element!(38, "Sr", "Strontium", 87.62, [2, 8, 18, 8, 2]),
+    element!(39, "Y", "Yttrium", 88.906, [2, 8, 18, 9, 2]),
+    element!(40, "Zr", "Zirconium", 91.224, [2, 8, 18, 10, 2]),
+    element!(41, "Nb", "Niobium", 92.906, [2, 8, 18, 12, 1]),
+    element!(42, "Mo", "Molybdenum", 95.95, [2, 8, 18, 13, 1]),
+    element!(43, "Tc", "Technetium", 98.0, [2, 8, 18, 13, 2]),
+    element!(44, "Ru", "Ruthenium", 101.07, [2, 8, 18, 15, 1]),
+    element!(45, "Rh", "Rhodium", 102.91, [2, 8, 18, 16, 1]),
+    element!(46, "Pd", "Palladium", 106.42, [2, 8, 18, 18]),
+    element!(47, "Ag", "Silver", 107.87, [2, 8, 18, 18, 1]),
+    element!(48, "Cd", "Cadmium", 112.41, [2, 8, 18, 18, 2]),
+    element!(49, "In", "Indium", 114.82, [2, 8, 18, 18, 3]),
+    element!(50, "Sn", "Tin", 118.71, [2, 8, 18, 18, 4]),
+    element!(51, "Sb", "Antimony", 121.76, [2, 8, 18, 18, 5]),
+    element!(52, "Te", "Tellurium", 127.60, [2, 8, 18, 18, 6]),
+    element!(53, "I", "Iodine", 126.90, [2, 8, 18, 18, 7]),
+    element!(54, "Xe", "Xenon", 131.29, [2, 8, 18, 18, 8]),
+    element!(55, "Cs", "Caesium", 132.91, [2, 8, 18, 18, 8, 1]),
+    element!(56, "Ba", "Barium", 137.33, [2, 8, 18, 18, 8, 2]),
+    element!(57, "La", "Lanthanum", 138.91, [2, 8, 18, 18, 9, 2]),
+    element!(58, "Ce", "Cerium", 140.12, [2, 8, 18, 19, 9, 2]),
+    element!(59, "Pr", "Praseodymium", 140.91, [2, 8, 18, 21, 8, 2]),
+    element!(60, "Nd", "Neodymium", 144.24, [2, 8, 18, 22, 8, 2]),
+    element!(61, "Pm", "Promethium", 145.0, [2, 8, 18, 23, 8, 2]),
+    element!(62, "Sm", "Samarium", 150.36, [2, 8, 18, 24, 8, 2]),
+    element!(63, "Eu", "Europium", 151.96, [2, 8, 18, 25, 8, 2]),
+    element!(64, "Gd", "Gadolinium", 157.25, [2, 8, 18, 25, 9, 2]),
+    element!(65, "Tb", "Terbium", 158.93, [2, 8, 18, 27, 8, 2]),
+    element!(66, "Dy", "Dysprosium", 162.50, [2, 8, 18, 28, 8, 2]),
+    element!(67, "Ho", "Holmium", 164.93, [2, 8, 18, 29, 8, 2]),
+    element!(68, "Er", "Erbium", 167.26, [2, 8, 18, 30, 8, 2]),
+    element!(69, "Tm", "Thulium", 168.93, [2, 8, 18, 31, 8, 2]),
+    element!(70, "Yb", "Ytterbium", 173.05, [2, 8, 18, 32, 8, 2]),
+    element!(71, "Lu", "Lutetium", 174.97, [2, 8, 18, 32, 9, 2]),
+    element!(72, "Hf", "Hafnium", 178.49, [2, 8, 18, 32, 10, 2]),
+    element!(73, "Ta", "Tantalum", 180.95, [2, 8, 18, 32, 11, 2]),
+    element!(74, "W", "Tungsten", 183.84, [2, 8, 18, 32, 12, 2]),
+    element!(75, "Re", "Rhenium", 186.21, [2, 8, 18, 32, 13, 2]),
+    element!(76, "Os", "Osmium", 190.23, [2, 8, 18, 32, 14, 2]),
+    element!(77, "Ir", "Iridium", 192.22, [2, 8, 18, 32, 15, 2]),
+    element!(78, "Pt", "Platinum", 195.08, [2, 8, 18, 32, 17, 1]),
+    element!(79, "Au", "Gold", 196.97, [2, 8, 18, 32, 18, 1]),
+    element!(80, "Hg", "Mercury", 200.59, [2, 8, 18, 32, 18, 2]),
+    element!(81, "Tl", "Thallium", 204.38, [2, 8, 18, 32, 18, 3]),
+    element!(82, "Pb", "Lead", 207.2, [2, 8, 18, 32, 18, 4]),
+    element!(83, "Bi", "Bismuth", 208.98, [2, 8, 18, 32, 18, 5]),
+    element!(84, "Po", "Polonium", 209.0, [2, 8, 18, 32, 18, 6]),
+    element!(85, "At", "Astatine", 210.0, [2, 8, 18, 32, 18, 7]),
+    element!(86, "Rn", "Radon", 222.0, [2, 8, 18, 32, 18, 8]),
+    element!(87, "Fr", "Francium", 223.0, [2, 8, 18, 32, 18, 8, 1]),
+    element!(88, "Ra", "Radium", 226.0, [2, 8, 18, 32, 18, 8, 2]),
+    element!(89, "Ac", "Actinium", 227.0, [2, 8, 18, 32, 18, 9, 2]),
+    element!(90, "Th", "Thorium", 232.04, [2, 8, 18, 32, 18, 10, 2]),
+    element!(91, "Pa", "Protactinium", 231.04, [2, 8, 18, 32, 20, 9, 2]),
+    element!(92, "U", "Uranium", 238.03, [2, 8, 18, 32, 21, 9, 2]),
+    element!(93, "Np", "Neptunium", 237.0, [2, 8, 18, 32, 22, 9, 2]),
+    element!(94, "Pu", "Plutonium", 244.0, [2, 8, 18, 32, 24, 8, 2]),
+    element!(95, "Am", "Americium", 243.0, [2, 8, 18, 32, 25, 8, 2]),
+    element!(96, "Cm", "Curium", 247.0, [2, 8, 18, 32, 25, 9, 2]),
+    element!(97, "Bk", "Berkelium", 247.0, [2, 8, 18, 32, 27, 8, 2]),
+    element!(98, "Cf", "Californium", 251.0, [2, 8, 18, 32, 28, 8, 2]),
+    element!(99, "Es", "Einsteinium", 252.0, [2, 8, 18, 32, 29, 8, 2]),
+    element!(100, "Fm", "Fermium", 257.0, [2, 8, 18, 32, 30, 8, 2]),
+    element!(101, "Md", "Mendelevium", 258.0, [2, 8, 18, 32, 31, 8, 2]),
+    element!(102, "No", "Nobelium", 259.0, [2, 8, 18, 32, 32, 8, 2]),
+    element!(103, "Lr", "Lawrencium", 266.0, [2, 8, 18, 32, 32, 8, 3]),
+    element!(104, "Rf", "Rutherfordium", 267.0, [2, 8, 18, 32, 32, 10, 2]),
+    element!(105, "Db", "Dubnium", 268.0, [2, 8, 18, 32, 32, 11, 2]),
+    element!(106, "Sg", "Seaborgium", 269.0, [2, 8, 18, 32, 32, 12, 2]),
+    element!(107, "Bh", "Bohrium", 270.0, [2, 8, 18, 32, 32, 13, 2]),
+    element!(108, "Hs", "Hassium", 277.0, [2, 8, 18, 32, 32, 14, 2]),
+    element!(109, "Mt", "Meitnerium", 278.0, [2, 8, 18, 32, 32, 15, 2]),
+    element!(110, "Ds", "Darmstadtium", 281.0, [2, 8, 18, 32, 32, 16, 2]),
+    element!(111, "Rg", "Roentgenium", 282.0, [2, 8, 18, 32, 32, 17, 2]),
+    element!(112, "Cn", "Copernicium", 285.0, [2, 8, 18, 32, 32, 18, 2]),
+    element!(113, "Nh", "Nihonium", 286.0, [2, 8, 18, 32, 32, 18, 3]),
+    element!(114, "Fl", "Flerovium", 289.0, [2, 8, 18, 32, 32, 18, 4]),
+    element!(115, "Mc", "Moscovium", 290.0, [2, 8, 18, 32, 32, 18, 5]),
+    element!(116, "Lv", "Livermorium", 293.0, [2, 8, 18, 32, 32, 18, 6]),
+    element!(117, "Ts", "Tennessine", 294.0, [2, 8, 18, 32, 32, 18, 7]),
+    element!(118, "Og", "Oganesson", 294.0, [2, 8, 18, 32, 32, 18, 8]),
+];
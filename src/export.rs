@@ -0,0 +1,360 @@
+use crate::material::Material as SurfaceMaterial; // bond color
+use crate::render::{generate_cylinder, generate_sphere, generate_torus, Mesh};
+use crate::renderer::bond_models; // same sticks as on screen
+use crate::scene::{EntityId, Scene};
+use nalgebra_glm as glm;
+use std::collections::HashMap;
+use std::fmt; // pretty errors
+use std::fs;
+use std::io::{BufWriter, Write};
+
+// the bohr view as a mesh file, for rendering it properly in blender and friends
+// (Ctrl+E = obj + mtl, Ctrl+Shift+E = gltf + bin)
+// everything is baked into world space as it is right now: every nucleon and electron
+// its own sphere, bonds as cylinders, and a thin torus along every occupied visible shell
+// objects share a material per role (proton, neutron, electron, shell_<n>, bond)
+// colors only, no lighting or bloom, the renderer on the other side does that
+
+// finer than on screen, nobody is counting triangles offline
+const SPHERE_SECTORS: i32 = 32;
+const SPHERE_STACKS: i32 = 20;
+const BOND_SECTORS: i32 = 24;
+const RING_TUBE: f32 = 0.03; // ring thickness in world units
+const RING_SECTORS: i32 = 128;
+const RING_SIDES: i32 = 8;
+
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Obj,
+    Gltf,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Obj => "obj",
+            ExportFormat::Gltf => "gltf",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io { path: String, message: String },
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::Io { path, message } => {
+                write!(f, "could not write export '{}': {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+struct Material {
+    name: String,
+    color: glm::Vec4,
+}
+
+struct Object {
+    name: String,
+    material: usize, // into Geometry::materials
+    positions: Vec<glm::Vec3>,
+    normals: Vec<glm::Vec3>,
+    indices: Vec<u32>,
+}
+
+#[derive(Default)]
+struct Geometry {
+    materials: Vec<Material>,
+    by_name: HashMap<String, usize>,
+    objects: Vec<Object>,
+}
+
+impl Geometry {
+    fn material(&mut self, name: &str, color: glm::Vec4) -> usize {
+        // first color seen for a role wins, they are all the same anyway
+        if let Some(&index) = self.by_name.get(name) {
+            return index;
+        }
+        self.materials.push(Material {
+            name: name.to_string(),
+            color,
+        });
+        self.by_name
+            .insert(name.to_string(), self.materials.len() - 1);
+        self.materials.len() - 1
+    }
+
+    fn add(&mut self, name: String, material: usize, mesh: &Mesh, model: &glm::Mat4) {
+        // copy of the mesh moved into world space
+        // normals only see the rotation, none of the exported transforms shear
+        let rotation = glm::mat4_to_mat3(model);
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        for vertex in mesh.vertices.chunks(6) {
+            let p = model * glm::vec4(vertex[0], vertex[1], vertex[2], 1.0);
+            positions.push(p.xyz());
+            normals.push(glm::normalize(
+                &(rotation * glm::vec3(vertex[3], vertex[4], vertex[5])),
+            ));
+        }
+        self.objects.push(Object {
+            name,
+            material,
+            positions,
+            normals,
+            indices: mesh.indices.clone(),
+        });
+    }
+}
+
+fn collect(scene: &Scene) -> Geometry {
+    let mut geometry = Geometry::default();
+    let sphere = generate_sphere(1.0, SPHERE_SECTORS, SPHERE_STACKS);
+    let display = &scene.display;
+
+    // the same spheres the renderer draws, so hidden shells stay hidden here too
+    let world = &scene.world;
+    let mut jumping = 0;
+    for entity in world.spheres() {
+        let (name, role) = match world.pick_id(entity.entity) {
+            Some(EntityId::Nucleon { atom, index }) => {
+                let proton = world
+                    .nucleons
+                    .get(entity.entity)
+                    .is_some_and(|nucleon| nucleon.proton);
+                let role = if proton { "proton" } else { "neutron" };
+                (format!("atom{}_{}_{}", atom + 1, role, index), role)
+            }
+            Some(EntityId::Electron { atom, shell, index }) => (
+                format!("atom{}_electron_n{}_{}", atom + 1, shell + 1, index),
+                "electron",
+            ),
+            None => {
+                // between two shells right now, not attached to either
+                jumping += 1;
+                (format!("electron_jumping_{}", jumping), "electron")
+            }
+        };
+        let rgb = entity.material.albedo;
+        let material = geometry.material(role, glm::vec4(rgb.x, rgb.y, rgb.z, 1.0));
+        geometry.add(name, material, &sphere, &entity.model);
+    }
+
+    for (atom_index, scene_atom) in scene.atoms.iter().enumerate() {
+        for shell in &scene_atom.atom.shells {
+            if shell.electron_count() == 0 || !display.shell_visible(shell.n) {
+                continue;
+            }
+            let ring = generate_torus(shell.radius as f32, RING_TUBE, RING_SECTORS, RING_SIDES);
+            let model = scene_atom.model() * shell.plane_rotation();
+            let c = display.theme.trail(shell.n);
+            let material =
+                geometry.material(&format!("shell_{}", shell.n), glm::vec4(c.x, c.y, c.z, 1.0));
+            let name = format!("atom{}_shell_{}", atom_index + 1, shell.n);
+            geometry.add(name, material, &ring, &model);
+        }
+    }
+
+    let cylinder = generate_cylinder(1.0, 1.0, BOND_SECTORS);
+    for bond in &scene.bonds {
+        let (a, b) = (&scene.atoms[bond.a].position, &scene.atoms[bond.b].position);
+        for (stick, model) in bond_models(a, b, bond.order).iter().enumerate() {
+            let rgb = SurfaceMaterial::bond().albedo;
+            let material = geometry.material("bond", glm::vec4(rgb.x, rgb.y, rgb.z, 1.0));
+            let name = format!("bond_{}_{}_{}", bond.a + 1, bond.b + 1, stick + 1);
+            geometry.add(name, material, &cylinder, model);
+        }
+    }
+
+    geometry
+}
+
+pub fn export(scene: &Scene, format: ExportFormat) -> Result<String, ExportError> {
+    // writes atom_<stamp>.<ext> (+ its .mtl / .bin) and returns the main file's name
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let base = format!("atom_{}", stamp);
+    let geometry = collect(scene);
+    log::debug!(
+        "exporting {} objects with {} materials",
+        geometry.objects.len(),
+        geometry.materials.len()
+    );
+
+    let path = format!("{}.{}", base, format.extension());
+    let io_error = |path: &str, err: std::io::Error| ExportError::Io {
+        path: path.to_string(),
+        message: err.to_string(),
+    };
+    match format {
+        ExportFormat::Obj => {
+            let mtl = format!("{}.mtl", base);
+            write_obj(&geometry, &path, &mtl).map_err(|err| io_error(&path, err))?;
+            write_mtl(&geometry, &mtl).map_err(|err| io_error(&mtl, err))?;
+        }
+        ExportFormat::Gltf => {
+            let bin = format!("{}.bin", base);
+            let buffer = gltf_buffer(&geometry);
+            fs::write(&bin, &buffer).map_err(|err| io_error(&bin, err))?;
+            fs::write(&path, gltf_json(&geometry, &bin, buffer.len()))
+                .map_err(|err| io_error(&path, err))?;
+        }
+    }
+    Ok(path)
+}
+
+fn write_obj(geometry: &Geometry, path: &str, mtl: &str) -> std::io::Result<()> {
+    // one `o` per object, indices are global and 1-based in obj
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    writeln!(out, "# atom simulator export")?;
+    writeln!(out, "mtllib {}", mtl)?;
+    let mut offset = 1;
+    for object in &geometry.objects {
+        writeln!(out, "o {}", object.name)?;
+        writeln!(out, "usemtl {}", geometry.materials[object.material].name)?;
+        for p in &object.positions {
+            writeln!(out, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        for n in &object.normals {
+            writeln!(out, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+        for triangle in object.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] + offset);
+            writeln!(out, "f {a}//{a} {b}//{b} {c}//{c}")?;
+        }
+        offset += object.positions.len() as u32;
+    }
+    out.flush()
+}
+
+fn write_mtl(geometry: &Geometry, path: &str) -> std::io::Result<()> {
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    for material in &geometry.materials {
+        let c = material.color;
+        writeln!(out, "newmtl {}", material.name)?;
+        writeln!(out, "Kd {} {} {}", c.x, c.y, c.z)?;
+        writeln!(out, "Ks 0.3 0.3 0.3")?;
+        writeln!(out, "Ns 32")?;
+        writeln!(out, "d {}", c.w)?;
+    }
+    out.flush()
+}
+
+fn gltf_buffer(geometry: &Geometry) -> Vec<u8> {
+    // per object: positions, normals, indices, back to back, little endian as gltf wants
+    // every block is a multiple of 4 bytes, so no padding is needed between them
+    let mut buffer = Vec::new();
+    for object in &geometry.objects {
+        for v in object.positions.iter().chain(&object.normals) {
+            for component in [v.x, v.y, v.z] {
+                buffer.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+        for index in &object.indices {
+            buffer.extend_from_slice(&index.to_le_bytes());
+        }
+    }
+    buffer
+}
+
+fn gltf_json(geometry: &Geometry, bin: &str, byte_length: usize) -> String {
+    // three buffer views + accessors per object, in the same order gltf_buffer wrote them
+    // one mesh and one node per object so they arrive as separate, named objects
+    let mut views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut meshes = Vec::new();
+    let mut nodes = Vec::new();
+    let mut offset = 0;
+    for (i, object) in geometry.objects.iter().enumerate() {
+        let vertex_bytes = object.positions.len() * 12;
+        let index_bytes = object.indices.len() * 4;
+        for (length, target) in [
+            (vertex_bytes, 34962), // ARRAY_BUFFER
+            (vertex_bytes, 34962),
+            (index_bytes, 34963), // ELEMENT_ARRAY_BUFFER
+        ] {
+            views.push(format!(
+                r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+                offset, length, target
+            ));
+            offset += length;
+        }
+
+        let (min, max) = bounds(&object.positions);
+        let count = object.positions.len();
+        accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
+            3 * i,
+            count,
+            min.x,
+            min.y,
+            min.z,
+            max.x,
+            max.y,
+            max.z
+        ));
+        accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC3"}}"#,
+            3 * i + 1,
+            count
+        ));
+        accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":5125,"count":{},"type":"SCALAR"}}"#,
+            3 * i + 2,
+            object.indices.len()
+        ));
+        meshes.push(format!(
+            r#"{{"name":"{}","primitives":[{{"attributes":{{"POSITION":{},"NORMAL":{}}},"indices":{},"material":{}}}]}}"#,
+            object.name,
+            3 * i,
+            3 * i + 1,
+            3 * i + 2,
+            object.material
+        ));
+        nodes.push(format!(r#"{{"name":"{}","mesh":{}}}"#, object.name, i));
+    }
+
+    let materials: Vec<String> = geometry
+        .materials
+        .iter()
+        .map(|material| {
+            let c = material.color;
+            format!(
+                r#"{{"name":"{}","pbrMetallicRoughness":{{"baseColorFactor":[{},{},{},{}],"metallicFactor":0.0,"roughnessFactor":0.5}}}}"#,
+                material.name, c.x, c.y, c.z, c.w
+            )
+        })
+        .collect();
+    let scene_nodes: Vec<String> = (0..nodes.len()).map(|i| i.to_string()).collect();
+
+    format!(
+        r#"{{"asset":{{"version":"2.0","generator":"atom-sim"}},"scene":0,"scenes":[{{"nodes":[{}]}}],"nodes":[{}],"meshes":[{}],"materials":[{}],"accessors":[{}],"bufferViews":[{}],"buffers":[{{"uri":"{}","byteLength":{}}}]}}"#,
+        scene_nodes.join(","),
+        nodes.join(","),
+        meshes.join(","),
+        materials.join(","),
+        accessors.join(","),
+        views.join(","),
+        bin,
+        byte_length
+    )
+}
+
+fn bounds(points: &[glm::Vec3]) -> (glm::Vec3, glm::Vec3) {
+    // gltf insists on min / max for positions
+    let mut min = glm::vec3(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = glm::vec3(f32::MIN, f32::MIN, f32::MIN);
+    for p in points {
+        min = glm::min2(&min, p);
+        max = glm::max2(&max, p);
+    }
+    (min, max)
+}
@@ -0,0 +1,366 @@
+use crate::nucleus; // where the lines start
+use crate::render::{srgb_to_linear, ShaderError, ShaderProgram, WatchedProgram};
+use crate::scale; // the nucleus shrinks in true scale mode
+use crate::scene::Scene;
+use crate::simulation::Atom;
+use crate::stats;
+use nalgebra_glm as glm;
+use std::mem; // buffer sizes
+use std::os::raw::c_void; // raw pointer casting
+
+// field view (Shift+F, on the selected atom or the main one): the electrostatic field of the
+// nucleus and the electrons, each atom on its own, so one can be looked at next to another
+// the charges are what the bohr view draws: +Z on the nucleus, -1 on every electron where it
+// is right now, worked out again every frame on the cpu as they go round
+// lines: field lines from the nucleus, traced along E until they end on an electron (or leave
+//        the atom, which only an ion's do); one per proton, so every electron gets about one
+// slice: the potential on a plane through the nucleus facing the camera, warm where the
+//        nucleus wins, cool next to the electrons, fading out where it is close to zero
+// units are the drawing's, e = 1 and lengths in world units, the colors only say where it
+// is strong and which sign it has
+
+const SOFTENING: f32 = 0.2; // keeps 1/r finite on top of a charge, in world units
+
+// lines
+const MIN_LINES: u32 = 12; // hydrogen would get a single line otherwise
+const MAX_LINES: u32 = 60;
+const MIN_STEP: f32 = 0.03; // steps shrink near an electron so a line lands on it
+const MAX_STEP: f32 = 0.5;
+const MAX_STEPS: usize = 1500;
+const CAPTURE: f32 = 0.15; // this close to an electron and the line ends on it
+const REACH: f32 = 6.0; // past the outermost shell, where an ion's lines are cut off
+const LINE_COLOR: glm::Vec3 = glm::Vec3::new(1.0, 0.95, 0.6);
+const LINE_OPACITY: f32 = 0.6;
+
+// slice
+const SLICE_RESOLUTION: usize = 96;
+const SLICE_MARGIN: f32 = 3.0; // past the outermost shell on every side
+const SLICE_OPACITY: f32 = 0.7; // where the potential is strongest
+const SLICE_KNEE: f32 = 0.2; // potential where the log color scale bends, see potential_image
+const POSITIVE_COLOR: [f32; 3] = [1.0, 0.45, 0.2]; // same as the ion glows
+const NEGATIVE_COLOR: [f32; 3] = [0.3, 0.6, 1.0];
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum FieldView {
+    #[default]
+    Off,
+    Lines,
+    Slice,
+}
+
+impl FieldView {
+    pub fn next(self) -> Self {
+        match self {
+            FieldView::Off => FieldView::Lines,
+            FieldView::Lines => FieldView::Slice,
+            FieldView::Slice => FieldView::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FieldView::Off => "off",
+            FieldView::Lines => "field lines",
+            FieldView::Slice => "potential slice",
+        }
+    }
+}
+
+struct Charges {
+    // one atom's, atom space
+    nucleus: f32,
+    electrons: Vec<glm::Vec3>,
+}
+
+impl Charges {
+    fn of(atom: &Atom) -> Self {
+        Self {
+            nucleus: atom.protons as f32,
+            electrons: atom.electron_positions(),
+        }
+    }
+
+    fn potential(&self, p: &glm::Vec3) -> f32 {
+        let term = |charge: f32, at: &glm::Vec3| {
+            charge / (glm::distance2(p, at) + SOFTENING * SOFTENING).sqrt()
+        };
+        let origin = glm::vec3(0.0, 0.0, 0.0);
+        term(self.nucleus, &origin)
+            - self
+                .electrons
+                .iter()
+                .map(|electron| term(1.0, electron))
+                .sum::<f32>()
+    }
+
+    fn field(&self, p: &glm::Vec3) -> (glm::Vec3, Option<(usize, f32)>) {
+        // E at p, and the nearest electron with its distance
+        let term = |charge: f32, at: &glm::Vec3| {
+            let d = p - at;
+            let r2 = glm::length2(&d) + SOFTENING * SOFTENING;
+            d * (charge / (r2 * r2.sqrt()))
+        };
+        let mut e = term(self.nucleus, &glm::vec3(0.0, 0.0, 0.0));
+        let mut nearest: Option<(usize, f32)> = None;
+        for (i, electron) in self.electrons.iter().enumerate() {
+            e -= term(1.0, electron);
+            let distance = glm::distance(p, electron);
+            if nearest.is_none_or(|(_, closest)| distance < closest) {
+                nearest = Some((i, distance));
+            }
+        }
+        (e, nearest)
+    }
+}
+
+pub fn field_lines(atom: &Atom, true_scale: f32) -> Vec<Vec<glm::Vec3>> {
+    // atom space polylines, each from the surface of the nucleus to wherever it ends
+    let charges = Charges::of(atom);
+    let start =
+        nucleus::radius(atom.protons + atom.neutrons) * scale::nucleus_factor(atom, true_scale);
+    let reach = atom.outer_radius() + REACH;
+    let count = atom.protons.clamp(MIN_LINES, MAX_LINES) as usize;
+    (0..count)
+        .map(|i| {
+            let mut p = sphere_point(i, count) * start;
+            let mut line = vec![p];
+            for _ in 0..MAX_STEPS {
+                let (e, nearest) = charges.field(&p);
+                if let Some((electron, distance)) = nearest {
+                    if distance < CAPTURE {
+                        line.push(charges.electrons[electron]);
+                        break;
+                    }
+                }
+                if glm::length(&p) > reach || glm::length2(&e) < 1e-12 {
+                    break;
+                }
+                // midpoint step along the field direction, short when an electron is near
+                let h = nearest.map_or(MAX_STEP, |(_, distance)| {
+                    (0.3 * distance).clamp(MIN_STEP, MAX_STEP)
+                });
+                let middle = p + glm::normalize(&e) * (0.5 * h);
+                let (e_middle, _) = charges.field(&middle);
+                if glm::length2(&e_middle) < 1e-12 {
+                    break;
+                }
+                p += glm::normalize(&e_middle) * h;
+                line.push(p);
+            }
+            line
+        })
+        .collect()
+}
+
+fn sphere_point(i: usize, count: usize) -> glm::Vec3 {
+    // fibonacci sphere, count evenly spread unit directions
+    let golden = std::f32::consts::PI * (3.0 - 5f32.sqrt());
+    let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+    let r = (1.0 - y * y).max(0.0).sqrt();
+    let angle = golden * i as f32;
+    glm::vec3(r * angle.cos(), y, r * angle.sin())
+}
+
+fn potential_image(charges: &Charges, right: &glm::Vec3, up: &glm::Vec3, half: f32) -> Vec<u8> {
+    // rgba rows, bottom row first like gl wants it; srgb colors, alpha straight
+    let along = |i: usize| ((i as f32 + 0.5) / SLICE_RESOLUTION as f32 * 2.0 - 1.0) * half;
+    let mut values = Vec::with_capacity(SLICE_RESOLUTION * SLICE_RESOLUTION);
+    for row in 0..SLICE_RESOLUTION {
+        for column in 0..SLICE_RESOLUTION {
+            values.push(charges.potential(&(right * along(column) + up * along(row))));
+        }
+    }
+    // log scale against the strongest point: the nucleus is hundreds of times stronger than
+    // anything out at the shells, linear would show a dot in the middle and nothing else
+    let strongest = values.iter().map(|v| v.abs()).fold(0.0, f32::max);
+    let full = (1.0 + strongest / SLICE_KNEE).ln().max(1e-6);
+    let mut pixels = Vec::with_capacity(values.len() * 4);
+    for value in values {
+        let strength = (1.0 + value.abs() / SLICE_KNEE).ln() / full;
+        let color = if value >= 0.0 {
+            POSITIVE_COLOR
+        } else {
+            NEGATIVE_COLOR
+        };
+        pixels.extend(color.iter().map(|c| (c * 255.0) as u8));
+        pixels.push((strength * SLICE_OPACITY * 255.0) as u8);
+    }
+    pixels
+}
+
+pub struct FieldRenderer {
+    lines_program: WatchedProgram, // single color lines, the gizmo shader
+    slice_program: WatchedProgram,
+    vao: gl::types::GLuint, // line segments, refilled every frame
+    vbo: gl::types::GLuint,
+    empty_vao: gl::types::GLuint, // the slice quad comes from gl_VertexID
+    texture: gl::types::GLuint,   // refilled for every atom that shows its slice
+}
+
+impl FieldRenderer {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut vao = 0;
+        let mut vbo = 0;
+        gl::GenVertexArrays(1, &mut vao);
+        gl::GenBuffers(1, &mut vbo);
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        gl::VertexAttribPointer(
+            0,
+            3,
+            gl::FLOAT,
+            gl::FALSE,
+            (3 * mem::size_of::<f32>()) as i32,
+            std::ptr::null(),
+        );
+        gl::EnableVertexAttribArray(0);
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl::BindVertexArray(0);
+
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        let mut texture = 0;
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+
+        Ok(Self {
+            lines_program: WatchedProgram::new("gizmo", ShaderProgram::gizmo)?,
+            slice_program: WatchedProgram::new("field", ShaderProgram::field)?,
+            vao,
+            vbo,
+            empty_vao,
+            texture,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.lines_program.poll();
+        self.slice_program.poll();
+    }
+
+    pub unsafe fn draw(&self, scene: &Scene, view: &glm::Mat4, projection: &glm::Mat4) {
+        // blended and without depth writes like the shell rings, the atom stays visible
+        // through its own field and the slice cuts it in two
+        let shown = || {
+            scene
+                .atoms
+                .iter()
+                .filter(|scene_atom| scene_atom.field != FieldView::Off)
+        };
+        if shown().next().is_none() {
+            return;
+        }
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::DepthMask(gl::FALSE);
+
+        // every atom's lines in one buffer, as separate segments so they draw in one call
+        let mut segments: Vec<f32> = Vec::new();
+        for scene_atom in shown().filter(|scene_atom| scene_atom.field == FieldView::Lines) {
+            for line in field_lines(&scene_atom.atom, scene.display.true_scale) {
+                for pair in line.windows(2) {
+                    for p in pair {
+                        let world = scene_atom.to_world(p);
+                        segments.extend_from_slice(&[world.x, world.y, world.z]);
+                    }
+                }
+            }
+        }
+        if !segments.is_empty() {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                mem::size_of_val(segments.as_slice()) as isize,
+                segments.as_ptr() as *const c_void,
+                gl::STREAM_DRAW,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            // drawn inside the linear scene
+            let c = srgb_to_linear(&LINE_COLOR);
+            self.lines_program.use_program();
+            self.lines_program
+                .set_uniform_mat4("model", &glm::identity());
+            self.lines_program.set_uniform_mat4("view", view);
+            self.lines_program
+                .set_uniform_mat4("projection", projection);
+            self.lines_program
+                .set_uniform_vec4("color", &glm::vec4(c.x, c.y, c.z, LINE_OPACITY));
+            gl::BindVertexArray(self.vao);
+            let vertices = (segments.len() / 3) as i32;
+            stats::count(gl::LINES, vertices, 1);
+            gl::DrawArrays(gl::LINES, 0, vertices);
+            gl::BindVertexArray(0);
+        }
+
+        // one quad per atom through its nucleus, turned to the camera
+        let eye = scene.camera.get_position();
+        self.slice_program.use_program();
+        self.slice_program.set_uniform_i32("potential", 0);
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, self.texture);
+        gl::BindVertexArray(self.empty_vao);
+        for scene_atom in shown().filter(|scene_atom| scene_atom.field == FieldView::Slice) {
+            let toward = eye - scene_atom.position;
+            if glm::length(&toward) < 1e-4 {
+                continue;
+            }
+            let normal = glm::normalize(&toward);
+            let world_up = if normal.y.abs() > 0.99 {
+                glm::vec3(0.0, 0.0, 1.0)
+            } else {
+                glm::vec3(0.0, 1.0, 0.0)
+            };
+            let right = glm::normalize(&glm::cross(&world_up, &normal));
+            let up = glm::cross(&normal, &right);
+            let half = scene_atom.atom.outer_radius() + SLICE_MARGIN;
+
+            // the charges sit in atom space, so the plane is turned back into it
+            let inverse = glm::quat_inverse(&scene_atom.orientation);
+            let pixels = potential_image(
+                &Charges::of(&scene_atom.atom),
+                &glm::quat_rotate_vec3(&inverse, &right),
+                &glm::quat_rotate_vec3(&inverse, &up),
+                half,
+            );
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::SRGB8_ALPHA8 as i32, // srgb colors, read back linear like the rest of the scene
+                SLICE_RESOLUTION as i32,
+                SLICE_RESOLUTION as i32,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const _,
+            );
+            self.slice_program
+                .set_uniform_vec3("center", &scene_atom.position);
+            self.slice_program
+                .set_uniform_vec3("right", &(right * half));
+            self.slice_program.set_uniform_vec3("up", &(up * half));
+            stats::count(gl::TRIANGLE_STRIP, 4, 1);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+        }
+        gl::BindVertexArray(0);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+
+        gl::DepthMask(gl::TRUE);
+        gl::Disable(gl::BLEND);
+    }
+}
+
+impl Drop for FieldRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
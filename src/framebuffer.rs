@@ -0,0 +1,276 @@
+// render target we own: fbo + color texture + optional depth renderbuffer
+// draw into it instead of the window, then sample the color texture in a later pass
+// (fxaa, headless frames, anything post-processing)
+// passes that read depth too (ambient occlusion) get a depth texture instead of the
+// renderbuffer, with_depth_texture; a shadow map is nothing but that texture, depth_only
+// attachments are (re)allocated lazily by resize, so creating one is cheap and
+// callers just resize to the frame size every frame
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorFormat {
+    Srgb8,   // 8 bit srgb encoded like the screen, reads decode to linear, writes encode
+    Rgba16F, // half float, keeps brightness above 1.0 around for bloom
+    R8,      // one 8 bit channel, a factor per pixel (ambient occlusion)
+}
+
+impl ColorFormat {
+    fn internal(self) -> gl::types::GLenum {
+        match self {
+            ColorFormat::Srgb8 => gl::SRGB8_ALPHA8,
+            ColorFormat::Rgba16F => gl::RGBA16F,
+            ColorFormat::R8 => gl::R8,
+        }
+    }
+
+    fn pixel_type(self) -> gl::types::GLenum {
+        match self {
+            ColorFormat::Srgb8 => gl::UNSIGNED_BYTE,
+            ColorFormat::Rgba16F => gl::HALF_FLOAT,
+            ColorFormat::R8 => gl::UNSIGNED_BYTE,
+        }
+    }
+}
+
+pub struct Framebuffer {
+    fbo: gl::types::GLuint,
+    color_texture: gl::types::GLuint,
+    depth_rb: gl::types::GLuint,      // 0 when made without depth
+    depth_texture: gl::types::GLuint, // 0 unless made with_depth_texture
+    format: ColorFormat,
+    depth: bool,
+    sampled_depth: bool, // depth in a texture a later pass can read
+    color: bool,         // false for depth_only, no color attachment at all
+    size: (u32, u32),    // current attachment size, (0, 0) = nothing allocated
+}
+
+impl Framebuffer {
+    pub unsafe fn new(format: ColorFormat, depth: bool) -> Self {
+        // depth is only needed when 3d geometry is drawn into it, not for fullscreen passes
+        let mut fbo = 0;
+        gl::GenFramebuffers(1, &mut fbo);
+        Self {
+            fbo,
+            color_texture: 0,
+            depth_rb: 0,
+            depth_texture: 0,
+            format,
+            depth,
+            sampled_depth: false,
+            color: true,
+            size: (0, 0),
+        }
+    }
+
+    pub unsafe fn with_depth_texture(format: ColorFormat) -> Self {
+        // depth as a texture instead of a renderbuffer, see bind_depth_texture
+        let mut framebuffer = Self::new(format, true);
+        framebuffer.sampled_depth = true;
+        framebuffer
+    }
+
+    pub unsafe fn depth_only() -> Self {
+        // only the depth texture, set up for sampler2DShadow lookups: comparing against it
+        // gives 1 where lit, and outside of it everything is lit
+        let mut framebuffer = Self::with_depth_texture(ColorFormat::R8);
+        framebuffer.color = false;
+        framebuffer
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub unsafe fn resize(&mut self, width: u32, height: u32) {
+        // (re)allocate attachments, only when the size actually changed
+        let (width, height) = (width.max(1), height.max(1));
+        if self.size == (width, height) {
+            return;
+        }
+        self.delete_attachments();
+        // resizing should not change what is being drawn into
+        let previous = current_binding();
+
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+        if self.color {
+            gl::GenTextures(1, &mut self.color_texture);
+            gl::BindTexture(gl::TEXTURE_2D, self.color_texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                self.format.internal() as i32,
+                width as i32,
+                height as i32,
+                0,
+                gl::RGBA,
+                self.format.pixel_type(),
+                std::ptr::null(),
+            );
+            // linear so passes can sample between texels (fxaa, half resolution blur)
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                self.color_texture,
+                0,
+            );
+        } else {
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+        }
+
+        if self.sampled_depth {
+            gl::GenTextures(1, &mut self.depth_texture);
+            gl::BindTexture(gl::TEXTURE_2D, self.depth_texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::DEPTH_COMPONENT24 as i32,
+                width as i32,
+                height as i32,
+                0,
+                gl::DEPTH_COMPONENT,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+            if self.color {
+                // nearest, depth in between two surfaces is a surface that is not there
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            } else {
+                // compared, not read: linear then blends the four nearest comparisons,
+                // which is already a little pcf for free
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_COMPARE_MODE,
+                    gl::COMPARE_REF_TO_TEXTURE as i32,
+                );
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_COMPARE_FUNC, gl::LEQUAL as i32);
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_WRAP_S,
+                    gl::CLAMP_TO_BORDER as i32,
+                );
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_WRAP_T,
+                    gl::CLAMP_TO_BORDER as i32,
+                );
+                let far = [1.0f32; 4];
+                gl::TexParameterfv(gl::TEXTURE_2D, gl::TEXTURE_BORDER_COLOR, far.as_ptr());
+            }
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_2D,
+                self.depth_texture,
+                0,
+            );
+        } else if self.depth {
+            gl::GenRenderbuffers(1, &mut self.depth_rb);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, self.depth_rb);
+            gl::RenderbufferStorage(
+                gl::RENDERBUFFER,
+                gl::DEPTH_COMPONENT24,
+                width as i32,
+                height as i32,
+            );
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::RENDERBUFFER,
+                self.depth_rb,
+            );
+        }
+
+        let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            panic!(
+                "framebuffer {}x{} is incomplete (0x{:x})",
+                width, height, status
+            );
+        }
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous);
+        check_gl_error!("framebuffer resize");
+
+        self.size = (width, height);
+    }
+
+    pub unsafe fn bind(&self) {
+        // draw into this one from now on, viewport covering all of it
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+        gl::Viewport(0, 0, self.size.0 as i32, self.size.1 as i32);
+    }
+
+    pub unsafe fn bind_texture(&self, unit: u32) {
+        // color attachment on texture unit `unit`, for a pass that reads it
+        gl::ActiveTexture(gl::TEXTURE0 + unit);
+        gl::BindTexture(gl::TEXTURE_2D, self.color_texture);
+    }
+
+    pub unsafe fn bind_depth_texture(&self, unit: u32) {
+        // depth attachment the same way, 0..1 window depth in the red channel
+        gl::ActiveTexture(gl::TEXTURE0 + unit);
+        gl::BindTexture(gl::TEXTURE_2D, self.depth_texture);
+    }
+
+    unsafe fn delete_attachments(&mut self) {
+        if self.color_texture != 0 {
+            gl::DeleteTextures(1, &self.color_texture);
+            self.color_texture = 0;
+        }
+        if self.depth_rb != 0 {
+            gl::DeleteRenderbuffers(1, &self.depth_rb);
+            self.depth_rb = 0;
+        }
+        if self.depth_texture != 0 {
+            gl::DeleteTextures(1, &self.depth_texture);
+            self.depth_texture = 0;
+        }
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.delete_attachments();
+            gl::DeleteFramebuffers(1, &self.fbo);
+        }
+    }
+}
+
+pub unsafe fn is_srgb() -> bool {
+    // does the currently bound draw framebuffer encode linear colors to srgb on write?
+    // (with GL_FRAMEBUFFER_SRGB on) the window asks for it, but the hint is only a wish
+    let attachment = if current_binding() == 0 {
+        gl::BACK_LEFT
+    } else {
+        gl::COLOR_ATTACHMENT0
+    };
+    let mut encoding = 0;
+    gl::GetFramebufferAttachmentParameteriv(
+        gl::DRAW_FRAMEBUFFER,
+        attachment,
+        gl::FRAMEBUFFER_ATTACHMENT_COLOR_ENCODING,
+        &mut encoding,
+    );
+    encoding as gl::types::GLenum == gl::SRGB
+}
+
+pub unsafe fn current_binding() -> gl::types::GLuint {
+    // whatever is bound for drawing right now (window = 0, or someone's fbo)
+    // passes remember it and write their result back there
+    let mut target = 0;
+    gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut target);
+    target as gl::types::GLuint
+}
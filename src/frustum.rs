@@ -0,0 +1,37 @@
+use nalgebra_glm as glm;
+
+// what the camera can see, as six planes, for dropping things that are off screen before
+// they become instances
+// zoomed into a big molecule most atoms are behind or beside the camera, and every one
+// of them used to be pushed, uploaded and run through the vertex shader for nothing
+// everything in the bohr view has a bounding sphere (spheres are one already), so the
+// only test needed is sphere against plane
+
+pub struct Frustum {
+    // a point p is inside when dot(plane, (p, 1)) >= 0 for every plane
+    // normalized, so that dot is a distance in world units
+    planes: [glm::Vec4; 6],
+}
+
+impl Frustum {
+    pub fn new(view_projection: &glm::Mat4) -> Self {
+        // gribb / hartmann: clip space is -w <= x, y, z <= w, so each plane is the
+        // matrix's w row plus or minus one of the others
+        // works for orthographic projections too, their w row is just (0, 0, 0, 1)
+        let row = |i: usize| view_projection.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z]
+            .map(|plane| plane / glm::length(&plane.xyz()));
+        Self { planes }
+    }
+
+    pub fn contains_sphere(&self, center: &glm::Vec3, radius: f32) -> bool {
+        // false only if the sphere is completely outside one of the planes
+        // a sphere off a corner of the frustum can pass without being on screen, it is
+        // drawn for nothing, never dropped by mistake
+        let point = glm::vec4(center.x, center.y, center.z, 1.0);
+        self.planes
+            .iter()
+            .all(|plane| glm::dot(plane, &point) >= -radius)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::framebuffer::{self, ColorFormat, Framebuffer};
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::stats;
+use nalgebra_glm as glm;
+
+// fallback antialiasing for when the framebuffer has no multisampling
+// (driver refused the msaa hint, or we are rendering into a plain offscreen fbo)
+// draw the frame into our own texture, then one fullscreen pass smooths the edges
+// cheaper and blurrier than msaa, but a lot better than jagged spheres
+
+pub struct Fxaa {
+    program: WatchedProgram,
+    empty_vao: gl::types::GLuint, // core profile refuses to draw without a vao, even an empty one
+    target: Framebuffer,
+    previous: gl::types::GLuint, // framebuffer to resolve into, remembered in begin()
+}
+
+impl Fxaa {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let program = WatchedProgram::new("fxaa", ShaderProgram::fxaa)?;
+
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+
+        Ok(Self {
+            program,
+            empty_vao,
+            target: Framebuffer::new(ColorFormat::Srgb8, true),
+            previous: 0,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn begin(&mut self, width: u32, height: u32) {
+        // redirect drawing into our texture
+        // whatever was bound before (window or offscreen fbo) gets the result in finish()
+        self.previous = framebuffer::current_binding();
+        self.target.resize(width, height);
+        self.target.bind();
+    }
+
+    pub unsafe fn finish(&mut self) {
+        // resolve: fullscreen triangle sampling our texture, written to the original target
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous);
+        gl::Disable(gl::DEPTH_TEST);
+
+        let (width, height) = self.target.size();
+        self.program.use_program();
+        self.program.set_uniform_i32("screen", 0);
+        self.program.set_uniform_vec2(
+            "texelSize",
+            &glm::vec2(1.0 / width as f32, 1.0 / height as f32),
+        );
+
+        self.target.bind_texture(0);
+        gl::BindVertexArray(self.empty_vao);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::BindVertexArray(0);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+
+        gl::Enable(gl::DEPTH_TEST);
+    }
+}
+
+impl Drop for Fxaa {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
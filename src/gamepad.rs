@@ -0,0 +1,156 @@
+use crate::camera::Camera; // sticks and triggers move it directly
+use crate::input::{Action, Bindings, PadInput}; // what each control is bound to
+use glfw::{GamepadAxis, GamepadState, JoystickId};
+
+// gamepad / controller input through glfw's gamepad api
+// glfw maps every controller it has a mapping for onto an xbox layout and hands out the
+// state on request, there are no events, so the pad is read once a frame
+// what each control does comes from the [gamepad] table of bindings.toml (see input.rs):
+// buttons fire their action once per press like a key, sticks orbit and pan the camera,
+// triggers zoom for as long as they are held
+// the first gamepad found is used, and plugging one in while running works
+
+const JOYSTICKS: [JoystickId; 16] = [
+    JoystickId::Joystick1,
+    JoystickId::Joystick2,
+    JoystickId::Joystick3,
+    JoystickId::Joystick4,
+    JoystickId::Joystick5,
+    JoystickId::Joystick6,
+    JoystickId::Joystick7,
+    JoystickId::Joystick8,
+    JoystickId::Joystick9,
+    JoystickId::Joystick10,
+    JoystickId::Joystick11,
+    JoystickId::Joystick12,
+    JoystickId::Joystick13,
+    JoystickId::Joystick14,
+    JoystickId::Joystick15,
+    JoystickId::Joystick16,
+];
+
+const DEADZONE: f32 = 0.15; // sticks never quite come back to 0, worn ones even less
+const STICK_PIXELS: f32 = 600.0; // per second at full tilt, as if the mouse dragged that far
+const ZOOM_NOTCHES: f32 = 10.0; // scroll notches per second with a trigger all the way in
+const PRESS: f32 = 0.5; // how far a trigger goes in before it counts as pressed
+
+pub struct Gamepad {
+    id: Option<JoystickId>, // the one in use, None until one shows up
+    held: Vec<bool>,        // per binding, so an action fires on the press and not every frame
+}
+
+impl Gamepad {
+    pub fn new() -> Self {
+        Self {
+            id: None,
+            held: Vec::new(),
+        }
+    }
+
+    // read the pad, move the camera, return the actions whose buttons went down since the
+    // last frame (run like key presses)
+    pub fn update(
+        &mut self,
+        glfw: &glfw::Glfw,
+        bindings: &Bindings,
+        camera: &mut Camera,
+        dt: f32,
+    ) -> Vec<Action> {
+        let Some(state) = self.state(glfw) else {
+            self.held.clear();
+            return Vec::new();
+        };
+        self.held.resize(bindings.gamepad().len(), false);
+
+        let mut pressed = Vec::new();
+        for (held, &(input, action)) in self.held.iter_mut().zip(bindings.gamepad()) {
+            if let Some((x, y)) = stick(&state, input) {
+                // only when pushed, panning cancels camera flights
+                if (x, y) == (0.0, 0.0) {
+                    continue;
+                }
+                // same directions as the mouse: down on the stick is down on the screen
+                let (dx, dy) = (x * STICK_PIXELS * dt, y * STICK_PIXELS * dt);
+                match action {
+                    Action::OrbitCamera => camera.orbit(dx, dy),
+                    Action::PanCamera => camera.pan(dx, dy),
+                    _ => {} // Bindings::load does not let a stick have anything else
+                }
+                continue;
+            }
+            let value = level(&state, input);
+            match action {
+                Action::ZoomIn | Action::ZoomOut => {
+                    if value > 0.0 {
+                        let sign = if action == Action::ZoomIn { 1.0 } else { -1.0 };
+                        camera.process_scroll((sign * value * ZOOM_NOTCHES * dt) as f64);
+                    }
+                }
+                _ => {
+                    let down = value > PRESS;
+                    if down && !*held {
+                        pressed.push(action);
+                    }
+                    *held = down;
+                }
+            }
+        }
+        pressed
+    }
+
+    fn state(&mut self, glfw: &glfw::Glfw) -> Option<GamepadState> {
+        if let Some(id) = self.id {
+            if let Some(state) = glfw.get_joystick(id).get_gamepad_state() {
+                return Some(state);
+            }
+            log::info!("gamepad disconnected");
+            self.id = None;
+        }
+        // joysticks glfw has no mapping for (flight sticks, wheels) are not gamepads
+        let joystick = JOYSTICKS
+            .iter()
+            .map(|&id| glfw.get_joystick(id))
+            .find(|joystick| joystick.is_gamepad())?;
+        log::info!(
+            "gamepad connected: {}",
+            joystick.get_gamepad_name().unwrap_or_default()
+        );
+        self.id = Some(joystick.id);
+        joystick.get_gamepad_state()
+    }
+}
+
+fn stick(state: &GamepadState, input: PadInput) -> Option<(f32, f32)> {
+    // the deadzone is round, then the rest is stretched so the edge of it is 0 and full
+    // tilt is still 1
+    let (x, y) = match input {
+        PadInput::LeftStick => (GamepadAxis::AxisLeftX, GamepadAxis::AxisLeftY),
+        PadInput::RightStick => (GamepadAxis::AxisRightX, GamepadAxis::AxisRightY),
+        _ => return None,
+    };
+    let (x, y) = (state.get_axis(x), state.get_axis(y));
+    let length = x.hypot(y);
+    if length <= DEADZONE {
+        return Some((0.0, 0.0));
+    }
+    let scale = ((length - DEADZONE) / (1.0 - DEADZONE)).min(1.0) / length;
+    Some((x * scale, y * scale))
+}
+
+fn level(state: &GamepadState, input: PadInput) -> f32 {
+    // how far in a button or trigger is, 0..1
+    let trigger = |axis| {
+        // glfw triggers rest at -1
+        let value = (state.get_axis(axis) + 1.0) * 0.5;
+        ((value - DEADZONE) / (1.0 - DEADZONE)).clamp(0.0, 1.0)
+    };
+    match input {
+        PadInput::Button(button) => match state.get_button_state(button) {
+            glfw::Action::Release => 0.0,
+            _ => 1.0,
+        },
+        PadInput::LeftTrigger => trigger(GamepadAxis::AxisLeftTrigger),
+        PadInput::RightTrigger => trigger(GamepadAxis::AxisRightTrigger),
+        PadInput::LeftStick | PadInput::RightStick => 0.0,
+    }
+}
@@ -0,0 +1,65 @@
+use crate::framebuffer::{self, ColorFormat, Framebuffer};
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::stats;
+
+// srgb output for windows that did not get an srgb framebuffer
+// the scene is lit and blended in linear, and normally the window encodes that to srgb on
+// write (GL_FRAMEBUFFER_SRGB); without that the frame would come out far too dark
+// so the frame goes into an srgb texture of our own instead, and one fullscreen pass
+// encodes it by hand into the window, same begin / finish shape as Fxaa (outermost)
+
+pub struct Gamma {
+    program: WatchedProgram,
+    empty_vao: gl::types::GLuint,
+    target: Framebuffer,
+    previous: gl::types::GLuint, // framebuffer to encode into, remembered in begin()
+}
+
+impl Gamma {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        Ok(Self {
+            program: WatchedProgram::new("gamma", ShaderProgram::gamma)?,
+            empty_vao,
+            target: Framebuffer::new(ColorFormat::Srgb8, true),
+            previous: 0,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn begin(&mut self, width: u32, height: u32) {
+        self.previous = framebuffer::current_binding();
+        self.target.resize(width, height);
+        self.target.bind();
+    }
+
+    pub unsafe fn finish(&mut self) {
+        // the shader encodes, so the hardware must not do it a second time
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous);
+        gl::Disable(gl::FRAMEBUFFER_SRGB);
+        gl::Disable(gl::DEPTH_TEST);
+
+        self.program.use_program();
+        self.program.set_uniform_i32("screen", 0);
+        self.target.bind_texture(0);
+        gl::BindVertexArray(self.empty_vao);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::BindVertexArray(0);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+
+        gl::Enable(gl::DEPTH_TEST);
+    }
+}
+
+impl Drop for Gamma {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::camera::Camera;
+use crate::render::{
+    generate_arrow, srgb_to_linear, ShaderError, ShaderProgram, VertexArray, WatchedProgram,
+};
+use crate::scene::Scene;
+use crate::text::TextRenderer; // axis letters go into the hud text batch
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+
+// orientation helpers, toggled together (G)
+// shell rings: a faint circle along every bohr orbit, so the shell structure stays readable
+//              when the electrons are bunched up on one side
+// corner axes: world x / y / z as seen from the camera, in a small square in the top right
+
+const RING_SEGMENTS: usize = 96;
+const RING_OPACITY: f32 = 0.35;
+const AXES_SIZE: f32 = 110.0; // side of the corner square in hud pixels
+const AXES_MARGIN: f32 = 10.0;
+
+// x red, y green, z blue, the usual convention
+const AXES: [(&str, glm::Vec3, [f32; 3]); 3] = [
+    ("X", glm::Vec3::new(1.0, 0.0, 0.0), [1.0, 0.3, 0.3]),
+    ("Y", glm::Vec3::new(0.0, 1.0, 0.0), [0.3, 1.0, 0.3]),
+    ("Z", glm::Vec3::new(0.0, 0.0, 1.0), [0.4, 0.5, 1.0]),
+];
+
+pub struct Gizmo {
+    program: WatchedProgram,
+    ring: VertexArray, // unit circle in the xz plane, same plane the orbits live in
+    arrow: VertexArray, // unit arrow along +y, turned onto each axis
+}
+
+impl Gizmo {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut circle = Vec::with_capacity(RING_SEGMENTS * 3);
+        for i in 0..RING_SEGMENTS {
+            let angle = 2.0 * PI * i as f32 / RING_SEGMENTS as f32;
+            circle.extend_from_slice(&[angle.cos(), 0.0, angle.sin()]);
+        }
+
+        Ok(Self {
+            program: WatchedProgram::new("gizmo", ShaderProgram::gizmo)?,
+            ring: VertexArray::new(&circle, &[3]),
+            arrow: VertexArray::from_mesh(&generate_arrow(1.0, 0.035, 0.09, 0.25, 16)),
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn draw_rings(&self, scene: &Scene, view: &glm::Mat4, projection: &glm::Mat4) {
+        // one loop per shell that has electrons, tilted like its orbit, tinted like its trails
+        // blended and without depth writes, a guide should never hide anything
+        self.program.use_program();
+        self.program.set_uniform_mat4("view", view);
+        self.program.set_uniform_mat4("projection", projection);
+
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::DepthMask(gl::FALSE);
+        self.ring.bind();
+        for scene_atom in &scene.atoms {
+            for shell in &scene_atom.atom.shells {
+                if shell.electron_count() == 0 || !scene.display.shell_visible(shell.n) {
+                    continue;
+                }
+                let r = shell.radius as f32;
+                let model =
+                    scene_atom.model() * shell.plane_rotation() * glm::scaling(&glm::vec3(r, r, r));
+                // drawn inside the linear scene, unlike the axes in the hud
+                let c = srgb_to_linear(&scene.display.theme.trail(shell.n));
+                self.program.set_uniform_mat4("model", &model);
+                self.program
+                    .set_uniform_vec4("color", &glm::vec4(c.x, c.y, c.z, RING_OPACITY));
+                self.ring.draw(gl::LINE_LOOP);
+            }
+        }
+        gl::BindVertexArray(0);
+        gl::DepthMask(gl::TRUE);
+        gl::Disable(gl::BLEND);
+    }
+
+    pub unsafe fn draw_axes(
+        &self,
+        camera: &Camera,
+        text: &mut TextRenderer,
+        width: u32,
+        height: u32,
+        hud_scale: f32,
+    ) {
+        // own little viewport, camera rotation only, so the axes turn but never move or shrink
+        // letters are queued into the text batch at the tips, the caller flushes
+        // width, height and the viewport are framebuffer pixels, the text batch is in hud
+        // pixels (see DisplaySettings::hud_scale)
+        let size = ((AXES_SIZE * hud_scale) as u32).min(width).min(height);
+        let margin = (AXES_MARGIN * hud_scale) as u32;
+        let x0 = width.saturating_sub(size + margin);
+        let y0 = height.saturating_sub(size + margin); // gl viewports start at the bottom
+        let mut view = camera.get_view_matrix();
+        view.set_column(3, &glm::vec4(0.0, 0.0, -3.0, 1.0));
+        let projection = glm::ortho(-1.3, 1.3, -1.3, 1.3, 0.1, 10.0);
+
+        gl::Viewport(x0 as i32, y0 as i32, size as i32, size as i32);
+        gl::Disable(gl::DEPTH_TEST);
+        self.program.use_program();
+        self.program.set_uniform_mat4("view", &view);
+        self.program.set_uniform_mat4("projection", &projection);
+        // flat colored and no depth test, so the one furthest away goes first
+        self.arrow.bind();
+        let mut order = AXES;
+        order.sort_by(|a, b| {
+            (view * a.1.push(0.0))
+                .z
+                .total_cmp(&(view * b.1.push(0.0)).z)
+        });
+        for (label, direction, rgb) in order {
+            let color = glm::vec4(rgb[0], rgb[1], rgb[2], 1.0);
+            self.program
+                .set_uniform_mat4("model", &arrow_model(&direction));
+            self.program.set_uniform_vec4("color", &color);
+            self.arrow.draw(gl::TRIANGLES);
+
+            // tip in the little viewport's ndc -> window pixels, y down for the text
+            // a bit past the tip of the arrow so the letter does not sit on it
+            let end = direction * 1.15;
+            let tip = projection * view * glm::vec4(end.x, end.y, end.z, 1.0);
+            let px = x0 as f32 + (tip.x / tip.w * 0.5 + 0.5) * size as f32;
+            let py = (height - y0 - size) as f32 + (0.5 - tip.y / tip.w * 0.5) * size as f32;
+            let (px, py) = (px / hud_scale, py / hud_scale);
+            text.draw_text(label, px - 5.0, py - 7.0, 2.0, &color);
+        }
+        gl::BindVertexArray(0);
+        gl::Enable(gl::DEPTH_TEST);
+        gl::Viewport(0, 0, width as i32, height as i32);
+    }
+}
+
+pub fn arrow_model(direction: &glm::Vec3) -> glm::Mat4 {
+    // turns the +y arrow onto a unit direction
+    let up = glm::vec3(0.0, 1.0, 0.0);
+    let axis = glm::cross(&up, direction);
+    if glm::length(&axis) < 1e-6 {
+        return glm::identity();
+    }
+    glm::rotation(glm::dot(&up, direction).acos(), &glm::normalize(&axis))
+}
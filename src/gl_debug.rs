@@ -0,0 +1,149 @@
+use std::ffi::CStr; // driver messages are C strings
+use std::os::raw::c_void; // callback user pointer
+use std::sync::atomic::{AtomicBool, Ordering}; // checks on / off
+
+// opengl does not complain, it just quietly draws nothing
+// (messages go through the log crate, RUST_LOG picks how much of it shows)
+// two ways to make it talk:
+//   1. GL_DEBUG_OUTPUT (gl 4.3 / KHR_debug): the driver calls us with a message
+//      whenever something is wrong, with a description far better than an error code
+//   2. check_gl_error!("label") after the calls that matter: polls glGetError and names
+//      the call site, works on every driver, including the ones without debug output
+// both are on in debug builds and with --gl-debug, and cost nothing otherwise
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+macro_rules! check_gl_error {
+    ($label:expr) => {
+        $crate::gl_debug::check($label, file!(), line!())
+    };
+}
+
+pub unsafe fn enable() {
+    // call once, right after the gl functions are loaded
+    // debug output needs a debug context, see the window hint in main
+    ENABLED.store(true, Ordering::Relaxed);
+
+    if !gl::DebugMessageCallback::is_loaded() {
+        log::info!("GL debug output not available, falling back to glGetError checks");
+        return;
+    }
+    gl::Enable(gl::DEBUG_OUTPUT);
+    // synchronous: the callback runs inside the offending call, so a backtrace points at it
+    gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+    gl::DebugMessageCallback(Some(callback), std::ptr::null());
+    // notifications are drivers chatting about buffer placement, not problems
+    gl::DebugMessageControl(
+        gl::DONT_CARE,
+        gl::DONT_CARE,
+        gl::DEBUG_SEVERITY_NOTIFICATION,
+        0,
+        std::ptr::null(),
+        gl::FALSE,
+    );
+    log::info!("GL debug output enabled");
+}
+
+pub unsafe fn log_context_info() {
+    // what the driver actually gave us, the first thing to know when a bug report comes in
+    let string = |name| {
+        let raw = gl::GetString(name);
+        if raw.is_null() {
+            "unknown".into()
+        } else {
+            CStr::from_ptr(raw as *const _).to_string_lossy()
+        }
+    };
+    log::info!("GL version {}", string(gl::VERSION));
+    log::info!(
+        "GL renderer {} ({})",
+        string(gl::RENDERER),
+        string(gl::VENDOR)
+    );
+    log::info!("GLSL version {}", string(gl::SHADING_LANGUAGE_VERSION));
+}
+
+pub fn check(label: &str, file: &str, line: u32) {
+    // drain every pending error, opengl queues them up until asked
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    loop {
+        let error = unsafe { gl::GetError() };
+        if error == gl::NO_ERROR {
+            break;
+        }
+        log::error!(
+            "gl error {} after {} ({}:{})",
+            error_name(error),
+            label,
+            file,
+            line
+        );
+    }
+}
+
+extern "system" fn callback(
+    source: gl::types::GLenum,
+    kind: gl::types::GLenum,
+    id: gl::types::GLuint,
+    severity: gl::types::GLenum,
+    _length: gl::types::GLsizei,
+    message: *const gl::types::GLchar,
+    _user: *mut c_void,
+) {
+    // the driver hands over a nul terminated message that is only valid during the call
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    log::log!(
+        severity_level(severity),
+        "gl [{} {} {}]: {}",
+        source_name(source),
+        type_name(kind),
+        id,
+        message.trim_end()
+    );
+}
+
+fn error_name(error: gl::types::GLenum) -> &'static str {
+    match error {
+        gl::INVALID_ENUM => "INVALID_ENUM",
+        gl::INVALID_VALUE => "INVALID_VALUE",
+        gl::INVALID_OPERATION => "INVALID_OPERATION",
+        gl::INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION",
+        gl::OUT_OF_MEMORY => "OUT_OF_MEMORY",
+        gl::STACK_UNDERFLOW => "STACK_UNDERFLOW",
+        gl::STACK_OVERFLOW => "STACK_OVERFLOW",
+        _ => "unknown error",
+    }
+}
+
+fn source_name(source: gl::types::GLenum) -> &'static str {
+    match source {
+        gl::DEBUG_SOURCE_API => "api",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "third party",
+        gl::DEBUG_SOURCE_APPLICATION => "application",
+        _ => "other",
+    }
+}
+
+fn type_name(kind: gl::types::GLenum) -> &'static str {
+    match kind {
+        gl::DEBUG_TYPE_ERROR => "error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+        gl::DEBUG_TYPE_PORTABILITY => "portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "performance",
+        _ => "other",
+    }
+}
+
+fn severity_level(severity: gl::types::GLenum) -> log::Level {
+    match severity {
+        gl::DEBUG_SEVERITY_HIGH => log::Level::Error,
+        gl::DEBUG_SEVERITY_MEDIUM => log::Level::Warn,
+        gl::DEBUG_SEVERITY_LOW => log::Level::Info,
+        _ => log::Level::Debug,
+    }
+}
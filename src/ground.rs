@@ -0,0 +1,110 @@
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::scene::Scene;
+use crate::shadow::{self, ShadowMap}; // the ground catches the atoms' shadows
+use crate::stats;
+use nalgebra_glm as glm;
+
+// a floor under the atoms (Shift+G), so their places in 3d can be read off it, best with
+// the shadows on (see shadow.rs):
+//   plane  solid matte grey
+//   grid   lines a world unit apart, every tenth stronger, over a faint fill
+// it is not a mesh: a fullscreen triangle whose pixels are intersected with the plane in
+// the shader, so it goes on forever and the lines stay a pixel wide and smooth at any
+// distance; it fades out around the point under the camera target, fade world units away
+// the height is from the bottom of the atoms' shells, 0 touches the lowest one, so it stays
+// under whatever is loaded; bohr view only, it is drawn before the atoms with its real
+// depth, they stand on it and the ambient occlusion sees where they touch
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GroundMode {
+    Off,
+    Plane,
+    Grid,
+}
+
+impl GroundMode {
+    pub fn next(self) -> Self {
+        // Shift+G cycles through these
+        match self {
+            GroundMode::Off => GroundMode::Plane,
+            GroundMode::Plane => GroundMode::Grid,
+            GroundMode::Grid => GroundMode::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GroundMode::Off => "off",
+            GroundMode::Plane => "plane",
+            GroundMode::Grid => "grid",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [GroundMode::Off, GroundMode::Plane, GroundMode::Grid]
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
+}
+
+pub struct Ground {
+    program: WatchedProgram,
+    empty_vao: gl::types::GLuint, // fullscreen triangle, generated in the vertex shader
+}
+
+impl Ground {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        Ok(Self {
+            program: WatchedProgram::new("ground", ShaderProgram::ground)?,
+            empty_vao,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn draw(&self, scene: &Scene, shadow: &ShadowMap) {
+        // the Frame block has to be uploaded already, it brings the camera and the light
+        let mode = scene.display.ground;
+        let Some(height) = height(scene).filter(|_| mode != GroundMode::Off) else {
+            return;
+        };
+        let camera = &scene.camera;
+        let inverse = glm::inverse(&(camera.get_projection_matrix() * camera.get_view_matrix()));
+        shadow.apply(&self.program);
+        self.program
+            .set_uniform_mat4("inverseViewProjection", &inverse);
+        self.program.set_uniform_f32("height", height);
+        self.program.set_uniform_vec3("fadeCenter", &camera.target);
+        self.program
+            .set_uniform_f32("fade", scene.display.ground_fade);
+        self.program
+            .set_uniform_i32("grid", (mode == GroundMode::Grid) as i32);
+
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::BindVertexArray(self.empty_vao);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::BindVertexArray(0);
+        gl::Disable(gl::BLEND);
+        check_gl_error!("ground");
+    }
+}
+
+impl Drop for Ground {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
+
+pub fn height(scene: &Scene) -> Option<f32> {
+    // world y of the ground, None without atoms to put it under
+    let (center, radius) = shadow::bounds(scene)?;
+    Some(center.y - radius + scene.display.ground_height)
+}
@@ -0,0 +1,140 @@
+use crate::scene::{Bond, BondOrder, Scene};
+use crate::simulation::Atom; // whole atoms are kept, before and after
+use nalgebra_glm as glm;
+
+// undo / redo of what the user does to the scene (Ctrl+Z, Ctrl+Shift+Z)
+// every edit is kept as a command that knows how to do itself again and how to take itself
+// back: a changed atom (ionized, an electron added, another isotope or element) keeps the
+// atom from before and after, atoms and bonds that came or went keep what is needed to
+// bring them back at their place
+// edits go in when they happen, from Scene::place_atom, delete_atom, connect, raise_bond,
+// disconnect, ionize and friends; comparison mode, builder mode and loading a save change
+// the atoms under them, so switching those forgets every edit
+// a new edit after undoing forgets what could have been redone, like every editor
+
+const MAX_EDITS: usize = 100; // older ones fall off, a uranium atom is a few kB
+
+pub enum Edit {
+    Atom {
+        index: usize, // in Scene::atoms
+        before: Atom,
+        after: Atom,
+        what: &'static str, // "ionization", "isotope", ...
+    },
+    AddAtom {
+        index: usize,
+        atom: Atom,
+        position: glm::Vec3,
+    },
+    RemoveAtom {
+        index: usize,
+        atom: Atom,
+        position: glm::Vec3,
+        orientation: glm::Quat,
+        bonds: Vec<Bond>, // the ones it had, numbered as before it went
+    },
+    AddBond(Bond),
+    RemoveBond(Bond),
+    BondOrder {
+        a: usize,
+        b: usize,
+        before: BondOrder,
+        after: BondOrder,
+    },
+}
+
+impl Edit {
+    fn redo(&self, scene: &mut Scene) {
+        match self {
+            Edit::Atom { index, after, .. } => scene.replace_atom(*index, after.clone()),
+            Edit::AddAtom {
+                index,
+                atom,
+                position,
+            } => scene.insert_atom(*index, atom.clone(), *position, glm::quat_identity(), &[]),
+            Edit::RemoveAtom { index, .. } => {
+                scene.remove_atom(*index);
+            }
+            Edit::AddBond(bond) => scene.add_bond(bond.a, bond.b, bond.order),
+            Edit::RemoveBond(bond) => {
+                scene.remove_bond(bond.a, bond.b);
+            }
+            Edit::BondOrder { a, b, after, .. } => scene.set_bond_order(*a, *b, *after),
+        }
+    }
+
+    fn undo(&self, scene: &mut Scene) {
+        match self {
+            Edit::Atom { index, before, .. } => scene.replace_atom(*index, before.clone()),
+            Edit::AddAtom { index, .. } => {
+                scene.remove_atom(*index);
+            }
+            Edit::RemoveAtom {
+                index,
+                atom,
+                position,
+                orientation,
+                bonds,
+            } => scene.insert_atom(*index, atom.clone(), *position, *orientation, bonds),
+            Edit::AddBond(bond) => {
+                scene.remove_bond(bond.a, bond.b);
+            }
+            Edit::RemoveBond(bond) => scene.add_bond(bond.a, bond.b, bond.order),
+            Edit::BondOrder { a, b, before, .. } => scene.set_bond_order(*a, *b, *before),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        // numbered from 1 like the hud lists them
+        match self {
+            Edit::Atom { index, what, .. } => format!("{} of atom {}", what, index + 1),
+            Edit::AddAtom { index, atom, .. } => {
+                format!("adding {} as atom {}", atom.element().symbol, index + 1)
+            }
+            Edit::RemoveAtom { index, atom, .. } => {
+                format!("removing atom {} ({})", index + 1, atom.element().symbol)
+            }
+            Edit::AddBond(bond) => format!("bond {}-{}", bond.a + 1, bond.b + 1),
+            Edit::RemoveBond(bond) => format!("removing bond {}-{}", bond.a + 1, bond.b + 1),
+            Edit::BondOrder { a, b, .. } => format!("bond order {}-{}", a + 1, b + 1),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct History {
+    done: Vec<Edit>,   // oldest first, undo takes the last
+    undone: Vec<Edit>, // most recently undone last, redo takes it
+}
+
+impl History {
+    pub fn record(&mut self, edit: Edit) {
+        // an edit that has just happened
+        self.done.push(edit);
+        let excess = self.done.len().saturating_sub(MAX_EDITS);
+        self.done.drain(..excess);
+        self.undone.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+pub fn undo(scene: &mut Scene) -> Option<String> {
+    // takes back the newest edit, says what it was; None when there is nothing left
+    let edit = scene.history.done.pop()?;
+    edit.undo(scene);
+    let description = edit.describe();
+    scene.history.undone.push(edit);
+    Some(description)
+}
+
+pub fn redo(scene: &mut Scene) -> Option<String> {
+    let edit = scene.history.undone.pop()?;
+    edit.redo(scene);
+    let description = edit.describe();
+    scene.history.done.push(edit);
+    Some(description)
+}
@@ -0,0 +1,308 @@
+use crate::elements::{self, Element}; // covalent radii for the bonds
+use crate::isotopes; // xyz labels like C or C-13
+use crate::scene::{Bond, BondOrder, Scene, ViewMode};
+use crate::simulation::Atom;
+use crate::vsepr; // bond lengths in the world
+use nalgebra_glm as glm;
+use std::fmt; // pretty errors
+use std::fs;
+use std::path::Path;
+
+// real molecules from the two plainest chemistry formats, dropped onto the window or with
+// --open, replacing whatever was in the scene:
+//   .xyz   atom count, a comment line, then "symbol x y z" per atom, in angstrom
+//   .pdb   the ATOM / HETATM records, element from columns 77-78 (or the atom name),
+//          only the first MODEL of a file with several
+// neither has to say which atoms are bonded (pdb CONECT records are ignored too), so bonds
+// are inferred: two atoms closer than their covalent radii added up, plus some slack
+// every bond comes out single, these formats know nothing about orders
+// the bohr shells are far bigger than real atoms, so the molecule is blown up until no two
+// atoms have their shells closer than a bond would, which keeps the real shape; molecule
+// shapes (vsepr.rs) are switched off for it, single bonds would relax rings and double bonds
+// into the wrong geometry
+// caffeine (24 atoms) is fine, a protein is not, every atom has its own nucleus and
+// electrons to simulate
+
+pub const MAX_ATOMS: usize = 500;
+const BOND_SLACK: f32 = 0.4; // angstrom past the covalent radii that still counts as bonded
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io {
+        path: String,
+        message: String,
+    },
+    Parse {
+        path: String,
+        line: usize,
+        message: String,
+    },
+    Invalid {
+        path: String,
+        message: String,
+    }, // parsed, but nothing the scene can show
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Io { path, message } => {
+                write!(f, "could not read molecule file '{}': {}", path, message)
+            }
+            ImportError::Parse {
+                path,
+                line,
+                message,
+            } => write!(
+                f,
+                "could not parse molecule file '{}', line {}: {}",
+                path, line, message
+            ),
+            ImportError::Invalid { path, message } => {
+                write!(f, "invalid molecule file '{}': {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+struct Record {
+    element: &'static Element,
+    mass_number: Option<u32>,
+    position: glm::Vec3, // angstrom
+}
+
+pub fn is_molecule(path: &str) -> bool {
+    // whether open can read this file, by its extension
+    matches!(extension(path).as_str(), "xyz" | "pdb" | "ent")
+}
+
+pub fn open(scene: &mut Scene, path: &str) -> Result<usize, ImportError> {
+    // the molecule in the file instead of the scene's atoms, returns how many there are
+    // all or nothing like save::load
+    let source = fs::read_to_string(path).map_err(|err| ImportError::Io {
+        path: path.to_string(),
+        message: err.to_string(),
+    })?;
+    let parse_error = |line: usize, message: String| ImportError::Parse {
+        path: path.to_string(),
+        line,
+        message,
+    };
+    let records = match extension(path).as_str() {
+        "xyz" => parse_xyz(&source).map_err(|(line, message)| parse_error(line, message))?,
+        "pdb" | "ent" => {
+            parse_pdb(&source).map_err(|(line, message)| parse_error(line, message))?
+        }
+        _ => {
+            return Err(ImportError::Invalid {
+                path: path.to_string(),
+                message: "not an .xyz or .pdb file".to_string(),
+            })
+        }
+    };
+    let invalid = |message: String| ImportError::Invalid {
+        path: path.to_string(),
+        message,
+    };
+    if records.is_empty() {
+        return Err(invalid("no atoms in it".to_string()));
+    }
+    if records.len() > MAX_ATOMS {
+        return Err(invalid(format!(
+            "{} atoms, at most {} can be simulated",
+            records.len(),
+            MAX_ATOMS
+        )));
+    }
+
+    let mut atoms = Vec::with_capacity(records.len());
+    for record in &records {
+        let atom = match record.mass_number {
+            Some(mass_number) => Atom::from_isotope(record.element.atomic_number, mass_number),
+            None => Atom::from_atomic_number(record.element.atomic_number),
+        }
+        .ok_or_else(|| invalid(format!("no atom for {}", record.element.symbol)))?;
+        atoms.push(atom);
+    }
+    let bonds = infer_bonds(&records);
+
+    // as big as it has to be for no two atoms to crowd each other, centered on the origin
+    let mut scale: f32 = 1.0;
+    for i in 0..records.len() {
+        for j in i + 1..records.len() {
+            let apart = glm::distance(&records[i].position, &records[j].position).max(0.1);
+            scale = scale.max(vsepr::bond_length(&atoms[i], &atoms[j]) / apart);
+        }
+    }
+    let middle = records
+        .iter()
+        .fold(glm::vec3(0.0, 0.0, 0.0), |sum, record| {
+            sum + record.position
+        })
+        / records.len() as f32;
+    let positions: Vec<glm::Vec3> = records
+        .iter()
+        .map(|record| (record.position - middle) * scale)
+        .collect();
+    let extent = positions
+        .iter()
+        .zip(&atoms)
+        .map(|(position, atom)| glm::length(position) + atom.outer_radius())
+        .fold(0.0, f32::max);
+
+    let count = atoms.len();
+    scene.restore(
+        atoms
+            .into_iter()
+            .zip(positions)
+            .map(|(atom, position)| (atom, position, glm::quat_identity()))
+            .collect(),
+        bonds,
+    );
+    scene.geometry = None;
+    scene.view_mode = ViewMode::Bohr;
+    scene
+        .camera
+        .fly_to(glm::vec3(0.0, 0.0, 0.0), (2.0 * extent).max(30.0), 1.0);
+    log::info!("opened {} atoms from {}", count, path);
+    Ok(count)
+}
+
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn parse_xyz(source: &str) -> Result<Vec<Record>, (usize, String)> {
+    // errors carry their 1-based line number
+    let mut lines = source.lines();
+    let count_line = lines.next().unwrap_or_default();
+    let count: usize = count_line
+        .trim()
+        .parse()
+        .map_err(|_| (1, format!("'{}' is not an atom count", count_line.trim())))?;
+    lines.next(); // the comment, anything goes
+
+    let mut records = Vec::with_capacity(count);
+    for (index, line) in lines.enumerate().take(count) {
+        let number = index + 3;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let [label, x, y, z, ..] = words[..] else {
+            return Err((
+                number,
+                "expected a symbol and three coordinates".to_string(),
+            ));
+        };
+        let (element, mass_number) = isotopes::parse(label).map_err(|err| (number, err))?;
+        records.push(Record {
+            element,
+            mass_number,
+            position: glm::vec3(
+                coordinate(x).map_err(|err| (number, err))?,
+                coordinate(y).map_err(|err| (number, err))?,
+                coordinate(z).map_err(|err| (number, err))?,
+            ),
+        });
+    }
+    if records.len() < count {
+        return Err((
+            records.len() + 3,
+            format!("{} atoms announced, only {} there", count, records.len()),
+        ));
+    }
+    Ok(records)
+}
+
+fn parse_pdb(source: &str) -> Result<Vec<Record>, (usize, String)> {
+    // fixed columns, 1-based in the format description, 0-based here
+    let mut records = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        if line.starts_with("ENDMDL") {
+            break; // the first model is enough
+        }
+        if !(line.starts_with("ATOM") || line.starts_with("HETATM")) {
+            continue;
+        }
+        let column = |from: usize, to: usize| line.get(from..to.min(line.len())).unwrap_or("");
+        let symbol = match column(76, 78).trim() {
+            "" => symbol_from_name(column(12, 16), line.starts_with("HETATM")),
+            symbol => symbol.to_string(),
+        };
+        let element = elements::by_symbol(&symbol)
+            .ok_or_else(|| (number, format!("'{}' is not an element symbol", symbol)))?;
+        records.push(Record {
+            element,
+            mass_number: None,
+            position: glm::vec3(
+                coordinate(column(30, 38)).map_err(|err| (number, err))?,
+                coordinate(column(38, 46)).map_err(|err| (number, err))?,
+                coordinate(column(46, 54)).map_err(|err| (number, err))?,
+            ),
+        });
+    }
+    Ok(records)
+}
+
+fn symbol_from_name(name: &str, hetero: bool) -> String {
+    // no element columns: the pdb convention puts a two-letter element right at the start of
+    // the atom name (column 13, "FE  ", "CL  ") and a one-letter one in column 14 (" CA " is an
+    // alpha carbon, "CA  " calcium)
+    // only HETATM records hold ions and metals, and a four letter name starting with H is a
+    // hydrogen that needed column 13 too ("HG12" is not mercury)
+    let chars: Vec<char> = name.chars().collect();
+    match chars[..] {
+        [first, second, ..] if first.is_ascii_alphabetic() => {
+            let hydrogen = first == 'H' && chars.len() == 4 && chars[3] != ' ';
+            let pair: String = [first, second].iter().collect();
+            if hetero && !hydrogen && elements::by_symbol(&pair).is_some() {
+                pair
+            } else {
+                first.to_string()
+            }
+        }
+        [' ' | '0'..='9', second, ..] if second.is_ascii_alphabetic() => second.to_string(),
+        // files that do not keep to it get their first letter
+        _ => name
+            .trim()
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .chars()
+            .take_while(char::is_ascii_alphabetic)
+            .take(1)
+            .collect(),
+    }
+}
+
+fn coordinate(word: &str) -> Result<f32, String> {
+    word.trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| format!("'{}' is not a coordinate", word.trim()))
+}
+
+fn infer_bonds(records: &[Record]) -> Vec<Bond> {
+    // single bonds between atoms about as close as their covalent radii
+    let radius = |record: &Record| record.element.covalent_radius().map(|pm| pm as f32 / 100.0);
+    let mut bonds = Vec::new();
+    for a in 0..records.len() {
+        for b in a + 1..records.len() {
+            let (Some(ra), Some(rb)) = (radius(&records[a]), radius(&records[b])) else {
+                continue;
+            };
+            if glm::distance(&records[a].position, &records[b].position) < ra + rb + BOND_SLACK {
+                bonds.push(Bond {
+                    a,
+                    b,
+                    order: BondOrder::Single,
+                });
+            }
+        }
+    }
+    bonds
+}
@@ -0,0 +1,650 @@
+use crate::camera::BOOKMARKS; // one per digit key
+use glfw::{GamepadButton, Key, Modifiers, MouseButton};
+use serde::Deserialize; // bindings file format
+use std::collections::HashMap;
+use std::fmt; // pretty errors
+use std::fs; // bindings file
+
+// keyboard / mouse -> named actions
+// the event loop only ever asks "which action is this?", never "is this the V key?"
+// so remapping is a matter of editing bindings.toml, no recompiling
+//
+// bindings.toml looks like
+//
+//   [keys]
+//   CycleView = "Tab"
+//   Screenshot = ["F12", "Ctrl+P"]
+//
+//   [mouse]
+//   PanCamera = ["Right", "Shift+Left"]
+//
+//   [gamepad]
+//   OrbitCamera = "LeftStick"
+//   Pause = ["Start", "B"]
+//
+// every action listed replaces its default bindings, everything else keeps the defaults
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum Action {
+    Quit,
+    Screenshot,
+    ToggleRecording, // start / stop capturing frames, see recorder.rs
+    ExportObj,       // bohr view geometry as obj + mtl, see export.rs
+    ExportGltf,      // same as gltf + bin
+    SaveScene,       // whole arrangement to scene.json, see save.rs
+    LoadScene,       // and back
+    ReplayScript,    // start the --script demo over
+    ToggleConsole,   // drop-down command line, see console.rs
+    ToggleMute,      // sound effects off / on, see audio.rs
+    ToggleFullscreen,
+    Recenter,        // fly back to the middle of the scene
+    ResetRotation,   // hand-turned selected (or main) atom back the way it was built
+    CycleView,       // cloud -> bohr -> orbital
+    CycleRenderMode, // solid -> wireframe -> points
+    CycleBackground, // solid -> gradient -> skybox
+    CycleTheme,      // classic -> dark -> colorblind
+    CycleStereo,     // off -> red-cyan anaglyph -> side by side, see stereo.rs
+    CycleQuality,    // low -> medium -> high -> ultra, see quality.rs
+    Pause,           // freeze the simulation, camera keeps working
+    Step,            // advance one frame of simulated time, pauses first
+    SpeedUp,         // double the time scale
+    SlowDown,        // halve the time scale
+    NextElement,     // main atom becomes Z + 1
+    PreviousElement, // main atom becomes Z - 1
+    NextIsotope,     // main atom gains neutrons, next isotope in the table
+    PreviousIsotope, // and loses them again
+    ToggleAntialiasing,
+    ToggleOcclusion,     // ambient occlusion, see ssao.rs
+    ToggleShadows,       // key light shadows, see shadow.rs
+    CycleGround,         // off -> plane -> grid under the atoms, see ground.rs
+    ToggleDepthOfField,  // focus blur, see dof.rs
+    CycleToneMapping,    // off -> reinhard -> aces, see tonemap.rs
+    TogglePeriodicTable, // element picker overlay
+    ToggleEditor,        // molecule editor palette, see molecule.rs
+    ToggleGeometry,      // bonded atoms slide into their vsepr shape, see vsepr.rs
+    CycleMoleculeStyle,  // shells -> ball-and-stick -> space-filling -> wireframe, see style.rs
+    DeleteAtom,          // selected atom and its bonds out, the main atom stays
+    ToggleProjection,    // perspective <-> orthographic
+    Excite,              // selected (or outermost) electron jumps up a shell
+    Ionize,              // selected (or main) atom loses an electron
+    AddElectron,         // and gains one
+    Undo,                // take back the last scene edit, see history.rs
+    Redo,                // and do it again
+    ToggleSpin,          // spin arrows through the electrons
+    ToggleRepulsion,     // electrons push each other around their shell spheres
+    ToggleVibration,     // nucleons move under their own forces, see vibration.rs
+    NextPrincipal,       // orbital view: n + 1, l and m shrink to fit
+    PreviousPrincipal,
+    NextAngular, // l + 1, wraps within 0..n
+    PreviousAngular,
+    NextMagnetic, // m + 1, wraps within -l..=l
+    PreviousMagnetic,
+    ToggleSubshell,     // one orbital <-> every m of the subshell at once
+    ToggleClipPlane,    // cross-section, comes on facing the camera, see slice.rs
+    ToggleDensitySlice, // |psi|^2 on that plane as a map in the corner
+    ToggleRadialPlot,   // P(r) chart of the same orbital
+    ToggleSpectrum,     // emission spectrum bar
+    ToggleLevels,       // energy level diagram of the main atom
+    ToggleGuides,       // shell rings + axis gizmo
+    ToggleTextures,     // charge marks on the nucleons and electrons, see texture.rs
+    ToggleProfiler,     // gpu milliseconds per render pass in the hud
+    ToggleBuilder,      // main atom filled electron by electron, see aufbau.rs
+    BuilderNext,        // next electron in
+    BuilderPrevious,    // last one out again
+    ToggleComparison,   // main atom and a partner side by side, see compare.rs
+    NextPartnerElement, // partner becomes Z + 1
+    PreviousPartnerElement,
+    ToggleTrueScale,  // real nucleus size against the shells, see scale.rs
+    CycleField,       // selected atom's field lines / potential slice / off, see field.rs
+    ToggleMagnetic,   // orbital current loops and magnetic moment, see magnetic.rs
+    ToggleScattering, // alpha particles fired at the main atom, see scattering.rs
+    FireAlpha,
+    WiderImpact, // impact parameter of the next shot
+    NarrowerImpact,
+    MoreEnergy, // energy of the next shot
+    LessEnergy,
+    FirePhoton, // light of the chosen wavelength at the selected atom, see absorption.rs
+    LongerWavelength, // of the next photon, a few percent
+    ShorterWavelength,
+    NextLine,     // straight onto the target atom's next absorption line, longer
+    PreviousLine, // and shorter
+    // camera bookmarks, slots 0..8 on the digit row (key 1 = slot 0)
+    // they carry the slot so bindings.toml cannot name them, they stay on the digits
+    #[serde(skip)]
+    SetBookmark(usize), // remember the camera pose
+    #[serde(skip)]
+    GoToBookmark(usize), // and fly back to it
+    OrbitCamera, // hold + drag on a mouse button, or a gamepad stick
+    PanCamera,   // same
+    RotateAtom,  // hold + drag on a mouse button, turns one atom, see arcball.rs
+    ZoomIn,      // one scroll notch closer, or closer for as long as a trigger is held
+    ZoomOut,     // and further away
+}
+
+impl Action {
+    pub fn is_drag(self) -> bool {
+        // actions that last as long as the button is held
+        matches!(
+            self,
+            Action::OrbitCamera | Action::PanCamera | Action::RotateAtom
+        )
+    }
+}
+
+// what a gamepad has to bind: buttons press like keys, sticks and triggers are analog
+// (see gamepad.rs for how each one is read)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PadInput {
+    Button(GamepadButton),
+    LeftStick,
+    RightStick,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl PadInput {
+    fn is_stick(self) -> bool {
+        matches!(self, PadInput::LeftStick | PadInput::RightStick)
+    }
+}
+
+const DIGITS: [Key; 10] = [
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+// only these modifiers take part in matching
+// caps lock / num lock being on should not break every binding
+const CHORD_MODIFIERS: Modifiers = Modifiers::Shift
+    .union(Modifiers::Control)
+    .union(Modifiers::Alt)
+    .union(Modifiers::Super);
+
+#[derive(Clone, Copy)]
+struct Chord<T> {
+    // a key or button plus the modifiers that must be held with it
+    input: T,
+    mods: Modifiers,
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io { path: String, message: String },
+    Parse { path: String, message: String }, // not valid toml, or an unknown action name
+    UnknownInput(String),                    // "Shift+Banana"
+    WrongInput { action: Action, name: String }, // a stick on Pause, a button on OrbitCamera
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingsError::Io { path, message } => {
+                write!(f, "could not read bindings '{}': {}", path, message)
+            }
+            BindingsError::Parse { path, message } => {
+                write!(f, "invalid bindings file '{}': {}", path, message)
+            }
+            BindingsError::UnknownInput(name) => write!(f, "unknown key or button '{}'", name),
+            BindingsError::WrongInput { action, name } => write!(
+                f,
+                "cannot bind {:?} to '{}', sticks orbit and pan the camera and nothing else does",
+                action, name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+// toml side: a binding is either one string or a list of them
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(name) => vec![name],
+            OneOrMany::Many(names) => names,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BindingsFile {
+    #[serde(default)]
+    keys: HashMap<Action, OneOrMany>,
+    #[serde(default)]
+    mouse: HashMap<Action, OneOrMany>,
+    #[serde(default)]
+    gamepad: HashMap<Action, OneOrMany>,
+}
+
+pub struct Bindings {
+    keys: Vec<(Chord<Key>, Action)>,
+    mouse: Vec<(Chord<MouseButton>, Action)>,
+    gamepad: Vec<(PadInput, Action)>, // no modifiers, a pad has nothing to hold them with
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        // the classic layout, same keys this app always had
+        let key = |input, action| {
+            (
+                Chord {
+                    input,
+                    mods: Modifiers::empty(),
+                },
+                action,
+            )
+        };
+        let chord = |input, mods, action| (Chord { input, mods }, action);
+        let button = |input, mods, action| (Chord { input, mods }, action);
+        let pad = |button, action| (PadInput::Button(button), action);
+
+        let mut bindings = Self {
+            keys: vec![
+                key(Key::Escape, Action::Quit),
+                key(Key::F12, Action::Screenshot),
+                key(Key::R, Action::ToggleRecording),
+                key(Key::GraveAccent, Action::ToggleConsole),
+                key(Key::F11, Action::ToggleFullscreen),
+                key(Key::C, Action::Recenter),
+                chord(Key::C, Modifiers::Shift, Action::ResetRotation),
+                key(Key::V, Action::CycleView),
+                chord(Key::V, Modifiers::Shift, Action::CycleStereo),
+                key(Key::W, Action::CycleRenderMode),
+                chord(Key::W, Modifiers::Shift, Action::CycleQuality),
+                key(Key::B, Action::CycleBackground),
+                key(Key::K, Action::CycleTheme),
+                key(Key::Space, Action::Pause),
+                key(Key::Period, Action::Step),
+                key(Key::Equal, Action::SpeedUp),
+                key(Key::Minus, Action::SlowDown),
+                key(Key::RightBracket, Action::NextElement),
+                key(Key::LeftBracket, Action::PreviousElement),
+                key(Key::M, Action::ToggleAntialiasing),
+                key(Key::T, Action::TogglePeriodicTable),
+                chord(Key::B, Modifiers::Control, Action::ToggleEditor),
+                chord(Key::B, Modifiers::Shift, Action::ToggleGeometry),
+                chord(
+                    Key::B,
+                    Modifiers::Control | Modifiers::Shift,
+                    Action::CycleMoleculeStyle,
+                ),
+                key(Key::Delete, Action::DeleteAtom),
+                key(Key::O, Action::ToggleProjection),
+                chord(Key::O, Modifiers::Shift, Action::ToggleOcclusion),
+                chord(Key::H, Modifiers::Shift, Action::ToggleShadows),
+                chord(Key::G, Modifiers::Shift, Action::CycleGround),
+                chord(Key::F, Modifiers::Control, Action::ToggleDepthOfField),
+                chord(Key::T, Modifiers::Control, Action::CycleToneMapping),
+                key(Key::E, Action::Excite),
+                key(Key::X, Action::Ionize),
+                key(Key::A, Action::AddElectron),
+                chord(Key::Z, Modifiers::Control, Action::Undo),
+                chord(Key::Z, Modifiers::Control | Modifiers::Shift, Action::Redo),
+                key(Key::D, Action::ToggleRepulsion),
+                chord(Key::D, Modifiers::Shift, Action::ToggleVibration),
+                key(Key::U, Action::ToggleSpin),
+                key(Key::N, Action::NextPrincipal),
+                chord(Key::N, Modifiers::Shift, Action::PreviousPrincipal),
+                key(Key::L, Action::NextAngular),
+                chord(Key::L, Modifiers::Shift, Action::PreviousAngular),
+                key(Key::J, Action::NextMagnetic),
+                chord(Key::J, Modifiers::Shift, Action::PreviousMagnetic),
+                key(Key::F, Action::ToggleSubshell),
+                key(Key::P, Action::ToggleClipPlane),
+                chord(Key::P, Modifiers::Shift, Action::ToggleDensitySlice),
+                key(Key::H, Action::ToggleRadialPlot),
+                key(Key::S, Action::ToggleSpectrum),
+                chord(Key::E, Modifiers::Shift, Action::ToggleLevels),
+                key(Key::G, Action::ToggleGuides),
+                key(Key::Y, Action::ToggleTextures),
+                key(Key::F3, Action::ToggleProfiler),
+                key(Key::Z, Action::ToggleBuilder),
+                key(Key::Right, Action::BuilderNext),
+                key(Key::Left, Action::BuilderPrevious),
+                key(Key::Q, Action::ToggleComparison),
+                chord(Key::T, Modifiers::Shift, Action::ToggleTrueScale),
+                chord(Key::F, Modifiers::Shift, Action::CycleField),
+                chord(Key::M, Modifiers::Shift, Action::ToggleMagnetic),
+                chord(Key::A, Modifiers::Shift, Action::ToggleScattering),
+                key(Key::Enter, Action::FireAlpha),
+                key(Key::Up, Action::WiderImpact),
+                key(Key::Down, Action::NarrowerImpact),
+                chord(Key::Up, Modifiers::Shift, Action::MoreEnergy),
+                chord(Key::Down, Modifiers::Shift, Action::LessEnergy),
+                chord(Key::Enter, Modifiers::Shift, Action::FirePhoton),
+                chord(Key::Right, Modifiers::Shift, Action::LongerWavelength),
+                chord(Key::Left, Modifiers::Shift, Action::ShorterWavelength),
+                chord(Key::Right, Modifiers::Control, Action::NextLine),
+                chord(Key::Left, Modifiers::Control, Action::PreviousLine),
+                chord(
+                    Key::RightBracket,
+                    Modifiers::Shift,
+                    Action::NextPartnerElement,
+                ),
+                chord(
+                    Key::LeftBracket,
+                    Modifiers::Shift,
+                    Action::PreviousPartnerElement,
+                ),
+                key(Key::I, Action::NextIsotope),
+                chord(Key::I, Modifiers::Shift, Action::PreviousIsotope),
+                chord(Key::E, Modifiers::Control, Action::ExportObj),
+                chord(
+                    Key::E,
+                    Modifiers::Control | Modifiers::Shift,
+                    Action::ExportGltf,
+                ),
+                chord(Key::S, Modifiers::Control, Action::SaveScene),
+                chord(Key::O, Modifiers::Control, Action::LoadScene),
+                chord(Key::R, Modifiers::Control, Action::ReplayScript),
+                chord(Key::M, Modifiers::Control, Action::ToggleMute),
+            ],
+            mouse: vec![
+                button(
+                    MouseButton::Button1,
+                    Modifiers::empty(),
+                    Action::OrbitCamera,
+                ),
+                button(MouseButton::Button3, Modifiers::empty(), Action::PanCamera),
+                button(MouseButton::Button1, Modifiers::Shift, Action::PanCamera),
+                button(MouseButton::Button2, Modifiers::empty(), Action::RotateAtom),
+            ],
+            gamepad: vec![
+                (PadInput::RightStick, Action::OrbitCamera),
+                (PadInput::LeftStick, Action::PanCamera),
+                (PadInput::RightTrigger, Action::ZoomIn),
+                (PadInput::LeftTrigger, Action::ZoomOut),
+                pad(GamepadButton::ButtonDpadRight, Action::NextElement),
+                pad(GamepadButton::ButtonDpadLeft, Action::PreviousElement),
+                pad(GamepadButton::ButtonDpadUp, Action::NextIsotope),
+                pad(GamepadButton::ButtonDpadDown, Action::PreviousIsotope),
+                pad(GamepadButton::ButtonStart, Action::Pause),
+                pad(GamepadButton::ButtonA, Action::Excite),
+                pad(GamepadButton::ButtonB, Action::Ionize),
+                pad(GamepadButton::ButtonX, Action::AddElectron),
+                pad(GamepadButton::ButtonY, Action::CycleView),
+                pad(GamepadButton::ButtonBack, Action::Recenter),
+                pad(GamepadButton::ButtonLeftBumper, Action::SlowDown),
+                pad(GamepadButton::ButtonRightBumper, Action::SpeedUp),
+            ],
+        };
+        for (slot, &digit) in DIGITS[1..=BOOKMARKS].iter().enumerate() {
+            bindings.keys.push(key(digit, Action::GoToBookmark(slot)));
+            bindings
+                .keys
+                .push(chord(digit, Modifiers::Control, Action::SetBookmark(slot)));
+        }
+        bindings.sort();
+        bindings
+    }
+}
+
+impl Bindings {
+    pub fn load(path: &str) -> Result<Self, BindingsError> {
+        // defaults, overridden per action by whatever the file lists
+        let source = fs::read_to_string(path).map_err(|err| BindingsError::Io {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+        let file: BindingsFile = toml::from_str(&source).map_err(|err| BindingsError::Parse {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+
+        let mut bindings = Self::default();
+
+        for (action, names) in file.keys {
+            bindings.keys.retain(|&(_, bound)| bound != action);
+            for name in names.into_vec() {
+                let (input, mods) = parse_chord(&name, parse_key)?;
+                bindings.keys.push((Chord { input, mods }, action));
+            }
+        }
+
+        for (action, names) in file.mouse {
+            bindings.mouse.retain(|&(_, bound)| bound != action);
+            for name in names.into_vec() {
+                let (input, mods) = parse_chord(&name, parse_mouse_button)?;
+                bindings.mouse.push((Chord { input, mods }, action));
+            }
+        }
+
+        for (action, names) in file.gamepad {
+            bindings.gamepad.retain(|&(_, bound)| bound != action);
+            for name in names.into_vec() {
+                let input = parse_pad_input(&name)
+                    .ok_or_else(|| BindingsError::UnknownInput(name.clone()))?;
+                // a stick has a direction and nothing to press, the drags are the only
+                // actions that want one, and they cannot do anything with a button
+                // (except the arcball, which needs a cursor to grab the atom under)
+                if input.is_stick() != action.is_drag() || action == Action::RotateAtom {
+                    return Err(BindingsError::WrongInput { action, name });
+                }
+                bindings.gamepad.push((input, action));
+            }
+        }
+
+        bindings.sort();
+        log::info!("loaded key bindings from {}", path);
+        Ok(bindings)
+    }
+
+    pub fn load_or_default(path: &str) -> Self {
+        // a missing file is normal (defaults), a broken one is worth a warning but not a crash
+        if !std::path::Path::new(path).exists() {
+            return Self::default();
+        }
+        Self::load(path).unwrap_or_else(|err| {
+            log::warn!("{}, using default bindings", err);
+            Self::default()
+        })
+    }
+
+    fn sort(&mut self) {
+        // most modifiers first, so Shift+Left wins over plain Left when shift is held
+        self.keys
+            .sort_by_key(|(chord, _)| std::cmp::Reverse(chord.mods.bits().count_ones()));
+        self.mouse
+            .sort_by_key(|(chord, _)| std::cmp::Reverse(chord.mods.bits().count_ones()));
+    }
+
+    pub fn key_action(&self, key: Key, mods: Modifiers) -> Option<Action> {
+        find(&self.keys, key, mods)
+    }
+
+    pub fn mouse_action(&self, button: MouseButton, mods: Modifiers) -> Option<Action> {
+        find(&self.mouse, button, mods)
+    }
+
+    pub fn gamepad(&self) -> &[(PadInput, Action)] {
+        // all of them, the pad is polled rather than sending events (see gamepad.rs)
+        &self.gamepad
+    }
+
+    pub fn is_drag_button(&self, button: MouseButton) -> bool {
+        // used on release: modifiers may have changed since the press, so ignore them
+        self.mouse
+            .iter()
+            .any(|(chord, action)| chord.input == button && action.is_drag())
+    }
+}
+
+fn find<T: PartialEq + Copy>(
+    bindings: &[(Chord<T>, Action)],
+    input: T,
+    mods: Modifiers,
+) -> Option<Action> {
+    // first binding whose modifiers are all held (list is sorted most specific first)
+    let held = mods & CHORD_MODIFIERS;
+    bindings
+        .iter()
+        .find(|(chord, _)| chord.input == input && held.contains(chord.mods))
+        .map(|&(_, action)| action)
+}
+
+fn parse_chord<T>(
+    name: &str,
+    parse_input: fn(&str) -> Option<T>,
+) -> Result<(T, Modifiers), BindingsError> {
+    // "Ctrl+Shift+S" -> (S, Control | Shift)
+    let unknown = || BindingsError::UnknownInput(name.to_string());
+
+    let mut parts: Vec<&str> = name.split('+').map(str::trim).collect();
+    let input = parse_input(parts.pop().ok_or_else(unknown)?).ok_or_else(unknown)?;
+
+    let mut mods = Modifiers::empty();
+    for part in parts {
+        mods |= match part.to_ascii_lowercase().as_str() {
+            "shift" => Modifiers::Shift,
+            "ctrl" | "control" => Modifiers::Control,
+            "alt" => Modifiers::Alt,
+            "super" | "cmd" => Modifiers::Super,
+            _ => return Err(unknown()),
+        };
+    }
+
+    Ok((input, mods))
+}
+
+fn parse_mouse_button(name: &str) -> Option<MouseButton> {
+    match name.to_ascii_lowercase().as_str() {
+        "left" => Some(MouseButton::Button1),
+        "right" => Some(MouseButton::Button2),
+        "middle" => Some(MouseButton::Button3),
+        _ => None,
+    }
+}
+
+fn parse_pad_input(name: &str) -> Option<PadInput> {
+    // xbox names, glfw maps every pad it knows onto that layout
+    let button = match name.to_ascii_lowercase().as_str() {
+        "leftstick" => return Some(PadInput::LeftStick),
+        "rightstick" => return Some(PadInput::RightStick),
+        "lefttrigger" | "lt" => return Some(PadInput::LeftTrigger),
+        "righttrigger" | "rt" => return Some(PadInput::RightTrigger),
+        "a" => GamepadButton::ButtonA,
+        "b" => GamepadButton::ButtonB,
+        "x" => GamepadButton::ButtonX,
+        "y" => GamepadButton::ButtonY,
+        "leftbumper" | "lb" => GamepadButton::ButtonLeftBumper,
+        "rightbumper" | "rb" => GamepadButton::ButtonRightBumper,
+        "back" | "select" => GamepadButton::ButtonBack,
+        "start" => GamepadButton::ButtonStart,
+        "guide" => GamepadButton::ButtonGuide,
+        "leftthumb" => GamepadButton::ButtonLeftThumb, // pressing the stick in
+        "rightthumb" => GamepadButton::ButtonRightThumb,
+        "dpadup" => GamepadButton::ButtonDpadUp,
+        "dpadright" => GamepadButton::ButtonDpadRight,
+        "dpaddown" => GamepadButton::ButtonDpadDown,
+        "dpadleft" => GamepadButton::ButtonDpadLeft,
+        _ => return None,
+    };
+    Some(PadInput::Button(button))
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    // single letters and digits, F1..F12, plus the named keys people actually bind
+    const LETTERS: [Key; 26] = [
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+    ];
+    const FUNCTION_KEYS: [Key; 12] = [
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+    ];
+
+    let lower = name.to_ascii_lowercase();
+    let mut chars = lower.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_lowercase() {
+            return Some(LETTERS[(c as u8 - b'a') as usize]);
+        }
+        if c.is_ascii_digit() {
+            return Some(DIGITS[(c as u8 - b'0') as usize]);
+        }
+    }
+
+    if let Some(number) = lower
+        .strip_prefix('f')
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        return FUNCTION_KEYS.get(number.checked_sub(1)?).copied();
+    }
+
+    let key = match lower.as_str() {
+        "escape" | "esc" => Key::Escape,
+        "space" => Key::Space,
+        "enter" | "return" => Key::Enter,
+        "tab" => Key::Tab,
+        "backspace" => Key::Backspace,
+        "delete" | "del" => Key::Delete,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "minus" | "-" => Key::Minus,
+        "equal" | "=" => Key::Equal,
+        "leftbracket" | "[" => Key::LeftBracket,
+        "rightbracket" | "]" => Key::RightBracket,
+        "comma" | "," => Key::Comma,
+        "period" | "." => Key::Period,
+        "slash" | "/" => Key::Slash,
+        "grave" | "`" => Key::GraveAccent,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,279 @@
+use crate::elements::{self, Element}; // isotope names start with the symbol
+
+// isotopes: same element (same Z), different number of neutrons N, mass number A = Z + N
+// the table lists the well-known ones, stable or not, with half-lives for the unstable ones
+// anything else is still allowed as long as the nucleus is not absurd, it just has no data
+
+const MINUTE: f64 = 60.0;
+const HOUR: f64 = 60.0 * MINUTE;
+const DAY: f64 = 24.0 * HOUR;
+const YEAR: f64 = 365.25 * DAY;
+
+// sanity bounds for isotopes not in the table
+// real nuclei fall apart long before these (drip lines), this only keeps the nucleus packer sane
+const MAX_EXTRA_NEUTRONS: u32 = 10; // beyond N = 2 Z
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DecayMode {
+    Alpha,           // spits out a helium-4 nucleus
+    BetaMinus,       // neutron -> proton + electron + antineutrino
+    BetaPlus,        // proton -> neutron + positron + neutrino
+    ElectronCapture, // proton eats an inner electron, becomes a neutron
+}
+
+impl DecayMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            DecayMode::Alpha => "alpha",
+            DecayMode::BetaMinus => "beta-",
+            DecayMode::BetaPlus => "beta+",
+            DecayMode::ElectronCapture => "electron capture",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Stability {
+    Stable,
+    Unstable { half_life: f64, mode: DecayMode }, // half-life in seconds, dominant decay mode
+    Unknown,                                      // not in the table
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Isotope {
+    pub protons: u32,
+    pub mass_number: u32,
+    pub stability: Stability,
+}
+
+// half-life in seconds and decay mode, None = stable
+type Decay = Option<(f64, DecayMode)>;
+
+// (Z, A, decay), sorted by Z, then A
+#[rustfmt::skip]
+const ISOTOPES: &[(u32, u32, Decay)] = &[
+    (1, 1, None), (1, 2, None), (1, 3, Some((12.32 * YEAR, DecayMode::BetaMinus))),
+    (2, 3, None), (2, 4, None),
+    (3, 6, None), (3, 7, None),
+    (4, 7, Some((53.22 * DAY, DecayMode::ElectronCapture))), (4, 9, None),
+    (4, 10, Some((1.387e6 * YEAR, DecayMode::BetaMinus))),
+    (5, 10, None), (5, 11, None),
+    (6, 11, Some((20.36 * MINUTE, DecayMode::BetaPlus))), (6, 12, None), (6, 13, None),
+    (6, 14, Some((5730.0 * YEAR, DecayMode::BetaMinus))),
+    (7, 13, Some((9.965 * MINUTE, DecayMode::BetaPlus))), (7, 14, None), (7, 15, None),
+    (8, 15, Some((122.24, DecayMode::BetaPlus))), (8, 16, None), (8, 17, None), (8, 18, None),
+    (9, 18, Some((109.77 * MINUTE, DecayMode::BetaPlus))), (9, 19, None),
+    (10, 20, None), (10, 21, None), (10, 22, None),
+    (11, 22, Some((2.602 * YEAR, DecayMode::BetaPlus))), (11, 23, None),
+    (11, 24, Some((14.96 * HOUR, DecayMode::BetaMinus))),
+    (12, 24, None), (12, 25, None), (12, 26, None),
+    (13, 26, Some((7.17e5 * YEAR, DecayMode::BetaPlus))), (13, 27, None),
+    (14, 28, None), (14, 29, None), (14, 30, None),
+    (15, 31, None), (15, 32, Some((14.27 * DAY, DecayMode::BetaMinus))),
+    (16, 32, None), (16, 35, Some((87.37 * DAY, DecayMode::BetaMinus))),
+    (17, 35, None), (17, 36, Some((3.01e5 * YEAR, DecayMode::BetaMinus))), (17, 37, None),
+    (18, 36, None), (18, 38, None), (18, 39, Some((269.0 * YEAR, DecayMode::BetaMinus))), (18, 40, None),
+    (19, 39, None), (19, 40, Some((1.248e9 * YEAR, DecayMode::BetaMinus))), (19, 41, None),
+    (20, 40, None), (20, 41, Some((9.94e4 * YEAR, DecayMode::ElectronCapture))), (20, 44, None),
+    (25, 55, None),
+    (26, 54, None), (26, 55, Some((2.744 * YEAR, DecayMode::ElectronCapture))), (26, 56, None),
+    (26, 57, None), (26, 58, None), (26, 59, Some((44.5 * DAY, DecayMode::BetaMinus))),
+    (27, 59, None), (27, 60, Some((5.271 * YEAR, DecayMode::BetaMinus))),
+    (28, 58, None), (28, 60, None),
+    (29, 63, None), (29, 65, None),
+    (30, 64, None), (30, 66, None),
+    (36, 84, None), (36, 85, Some((10.74 * YEAR, DecayMode::BetaMinus))),
+    (37, 85, None),
+    (38, 88, None), (38, 90, Some((28.79 * YEAR, DecayMode::BetaMinus))),
+    (39, 89, None), (39, 90, Some((64.0 * HOUR, DecayMode::BetaMinus))),
+    (40, 90, None),
+    (43, 98, Some((4.2e6 * YEAR, DecayMode::BetaMinus))), (43, 99, Some((2.111e5 * YEAR, DecayMode::BetaMinus))),
+    (44, 98, None), (44, 99, None),
+    (53, 127, None), (53, 129, Some((1.57e7 * YEAR, DecayMode::BetaMinus))),
+    (53, 131, Some((8.025 * DAY, DecayMode::BetaMinus))),
+    (54, 129, None), (54, 131, None), (54, 132, None), (54, 133, Some((5.247 * DAY, DecayMode::BetaMinus))),
+    (55, 133, None), (55, 137, Some((30.08 * YEAR, DecayMode::BetaMinus))),
+    (56, 137, None),
+    (60, 145, None),
+    (61, 145, Some((17.7 * YEAR, DecayMode::ElectronCapture))),
+    // heavy end: the thorium, uranium and neptunium decay chains, so decays keep going to lead
+    (81, 205, None), (81, 207, Some((4.77 * MINUTE, DecayMode::BetaMinus))),
+    (82, 204, None), (82, 205, Some((1.73e7 * YEAR, DecayMode::ElectronCapture))),
+    (82, 206, None), (82, 207, None), (82, 208, None),
+    (82, 209, Some((3.25 * HOUR, DecayMode::BetaMinus))), (82, 210, Some((22.2 * YEAR, DecayMode::BetaMinus))),
+    (82, 211, Some((36.1 * MINUTE, DecayMode::BetaMinus))), (82, 212, Some((10.64 * HOUR, DecayMode::BetaMinus))),
+    (82, 214, Some((26.8 * MINUTE, DecayMode::BetaMinus))),
+    (83, 209, Some((2.01e19 * YEAR, DecayMode::Alpha))), (83, 210, Some((5.012 * DAY, DecayMode::BetaMinus))),
+    (83, 211, Some((2.14 * MINUTE, DecayMode::Alpha))), (83, 212, Some((60.55 * MINUTE, DecayMode::BetaMinus))),
+    (83, 213, Some((45.6 * MINUTE, DecayMode::BetaMinus))), (83, 214, Some((19.9 * MINUTE, DecayMode::BetaMinus))),
+    (84, 209, Some((124.0 * YEAR, DecayMode::Alpha))), (84, 210, Some((138.376 * DAY, DecayMode::Alpha))),
+    (84, 212, Some((2.99e-7, DecayMode::Alpha))), (84, 213, Some((3.72e-6, DecayMode::Alpha))),
+    (84, 214, Some((1.643e-4, DecayMode::Alpha))), (84, 215, Some((1.781e-3, DecayMode::Alpha))),
+    (84, 216, Some((0.145, DecayMode::Alpha))), (84, 218, Some((3.10 * MINUTE, DecayMode::Alpha))),
+    (85, 217, Some((3.23e-2, DecayMode::Alpha))),
+    (86, 219, Some((3.96, DecayMode::Alpha))), (86, 220, Some((55.6, DecayMode::Alpha))),
+    (86, 222, Some((3.8235 * DAY, DecayMode::Alpha))),
+    (87, 221, Some((4.8 * MINUTE, DecayMode::Alpha))),
+    (88, 223, Some((11.43 * DAY, DecayMode::Alpha))), (88, 224, Some((3.632 * DAY, DecayMode::Alpha))),
+    (88, 225, Some((14.9 * DAY, DecayMode::BetaMinus))), (88, 226, Some((1600.0 * YEAR, DecayMode::Alpha))),
+    (88, 228, Some((5.75 * YEAR, DecayMode::BetaMinus))),
+    (89, 225, Some((9.92 * DAY, DecayMode::Alpha))), (89, 227, Some((21.77 * YEAR, DecayMode::BetaMinus))),
+    (89, 228, Some((6.15 * HOUR, DecayMode::BetaMinus))),
+    (90, 227, Some((18.7 * DAY, DecayMode::Alpha))), (90, 228, Some((1.912 * YEAR, DecayMode::Alpha))),
+    (90, 229, Some((7917.0 * YEAR, DecayMode::Alpha))), (90, 230, Some((7.54e4 * YEAR, DecayMode::Alpha))),
+    (90, 231, Some((25.52 * HOUR, DecayMode::BetaMinus))), (90, 232, Some((1.405e10 * YEAR, DecayMode::Alpha))),
+    (90, 234, Some((24.1 * DAY, DecayMode::BetaMinus))),
+    (91, 231, Some((3.276e4 * YEAR, DecayMode::Alpha))), (91, 233, Some((26.97 * DAY, DecayMode::BetaMinus))),
+    (91, 234, Some((6.70 * HOUR, DecayMode::BetaMinus))),
+    (92, 233, Some((1.592e5 * YEAR, DecayMode::Alpha))), (92, 234, Some((2.455e5 * YEAR, DecayMode::Alpha))),
+    (92, 235, Some((7.04e8 * YEAR, DecayMode::Alpha))), (92, 236, Some((2.342e7 * YEAR, DecayMode::Alpha))),
+    (92, 238, Some((4.468e9 * YEAR, DecayMode::Alpha))), (92, 240, Some((14.1 * HOUR, DecayMode::BetaMinus))),
+    (93, 237, Some((2.144e6 * YEAR, DecayMode::Alpha))), (93, 239, Some((2.356 * DAY, DecayMode::BetaMinus))),
+    (93, 240, Some((61.9 * MINUTE, DecayMode::BetaMinus))),
+    (94, 238, Some((87.7 * YEAR, DecayMode::Alpha))), (94, 239, Some((24110.0 * YEAR, DecayMode::Alpha))),
+    (94, 240, Some((6561.0 * YEAR, DecayMode::Alpha))), (94, 244, Some((8.0e7 * YEAR, DecayMode::Alpha))),
+    (95, 241, Some((432.2 * YEAR, DecayMode::Alpha))), (95, 243, Some((7370.0 * YEAR, DecayMode::Alpha))),
+];
+
+impl Isotope {
+    pub fn neutrons(&self) -> u32 {
+        self.mass_number - self.protons
+    }
+
+    pub fn name(&self) -> String {
+        // "C-14"
+        let element = elements::by_atomic_number(self.protons).expect("isotope of no element");
+        format!("{}-{}", element.symbol, self.mass_number)
+    }
+
+    pub fn is_unstable(&self) -> bool {
+        matches!(self.stability, Stability::Unstable { .. })
+    }
+
+    pub fn describe(&self) -> String {
+        // "unstable, half-life 5730 y (beta-)"
+        match self.stability {
+            Stability::Stable => "stable".to_string(),
+            Stability::Unstable { half_life, mode } => format!(
+                "unstable, half-life {} ({})",
+                format_half_life(half_life),
+                mode.name()
+            ),
+            Stability::Unknown => "no decay data".to_string(),
+        }
+    }
+}
+
+pub fn isotope(z: u32, mass_number: u32) -> Option<Isotope> {
+    // None if no nucleus like that could be built at all
+    if elements::by_atomic_number(z).is_none()
+        || mass_number < z
+        || mass_number - z > 2 * z + MAX_EXTRA_NEUTRONS
+    {
+        return None;
+    }
+    let stability = ISOTOPES
+        .iter()
+        .find(|(iz, ia, _)| *iz == z && *ia == mass_number)
+        .map_or(Stability::Unknown, |(_, _, decay)| match decay {
+            Some((half_life, mode)) => Stability::Unstable {
+                half_life: *half_life,
+                mode: *mode,
+            },
+            None => Stability::Stable,
+        });
+    Some(Isotope {
+        protons: z,
+        mass_number,
+        stability,
+    })
+}
+
+pub fn tabulated(z: u32) -> Vec<Isotope> {
+    // the isotopes of an element the table knows about, lightest first
+    ISOTOPES
+        .iter()
+        .filter(|(iz, _, _)| *iz == z)
+        .filter_map(|&(_, a, _)| isotope(z, a))
+        .collect()
+}
+
+pub fn neighbour(z: u32, mass_number: u32, heavier: bool) -> Option<Isotope> {
+    // next tabulated isotope up or down from mass_number, wrapping at both ends
+    // None if the table has nothing else for this element
+    let known = tabulated(z);
+    let next = if heavier {
+        known
+            .iter()
+            .find(|iso| iso.mass_number > mass_number)
+            .or(known.first())
+    } else {
+        known
+            .iter()
+            .rev()
+            .find(|iso| iso.mass_number < mass_number)
+            .or(known.last())
+    };
+    next.copied().filter(|iso| iso.mass_number != mass_number)
+}
+
+pub fn parse(text: &str) -> Result<(&'static Element, Option<u32>), String> {
+    // "C", "6", "C-14", "c14" or "6-14"
+    // no mass number means the element's usual isotope
+    let (name, mass) = match text.split_once('-') {
+        Some((name, mass)) => (name, Some(mass)),
+        None => match text.find(|c: char| c.is_ascii_digit()) {
+            Some(i) if i > 0 => (&text[..i], Some(&text[i..])),
+            _ => (text, None),
+        },
+    };
+    let element = match name.parse::<u32>() {
+        Ok(z) => elements::by_atomic_number(z),
+        Err(_) => elements::by_symbol(name),
+    }
+    .ok_or_else(|| format!("'{}' is not an element symbol or atomic number", name))?;
+
+    let Some(mass) = mass else {
+        return Ok((element, None));
+    };
+    let mass_number = mass
+        .parse::<u32>()
+        .map_err(|_| format!("'{}' is not a mass number", mass))?;
+    let z = element.atomic_number;
+    if isotope(z, mass_number).is_none() {
+        return Err(format!(
+            "{}-{} cannot exist, the mass number has to be between {} and {}",
+            element.symbol,
+            mass_number,
+            z,
+            3 * z + MAX_EXTRA_NEUTRONS
+        ));
+    }
+    Ok((element, Some(mass_number)))
+}
+
+fn format_half_life(seconds: f64) -> String {
+    // biggest unit that keeps the number readable, 4 significant digits
+    // scientific notation for geological times
+    let years = seconds / YEAR;
+    if years >= 1e5 {
+        return format!("{:.3e} y", years);
+    }
+    let (value, unit) = if years >= 1.0 {
+        (years, "y")
+    } else if seconds >= DAY {
+        (seconds / DAY, "d")
+    } else if seconds >= HOUR {
+        (seconds / HOUR, "h")
+    } else if seconds >= MINUTE {
+        (seconds / MINUTE, "min")
+    } else if seconds >= 1.0 {
+        (seconds, "s")
+    } else if seconds >= 1e-3 {
+        (seconds * 1e3, "ms")
+    } else {
+        (seconds * 1e6, "us")
+    };
+    let decimals = (3 - value.log10().floor() as i32).max(0) as usize;
+    format!("{:.*} {}", decimals, value, unit)
+}
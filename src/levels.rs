@@ -0,0 +1,191 @@
+use crate::locale::{self, fill}; // the captions
+use crate::photon::wavelength_color; // transition arrows take the photon's color
+use crate::simulation::{hund_filling, Atom, HC_EV_NM, MAX_FILLED_L};
+use crate::text::TextRenderer; // drawn with the hud's rectangles
+use nalgebra_glm as glm;
+
+// energy level diagram of the main atom as a sidebar on the right (Shift+E toggles it)
+// one horizontal line per shell at its bohr energy E_n (with the same crude screening the
+// photons use), log scale so hydrogen and mercury both fit
+// each line carries the orbital boxes of its s, p, d and f subshells, filled with spin
+// arrows the textbook way: subshells in order, and inside one subshell every box gets an
+// up arrow before any gets a down arrow (hund), never two the same way in a box (pauli)
+// electrons in flight between shells show as an arrow growing from one level to the other
+
+// panel layout in pixels
+const PANEL_WIDTH: f32 = 380.0;
+const MARGIN: f32 = 10.0;
+const TOP: f32 = 130.0; // below the axis gizmo
+const LEVELS_HEIGHT: f32 = 240.0; // from the outermost level down to the deepest
+const MIN_GAP: f32 = 22.0; // closer levels are pushed apart, the scale gives a little there
+
+// columns inside the panel
+const LABEL_X: f32 = 6.0;
+const JUMP_X: f32 = 100.0;
+const BOXES_X: f32 = 114.0;
+
+const BOX_WIDTH: f32 = 14.0;
+const BOX_HEIGHT: f32 = 14.0;
+const SUBSHELL_GAP: f32 = 6.0;
+
+const GROUND_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 1.0, 1.0, 1.0);
+const EXCITED_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 0.6, 0.2, 1.0);
+
+pub fn draw(text: &mut TextRenderer, atom: &Atom, width: u32) {
+    // queued into the text batch, the caller flushes
+    let x0 = width as f32 - MARGIN - PANEL_WIDTH;
+    let grey = glm::vec4(0.8, 0.8, 0.8, 1.0);
+    let energies: Vec<f32> = (0..atom.shells.len())
+        .map(|shell| atom.electron_energy(shell))
+        .collect();
+    let levels_top = TOP + 44.0;
+    let ys = level_heights(&energies, levels_top);
+    let bottom = ys.first().copied().unwrap_or(levels_top) + BOX_HEIGHT;
+
+    text.draw_rect(
+        x0,
+        TOP,
+        PANEL_WIDTH,
+        bottom - TOP,
+        &glm::vec4(0.0, 0.0, 0.0, 0.85),
+    );
+    let caption = fill("{} energy levels", &[&atom.element().symbol]);
+    text.draw_text(&caption, x0 + 6.0, TOP + 6.0, 2.0, &grey);
+    text.draw_text(
+        locale::text("E_n, log scale"),
+        x0 + 6.0,
+        TOP + 24.0,
+        1.0,
+        &grey,
+    );
+
+    for (shell_index, shell) in atom.shells.iter().enumerate() {
+        let y = ys[shell_index];
+        let energy = energies[shell_index];
+        let label = if energy.abs() < 100.0 {
+            format!("n={} {:.1} eV", shell.n, energy)
+        } else {
+            format!("n={} {:.0} eV", shell.n, energy)
+        };
+        text.draw_text(&label, x0 + LABEL_X, y - 3.0, 1.0, &grey);
+        text.draw_rect(
+            x0 + BOXES_X,
+            y,
+            PANEL_WIDTH - BOXES_X - 8.0,
+            1.0,
+            &glm::vec4(0.5, 0.5, 0.5, 1.0),
+        );
+
+        // ground state electrons first, so the excited ones end up in the last slots
+        let excited = shell
+            .electrons
+            .iter()
+            .filter(|electron| electron.decay_to.is_some())
+            .count();
+        let count = shell.electron_count();
+        let slots = spin_slots(shell.n, count);
+        let mut box_x = x0 + BOXES_X;
+        for l in 0..(shell.n as usize).min(MAX_FILLED_L + 1) {
+            for _ in 0..2 * l + 1 {
+                text.draw_rect(box_x, y - BOX_HEIGHT / 2.0, BOX_WIDTH, BOX_HEIGHT, &grey);
+                text.draw_rect(
+                    box_x + 1.0,
+                    y - BOX_HEIGHT / 2.0 + 1.0,
+                    BOX_WIDTH - 2.0,
+                    BOX_HEIGHT - 2.0,
+                    &glm::vec4(0.1, 0.1, 0.1, 1.0),
+                );
+                box_x += BOX_WIDTH + 1.0;
+            }
+            box_x += SUBSHELL_GAP;
+        }
+        for (i, &(slot, up)) in slots.iter().enumerate() {
+            let color = if i >= count - excited {
+                &EXCITED_COLOR
+            } else {
+                &GROUND_COLOR
+            };
+            let (x, tail, tip) = if up {
+                (slot + 4.5, y + 5.0, y - 5.0)
+            } else {
+                (slot + 9.5, y - 5.0, y + 5.0)
+            };
+            arrow(text, x0 + BOXES_X + x, tail, tip, color);
+        }
+        if count > slots.len() {
+            // more electrons than a shell can hold, piled up by repeated excitation
+            let extra = format!("+{}", count - slots.len());
+            text.draw_text(&extra, box_x, y - 3.0, 1.0, &EXCITED_COLOR);
+        }
+    }
+
+    // transitions in progress: the arrow grows from the old level to the new one
+    for (i, jump) in atom.jumps.iter().enumerate() {
+        let t = jump.progress.clamp(0.0, 1.0) as f32;
+        let t = t * t * (3.0 - 2.0 * t); // same easing as the 3d jump
+        let (from, to) = (ys[jump.from], ys[jump.to]);
+        let photon = (energies[jump.to] - energies[jump.from]).abs();
+        let c = wavelength_color(HC_EV_NM / photon.max(1e-3));
+        let x = x0 + JUMP_X + (i % 3) as f32 * 4.0;
+        arrow(
+            text,
+            x,
+            from,
+            from + (to - from) * t,
+            &glm::vec4(c.x, c.y, c.z, 1.0),
+        );
+    }
+}
+
+fn level_heights(energies: &[f32], top: f32) -> Vec<f32> {
+    // screen y of every level, deepest (shell 0) at the bottom
+    // log |E| keeps every level apart, a linear axis squashes all but the innermost of a
+    // heavy atom into one line at the top
+    let logs: Vec<f32> = energies.iter().map(|e| e.abs().max(1e-3).log10()).collect();
+    let (low, high) = logs.iter().fold((f32::MAX, f32::MIN), |(low, high), &v| {
+        (low.min(v), high.max(v))
+    });
+    let span = high - low;
+    let mut ys: Vec<f32> = logs
+        .iter()
+        .map(|&v| {
+            if span > 1e-6 {
+                top + LEVELS_HEIGHT * (v - low) / span
+            } else {
+                top + LEVELS_HEIGHT
+            }
+        })
+        .collect();
+    // from the top down, keep every line clear of the one above it
+    for i in (0..ys.len().saturating_sub(1)).rev() {
+        ys[i] = ys[i].max(ys[i + 1] + MIN_GAP);
+    }
+    ys
+}
+
+fn spin_slots(n: u32, count: usize) -> Vec<(f32, bool)> {
+    // x offset of the box (from the first box of the level) and spin of every electron
+    // on a level, in filling order; at most as many as its boxes have room for
+    let subshell_x = |l: usize| {
+        (0..l)
+            .map(|below| (2 * below + 1) as f32 * (BOX_WIDTH + 1.0) + SUBSHELL_GAP)
+            .sum::<f32>()
+    };
+    hund_filling(n, count)
+        .into_iter()
+        .map(|(l, orbital, up)| (subshell_x(l) + orbital as f32 * (BOX_WIDTH + 1.0), up))
+        .collect()
+}
+
+pub fn arrow(text: &mut TextRenderer, x: f32, tail: f32, tip: f32, color: &glm::Vec4) {
+    // vertical arrow centered on x, pointing from tail to tip (either way)
+    let direction = if tip < tail { 1.0 } else { -1.0 }; // from the tip back to the tail
+    let (top, bottom) = (tail.min(tip), tail.max(tip));
+    text.draw_rect(x - 1.0, top, 2.0, (bottom - top).max(1.0), color);
+    // head: rows 2, 4 and 6 px wide, narrowest at the tip
+    for row in 0..3 {
+        let half = 1.0 + row as f32;
+        let y = tip + direction * row as f32 - if direction > 0.0 { 0.0 } else { 1.0 };
+        text.draw_rect(x - half, y, 2.0 * half, 1.0, color);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::elements::{self, Element}; // element names are translated too
+use serde::Deserialize; // locale file format
+use std::collections::HashMap;
+use std::fmt; // pretty errors
+use std::fs;
+use std::sync::OnceLock; // loaded once at startup, read from everywhere
+
+// translations of the control panel, the hud captions and the element names
+// assets/locales/<language>.toml, the language from window.language in
+// atomsim.toml, or from the system (LC_ALL, LC_MESSAGES, LANG) when that is "auto":
+//
+//   [elements]
+//   H = "Wasserstoff"
+//
+//   [text]
+//   "simulation speed" = "Simulationsgeschwindigkeit"
+//   "{} energy levels" = "Energieniveaus von {}"
+//
+// the english text in the code is the key, gettext style, so anything not in the file (or
+// no file at all) just stays english; {} are filled in order, see fill
+// console output stays english, it goes to whoever runs the program, not to the class
+
+const DIRECTORY: &str = "assets/locales";
+const ENGLISH: &str = "en";
+
+#[derive(Debug)]
+pub enum LocaleError {
+    Io { path: String, message: String },
+    Parse { path: String, message: String }, // not valid toml, or not a table of strings
+    Invalid { path: String, message: String }, // an element symbol that does not exist
+}
+
+impl fmt::Display for LocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocaleError::Io { path, message } => {
+                write!(f, "could not read locale '{}': {}", path, message)
+            }
+            LocaleError::Parse { path, message } => {
+                write!(f, "invalid locale file '{}':\n{}", path, message)
+            }
+            LocaleError::Invalid { path, message } => {
+                write!(f, "invalid locale file '{}': {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocaleError {}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct Locale {
+    #[serde(default)]
+    elements: HashMap<String, String>, // symbol -> name
+    #[serde(default)]
+    text: HashMap<String, String>, // english -> translation
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+pub fn init(language: &str) {
+    // once, before anything is drawn; english for "en", a missing file or a broken one
+    let language = match language {
+        "auto" => system_language(),
+        language => language.to_string(),
+    };
+    let locale = if language == ENGLISH {
+        Locale::default()
+    } else {
+        let path = format!("{}/{}.toml", DIRECTORY, language);
+        if !std::path::Path::new(&path).exists() {
+            log::info!(
+                "no translation for '{}' ({}), staying english",
+                language,
+                path
+            );
+            Locale::default()
+        } else {
+            load(&path).unwrap_or_else(|err| {
+                log::warn!("{}\nstaying english", err);
+                Locale::default()
+            })
+        }
+    };
+    let _ = LOCALE.set(locale);
+}
+
+fn load(path: &str) -> Result<Locale, LocaleError> {
+    let source = fs::read_to_string(path).map_err(|err| LocaleError::Io {
+        path: path.to_string(),
+        message: err.to_string(),
+    })?;
+    let locale: Locale = toml::from_str(&source).map_err(|err| LocaleError::Parse {
+        path: path.to_string(),
+        message: err.to_string(),
+    })?;
+    if let Some(symbol) = locale
+        .elements
+        .keys()
+        .find(|symbol| elements::by_symbol(symbol).is_none())
+    {
+        return Err(LocaleError::Invalid {
+            path: path.to_string(),
+            message: format!("'{}' is not an element symbol", symbol),
+        });
+    }
+    log::info!(
+        "loaded {} ({} texts, {} elements)",
+        path,
+        locale.text.len(),
+        locale.elements.len()
+    );
+    Ok(locale)
+}
+
+fn system_language() -> String {
+    // "de_DE.UTF-8" -> "de", the first of these that is set says it; C and POSIX are english
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .split(['_', '.', '@'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase()
+        })
+        .filter(|language| !language.is_empty() && language != "c" && language != "posix")
+        .unwrap_or_else(|| ENGLISH.to_string())
+}
+
+pub fn text(english: &'static str) -> &'static str {
+    // before init, and for anything untranslated, the english itself
+    LOCALE
+        .get()
+        .and_then(|locale| locale.text.get(english))
+        .map_or(english, String::as_str)
+}
+
+pub fn fill(english: &'static str, values: &[&dyn fmt::Display]) -> String {
+    // text with every {} replaced by the next value, wherever the translation put them
+    let mut values = values.iter();
+    let mut parts = text(english).split("{}");
+    let mut filled = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        if let Some(value) = values.next() {
+            filled.push_str(&value.to_string());
+        }
+        filled.push_str(part);
+    }
+    filled
+}
+
+pub fn element_name(element: &Element) -> &'static str {
+    LOCALE
+        .get()
+        .and_then(|locale| locale.elements.get(element.symbol))
+        .map_or(element.name, String::as_str)
+}
@@ -0,0 +1,86 @@
+use crate::quality::Quality; // fewer or more triangles per level
+use crate::render::{generate_sphere, InstancedRenderer, VertexArray};
+use nalgebra_glm as glm;
+
+// level of detail for spheres
+// a nucleon 3 pixels across looks the same with 40 triangles as with 4000
+// so keep a few tessellations of the unit sphere around and give every instance
+// the cheapest one that still looks round at its size on screen
+
+// (sectors, stacks, smallest projected radius in pixels that gets this level)
+// ordered finest first, the last level catches everything smaller
+// these are for high quality, the others scale the meshes, see Quality::tessellation
+const LEVELS: [(i32, i32, f32); 4] = [(48, 32, 40.0), (24, 16, 12.0), (12, 8, 4.0), (6, 4, 0.0)];
+
+pub struct SphereLod {
+    // one instanced renderer per level, so each level is still a single draw call
+    levels: Vec<(f32, InstancedRenderer)>,
+}
+
+impl SphereLod {
+    pub unsafe fn new(quality: Quality) -> Self {
+        let levels = LEVELS
+            .iter()
+            .map(|&(sectors, stacks, min_radius)| {
+                let (sectors, stacks) = quality.tessellation(sectors, stacks);
+                let mesh = generate_sphere(1.0, sectors, stacks);
+                (
+                    min_radius,
+                    InstancedRenderer::new(VertexArray::from_mesh(&mesh)),
+                )
+            })
+            .collect();
+        Self { levels }
+    }
+
+    pub fn clear(&mut self) {
+        for (_, renderer) in &mut self.levels {
+            renderer.clear();
+        }
+    }
+
+    pub fn push(&mut self, model: &glm::Mat4, color: &glm::Vec4, screen_radius: f32) {
+        // queue a unit sphere instance on the level its on-screen size asks for
+        let index = self
+            .levels
+            .iter()
+            .position(|(min_radius, _)| screen_radius >= *min_radius)
+            .unwrap_or(self.levels.len() - 1);
+        self.levels[index].1.push(model, color);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels
+            .iter()
+            .all(|(_, renderer)| renderer.instance_count() == 0)
+    }
+
+    pub unsafe fn draw(&mut self) {
+        for (_, renderer) in &mut self.levels {
+            renderer.draw();
+        }
+    }
+}
+
+pub fn projected_radius(
+    model: &glm::Mat4,
+    view: &glm::Mat4,
+    projection: &glm::Mat4,
+    viewport_height: u32,
+) -> f32 {
+    // approximate radius in pixels of a unit sphere transformed by model
+    // world radius = length of the model's x axis (spheres are scaled uniformly)
+    // perspective: size shrinks with distance along the view direction
+    // orthographic (bottom row 0 0 0 1): distance does not matter
+    let radius = glm::length(&model.column(0).xyz());
+    let center = view * model.column(3);
+    let depth = if projection[(3, 3)] == 1.0 {
+        1.0
+    } else {
+        (-center.z).max(1e-3)
+    };
+
+    // projection[(1, 1)] maps view space height to ndc (cot(fov / 2) for perspective)
+    // ndc spans 2 units across the viewport
+    radius * projection[(1, 1)] / depth * viewport_height as f32 * 0.5
+}
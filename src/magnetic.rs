@@ -0,0 +1,233 @@
+use crate::gizmo; // turns the unit arrow onto the moment
+use crate::render::{
+    generate_arrow, srgb_to_linear, ShaderError, ShaderProgram, VertexArray, WatchedProgram,
+};
+use crate::scene::Scene;
+use crate::simulation::{Atom, Shell};
+use crate::text::TextRenderer; // labels go into the hud text batch
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+
+// magnetic overlay (Shift+M, bohr view): every shell's electrons going round are a current
+// loop, and a current loop is a little magnet
+// loops: field lines of a shell's current through the meridian of some of its electrons,
+//        closing around the orbit, they turn with the electrons that carry them
+// arrow: the atom's net orbital magnetic moment through the nucleus, labelled in bohr
+//        magnetons; bohr's model gives an electron on shell n n magnetons along its orbit's
+//        axis, with repulsion on an electron off the ring counts with its squared distance
+//        from the axis, like any current loop of that size
+// in the drawing every electron of a shell goes round the same way, so their moments add
+// up; in a real atom filled subshells cancel and the net moment is a matter of the last few
+// electrons, read the arrow as what this picture's currents amount to
+
+const LOOP_SEEDS: [f32; 2] = [0.7, 0.85]; // where the lines cross the orbit plane, inside the ring
+const LOOPS_PER_SHELL: usize = 4; // electrons carrying lines, a full shell would be a tangle
+const LOOP_OPACITY: f32 = 0.4;
+const WIRE_SEGMENTS: usize = 64; // the ring in the biot-savart sum
+const TRACE_STEP: f32 = 0.004; // orbit radii
+const MAX_TRACE_STEPS: usize = 20000;
+const KEEP_EVERY: usize = 8; // traced points per drawn vertex
+
+const ARROW_COLOR: glm::Vec3 = glm::Vec3::new(0.85, 0.5, 1.0);
+const ARROW_LENGTH: f32 = 2.0; // world units for one magneton, see arrow_length
+
+pub fn moment(atom: &Atom) -> glm::Vec3 {
+    // atom space, bohr magnetons
+    // electrons between shells are on neither orbit and are left out until they land
+    atom.shells
+        .iter()
+        .map(|shell| {
+            let axis = (shell.plane_rotation() * glm::vec4(0.0, 1.0, 0.0, 0.0)).xyz();
+            let along: f32 = shell
+                .electrons
+                .iter()
+                .map(|electron| {
+                    let d = electron.direction;
+                    (d.x * d.x + d.z * d.z) as f32 * shell.n as f32
+                })
+                .sum();
+            axis * along
+        })
+        .fold(glm::vec3(0.0, 0.0, 0.0), |sum, shell| sum + shell)
+}
+
+fn arrow_length(magnetons: f32) -> f32 {
+    // slower than the moment, so uranium's hundreds of magnetons still fit in its atom
+    ARROW_LENGTH * magnetons.powf(0.4)
+}
+
+fn ring_field(p: &glm::Vec3) -> glm::Vec3 {
+    // biot-savart of a unit ring in the xz plane, current running opposite to the electrons
+    // (they go with rising phase), constants left out, only the direction is traced
+    let mut b = glm::vec3(0.0, 0.0, 0.0);
+    for i in 0..WIRE_SEGMENTS {
+        let angle = 2.0 * PI * (i as f32 + 0.5) / WIRE_SEGMENTS as f32;
+        let at = glm::vec3(angle.cos(), 0.0, angle.sin());
+        let dl = glm::vec3(angle.sin(), 0.0, -angle.cos()) * (2.0 * PI / WIRE_SEGMENTS as f32);
+        let r = p - at;
+        let d = glm::length(&r).max(1e-4);
+        b += glm::cross(&dl, &r) / (d * d * d);
+    }
+    b
+}
+
+fn field_loop(seed: f32) -> Vec<f32> {
+    // one closed field line of the unit ring, in the meridian at phase 0 (the xy half plane),
+    // from a point inside the ring round the wire and back
+    // the ring is symmetric about its axis, so this one line turned to an electron's phase
+    // and scaled to its shell is that electron's line
+    let start = glm::vec3(seed, 0.0, 0.0);
+    let mut p = start;
+    let mut points = vec![p];
+    let mut below = false; // been under the plane, on the way back
+    for step in 0..MAX_TRACE_STEPS {
+        // runge-kutta 4 along the unit direction of b
+        let direction = |p: &glm::Vec3| glm::normalize(&ring_field(p));
+        let k1 = direction(&p);
+        let k2 = direction(&(p + k1 * (0.5 * TRACE_STEP)));
+        let k3 = direction(&(p + k2 * (0.5 * TRACE_STEP)));
+        let k4 = direction(&(p + k3 * TRACE_STEP));
+        let next = p + (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (TRACE_STEP / 6.0);
+        below |= next.y < 0.0;
+        // up through the plane inside the ring again: the loop is closed
+        if below && p.y < 0.0 && next.y >= 0.0 && next.x < 1.0 {
+            break;
+        }
+        p = glm::vec3(next.x, next.y, 0.0); // stays in its meridian, the sum's noise aside
+        if step % KEEP_EVERY == 0 {
+            points.push(p);
+        }
+    }
+    points.iter().flat_map(|p| [p.x, p.y, p.z]).collect()
+}
+
+pub struct MagneticOverlay {
+    program: WatchedProgram, // the gizmo shader, single color
+    loops: Vec<VertexArray>, // one per LOOP_SEEDS, unit ring
+    arrow: VertexArray,      // unit arrow along +y
+}
+
+impl MagneticOverlay {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        Ok(Self {
+            program: WatchedProgram::new("gizmo", ShaderProgram::gizmo)?,
+            loops: LOOP_SEEDS
+                .iter()
+                .map(|&seed| VertexArray::new(&field_loop(seed), &[3]))
+                .collect(),
+            arrow: VertexArray::from_mesh(&generate_arrow(1.0, 0.035, 0.09, 0.25, 16)),
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn draw(&self, scene: &Scene, view: &glm::Mat4, projection: &glm::Mat4) {
+        self.program.use_program();
+        self.program.set_uniform_mat4("view", view);
+        self.program.set_uniform_mat4("projection", projection);
+
+        // the arrows are solid, they stick out of the nucleus like a bar magnet
+        let c = srgb_to_linear(&ARROW_COLOR);
+        self.program
+            .set_uniform_vec4("color", &glm::vec4(c.x, c.y, c.z, 1.0));
+        self.arrow.bind();
+        for scene_atom in &scene.atoms {
+            // turned with its atom, like everything else it carries
+            let moment = glm::quat_rotate_vec3(&scene_atom.orientation, &moment(&scene_atom.atom));
+            let magnetons = glm::length(&moment);
+            if magnetons < 1e-3 {
+                continue;
+            }
+            let direction = moment / magnetons;
+            let length = arrow_length(magnetons);
+            let thickness = length.sqrt();
+            let model = glm::translation(&(scene_atom.position - direction * (0.5 * length)))
+                * gizmo::arrow_model(&direction)
+                * glm::scaling(&glm::vec3(thickness, length, thickness));
+            self.program.set_uniform_mat4("model", &model);
+            self.arrow.draw(gl::TRIANGLES);
+        }
+
+        // loops blended and without depth writes, like the shell rings
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::DepthMask(gl::FALSE);
+        for scene_atom in &scene.atoms {
+            for shell in &scene_atom.atom.shells {
+                if shell.electron_count() == 0 || !scene.display.shell_visible(shell.n) {
+                    continue;
+                }
+                let c = srgb_to_linear(&scene.display.theme.trail(shell.n));
+                self.program
+                    .set_uniform_vec4("color", &glm::vec4(c.x, c.y, c.z, LOOP_OPACITY));
+                for model in loop_models(&scene_atom.model(), shell) {
+                    self.program.set_uniform_mat4("model", &model);
+                    for line in &self.loops {
+                        line.bind();
+                        line.draw(gl::LINE_LOOP);
+                    }
+                }
+            }
+        }
+        gl::BindVertexArray(0);
+        gl::DepthMask(gl::TRUE);
+        gl::Disable(gl::BLEND);
+    }
+}
+
+fn loop_models(atom_model: &glm::Mat4, shell: &Shell) -> Vec<glm::Mat4> {
+    // the unit meridian turned to the phase of every few electrons, scaled to the orbit
+    let count = shell.electron_count();
+    let r = shell.radius as f32;
+    (0..count.min(LOOPS_PER_SHELL))
+        .map(|i| {
+            let electron = &shell.electrons[i * count / count.min(LOOPS_PER_SHELL)];
+            let d = electron.direction;
+            let phase = d.z.atan2(d.x) as f32;
+            // rotating about y by -phase takes +x to (cos, 0, sin), where the electron is
+            atom_model
+                * shell.plane_rotation()
+                * glm::rotation(-phase, &glm::vec3(0.0, 1.0, 0.0))
+                * glm::scaling(&glm::vec3(r, r, r))
+        })
+        .collect()
+}
+
+pub fn draw_labels(text: &mut TextRenderer, scene: &Scene, width: u32, height: u32) {
+    // the moment next to the tip of each arrow, queued into the text batch, the caller flushes
+    // width and height in hud pixels
+    let view_projection = scene.camera.get_projection_matrix() * scene.camera.get_view_matrix();
+    let color = glm::vec4(ARROW_COLOR.x, ARROW_COLOR.y, ARROW_COLOR.z, 1.0);
+    for scene_atom in &scene.atoms {
+        let moment = glm::quat_rotate_vec3(&scene_atom.orientation, &moment(&scene_atom.atom));
+        let magnetons = glm::length(&moment);
+        if magnetons < 1e-3 {
+            continue;
+        }
+        let tip = scene_atom.position + moment / magnetons * (0.5 * arrow_length(magnetons));
+        let Some((x, y)) = screen_point(&view_projection, &tip, width, height) else {
+            continue;
+        };
+        let label = format!("{:.1} mu_B", magnetons);
+        text.draw_text(&label, x + 6.0, y - 4.0, 1.0, &color);
+    }
+}
+
+fn screen_point(
+    view_projection: &glm::Mat4,
+    p: &glm::Vec3,
+    width: u32,
+    height: u32,
+) -> Option<(f32, f32)> {
+    // hud pixels, y down; None behind the camera
+    let clip = view_projection * glm::vec4(p.x, p.y, p.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    Some((
+        (clip.x / clip.w * 0.5 + 0.5) * width as f32,
+        (0.5 - clip.y / clip.w * 0.5) * height as f32,
+    ))
+}
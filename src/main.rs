@@ -1,15 +1,105 @@
-use glfw::{Action, Context, Key};
+use clap::Parser; // Cli::parse
+use glfw::Action;
 use nalgebra_glm as glm;
-use std::ffi::CString;
-use std::fs;
 use std::io::{self, Write};
-use std::sync::{Arc, Mutex};
 
+#[macro_use]
+mod gl_debug; // first, every module after it can use check_gl_error!
+mod absorption;
+mod arcball;
+mod audio;
+mod aufbau;
+mod background;
+mod benchmark;
+mod bloom;
 mod camera;
+mod capture;
+mod cli;
+mod compare;
+mod config;
+mod console;
+mod decay;
+mod dof;
+mod ecs;
+mod elements;
+mod export;
+mod field;
+mod framebuffer;
+mod frustum;
+mod fxaa;
+mod gamepad;
+mod gamma;
+mod gizmo;
+mod ground;
+mod history;
+mod import;
+mod input;
+mod isotopes;
+mod levels;
+mod locale;
+mod lod;
+mod magnetic;
+mod material;
+mod molecule;
+mod nucleus;
+mod orbital;
+mod particles;
+mod periodic;
+mod photon;
 mod physics;
+mod profiler;
+mod quality;
+mod radial;
+mod recorder;
 mod render;
+mod renderer;
+mod save;
+mod scale;
+mod scattering;
+mod scene;
+mod script;
+mod shadow;
+mod simulation;
+mod slice;
+mod smiles;
+mod spectrum;
+mod sprites;
+mod ssao;
+mod stats;
+mod stereo;
+mod style;
+mod text;
+mod texture;
+mod theme;
+mod thermal;
+mod timer;
+mod tonemap;
+mod trackball;
+mod trail;
+mod transparency;
+#[cfg(feature = "ui")]
+mod ui;
+mod vibration;
+mod vsepr;
+mod window;
+use audio::{Audio, Sound};
+use benchmark::Benchmark;
 use camera::Camera;
-use render::{generate_sphere, ShaderProgram, VertexArray};
+use cli::Cli;
+use config::Config;
+use console::Console;
+use gamepad::Gamepad;
+use input::Bindings;
+use molecule::Editor;
+use orbital::{Orbital, OrbitalCloud};
+use periodic::PeriodicTable;
+use recorder::Recorder;
+use renderer::Renderer;
+use scene::{BondOrder, Scene};
+use script::Script;
+use simulation::Atom;
+use timer::FrameTimer;
+use window::{Window, WindowSettings};
 
 // Helper function to get user input from the terminal
 fn get_quantum_number(prompt: &str, default: i32) -> i32 {
@@ -35,12 +125,192 @@ fn get_quantum_number(prompt: &str, default: i32) -> i32 {
     }
 }
 
+// Helper to read one raw line, empty = default of nothing
+fn get_line(prompt: &str) -> String {
+    print!("{} (default: none): ", prompt);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read line");
+    input.trim().to_string()
+}
+
+// Helper to turn a space separated list of element symbols into elements
+// isotopes work too ("C-14"), a bare symbol is the usual isotope (None)
+// unknown symbols are reported and skipped, empty input = no extras
+fn parse_element_list(input: &str) -> Vec<(&'static elements::Element, Option<u32>)> {
+    input
+        .split_whitespace()
+        .filter_map(|token| match isotopes::parse(token) {
+            Ok(parsed) => Some(parsed),
+            Err(message) => {
+                println!("{}, skipping.", message);
+                None
+            }
+        })
+        .collect()
+}
+
+// "1-2 1=3" -> [(0, 1, Single), (0, 2, Double)]
+// atom numbers are 1-based for humans, 0-based for the scene
+fn parse_bonds(input: &str, atom_count: usize) -> Vec<(usize, usize, BondOrder)> {
+    let mut bonds = Vec::new();
+
+    for token in input.split_whitespace() {
+        let parsed = token
+            .char_indices()
+            .find_map(|(i, c)| BondOrder::from_symbol(c).map(|order| (i, order)))
+            .and_then(|(i, order)| {
+                let a: usize = token[..i].parse().ok()?;
+                let b: usize = token[i + 1..].parse().ok()?;
+                let valid =
+                    (1..=atom_count).contains(&a) && (1..=atom_count).contains(&b) && a != b;
+                valid.then_some((a - 1, b - 1, order))
+            });
+
+        match parsed {
+            Some(bond) => bonds.push(bond),
+            None => println!("Invalid bond '{}', skipping.", token),
+        }
+    }
+
+    bonds
+}
+
+fn neighbour_element(z: u32, next: bool) -> &'static elements::Element {
+    // one step through the periodic table, wrapping at both ends
+    let count = elements::ELEMENTS.len() as u32;
+    let z = if next {
+        z % count + 1
+    } else {
+        (z + count - 2) % count + 1
+    };
+    elements::by_atomic_number(z).unwrap()
+}
+
+fn print_atom_info(atom: &Atom) {
+    let element = atom.element();
+    println!(
+        "Bohr atom: {} ({}), Z={}, N={}, mass {} u, {} electrons",
+        element.name,
+        element.symbol,
+        atom.protons,
+        atom.neutrons,
+        element.atomic_mass,
+        atom.electron_count()
+    );
+    let isotope = atom.isotope();
+    println!("  isotope {}: {}", isotope.name(), isotope.describe());
+    if let Some(ion) = atom.ion_label() {
+        println!("  ion {}: charge {:+} e", ion, atom.charge());
+    }
+    for shell in &atom.shells {
+        println!(
+            "  shell n={}: {} electrons",
+            shell.n,
+            shell.electron_count()
+        );
+    }
+}
+
+// the atoms to place: element + mass number (None = usual isotope), main atom first
+type AtomList = Vec<(&'static elements::Element, Option<u32>)>;
+
+// the interactive way to pick atoms: main element and isotope, extras, bonds
+fn prompt_atoms(config: &Config) -> (AtomList, String) {
+    let z = loop {
+        let z = get_quantum_number(
+            "Atomic number (Z) for the Bohr view",
+            config.element().atomic_number as i32,
+        );
+        if (1..=elements::ELEMENTS.len() as i32).contains(&z) {
+            break z as u32;
+        }
+        println!(
+            "Atomic number must be between 1 and {}.",
+            elements::ELEMENTS.len()
+        );
+    };
+
+    let element = elements::by_atomic_number(z).unwrap();
+    let usual = z + element.neutron_count();
+    let mass_number = loop {
+        let a = get_quantum_number("Mass number (A), e.g. 14 for C-14", usual as i32);
+        if a >= 0 && isotopes::isotope(z, a as u32).is_some() {
+            break a as u32;
+        }
+        println!(
+            "No {} nucleus with {} nucleons, pick a mass number from {} up.",
+            element.symbol, a, z
+        );
+    };
+
+    let mut atoms: AtomList = vec![(element, Some(mass_number))];
+    atoms.extend(parse_element_list(&get_line(
+        "Extra atoms to place beside it (symbols or isotopes, e.g. 'H H-2 O')",
+    )));
+    let bond_input = if atoms.len() == 1 {
+        String::new()
+    } else {
+        get_line("Bonds by atom number, - single = double # triple (e.g. '1-2 1-3')")
+    };
+    (atoms, bond_input)
+}
+
+// print a fatal setup error and quit without a panic backtrace
+fn exit_with_error(err: &dyn std::error::Error) -> ! {
+    eprintln!("error: {}", err);
+    std::process::exit(1);
+}
+
 fn main() {
+    // diagnostics go through the log crate, RUST_LOG=debug (or =atom_sim::render=debug, ...)
+    // for more, interactive output stays plain println
+    env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("warn,atom_sim=info"),
+    )
+    .init();
+
+    // flags first: --help should not have to wait for anything
+    let cli = Cli::parse();
+    let headless = cli.headless;
+    let benchmark = cli.benchmark;
+    let msaa = cli.msaa;
+    // optional startup settings, see config.rs for the format
+    let config = Config::load_or_default("atomsim.toml");
+    locale::init(&config.window.language);
+    // a broken script should fail before any prompt, not halfway into the demo
+    let script = cli
+        .script
+        .as_deref()
+        .map(|path| Script::load(path).unwrap_or_else(|err| exit_with_error(&err)));
+
     // --- Get Quantum Numbers from User ---
-    println!("Enter initial quantum numbers for the simulation.");
-    let n = get_quantum_number("Principal quantum number (n)", 2);
-    let l = get_quantum_number("Azimuthal quantum number (l)", 1);
-    let m = get_quantum_number("Magnetic quantum number (m)", 0);
+    // only asked for what the command line left open, a benchmark asks nothing
+    let benchmark_orbital = benchmark.map(|_| (2, 1, 0));
+    let (n, l, m) = cli.orbital.or(benchmark_orbital).unwrap_or_else(|| {
+        println!("Enter initial quantum numbers for the simulation.");
+        (
+            get_quantum_number("Principal quantum number (n)", 2),
+            get_quantum_number("Azimuthal quantum number (l)", 1),
+            get_quantum_number("Magnetic quantum number (m)", 0),
+        )
+    });
+
+    // --element means the atoms come from the command line, no prompts at all
+    let (placed, bond_input) = match cli.element {
+        Some(main_atom) => {
+            let mut atoms: AtomList = vec![main_atom];
+            atoms.extend(parse_element_list(cli.extra.as_deref().unwrap_or("")));
+            (atoms, cli.bonds.clone().unwrap_or_default())
+        }
+        // brings its own, see benchmark::stress_scene
+        None if benchmark.is_some() => (Vec::new(), String::new()),
+        // and so does a molecule file, opened below
+        None if cli.open.is_some() => (Vec::new(), String::new()),
+        None => prompt_atoms(&config),
+    };
 
     // --- Set initial physics state ---
     *physics::N.lock().unwrap() = n;
@@ -48,131 +318,854 @@ fn main() {
     *physics::M.lock().unwrap() = m;
 
     // --- Standard Setup ---
-    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
-    let (win_width, win_height) = (1280, 720);
-    let (mut window, events) = glfw
-        .create_window(
-            win_width as u32,
-            win_height as u32,
-            "Atom Simulator",
-            glfw::WindowMode::Windowed,
-        )
-        .expect("Failed to create GLFW window.");
-    window.make_current();
-    window.set_key_polling(true);
-    window.set_cursor_pos_polling(true);
-    window.set_mouse_button_polling(true);
-    window.set_scroll_polling(true);
-    gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+    let (win_width, win_height) = cli
+        .resolution
+        .unwrap_or((config.window.width, config.window.height));
+    // debug output only fires on a debug context
+    let gl_debug = cli.gl_debug || cfg!(debug_assertions);
+    let mut window = Window::new(&WindowSettings {
+        width: win_width,
+        height: win_height,
+        fullscreen: cli.fullscreen && headless.is_none(),
+        // still need a window for the gl context when headless, just never show it
+        visible: headless.is_none(),
+        samples: msaa,
+        debug_context: gl_debug,
+        // a benchmark capped at the refresh rate would measure the monitor
+        vsync: config.window.vsync && benchmark.is_none(),
+    })
+    .unwrap_or_else(|err| exit_with_error(&err));
+    unsafe { gl_debug::log_context_info() };
+    if gl_debug {
+        unsafe { gl_debug::enable() };
+    }
 
-    // --- Create rendering objects ---
-    let shader_program = unsafe {
-        let vs_src = CString::new(
-            fs::read_to_string("src/vertex_shader.glsl")
-                .unwrap()
-                .as_bytes(),
-        )
-        .unwrap();
-        let fs_src = CString::new(
-            fs::read_to_string("src/fragment_shader.glsl")
-                .unwrap()
-                .as_bytes(),
-        )
-        .unwrap();
-        ShaderProgram::new(&vs_src, &fs_src)
-    };
-    let sphere_vertices = generate_sphere(1.0, 10, 10);
-    let sphere = unsafe { VertexArray::new(&sphere_vertices) };
+    // the hint is only a wish, check what the driver actually gave us
+    let samples = unsafe { renderer::framebuffer_samples() };
+    if samples > 0 {
+        log::info!("antialiasing: {}x MSAA", samples);
+    } else {
+        log::info!("antialiasing: MSAA unavailable, using FXAA");
+    }
 
     // --- Create Particles ---
     println!("\nGenerating particle set for n={}, l={}, m={}...", n, l, m);
-    let particles = physics::generate_particles(100000);
+    let particles = physics::generate_particles(config.simulation.cloud_particles);
     println!("Done.");
 
-    // --- Create Camera ---
-    let camera = Arc::new(Mutex::new(Camera::new(glm::vec3(0.0, 0.0, 0.0), 30.0)));
+    println!("Sampling real orbital point cloud...");
+    let orbital = Orbital::new(n, l, m);
+    let orbital_cloud = unsafe {
+        OrbitalCloud::sample(
+            orbital,
+            config.simulation.orbital_samples,
+            config.simulation.gpu_sampling,
+        )
+    };
+    if orbital_cloud.is_empty() {
+        println!(
+            "No samples for n={}, l={}, m={} (invalid quantum numbers?)",
+            n, l, m
+        );
+    }
+    println!("Done.");
 
-    // --- Create CStrings for uniform names ---
-    let color_name = CString::new("ourColor").unwrap();
-    let model_name = CString::new("model").unwrap();
-    let view_name = CString::new("view").unwrap();
-    let proj_name = CString::new("projection").unwrap();
+    // --- Create Bohr Atoms ---
+    // main atom at the origin, extras lined up along +x with a small gap between outer shells
+    let mut scene = Scene::new(Camera::new(glm::vec3(0.0, 0.0, 0.0), 30.0), particles);
+    config.apply_display(&mut scene.display);
+    scene
+        .time
+        .set_scale(cli.speed.unwrap_or(config.simulation.time_scale));
+    scene.repulsion = config.simulation.repulsion;
+    scene.vibration = config.simulation.vibration;
+    scene.temperature = config.simulation.temperature;
+    scene.orbital = orbital;
+    if let Some(mode) = cli.mode {
+        scene.view_mode = mode.into();
+    }
 
-    // Enable Depth Test
-    unsafe {
-        gl::Enable(gl::DEPTH_TEST);
+    let mut cursor = 0.0;
+    let mut previous_radius: Option<f32> = None;
+    for (element, mass_number) in placed {
+        let radius = Atom::from_atomic_number(element.atomic_number)
+            .unwrap()
+            .outer_radius();
+        if let Some(previous) = previous_radius {
+            cursor += previous + radius + 2.0;
+        }
+        previous_radius = Some(radius);
+
+        let index = scene.add_atom(element, glm::vec3(cursor, 0.0, 0.0));
+        if let Some(mass_number) = mass_number {
+            // checked when parsed, so this always takes
+            scene.set_isotope(index, mass_number);
+        }
+        print_atom_info(&scene.atoms[index].atom);
     }
 
+    for (a, b, order) in parse_bonds(&bond_input, scene.atoms.len()) {
+        scene.add_bond(a, b, order);
+    }
+    scene.history.clear(); // the isotopes above are the starting scene, nothing to undo
+    if benchmark.is_some() {
+        benchmark::stress_scene(&mut scene);
+    }
+    if let Some(path) = &cli.open {
+        let count = import::open(&mut scene, path).unwrap_or_else(|err| exit_with_error(&err));
+        println!("Opened {} atoms from {}", count, path);
+    }
+
+    // frame the whole row
+    let extent = cursor + 2.0 * previous_radius.unwrap_or(0.0);
+    scene.camera = Camera::new(scene.center(), extent.max(30.0));
+    config.apply_camera(&mut scene.camera);
+    let (fb_width, fb_height) = window.get_framebuffer_size();
+    scene.camera.projection.resize(fb_width, fb_height);
+    if cli.compare {
+        scene.toggle_comparison();
+    }
+
+    // --- Create rendering objects ---
+    let mut renderer = unsafe {
+        Renderer::new(
+            &scene,
+            orbital_cloud,
+            config.background.skybox.as_deref(),
+            &config.texture_files(),
+        )
+    }
+    .unwrap_or_else(|err| exit_with_error(&err));
+
+    let mut tools = Tools {
+        screenshot_requested: false,
+        recorder: Recorder::new(&config.recording),
+        script,
+        audio: Audio::new(&config.audio),
+        console: Console::new(),
+    };
+
+    if let Some(frames) = headless {
+        // render a fixed number of frames at 30 fps simulated time, then quit
+        // a script runs on the same clock, so a demo can be rendered frame by frame
+        for frame in 0..frames {
+            run_script(1.0 / 30.0, &mut window, &mut scene, &mut tools);
+            scene.update(1.0 / 30.0);
+            // offscreen target is win_width x win_height, not whatever the hidden window got
+            scene
+                .camera
+                .projection
+                .resize(win_width as i32, win_height as i32);
+            let image = unsafe { renderer.render_offscreen(&mut scene, win_width, win_height) };
+            let path = format!("frame_{:04}.png", frame);
+            if let Err(err) = image.save(&path) {
+                exit_with_error(&err);
+            }
+            println!("Saved {}", path);
+        }
+        return;
+    }
+
+    if let Some(duration) = benchmark {
+        run_benchmark(duration, &mut window, &mut scene, &mut renderer);
+        return;
+    }
+
+    // optional remapping, see input.rs for the format
+    let bindings = Bindings::load_or_default("bindings.toml");
+
+    println!("Press V to cycle cloud / Bohr / orbital views.");
+
+    #[cfg(feature = "ui")]
+    let mut ui = ui::Ui::new(&mut window).unwrap_or_else(|err| exit_with_error(&err));
+
+    let mut periodic_table = PeriodicTable::new();
+    let mut editor = Editor::new();
+    let mut gamepad = Gamepad::new();
+    let mut timer = FrameTimer::new();
+    let mut stats_elapsed = 0.0;
+    let mut press_position = None;
+
     while !window.should_close() {
+        // --- Timing ---
+        // one dt for the whole frame: the camera scales by it, the simulation turns it into
+        // fixed steps (see Scene::update)
+        timer.tick();
+        // changes when the window is dragged to a monitor with a different scale
+        scene.display.content_scale = window.content_scale();
+
         // --- Event Handling ---
-        glfw.poll_events();
-        for (_, event) in glfw::flush_messages(&events) {
-            handle_window_event(&mut window, &event, &camera);
+        for event in window.poll_events(&mut scene.camera.projection) {
+            // an open console takes the keyboard before anyone else
+            if tools.console.handle_event(&event, &mut scene, &bindings) {
+                continue;
+            }
+            // control panel gets first pick, clicks on it should not spin the camera
+            #[cfg(feature = "ui")]
+            if ui.handle_event(&event) {
+                continue;
+            }
+            // same for the periodic table overlay while it is open
+            if periodic_table.handle_event(&window, &event, &mut scene) {
+                continue;
+            }
+            // and the molecule editor's palette and drags
+            if editor.handle_event(&window, &event, &mut scene) {
+                continue;
+            }
+            handle_window_event(
+                &mut window,
+                &event,
+                &mut scene,
+                &bindings,
+                &mut tools,
+                &mut press_position,
+            );
+        }
+
+        // what the commands typed into the console asked for, like key presses
+        for action in tools.console.take_actions() {
+            run_action(action, &mut window, &mut scene, &mut tools);
+        }
+
+        // polled, glfw has no gamepad events
+        let pressed = gamepad.update(&window.glfw, &bindings, &mut scene.camera, timer.dt());
+        for action in pressed {
+            run_action(action, &mut window, &mut scene, &mut tools);
+        }
+
+        // --- Simulation ---
+        run_script(timer.dt(), &mut window, &mut scene, &mut tools);
+        scene.update(timer.dt());
+        tools.audio.play(&scene.sounds);
+        stats_elapsed += timer.dt();
+        if stats_elapsed >= 1.0 {
+            // once a second, RUST_LOG=atom_sim=debug to see it
+            stats_elapsed = 0.0;
+            scene.log_stats(timer.fps());
         }
 
         // --- Rendering ---
+        let (fb_width, fb_height) = window.get_framebuffer_size();
         unsafe {
-            gl::ClearColor(0.3, 0.3, 0.3, 1.0);
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-            shader_program.use_program();
-
-            let view = camera.lock().unwrap().get_view_matrix();
-            let projection = glm::perspective(
-                (win_width as f32) / (win_height as f32),
-                glm::radians(&glm::vec1(45.0))[0],
-                0.1,
-                100.0,
-            );
-            shader_program.set_uniform_mat4(&view_name, &view);
-            shader_program.set_uniform_mat4(&proj_name, &projection);
-
-            sphere.bind();
-            for particle in &particles {
-                let mut model = glm::identity();
-                let pos_f32 = glm::vec3(
-                    particle.position.x as f32,
-                    particle.position.y as f32,
-                    particle.position.z as f32,
-                );
-                model = glm::translate(&model, &pos_f32);
-                model = glm::scale(&model, &glm::vec3(0.05, 0.05, 0.05));
-                shader_program.set_uniform_mat4(&model_name, &model);
-                shader_program.set_uniform_4f(
-                    &color_name,
-                    particle.color.x,
-                    particle.color.y,
-                    particle.color.z,
-                    particle.color.w,
+            renderer.render(&mut scene, fb_width as u32, fb_height as u32);
+            renderer.draw_hud(&scene, timer.fps(), fb_width as u32, fb_height as u32);
+            if scene.display.editor {
+                renderer.draw_editor(&editor, &scene, fb_width as u32, fb_height as u32);
+            }
+            if scene.display.periodic_table {
+                renderer.draw_periodic_table(
+                    &periodic_table,
+                    &scene,
+                    fb_width as u32,
+                    fb_height as u32,
                 );
-                gl::DrawArrays(gl::TRIANGLES, 0, sphere.vertex_count());
             }
+            if tools.console.open {
+                renderer.draw_console(&tools.console, &scene, fb_width as u32, fb_height as u32);
+            }
+        }
+        #[cfg(feature = "ui")]
+        unsafe {
+            renderer.profiler.begin(profiler::Pass::Ui);
+            ui.frame(&window, &mut scene, window.glfw.get_time());
+            renderer.profiler.end();
+        }
+        unsafe { renderer.profiler.finish_frame() };
+
+        // --- Screenshot ---
+        // has to happen after drawing but before the swap, while the back buffer is still ours
+        if tools.screenshot_requested {
+            tools.screenshot_requested = false;
+            let path = capture::screenshot_filename();
+            match unsafe { capture::save_screenshot(&path, fb_width as u32, fb_height as u32) } {
+                Ok(()) => println!("Saved {}", path),
+                Err(err) => log::error!("failed to save screenshot: {}", err),
+            }
+        }
+
+        // every frame, the recorder itself decides which ones it keeps
+        unsafe { tools.recorder.capture(fb_width as u32, fb_height as u32) };
+
+        window.swap_buffers();
+    }
+}
+
+fn run_benchmark(duration: f32, window: &mut Window, scene: &mut Scene, renderer: &mut Renderer) {
+    // the normal frame without input or tools, on the camera path, see benchmark.rs
+    println!("\nBenchmarking for {} s (Escape stops early)...", duration);
+    let mut benchmark = Benchmark::new(duration, scene);
+    let mut timer = FrameTimer::new();
+    loop {
+        timer.tick();
+        // resizes are handled in poll_events, Escape is the only key listened to
+        let quit = window
+            .poll_events(&mut scene.camera.projection)
+            .any(|event| matches!(event, glfw::WindowEvent::Key(glfw::Key::Escape, ..)));
+        if quit || window.should_close() {
+            break;
+        }
+        benchmark.place_camera(&mut scene.camera);
+        scene.update(timer.dt());
+        let (fb_width, fb_height) = window.get_framebuffer_size();
+        unsafe {
+            renderer.render(scene, fb_width as u32, fb_height as u32);
+            renderer.draw_hud(scene, timer.fps(), fb_width as u32, fb_height as u32);
         }
         window.swap_buffers();
+        if !benchmark.frame_done() {
+            break;
+        }
+    }
+    let (fb_width, fb_height) = window.get_framebuffer_size();
+    benchmark.print_report(scene, fb_width, fb_height);
+}
+
+// what actions can reach besides the window and the scene
+struct Tools {
+    screenshot_requested: bool, // taken once the frame is drawn, before the swap
+    recorder: Recorder,
+    script: Option<Script>, // --script, Ctrl+R starts it over
+    audio: Audio,           // sound effects, silent without the audio feature
+    console: Console,       // ~, see console.rs
+}
+
+fn run_script(dt: f32, window: &mut Window, scene: &mut Scene, tools: &mut Tools) {
+    // scripted key actions go through run_action like real key presses
+    let Some(script) = &mut tools.script else {
+        return;
+    };
+    for action in script.update(dt, scene) {
+        run_action(action, window, scene, tools);
     }
 }
 
+// how far (in window units) the mouse may move between press and release for a click
+const CLICK_TOLERANCE: f64 = 4.0;
+
 fn handle_window_event(
-    window: &mut glfw::Window,
+    window: &mut Window,
     event: &glfw::WindowEvent,
-    camera: &Arc<Mutex<Camera>>,
+    scene: &mut Scene,
+    bindings: &Bindings,
+    tools: &mut Tools,
+    press_position: &mut Option<(f64, f64)>,
 ) {
+    // raw glfw event -> bound action, see input.rs
+    // press_position remembers where an orbit drag started:
+    // if the button comes back up without the mouse really moving, it was a click, so pick
     match event {
-        glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
-            window.set_should_close(true);
+        glfw::WindowEvent::Key(key, _, Action::Press, mods) => {
+            if let Some(action) = bindings.key_action(*key, *mods) {
+                run_action(action, window, scene, tools);
+            }
         }
-        glfw::WindowEvent::CursorPos(x, y) => {
-            camera.lock().unwrap().process_mouse_move(*x, *y);
+        glfw::WindowEvent::MouseButton(button, Action::Press, mods) => {
+            if let Some(action) = bindings.mouse_action(*button, *mods) {
+                // drags need to know where they started
+                let (x, y) = window.get_cursor_pos();
+                match action {
+                    input::Action::OrbitCamera => {
+                        scene.camera.start_orbit(x, y);
+                        *press_position = Some((x, y));
+                    }
+                    input::Action::PanCamera => scene.camera.start_pan(x, y),
+                    input::Action::RotateAtom => {
+                        let (width, height) = window.get_size();
+                        let window_size = glm::vec2(width.max(1) as f32, height.max(1) as f32);
+                        let ndc = glm::vec2(
+                            2.0 * x as f32 / window_size.x - 1.0,
+                            1.0 - 2.0 * y as f32 / window_size.y,
+                        );
+                        scene.grab_atom(ndc, glm::vec2(x as f32, y as f32), window_size);
+                    }
+                    _ => run_action(action, window, scene, tools),
+                }
+            }
+        }
+        glfw::WindowEvent::MouseButton(button, Action::Release, _)
+            if bindings.is_drag_button(*button) =>
+        {
+            scene.camera.stop_drag();
+            scene.release_atom();
+
+            if let Some((press_x, press_y)) = press_position.take() {
+                let (x, y) = window.get_cursor_pos();
+                if (x - press_x).hypot(y - press_y) < CLICK_TOLERANCE {
+                    // window coords (y down) -> normalized device coords (y up)
+                    let (width, height) = window.get_size();
+                    let ndc_x = 2.0 * x / width.max(1) as f64 - 1.0;
+                    let ndc_y = 1.0 - 2.0 * y / height.max(1) as f64;
+                    // clicking empty space clears the selection
+                    scene.selected = scene.pick(ndc_x as f32, ndc_y as f32);
+                }
+            }
         }
-        glfw::WindowEvent::MouseButton(button, action, _) => {
-            let pos = window.get_cursor_pos();
-            camera
-                .lock()
-                .unwrap()
-                .process_mouse_button(*button, *action, pos.0, pos.1);
+        glfw::WindowEvent::CursorPos(x, y) => {
+            scene.camera.process_mouse_move(*x, *y);
+            scene.drag_atom(glm::vec2(*x as f32, *y as f32));
         }
         glfw::WindowEvent::Scroll(_, y_offset) => {
-            camera.lock().unwrap().process_scroll(*y_offset);
+            scene.camera.process_scroll(*y_offset);
+        }
+        glfw::WindowEvent::FileDrop(paths) => {
+            // a molecule file or a saved scene dropped onto the window, see import.rs
+            for path in paths {
+                let path = path.to_string_lossy();
+                let opened = if import::is_molecule(&path) {
+                    import::open(scene, &path)
+                        .map(|count| format!("{} atoms", count))
+                        .map_err(|err| err.to_string())
+                } else {
+                    save::load(scene, &path)
+                        .map(|()| "the scene".to_string())
+                        .map_err(|err| err.to_string())
+                };
+                match opened {
+                    Ok(what) => println!("Opened {} from {}", what, path),
+                    Err(err) => log::error!("{}", err),
+                }
+            }
         }
         _ => {}
     }
 }
+
+fn run_action(action: input::Action, window: &mut Window, scene: &mut Scene, tools: &mut Tools) {
+    // a click for every press, but not for zooming, which comes a notch at a time
+    if !matches!(action, input::Action::ZoomIn | input::Action::ZoomOut) {
+        tools.audio.play(&[Sound::Interface]);
+    }
+    match action {
+        input::Action::Quit => window.set_should_close(true),
+        input::Action::Screenshot => tools.screenshot_requested = true,
+        input::Action::ToggleMute => {
+            let muted = tools.audio.toggle_mute();
+            println!("Sound {}", if muted { "muted" } else { "on" });
+        }
+        input::Action::ExportObj | input::Action::ExportGltf => {
+            let format = if action == input::Action::ExportObj {
+                export::ExportFormat::Obj
+            } else {
+                export::ExportFormat::Gltf
+            };
+            match export::export(scene, format) {
+                Ok(path) => println!("Exported {}", path),
+                Err(err) => log::error!("{}", err),
+            }
+        }
+        input::Action::SaveScene => match save::save(scene, save::SAVE_FILE) {
+            Ok(()) => println!("Saved scene to {}", save::SAVE_FILE),
+            Err(err) => log::error!("{}", err),
+        },
+        input::Action::LoadScene => match save::load(scene, save::SAVE_FILE) {
+            Ok(()) => println!("Loaded scene from {}", save::SAVE_FILE),
+            Err(err) => log::error!("{}", err),
+        },
+        input::Action::ToggleRecording => {
+            let (width, height) = window.get_framebuffer_size();
+            tools.recorder.toggle(width as u32, height as u32);
+        }
+        input::Action::ToggleConsole => tools.console.toggle(),
+        input::Action::ReplayScript => match &mut tools.script {
+            Some(script) => {
+                script.restart();
+                println!("Script restarted.");
+            }
+            None => println!("No script loaded (atom-sim --script FILE)."),
+        },
+        input::Action::ToggleFullscreen => window.toggle_fullscreen(),
+        input::Action::Recenter => {
+            // glide back to the middle of the scene at the default distance
+            let center = scene.center();
+            scene.camera.fly_to(center, 30.0, 1.0);
+        }
+        input::Action::ResetRotation => {
+            scene.reset_rotation();
+            println!("Rotation of atom {} reset", scene.target_atom() + 1);
+        }
+        input::Action::SetBookmark(slot) => {
+            scene.camera.bookmarks[slot] = Some(scene.camera.pose());
+            println!("Camera bookmark {} stored", slot + 1);
+        }
+        input::Action::GoToBookmark(slot) => match scene.camera.bookmarks[slot] {
+            Some(pose) => scene.camera.fly_to_pose(&pose, 1.0),
+            None => println!(
+                "Camera bookmark {} is empty (Ctrl+{} stores one)",
+                slot + 1,
+                slot + 1
+            ),
+        },
+        input::Action::CycleView => scene.view_mode = scene.view_mode.next(),
+        input::Action::CycleRenderMode => scene.render_mode = scene.render_mode.next(),
+        input::Action::CycleBackground => {
+            let display = &mut scene.display;
+            display.background_mode = display.background_mode.next();
+            println!("Background: {}", display.background_mode.name());
+        }
+        input::Action::CycleStereo => {
+            let display = &mut scene.display;
+            display.stereo = display.stereo.next();
+            println!("Stereo: {}", display.stereo.name());
+        }
+        input::Action::CycleQuality => {
+            let display = &mut scene.display;
+            display.quality = display.quality.next();
+            println!("Render quality: {}", display.quality.name());
+        }
+        input::Action::CycleTheme => {
+            scene.set_theme(scene.display.theme.next());
+            println!("Theme: {}", scene.display.theme.name);
+        }
+        input::Action::ToggleAntialiasing => {
+            scene.display.antialiasing = !scene.display.antialiasing;
+            println!(
+                "Antialiasing {}",
+                if scene.display.antialiasing {
+                    "on"
+                } else {
+                    "off"
+                }
+            );
+        }
+        input::Action::ToggleOcclusion => {
+            scene.display.ssao = !scene.display.ssao;
+            println!(
+                "Ambient occlusion {}",
+                if scene.display.ssao { "on" } else { "off" }
+            );
+        }
+        input::Action::ToggleShadows => {
+            scene.display.shadows = !scene.display.shadows;
+            println!(
+                "Shadows {}",
+                if scene.display.shadows { "on" } else { "off" }
+            );
+        }
+        input::Action::CycleGround => {
+            let display = &mut scene.display;
+            display.ground = display.ground.next();
+            println!("Ground: {}", display.ground.name());
+        }
+        input::Action::ToggleDepthOfField => {
+            scene.display.dof = !scene.display.dof;
+            println!(
+                "Depth of field {}",
+                if scene.display.dof { "on" } else { "off" }
+            );
+        }
+        input::Action::CycleToneMapping => {
+            let display = &mut scene.display;
+            display.tone_mapping = display.tone_mapping.next();
+            println!("Tone mapping: {}", display.tone_mapping.name());
+        }
+        input::Action::TogglePeriodicTable => {
+            scene.display.periodic_table = !scene.display.periodic_table
+        }
+        input::Action::ToggleEditor => {
+            scene.display.editor = !scene.display.editor;
+            println!(
+                "Molecule editor {}",
+                if scene.display.editor { "on" } else { "off" }
+            );
+        }
+        input::Action::ToggleGeometry => {
+            scene.geometry = match scene.geometry {
+                Some(_) => None,
+                None => Some(vsepr::Geometry::new()),
+            };
+            println!(
+                "Molecule shapes {}",
+                if scene.geometry.is_some() {
+                    "on"
+                } else {
+                    "off"
+                }
+            );
+        }
+        input::Action::CycleMoleculeStyle => {
+            let display = &mut scene.display;
+            display.molecule_style = display.molecule_style.next();
+            println!("Molecule style: {}", display.molecule_style.name());
+        }
+        input::Action::DeleteAtom => {
+            let index = scene.target_atom();
+            if scene.delete_atom(index) {
+                println!("Removed atom {}", index + 1);
+            } else {
+                println!("Select an atom other than the main one (not while comparing).");
+            }
+        }
+        input::Action::ToggleProjection => {
+            let projection = &mut scene.camera.projection;
+            projection.orthographic = !projection.orthographic;
+            println!(
+                "Projection: {}",
+                if projection.orthographic {
+                    "orthographic"
+                } else {
+                    "perspective"
+                }
+            );
+        }
+        input::Action::ToggleSpectrum => scene.display.spectrum = !scene.display.spectrum,
+        input::Action::ToggleLevels => scene.display.levels = !scene.display.levels,
+        input::Action::ToggleGuides => scene.display.guides = !scene.display.guides,
+        input::Action::ToggleMagnetic => scene.display.magnetic = !scene.display.magnetic,
+        input::Action::Excite => {
+            if !scene.excite() {
+                println!("No electron to excite.");
+            }
+        }
+        input::Action::Ionize => {
+            if !scene.ionize() {
+                println!("No electron left to remove.");
+            }
+        }
+        input::Action::AddElectron => {
+            if !scene.add_electron() {
+                println!("That atom will not hold another electron.");
+            }
+        }
+        input::Action::Undo => match history::undo(scene) {
+            Some(edit) => println!("Undid {}", edit),
+            None => println!("Nothing to undo."),
+        },
+        input::Action::Redo => match history::redo(scene) {
+            Some(edit) => println!("Redid {}", edit),
+            None => println!("Nothing to redo."),
+        },
+        input::Action::NextPrincipal
+        | input::Action::PreviousPrincipal
+        | input::Action::NextAngular
+        | input::Action::PreviousAngular
+        | input::Action::NextMagnetic
+        | input::Action::PreviousMagnetic
+        | input::Action::ToggleSubshell => {
+            // only ever lands on valid combinations, the orbital view samples it when drawn
+            let orbital = scene.orbital;
+            scene.orbital = match action {
+                input::Action::NextPrincipal => orbital.step_n(true),
+                input::Action::PreviousPrincipal => orbital.step_n(false),
+                input::Action::NextAngular => orbital.step_l(true),
+                input::Action::PreviousAngular => orbital.step_l(false),
+                input::Action::NextMagnetic => orbital.step_m(true),
+                input::Action::PreviousMagnetic => orbital.step_m(false),
+                _ => Orbital {
+                    subshell: !orbital.subshell,
+                    ..orbital.clamped()
+                },
+            };
+            let orbital = scene.orbital;
+            println!(
+                "Orbital: {} (n={}, l={}, m={})",
+                orbital.name(),
+                orbital.n,
+                orbital.l,
+                orbital.m
+            );
+        }
+        input::Action::ToggleClipPlane => {
+            // comes on facing the camera, wherever it is looking from
+            let clip = &mut scene.display.clip;
+            clip.enabled = !clip.enabled;
+            if clip.enabled {
+                clip.face(&scene.camera);
+            }
+            println!("Cross-section {}", if clip.enabled { "on" } else { "off" });
+        }
+        input::Action::ToggleDensitySlice => {
+            scene.display.density_slice = !scene.display.density_slice
+        }
+        input::Action::ToggleRadialPlot => scene.display.radial_plot = !scene.display.radial_plot,
+        input::Action::ToggleSpin => scene.display.spin = !scene.display.spin,
+        input::Action::ToggleTextures => scene.display.textures = !scene.display.textures,
+        input::Action::ToggleProfiler => scene.display.profiler = !scene.display.profiler,
+        input::Action::ToggleBuilder => {
+            if scene.toggle_builder() {
+                println!(
+                    "Builder mode: {} without electrons, Right adds the next one",
+                    scene.atoms[0].atom.element().name
+                );
+            } else {
+                println!("Builder mode off");
+            }
+        }
+        input::Action::BuilderNext | input::Action::BuilderPrevious => {
+            if scene.builder.is_none() {
+                println!("Builder mode is off (Z turns it on).");
+            } else if action == input::Action::BuilderNext {
+                if !scene.builder_next() {
+                    println!("Every electron is in, the atom is neutral.");
+                }
+            } else if !scene.builder_previous() {
+                println!("No electron left to take away.");
+            }
+        }
+        input::Action::ToggleScattering => {
+            if scene.toggle_scattering() {
+                println!(
+                    "Scattering mode: alpha particles at {}, Enter fires",
+                    scene.atoms[0].atom.element().name
+                );
+            } else {
+                println!("Scattering mode off");
+            }
+        }
+        input::Action::FireAlpha => {
+            if !scene.fire_alpha() {
+                println!("Scattering mode is off (Shift+A turns it on).");
+            }
+        }
+        input::Action::WiderImpact
+        | input::Action::NarrowerImpact
+        | input::Action::MoreEnergy
+        | input::Action::LessEnergy => match &mut scene.scattering {
+            Some(scattering) => {
+                match action {
+                    input::Action::WiderImpact => scattering.impact += scattering::IMPACT_STEP,
+                    input::Action::NarrowerImpact => scattering.impact -= scattering::IMPACT_STEP,
+                    input::Action::MoreEnergy => scattering.energy += scattering::ENERGY_STEP,
+                    _ => scattering.energy -= scattering::ENERGY_STEP,
+                }
+                scattering.impact = scattering
+                    .impact
+                    .clamp(scattering::MIN_IMPACT, scattering::MAX_IMPACT);
+                scattering.energy = scattering
+                    .energy
+                    .clamp(scattering::MIN_ENERGY, scattering::MAX_ENERGY);
+                println!(
+                    "Next shot: b = {:.0} fm, E = {:.1} MeV",
+                    scattering.impact, scattering.energy
+                );
+            }
+            None => println!("Scattering mode is off (Shift+A turns it on)."),
+        },
+        input::Action::FirePhoton => {
+            scene.fire_photon();
+        }
+        input::Action::LongerWavelength
+        | input::Action::ShorterWavelength
+        | input::Action::NextLine
+        | input::Action::PreviousLine => {
+            let atom = &scene.atoms[scene.target_atom()].atom;
+            let wavelength = scene.lamp_wavelength;
+            let tuned = match action {
+                input::Action::LongerWavelength => wavelength * absorption::WAVELENGTH_STEP,
+                input::Action::ShorterWavelength => wavelength / absorption::WAVELENGTH_STEP,
+                input::Action::NextLine => {
+                    absorption::next_line(atom, wavelength, true).unwrap_or(wavelength)
+                }
+                _ => absorption::next_line(atom, wavelength, false).unwrap_or(wavelength),
+            };
+            scene.lamp_wavelength =
+                tuned.clamp(absorption::MIN_WAVELENGTH, absorption::MAX_WAVELENGTH);
+            let atom = &scene.atoms[scene.target_atom()].atom;
+            let symbol = atom.element().symbol;
+            match absorption::matching(atom, scene.lamp_wavelength) {
+                Some(transition) => println!(
+                    "Next photon: {}, {} absorbs it (n={} -> n={}, {:.2} eV)",
+                    absorption::wavelength_label(scene.lamp_wavelength),
+                    symbol,
+                    transition.electron.0 + 1,
+                    transition.to + 1,
+                    transition.energy
+                ),
+                None => println!(
+                    "Next photon: {}, passes through {}",
+                    absorption::wavelength_label(scene.lamp_wavelength),
+                    symbol
+                ),
+            }
+        }
+        input::Action::ToggleRepulsion => {
+            scene.set_repulsion(!scene.repulsion);
+            println!(
+                "Electron repulsion {}",
+                if scene.repulsion { "on" } else { "off" }
+            );
+        }
+        input::Action::ToggleVibration => {
+            scene.vibration = !scene.vibration;
+            println!(
+                "Nucleon dynamics {}",
+                if scene.vibration { "on" } else { "off" }
+            );
+        }
+        input::Action::Pause => scene.time.toggle_pause(),
+        input::Action::Step => scene.time.step(),
+        input::Action::SpeedUp => scene.time.set_scale(scene.time.scale * 2.0),
+        input::Action::SlowDown => scene.time.set_scale(scene.time.scale / 2.0),
+        input::Action::NextElement | input::Action::PreviousElement => {
+            let next = action == input::Action::NextElement;
+            let element = neighbour_element(scene.atoms[0].atom.protons, next);
+            scene.set_element(0, element);
+            print_atom_info(&scene.atoms[0].atom);
+        }
+        input::Action::NextPartnerElement | input::Action::PreviousPartnerElement => {
+            match scene
+                .comparison
+                .as_ref()
+                .map(|comparison| comparison.partner)
+            {
+                Some(partner) => {
+                    let next = action == input::Action::NextPartnerElement;
+                    let element = neighbour_element(scene.atoms[partner].atom.protons, next);
+                    scene.set_partner_element(element);
+                    print_atom_info(&scene.atoms[partner].atom);
+                }
+                None => println!("Comparison mode is off (Q turns it on)."),
+            }
+        }
+        input::Action::ToggleTrueScale => {
+            // the slider in the control panel goes in between
+            let display = &mut scene.display;
+            display.true_scale = if display.true_scale > 0.0 { 0.0 } else { 1.0 };
+            println!(
+                "True scale {}",
+                if display.true_scale > 0.0 {
+                    "on"
+                } else {
+                    "off"
+                }
+            );
+        }
+        input::Action::CycleField => match scene.cycle_field() {
+            Some(view) => println!(
+                "Field of {}: {}",
+                scene.atoms[scene.target_atom()].atom.element().name,
+                view.name()
+            ),
+            None => println!("No atom to show the field of."),
+        },
+        input::Action::ToggleComparison => {
+            if scene.toggle_comparison() {
+                let partner = scene.comparison.as_ref().unwrap().partner;
+                println!(
+                    "Comparing {} with {}",
+                    scene.atoms[0].atom.element().name,
+                    scene.atoms[partner].atom.element().name
+                );
+            } else {
+                println!("Comparison mode off");
+            }
+        }
+        input::Action::NextIsotope | input::Action::PreviousIsotope => {
+            // step the main atom's nucleus through the isotopes the table knows
+            let atom = &scene.atoms[0].atom;
+            let heavier = action == input::Action::NextIsotope;
+            match isotopes::neighbour(atom.protons, atom.protons + atom.neutrons, heavier) {
+                Some(isotope) => {
+                    scene.set_isotope(0, isotope.mass_number);
+                    print_atom_info(&scene.atoms[0].atom);
+                }
+                None => println!("No other isotopes of {} in the table.", atom.element().name),
+            }
+        }
+        // a key press is one notch of the scroll wheel, gamepad triggers zoom in gamepad.rs
+        input::Action::ZoomIn => scene.camera.process_scroll(1.0),
+        input::Action::ZoomOut => scene.camera.process_scroll(-1.0),
+        // drags only make sense on a held mouse button or a stick, nothing to do from a key
+        input::Action::OrbitCamera | input::Action::PanCamera | input::Action::RotateAtom => {}
+    }
+}
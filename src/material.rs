@@ -0,0 +1,150 @@
+use nalgebra_glm as glm;
+
+// how a surface looks, separate from what it is
+// every sphere entity points at one of these (see ecs.rs), bonds and photons have their
+// own; the renderer sorts instances by material and binds each one once, so a new kind of
+// object is a new preset here instead of another set of uniforms in renderer.rs
+// the color of a single instance is still multiplied on top, that is how one photon
+// material covers every wavelength
+
+// which shading path draws it, both live in phong.frag
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Shading {
+    Lit,   // blinn-phong with the scene lights
+    Unlit, // flat color, for things that are light themselves
+}
+
+// an image wrapped around the sphere, multiplied into the albedo; see texture.rs
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SurfaceTexture {
+    Plus,  // + signs, protons
+    Minus, // - signs, electrons
+    Fuzz,  // soft noise, neutrons have no charge to show
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Material {
+    pub albedo: glm::Vec3, // srgb, multiplied with the instance color
+    pub emissive: f32,     // 0 = plain surface, 1 = glows, drawn brighter than white under bloom
+    pub shininess: f32,    // blinn-phong exponent, higher = smaller, sharper highlight
+    pub specular: f32,     // highlight strength, 0 = matte
+    pub opacity: f32,      // 1 = solid, below that it is blended over what is behind
+    pub shading: Shading,
+    pub texture: Option<SurfaceTexture>, // only drawn with textures turned on
+}
+
+impl Material {
+    // protons a little glossy, neutrons matte, so the two read apart even in a colorblind
+    // palette or a grey screenshot
+    pub fn proton(albedo: glm::Vec3) -> Self {
+        Self {
+            albedo,
+            emissive: 0.0,
+            shininess: 48.0,
+            specular: 0.6,
+            opacity: 1.0,
+            shading: Shading::Lit,
+            texture: Some(SurfaceTexture::Plus),
+        }
+    }
+
+    pub fn neutron(albedo: glm::Vec3) -> Self {
+        Self {
+            shininess: 12.0,
+            specular: 0.2,
+            texture: Some(SurfaceTexture::Fuzz),
+            ..Self::proton(albedo)
+        }
+    }
+
+    pub fn electron(albedo: glm::Vec3) -> Self {
+        Self {
+            albedo,
+            emissive: 1.0,
+            shininess: 32.0,
+            specular: 0.5,
+            opacity: 1.0,
+            shading: Shading::Lit,
+            texture: Some(SurfaceTexture::Minus),
+        }
+    }
+
+    pub fn bond() -> Self {
+        // sticks stay in the background: grey, barely any highlight
+        Self {
+            albedo: glm::vec3(0.8, 0.8, 0.8),
+            emissive: 0.0,
+            shininess: 8.0,
+            specular: 0.15,
+            opacity: 1.0,
+            shading: Shading::Lit,
+            texture: None,
+        }
+    }
+
+    pub fn photon() -> Self {
+        // the wavelength color comes in per instance
+        Self {
+            albedo: glm::vec3(1.0, 1.0, 1.0),
+            emissive: 1.0,
+            shininess: 1.0,
+            specular: 0.0,
+            opacity: 1.0,
+            shading: Shading::Unlit,
+            texture: None,
+        }
+    }
+
+    pub fn plain() -> Self {
+        // white and lit, for things whose instances bring their own colors (decay products,
+        // spin arrows)
+        Self {
+            albedo: glm::vec3(1.0, 1.0, 1.0),
+            emissive: 0.0,
+            shininess: 32.0,
+            specular: 0.5,
+            opacity: 1.0,
+            shading: Shading::Lit,
+            texture: None,
+        }
+    }
+
+    pub fn brightness(&self, bloom: f32) -> f32 {
+        // bloom = how much brighter than white glowing things get, 1 = bloom off
+        1.0 + self.emissive * (bloom - 1.0)
+    }
+}
+
+// index into a MaterialLibrary
+pub type MaterialId = usize;
+
+// the materials in use, each one stored once
+pub struct MaterialLibrary {
+    materials: Vec<Material>,
+}
+
+impl MaterialLibrary {
+    pub fn new() -> Self {
+        Self {
+            materials: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, material: Material) -> MaterialId {
+        // the same material twice (every electron of a shell) gets the same id, so they
+        // end up in one batch
+        if let Some(id) = self.materials.iter().position(|m| *m == material) {
+            return id;
+        }
+        self.materials.push(material);
+        self.materials.len() - 1
+    }
+
+    pub fn get(&self, id: MaterialId) -> &Material {
+        &self.materials[id]
+    }
+
+    pub fn count(&self) -> usize {
+        self.materials.len()
+    }
+}
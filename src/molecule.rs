@@ -0,0 +1,362 @@
+use crate::elements::{self, Element}; // palette cells and valences
+use crate::locale::{self, fill}; // captions and warnings
+use crate::scene::{BondOrder, Scene};
+use crate::simulation::Atom; // shell sizes of a dropped atom
+use crate::text::{hud_size, TextRenderer}; // drawn with the hud text batch
+use crate::window::Window; // cursor -> hud pixels
+use glfw::{Action, MouseButton};
+use nalgebra_glm as glm;
+
+// molecule editor (Ctrl+B): a palette of elements along the top of the window
+//   drag a cell into the scene   a new atom where it is dropped, onto an atom and it comes
+//                                in beside that one, bonded to it
+//   drag from atom to atom       a single bond, again on a bonded pair raises it to double,
+//                                triple, and back to single
+//   click an atom                selects it, Delete takes it out
+// every one of those is an edit Ctrl+Z takes back, see history.rs
+// underneath runs a simple valence model: main-group atoms should make as many bonds as they
+// have outer electrons up to four, or holes left in their octet past that; too many bonds
+// is a warning in red, bonds still missing are listed in grey
+// the palette is laid out in hud pixels like the periodic table, drags on the scene use the
+// same ray casts as picking
+
+const PALETTE: [&str; 12] = [
+    "H", "C", "N", "O", "F", "Na", "Si", "P", "S", "Cl", "Br", "I",
+];
+const CELL: f32 = 40.0; // hud pixels
+const GAP: f32 = 4.0;
+const TOP: f32 = 10.0;
+const SPACING: f32 = 2.0; // between the shells of an atom dropped onto another one
+
+const OVER_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 0.35, 0.3, 1.0);
+const OPEN_COLOR: glm::Vec4 = glm::Vec4::new(0.75, 0.75, 0.75, 1.0);
+
+enum Drag {
+    Element(&'static Element), // out of the palette
+    Bond(usize),               // from this atom
+}
+
+pub struct Editor {
+    pointer: glm::Vec2,                // cursor in hud pixels, for the palette
+    ndc: glm::Vec2,                    // and in normalized device coordinates, for the scene
+    hovered: Option<&'static Element>, // palette cell under the cursor
+    drag: Option<Drag>,
+}
+
+pub enum Valence {
+    Fine,
+    Open(u32), // bonds still missing
+    Over(u32), // the most it can make, it has more
+}
+
+pub fn valence(element: &Element) -> Option<(u32, u32)> {
+    // bonds the element usually makes and the most it can; from the third period on the ones
+    // past group 14 can use all their outer electrons (PCl5, SF6)
+    // None for the transition and inner transition metals, they make whatever they like
+    let z = element.atomic_number;
+    if matches!(z, 21..=30 | 39..=48 | 57..=80 | 89..=112) {
+        return None;
+    }
+    let outer = *element.shells.last()?;
+    if z == 2 {
+        return Some((0, 0)); // its one shell is full with two
+    }
+    let usual = if outer <= 4 { outer } else { 8 - outer };
+    let most = if outer > 4 && outer < 8 && element.shells.len() >= 3 {
+        outer
+    } else {
+        usual
+    };
+    Some((usual, most))
+}
+
+pub fn bond_count(scene: &Scene, index: usize) -> u32 {
+    // a double bond counts twice
+    scene
+        .bonds
+        .iter()
+        .filter(|bond| bond.a == index || bond.b == index)
+        .map(|bond| bond.order.count() as u32)
+        .sum()
+}
+
+pub fn check(scene: &Scene, index: usize) -> Valence {
+    let Some((usual, most)) = valence(scene.atoms[index].atom.element()) else {
+        return Valence::Fine;
+    };
+    let bonds = bond_count(scene, index);
+    if bonds > most {
+        Valence::Over(most)
+    } else if bonds < usual {
+        Valence::Open(usual - bonds)
+    } else {
+        Valence::Fine
+    }
+}
+
+pub fn describe(scene: &Scene, index: usize) -> Option<String> {
+    // one line about what is wrong with atom index, numbered from 1 like the console
+    let symbol = scene.atoms[index].atom.element().symbol;
+    let bonds = bond_count(scene, index);
+    match check(scene, index) {
+        Valence::Fine => None,
+        Valence::Open(missing) => Some(fill(
+            "{} {}: {} bonds, {} more open",
+            &[&symbol, &(index + 1), &bonds, &missing],
+        )),
+        Valence::Over(most) => Some(fill(
+            "{} {}: {} bonds, more than {} can make",
+            &[&symbol, &(index + 1), &bonds, &most],
+        )),
+    }
+}
+
+struct Layout {
+    x: f32, // left edge of the first cell, the row is centered
+}
+
+impl Layout {
+    fn new(width: u32) -> Self {
+        let row = PALETTE.len() as f32 * (CELL + GAP) - GAP;
+        Self {
+            x: (width as f32 - row) * 0.5,
+        }
+    }
+
+    fn cell_x(&self, slot: usize) -> f32 {
+        self.x + slot as f32 * (CELL + GAP)
+    }
+
+    fn pick(&self, pointer: glm::Vec2) -> Option<&'static Element> {
+        if !(TOP..TOP + CELL).contains(&pointer.y) {
+            return None;
+        }
+        PALETTE
+            .iter()
+            .enumerate()
+            .find(|(slot, _)| (self.cell_x(*slot)..self.cell_x(*slot) + CELL).contains(&pointer.x))
+            .and_then(|(_, symbol)| elements::by_symbol(symbol))
+    }
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Self {
+            pointer: glm::vec2(0.0, 0.0),
+            ndc: glm::vec2(0.0, 0.0),
+            hovered: None,
+            drag: None,
+        }
+    }
+
+    pub fn handle_event(
+        &mut self,
+        window: &Window,
+        event: &glfw::WindowEvent,
+        scene: &mut Scene,
+    ) -> bool {
+        // true when the event was a press or release of the editor's and nobody else should see
+        // it; presses on empty space still orbit the camera
+        if !scene.display.editor {
+            self.hovered = None;
+            self.drag = None;
+            return false;
+        }
+
+        match event {
+            glfw::WindowEvent::CursorPos(x, y) => {
+                let (ratio_x, ratio_y) = window.pixel_ratio();
+                let scale = scene.display.hud_scale();
+                self.pointer =
+                    glm::vec2((*x * ratio_x) as f32 / scale, (*y * ratio_y) as f32 / scale);
+                let (width, height) = window.get_size();
+                self.ndc = glm::vec2(
+                    2.0 * *x as f32 / width.max(1) as f32 - 1.0,
+                    1.0 - 2.0 * *y as f32 / height.max(1) as f32,
+                );
+                let (fb_width, _) = window.get_framebuffer_size();
+                self.hovered = Layout::new(hud_size(fb_width as u32, scale)).pick(self.pointer);
+                false
+            }
+            glfw::WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                self.drag = match (self.hovered, scene.atom_at(self.ndc.x, self.ndc.y)) {
+                    (Some(element), _) => Some(Drag::Element(element)),
+                    (None, Some(index)) => Some(Drag::Bond(index)),
+                    (None, None) => None,
+                };
+                self.drag.is_some()
+            }
+            glfw::WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+                match self.drag.take() {
+                    Some(Drag::Element(element)) => {
+                        if self.hovered.is_none() {
+                            self.drop_element(scene, element);
+                        }
+                        true
+                    }
+                    Some(Drag::Bond(a)) => {
+                        match scene.atom_at(self.ndc.x, self.ndc.y) {
+                            // pressed and let go on the same atom, a click
+                            Some(b) if b == a => {
+                                scene.selected = scene.pick(self.ndc.x, self.ndc.y);
+                            }
+                            Some(b) => bond(scene, a, b),
+                            None => {}
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn drop_element(&self, scene: &mut Scene, element: &'static Element) {
+        let Some(onto) = scene.atom_at(self.ndc.x, self.ndc.y) else {
+            let position = scene.cursor_point(self.ndc.x, self.ndc.y);
+            let index = scene.place_atom(element, None, position);
+            println!(
+                "{}",
+                fill("Placed {} as atom {}", &[&element.symbol, &(index + 1)])
+            );
+            return;
+        };
+        // dropped onto an atom: beside it to the right on screen, shells clear of each other
+        let view = scene.camera.get_view_matrix();
+        let right = glm::vec3(view[(0, 0)], view[(0, 1)], view[(0, 2)]);
+        let own_radius =
+            Atom::from_atomic_number(element.atomic_number).map_or(0.0, |atom| atom.outer_radius());
+        let host = &scene.atoms[onto];
+        let position = host.position + right * (host.atom.outer_radius() + own_radius + SPACING);
+        let index = scene.place_atom(element, None, position);
+        println!(
+            "{}",
+            fill("Placed {} as atom {}", &[&element.symbol, &(index + 1)])
+        );
+        bond(scene, onto, index);
+    }
+
+    pub fn draw(&self, text: &mut TextRenderer, scene: &Scene, width: u32, height: u32) {
+        // queued into the text batch, the caller flushes
+        let layout = Layout::new(width);
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        let highlight = scene.display.theme.highlight;
+        let symbol_scale = 2.0;
+        for (slot, symbol) in PALETTE.iter().enumerate() {
+            let x = layout.cell_x(slot);
+            let hovered = self
+                .hovered
+                .is_some_and(|element| element.symbol == *symbol);
+            let color = if hovered {
+                glm::vec4(highlight.x, highlight.y, highlight.z, 0.9)
+            } else {
+                glm::vec4(0.15, 0.15, 0.2, 0.85)
+            };
+            text.draw_rect(x, TOP, CELL, CELL, &color);
+            let symbol_width = TextRenderer::text_width(symbol, symbol_scale);
+            text.draw_text(
+                symbol,
+                x + (CELL - symbol_width) * 0.5,
+                TOP + (CELL - 7.0 * symbol_scale) * 0.5,
+                symbol_scale,
+                &white,
+            );
+        }
+        let mut y = TOP + CELL + 8.0;
+        text.draw_text(
+            locale::text("drag an element into the scene, from atom to atom to bond"),
+            layout.x,
+            y,
+            1.0,
+            &OPEN_COLOR,
+        );
+        y += 14.0;
+
+        // one line per atom whose bonds do not add up, too many first
+        let (over, open): (Vec<usize>, Vec<usize>) = (0..scene.atoms.len())
+            .filter(|&index| !matches!(check(scene, index), Valence::Fine))
+            .partition(|&index| matches!(check(scene, index), Valence::Over(_)));
+        for (indices, color) in [(over, &OVER_COLOR), (open, &OPEN_COLOR)] {
+            for index in indices {
+                if let Some(line) = describe(scene, index) {
+                    text.draw_text(&line, layout.x, y, 1.0, color);
+                    y += 12.0;
+                }
+            }
+        }
+
+        match self.drag {
+            Some(Drag::Element(element)) => {
+                // the symbol follows the cursor until it is dropped
+                let symbol_width = TextRenderer::text_width(element.symbol, symbol_scale);
+                text.draw_rect(
+                    self.pointer.x - CELL * 0.5,
+                    self.pointer.y - CELL * 0.5,
+                    CELL,
+                    CELL,
+                    &glm::vec4(highlight.x, highlight.y, highlight.z, 0.5),
+                );
+                text.draw_text(
+                    element.symbol,
+                    self.pointer.x - symbol_width * 0.5,
+                    self.pointer.y - 3.5 * symbol_scale,
+                    symbol_scale,
+                    &white,
+                );
+            }
+            Some(Drag::Bond(index)) => {
+                // a dotted line from the atom to the cursor
+                let Some(start) = hud_point(scene, &scene.atoms[index].position, width, height)
+                else {
+                    return;
+                };
+                let along = self.pointer - start;
+                let dots = (glm::length(&along) / 8.0) as usize;
+                for dot in 0..=dots {
+                    let p = start + along * (dot as f32 / dots.max(1) as f32);
+                    text.draw_rect(p.x - 2.0, p.y - 2.0, 4.0, 4.0, &white);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+fn bond(scene: &mut Scene, a: usize, b: usize) {
+    // a new single bond, or the next order up on one that is there, both undoable
+    let order = if scene.connect(a, b, BondOrder::Single) {
+        Some(BondOrder::Single)
+    } else {
+        scene.raise_bond(a, b)
+    };
+    if let Some(order) = order {
+        let name = locale::text(match order {
+            BondOrder::Single => "single",
+            BondOrder::Double => "double",
+            BondOrder::Triple => "triple",
+        });
+        println!("{}", fill("Bond {}-{}: {}", &[&(a + 1), &(b + 1), &name]));
+    }
+    for index in [a, b] {
+        if let Valence::Over(_) = check(scene, index) {
+            if let Some(line) = describe(scene, index) {
+                println!("{}", fill("Warning: {}", &[&line]));
+            }
+        }
+    }
+}
+
+fn hud_point(scene: &Scene, world: &glm::Vec3, width: u32, height: u32) -> Option<glm::Vec2> {
+    // where a point of the world shows up in hud pixels, None behind the camera
+    let clip = scene.camera.get_projection_matrix()
+        * scene.camera.get_view_matrix()
+        * glm::vec4(world.x, world.y, world.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    Some(glm::vec2(
+        (clip.x / clip.w + 1.0) * 0.5 * width as f32,
+        (1.0 - clip.y / clip.w) * 0.5 * height as f32,
+    ))
+}
@@ -0,0 +1,121 @@
+use lazy_static::lazy_static; // packing cache
+use nalgebra_glm as glm;
+use rand::rngs::StdRng; // seeded, so every run packs an element the same way
+use rand::seq::SliceRandom; // shuffle
+use rand::{Rng, SeedableRng};
+use std::sync::Mutex;
+
+// a nucleus is not one blob, it is Z protons and N neutrons squashed together
+// real nucleons are roughly hard spheres that attract each other
+// so that is exactly what we simulate here, just once, before anything is drawn:
+//   1. scatter nucleons randomly inside a ball
+//   2. repeat: pull everyone toward the center, then push overlapping pairs apart
+// after a few hundred rounds it settles into a nice lumpy raspberry
+
+pub const NUCLEON_RADIUS: f32 = 0.25;
+// world units, picked so the packed nucleus is about as big as the old single sphere
+
+const RELAX_ITERATIONS: usize = 200;
+const CENTER_PULL: f32 = 0.05; // fraction of the distance to the center removed per round
+
+#[derive(Clone, Copy)]
+pub struct Nucleon {
+    pub position: glm::Vec3, // relative to the nucleus center
+    pub is_proton: bool,
+}
+
+pub fn packed(protons: u32, neutrons: u32) -> Vec<Nucleon> {
+    // packing is O(A^2) per round, heavy nuclei take a moment
+    // so compute each (Z, N) once and hand out copies afterwards
+
+    lazy_static! {
+        static ref PACKING_CACHE: Mutex<Vec<(u32, u32, Vec<Nucleon>)>> = Mutex::new(Vec::new());
+    }
+
+    let mut cache = PACKING_CACHE.lock().unwrap();
+
+    if let Some((_, _, nucleons)) = cache
+        .iter()
+        .find(|(z, n, _)| *z == protons && *n == neutrons)
+    {
+        return nucleons.clone();
+    }
+
+    let nucleons = pack(protons, neutrons);
+    cache.push((protons, neutrons, nucleons.clone()));
+    nucleons
+}
+
+pub fn radius(mass_number: u32) -> f32 {
+    // random packing of spheres fills about 64% of space
+    // so this ball is roughly the size a packed nucleus of A nucleons ends up
+    NUCLEON_RADIUS * (mass_number as f32 / 0.64).cbrt()
+}
+
+fn pack(protons: u32, neutrons: u32) -> Vec<Nucleon> {
+    let count = (protons + neutrons) as usize;
+    let mut rng = StdRng::seed_from_u64(((protons as u64) << 32) | neutrons as u64);
+
+    let start_radius = radius(protons + neutrons);
+
+    let mut positions: Vec<glm::Vec3> = (0..count)
+        .map(|_| loop {
+            // rejection sample a point in the unit ball
+            let p = glm::vec3(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            if glm::length(&p) <= 1.0 {
+                break p * start_radius;
+            }
+        })
+        .collect();
+
+    let min_distance = 2.0 * NUCLEON_RADIUS;
+
+    for _ in 0..RELAX_ITERATIONS {
+        // "strong force": everybody drifts inward
+        for p in positions.iter_mut() {
+            *p *= 1.0 - CENTER_PULL;
+        }
+
+        // hard spheres: split every overlap evenly between the two
+        for i in 0..count {
+            for j in i + 1..count {
+                let delta = positions[j] - positions[i];
+                let distance = glm::length(&delta);
+                if distance >= min_distance {
+                    continue;
+                }
+
+                // two nucleons on the exact same spot have no push direction, invent one
+                let dir = if distance > 1e-6 {
+                    delta / distance
+                } else {
+                    glm::normalize(&glm::vec3(
+                        rng.gen_range(-1.0..1.0),
+                        rng.gen_range(-1.0..1.0),
+                        rng.gen_range(-1.0..1.0),
+                    ))
+                };
+                let push = dir * ((min_distance - distance) * 0.5);
+                positions[i] -= push;
+                positions[j] += push;
+            }
+        }
+    }
+
+    // positions are all equivalent, so deciding who is a proton is just a shuffle
+    let mut is_proton: Vec<bool> = (0..count).map(|i| i < protons as usize).collect();
+    is_proton.shuffle(&mut rng);
+
+    positions
+        .into_iter()
+        .zip(is_proton)
+        .map(|(position, is_proton)| Nucleon {
+            position,
+            is_proton,
+        })
+        .collect()
+}
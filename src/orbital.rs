@@ -0,0 +1,468 @@
+use crate::physics; // radial wavefunction + legendre polynomials live there
+use crate::render::{ShaderError, ShaderProgram, VertexArray}; // gpu buffer wrapper, compute sampler
+use nalgebra_glm as glm; // vectors
+use rand::Rng; // uniform proposals for rejection sampling
+use serde::{Deserialize, Serialize}; // the chosen orbital goes into save files
+use std::f64::consts::PI;
+use std::{mem, ptr}; // buffer sizes, no initial data for the sample buffer
+
+// probability cloud view of a hydrogen orbital
+// physics.rs samples the complex |psi|^2 with separable cdfs (phi is uniform there)
+// this one samples the REAL orbitals (p_x, p_y, d_xy, ...) with plain rejection sampling
+// so the lobes people recognize from textbooks actually show up
+// rendered as GL_POINTS with additive blending: dense regions glow, sparse ones fade
+// on gl 4.3+ the same rejection sampling runs in a compute shader instead
+// (assets/shaders/orbital_sample.comp), writing the points straight into the vertex buffer,
+// so millions of samples take a moment instead of minutes; older drivers use the cpu path
+
+// vertex layout of the point buffer: position (vec3) + color (vec4)
+const POINT_LAYOUT: &[i32] = &[3, 4];
+
+// rejection sampling needs a bound on |psi|^2
+// we estimate it from random probes and pad it a bit
+const MAX_PROBES: usize = 20000;
+const MAX_PADDING: f64 = 1.5;
+
+// sign of psi -> color, so the two phases of a lobe differ
+// positive = warm orange, negative = cool blue
+pub const POSITIVE_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 0.55, 0.15, 0.35);
+pub const NEGATIVE_COLOR: glm::Vec4 = glm::Vec4::new(0.2, 0.5, 1.0, 0.35);
+
+// give up on a sample after this many rejected proposals (per sample on the gpu,
+// times the sample count on the cpu) instead of spinning forever on pathological input
+const ATTEMPTS_PER_SAMPLE: usize = 5000;
+
+// compute path: samples per dispatch, small batches keep each one well below
+// the driver's watchdog timeout; 256 = local_size_x in the shader
+const GPU_BATCH: usize = 1 << 16;
+const GPU_GROUP: usize = 256;
+
+// highest n the selector goes up to; the sampling cube grows like n^2 and is mostly empty,
+// so past this the cpu path takes ages for a cloud nobody can make out anyway
+pub const MAX_N: i32 = 6;
+
+// which orbital the view shows, picked at startup and changed with N / L / J / F or the panel
+// subshell = every m of that n, l at once, each in its own color; together they come out
+// spherical (unsold's theorem), which is why a full shell looks like a ball
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Orbital {
+    pub n: i32,
+    pub l: i32,
+    pub m: i32,
+    pub subshell: bool,
+}
+
+impl Orbital {
+    pub fn new(n: i32, l: i32, m: i32) -> Self {
+        // not checked, a bad prompt answer just draws nothing until the numbers are stepped
+        Self {
+            n,
+            l,
+            m,
+            subshell: false,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.n >= 1 && self.l >= 0 && self.l < self.n && self.m.abs() <= self.l
+    }
+
+    pub fn clamped(self) -> Self {
+        // nearest valid combination, l and m give way to n
+        let n = self.n.clamp(1, MAX_N);
+        let l = self.l.clamp(0, n - 1);
+        Self {
+            n,
+            l,
+            m: self.m.clamp(-l, l),
+            subshell: self.subshell,
+        }
+    }
+
+    // steps wrap around at the ends of the allowed range, l and m are clamped to fit
+    pub fn step_n(self, up: bool) -> Self {
+        let n = wrap(self.n, up, 1, MAX_N);
+        Self { n, ..self }.clamped()
+    }
+
+    pub fn step_l(self, up: bool) -> Self {
+        let current = self.clamped();
+        let l = wrap(current.l, up, 0, current.n - 1);
+        Self { l, ..current }.clamped()
+    }
+
+    pub fn step_m(self, up: bool) -> Self {
+        let current = self.clamped();
+        let m = wrap(current.m, up, -current.l, current.l);
+        Self { m, ..current }
+    }
+
+    pub fn name(&self) -> String {
+        // textbook names, "2p_x", "3d_z²", or "3d (all 5)" for the whole subshell
+        // textbooks put the polar axis along z, here it is y (up on screen), so
+        // z in a name means up and y means into the screen
+        let letter = letter(self.l);
+        if self.subshell {
+            return format!("{}{} (all {})", self.n, letter, 2 * self.l + 1);
+        }
+        let shape = match (self.l, self.m) {
+            (0, _) => "",
+            (1, 0) => "_z",
+            (1, 1) => "_x",
+            (1, -1) => "_y",
+            (2, 0) => "_z²",
+            (2, 1) => "_xz",
+            (2, -1) => "_yz",
+            (2, 2) => "_x²-y²",
+            (2, -2) => "_xy",
+            (3, 0) => "_z³",
+            (3, 1) => "_xz²",
+            (3, -1) => "_yz²",
+            (3, 2) => "_z(x²-y²)",
+            (3, -2) => "_xyz",
+            (3, 3) => "_x(x²-3y²)",
+            (3, -3) => "_y(3x²-y²)",
+            // nobody has names for these
+            _ => return format!("{}{} (m={})", self.n, letter, self.m),
+        };
+        format!("{}{}{}", self.n, letter, shape)
+    }
+
+    fn parts(&self, count: usize) -> Vec<(i32, usize, Option<glm::Vec4>)> {
+        // (m, samples, color) for every orbital that gets drawn
+        // color None = by the sign of psi; a subshell is colored by m instead, the lobes of
+        // neighbouring orbitals would be impossible to tell apart otherwise
+        if !self.subshell {
+            return vec![(self.m, count, None)];
+        }
+        let orbitals = (2 * self.l + 1) as usize;
+        (-self.l..=self.l)
+            .enumerate()
+            .map(|(i, m)| {
+                // an equal share each, so the densities add up like the real sum over m
+                let share = count / orbitals + usize::from(i < count % orbitals);
+                (m, share, Some(subshell_color(i, orbitals)))
+            })
+            .collect()
+    }
+}
+
+pub fn letter(l: i32) -> &'static str {
+    // spectroscopic letter of l, far enough for every l up to MAX_N - 1
+    ["s", "p", "d", "f", "g", "h"]
+        .get(l as usize)
+        .copied()
+        .unwrap_or("?")
+}
+
+fn wrap(value: i32, up: bool, min: i32, max: i32) -> i32 {
+    let span = max - min + 1;
+    let step = if up { 1 } else { -1 };
+    (value - min + step).rem_euclid(span) + min
+}
+
+fn subshell_color(index: usize, count: usize) -> glm::Vec4 {
+    // evenly spread hues around the color wheel, same alpha as the sign colors
+    let hue = index as f32 / count as f32 * 6.0;
+    let channel = |offset: f32| {
+        let k = (hue + offset) % 6.0;
+        1.0 - (k.min(4.0 - k).clamp(0.0, 1.0)) * 0.85
+    };
+    glm::vec4(channel(5.0), channel(3.0), channel(1.0), POSITIVE_COLOR.w)
+}
+
+pub fn real_wavefunction(n: i32, l: i32, m: i32, r: f64, theta: f64, phi: f64) -> f64 {
+    // psi = R_nl(r) * P_l^|m|(cos theta) * angular phase
+    // real combination of +m and -m:
+    //   m > 0 -> cos(m phi), m < 0 -> sin(|m| phi), m = 0 -> 1
+    // angular normalization is skipped, rejection sampling only cares about ratios
+
+    let radial = physics::radial_wavefunction(n, l, r);
+    let legendre = physics::associated_legendre(l, m.abs(), theta.cos());
+
+    let azimuthal = match m {
+        0 => 1.0,
+        m if m > 0 => (m as f64 * phi).cos(),
+        m => (-m as f64 * phi).sin(),
+    };
+
+    radial * legendre * azimuthal
+}
+
+fn sampling_radius(n: i32) -> f64 {
+    // half-size of the sampling cube
+    // <r> ~ 1.5 n^2, tails die off quickly after ~3 n^2
+    4.0 * (n * n) as f64 + 4.0
+}
+
+fn to_spherical(p: &glm::DVec3) -> (f64, f64, f64) {
+    // inverse of physics::spherical_to_cartesian
+    // same convention: y is the polar axis
+    let r = glm::length(p);
+    if r == 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+    let theta = (p.y / r).clamp(-1.0, 1.0).acos();
+    let phi = p.z.atan2(p.x).rem_euclid(2.0 * PI);
+    (r, theta, phi)
+}
+
+pub struct OrbitalSample {
+    pub position: glm::Vec3,
+    pub color: glm::Vec4,
+}
+
+fn propose(rng: &mut impl Rng, extent: f64) -> glm::DVec3 {
+    // uniform point in the sampling cube
+    glm::vec3(
+        rng.gen_range(-extent..extent),
+        rng.gen_range(-extent..extent),
+        rng.gen_range(-extent..extent),
+    )
+}
+
+fn psi_at(n: i32, l: i32, m: i32, p: &glm::DVec3) -> f64 {
+    // psi at a cartesian point, callers square it (and keep the sign for the color)
+    let (r, theta, phi) = to_spherical(p);
+    real_wavefunction(n, l, m, r, theta, phi)
+}
+
+fn density_bound(n: i32, l: i32, m: i32) -> Option<f64> {
+    // rejection sampling needs a bound on |psi|^2, same one for the cpu and gpu paths
+    // None = invalid quantum numbers (or a cube full of nodes), nothing to draw
+    let mut rng = rand::thread_rng();
+    let extent = sampling_radius(n);
+    let mut max_density: f64 = 0.0;
+    for _ in 0..MAX_PROBES {
+        let psi = psi_at(n, l, m, &propose(&mut rng, extent));
+        max_density = max_density.max(psi * psi);
+    }
+    (max_density > 0.0).then_some(max_density * MAX_PADDING)
+}
+
+pub fn sample_orbital(orbital: &Orbital, count: usize) -> Vec<OrbitalSample> {
+    // every part of the orbital (one, or a whole subshell) into one list
+    let mut samples = Vec::with_capacity(count);
+    for (m, share, color) in orbital.parts(count) {
+        samples.extend(sample_part(orbital.n, orbital.l, m, share, color));
+    }
+    samples
+}
+
+fn sample_part(
+    n: i32,
+    l: i32,
+    m: i32,
+    count: usize,
+    color: Option<glm::Vec4>,
+) -> Vec<OrbitalSample> {
+    // rejection sampling:
+    // propose a uniform point in a cube, accept with probability |psi|^2 / max
+    // simple, unbiased, and slow for big n (most of the cube is empty)
+
+    let mut rng = rand::thread_rng();
+    let extent = sampling_radius(n);
+    let Some(bound) = density_bound(n, l, m) else {
+        return Vec::new();
+    };
+
+    let mut samples = Vec::with_capacity(count);
+
+    let max_attempts = count.saturating_mul(ATTEMPTS_PER_SAMPLE);
+    let mut attempts = 0;
+
+    while samples.len() < count && attempts < max_attempts {
+        attempts += 1;
+
+        let p = propose(&mut rng, extent);
+        let psi = psi_at(n, l, m, &p);
+
+        if rng.gen::<f64>() * bound < psi * psi {
+            let color = color.unwrap_or(if psi >= 0.0 {
+                POSITIVE_COLOR
+            } else {
+                NEGATIVE_COLOR
+            });
+
+            samples.push(OrbitalSample {
+                position: glm::vec3(p.x as f32, p.y as f32, p.z as f32),
+                color,
+            });
+        }
+    }
+
+    samples
+}
+
+pub struct OrbitalCloud {
+    // gpu side of the point cloud
+    vertex_array: Option<VertexArray>, // None when sampling produced nothing
+    orbital: Orbital,                  // what the points show
+    count: usize,                      // for sampling again when the orbital changes
+    gpu: bool,
+}
+
+impl OrbitalCloud {
+    pub unsafe fn sample(orbital: Orbital, count: usize, gpu: bool) -> Self {
+        Self {
+            vertex_array: Self::sample_points(&orbital, count, gpu),
+            orbital,
+            count,
+            gpu,
+        }
+    }
+
+    pub unsafe fn follow(&mut self, orbital: &Orbital) {
+        // call before drawing: samples again when another orbital was picked
+        // (only while the orbital view is up, nobody waits for a cloud they cannot see)
+        if *orbital == self.orbital {
+            return;
+        }
+        log::info!(
+            "sampling {} orbital points for {}",
+            self.count,
+            orbital.name()
+        );
+        self.orbital = *orbital;
+        self.vertex_array = Self::sample_points(orbital, self.count, self.gpu);
+        if self.vertex_array.is_none() {
+            println!(
+                "No samples for {} (invalid quantum numbers?)",
+                orbital.name()
+            );
+        }
+    }
+
+    unsafe fn sample_points(orbital: &Orbital, count: usize, gpu: bool) -> Option<VertexArray> {
+        // compute shader when asked for and the driver has it, cpu rejection sampling otherwise
+        // (also when the compute shader fails to build, a broken driver should not cost the view)
+        if !orbital.is_valid() {
+            return None;
+        }
+        if gpu && compute_supported() {
+            match Self::sample_on_gpu(orbital, count) {
+                Ok(vertex_array) => return vertex_array,
+                Err(err) => log::error!(
+                    "{}
+sampling the orbital on the cpu instead",
+                    err
+                ),
+            }
+        } else if gpu {
+            log::info!("compute shaders need GL 4.3, sampling the orbital on the cpu");
+        }
+        upload(&sample_orbital(orbital, count))
+    }
+
+    unsafe fn sample_on_gpu(
+        orbital: &Orbital,
+        count: usize,
+    ) -> Result<Option<VertexArray>, ShaderError> {
+        // fill a buffer in batches with the compute shader, then hand it to a vao as is
+        // the points never exist on the cpu side
+        // a subshell is one run of the shader per m, each into its own stretch of the buffer
+        let program = ShaderProgram::orbital_sample()?;
+        let (n, l) = (orbital.n, orbital.l);
+        let mut parts = Vec::new();
+        for (m, share, color) in orbital.parts(count) {
+            let Some(bound) = density_bound(n, l, m) else {
+                return Ok(None);
+            };
+            parts.push((m, share, color, bound));
+        }
+        if count == 0 {
+            return Ok(None);
+        }
+        log::debug!("sampling {} orbital points with a compute shader", count);
+
+        let floats_per_sample: i32 = POINT_LAYOUT.iter().sum();
+        let mut buffer = 0;
+        gl::GenBuffers(1, &mut buffer);
+        gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, buffer);
+        gl::BufferData(
+            gl::SHADER_STORAGE_BUFFER,
+            (count * floats_per_sample as usize * mem::size_of::<f32>()) as isize,
+            ptr::null(),
+            gl::STATIC_DRAW,
+        );
+        gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, 0, buffer);
+
+        program.use_program();
+        program.set_uniform_i32("n", n);
+        program.set_uniform_i32("l", l);
+        program.set_uniform_f32("radialNorm", physics::radial_normalization(n, l) as f32);
+        program.set_uniform_f32("extent", sampling_radius(n) as f32);
+        program.set_uniform_u32("maxAttempts", ATTEMPTS_PER_SAMPLE as u32);
+        program.set_uniform_u32("seed", rand::random());
+        let mut start = 0;
+        for (m, share, color, bound) in parts {
+            // sampleCount is where this part ends, the shader skips everything past it
+            let end = start + share;
+            program.set_uniform_i32("m", m);
+            program.set_uniform_f32("bound", bound as f32);
+            program.set_uniform_u32("sampleCount", end as u32);
+            program.set_uniform_vec4("positiveColor", &color.unwrap_or(POSITIVE_COLOR));
+            program.set_uniform_vec4("negativeColor", &color.unwrap_or(NEGATIVE_COLOR));
+            for first in (start..end).step_by(GPU_BATCH) {
+                let batch = GPU_BATCH.min(end - first);
+                program.set_uniform_u32("firstSample", first as u32);
+                gl::DispatchCompute(batch.div_ceil(GPU_GROUP) as u32, 1, 1);
+            }
+            start = end;
+        }
+        // the writes have to land before the buffer is read as vertices
+        gl::MemoryBarrier(gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+        gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, 0, 0);
+        gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        check_gl_error!("orbital compute sampling");
+
+        Ok(Some(VertexArray::from_buffer(
+            buffer,
+            count as i32,
+            POINT_LAYOUT,
+        )))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertex_array.is_none()
+    }
+
+    pub unsafe fn draw(&self) {
+        // additive blending, no depth writes
+        // points add up light instead of hiding each other
+        let Some(vertex_array) = &self.vertex_array else {
+            return;
+        };
+
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE);
+        gl::DepthMask(gl::FALSE);
+        gl::Enable(gl::PROGRAM_POINT_SIZE);
+
+        vertex_array.bind();
+        vertex_array.draw(gl::POINTS);
+
+        gl::Disable(gl::PROGRAM_POINT_SIZE);
+        gl::DepthMask(gl::TRUE);
+        gl::Disable(gl::BLEND);
+    }
+}
+
+unsafe fn upload(samples: &[OrbitalSample]) -> Option<VertexArray> {
+    // interleave position + color and upload once
+    let mut vertices = Vec::with_capacity(samples.len() * 7);
+    for sample in samples {
+        vertices.extend_from_slice(sample.position.as_slice());
+        vertices.extend_from_slice(sample.color.as_slice());
+    }
+    (!vertices.is_empty()).then(|| VertexArray::new(&vertices, POINT_LAYOUT))
+}
+
+unsafe fn compute_supported() -> bool {
+    // compute shaders + storage buffers are core in 4.3
+    // the context is whatever the driver hands out by default, so ask it
+    let (mut major, mut minor) = (0, 0);
+    gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+    gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    (major, minor) >= (4, 3) && gl::DispatchCompute::is_loaded()
+}
@@ -0,0 +1,172 @@
+use crate::decay; // random directions for bursts
+use crate::quality::Quality; // the default cap
+use crate::render::{
+    generate_quad, InstancedRenderer, ShaderError, ShaderProgram, VertexArray, WatchedProgram,
+};
+use nalgebra_glm as glm;
+
+// small glowing effects: sparks, photon streaks, decay debris
+// cpu side every particle is a point with a velocity, a lifetime and a look that
+// changes over its life (size and color from birth to death)
+// gpu side they are all camera-facing soft quads in one instanced draw call,
+// blended additively so overlaps glow instead of hiding each other
+
+// hard cap, new particles are dropped once it is hit
+// effects are decoration, the frame rate is not; how many depends on the render quality
+
+pub struct Particle {
+    pub position: glm::Vec3,
+    pub velocity: glm::Vec3,
+    pub lifetime: f32,                 // seconds from birth to gone
+    pub size: (f32, f32),              // radius at birth, at death
+    pub color: (glm::Vec4, glm::Vec4), // rgba at birth, at death
+    pub drag: f32,                     // fraction of the velocity lost per second
+    age: f32,
+}
+
+impl Particle {
+    pub fn new(
+        position: glm::Vec3,
+        velocity: glm::Vec3,
+        lifetime: f32,
+        size: (f32, f32),
+        color: (glm::Vec4, glm::Vec4),
+    ) -> Self {
+        Self {
+            position,
+            velocity,
+            lifetime,
+            size,
+            color,
+            drag: 0.0,
+            age: 0.0,
+        }
+    }
+
+    fn life(&self) -> f32 {
+        // 0 at birth, 1 at death
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+
+    pub fn current_size(&self) -> f32 {
+        let t = self.life();
+        self.size.0 + (self.size.1 - self.size.0) * t
+    }
+
+    pub fn current_color(&self) -> glm::Vec4 {
+        glm::lerp(&self.color.0, &self.color.1, self.life())
+    }
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    pub limit: usize, // the cap, Quality::max_particles, set by the scene every step
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::new(),
+            limit: Quality::High.max_particles(),
+        }
+    }
+
+    pub fn spawn(&mut self, particle: Particle) {
+        if self.particles.len() < self.limit {
+            self.particles.push(particle);
+        }
+    }
+
+    pub fn burst(&mut self, position: glm::Vec3, count: usize, speed: f32, rgb: glm::Vec3) {
+        // sparks flying out in every direction, slowing down, fading to nothing
+        // speeds vary so the burst does not look like a perfect expanding shell
+        for _ in 0..count {
+            let speed = speed * (0.3 + 0.7 * rand::random::<f32>());
+            let lifetime = 0.4 + 0.6 * rand::random::<f32>();
+            let mut spark = Particle::new(
+                position,
+                decay::random_direction() * speed,
+                lifetime,
+                (0.15, 0.02),
+                (
+                    glm::vec4(rgb.x, rgb.y, rgb.z, 1.0),
+                    glm::vec4(rgb.x, rgb.y, rgb.z, 0.0),
+                ),
+            );
+            spark.drag = 2.0;
+            self.spawn(spark);
+        }
+    }
+
+    pub fn streak(&mut self, position: glm::Vec3, size: f32, rgb: glm::Vec3) {
+        // one resting puff that fades where something just flew by
+        // drop one per step and a moving thing leaves a glowing tail
+        self.spawn(Particle::new(
+            position,
+            glm::Vec3::zeros(),
+            0.5,
+            (size, 0.0),
+            (
+                glm::vec4(rgb.x, rgb.y, rgb.z, 0.6),
+                glm::vec4(rgb.x, rgb.y, rgb.z, 0.0),
+            ),
+        ));
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.velocity *= (1.0 - particle.drag * dt).max(0.0);
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter()
+    }
+}
+
+pub struct ParticleRenderer {
+    // every live particle becomes one instance of a unit quad
+    // the vertex shader turns it to face the camera, so only position and size go up
+    program: WatchedProgram,
+    quads: InstancedRenderer,
+}
+
+impl ParticleRenderer {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        Ok(Self {
+            program: WatchedProgram::new("particle", ShaderProgram::particle)?,
+            quads: InstancedRenderer::new(VertexArray::from_mesh(&generate_quad())),
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn draw(&mut self, particles: &ParticleSystem) {
+        self.quads.clear();
+        for particle in particles.iter() {
+            let s = particle.current_size();
+            let model = glm::scale(&glm::translation(&particle.position), &glm::vec3(s, s, s));
+            self.quads.push(&model, &particle.current_color());
+        }
+        if self.quads.instance_count() == 0 {
+            return;
+        }
+
+        self.program.use_program(); // camera from the shared Frame block
+
+        // additive, depth tested against the solid spheres but not writing depth,
+        // so particles never cut holes into each other
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE);
+        gl::DepthMask(gl::FALSE);
+        self.quads.draw();
+        gl::DepthMask(gl::TRUE);
+        gl::Disable(gl::BLEND);
+    }
+}
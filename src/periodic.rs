@@ -0,0 +1,257 @@
+use crate::elements::{self, Element}; // cell contents + tooltip data
+use crate::scene::Scene;
+use crate::text::{hud_size, TextRenderer}; // cells are drawn with the hud text batch
+use crate::window::Window; // cursor -> hud pixels
+use glfw::{Action, MouseButton};
+use nalgebra_glm as glm;
+
+// periodic table overlay, toggled with T
+// 18 columns x 7 periods, lanthanides and actinides in two rows underneath
+// click a cell to make that element the main atom, hover for name, mass and configuration
+// everything is laid out in hud pixels, same space the hud text uses
+
+const COLUMNS: f32 = 18.0;
+const ROWS: f32 = 9.5; // 7 periods, half a row of gap, 2 f-block rows
+const F_BLOCK_ROW: f32 = 7.5;
+
+pub struct PeriodicTable {
+    hovered: Option<u32>, // atomic number under the cursor
+    pointer: glm::Vec2,   // cursor in hud pixels, for the tooltip
+}
+
+struct Layout {
+    // top-left corner of the grid and the size of one cell, all pixels
+    x: f32,
+    y: f32,
+    cell: f32,
+}
+
+impl Layout {
+    fn new(width: u32, height: u32) -> Self {
+        // as big as fits in 90% of the width / 75% of the height, centered
+        let cell = (width as f32 * 0.9 / COLUMNS).min(height as f32 * 0.75 / ROWS);
+        Self {
+            x: (width as f32 - COLUMNS * cell) * 0.5,
+            y: (height as f32 - ROWS * cell) * 0.5,
+            cell,
+        }
+    }
+
+    fn cell_rect(&self, z: u32) -> (f32, f32, f32) {
+        // top-left corner and side length of an element's cell, minus a small gap
+        let (column, row) = grid_position(z);
+        let gap = (self.cell * 0.08).max(1.0);
+        (
+            self.x + column as f32 * self.cell + gap * 0.5,
+            self.y + row * self.cell + gap * 0.5,
+            self.cell - gap,
+        )
+    }
+
+    fn pick(&self, x: f32, y: f32) -> Option<u32> {
+        elements::ELEMENTS
+            .iter()
+            .map(|element| element.atomic_number)
+            .find(|&z| {
+                let (cx, cy, size) = self.cell_rect(z);
+                x >= cx && x < cx + size && y >= cy && y < cy + size
+            })
+    }
+}
+
+impl PeriodicTable {
+    pub fn new() -> Self {
+        Self {
+            hovered: None,
+            pointer: glm::vec2(0.0, 0.0),
+        }
+    }
+
+    pub fn handle_event(
+        &mut self,
+        window: &Window,
+        event: &glfw::WindowEvent,
+        scene: &mut Scene,
+    ) -> bool {
+        // returns true if the click picked an element and nobody else should see it
+        if !scene.display.periodic_table {
+            self.hovered = None;
+            return false;
+        }
+
+        match event {
+            glfw::WindowEvent::CursorPos(x, y) => {
+                // glfw cursor coords are in window units, the layout is in hud pixels
+                // (framebuffer pixels / hud_scale, see Renderer::draw_periodic_table)
+                let (ratio_x, ratio_y) = window.pixel_ratio();
+                let scale = scene.display.hud_scale();
+                self.pointer =
+                    glm::vec2((*x * ratio_x) as f32 / scale, (*y * ratio_y) as f32 / scale);
+                let (fb_width, fb_height) = window.get_framebuffer_size();
+                let layout = Layout::new(
+                    hud_size(fb_width as u32, scale),
+                    hud_size(fb_height as u32, scale),
+                );
+                self.hovered = layout.pick(self.pointer.x, self.pointer.y);
+                false
+            }
+            glfw::WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                match self.hovered.and_then(elements::by_atomic_number) {
+                    Some(element) => {
+                        scene.set_element(0, element);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    pub fn draw(
+        &self,
+        text: &mut TextRenderer,
+        current: u32,
+        highlight: &glm::Vec3,
+        width: u32,
+        height: u32,
+    ) {
+        // queue the whole table into the text batch, caller flushes
+        let layout = Layout::new(width, height);
+
+        // dim the scene behind so the cells stay readable
+        text.draw_rect(
+            0.0,
+            0.0,
+            width as f32,
+            height as f32,
+            &glm::vec4(0.0, 0.0, 0.0, 0.5),
+        );
+
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        let title_scale = (layout.cell / 16.0).floor().max(1.0);
+        text.draw_text(
+            "click an element to simulate it",
+            layout.x,
+            layout.y - 10.0 * title_scale,
+            title_scale,
+            &white,
+        );
+
+        // symbols take about half the cell width, pixel fonts only scale by whole numbers
+        let symbol_scale = (layout.cell * 0.5 / TextRenderer::text_width("XX", 1.0))
+            .floor()
+            .max(1.0);
+
+        for element in elements::ELEMENTS.iter() {
+            let z = element.atomic_number;
+            let (x, y, size) = layout.cell_rect(z);
+
+            if z == current {
+                // outline the element being simulated
+                let border = (size * 0.06).max(1.0);
+                text.draw_rect(
+                    x - border,
+                    y - border,
+                    size + 2.0 * border,
+                    size + 2.0 * border,
+                    &glm::vec4(highlight.x, highlight.y, highlight.z, 1.0),
+                );
+            }
+
+            let mut color = block_color(z);
+            if self.hovered == Some(z) {
+                color = glm::vec4(color.x + 0.25, color.y + 0.25, color.z + 0.25, 1.0);
+            }
+            text.draw_rect(x, y, size, size, &color);
+
+            let symbol_width = TextRenderer::text_width(element.symbol, symbol_scale);
+            text.draw_text(
+                element.symbol,
+                x + (size - symbol_width) * 0.5,
+                y + (size - 7.0 * symbol_scale) * 0.5,
+                symbol_scale,
+                &white,
+            );
+        }
+
+        if let Some(element) = self.hovered.and_then(elements::by_atomic_number) {
+            draw_tooltip(text, element, self.pointer, width, height);
+        }
+    }
+}
+
+fn draw_tooltip(
+    text: &mut TextRenderer,
+    element: &Element,
+    pointer: glm::Vec2,
+    width: u32,
+    height: u32,
+) {
+    let scale = 2.0;
+    let padding = 6.0;
+    let lines = format!(
+        "{} {} ({})\nmass {:.3} u\n{}",
+        element.atomic_number,
+        element.local_name(),
+        element.symbol,
+        element.atomic_mass,
+        elements::electron_configuration(element.atomic_number)
+    );
+    let box_width = TextRenderer::text_width(&lines, scale) + 2.0 * padding;
+    let box_height = 3.0 * 8.0 * scale + 2.0 * padding;
+
+    // below-right of the cursor, flipped back inside when it would leave the window
+    let mut x = pointer.x + 16.0;
+    let mut y = pointer.y + 16.0;
+    if x + box_width > width as f32 {
+        x = pointer.x - box_width - 4.0;
+    }
+    if y + box_height > height as f32 {
+        y = pointer.y - box_height - 4.0;
+    }
+
+    text.draw_rect(x, y, box_width, box_height, &glm::vec4(0.1, 0.1, 0.1, 0.9));
+    text.draw_text(
+        &lines,
+        x + padding,
+        y + padding,
+        scale,
+        &glm::vec4(1.0, 1.0, 1.0, 1.0),
+    );
+}
+
+fn grid_position(z: u32) -> (u32, f32) {
+    // (column, row) of an element in the usual 18 column layout
+    // la-lu and ac-lr go into the f-block rows, group 3 of periods 6 and 7 stays empty
+    match z {
+        1 => (0, 0.0),
+        2 => (17, 0.0),
+        3..=4 => (z - 3, 1.0),
+        5..=10 => (z - 5 + 12, 1.0),
+        11..=12 => (z - 11, 2.0),
+        13..=18 => (z - 13 + 12, 2.0),
+        19..=36 => (z - 19, 3.0),
+        37..=54 => (z - 37, 4.0),
+        55..=56 => (z - 55, 5.0),
+        57..=71 => (z - 57 + 2, F_BLOCK_ROW),
+        72..=86 => (z - 72 + 3, 5.0),
+        87..=88 => (z - 87, 6.0),
+        89..=103 => (z - 89 + 2, F_BLOCK_ROW + 1.0),
+        _ => (z - 104 + 3, 6.0),
+    }
+}
+
+fn block_color(z: u32) -> glm::Vec4 {
+    // cell background by which subshell is being filled: s, p, d, f
+    let (column, row) = grid_position(z);
+    if row >= F_BLOCK_ROW {
+        glm::vec4(0.45, 0.3, 0.5, 1.0)
+    } else if column < 2 || z == 2 {
+        glm::vec4(0.6, 0.25, 0.25, 1.0)
+    } else if column >= 12 {
+        glm::vec4(0.25, 0.45, 0.3, 1.0)
+    } else {
+        glm::vec4(0.25, 0.35, 0.55, 1.0)
+    }
+}
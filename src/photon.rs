@@ -0,0 +1,117 @@
+use nalgebra_glm as glm;
+
+// light given off when an electron falls to a lower shell
+// a small glowing ball that flies away from the atom and shrinks to nothing
+// its color is the color of light with the transition's wavelength
+// photons fired at an atom (see absorption.rs) keep their size until they are absorbed or
+// have flown through, and then go on like any other
+
+const PHOTON_SPEED: f32 = 12.0; // world units per simulated second, c at didactic scale
+const PHOTON_LIFETIME: f32 = 3.0; // seconds until it is gone
+const PHOTON_RADIUS: f32 = 0.3;
+const INCOMING_LIFETIME: f32 = 20.0; // long enough to reach any atom
+
+pub struct Photon {
+    pub position: glm::Vec3,
+    previous: glm::Vec3, // position one simulation step ago
+    pub velocity: glm::Vec3,
+    pub color: glm::Vec3,
+    pub wavelength: f32,       // nm
+    pub target: Option<usize>, // fired at this atom in Scene::atoms, None once past it
+    age: f32,
+}
+
+impl Photon {
+    pub fn new(position: glm::Vec3, direction: glm::Vec3, wavelength: f32) -> Self {
+        Self {
+            position,
+            previous: position,
+            velocity: direction * PHOTON_SPEED,
+            color: wavelength_color(wavelength),
+            wavelength,
+            target: None,
+            age: 0.0,
+        }
+    }
+
+    pub fn incoming(
+        position: glm::Vec3,
+        direction: glm::Vec3,
+        wavelength: f32,
+        target: usize,
+    ) -> Self {
+        Self {
+            target: Some(target),
+            ..Self::new(position, direction, wavelength)
+        }
+    }
+
+    pub fn pass(&mut self) {
+        // not absorbed, from here on it flies off and fades like one given off
+        self.target = None;
+        self.age = 0.0;
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        self.previous = self.position;
+        self.position += self.velocity * dt;
+        self.age += dt;
+    }
+
+    pub fn alive(&self) -> bool {
+        if self.target.is_some() {
+            return self.age < INCOMING_LIFETIME;
+        }
+        self.age < PHOTON_LIFETIME
+    }
+
+    pub fn interpolated(&self, alpha: f32) -> glm::Vec3 {
+        // where to draw it, alpha = Simulation::alpha
+        glm::lerp(&self.previous, &self.position, alpha)
+    }
+
+    pub fn radius(&self) -> f32 {
+        // shrinks linearly over its lifetime
+        if self.target.is_some() {
+            return PHOTON_RADIUS;
+        }
+        PHOTON_RADIUS * (1.0 - self.age / PHOTON_LIFETIME).max(0.0)
+    }
+}
+
+pub fn wavelength_color(wavelength: f32) -> glm::Vec3 {
+    // visible spectrum, 380 to 780 nm, as piecewise linear rgb (after dan bruton)
+    // eyes cannot see outside that, but invisible photons are no fun,
+    // so ultraviolet / x-rays show as pale violet and infrared as dull red
+    let w = wavelength;
+    if w < 380.0 {
+        return glm::vec3(0.7, 0.5, 1.0);
+    }
+    if w > 780.0 {
+        return glm::vec3(0.5, 0.1, 0.1);
+    }
+
+    let rgb = if w < 440.0 {
+        glm::vec3((440.0 - w) / 60.0, 0.0, 1.0)
+    } else if w < 490.0 {
+        glm::vec3(0.0, (w - 440.0) / 50.0, 1.0)
+    } else if w < 510.0 {
+        glm::vec3(0.0, 1.0, (510.0 - w) / 20.0)
+    } else if w < 580.0 {
+        glm::vec3((w - 510.0) / 70.0, 1.0, 0.0)
+    } else if w < 645.0 {
+        glm::vec3(1.0, (645.0 - w) / 65.0, 0.0)
+    } else {
+        glm::vec3(1.0, 0.0, 0.0)
+    };
+
+    // the eye is less sensitive near both ends, dim them a little
+    let intensity = if w < 420.0 {
+        0.3 + 0.7 * (w - 380.0) / 40.0
+    } else if w > 700.0 {
+        0.3 + 0.7 * (780.0 - w) / 80.0
+    } else {
+        1.0
+    };
+    rgb * intensity
+}
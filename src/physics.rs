@@ -119,7 +119,7 @@ fn sample_r(n: i32, l: i32) -> f64 {
 
     let mut sum = 0.0;
 
-    for i in 0..N_CDF {
+    for (i, slot) in cdf.iter_mut().enumerate() {
         let r = i as f64 * dr;
 
         let rho = 2.0 * r / (n as f64 * A0);
@@ -136,7 +136,7 @@ fn sample_r(n: i32, l: i32) -> f64 {
 
         // r^2 jacobian included here
         sum += r * r * r_wave * r_wave;
-        cdf[i] = sum;
+        *slot = sum;
     }
 
     // normalize cdf
@@ -185,13 +185,13 @@ fn sample_theta(l: i32, m: i32) -> f64 {
 
     let mut sum = 0.0;
 
-    for i in 0..N_CDF {
+    for (i, slot) in cdf.iter_mut().enumerate() {
         let theta = i as f64 * d_theta;
 
         let plm = associated_legendre(l, m_abs, theta.cos());
 
         sum += theta.sin() * plm * plm; // jacobian
-        cdf[i] = sum;
+        *slot = sum;
     }
 
     for val in cdf.iter_mut() {
@@ -240,7 +240,7 @@ fn associated_laguerre(k: i32, alpha: i32, x: f64) -> f64 {
     l_val
 }
 
-fn associated_legendre(l: i32, m: i32, x: f64) -> f64 {
+pub fn associated_legendre(l: i32, m: i32, x: f64) -> f64 {
     // also recurrence
     // because stability > ego
 
@@ -279,18 +279,31 @@ fn associated_legendre(l: i32, m: i32, x: f64) -> f64 {
     pm1m
 }
 
-fn get_particle_color(r: f64, theta: f64, _phi: f64, n: i32, l: i32, m: i32) -> glm::Vec4 {
-    // compute |psi|^2 intensity and map to color
+pub fn radial_wavefunction(n: i32, l: i32, r: f64) -> f64 {
+    // normalized R_nl(r) for hydrogen
+    // shared by the color mapping here and the rejection sampler in orbital.rs
 
     let rho = 2.0 * r / (n as f64 * A0);
 
     let laguerre = associated_laguerre(n - l - 1, 2 * l + 1, rho);
 
+    radial_normalization(n, l) * (-rho / 2.0).exp() * rho.powi(l) * laguerre
+}
+
+pub fn radial_normalization(n: i32, l: i32) -> f64 {
+    // constant in front of R_nl, split out so the gpu sampler can take it as a uniform
+    // (gamma of large arguments is no fun in single precision glsl)
     let norm_part1 = (2.0 / (n as f64 * A0)).powi(3);
     let norm_part2 =
         gamma::gamma((n - l) as f64) / (2.0 * n as f64 * gamma::gamma((n + l + 1) as f64));
 
-    let r_wave = (norm_part1 * norm_part2).sqrt() * (-rho / 2.0).exp() * rho.powi(l) * laguerre;
+    (norm_part1 * norm_part2).sqrt()
+}
+
+fn get_particle_color(r: f64, theta: f64, _phi: f64, n: i32, l: i32, m: i32) -> glm::Vec4 {
+    // compute |psi|^2 intensity and map to color
+
+    let r_wave = radial_wavefunction(n, l, r);
 
     let angular = associated_legendre(l, m.abs(), theta.cos());
 
@@ -304,7 +317,7 @@ fn heatmap_cool(value: f64) -> glm::Vec4 {
     // no fancy colormap library
     // just linear interpolation
 
-    let value = value.max(0.0).min(1.0);
+    let value = value.clamp(0.0, 1.0);
 
     let colors = [
         glm::vec4(0.0, 0.0, 0.0, 1.0),
@@ -0,0 +1,153 @@
+// gpu time per render pass, from GL_TIME_ELAPSED queries, shown in the hud (F3)
+// the cpu side of a frame says little about what the gpu is busy with: a draw call returns
+// as soon as it is queued, the time shows up later in whatever waits on the gpu (the swap)
+// queries cannot nest, so a pass is a run of segments: begin() ends whatever pass was
+// running and starts the next, and a pass that comes back later in the frame (opaque after
+// the see-through spheres) is just another segment summed into it
+// results arrive a few frames late; reading them right away would stall until the gpu
+// caught up, so each frame's queries are read LATENCY frames later instead
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pass {
+    Opaque,      // background and every solid thing
+    Transparent, // see-through spheres and cloud, glows
+    Particles,   // sparks and trails
+    Post,        // ssao, depth of field, bloom, tone mapping, fxaa, gamma, stereo
+    Hud,         // text overlays, plots, periodic table
+    Ui,          // the egui control panel
+}
+
+pub const PASSES: [Pass; 6] = [
+    Pass::Opaque,
+    Pass::Transparent,
+    Pass::Particles,
+    Pass::Post,
+    Pass::Hud,
+    Pass::Ui,
+];
+
+impl Pass {
+    pub fn name(self) -> &'static str {
+        match self {
+            Pass::Opaque => "opaque",
+            Pass::Transparent => "transparent",
+            Pass::Particles => "particles",
+            Pass::Post => "post",
+            Pass::Hud => "hud",
+            Pass::Ui => "ui",
+        }
+    }
+}
+
+const LATENCY: usize = 3; // frames between issuing a query and reading it
+const SMOOTHING: f32 = 0.1; // weight of the newest frame, the numbers jitter a lot
+
+// the queries of one frame
+struct FrameQueries {
+    pool: Vec<gl::types::GLuint>, // grows to the most segments a frame has needed
+    segments: Vec<(Pass, usize)>, // index into pool, in the order they ran
+}
+
+pub struct GpuProfiler {
+    enabled: bool,
+    frames: Vec<FrameQueries>, // ring, LATENCY long
+    current: usize,
+    running: Option<Pass>,
+    milliseconds: [f32; PASSES.len()], // smoothed, by position in PASSES
+}
+
+impl GpuProfiler {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            frames: (0..LATENCY)
+                .map(|_| FrameQueries {
+                    pool: Vec::new(),
+                    segments: Vec::new(),
+                })
+                .collect(),
+            current: 0,
+            running: None,
+            milliseconds: [0.0; PASSES.len()],
+        }
+    }
+
+    pub unsafe fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled && !enabled {
+            self.end();
+            // left over queries would be read as if they were from the next time it is on
+            self.frames
+                .iter_mut()
+                .for_each(|frame| frame.segments.clear());
+            self.milliseconds = [0.0; PASSES.len()];
+        }
+        self.enabled = enabled;
+    }
+
+    // from here on the gpu's time goes to pass, until the next begin or end
+    pub unsafe fn begin(&mut self, pass: Pass) {
+        if !self.enabled || self.running == Some(pass) {
+            return;
+        }
+        self.end();
+        let frame = &mut self.frames[self.current];
+        let index = frame.segments.len();
+        if index == frame.pool.len() {
+            let mut query = 0;
+            gl::GenQueries(1, &mut query);
+            frame.pool.push(query);
+        }
+        gl::BeginQuery(gl::TIME_ELAPSED, frame.pool[index]);
+        frame.segments.push((pass, index));
+        self.running = Some(pass);
+    }
+
+    pub unsafe fn end(&mut self) {
+        if self.running.take().is_some() {
+            gl::EndQuery(gl::TIME_ELAPSED);
+        }
+    }
+
+    // once a frame, after the last pass: collect the frame issued LATENCY - 1 frames ago
+    pub unsafe fn finish_frame(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        self.end();
+        self.current = (self.current + 1) % LATENCY;
+        let frame = &mut self.frames[self.current];
+        let Some(&(_, last)) = frame.segments.last() else {
+            return;
+        };
+        // queries finish in order, if the last one is ready they all are; if not (a very
+        // slow gpu) the frame is dropped rather than waited for
+        let mut available = 0;
+        gl::GetQueryObjectiv(frame.pool[last], gl::QUERY_RESULT_AVAILABLE, &mut available);
+        if available != 0 {
+            let mut totals = [0.0; PASSES.len()];
+            for &(pass, index) in &frame.segments {
+                let mut nanoseconds = 0;
+                gl::GetQueryObjectui64v(frame.pool[index], gl::QUERY_RESULT, &mut nanoseconds);
+                totals[pass as usize] += nanoseconds as f32 / 1.0e6;
+            }
+            for (average, total) in self.milliseconds.iter_mut().zip(totals) {
+                *average += (total - *average) * SMOOTHING;
+            }
+        }
+        frame.segments.clear();
+    }
+
+    pub fn milliseconds(&self, pass: Pass) -> f32 {
+        self.milliseconds[pass as usize]
+    }
+}
+
+impl Drop for GpuProfiler {
+    fn drop(&mut self) {
+        for frame in &self.frames {
+            unsafe {
+                gl::DeleteQueries(frame.pool.len() as i32, frame.pool.as_ptr());
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// render quality (Shift+W), for weak integrated gpus: one knob for the things that cost most
+//   spheres     sectors / stacks of every sphere mesh, the lod levels included, see lod.rs
+//   trails      the longest trail the slider allows, see trail.rs
+//   particles   how many sparks and streaks can be alive at once, see particles.rs
+//   edges       no antialiasing, fxaa, or msaa (when the window got samples)
+//   occlusion   depth samples per pixel of the ambient occlusion pass, see ssao.rs
+//   shadows     texels along each side of the shadow map, see shadow.rs
+// the window's msaa samples are fixed when it is created (--msaa), so low and medium do
+// not get them back, they just stop using them; high is what it was before this setting
+// switching rebuilds the sphere meshes on the next frame, nothing else has to restart
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Quality {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Edges {
+    Jagged, // nothing, the cheapest
+    Fxaa,   // one fullscreen pass, blurrier
+    Msaa,   // multisampling, fxaa when the window has no samples or bloom or ssao is on
+}
+
+impl Quality {
+    pub fn next(self) -> Self {
+        // Shift+W cycles through these
+        match self {
+            Quality::Low => Quality::Medium,
+            Quality::Medium => Quality::High,
+            Quality::High => Quality::Ultra,
+            Quality::Ultra => Quality::Low,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Quality::Low => "low",
+            Quality::Medium => "medium",
+            Quality::High => "high",
+            Quality::Ultra => "ultra",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Quality::Low, Quality::Medium, Quality::High, Quality::Ultra]
+            .into_iter()
+            .find(|quality| quality.name() == name)
+    }
+
+    pub fn tessellation(self, sectors: i32, stacks: i32) -> (i32, i32) {
+        // a sphere mesh laid out for high, at this quality; never below 6 x 4, less than
+        // that stops looking like a ball at all
+        let detail = match self {
+            Quality::Low => 0.5,
+            Quality::Medium => 0.75,
+            Quality::High => 1.0,
+            Quality::Ultra => 1.5,
+        };
+        (
+            ((sectors as f32 * detail).round() as i32).max(6),
+            ((stacks as f32 * detail).round() as i32).max(4),
+        )
+    }
+
+    pub fn max_trail(self) -> usize {
+        match self {
+            Quality::Low => 60,
+            Quality::Medium => 200,
+            Quality::High | Quality::Ultra => 600,
+        }
+    }
+
+    pub fn max_particles(self) -> usize {
+        match self {
+            Quality::Low => 2000,
+            Quality::Medium => 8000,
+            Quality::High => 20000,
+            Quality::Ultra => 50000,
+        }
+    }
+
+    pub fn occlusion_samples(self) -> i32 {
+        match self {
+            Quality::Low => 8,
+            Quality::Medium => 12,
+            Quality::High => 16,
+            Quality::Ultra => 32,
+        }
+    }
+
+    pub fn shadow_size(self) -> u32 {
+        match self {
+            Quality::Low => 1024,
+            Quality::Medium | Quality::High => 2048,
+            Quality::Ultra => 4096,
+        }
+    }
+
+    pub fn edges(self) -> Edges {
+        match self {
+            Quality::Low => Edges::Jagged,
+            Quality::Medium => Edges::Fxaa,
+            Quality::High | Quality::Ultra => Edges::Msaa,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::orbital; // subshell letters
+use crate::physics; // R_nl(r)
+use crate::text::TextRenderer; // drawn with the hud's rectangles
+use nalgebra_glm as glm;
+
+// radial probability P(r) = r^2 R_nl(r)^2 of the orbital on screen, as a line chart
+// (H toggles it), bottom left above the spectrum bar
+// how likely the electron is to be found at distance r from the nucleus, summed over
+// every direction; the 3d cloud shows the same thing as how dense its shells of points are
+// marks: most likely radius (peak), <r> (white) and the radial nodes, where R_nl = 0
+// with the cross-section on, its distance from the nucleus is marked too
+
+// plot area in pixels
+const PLOT_WIDTH: f32 = 360.0;
+const PLOT_HEIGHT: f32 = 140.0;
+const MARGIN: f32 = 10.0;
+
+// kept clear along the bottom for the spectrum bar and, on this side, its caption
+const SPECTRUM_SPACE: f32 = 76.0;
+
+const CURVE_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 0.55, 0.15, 1.0);
+
+fn plot_range(n: i32) -> f64 {
+    // x axis in bohr radii, far enough that the outermost bump has died down
+    3.0 * (n * n) as f64 + 3.0
+}
+
+fn expected_radius(n: i32, l: i32) -> f64 {
+    // <r> for hydrogen, exact: (3 n^2 - l (l + 1)) / 2 bohr radii
+    (3 * n * n - l * (l + 1)) as f64 / 2.0
+}
+
+pub fn draw(text: &mut TextRenderer, (n, l): (i32, i32), cut: Option<f32>, height: u32) {
+    // queued into the text batch, the caller flushes
+    // cut: distance of the cross-section plane from the nucleus, when there is one
+    let x0 = MARGIN;
+    let y0 = height as f32 - SPECTRUM_SPACE - PLOT_HEIGHT;
+    let grey = glm::vec4(0.8, 0.8, 0.8, 1.0);
+
+    text.draw_rect(
+        x0,
+        y0,
+        PLOT_WIDTH,
+        PLOT_HEIGHT,
+        &glm::vec4(0.0, 0.0, 0.0, 0.85),
+    );
+    if n < 1 || l < 0 || l >= n {
+        text.draw_text("no such orbital", x0 + 8.0, y0 + 8.0, 2.0, &grey);
+        return;
+    }
+
+    // one sample per 2 pixel column
+    let range = plot_range(n);
+    let columns = (PLOT_WIDTH / 2.0) as usize;
+    let r_at = |column: usize| column as f64 / (columns - 1) as f64 * range;
+    let radial: Vec<f64> = (0..columns)
+        .map(|column| physics::radial_wavefunction(n, l, r_at(column)))
+        .collect();
+    let probability: Vec<f64> = radial
+        .iter()
+        .enumerate()
+        .map(|(column, value)| r_at(column).powi(2) * value * value)
+        .collect();
+    let peak = probability.iter().copied().fold(0.0, f64::max);
+    if peak <= 0.0 {
+        return;
+    }
+
+    // the curve: a vertical run from the previous sample's height to this one's, so steep
+    // flanks stay connected
+    let inner_top = y0 + 22.0; // room for the caption
+    let inner_height = PLOT_HEIGHT - 22.0 - 14.0; // and the axis labels
+    let to_y = |p: f64| inner_top + inner_height * (1.0 - (p / peak) as f32);
+    let to_x = |r: f64| x0 + (r / range) as f32 * PLOT_WIDTH;
+    let mut previous = to_y(probability[0]);
+    for (column, &p) in probability.iter().enumerate() {
+        let y = to_y(p);
+        let (top, bottom) = (previous.min(y), previous.max(y));
+        text.draw_rect(
+            x0 + column as f32 * 2.0,
+            top - 1.0,
+            2.0,
+            bottom - top + 2.0,
+            &CURVE_COLOR,
+        );
+        previous = y;
+    }
+
+    let mark = |text: &mut TextRenderer, r: f64, color: &glm::Vec4| {
+        text.draw_rect(to_x(r), inner_top, 1.0, inner_height, color);
+    };
+
+    // nodes: R_nl changes sign between two samples (r = 0 does not count)
+    let node_color = glm::vec4(0.3, 0.5, 1.0, 0.8);
+    for column in 1..columns {
+        if radial[column - 1] * radial[column] < 0.0 {
+            mark(text, r_at(column), &node_color);
+        }
+    }
+    let most_likely = r_at(
+        probability
+            .iter()
+            .position(|&p| p == peak)
+            .unwrap_or_default(),
+    );
+    mark(text, most_likely, &glm::vec4(1.0, 0.55, 0.15, 0.6));
+    let expected = expected_radius(n, l);
+    mark(text, expected, &glm::vec4(1.0, 1.0, 1.0, 0.8));
+    if let Some(cut) = cut.filter(|cut| (cut.abs() as f64) < range) {
+        mark(text, cut.abs() as f64, &glm::vec4(0.4, 1.0, 0.5, 0.8));
+    }
+
+    let caption = format!(
+        "P(r) {}{}  peak {:.1}  <r> {:.1}",
+        n,
+        orbital::letter(l),
+        most_likely,
+        expected
+    );
+    text.draw_text(&caption, x0 + 6.0, y0 + 6.0, 2.0, &grey);
+    let label_y = y0 + PLOT_HEIGHT - 11.0;
+    text.draw_text("0", x0 + 4.0, label_y, 1.0, &grey);
+    let end = format!("{:.0} a0", range);
+    let end_x = x0 + PLOT_WIDTH - TextRenderer::text_width(&end, 1.0) - 4.0;
+    text.draw_text(&end, end_x, label_y, 1.0, &grey);
+}
@@ -0,0 +1,235 @@
+use crate::capture; // back buffer -> image
+use crate::config::RecordingConfig;
+use image::RgbaImage;
+use std::fmt; // pretty errors
+use std::fs;
+use std::io::Write; // raw frames into ffmpeg's stdin
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+
+// video capture without screen recording tools (R to start / stop)
+// every Nth frame is read back from the back buffer, like a screenshot, and handed to a
+// writer thread so png encoding does not stall the render loop:
+//   - default: a png sequence, recordings/<stamp>/frame_00000.png, ...
+//   - ffmpeg = true: raw rgba piped into an ffmpeg process, recordings/<stamp>.mp4
+// frames are taken from real frames, so a steady frame rate (vsync) makes a steady video
+
+// frames waiting for the writer; when it falls behind the render loop waits instead of
+// piling up full frames in memory
+const QUEUE_LENGTH: usize = 8;
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Directory { path: String, message: String },
+    Ffmpeg(String), // not installed, or would not start
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordingError::Directory { path, message } => {
+                write!(
+                    f,
+                    "could not create recording directory '{}': {}",
+                    path, message
+                )
+            }
+            RecordingError::Ffmpeg(message) => write!(f, "could not start ffmpeg: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+enum Sink {
+    Png(PathBuf),              // directory the numbered frames go into
+    Ffmpeg(Child, ChildStdin), // stdin kept apart so it can be closed before waiting
+}
+
+struct Session {
+    frames: Option<SyncSender<RgbaImage>>, // None once stopping, closes the writer's queue
+    writer: Option<JoinHandle<usize>>,     // returns how many frames made it out
+    output: String,                        // what to tell the user at the end
+    size: (u32, u32),                      // ffmpeg was told this size, frames must keep it
+    frame: u64,                            // rendered frames since the start, for every-Nth
+}
+
+pub struct Recorder {
+    every: u32, // capture every Nth rendered frame
+    fps: u32,   // frame rate written into the video
+    directory: String,
+    ffmpeg: bool,
+    session: Option<Session>,
+}
+
+impl Recorder {
+    pub fn new(config: &RecordingConfig) -> Self {
+        Self {
+            every: config.every,
+            fps: config.fps,
+            directory: config.directory.clone(),
+            ffmpeg: config.ffmpeg,
+            session: None,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.session.is_some()
+    }
+
+    pub fn toggle(&mut self, width: u32, height: u32) {
+        // the hotkey: start with the current framebuffer size, or stop and report
+        if self.is_recording() {
+            self.stop();
+            return;
+        }
+        match self.start(width, height) {
+            Ok(output) => println!("Recording to {} (R to stop)", output),
+            Err(err) => log::error!("{}", err),
+        }
+    }
+
+    fn start(&mut self, width: u32, height: u32) -> Result<String, RecordingError> {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let directory_error = |path: &str, err: std::io::Error| RecordingError::Directory {
+            path: path.to_string(),
+            message: err.to_string(),
+        };
+
+        let (sink, output) = if self.ffmpeg {
+            fs::create_dir_all(&self.directory)
+                .map_err(|err| directory_error(&self.directory, err))?;
+            let output = format!("{}/recording_{}.mp4", self.directory, stamp);
+            (self.spawn_ffmpeg(&output, width, height)?, output)
+        } else {
+            let output = format!("{}/recording_{}", self.directory, stamp);
+            fs::create_dir_all(&output).map_err(|err| directory_error(&output, err))?;
+            (Sink::Png(PathBuf::from(&output)), output)
+        };
+
+        let (frames, queue) = mpsc::sync_channel(QUEUE_LENGTH);
+        let writer = thread::spawn(move || write_frames(sink, queue));
+        self.session = Some(Session {
+            frames: Some(frames),
+            writer: Some(writer),
+            output: output.clone(),
+            size: (width, height),
+            frame: 0,
+        });
+        Ok(output)
+    }
+
+    fn spawn_ffmpeg(&self, output: &str, width: u32, height: u32) -> Result<Sink, RecordingError> {
+        // rgba frames on stdin, h264 out; yuv420p and even sizes so every player opens it
+        let mut child = Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-f",
+                "rawvideo",
+                "-pixel_format",
+                "rgba",
+            ])
+            .args(["-video_size", &format!("{}x{}", width, height)])
+            .args(["-framerate", &self.fps.to_string(), "-i", "-"])
+            .args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+            ])
+            .arg(output)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| RecordingError::Ffmpeg(err.to_string()))?;
+        let stdin = child.stdin.take().expect("ffmpeg stdin was piped");
+        Ok(Sink::Ffmpeg(child, stdin))
+    }
+
+    pub unsafe fn capture(&mut self, width: u32, height: u32) {
+        // call after everything is drawn, before the swap, every frame
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        if (width, height) != session.size {
+            // a video cannot change size halfway, and mixed png sizes make bad input too
+            println!("Window size changed, recording stopped.");
+            self.stop();
+            return;
+        }
+        session.frame += 1;
+        if (session.frame - 1) % self.every as u64 != 0 {
+            return;
+        }
+        let image = capture::read_framebuffer(width, height);
+        let sent = session
+            .frames
+            .as_ref()
+            .is_some_and(|frames| frames.send(image).is_ok());
+        if !sent {
+            // the writer gave up (disk full, ffmpeg died), it has logged why
+            self.stop();
+        }
+    }
+
+    pub fn stop(&mut self) {
+        let Some(mut session) = self.session.take() else {
+            return;
+        };
+        // closing the queue lets the writer finish what is left and return
+        session.frames = None;
+        let written = session
+            .writer
+            .take()
+            .and_then(|writer| writer.join().ok())
+            .unwrap_or(0);
+        println!("Recorded {} frames to {}", written, session.output);
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // quitting while recording still leaves a complete png sequence / playable video
+        self.stop();
+    }
+}
+
+fn write_frames(sink: Sink, queue: mpsc::Receiver<RgbaImage>) -> usize {
+    // writer thread: runs until the queue is closed or a write fails
+    let mut written = 0;
+    match sink {
+        Sink::Png(directory) => {
+            for image in queue {
+                let path = directory.join(format!("frame_{:05}.png", written));
+                if let Err(err) = image.save(&path) {
+                    log::error!("could not save {}: {}", path.display(), err);
+                    break;
+                }
+                written += 1;
+            }
+        }
+        Sink::Ffmpeg(mut child, mut stdin) => {
+            for image in queue {
+                if let Err(err) = stdin.write_all(image.as_raw()) {
+                    log::error!("could not write to ffmpeg: {}", err);
+                    break;
+                }
+                written += 1;
+            }
+            // end of input, ffmpeg finishes the file and exits
+            drop(stdin);
+            match child.wait() {
+                Ok(status) if !status.success() => log::error!("ffmpeg exited with {}", status),
+                Err(err) => log::error!("could not wait for ffmpeg: {}", err),
+                _ => {}
+            }
+        }
+    }
+    written
+}
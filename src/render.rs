@@ -1,38 +1,362 @@
+use crate::stats; // draw call counts for --benchmark
 use nalgebra_glm as glm; // math because trig is pain
+use std::cell::RefCell; // interior mutability for the uniform cache
+use std::collections::HashMap; // uniform name -> location
 use std::f32::consts::PI; // pi because sphere
-use std::ffi::CStr; // C strings for opengl
+use std::ffi::{CStr, CString}; // C strings for opengl
+use std::fmt; // pretty errors
+use std::fs; // shader files
 use std::mem; // size_of for buffer sizes
 use std::os::raw::c_void; // raw pointer casting
 use std::ptr; // null pointers etc
+use std::time::{Duration, Instant, SystemTime}; // shader file watching
+
+#[derive(Debug)]
+pub enum ShaderError {
+    // everything that can go wrong between "here is some glsl" and "here is a program"
+    Io { path: String, message: String }, // shader file missing / unreadable
+    InvalidSource(String),                // source contains a nul byte, opengl wants C strings
+    Compile { stage: &'static str, log: String },
+    Link { log: String },
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderError::Io { path, message } => {
+                write!(f, "could not read shader '{}': {}", path, message)
+            }
+            ShaderError::InvalidSource(what) => {
+                write!(f, "shader source {} contains a nul byte", what)
+            }
+            ShaderError::Compile { stage, log } => {
+                write!(f, "{} shader compilation error:\n{}", stage, log)
+            }
+            ShaderError::Link { log } => write!(f, "shader link error:\n{}", log),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
 
 pub struct ShaderProgram {
     // wrapper around opengl shader program
     // holds program id
     id: gl::types::GLuint,
+    // uniform name -> location, filled lazily on first use
+    // RefCell because setters take &self (the program is logically unchanged)
+    locations: RefCell<HashMap<String, gl::types::GLint>>,
 }
 
 impl ShaderProgram {
-    pub unsafe fn new(vs_src: &CStr, fs_src: &CStr) -> Self {
+    pub unsafe fn new(vs_src: &CStr, fs_src: &CStr) -> Result<Self, ShaderError> {
         // compile vertex and fragment shaders
         // attach them
         // link them
         // delete intermediates
         // classic opengl ritual
+        // any failure comes back as ShaderError with the driver's log attached
+
+        let vertex_shader = shader_from_source(vs_src, gl::VERTEX_SHADER)?;
+        let fragment_shader = match shader_from_source(fs_src, gl::FRAGMENT_SHADER) {
+            Ok(shader) => shader,
+            Err(err) => {
+                gl::DeleteShader(vertex_shader);
+                return Err(err);
+            }
+        };
+
+        Self::link(&[vertex_shader, fragment_shader])
+    }
 
-        let vertex_shader = shader_from_source(vs_src, gl::VERTEX_SHADER);
-        let fragment_shader = shader_from_source(fs_src, gl::FRAGMENT_SHADER);
+    pub unsafe fn compute(cs_src: &str) -> Result<Self, ShaderError> {
+        // single compute stage, needs gl 4.3 (check before calling, see orbital.rs)
+        let cs =
+            CString::new(cs_src).map_err(|_| ShaderError::InvalidSource("compute".to_string()))?;
+        let compute_shader = shader_from_source(&cs, gl::COMPUTE_SHADER)?;
+        Self::link(&[compute_shader])
+    }
 
+    unsafe fn link(shaders: &[gl::types::GLuint]) -> Result<Self, ShaderError> {
+        // takes ownership of the compiled shaders, they are deleted either way
         let id = gl::CreateProgram();
-        gl::AttachShader(id, vertex_shader);
-        gl::AttachShader(id, fragment_shader);
+        for &shader in shaders {
+            gl::AttachShader(id, shader);
+        }
         gl::LinkProgram(id);
 
         // once linked, shaders can be deleted
         // program keeps compiled version internally
-        gl::DeleteShader(vertex_shader);
-        gl::DeleteShader(fragment_shader);
+        for &shader in shaders {
+            gl::DeleteShader(shader);
+        }
+        check_gl_error!("shader link");
+
+        let mut success: gl::types::GLint = 1;
+        gl::GetProgramiv(id, gl::LINK_STATUS, &mut success);
+
+        if success == 0 {
+            let mut len: gl::types::GLint = 0;
+            gl::GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut len);
+
+            let mut info_log = vec![0u8; len.max(1) as usize];
+            gl::GetProgramInfoLog(
+                id,
+                len,
+                ptr::null_mut(),
+                info_log.as_mut_ptr() as *mut gl::types::GLchar,
+            );
+            gl::DeleteProgram(id);
+
+            return Err(ShaderError::Link {
+                log: log_to_string(&info_log),
+            });
+        }
+
+        // point the shared Frame block at its buffer, see FrameUniforms
+        // (glsl 330 has no layout(binding = ...), so it has to happen here)
+        let block = CString::new("Frame").unwrap();
+        let index = gl::GetUniformBlockIndex(id, block.as_ptr());
+        if index != gl::INVALID_INDEX {
+            gl::UniformBlockBinding(id, index, FRAME_BINDING);
+        }
+
+        log::debug!("linked shader program {}", id);
+        Ok(Self {
+            id,
+            locations: RefCell::new(HashMap::new()),
+        })
+    }
+
+    pub unsafe fn from_sources(vs_src: &str, fs_src: &str) -> Result<Self, ShaderError> {
+        // same as new but takes plain rust strings
+        let vs =
+            CString::new(vs_src).map_err(|_| ShaderError::InvalidSource("vertex".to_string()))?;
+        let fs =
+            CString::new(fs_src).map_err(|_| ShaderError::InvalidSource("fragment".to_string()))?;
+        Self::new(&vs, &fs)
+    }
+
+    pub unsafe fn from_files(vs_path: &str, fs_path: &str) -> Result<Self, ShaderError> {
+        // load glsl from disk, then compile + link
+        log::debug!("loading shader {} + {}", vs_path, fs_path);
+        let read = |path: &str| {
+            fs::read_to_string(path).map_err(|err| ShaderError::Io {
+                path: path.to_string(),
+                message: err.to_string(),
+            })
+        };
+        Self::from_sources(&read(vs_path)?, &read(fs_path)?)
+    }
+
+    // presets: copies of assets/shaders baked into the binary
+    // so the app still works when started outside the repo
+    // WatchedProgram prefers the files on disk when they are there
+
+    pub unsafe fn flat() -> Result<Self, ShaderError> {
+        // preset: unlit, per-instance colored (the density cloud)
+        Self::from_sources(
+            include_str!("../assets/shaders/flat.vert"),
+            include_str!("../assets/shaders/flat.frag"),
+        )
+    }
+
+    pub unsafe fn phong() -> Result<Self, ShaderError> {
+        // preset: blinn-phong lit, per-instance colored
+        Self::from_sources(
+            include_str!("../assets/shaders/phong.vert"),
+            include_str!("../assets/shaders/phong.frag"),
+        )
+    }
+
+    pub unsafe fn points() -> Result<Self, ShaderError> {
+        // preset: soft round GL_POINTS, per-vertex color, size uniform
+        Self::from_sources(
+            include_str!("../assets/shaders/points.vert"),
+            include_str!("../assets/shaders/points.frag"),
+        )
+    }
+
+    pub unsafe fn text() -> Result<Self, ShaderError> {
+        // preset: screen space textured quads for the bitmap font
+        Self::from_sources(
+            include_str!("../assets/shaders/text.vert"),
+            include_str!("../assets/shaders/text.frag"),
+        )
+    }
+
+    pub unsafe fn trail() -> Result<Self, ShaderError> {
+        // preset: fading line strips out of a ring buffer
+        Self::from_sources(
+            include_str!("../assets/shaders/trail.vert"),
+            include_str!("../assets/shaders/trail.frag"),
+        )
+    }
+
+    pub unsafe fn particle() -> Result<Self, ShaderError> {
+        // preset: camera-facing soft quads for the particle system
+        Self::from_sources(
+            include_str!("../assets/shaders/particle.vert"),
+            include_str!("../assets/shaders/particle.frag"),
+        )
+    }
+
+    pub unsafe fn sprite() -> Result<Self, ShaderError> {
+        // preset: camera-facing textured quads standing in for small far spheres
+        Self::from_sources(
+            include_str!("../assets/shaders/sprite.vert"),
+            include_str!("../assets/shaders/sprite.frag"),
+        )
+    }
+
+    pub unsafe fn fxaa() -> Result<Self, ShaderError> {
+        // preset: fullscreen fxaa resolve pass, no vertex buffer needed
+        Self::from_sources(
+            include_str!("../assets/shaders/fxaa.vert"),
+            include_str!("../assets/shaders/fxaa.frag"),
+        )
+    }
+
+    pub unsafe fn gamma() -> Result<Self, ShaderError> {
+        // preset: fullscreen srgb encode, for windows that cannot do it themselves
+        Self::from_sources(
+            include_str!("../assets/shaders/gamma.vert"),
+            include_str!("../assets/shaders/gamma.frag"),
+        )
+    }
+
+    pub unsafe fn stereo() -> Result<Self, ShaderError> {
+        // preset: fullscreen pass putting the two eyes of a stereo frame together
+        Self::from_sources(
+            include_str!("../assets/shaders/stereo.vert"),
+            include_str!("../assets/shaders/stereo.frag"),
+        )
+    }
+
+    pub unsafe fn slice() -> Result<Self, ShaderError> {
+        // preset: one textured screen quad, the density slice overlay
+        Self::from_sources(
+            include_str!("../assets/shaders/slice.vert"),
+            include_str!("../assets/shaders/slice.frag"),
+        )
+    }
+
+    pub unsafe fn field() -> Result<Self, ShaderError> {
+        // preset: one textured world space quad, the potential slice of the field view
+        Self::from_sources(
+            include_str!("../assets/shaders/field.vert"),
+            include_str!("../assets/shaders/field.frag"),
+        )
+    }
+
+    pub unsafe fn gizmo() -> Result<Self, ShaderError> {
+        // preset: single-color lines and meshes, shell rings and the corner axes
+        Self::from_sources(
+            include_str!("../assets/shaders/gizmo.vert"),
+            include_str!("../assets/shaders/gizmo.frag"),
+        )
+    }
+
+    pub unsafe fn background() -> Result<Self, ShaderError> {
+        // preset: fullscreen gradient or cubemap lookup behind the scene
+        Self::from_sources(
+            include_str!("../assets/shaders/background.vert"),
+            include_str!("../assets/shaders/background.frag"),
+        )
+    }
+
+    pub unsafe fn bloom_extract() -> Result<Self, ShaderError> {
+        // preset: bloom step 1, keep only the pixels above the brightness threshold
+        Self::from_sources(
+            include_str!("../assets/shaders/bloom_extract.vert"),
+            include_str!("../assets/shaders/bloom_extract.frag"),
+        )
+    }
+
+    pub unsafe fn bloom_blur() -> Result<Self, ShaderError> {
+        // preset: bloom step 2, one direction of a separable gaussian
+        Self::from_sources(
+            include_str!("../assets/shaders/bloom_blur.vert"),
+            include_str!("../assets/shaders/bloom_blur.frag"),
+        )
+    }
+
+    pub unsafe fn bloom_composite() -> Result<Self, ShaderError> {
+        // preset: bloom step 3, frame + blurred glow
+        Self::from_sources(
+            include_str!("../assets/shaders/bloom_composite.vert"),
+            include_str!("../assets/shaders/bloom_composite.frag"),
+        )
+    }
+
+    pub unsafe fn dof() -> Result<Self, ShaderError> {
+        // preset: depth of field, one direction of the blur, as wide as the depth asks
+        Self::from_sources(
+            include_str!("../assets/shaders/dof.vert"),
+            include_str!("../assets/shaders/dof.frag"),
+        )
+    }
+
+    pub unsafe fn dof_composite() -> Result<Self, ShaderError> {
+        // preset: depth of field, the blurred frame back with its depth
+        Self::from_sources(
+            include_str!("../assets/shaders/dof_composite.vert"),
+            include_str!("../assets/shaders/dof_composite.frag"),
+        )
+    }
+
+    pub unsafe fn tonemap() -> Result<Self, ShaderError> {
+        // preset: hdr frame squeezed under 1.0, see tonemap.rs
+        Self::from_sources(
+            include_str!("../assets/shaders/tonemap.vert"),
+            include_str!("../assets/shaders/tonemap.frag"),
+        )
+    }
+
+    pub unsafe fn ground() -> Result<Self, ShaderError> {
+        // preset: fullscreen, every pixel's ray against the ground plane, lines and all
+        Self::from_sources(
+            include_str!("../assets/shaders/ground.vert"),
+            include_str!("../assets/shaders/ground.frag"),
+        )
+    }
+
+    pub unsafe fn shadow() -> Result<Self, ShaderError> {
+        // preset: depth only, the casters seen from the key light into the shadow map
+        Self::from_sources(
+            include_str!("../assets/shaders/shadow.vert"),
+            include_str!("../assets/shaders/shadow.frag"),
+        )
+    }
+
+    pub unsafe fn ssao() -> Result<Self, ShaderError> {
+        // preset: ambient occlusion step 1, how much of the hemisphere the depth buffer blocks
+        Self::from_sources(
+            include_str!("../assets/shaders/ssao.vert"),
+            include_str!("../assets/shaders/ssao.frag"),
+        )
+    }
+
+    pub unsafe fn ssao_blur() -> Result<Self, ShaderError> {
+        // preset: ambient occlusion step 2, one direction of a depth aware blur
+        Self::from_sources(
+            include_str!("../assets/shaders/ssao_blur.vert"),
+            include_str!("../assets/shaders/ssao_blur.frag"),
+        )
+    }
+
+    pub unsafe fn ssao_composite() -> Result<Self, ShaderError> {
+        // preset: ambient occlusion step 3, the frame darkened by it
+        Self::from_sources(
+            include_str!("../assets/shaders/ssao_composite.vert"),
+            include_str!("../assets/shaders/ssao_composite.frag"),
+        )
+    }
 
-        Self { id }
+    pub unsafe fn orbital_sample() -> Result<Self, ShaderError> {
+        // preset: compute, rejection samples a real orbital straight into a vertex buffer
+        // only runs when a new orbital is picked, so it is not hot-reloaded, edits need a rebuild
+        Self::compute(include_str!("../assets/shaders/orbital_sample.comp"))
     }
 
     pub unsafe fn use_program(&self) {
@@ -40,17 +364,68 @@ impl ShaderProgram {
         gl::UseProgram(self.id);
     }
 
-    pub unsafe fn set_uniform_4f(&self, name: &CStr, v0: f32, v1: f32, v2: f32, v3: f32) {
-        // set vec4 uniform (color mostly)
-        let location = gl::GetUniformLocation(self.id, name.as_ptr());
-        gl::Uniform4f(location, v0, v1, v2, v3);
+    unsafe fn uniform_location(&self, name: &str) -> gl::types::GLint {
+        // glGetUniformLocation is a string lookup inside the driver
+        // doing it every frame for every uniform adds up, so cache per name
+        // missing uniforms are cached as -1 too, and reported exactly once
+
+        if let Some(&location) = self.locations.borrow().get(name) {
+            return location;
+        }
+
+        let c_name = CString::new(name).unwrap();
+        let location = gl::GetUniformLocation(self.id, c_name.as_ptr());
+
+        if location == -1 {
+            // glsl compilers strip uniforms that are declared but unused
+            // so this is either a typo or dead shader code
+            log::warn!(
+                "uniform '{}' not found in shader program {} (misspelled or optimized out?)",
+                name,
+                self.id
+            );
+        }
+
+        self.locations
+            .borrow_mut()
+            .insert(name.to_string(), location);
+        location
     }
 
-    pub unsafe fn set_uniform_mat4(&self, name: &CStr, mat: &glm::Mat4) {
+    pub unsafe fn set_uniform_mat4(&self, name: &str, mat: &glm::Mat4) {
         // send 4x4 matrix to shader
-        // used for model/view/projection
-        let location = gl::GetUniformLocation(self.id, name.as_ptr());
-        gl::UniformMatrix4fv(location, 1, gl::FALSE, mat.as_ptr());
+        // model matrices, overlay projections (the scene camera is in the Frame block)
+        gl::UniformMatrix4fv(self.uniform_location(name), 1, gl::FALSE, mat.as_ptr());
+    }
+
+    pub unsafe fn set_uniform_f32(&self, name: &str, v: f32) {
+        // send a single float (point size, intensities, ...)
+        gl::Uniform1f(self.uniform_location(name), v);
+    }
+
+    pub unsafe fn set_uniform_i32(&self, name: &str, v: i32) {
+        // ints: counts, flags, sampler units
+        gl::Uniform1i(self.uniform_location(name), v);
+    }
+
+    pub unsafe fn set_uniform_u32(&self, name: &str, v: u32) {
+        // uints: seeds and offsets in compute shaders
+        gl::Uniform1ui(self.uniform_location(name), v);
+    }
+
+    pub unsafe fn set_uniform_vec2(&self, name: &str, v: &glm::Vec2) {
+        // screen sizes, texel sizes
+        gl::Uniform2f(self.uniform_location(name), v.x, v.y);
+    }
+
+    pub unsafe fn set_uniform_vec3(&self, name: &str, v: &glm::Vec3) {
+        // send vec3 (light position, colors, camera position)
+        gl::Uniform3f(self.uniform_location(name), v.x, v.y, v.z);
+    }
+
+    pub unsafe fn set_uniform_vec4(&self, name: &str, v: &glm::Vec4) {
+        // rgba colors
+        gl::Uniform4f(self.uniform_location(name), v.x, v.y, v.z, v.w);
     }
 }
 
@@ -64,67 +439,350 @@ impl Drop for ShaderProgram {
     }
 }
 
+// how often WatchedProgram looks at file timestamps
+const SHADER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct WatchedProgram {
+    // a ShaderProgram that follows its source files in assets/shaders
+    // edit the glsl, save, and the next frame uses the new program
+    // a broken edit prints the compile log and keeps the last program that worked
+    program: ShaderProgram,
+    vs_path: String,
+    fs_path: String,
+    modified: Option<SystemTime>, // newest mtime of the two files at the last (re)load
+    last_poll: Instant,
+}
+
+impl WatchedProgram {
+    pub unsafe fn new(
+        name: &str,
+        preset: unsafe fn() -> Result<ShaderProgram, ShaderError>,
+    ) -> Result<Self, ShaderError> {
+        // assets/shaders/<name>.vert + .frag relative to the working directory
+        // missing files (installed binary) or a broken file = start from the baked-in preset
+        let vs_path = format!("assets/shaders/{}.vert", name);
+        let fs_path = format!("assets/shaders/{}.frag", name);
+        let modified = newest_mtime(&vs_path, &fs_path);
+
+        let program = match modified {
+            Some(_) => ShaderProgram::from_files(&vs_path, &fs_path).or_else(|err| {
+                log::error!("{}\nfalling back to the built-in '{}' shader", err, name);
+                preset()
+            })?,
+            None => {
+                log::debug!(
+                    "no {} on disk, using the built-in '{}' shader",
+                    vs_path,
+                    name
+                );
+                preset()?
+            }
+        };
+
+        Ok(Self {
+            program,
+            vs_path,
+            fs_path,
+            modified,
+            last_poll: Instant::now(),
+        })
+    }
+
+    pub unsafe fn poll(&mut self) {
+        // call once per frame, only touches the filesystem every SHADER_POLL_INTERVAL
+        if self.last_poll.elapsed() < SHADER_POLL_INTERVAL {
+            return;
+        }
+        self.last_poll = Instant::now();
+
+        let modified = newest_mtime(&self.vs_path, &self.fs_path);
+        if modified.is_none() || modified == self.modified {
+            return;
+        }
+        // remember the stamp even on failure, so a broken file is reported once, not every poll
+        self.modified = modified;
+
+        match ShaderProgram::from_files(&self.vs_path, &self.fs_path) {
+            Ok(program) => {
+                log::info!("reloaded {} + {}", self.vs_path, self.fs_path);
+                self.program = program;
+            }
+            Err(err) => log::error!("{}\nkeeping the previous program", err),
+        }
+    }
+}
+
+impl std::ops::Deref for WatchedProgram {
+    // use it exactly like the ShaderProgram inside
+    type Target = ShaderProgram;
+
+    fn deref(&self) -> &ShaderProgram {
+        &self.program
+    }
+}
+
+fn newest_mtime(a: &str, b: &str) -> Option<SystemTime> {
+    // None if either file is missing
+    let mtime = |path: &str| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    Some(mtime(a)?.max(mtime(b)?))
+}
+
+// camera, cross-section and lights, the same for every shader that draws in world space
+// uploaded once per frame into one uniform buffer instead of into every program again;
+// a shader only has to declare the block to see it:
+//
+//   layout (std140) uniform Frame {
+//       mat4 view;
+//       mat4 projection;
+//       vec4 viewPos;           // camera position, w unused
+//       vec4 clipPlane;         // cross-section, see slice.rs
+//       vec4 lightPositions[4]; // world space point lights, w unused
+//       vec4 lightColor;        // rgb, w unused
+//       int lightCount;
+//   };
+//
+// (the gizmo and the screen space overlays keep their own matrices, they do not
+// look through the scene camera)
+pub const FRAME_BINDING: u32 = 0;
+pub const MAX_LIGHTS: usize = 4;
+
+pub struct FrameUniforms {
+    pub view: glm::Mat4,
+    pub projection: glm::Mat4,
+    pub camera: glm::Vec3,
+    pub clip: glm::Vec4,
+    pub lights: Vec<glm::Vec3>, // only the first MAX_LIGHTS are used
+    pub light_color: glm::Vec3,
+}
+
+impl FrameUniforms {
+    fn std140(&self) -> Vec<f32> {
+        // std140 pads every vec3 (and every array element) to a vec4, so the layout is
+        // plain: member after member, 4 floats at a time
+        let mut data = Vec::with_capacity(FRAME_FLOATS);
+        data.extend_from_slice(self.view.as_slice());
+        data.extend_from_slice(self.projection.as_slice());
+        data.extend_from_slice(&[self.camera.x, self.camera.y, self.camera.z, 0.0]);
+        data.extend_from_slice(self.clip.as_slice());
+        for i in 0..MAX_LIGHTS {
+            let light = self.lights.get(i).copied().unwrap_or_default();
+            data.extend_from_slice(&[light.x, light.y, light.z, 0.0]);
+        }
+        let c = self.light_color;
+        data.extend_from_slice(&[c.x, c.y, c.z, 0.0]);
+        // an int in a float slot: same 4 bytes, the buffer does not care
+        let count = self.lights.len().min(MAX_LIGHTS) as u32;
+        data.extend_from_slice(&[f32::from_bits(count), 0.0, 0.0, 0.0]);
+        data
+    }
+}
+
+// 2 matrices, 8 vec4s, lightCount padded up to a whole vec4
+const FRAME_FLOATS: usize = 2 * 16 + (3 + MAX_LIGHTS) * 4 + 4;
+
+pub struct FrameBlock {
+    // the buffer behind the Frame block, bound at FRAME_BINDING for good
+    ubo: gl::types::GLuint,
+}
+
+impl FrameBlock {
+    pub unsafe fn new() -> Self {
+        let mut ubo = 0;
+        gl::GenBuffers(1, &mut ubo);
+        gl::BindBuffer(gl::UNIFORM_BUFFER, ubo);
+        gl::BufferData(
+            gl::UNIFORM_BUFFER,
+            (FRAME_FLOATS * mem::size_of::<f32>()) as isize,
+            ptr::null(),
+            gl::DYNAMIC_DRAW,
+        );
+        gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        gl::BindBufferBase(gl::UNIFORM_BUFFER, FRAME_BINDING, ubo);
+        check_gl_error!("frame uniform buffer setup");
+        Self { ubo }
+    }
+
+    pub unsafe fn upload(&self, frame: &FrameUniforms) {
+        let data = frame.std140();
+        gl::BindBuffer(gl::UNIFORM_BUFFER, self.ubo);
+        gl::BufferSubData(
+            gl::UNIFORM_BUFFER,
+            0,
+            mem::size_of_val(data.as_slice()) as isize,
+            data.as_ptr() as *const c_void,
+        );
+        gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+    }
+}
+
+impl Drop for FrameBlock {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.ubo);
+        }
+    }
+}
+
 pub struct VertexArray {
-    // wrapper for vao + vbo
+    // wrapper for vao + vbo (+ optional ebo)
     // because raw opengl calls everywhere is messy
     vao: gl::types::GLuint,
     vbo: gl::types::GLuint,
+    ebo: gl::types::GLuint,    // 0 = no index buffer, draw as triangle soup
+    uv_vbo: gl::types::GLuint, // 0 = no texture coordinates
     vertex_count: i32,
+    index_count: i32,
 }
 
 impl VertexArray {
-    pub unsafe fn new(vertices: &[f32]) -> Self {
+    pub unsafe fn new(vertices: &[f32], layout: &[i32]) -> Self {
         // create vertex array and buffer
         // upload vertex data to gpu
+        // layout = float count of each interleaved attribute, e.g. [3, 3] = position + normal
+        // attribute i goes to location i
 
-        let mut vao = 0;
         let mut vbo = 0;
-        let vertex_count = (vertices.len() / 3) as i32;
-
-        gl::GenVertexArrays(1, &mut vao);
         gl::GenBuffers(1, &mut vbo);
-
-        gl::BindVertexArray(vao);
-
         gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
         gl::BufferData(
             gl::ARRAY_BUFFER,
-            (vertices.len() * mem::size_of::<f32>()) as isize,
+            mem::size_of_val(vertices) as isize,
             &vertices[0] as *const f32 as *const c_void,
             gl::STATIC_DRAW,
         );
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+        let floats_per_vertex: i32 = layout.iter().sum();
+        Self::from_buffer(vbo, vertices.len() as i32 / floats_per_vertex, layout)
+    }
+
+    pub unsafe fn from_buffer(vbo: gl::types::GLuint, vertex_count: i32, layout: &[i32]) -> Self {
+        // vao over a buffer that was filled some other way (a compute shader, say)
+        // takes ownership, the buffer is deleted with the vertex array
+
+        let mut vao = 0;
+        let floats_per_vertex: i32 = layout.iter().sum();
+
+        gl::GenVertexArrays(1, &mut vao);
+
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
 
         // describe vertex layout to opengl
-        // attribute 0 = position (vec3)
-        gl::VertexAttribPointer(
-            0,
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            (3 * mem::size_of::<f32>()) as i32,
-            ptr::null(),
-        );
-        gl::EnableVertexAttribArray(0);
+        let stride = floats_per_vertex * mem::size_of::<f32>() as i32;
+        let mut offset = 0;
+        for (location, &size) in layout.iter().enumerate() {
+            gl::VertexAttribPointer(
+                location as u32,
+                size,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (offset * mem::size_of::<f32>()) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(location as u32);
+            offset += size as usize;
+        }
 
         // unbind to avoid accidental modification
         gl::BindBuffer(gl::ARRAY_BUFFER, 0);
         gl::BindVertexArray(0);
+        check_gl_error!("vertex array setup");
 
         Self {
             vao,
             vbo,
+            ebo: 0,
+            uv_vbo: 0,
             vertex_count,
+            index_count: 0,
         }
     }
 
-    pub fn vertex_count(&self) -> i32 {
-        self.vertex_count
+    pub unsafe fn with_indices(vertices: &[f32], indices: &[u32], layout: &[i32]) -> Self {
+        // same as new, plus an element buffer
+        // shared vertices get reused instead of duplicated per triangle
+
+        let mut array = Self::new(vertices, layout);
+
+        let mut ebo = 0;
+        gl::GenBuffers(1, &mut ebo);
+
+        // the ebo binding is stored inside the vao
+        // so bind the vao first and do NOT unbind the ebo before the vao
+        gl::BindVertexArray(array.vao);
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+        gl::BufferData(
+            gl::ELEMENT_ARRAY_BUFFER,
+            mem::size_of_val(indices) as isize,
+            &indices[0] as *const u32 as *const c_void,
+            gl::STATIC_DRAW,
+        );
+        gl::BindVertexArray(0);
+        check_gl_error!("index buffer upload");
+
+        array.ebo = ebo;
+        array.index_count = indices.len() as i32;
+        array
+    }
+
+    pub unsafe fn from_mesh(mesh: &Mesh) -> Self {
+        // upload a cpu mesh as-is
+        let mut array = Self::with_indices(&mesh.vertices, &mesh.indices, Mesh::LAYOUT);
+        if !mesh.uvs.is_empty() {
+            // a buffer of their own, see Mesh
+            gl::BindVertexArray(array.vao);
+            gl::GenBuffers(1, &mut array.uv_vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, array.uv_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                mem::size_of_val(mesh.uvs.as_slice()) as isize,
+                mesh.uvs.as_ptr() as *const c_void,
+                gl::STATIC_DRAW,
+            );
+            gl::VertexAttribPointer(UV_LOCATION, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
+            gl::EnableVertexAttribArray(UV_LOCATION);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+            check_gl_error!("uv buffer upload");
+        }
+        array
     }
 
     pub unsafe fn bind(&self) {
         // bind vao before drawing
         gl::BindVertexArray(self.vao);
     }
+
+    pub unsafe fn draw(&self, mode: gl::types::GLenum) {
+        // single non-instanced draw with the given primitive (TRIANGLES, POINTS, LINES, ...)
+        // indexed if we have an ebo, plain arrays otherwise
+        if self.ebo != 0 {
+            stats::count(mode, self.index_count, 1);
+            gl::DrawElements(mode, self.index_count, gl::UNSIGNED_INT, ptr::null());
+        } else {
+            stats::count(mode, self.vertex_count, 1);
+            gl::DrawArrays(mode, 0, self.vertex_count);
+        }
+    }
+
+    pub unsafe fn draw_instanced(&self, instance_count: i32) {
+        // same as draw but repeated instance_count times in one call
+        // per-instance attributes decide where each copy ends up
+        if self.ebo != 0 {
+            stats::count(gl::TRIANGLES, self.index_count, instance_count);
+            gl::DrawElementsInstanced(
+                gl::TRIANGLES,
+                self.index_count,
+                gl::UNSIGNED_INT,
+                ptr::null(),
+                instance_count,
+            );
+        } else {
+            stats::count(gl::TRIANGLES, self.vertex_count, instance_count);
+            gl::DrawArraysInstanced(gl::TRIANGLES, 0, self.vertex_count, instance_count);
+        }
+    }
 }
 
 impl Drop for VertexArray {
@@ -133,69 +791,443 @@ impl Drop for VertexArray {
         unsafe {
             gl::DeleteVertexArrays(1, &self.vao);
             gl::DeleteBuffers(1, &self.vbo);
+            if self.ebo != 0 {
+                gl::DeleteBuffers(1, &self.ebo);
+            }
+            if self.uv_vbo != 0 {
+                gl::DeleteBuffers(1, &self.uv_vbo);
+            }
         }
     }
 }
 
-pub fn generate_sphere(radius: f32, sectors: i32, stacks: i32) -> Vec<f32> {
-    // manual sphere tessellation
-    // generates triangles from lat-long grid
-    // no indices, fully expanded triangle list
+// per-instance layout: 4 floats color + 16 floats model matrix
+// attribute locations 2 (color) and 3..=6 (mat4 columns)
+// locations 0 and 1 belong to the per-vertex position + normal
+const INSTANCE_FLOATS: usize = 20;
+const INSTANCE_COLOR_LOCATION: u32 = 2;
+const INSTANCE_MODEL_LOCATION: u32 = 3;
+// after the instance attributes, so meshes with uvs still fit the instanced renderer
+const UV_LOCATION: u32 = 7;
+
+pub struct InstancedRenderer {
+    // draws many copies of one mesh with a single draw call
+    // each copy gets its own model matrix and color from an instance vbo
+    // cpu side we just collect instances, gpu side it is one buffer upload + one draw
+    mesh: VertexArray,
+    instance_vbo: gl::types::GLuint,
+    instances: Vec<f32>, // staging buffer, INSTANCE_FLOATS per instance
+    capacity: usize,     // how many instances the gpu buffer can currently hold
+    dirty: bool,         // staging changed since last upload
+}
+
+impl InstancedRenderer {
+    pub unsafe fn new(mesh: VertexArray) -> Self {
+        // hook an instance buffer into the mesh vao
+        // the vao remembers these attribute bindings, so this is one-time setup
+
+        let mut instance_vbo = 0;
+        gl::GenBuffers(1, &mut instance_vbo);
+
+        gl::BindVertexArray(mesh.vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, instance_vbo);
+
+        let stride = (INSTANCE_FLOATS * mem::size_of::<f32>()) as i32;
+
+        // color: vec4 at the start of each instance
+        gl::VertexAttribPointer(
+            INSTANCE_COLOR_LOCATION,
+            4,
+            gl::FLOAT,
+            gl::FALSE,
+            stride,
+            ptr::null(),
+        );
+        gl::EnableVertexAttribArray(INSTANCE_COLOR_LOCATION);
+        gl::VertexAttribDivisor(INSTANCE_COLOR_LOCATION, 1);
+
+        // model: mat4 = four vec4 columns in consecutive locations
+        for column in 0..4 {
+            let location = INSTANCE_MODEL_LOCATION + column;
+            let offset = (4 + 4 * column as usize) * mem::size_of::<f32>();
+            gl::VertexAttribPointer(
+                location,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset as *const c_void,
+            );
+            gl::EnableVertexAttribArray(location);
+            gl::VertexAttribDivisor(location, 1);
+        }
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl::BindVertexArray(0);
+        check_gl_error!("instance attribute setup");
+
+        Self {
+            mesh,
+            instance_vbo,
+            instances: Vec::new(),
+            capacity: 0,
+            dirty: false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        // forget all instances, keep the allocations
+        self.instances.clear();
+        self.dirty = true;
+    }
+
+    pub fn push(&mut self, model: &glm::Mat4, color: &glm::Vec4) {
+        // queue one copy of the mesh
+        // glm matrices are column-major which is exactly what the shader wants
+        self.instances.extend_from_slice(color.as_slice());
+        self.instances.extend_from_slice(model.as_slice());
+        self.dirty = true;
+    }
+
+    pub fn instance_count(&self) -> usize {
+        self.instances.len() / INSTANCE_FLOATS
+    }
+
+    pub unsafe fn draw(&mut self) {
+        // upload (only if something changed) and draw everything in one go
+        let count = self.instance_count();
+        if count == 0 {
+            return;
+        }
+
+        if self.dirty {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+            let size = mem::size_of_val(self.instances.as_slice()) as isize;
+
+            if count > self.capacity {
+                // grow: reallocate the whole buffer
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    size,
+                    self.instances.as_ptr() as *const c_void,
+                    gl::DYNAMIC_DRAW,
+                );
+                self.capacity = count;
+            } else {
+                // fits: overwrite in place
+                gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    0,
+                    size,
+                    self.instances.as_ptr() as *const c_void,
+                );
+            }
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            self.dirty = false;
+            check_gl_error!("instance upload");
+        }
+
+        self.mesh.bind();
+        self.mesh.draw_instanced(count as i32);
+        check_gl_error!("instanced draw");
+    }
+}
+
+impl Drop for InstancedRenderer {
+    fn drop(&mut self) {
+        // mesh cleans itself up, we only own the instance buffer
+        unsafe {
+            gl::DeleteBuffers(1, &self.instance_vbo);
+        }
+    }
+}
+
+pub struct Mesh {
+    // cpu side mesh data
+    // vertices = interleaved position + normal, indices = triangles into that list
+    // uvs = texture coordinates, 2 per vertex, kept apart so everything that walks the
+    // vertices 6 floats at a time stays as it is; empty for meshes that have none
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u32>,
+    pub uvs: Vec<f32>,
+}
+
+impl Mesh {
+    // location 0 = position (vec3), location 1 = normal (vec3)
+    pub const LAYOUT: &'static [i32] = &[3, 3];
+
+    pub fn append(&mut self, other: &Mesh, model: &glm::Mat4) {
+        // glue a transformed copy of other onto this mesh, for shapes built from parts
+        // normals go through the inverse transpose so a squashed part still shades right
+        let normal_matrix = glm::mat4_to_mat3(&glm::inverse_transpose(*model));
+        let offset = (self.vertices.len() / 6) as u32;
+        for vertex in other.vertices.chunks_exact(6) {
+            let position = model * glm::vec4(vertex[0], vertex[1], vertex[2], 1.0);
+            let normal =
+                glm::normalize(&(normal_matrix * glm::vec3(vertex[3], vertex[4], vertex[5])));
+            self.vertices
+                .extend_from_slice(&[position.x, position.y, position.z]);
+            self.vertices.extend_from_slice(normal.as_slice());
+        }
+        self.indices
+            .extend(other.indices.iter().map(|index| index + offset));
+        // uvs only survive if every part had them
+        if self.uvs.len() / 2 == offset as usize && !other.uvs.is_empty() {
+            self.uvs.extend_from_slice(&other.uvs);
+        } else {
+            self.uvs.clear();
+        }
+    }
+}
+
+pub fn generate_sphere(radius: f32, sectors: i32, stacks: i32) -> Mesh {
+    // lat-long sphere tessellation
+    // one vertex per grid point, triangles reference them by index
+    // the seam column is duplicated (j = 0 and j = sectors) so the uvs can go from 0 to 1
+    // without the last column wrapping back over the whole texture
+    // u runs around the z axis, v from the +z pole (0) to the -z one (1)
 
     let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut uvs = Vec::new();
     let sector_step = 2.0 * PI / sectors as f32;
     let stack_step = PI / stacks as f32;
 
-    for i in 0..stacks {
-        let stack_angle1 = PI / 2.0 - (i as f32 * stack_step);
-        let stack_angle2 = PI / 2.0 - ((i + 1) as f32 * stack_step);
+    for i in 0..=stacks {
+        let stack_angle = PI / 2.0 - (i as f32 * stack_step);
+        let xy = radius * stack_angle.cos();
+        let z = radius * stack_angle.sin();
 
-        for j in 0..sectors {
-            let sector_angle1 = j as f32 * sector_step;
-            let sector_angle2 = (j + 1) as f32 * sector_step;
+        for j in 0..=sectors {
+            let sector_angle = j as f32 * sector_step;
+            let x = xy * sector_angle.cos();
+            let y = xy * sector_angle.sin();
+
+            // position, then normal (sphere normal = position / radius)
+            vertices.extend_from_slice(&[x, y, z]);
+            vertices.extend_from_slice(&[x / radius, y / radius, z / radius]);
+            uvs.extend_from_slice(&[j as f32 / sectors as f32, i as f32 / stacks as f32]);
+        }
+    }
+
+    for i in 0..stacks {
+        let k1 = (i * (sectors + 1)) as u32; // start of this stack row
+        let k2 = k1 + sectors as u32 + 1; // start of the next one
 
+        for j in 0..sectors as u32 {
             // quad on sphere surface
             // split into two triangles
+            // pole rows collapse to a point so skip their degenerate half
 
-            let v1 = glm::vec3(
-                radius * stack_angle1.cos() * sector_angle1.cos(),
-                radius * stack_angle1.cos() * sector_angle1.sin(),
-                radius * stack_angle1.sin(),
-            );
-            let v2 = glm::vec3(
-                radius * stack_angle1.cos() * sector_angle2.cos(),
-                radius * stack_angle1.cos() * sector_angle2.sin(),
-                radius * stack_angle1.sin(),
-            );
-            let v3 = glm::vec3(
-                radius * stack_angle2.cos() * sector_angle1.cos(),
-                radius * stack_angle2.cos() * sector_angle1.sin(),
-                radius * stack_angle2.sin(),
-            );
-            let v4 = glm::vec3(
-                radius * stack_angle2.cos() * sector_angle2.cos(),
-                radius * stack_angle2.cos() * sector_angle2.sin(),
-                radius * stack_angle2.sin(),
-            );
+            if i != 0 {
+                indices.extend_from_slice(&[k1 + j, k2 + j, k1 + j + 1]);
+            }
+            if i != stacks - 1 {
+                indices.extend_from_slice(&[k1 + j + 1, k2 + j, k2 + j + 1]);
+            }
+        }
+    }
+
+    Mesh {
+        vertices,
+        indices,
+        uvs,
+    }
+}
+
+pub fn generate_quad() -> Mesh {
+    // square from -1 to 1 in the xy plane, facing +z
+    // billboards use the corners as offsets, so it is never transformed as a mesh
+    let mut vertices = Vec::new();
+    for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+        vertices.extend_from_slice(&[x, y, 0.0, 0.0, 0.0, 1.0]);
+    }
+    Mesh {
+        vertices,
+        indices: vec![0, 1, 2, 0, 2, 3],
+        uvs: Vec::new(),
+    }
+}
+
+pub fn generate_cylinder(radius: f32, height: f32, sectors: i32) -> Mesh {
+    // open tube along +y, from y = 0 to y = height
+    // no caps: bonds always end inside a sphere so nobody sees them
+    // two rings of vertices, normals point straight out of the side
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let sector_step = 2.0 * PI / sectors as f32;
+
+    for j in 0..=sectors {
+        let angle = j as f32 * sector_step;
+        let (nx, nz) = (angle.cos(), angle.sin());
+
+        // bottom vertex, then top vertex, same normal
+        vertices.extend_from_slice(&[radius * nx, 0.0, radius * nz, nx, 0.0, nz]);
+        vertices.extend_from_slice(&[radius * nx, height, radius * nz, nx, 0.0, nz]);
+    }
+
+    for j in 0..sectors as u32 {
+        let bottom = 2 * j;
+        let top = bottom + 1;
+        let next_bottom = bottom + 2;
+        let next_top = bottom + 3;
+
+        // quad between this column and the next
+        indices.extend_from_slice(&[bottom, top, next_bottom]);
+        indices.extend_from_slice(&[next_bottom, top, next_top]);
+    }
+
+    Mesh {
+        vertices,
+        indices,
+        uvs: Vec::new(),
+    }
+}
+
+pub fn generate_torus(radius: f32, tube: f32, sectors: i32, sides: i32) -> Mesh {
+    // ring around +y in the xz plane (same plane the orbits use), `tube` thick
+    // only exported, on screen the shell rings are plain line loops
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
 
-            // triangle 1
-            vertices.extend_from_slice(&[v1.x, v1.y, v1.z]);
-            vertices.extend_from_slice(&[v3.x, v3.y, v3.z]);
-            vertices.extend_from_slice(&[v2.x, v2.y, v2.z]);
+    for j in 0..=sectors {
+        let around = j as f32 * 2.0 * PI / sectors as f32;
+        let (ca, sa) = (around.cos(), around.sin());
+        for k in 0..=sides {
+            let fold = k as f32 * 2.0 * PI / sides as f32;
+            let (cf, sf) = (fold.cos(), fold.sin());
+            // normal points away from the circle through the middle of the tube
+            let normal = [cf * ca, sf, cf * sa];
+            let distance = radius + tube * cf;
+            vertices.extend_from_slice(&[distance * ca, tube * sf, distance * sa]);
+            vertices.extend_from_slice(&normal);
+        }
+    }
 
-            // triangle 2
-            vertices.extend_from_slice(&[v2.x, v2.y, v2.z]);
-            vertices.extend_from_slice(&[v3.x, v3.y, v3.z]);
-            vertices.extend_from_slice(&[v4.x, v4.y, v4.z]);
+    let row = sides as u32 + 1;
+    for j in 0..sectors as u32 {
+        for k in 0..sides as u32 {
+            let a = j * row + k;
+            let b = a + row; // same spot on the next ring
+            indices.extend_from_slice(&[a, a + 1, b]);
+            indices.extend_from_slice(&[b, a + 1, b + 1]);
         }
     }
 
-    vertices
+    Mesh {
+        vertices,
+        indices,
+        uvs: Vec::new(),
+    }
+}
+
+pub fn generate_ring(inner: f32, outer: f32, sectors: i32) -> Mesh {
+    // flat annulus in the xz plane, between the two radii, facing +y (one sided)
+    // an inner and an outer vertex per column, the seam column duplicated like the sphere
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let sector_step = 2.0 * PI / sectors as f32;
+
+    for j in 0..=sectors {
+        let angle = j as f32 * sector_step;
+        let (c, s) = (angle.cos(), angle.sin());
+        vertices.extend_from_slice(&[inner * c, 0.0, inner * s, 0.0, 1.0, 0.0]);
+        vertices.extend_from_slice(&[outer * c, 0.0, outer * s, 0.0, 1.0, 0.0]);
+    }
+
+    for j in 0..sectors as u32 {
+        let inside = 2 * j;
+        let outside = inside + 1;
+        // counter-clockwise seen from above
+        indices.extend_from_slice(&[inside, inside + 2, outside + 2]);
+        indices.extend_from_slice(&[inside, outside + 2, outside]);
+    }
+
+    Mesh {
+        vertices,
+        indices,
+        uvs: Vec::new(),
+    }
+}
+
+pub fn generate_disc(radius: f32, sectors: i32) -> Mesh {
+    // filled circle in the xz plane facing +y (one sided), a fan around a center vertex
+    // a ring with inner radius 0 would do too, with a column of degenerate triangles
+
+    let mut vertices = vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+    let mut indices = Vec::new();
+    let sector_step = 2.0 * PI / sectors as f32;
+
+    for j in 0..=sectors {
+        let angle = j as f32 * sector_step;
+        vertices.extend_from_slice(&[radius * angle.cos(), 0.0, radius * angle.sin()]);
+        vertices.extend_from_slice(&[0.0, 1.0, 0.0]);
+    }
+    for j in 1..=sectors as u32 {
+        indices.extend_from_slice(&[0, j + 1, j]);
+    }
+
+    Mesh {
+        vertices,
+        indices,
+        uvs: Vec::new(),
+    }
+}
+
+pub fn generate_arrow(length: f32, shaft: f32, head: f32, head_length: f32, sectors: i32) -> Mesh {
+    // closed arrow along +y, tail at the origin and tip at y = length
+    // shaft: radius of the stick, head: radius at the base of the cone, head_length of
+    // the cone (squeezed to fit when the arrow is shorter than that)
+    // built from the other primitives: a tube, a disc to close the tail, a ring under the
+    // overhang of the head, and the cone itself
+
+    let head_length = head_length.min(length);
+    let stick = length - head_length;
+    let mut mesh = generate_cylinder(shaft, stick, sectors);
+    let flip = glm::rotation(PI, &glm::vec3(1.0, 0.0, 0.0)); // facing -y
+    mesh.append(&generate_disc(shaft, sectors), &flip);
+    mesh.append(
+        &generate_ring(shaft, head, sectors),
+        &(glm::translation(&glm::vec3(0.0, stick, 0.0)) * flip),
+    );
+
+    // cone: a base and an apex vertex per column, the apex repeated so every column
+    // keeps its own normal; those lean up by the cone's slope
+    let mut cone = Mesh {
+        vertices: Vec::new(),
+        indices: Vec::new(),
+        uvs: Vec::new(),
+    };
+    let sector_step = 2.0 * PI / sectors as f32;
+    for j in 0..=sectors {
+        let angle = j as f32 * sector_step;
+        let (c, s) = (angle.cos(), angle.sin());
+        let normal = glm::normalize(&glm::vec3(c * head_length, head, s * head_length));
+        cone.vertices
+            .extend_from_slice(&[head * c, stick, head * s, normal.x, normal.y, normal.z]);
+        cone.vertices
+            .extend_from_slice(&[0.0, length, 0.0, normal.x, normal.y, normal.z]);
+    }
+    for j in 0..sectors as u32 {
+        let base = 2 * j;
+        // same winding as the cylinder side, outward
+        cone.indices.extend_from_slice(&[base, base + 1, base + 2]);
+    }
+    mesh.append(&cone, &glm::identity());
+
+    mesh
 }
 
 // compile shader from source
-// this is where errors scream loudly
-unsafe fn shader_from_source(source: &CStr, kind: gl::types::GLenum) -> gl::types::GLuint {
+// this is where errors scream loudly (well, return loudly now)
+unsafe fn shader_from_source(
+    source: &CStr,
+    kind: gl::types::GLenum,
+) -> Result<gl::types::GLuint, ShaderError> {
     let id = gl::CreateShader(kind);
     gl::ShaderSource(id, 1, &source.as_ptr(), ptr::null());
     gl::CompileShader(id);
@@ -204,12 +1236,12 @@ unsafe fn shader_from_source(source: &CStr, kind: gl::types::GLenum) -> gl::type
     gl::GetShaderiv(id, gl::COMPILE_STATUS, &mut success);
 
     if success == 0 {
-        // if compilation fails, grab error log and panic
+        // if compilation fails, grab error log and hand it back
         let mut len: gl::types::GLint = 0;
         gl::GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut len);
 
-        let mut info_log = Vec::with_capacity(len as usize);
-        info_log.set_len((len as usize) - 1);
+        // len includes the nul terminator, the buffer must fit all of it
+        let mut info_log = vec![0u8; len.max(1) as usize];
 
         gl::GetShaderInfoLog(
             id,
@@ -217,12 +1249,38 @@ unsafe fn shader_from_source(source: &CStr, kind: gl::types::GLenum) -> gl::type
             ptr::null_mut(),
             info_log.as_mut_ptr() as *mut gl::types::GLchar,
         );
+        gl::DeleteShader(id);
 
-        panic!(
-            "shader compilation error: {}",
-            String::from_utf8_lossy(&info_log)
-        );
+        let stage = match kind {
+            gl::VERTEX_SHADER => "vertex",
+            gl::FRAGMENT_SHADER => "fragment",
+            gl::COMPUTE_SHADER => "compute",
+            _ => "unknown",
+        };
+
+        return Err(ShaderError::Compile {
+            stage,
+            log: log_to_string(&info_log),
+        });
     }
 
-    id
+    Ok(id)
+}
+
+// driver logs come back nul terminated, strip that for printing
+fn log_to_string(log: &[u8]) -> String {
+    let end = log.iter().position(|&b| b == 0).unwrap_or(log.len());
+    String::from_utf8_lossy(&log[..end]).trim_end().to_string()
+}
+
+pub fn srgb_to_linear(c: &glm::Vec3) -> glm::Vec3 {
+    // same curve as srgbToLinear in the shaders, for colors handed to gl outside of them
+    // (clear color, single-color uniforms)
+    c.map(|v| {
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    })
 }
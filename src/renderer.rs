@@ -0,0 +1,1271 @@
+use crate::absorption; // photon wavelength labels
+use crate::aufbau; // builder mode panel
+use crate::background::Background; // gradient / skybox
+use crate::bloom::Bloom; // glow post-processing
+use crate::capture; // framebuffer readback
+use crate::compare; // comparison table
+use crate::console::Console; // command line overlay
+use crate::decay; // alpha particles of scattering mode
+use crate::dof::DepthOfField; // focus blur
+use crate::field::FieldRenderer; // field lines and potential slices
+use crate::framebuffer::{self, ColorFormat, Framebuffer}; // offscreen target
+use crate::frustum::Frustum; // off screen culling
+use crate::fxaa::Fxaa; // antialiasing fallback
+use crate::gamma::Gamma; // srgb encode fallback
+use crate::gizmo::Gizmo; // shell rings + corner axes
+use crate::ground::Ground; // floor under the atoms
+use crate::levels; // energy level sidebar
+use crate::lod::{self, SphereLod}; // sphere tessellation per screen size
+use crate::magnetic::{self, MagneticOverlay}; // orbital current loops + moment arrow
+use crate::material::{Material, Shading}; // per batch surface uniforms
+use crate::molecule::Editor; // molecule editor palette
+use crate::nucleus; // nucleus size for the ion glow
+use crate::orbital::OrbitalCloud; // point cloud gpu buffer
+use crate::particles::ParticleRenderer; // sparks and streaks
+use crate::periodic::PeriodicTable; // element picker overlay
+use crate::physics; // quantum numbers of the density cloud
+use crate::profiler::{GpuProfiler, Pass, PASSES}; // gpu time per pass in the hud
+use crate::quality::{Edges, Quality}; // mesh detail and antialiasing
+use crate::radial; // P(r) chart
+use crate::render::{
+    generate_arrow, generate_cylinder, generate_sphere, srgb_to_linear, FrameBlock, FrameUniforms,
+    InstancedRenderer, ShaderError, ShaderProgram, VertexArray, WatchedProgram,
+};
+use crate::scale; // true scale nucleus and scale bar
+use crate::scattering::{self, ScatteringRenderer}; // alpha particle paths and readout
+use crate::scene::{BondOrder, RenderMode, Scene, ViewMode};
+use crate::shadow::ShadowMap; // key light shadows
+use crate::slice::{DensitySlice, SliceSource}; // cross-section density map
+use crate::spectrum; // emission lines bar
+use crate::sprites::{SpriteRenderer, SPRITE_RADIUS}; // far electrons and photons
+use crate::ssao::Ssao; // contact shading
+use crate::stereo::{Stereo, StereoMode}; // one frame per eye, put together
+use crate::style::{self, MoleculeStyle}; // balls and sticks instead of shells
+use crate::text::{hud_size, TextRenderer}; // hud overlay
+use crate::texture::{SurfaceTextures, TextureFiles}; // charge marks on the spheres
+use crate::tonemap::{ToneMap, ToneMapping}; // hdr to screen
+use crate::trail::Trails; // electron trails
+use crate::transparency::TransparentPass; // back-to-front blended spheres
+use image::RgbaImage; // offscreen render result
+use nalgebra_glm as glm;
+use std::f32::consts::PI; // flips the spin arrows
+
+// turns a Scene into pixels
+// owns every gpu resource needed for that: shaders, meshes, instance buffers
+// render() draws into whatever framebuffer is bound (normally the window)
+// render_offscreen() draws into a private fbo and hands back an image
+
+pub struct Renderer {
+    // all shaders follow their files in assets/shaders, see WatchedProgram
+    flat_program: WatchedProgram, // raw color, used for the density cloud
+    lit_program: WatchedProgram,  // blinn-phong, used for the bohr atom
+    points_program: WatchedProgram, // soft points, used for the orbital cloud
+    frame: FrameBlock,            // camera + lights, shared by every world space shader
+
+    // one instanced renderer per batch
+    // each owns its own vao so their instance buffers dont fight
+    cloud_renderer: InstancedRenderer,
+    sphere_batches: Vec<SphereLod>, // nuclei + electrons, one per material in the world
+    ejecta_spheres: SphereLod,      // decay products
+    molecule_spheres: SphereLod,    // one ball per atom in the molecule styles
+    highlight: InstancedRenderer,   // outline around the selected particle
+    photons: InstancedRenderer,     // unlit, they are light after all
+    transparent: TransparentPass,   // sorted, blended: glows, translucent cloud
+    bond_renderer: InstancedRenderer,
+    ground: Ground,
+    shadow: ShadowMap,
+    spin_arrows: InstancedRenderer, // one through every electron when spins are shown
+    sprites: SpriteRenderer,        // electrons and photons too small on screen for a mesh
+    surfaces: SurfaceTextures,      // what textured materials wrap around their spheres
+    orbital_cloud: OrbitalCloud,
+    text: TextRenderer,
+    fxaa: Fxaa,   // only used when the target framebuffer has no msaa
+    gamma: Gamma, // only used when the target framebuffer has no srgb encoding
+    bloom: Bloom,
+    ssao: Ssao,
+    dof: DepthOfField,
+    tonemap: ToneMap,
+    stereo: Stereo,
+    background: Background,
+    gizmo: Gizmo,
+    trails: Trails,
+    particles: ParticleRenderer,
+    slice: DensitySlice,
+    field: FieldRenderer,
+    magnetic: MagneticOverlay,
+    scattering: ScatteringRenderer,
+    pub profiler: GpuProfiler, // main times the control panel with it too
+    quality: Quality,          // what the sphere meshes are built at, see set_quality
+}
+
+impl Renderer {
+    pub unsafe fn new(
+        scene: &Scene,
+        orbital_cloud: OrbitalCloud,
+        skybox: Option<&str>,
+        textures: &TextureFiles,
+    ) -> Result<Self, ShaderError> {
+        // orbital_cloud: already sampled, on the gpu or the cpu, see orbital.rs
+        // (and sampled again whenever scene.orbital changes)
+        // skybox: directory with the six cubemap faces, see background.rs
+        // textures: image files replacing the generated sphere textures, see texture.rs
+        let flat_program = WatchedProgram::new("flat", ShaderProgram::flat)?;
+        let lit_program = WatchedProgram::new("phong", ShaderProgram::phong)?;
+        let points_program = WatchedProgram::new("points", ShaderProgram::points)?;
+
+        let quality = scene.display.quality;
+        let cloud_renderer = cloud_spheres(scene, quality);
+        let ejecta_spheres = SphereLod::new(quality);
+        let molecule_spheres = SphereLod::new(quality);
+        let highlight = sphere_renderer(quality, 24, 16);
+        let photons = sphere_renderer(quality, 12, 8);
+        let transparent = TransparentPass::new(quality);
+
+        // unit bond stick: radius 1, length 1, scaled per bond
+        let cylinder_mesh = generate_cylinder(1.0, 1.0, 12);
+        let bond_renderer = InstancedRenderer::new(VertexArray::from_mesh(&cylinder_mesh));
+        let ground = Ground::new()?;
+        let shadow = ShadowMap::new()?;
+        // unit arrow, scaled to the electrons and centered on them
+        let arrow_mesh = generate_arrow(1.0, 0.05, 0.14, 0.25, 12);
+        let spin_arrows = InstancedRenderer::new(VertexArray::from_mesh(&arrow_mesh));
+
+        let text = TextRenderer::new()?;
+        let fxaa = Fxaa::new()?;
+        let gamma = Gamma::new()?;
+        let bloom = Bloom::new()?;
+        let ssao = Ssao::new()?;
+        let dof = DepthOfField::new()?;
+        let tonemap = ToneMap::new()?;
+        let stereo = Stereo::new()?;
+        let background = Background::new(skybox)?;
+        let gizmo = Gizmo::new()?;
+        let trails = Trails::new()?;
+        let particles = ParticleRenderer::new()?;
+        let slice = DensitySlice::new()?;
+        let field = FieldRenderer::new()?;
+        let magnetic = MagneticOverlay::new()?;
+        let scattering = ScatteringRenderer::new()?;
+        let sprites = SpriteRenderer::new()?;
+        let surfaces = SurfaceTextures::new(textures);
+
+        // Enable Depth Test
+        gl::Enable(gl::DEPTH_TEST);
+
+        Ok(Self {
+            flat_program,
+            lit_program,
+            points_program,
+            frame: FrameBlock::new(),
+            cloud_renderer,
+            sphere_batches: Vec::new(),
+            ejecta_spheres,
+            molecule_spheres,
+            highlight,
+            photons,
+            transparent,
+            bond_renderer,
+            ground,
+            shadow,
+            spin_arrows,
+            sprites,
+            surfaces,
+            orbital_cloud,
+            text,
+            fxaa,
+            gamma,
+            bloom,
+            ssao,
+            dof,
+            tonemap,
+            stereo,
+            background,
+            gizmo,
+            trails,
+            particles,
+            slice,
+            field,
+            magnetic,
+            scattering,
+            profiler: GpuProfiler::new(),
+            quality,
+        })
+    }
+
+    unsafe fn set_quality(&mut self, scene: &Scene) {
+        // every sphere mesh again at the new detail; the batches fill themselves up again as
+        // needed, the cloud is filled into its new mesh right here like at startup
+        let quality = scene.display.quality;
+        self.quality = quality;
+        self.cloud_renderer = cloud_spheres(scene, quality);
+        self.sphere_batches.clear();
+        self.ejecta_spheres = SphereLod::new(quality);
+        self.molecule_spheres = SphereLod::new(quality);
+        self.highlight = sphere_renderer(quality, 24, 16);
+        self.photons = sphere_renderer(quality, 12, 8);
+        self.transparent = TransparentPass::new(quality);
+        log::info!("render quality: {}", quality.name());
+    }
+
+    pub unsafe fn render(&mut self, scene: &mut Scene, width: u32, height: u32) {
+        // draw one frame of the scene into the currently bound framebuffer
+        // in stereo every eye goes through all of render_view into its own target, with the
+        // camera slid over for it (mutable just for that, it is back in the middle after)
+        self.poll_shaders();
+        self.profiler.set_enabled(scene.display.profiler);
+        if scene.display.quality != self.quality {
+            self.set_quality(scene);
+        }
+        let mode = scene.display.stereo;
+        if mode == StereoMode::Off {
+            self.render_view(scene, width, height);
+            return;
+        }
+        let (eye_width, eye_height) = mode.eye_size(width, height);
+        let half = scene.display.eye_separation / 2.0;
+        self.stereo.begin();
+        for (index, eye) in [-half, half].into_iter().enumerate() {
+            scene.camera.eye = eye;
+            self.stereo.bind_eye(index, eye_width, eye_height);
+            self.render_view(scene, eye_width, eye_height);
+        }
+        scene.camera.eye = 0.0;
+
+        // the eyes come out of their textures linear, so the target encodes again
+        self.profiler.begin(Pass::Post);
+        self.stereo.finish();
+        let encode_by_hand = !framebuffer::is_srgb();
+        gl::Enable(gl::FRAMEBUFFER_SRGB);
+        if encode_by_hand {
+            self.gamma.begin(width, height);
+        }
+        self.stereo.compose(mode, width, height);
+        if encode_by_hand {
+            self.gamma.finish();
+        }
+        self.profiler.end();
+        gl::Disable(gl::FRAMEBUFFER_SRGB);
+        check_gl_error!("stereo render");
+    }
+
+    unsafe fn render_view(&mut self, scene: &Scene, width: u32, height: u32) {
+        // one picture from the camera as it is
+        // antialiasing: msaa if the target has samples, otherwise an fxaa pass
+        // bloom, ssao, depth of field and tone mapping draw into their own single-sampled
+        // targets, so with any of them on msaa has nothing to work on and fxaa takes over
+        // color: everything in here is lit and blended in linear space, the target encodes
+        // to srgb on write; a target that cannot (some windows) gets the gamma pass instead,
+        // which draws into its own single-sampled texture, so then fxaa takes over as well
+        let encode_by_hand = !framebuffer::is_srgb();
+        gl::Enable(gl::FRAMEBUFFER_SRGB);
+        if encode_by_hand {
+            self.gamma.begin(width, height);
+        }
+        // below high quality the samples go unused: fxaa only, or nothing at all
+        let samples = framebuffer_samples();
+        let bloom = scene.display.bloom;
+        let ssao = scene.display.ssao;
+        let dof = scene.display.dof;
+        let tonemap = scene.display.tone_mapping != ToneMapping::Off;
+        let edges = scene.display.quality.edges();
+        let antialiasing = scene.display.antialiasing && edges != Edges::Jagged;
+        let single_sampled = bloom || ssao || dof || tonemap;
+        let use_msaa = antialiasing && edges == Edges::Msaa && samples > 0 && !single_sampled;
+        let use_fxaa = antialiasing && !use_msaa;
+
+        if use_msaa {
+            gl::Enable(gl::MULTISAMPLE);
+        } else {
+            gl::Disable(gl::MULTISAMPLE);
+        }
+
+        // nested: scene -> ssao target -> shade into the dof target -> blur into the bloom
+        //        target -> composite into the tone map target -> map into the fxaa target
+        //        -> resolve (-> gamma target -> encode)
+        if use_fxaa {
+            self.fxaa.begin(width, height);
+        }
+        if tonemap {
+            self.tonemap.begin(width, height);
+        }
+        if bloom {
+            self.bloom.begin(width, height);
+        }
+        if dof {
+            self.dof.begin(width, height);
+        }
+        if ssao {
+            self.ssao.begin(width, height);
+        }
+        self.draw_scene(scene, width, height);
+        self.profiler.begin(Pass::Post);
+        if ssao {
+            self.ssao.finish(
+                &scene.camera.get_projection_matrix(),
+                scene.display.ssao_strength,
+                scene.display.ssao_radius,
+                scene.display.quality.occlusion_samples(),
+            );
+        }
+        if dof {
+            self.dof.finish(
+                &scene.camera.get_projection_matrix(),
+                focus_distance(scene),
+                scene.display.dof_aperture,
+            );
+        }
+        if bloom {
+            self.bloom
+                .finish(scene.display.bloom_threshold, scene.display.bloom_intensity);
+        }
+        if tonemap {
+            self.tonemap
+                .finish(scene.display.tone_mapping, scene.display.exposure);
+        }
+        if use_fxaa {
+            self.fxaa.finish();
+        }
+        if encode_by_hand {
+            self.gamma.finish();
+        }
+        self.profiler.end();
+        // overlays drawn after this (hud, periodic table, egui) are flat colors that never get
+        // lit, they write their srgb colors straight through
+        gl::Disable(gl::FRAMEBUFFER_SRGB);
+        check_gl_error!("scene render");
+    }
+
+    unsafe fn poll_shaders(&mut self) {
+        // pick up edited glsl files, cheap when nothing changed
+        self.flat_program.poll();
+        self.lit_program.poll();
+        self.points_program.poll();
+        self.text.poll_shaders();
+        self.fxaa.poll_shaders();
+        self.gamma.poll_shaders();
+        self.bloom.poll_shaders();
+        self.ssao.poll_shaders();
+        self.dof.poll_shaders();
+        self.tonemap.poll_shaders();
+        self.shadow.poll_shaders();
+        self.ground.poll_shaders();
+        self.stereo.poll_shaders();
+        self.background.poll_shaders();
+        self.gizmo.poll_shaders();
+        self.trails.poll_shaders();
+        self.particles.poll_shaders();
+        self.slice.poll_shaders();
+        self.field.poll_shaders();
+        self.magnetic.poll_shaders();
+        self.scattering.poll_shaders();
+        self.sprites.poll_shaders();
+    }
+
+    unsafe fn draw_scene(&mut self, scene: &Scene, width: u32, height: u32) {
+        self.profiler.begin(Pass::Opaque);
+        // key light up and to the side, dim fill light from below-behind
+        let lights = vec![glm::vec3(20.0, 30.0, 20.0), glm::vec3(-15.0, -10.0, -25.0)];
+        // into its own framebuffer, before this one is cleared
+        if scene.display.shadows && scene.view_mode == ViewMode::Bohr {
+            self.shadow.render(scene, &lights[0]);
+        } else {
+            self.shadow.skip();
+        }
+        gl::Viewport(0, 0, width as i32, height as i32);
+        // the clear color is written like any other color, so it has to be linear too
+        let background = srgb_to_linear(&scene.display.theme.background);
+        gl::ClearColor(background.x, background.y, background.z, 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        self.background.draw(&scene.display, &scene.camera);
+
+        let view = scene.camera.get_view_matrix();
+        let projection = scene.camera.get_projection_matrix();
+
+        // rasterization mode for the meshes only
+        // trails, text and post-processing always draw filled, so it is reset afterwards
+        set_polygon_mode(scene.render_mode);
+
+        // everything the world space shaders have in common, in one upload
+        // the cross-section plane cuts whatever draws clouds and spheres (flat, phong,
+        // points, sprites)
+        let clip = &scene.display.clip;
+        self.frame.upload(&FrameUniforms {
+            view,
+            projection,
+            camera: scene.camera.get_position(),
+            clip: clip.equation(),
+            lights,
+            light_color: glm::vec3(1.0, 1.0, 1.0),
+        });
+        self.shadow.apply(&self.lit_program);
+        if clip.enabled {
+            gl::Enable(gl::CLIP_DISTANCE0);
+        }
+
+        match scene.view_mode {
+            ViewMode::Bohr => self.draw_bohr(scene, &view, &projection, height),
+            ViewMode::Orbital => {
+                self.points_program.use_program();
+                self.points_program.set_uniform_f32("pointSize", 60.0);
+                self.orbital_cloud.follow(&scene.orbital);
+                self.orbital_cloud.draw();
+            }
+            ViewMode::Cloud => {
+                self.flat_program.use_program();
+                let opacity = scene.display.cloud_opacity;
+                if opacity >= 1.0 {
+                    self.cloud_renderer.draw();
+                } else {
+                    // see-through cloud: every sample is re-sorted each frame,
+                    // so the inner structure shows through the outer layers correctly
+                    // (the opaque cloud is one buffer filled once, it is not culled)
+                    self.profiler.begin(Pass::Transparent);
+                    let frustum = Frustum::new(&(projection * view));
+                    for particle in &scene.cloud_particles {
+                        let p = particle.position;
+                        let center = glm::vec3(p.x as f32, p.y as f32, p.z as f32);
+                        if !frustum.contains_sphere(&center, CLOUD_PARTICLE_SIZE) {
+                            continue;
+                        }
+                        let model = glm::scale(
+                            &glm::translation(&center),
+                            &glm::vec3(
+                                CLOUD_PARTICLE_SIZE,
+                                CLOUD_PARTICLE_SIZE,
+                                CLOUD_PARTICLE_SIZE,
+                            ),
+                        );
+                        let c = particle.color;
+                        self.transparent.push(
+                            model,
+                            glm::vec4(c.x, c.y, c.z, c.w * opacity),
+                            &view,
+                        );
+                    }
+                    self.transparent.flush();
+                }
+            }
+        }
+
+        gl::Disable(gl::CLIP_DISTANCE0);
+        set_polygon_mode(RenderMode::Solid);
+    }
+
+    unsafe fn draw_bohr(
+        &mut self,
+        scene: &Scene,
+        view: &glm::Mat4,
+        projection: &glm::Mat4,
+        height: u32,
+    ) {
+        // rebuilt every frame because electrons move
+        // every sphere entity in the world becomes one sphere instance, batched by material;
+        // the material carries the color, so the instance itself is white
+        // detail level is picked per instance from how big it ends up on screen
+        // anything entirely outside the view is left out, see frustum.rs
+        // the molecule styles leave all of that out and draw a ball per atom, see style.rs
+        let style = scene.display.molecule_style;
+        let shells = style == MoleculeStyle::Shells;
+        let frustum = Frustum::new(&(projection * view));
+        let emissive = emissive_boost(scene);
+        let world = &scene.world;
+        while self.sphere_batches.len() < world.library.count() {
+            self.sphere_batches.push(SphereLod::new(self.quality));
+        }
+        let batches = &mut self.sphere_batches;
+        let spin_arrows = &mut self.spin_arrows;
+        let sprites = &mut self.sprites;
+        batches.iter_mut().for_each(SphereLod::clear);
+        spin_arrows.clear();
+        sprites.clear();
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        for sphere in world.spheres().filter(|_| shells) {
+            // the arrow sticks out past the sphere, so it gets its own test
+            let center = sphere.world.column(3).xyz();
+            let spin = world.pick_id(sphere.entity).filter(|_| scene.display.spin);
+            if let Some(up) = spin.and_then(|id| scene.spin(id)) {
+                let half_length = sphere.radius * SPIN_ARROW_LENGTH * 0.5;
+                if frustum.contains_sphere(&center, half_length) {
+                    spin_arrows.push(
+                        &spin_model(&sphere.world, sphere.radius, up),
+                        spin_color(up),
+                    );
+                }
+            }
+            if !frustum.contains_sphere(&center, sphere.radius) {
+                continue;
+            }
+            let model = &sphere.model;
+            let screen_radius = lod::projected_radius(model, view, projection, height);
+            // the sprite shader knows nothing about materials, it gets the final color
+            if screen_radius < SPRITE_RADIUS && world.electrons.get(sphere.entity).is_some() {
+                let material = sphere.material;
+                let rgb = material.albedo * material.brightness(emissive);
+                sprites.push(
+                    model,
+                    &glm::vec4(rgb.x, rgb.y, rgb.z, material.opacity),
+                    true,
+                );
+            } else {
+                batches[sphere.material_id].push(model, &white, screen_radius);
+            }
+        }
+        // decay products are lit like the rest, they are nucleons and electrons too
+        self.ejecta_spheres.clear();
+        for ejecta in &scene.ejecta {
+            for (center, r, c) in ejecta.spheres(&scene.display, scene.simulation.alpha()) {
+                if !frustum.contains_sphere(&center, r) {
+                    continue;
+                }
+                let model = glm::scale(&glm::translation(&center), &glm::vec3(r, r, r));
+                let screen_radius = lod::projected_radius(&model, view, projection, height);
+                self.ejecta_spheres
+                    .push(&model, &glm::vec4(c.x, c.y, c.z, 1.0), screen_radius);
+            }
+        }
+        // and so is the builder's electron on its way in, in the color of the shell it is for
+        let incoming = scene.builder.as_ref().zip(scene.atoms.first());
+        if let Some((builder, main_atom)) = incoming {
+            if let Some((n, position)) = builder.incoming(scene.simulation.alpha()) {
+                let center = main_atom.to_world(&position);
+                let r = scene.display.electron_scale;
+                let c = scene.display.theme.electron(n);
+                let model = glm::scale(&glm::translation(&center), &glm::vec3(r, r, r));
+                let screen_radius = lod::projected_radius(&model, view, projection, height);
+                self.ejecta_spheres
+                    .push(&model, &glm::vec4(c.x, c.y, c.z, 1.0), screen_radius);
+            }
+        }
+        // and the alpha particles of scattering mode
+        if let Some(scattering) = &scene.scattering {
+            for (position, age) in scattering.flying(scene.simulation.alpha()) {
+                for (center, r, c) in decay::alpha_spheres(position, age, &scene.display) {
+                    if !frustum.contains_sphere(&center, r) {
+                        continue;
+                    }
+                    let model = glm::scale(&glm::translation(&center), &glm::vec3(r, r, r));
+                    let screen_radius = lod::projected_radius(&model, view, projection, height);
+                    self.ejecta_spheres
+                        .push(&model, &glm::vec4(c.x, c.y, c.z, 1.0), screen_radius);
+                }
+            }
+        }
+
+        // the cross-section cuts atoms, not the floor
+        gl::Disable(gl::CLIP_DISTANCE0);
+        self.ground.draw(scene, &self.shadow);
+        if scene.display.clip.enabled {
+            gl::Enable(gl::CLIP_DISTANCE0);
+        }
+
+        // solid materials first, see-through ones blend over them
+        let mut order: Vec<usize> = (0..world.library.count()).collect();
+        order.sort_by_key(|&id| world.library.get(id).opacity < 1.0);
+        for id in order {
+            if self.sphere_batches[id].is_empty() {
+                continue; // nothing of it on screen, skip the uniforms too
+            }
+            let material = world.library.get(id);
+            self.profiler.begin(if material.opacity < 1.0 {
+                Pass::Transparent
+            } else {
+                Pass::Opaque
+            });
+            self.bind_material(material, scene);
+            self.sphere_batches[id].draw();
+            self.unbind_material(material);
+        }
+        self.profiler.begin(Pass::Opaque);
+        let plain = Material::plain();
+        self.bind_material(&plain, scene);
+        self.ejecta_spheres.draw();
+        if scene.display.spin {
+            self.spin_arrows.draw();
+        }
+
+        let balls = style::balls(style, &scene.atoms, &scene.bonds);
+        self.molecule_spheres.clear();
+        for ball in &balls {
+            if !frustum.contains_sphere(&ball.center, ball.radius) {
+                continue;
+            }
+            let r = ball.radius;
+            let model = glm::scale(&glm::translation(&ball.center), &glm::vec3(r, r, r));
+            let screen_radius = lod::projected_radius(&model, view, projection, height);
+            self.molecule_spheres
+                .push(&model, &ball.color, screen_radius);
+        }
+        self.molecule_spheres.draw();
+
+        // in a molecule style the selected atom's ball is outlined, not the hidden nucleon
+        let highlighted = if shells {
+            scene.selected.and_then(|id| scene.entity_sphere(id))
+        } else {
+            let target = scene.selected.map(|_| scene.target_atom());
+            balls
+                .iter()
+                .find(|ball| Some(ball.atom) == target)
+                .map(|ball| (ball.center, ball.radius))
+        };
+        if let Some((center, radius)) = highlighted {
+            self.draw_highlight(&center, radius, scene, view, projection, height);
+        }
+
+        if !scene.photons.is_empty() {
+            self.photons.clear();
+            for photon in &scene.photons {
+                let r = photon.radius();
+                let position = photon.interpolated(scene.simulation.alpha());
+                if !frustum.contains_sphere(&position, r) {
+                    continue;
+                }
+                let model = glm::scale(&glm::translation(&position), &glm::vec3(r, r, r));
+                let c = photon.color;
+                let color = glm::vec4(c.x, c.y, c.z, 1.0);
+                if lod::projected_radius(&model, view, projection, height) < SPRITE_RADIUS {
+                    let boosted = color.xyz() * Material::photon().brightness(emissive);
+                    self.sprites.push(
+                        &model,
+                        &glm::vec4(boosted.x, boosted.y, boosted.z, 1.0),
+                        false,
+                    );
+                } else {
+                    self.photons.push(&model, &color);
+                }
+            }
+            self.bind_material(&Material::photon(), scene);
+            self.photons.draw();
+        }
+        // after the photons, some of them may have ended up as sprites too
+        self.sprites.draw();
+
+        self.bond_renderer.clear();
+        for bond in scene.bonds.iter().filter(|_| shells) {
+            let (a, b) = (&scene.atoms[bond.a].position, &scene.atoms[bond.b].position);
+            // every stick of the bond fits in a sphere around its middle
+            let reach = glm::distance(a, b) * 0.5 + BOND_SPACING + BOND_RADIUS;
+            if !frustum.contains_sphere(&((a + b) * 0.5), reach) {
+                continue;
+            }
+            for model in bond_models(a, b, bond.order) {
+                self.bond_renderer.push(&model, &white);
+            }
+        }
+        self.bind_material(&Material::bond(), scene);
+        self.bond_renderer.draw();
+        // the styles' sticks bring their own colors
+        self.bond_renderer.clear();
+        for (model, color) in style::sticks(style, &scene.atoms, &scene.bonds) {
+            let middle = model.column(3).xyz() + model.column(1).xyz() * 0.5;
+            if frustum.contains_sphere(&middle, glm::length(&model.column(1).xyz())) {
+                self.bond_renderer.push(&model, &color);
+            }
+        }
+        self.bind_material(&Material::plain(), scene);
+        self.bond_renderer.draw();
+
+        // only flat / phong / points know about the cross-section, with the clip distance
+        // enabled anything else would be clipped at random; rings, sparks and trails stay whole
+        if scene.display.guides || scene.display.magnetic {
+            gl::Disable(gl::CLIP_DISTANCE0);
+            if scene.display.guides {
+                self.gizmo.draw_rings(scene, view, projection);
+            }
+            if scene.display.magnetic {
+                self.magnetic.draw(scene, view, projection);
+            }
+            if scene.display.clip.enabled {
+                gl::Enable(gl::CLIP_DISTANCE0);
+            }
+        }
+        self.profiler.begin(Pass::Transparent);
+        gl::Disable(gl::CLIP_DISTANCE0);
+        self.field.draw(scene, view, projection);
+        if scene.display.clip.enabled {
+            gl::Enable(gl::CLIP_DISTANCE0);
+        }
+        self.draw_glows(scene, view, projection);
+        gl::Disable(gl::CLIP_DISTANCE0);
+        self.profiler.begin(Pass::Particles);
+        self.particles.draw(&scene.particles);
+        if let Some(scattering) = &scene.scattering {
+            self.scattering.draw(scattering, view, projection);
+        }
+
+        // trails last: they blend over the solid spheres
+        set_polygon_mode(RenderMode::Solid);
+        // still recorded under the balls, so they carry on when the shells come back
+        self.trails.record(scene);
+        if shells {
+            self.trails.draw(scene);
+        }
+    }
+
+    unsafe fn bind_material(&self, material: &Material, scene: &Scene) {
+        // the material bind step: everything drawn with the lit program after this call
+        // looks like `material` (times its instance colors)
+        // glow is folded into the color before it goes linear, that is how the instance
+        // colors used to be boosted and what bloom.threshold is tuned for
+        let program = &self.lit_program;
+        program.use_program();
+        let glow = material.brightness(emissive_boost(scene));
+        program.set_uniform_vec3("materialColor", &srgb_to_linear(&(material.albedo * glow)));
+        program.set_uniform_f32("shininess", material.shininess);
+        program.set_uniform_f32("specularStrength", material.specular);
+        program.set_uniform_f32("opacity", material.opacity);
+        // wireframe and points have no meaningful normals, they go flat whatever the material
+        let unlit = material.shading == Shading::Unlit || scene.render_mode != RenderMode::Solid;
+        program.set_uniform_i32("unlit", unlit as i32);
+        let texture = material.texture.filter(|_| scene.display.textures);
+        if let Some(texture) = texture {
+            self.surfaces.get(texture).bind(0);
+            program.set_uniform_i32("surface", 0);
+        }
+        program.set_uniform_i32("textured", texture.is_some() as i32);
+        if material.opacity < 1.0 {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::DepthMask(gl::FALSE);
+        }
+    }
+
+    unsafe fn unbind_material(&self, material: &Material) {
+        // back to opaque drawing after a see-through material
+        if material.opacity < 1.0 {
+            gl::DepthMask(gl::TRUE);
+            gl::Disable(gl::BLEND);
+        }
+    }
+
+    unsafe fn draw_highlight(
+        &mut self,
+        center: &glm::Vec3,
+        radius: f32,
+        scene: &Scene,
+        view: &glm::Mat4,
+        projection: &glm::Mat4,
+        height: u32,
+    ) {
+        // inverted hull outline: a slightly bigger sphere with only its back faces drawn
+        // the real sphere covers the middle, so only a rim sticks out around it
+        // width is in pixels: the hull grows by that many pixels' worth of world units at
+        // the sphere's distance, so a far away electron is marked as clearly as a close nucleus
+        let pixels_per_unit =
+            lod::projected_radius(&glm::translation(center), view, projection, height);
+        // pixels as on a 1x screen, like the hud
+        let width = scene.display.outline_width * scene.display.content_scale;
+        let outline = radius + width / pixels_per_unit.max(1e-3);
+        let color = scene.display.theme.highlight;
+        let model = glm::scale(
+            &glm::translation(center),
+            &glm::vec3(outline, outline, outline),
+        );
+
+        self.highlight.clear();
+        self.highlight
+            .push(&model, &glm::vec4(color.x, color.y, color.z, 1.0));
+
+        self.flat_program.use_program();
+
+        gl::Enable(gl::CULL_FACE);
+        gl::CullFace(gl::FRONT);
+        self.highlight.draw();
+        gl::Disable(gl::CULL_FACE);
+        gl::CullFace(gl::BACK);
+    }
+
+    unsafe fn draw_glows(&mut self, scene: &Scene, view: &glm::Mat4, projection: &glm::Mat4) {
+        // translucent balls around nuclei: decay flashes, and a steady glow on ions
+        // tinted warm for positive, cool for negative, stronger with more charge
+        // they overlap each other (and two atoms' glows overlap), so they go through the
+        // sorted transparent pass, the atom stays visible through them
+        // off screen ones are not even sorted
+        let frustum = Frustum::new(&(projection * view));
+        let transparent = &mut self.transparent;
+        let mut push = |center: &glm::Vec3, radius: f32, color: glm::Vec4| {
+            if !frustum.contains_sphere(center, radius) {
+                return;
+            }
+            let model = glm::scale(
+                &glm::translation(center),
+                &glm::vec3(radius, radius, radius),
+            );
+            transparent.push(model, color, view);
+        };
+
+        for scene_atom in &scene.atoms {
+            let atom = &scene_atom.atom;
+            let charge = atom.charge();
+            if charge == 0 {
+                continue;
+            }
+            let rgb = if charge > 0 {
+                glm::vec3(1.0, 0.45, 0.2)
+            } else {
+                glm::vec3(0.3, 0.6, 1.0)
+            };
+            let opacity = (0.12 * charge.unsigned_abs() as f32).min(0.45);
+            let factor = scale::nucleus_factor(atom, scene.display.true_scale);
+            let radius = (nucleus::radius(atom.protons + atom.neutrons) * 1.6 + 0.3) * factor;
+            push(
+                &scene_atom.position,
+                radius,
+                glm::vec4(rgb.x, rgb.y, rgb.z, opacity),
+            );
+        }
+        for flash in &scene.flashes {
+            if let Some((radius, opacity)) = flash.glow() {
+                push(
+                    &flash.position,
+                    radius,
+                    glm::vec4(1.0, 0.95, 0.7, 0.6 * opacity),
+                );
+            }
+        }
+
+        self.flat_program.use_program();
+        self.transparent.flush();
+    }
+
+    pub unsafe fn draw_hud(&mut self, scene: &Scene, fps: f32, fb_width: u32, fb_height: u32) {
+        // text overlay on top of whatever render() drew
+        // one line per atom: isotope + shell occupancy, fps underneath
+        // laid out in hud pixels, so it comes out the same size on hidpi screens
+        self.profiler.begin(Pass::Hud);
+        let hud_scale = scene.display.hud_scale();
+        let (width, height) = (
+            hud_size(fb_width, hud_scale),
+            hud_size(fb_height, hud_scale),
+        );
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        let scale = 2.0;
+        let line_height = 10.0 * scale;
+        let mut y = 10.0;
+
+        for scene_atom in &scene.atoms {
+            let atom = &scene_atom.atom;
+            let shells: Vec<String> = atom
+                .shells
+                .iter()
+                .map(|shell| shell.electron_count().to_string())
+                .collect();
+            let isotope = atom.isotope();
+            let mut line = format!("{:<6} {}", isotope.name(), shells.join("-"));
+            if let Some(ion) = atom.ion_label() {
+                line = format!("{}  {}", line, ion);
+            }
+            self.text.draw_text(&line, 10.0, y, scale, &white);
+            y += line_height;
+
+            // with spins on: pairs and unpaired electrons per shell, in the same 2-8-.. order
+            if scene.display.spin {
+                let (pairs, unpaired): (Vec<String>, Vec<String>) = atom
+                    .shells
+                    .iter()
+                    .map(|shell| {
+                        let (up, down) = shell.spins();
+                        (up.min(down).to_string(), up.abs_diff(down).to_string())
+                    })
+                    .unzip();
+                let spin_line =
+                    format!("pairs {}  unpaired {}", pairs.join("-"), unpaired.join("-"));
+                self.text
+                    .draw_text(&spin_line, 10.0, y, scale, &SPIN_UP_COLOR);
+                y += line_height;
+            }
+
+            // radioactive nuclei get flagged right under their atom
+            if isotope.is_unstable() {
+                let orange = glm::vec4(1.0, 0.6, 0.2, 1.0);
+                self.text
+                    .draw_text(&isotope.describe(), 10.0, y, scale, &orange);
+                y += line_height;
+            }
+        }
+
+        let time_line = if scene.time.paused {
+            "PAUSED".to_string()
+        } else {
+            format!("speed x{:.1}", scene.time.scale)
+        };
+        self.text.draw_text(&time_line, 10.0, y, scale, &white);
+        y += line_height;
+
+        // newest photon still in flight, in its own color
+        if let Some(photon) = scene.photons.last() {
+            let c = photon.color;
+            let photon_line = format!("photon {}", absorption::wavelength_label(photon.wavelength));
+            self.text
+                .draw_text(&photon_line, 10.0, y, scale, &glm::vec4(c.x, c.y, c.z, 1.0));
+            y += line_height;
+        }
+
+        // what the most recent decay turned into what
+        if let Some(flash) = scene.flashes.last() {
+            let decay_line = format!("decay {}", flash.label);
+            self.text
+                .draw_text(&decay_line, 10.0, y, scale, &glm::vec4(1.0, 0.95, 0.7, 1.0));
+            y += line_height;
+        }
+
+        let fps_line = format!("FPS {:.0}", fps);
+        self.text
+            .draw_text(&fps_line, 10.0, y, scale, &glm::vec4(1.0, 1.0, 0.4, 1.0));
+        y += line_height;
+
+        // gpu milliseconds per pass, a few frames old, see profiler.rs
+        if scene.display.profiler {
+            let cyan = glm::vec4(0.5, 0.9, 1.0, 1.0);
+            let passes = PASSES.map(|pass| (pass.name(), self.profiler.milliseconds(pass)));
+            let total: f32 = passes.iter().map(|(_, ms)| ms).sum();
+            let total_line = format!("GPU {:.2} ms", total);
+            self.text.draw_text(&total_line, 10.0, y, scale, &cyan);
+            y += line_height;
+            for (name, ms) in passes {
+                let pass_line = format!("  {:<11} {:5.2}", name, ms);
+                self.text.draw_text(&pass_line, 10.0, y, scale, &cyan);
+                y += line_height;
+            }
+        }
+
+        // properties of the clicked particle, same yellow as its outline
+        if let Some(selected) = scene.selected {
+            y += line_height * 0.5;
+            for line in scene.describe(selected) {
+                self.text
+                    .draw_text(&line, 10.0, y, scale, &glm::vec4(1.0, 0.9, 0.3, 1.0));
+                y += line_height;
+            }
+        }
+
+        if scene.display.guides {
+            // its little viewport is in real pixels, so it gets the framebuffer size
+            self.gizmo.draw_axes(
+                &scene.camera,
+                &mut self.text,
+                fb_width,
+                fb_height,
+                hud_scale,
+            );
+        }
+
+        if scene.display.spectrum {
+            if let Some(main_atom) = scene.atoms.first() {
+                spectrum::draw(&mut self.text, main_atom.atom.element(), width, height);
+            }
+        }
+        if scene.display.levels {
+            if let Some(main_atom) = scene.atoms.first() {
+                levels::draw(&mut self.text, &main_atom.atom, width);
+            }
+        }
+        if scene.display.magnetic && scene.view_mode == ViewMode::Bohr {
+            magnetic::draw_labels(&mut self.text, scene, width, height);
+        }
+        if let (Some(scattering), Some(main_atom)) = (&scene.scattering, scene.atoms.first()) {
+            scattering::draw(&mut self.text, &main_atom.atom, scattering, width);
+        }
+        if let (Some(builder), Some(main_atom)) = (&scene.builder, scene.atoms.first()) {
+            aufbau::draw(&mut self.text, &main_atom.atom, builder, height);
+        }
+        if scene.display.true_scale > 0.0 && scene.view_mode == ViewMode::Bohr {
+            if let Some(main_atom) = scene.atoms.first() {
+                scale::draw_bar(
+                    &mut self.text,
+                    &scene.camera,
+                    &main_atom.atom,
+                    main_atom.position,
+                    scene.display.true_scale,
+                    width,
+                    height,
+                );
+            }
+        }
+        if let Some(comparison) = &scene.comparison {
+            let atoms = [&scene.atoms[0].atom, &scene.atoms[comparison.partner].atom];
+            compare::draw(&mut self.text, atoms, width);
+        }
+
+        // the density map and radial plot belong to the cloud they describe, the bohr view has none
+        let source = match scene.view_mode {
+            ViewMode::Orbital => Some(SliceSource::Real(scene.orbital)),
+            ViewMode::Cloud => Some(SliceSource::Complex(
+                *physics::N.lock().unwrap(),
+                *physics::L.lock().unwrap(),
+                *physics::M.lock().unwrap(),
+            )),
+            ViewMode::Bohr => None,
+        };
+        if let Some(source) = source {
+            let clip = &scene.display.clip;
+            if scene.display.density_slice {
+                self.slice.draw(source, clip, &mut self.text, width, height);
+            }
+            if scene.display.radial_plot {
+                let cut = clip.enabled.then_some(clip.offset);
+                radial::draw(&mut self.text, source.quantum_numbers(), cut, height);
+            }
+        }
+
+        self.text.flush(width, height);
+        self.profiler.end();
+    }
+
+    pub unsafe fn draw_periodic_table(
+        &mut self,
+        table: &PeriodicTable,
+        scene: &Scene,
+        fb_width: u32,
+        fb_height: u32,
+    ) {
+        // overlay on top of the hud, the current main atom gets highlighted
+        // hud pixels like draw_hud, PeriodicTable::handle_event picks in the same space
+        self.profiler.begin(Pass::Hud);
+        let hud_scale = scene.display.hud_scale();
+        let (width, height) = (
+            hud_size(fb_width, hud_scale),
+            hud_size(fb_height, hud_scale),
+        );
+        table.draw(
+            &mut self.text,
+            scene.atoms[0].atom.protons,
+            &scene.display.theme.highlight,
+            width,
+            height,
+        );
+        self.text.flush(width, height);
+        self.profiler.end();
+    }
+
+    pub unsafe fn draw_editor(
+        &mut self,
+        editor: &Editor,
+        scene: &Scene,
+        fb_width: u32,
+        fb_height: u32,
+    ) {
+        // palette, valence warnings and whatever is being dragged, in hud pixels
+        self.profiler.begin(Pass::Hud);
+        let hud_scale = scene.display.hud_scale();
+        let (width, height) = (
+            hud_size(fb_width, hud_scale),
+            hud_size(fb_height, hud_scale),
+        );
+        editor.draw(&mut self.text, scene, width, height);
+        self.text.flush(width, height);
+        self.profiler.end();
+    }
+
+    pub unsafe fn draw_console(
+        &mut self,
+        console: &Console,
+        scene: &Scene,
+        fb_width: u32,
+        fb_height: u32,
+    ) {
+        // on top of everything but the control panel, hud pixels like draw_hud
+        self.profiler.begin(Pass::Hud);
+        let hud_scale = scene.display.hud_scale();
+        let (width, height) = (
+            hud_size(fb_width, hud_scale),
+            hud_size(fb_height, hud_scale),
+        );
+        console.draw(&mut self.text, width, height);
+        self.text.flush(width, height);
+        self.profiler.end();
+    }
+
+    pub unsafe fn render_offscreen(
+        &mut self,
+        scene: &mut Scene,
+        width: u32,
+        height: u32,
+    ) -> RgbaImage {
+        // headless path: no visible window needed
+        // invisible windows may not own any pixels of their default framebuffer
+        // (pixel ownership test), so render into our own fbo instead
+
+        let mut target = Framebuffer::new(ColorFormat::Srgb8, true);
+        target.resize(width, height);
+        target.bind();
+
+        self.render(scene, width, height);
+        let image = capture::read_framebuffer(width, height);
+
+        // back to the default framebuffer, the fbo goes away with `target`
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        image
+    }
+}
+
+unsafe fn set_polygon_mode(mode: RenderMode) {
+    match mode {
+        RenderMode::Solid => gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL),
+        RenderMode::Wireframe => gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE),
+        RenderMode::Points => {
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::POINT);
+            // one pixel vertices are invisible on a hidpi screen
+            gl::PointSize(3.0);
+        }
+    }
+}
+
+// radius of one probability cloud sample sphere
+const CLOUD_PARTICLE_SIZE: f32 = 0.05;
+
+unsafe fn sphere_renderer(quality: Quality, sectors: i32, stacks: i32) -> InstancedRenderer {
+    // a unit sphere laid out for high quality, tessellated for this one
+    let (sectors, stacks) = quality.tessellation(sectors, stacks);
+    InstancedRenderer::new(VertexArray::from_mesh(&generate_sphere(
+        1.0, sectors, stacks,
+    )))
+}
+
+unsafe fn cloud_spheres(scene: &Scene, quality: Quality) -> InstancedRenderer {
+    // the cloud never moves, so its instances are filled exactly once per mesh
+    let mut cloud_renderer = sphere_renderer(quality, 10, 10);
+    for particle in &scene.cloud_particles {
+        let mut model = glm::identity();
+        let pos_f32 = glm::vec3(
+            particle.position.x as f32,
+            particle.position.y as f32,
+            particle.position.z as f32,
+        );
+        model = glm::translate(&model, &pos_f32);
+        model = glm::scale(
+            &model,
+            &glm::vec3(
+                CLOUD_PARTICLE_SIZE,
+                CLOUD_PARTICLE_SIZE,
+                CLOUD_PARTICLE_SIZE,
+            ),
+        );
+        cloud_renderer.push(&model, &particle.color);
+    }
+    cloud_renderer
+}
+
+// how much brighter than their color electrons and photons are drawn when bloom is on
+// lit spheres top out around 1.0, so this puts them well past the default threshold
+const EMISSIVE_BOOST: f32 = 3.0;
+
+fn emissive_boost(scene: &Scene) -> f32 {
+    if scene.display.bloom {
+        EMISSIVE_BOOST
+    } else {
+        1.0
+    }
+}
+
+pub unsafe fn framebuffer_samples() -> i32 {
+    // msaa sample count of the currently bound draw framebuffer, 0 = not multisampled
+    let mut samples = 0;
+    gl::GetIntegerv(gl::SAMPLES, &mut samples);
+    samples
+}
+
+// spin arrows: warm for up, cool for down
+const SPIN_UP_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 0.75, 0.25, 1.0);
+const SPIN_DOWN_COLOR: glm::Vec4 = glm::Vec4::new(0.35, 0.8, 1.0, 1.0);
+const SPIN_ARROW_LENGTH: f32 = 4.0; // in electron radii, so the head clears the sphere
+
+fn spin_color(up: bool) -> &'static glm::Vec4 {
+    if up {
+        &SPIN_UP_COLOR
+    } else {
+        &SPIN_DOWN_COLOR
+    }
+}
+
+fn spin_model(world: &glm::Mat4, radius: f32, up: bool) -> glm::Mat4 {
+    // arrow through the electron's center along world y, whatever its shell is tilted to
+    // (one quantization axis for the whole scene, like a lab magnetic field)
+    let center = (world * glm::vec4(0.0, 0.0, 0.0, 1.0)).xyz();
+    let length = radius * SPIN_ARROW_LENGTH;
+    let flip = if up {
+        glm::identity()
+    } else {
+        glm::rotation(PI, &glm::vec3(1.0, 0.0, 0.0))
+    };
+    glm::translation(&center)
+        * flip
+        * glm::scaling(&glm::vec3(length, length, length))
+        * glm::translation(&glm::vec3(0.0, -0.5, 0.0))
+}
+
+// stick thickness and spacing between parallel sticks of a multiple bond
+const BOND_RADIUS: f32 = 0.15;
+const BOND_SPACING: f32 = 0.4;
+
+fn focus_distance(scene: &Scene) -> f32 {
+    // how far in front of the camera depth of field is sharp: the selected atom, or the
+    // camera target
+    let point = match scene.selected {
+        Some(_) => scene.atoms[scene.target_atom()].position,
+        None => scene.camera.target,
+    };
+    let view = scene.camera.get_view_matrix() * glm::vec4(point.x, point.y, point.z, 1.0);
+    (-view.z).max(1e-3)
+}
+
+pub fn bond_models(a: &glm::Vec3, b: &glm::Vec3, order: BondOrder) -> Vec<glm::Mat4> {
+    // one unit cylinder transform per stick, shared with the scene export
+    stick_models(a, b, order, BOND_RADIUS, BOND_SPACING)
+}
+
+pub fn stick_models(
+    a: &glm::Vec3,
+    b: &glm::Vec3,
+    order: BondOrder,
+    radius: f32,
+    spacing: f32,
+) -> Vec<glm::Mat4> {
+    // the same at any thickness, for the molecule styles (see style.rs)
+    // unit cylinder points along +y, so rotate y onto the bond direction
+    let delta = b - a;
+    let length = glm::length(&delta);
+    if length < 1e-4 {
+        return Vec::new();
+    }
+    let dir = delta / length;
+
+    let up = glm::vec3(0.0, 1.0, 0.0);
+    let axis = glm::cross(&up, &dir);
+    let rotation = if glm::length(&axis) < 1e-4 {
+        // already along y (or exactly opposite)
+        if dir.y > 0.0 {
+            glm::identity()
+        } else {
+            glm::rotation(std::f32::consts::PI, &glm::vec3(1.0, 0.0, 0.0))
+        }
+    } else {
+        glm::rotation(
+            glm::dot(&up, &dir).clamp(-1.0, 1.0).acos(),
+            &glm::normalize(&axis),
+        )
+    };
+
+    // sideways direction for double/triple bonds
+    let side = {
+        let s = glm::cross(&dir, &glm::vec3(0.0, 0.0, 1.0));
+        if glm::length(&s) < 1e-4 {
+            glm::vec3(1.0, 0.0, 0.0)
+        } else {
+            glm::normalize(&s)
+        }
+    };
+
+    let count = order.count();
+    let mut models = Vec::new();
+    for i in 0..count {
+        // centered offsets: 0 | -0.5, 0.5 | -1, 0, 1 (times spacing)
+        let offset = (i as f32 - (count - 1) as f32 / 2.0) * spacing;
+        let start = a + side * offset;
+
+        let mut model = glm::translation(&start);
+        model *= rotation;
+        model = glm::scale(&model, &glm::vec3(radius, length, radius));
+        models.push(model);
+    }
+    models
+}
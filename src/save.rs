@@ -0,0 +1,403 @@
+use crate::camera::{CameraPose, BOOKMARKS};
+use crate::elements;
+use crate::import; // the same atom limit as opened molecules
+use crate::isotopes;
+use crate::orbital::Orbital;
+use crate::scene::{Bond, BondOrder, Scene, ViewMode, MAX_SHELLS};
+use crate::simulation::{ring_direction, Atom, Electron, Jump};
+use crate::thermal; // temperature range
+use nalgebra_glm as glm;
+use serde::{Deserialize, Serialize}; // save file format
+use std::fmt; // pretty errors
+use std::fs;
+
+// the whole arrangement as a json file, to come back to later or hand to someone else
+// (Ctrl+S writes scene.json, Ctrl+O reads it back)
+// what is kept: every atom (isotope, position, rotation, each electron's phase, spin and
+// excitation, electrons between shells), bonds, the camera pose and bookmarks, time scale /
+// pause, the view, whether electron repulsion and nucleon dynamics are on and the
+// temperature (not where it has shaken things to, that is noise, nor how the nucleons were
+// moving, they start over)
+// what is not: photons, decay ejecta and sparks, they are gone in a second anyway,
+// and the orbital cloud points, only which orbital it shows (sampled again on load)
+//
+// shell radius, speed and tilt are not stored, they follow from n like on a fresh atom
+
+pub const SAVE_FILE: &str = "scene.json";
+
+// bumped when the format changes in a way old files cannot be read as
+const VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io { path: String, message: String },
+    Parse { path: String, message: String },
+    Invalid { path: String, message: String }, // parsed, but describes an impossible scene
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io { path, message } => {
+                write!(f, "could not access save file '{}': {}", path, message)
+            }
+            SaveError::Parse { path, message } => {
+                write!(f, "could not parse save file '{}': {}", path, message)
+            }
+            SaveError::Invalid { path, message } => {
+                write!(f, "invalid save file '{}': {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    atoms: Vec<SavedAtom>,
+    bonds: Vec<SavedBond>,
+    camera: SavedCamera,
+    time_scale: f32,
+    paused: bool,
+    view_mode: ViewMode,
+    #[serde(default)]
+    repulsion: bool,
+    #[serde(default)]
+    vibration: bool,
+    #[serde(default)]
+    temperature: f32, // older files are still
+    #[serde(default)]
+    orbital: Option<Orbital>, // older files keep whatever is on screen
+    #[serde(default)]
+    bookmarks: Vec<Option<SavedPose>>, // Ctrl+1..9, null for an empty slot
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedAtom {
+    element: String, // symbol, so the file stays readable
+    mass_number: u32,
+    position: [f32; 3],
+    #[serde(default)]
+    orientation: Option<[f32; 4]>, // turned by hand, x, y, z, w, older files do not have it
+    shells: Vec<Vec<SavedElectron>>, // [shell][electron], innermost first
+    jumps: Vec<SavedJump>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedElectron {
+    phase: f64,
+    #[serde(default)]
+    direction: Option<[f64; 3]>, // off the ring with repulsion on, older files do not have it
+    decay_to: Option<usize>,
+    lifetime: f64,
+    #[serde(default)]
+    spin_up: Option<bool>, // older files get the ground state spins of their shell
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedJump {
+    from: usize,
+    to: usize,
+    phase: f64,
+    progress: f64,
+    decay_to: Option<usize>,
+    #[serde(default)]
+    spin_up: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedBond {
+    a: usize,
+    b: usize,
+    order: BondOrder,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedCamera {
+    #[serde(flatten)]
+    pose: SavedPose,
+    orthographic: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedPose {
+    target: [f32; 3],
+    radius: f32,
+    // written either way, so an orbit camera can still load a trackball save
+    azimuth: f32,
+    elevation: f32,
+    #[serde(default)]
+    orientation: Option<[f32; 4]>, // trackball quaternion x, y, z, w, only in trackball mode
+}
+
+impl SavedPose {
+    fn from_pose(pose: &CameraPose) -> Self {
+        Self {
+            target: pose.target.into(),
+            radius: pose.radius,
+            azimuth: pose.azimuth,
+            elevation: pose.elevation,
+            orientation: pose.orientation.map(|q| q.coords.into()),
+        }
+    }
+
+    fn pose(&self) -> CameraPose {
+        CameraPose {
+            target: self.target.into(),
+            radius: self.radius.max(1.0),
+            azimuth: self.azimuth,
+            elevation: self.elevation,
+            // a zero quaternion has no rotation in it, fall back to the angles
+            orientation: self
+                .orientation
+                .map(|q| glm::quat(q[0], q[1], q[2], q[3]))
+                .filter(|q| glm::quat_length(q) > 1e-4),
+        }
+    }
+}
+
+pub fn save(scene: &Scene, path: &str) -> Result<(), SaveError> {
+    let atoms = scene
+        .atoms
+        .iter()
+        .map(|scene_atom| {
+            let atom = &scene_atom.atom;
+            SavedAtom {
+                element: atom.element().symbol.to_string(),
+                mass_number: atom.protons + atom.neutrons,
+                position: scene_atom.position.into(),
+                orientation: Some(scene_atom.orientation.coords.into()),
+                shells: atom
+                    .shells
+                    .iter()
+                    .map(|shell| {
+                        shell
+                            .electrons
+                            .iter()
+                            .map(|electron| SavedElectron {
+                                phase: electron.phase,
+                                direction: Some(electron.direction.into()),
+                                decay_to: electron.decay_to,
+                                lifetime: electron.lifetime,
+                                spin_up: Some(electron.spin_up),
+                            })
+                            .collect()
+                    })
+                    .collect(),
+                jumps: atom
+                    .jumps
+                    .iter()
+                    .map(|jump| SavedJump {
+                        from: jump.from,
+                        to: jump.to,
+                        phase: jump.phase,
+                        progress: jump.progress,
+                        decay_to: jump.decay_to,
+                        spin_up: Some(jump.spin_up),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    let camera = &scene.camera;
+    let file = SaveFile {
+        version: VERSION,
+        atoms,
+        bonds: scene
+            .bonds
+            .iter()
+            .map(|bond| SavedBond {
+                a: bond.a,
+                b: bond.b,
+                order: bond.order,
+            })
+            .collect(),
+        camera: SavedCamera {
+            pose: SavedPose::from_pose(&camera.pose()),
+            orthographic: camera.projection.orthographic,
+        },
+        time_scale: scene.time.scale,
+        paused: scene.time.paused,
+        view_mode: scene.view_mode,
+        repulsion: scene.repulsion,
+        vibration: scene.vibration,
+        temperature: scene.temperature,
+        orbital: Some(scene.orbital),
+        bookmarks: camera
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.as_ref().map(SavedPose::from_pose))
+            .collect(),
+    };
+
+    let json = serde_json::to_string_pretty(&file).expect("save file is always serializable");
+    fs::write(path, json).map_err(|err| SaveError::Io {
+        path: path.to_string(),
+        message: err.to_string(),
+    })?;
+    log::info!("saved {} atoms to {}", scene.atoms.len(), path);
+    Ok(())
+}
+
+pub fn load(scene: &mut Scene, path: &str) -> Result<(), SaveError> {
+    // all or nothing: the scene is only touched once the whole file checks out
+    let source = fs::read_to_string(path).map_err(|err| SaveError::Io {
+        path: path.to_string(),
+        message: err.to_string(),
+    })?;
+    let file: SaveFile = serde_json::from_str(&source).map_err(|err| SaveError::Parse {
+        path: path.to_string(),
+        message: err.to_string(),
+    })?;
+    let invalid = |message: String| SaveError::Invalid {
+        path: path.to_string(),
+        message,
+    };
+    if file.version != VERSION {
+        return Err(invalid(format!(
+            "version {} is not supported (expected {})",
+            file.version, VERSION
+        )));
+    }
+
+    if let Some(orbital) = file.orbital.filter(|orbital| !orbital.is_valid()) {
+        return Err(invalid(format!(
+            "orbital n={}, l={}, m={} is not a valid combination",
+            orbital.n, orbital.l, orbital.m
+        )));
+    }
+
+    // the main atom is what half the app hangs off, and every atom gets simulated
+    if file.atoms.is_empty() {
+        return Err(invalid("no atoms in it".to_string()));
+    }
+    if file.atoms.len() > import::MAX_ATOMS {
+        return Err(invalid(format!(
+            "{} atoms, at most {} can be simulated",
+            file.atoms.len(),
+            import::MAX_ATOMS
+        )));
+    }
+
+    let mut atoms = Vec::with_capacity(file.atoms.len());
+    for (i, saved) in file.atoms.iter().enumerate() {
+        let atom = restore_atom(saved)
+            .map_err(|message| invalid(format!("atom {}: {}", i + 1, message)))?;
+        // a zero quaternion has no rotation in it, that atom comes back unturned
+        let orientation = saved
+            .orientation
+            .map(|q| glm::quat(q[0], q[1], q[2], q[3]))
+            .filter(|q| glm::quat_length(q) > 1e-4)
+            .map_or_else(glm::quat_identity, |q| glm::quat_normalize(&q));
+        atoms.push((atom, glm::Vec3::from(saved.position), orientation));
+    }
+    let mut bonds = Vec::with_capacity(file.bonds.len());
+    for bond in &file.bonds {
+        if bond.a >= atoms.len() || bond.b >= atoms.len() || bond.a == bond.b {
+            return Err(invalid(format!(
+                "bond between invalid atoms {} and {}",
+                bond.a, bond.b
+            )));
+        }
+        bonds.push(Bond {
+            a: bond.a,
+            b: bond.b,
+            order: bond.order,
+        });
+    }
+
+    if file.bookmarks.len() > BOOKMARKS {
+        return Err(invalid(format!(
+            "{} camera bookmarks, there are only {} slots",
+            file.bookmarks.len(),
+            BOOKMARKS
+        )));
+    }
+
+    scene.restore(atoms, bonds);
+    scene.camera.jump_to(&file.camera.pose.pose());
+    scene.camera.projection.orthographic = file.camera.orthographic;
+    // older files have none, which empties the slots like a fresh start would
+    scene.camera.bookmarks = [None; BOOKMARKS];
+    for (slot, saved) in scene.camera.bookmarks.iter_mut().zip(&file.bookmarks) {
+        *slot = saved.as_ref().map(SavedPose::pose);
+    }
+    scene.time.set_scale(file.time_scale);
+    scene.time.paused = file.paused;
+    scene.view_mode = file.view_mode;
+    scene.repulsion = file.repulsion;
+    scene.vibration = file.vibration;
+    scene.temperature = file.temperature.clamp(0.0, thermal::MAX_TEMPERATURE);
+    if let Some(orbital) = file.orbital {
+        scene.orbital = orbital;
+    }
+    log::info!("loaded {} atoms from {}", scene.atoms.len(), path);
+    Ok(())
+}
+
+fn restore_atom(saved: &SavedAtom) -> Result<Atom, String> {
+    // nucleus from the isotope table, electrons exactly as they were
+    let element = elements::by_symbol(&saved.element)
+        .ok_or_else(|| format!("unknown element '{}'", saved.element))?;
+    let isotope = isotopes::isotope(element.atomic_number, saved.mass_number).ok_or_else(|| {
+        format!(
+            "{}-{} is not a known isotope",
+            element.symbol, saved.mass_number
+        )
+    })?;
+
+    let shell_count = saved.shells.len();
+    if shell_count > MAX_SHELLS {
+        return Err(format!(
+            "{} shells, at most {} are supported",
+            shell_count, MAX_SHELLS
+        ));
+    }
+    let in_range = |shell: Option<usize>| shell.is_none_or(|shell| shell < shell_count);
+
+    let occupancy: Vec<u32> = saved.shells.iter().map(|s| s.len() as u32).collect();
+    let mut atom = Atom::new(element.atomic_number, isotope.neutrons(), &occupancy);
+    for (shell, electrons) in atom.shells.iter_mut().zip(&saved.shells) {
+        // Atom::new has handed out ground state spins already, those stand in for missing ones
+        let ground: Vec<bool> = shell.electrons.iter().map(|e| e.spin_up).collect();
+        shell.electrons = electrons
+            .iter()
+            .zip(ground)
+            .map(|(e, ground)| Electron {
+                phase: e.phase,
+                // a zero vector would turn into NaN positions, put those back on the ring
+                direction: e
+                    .direction
+                    .map(glm::DVec3::from)
+                    .filter(|d| glm::length(d) > 1e-4)
+                    .map_or(ring_direction(e.phase), |d| glm::normalize(&d)),
+                decay_to: e.decay_to,
+                lifetime: e.lifetime,
+                spin_up: e.spin_up.unwrap_or(ground),
+                jitter: glm::vec3(0.0, 0.0, 0.0),
+            })
+            .collect();
+        if !shell.electrons.iter().all(|e| in_range(e.decay_to)) {
+            return Err(format!(
+                "electron on shell {} decays to a missing shell",
+                shell.n
+            ));
+        }
+    }
+    for jump in &saved.jumps {
+        if jump.from >= shell_count || jump.to >= shell_count || !in_range(jump.decay_to) {
+            return Err("electron jumping between missing shells".to_string());
+        }
+        atom.jumps.push(Jump {
+            from: jump.from,
+            to: jump.to,
+            phase: jump.phase,
+            progress: jump.progress.clamp(0.0, 1.0),
+            decay_to: jump.decay_to,
+            spin_up: jump.spin_up.unwrap_or(true),
+        });
+    }
+    Ok(atom)
+}
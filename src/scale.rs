@@ -0,0 +1,130 @@
+use crate::camera::Camera; // pixels per world unit at the main atom
+use crate::elements::Element; // covalent radius
+use crate::lod; // projected size
+use crate::nucleus; // the drawn nucleus
+use crate::simulation::{Atom, Shell};
+use crate::text::TextRenderer; // the bar is drawn with the hud's rectangles
+use nalgebra_glm as glm;
+
+// true scale (Shift+T): the nucleus at its real size against the atom
+// the bohr view draws it about a tenth of the outer shell radius so the nucleons can be seen;
+// really it is 1e-5 to 1e-4 of it, a pea in a stadium, and too small for a single pixel
+// the amount (DisplaySettings::true_scale, a slider in the control panel) goes from the
+// didactic picture at 0 to the real proportion at 1 on a log scale, anything in between
+// compresses the 10000x down to something that can still be seen
+// only the nucleus shrinks: the shells stay where they are, spaced n apart for looking at,
+// and the outermost one stands for the element's covalent radius; a scale bar in the
+// corner tells how long that makes things
+
+const NUCLEON_RADIUS_FM: f32 = 1.2; // r = 1.2 fm A^(1/3)
+const FALLBACK_RADIUS_PM: f32 = 150.0; // past curium, about what its neighbours measure
+const FM_PER_PM: f32 = 1000.0;
+
+// bar layout in pixels, bottom right above the spectrum bar
+const MARGIN: f32 = 10.0;
+const SPECTRUM_SPACE: f32 = 76.0;
+const BAR_TARGET: f32 = 160.0; // longest the bar gets, it is rounded down to 1, 2 or 5
+const PANEL_WIDTH: f32 = 240.0;
+const PANEL_HEIGHT: f32 = 46.0;
+
+pub fn atom_radius_pm(element: &Element) -> f32 {
+    element
+        .covalent_radius()
+        .map_or(FALLBACK_RADIUS_PM, |radius| radius as f32)
+}
+
+pub fn nucleus_radius_fm(atom: &Atom) -> f32 {
+    NUCLEON_RADIUS_FM * ((atom.protons + atom.neutrons) as f32).cbrt()
+}
+
+fn drawn_outer_radius(atom: &Atom) -> f32 {
+    // the neutral atom's outermost shell, so ions and half built atoms keep their nucleus
+    Shell::new(atom.element().shells.len() as u32, 0).radius as f32
+}
+
+pub fn nucleus_factor(atom: &Atom, amount: f32) -> f32 {
+    // what the drawn nucleus is scaled by, 1 = as packed
+    if amount <= 0.0 {
+        return 1.0;
+    }
+    let drawn = nucleus::radius(atom.protons + atom.neutrons) / drawn_outer_radius(atom);
+    let real = nucleus_radius_fm(atom) / (atom_radius_pm(atom.element()) * FM_PER_PM);
+    (real / drawn).min(1.0).powf(amount.min(1.0))
+}
+
+pub fn draw_bar(
+    text: &mut TextRenderer,
+    camera: &Camera,
+    atom: &Atom,
+    position: glm::Vec3,
+    amount: f32,
+    width: u32,
+    height: u32,
+) {
+    // queued into the text batch, the caller flushes
+    // width and height in hud pixels, the bar is measured at the main atom's distance
+    let pm_per_unit = atom_radius_pm(atom.element()) / drawn_outer_radius(atom);
+    let pixels_per_unit = lod::projected_radius(
+        &glm::translation(&position),
+        &camera.get_view_matrix(),
+        &camera.get_projection_matrix(),
+        height,
+    );
+    let most = BAR_TARGET / pixels_per_unit * pm_per_unit;
+    if !most.is_finite() || most <= 0.0 {
+        return;
+    }
+    // 1, 2 or 5 times a power of ten
+    let decade = 10f32.powf(most.log10().floor());
+    let length = [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|step| step * decade)
+        .find(|&length| length <= most)
+        .unwrap_or(decade);
+    let bar = length / pm_per_unit * pixels_per_unit;
+
+    let x0 = width as f32 - MARGIN - PANEL_WIDTH;
+    let y0 = height as f32 - SPECTRUM_SPACE - PANEL_HEIGHT;
+    let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+    let grey = glm::vec4(0.8, 0.8, 0.8, 1.0);
+    text.draw_rect(
+        x0,
+        y0,
+        PANEL_WIDTH,
+        PANEL_HEIGHT,
+        &glm::vec4(0.0, 0.0, 0.0, 0.85),
+    );
+    let (bar_x, bar_y) = (x0 + 8.0, y0 + 8.0);
+    text.draw_rect(bar_x, bar_y, bar, 2.0, &white);
+    text.draw_rect(bar_x, bar_y - 3.0, 1.0, 8.0, &white);
+    text.draw_rect(bar_x + bar - 1.0, bar_y - 3.0, 1.0, 8.0, &white);
+    text.draw_text(&length_label(length), bar_x, bar_y + 8.0, 1.0, &white);
+
+    // how far from the truth the nucleus still is
+    let drawn = nucleus::radius(atom.protons + atom.neutrons)
+        * nucleus_factor(atom, amount)
+        * pm_per_unit
+        * FM_PER_PM;
+    let real = nucleus_radius_fm(atom);
+    let oversize = drawn / real;
+    let nucleus_line = if oversize < 1.5 {
+        format!("nucleus {:.1} fm, to scale", real)
+    } else {
+        format!("nucleus {:.1} fm, drawn {:.0}x too big", real, oversize)
+    };
+    text.draw_text(&nucleus_line, x0 + 8.0, y0 + 32.0, 1.0, &grey);
+}
+
+fn length_label(pm: f32) -> String {
+    // pm for atoms, nm once a few of them fit under the bar
+    let (value, unit) = if pm >= 1000.0 {
+        (pm / 1000.0, "nm")
+    } else if pm >= 1.0 {
+        (pm, "pm")
+    } else {
+        (pm * FM_PER_PM, "fm")
+    };
+    // as many decimals as a 0.2 or 0.05 needs, and none of the float noise after them
+    let decimals = (-value.log10().floor()).max(0.0) as usize;
+    format!("{:.*} {}", decimals, value, unit)
+}
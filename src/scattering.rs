@@ -0,0 +1,356 @@
+use crate::decay::ALPHA_TRAIL_COLOR; // same alpha particles as a decay sends out
+use crate::nucleus; // the drawn nucleus sets the femtometre scale
+use crate::render::{srgb_to_linear, ShaderError, ShaderProgram, WatchedProgram};
+use crate::scale; // real nucleus radius, and the true scale shrinking
+use crate::simulation::Atom;
+use crate::stats;
+use crate::text::TextRenderer; // the readout is drawn with the hud's rectangles
+use nalgebra_glm as glm;
+use std::mem; // buffer sizes
+use std::os::raw::c_void; // raw pointer casting
+
+// scattering mode (Shift+A): alpha particles fired at the main atom's nucleus, geiger and
+// marsden's experiment one shot at a time (Enter fires)
+// the beam runs left to right across the screen and the impact parameter b (Up / Down) moves
+// it up; the energy (Shift+Up / Down) decides how hard the nucleus can push back
+// shots come from and go to far outside the picture, coulomb's force is felt a long way off,
+// so the angle in the readout is the one they really leave at and matches rutherford's
+// only the nucleus's coulomb repulsion acts, rutherford's own assumption: the electrons are
+// thousands of times lighter than the alpha particle and hardly deflect it
+// lengths are femtometres by the drawn nucleus, whose real radius is 1.2 fm A^(1/3); the
+// flight itself is slowed down to a steady speed, only the shape of the path is real
+// a shot that gets close enough to touch the nucleus stops there, the strong force takes
+// over at that distance and this knows nothing about it
+
+pub const MIN_IMPACT: f32 = 0.0; // fm
+pub const MAX_IMPACT: f32 = 200.0;
+pub const IMPACT_STEP: f32 = 5.0;
+pub const MIN_ENERGY: f32 = 0.5; // MeV
+pub const MAX_ENERGY: f32 = 50.0;
+pub const ENERGY_STEP: f32 = 0.5;
+
+const COULOMB_MEV_FM: f32 = 1.44; // e^2 / (4 pi epsilon0)
+const ALPHA_CHARGE: f32 = 2.0;
+const SPEED: f32 = 8.0; // world units per simulated second, slow enough to follow
+const APPROACH: f32 = 10.0; // how far past the outermost shell a shot starts
+const MAX_SHOTS: usize = 40; // trails kept, the oldest goes first
+const TRAIL_SPACING: f32 = 0.1; // world units between recorded points
+const SUBSTEP: f32 = 0.01; // of the distance to the nucleus per integration step
+const FAR: f32 = 100.0; // times the picture's size, where a shot really starts and ends
+
+// readout layout in pixels, top center like the comparison table
+const PANEL_WIDTH: f32 = 330.0;
+const PANEL_HEIGHT: f32 = 58.0;
+const TOP: f32 = 10.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Outcome {
+    Flying,
+    Scattered(f32), // degrees between the way in and the way out
+    Stopped,        // touched the nucleus
+}
+
+struct Shot {
+    position: glm::Vec3,
+    previous: glm::Vec3, // one simulation step ago
+    velocity: glm::Vec3,
+    direction: glm::Vec3, // the way it was fired
+    impact: f32,          // fm, as fired
+    energy: f32,          // MeV, as fired
+    age: f32,
+    trail: Vec<glm::Vec3>,
+    outcome: Outcome,
+}
+
+pub struct Scattering {
+    pub impact: f32, // fm, for the next shot
+    pub energy: f32, // MeV
+    shots: Vec<Shot>,
+}
+
+impl Scattering {
+    pub fn new() -> Self {
+        // the polonium of the original experiment sent them out at about 5 MeV
+        Self {
+            impact: 20.0,
+            energy: 5.0,
+            shots: Vec::new(),
+        }
+    }
+
+    pub fn fire(&mut self, atom: &Atom, center: glm::Vec3, right: &glm::Vec3, up: &glm::Vec3) {
+        // right and up: the screen's directions in the world, from the camera
+        // coulomb's force reaches a long way, a shot fired straight from the edge of the
+        // picture would already be bent there without having been bent before; so it starts
+        // FAR times further out, really aimed with b, and coasts in unseen to the edge
+        let start = atom.outer_radius() + APPROACH;
+        let k = coupling(atom, self.energy);
+        let mut position = center - right * (start * FAR) + up * (self.impact * units_per_fm(atom));
+        let mut velocity = right * SPEED;
+        // a wide shot never gets that close, it stops coasting where it is closest
+        while glm::distance(&position, &center) > start
+            && glm::dot(&(position - center), &velocity) < 0.0
+        {
+            advance(&mut position, &mut velocity, center, k, f32::MAX);
+        }
+        if self.shots.len() >= MAX_SHOTS {
+            self.shots.remove(0);
+        }
+        self.shots.push(Shot {
+            position,
+            previous: position,
+            velocity,
+            direction: *right,
+            impact: self.impact,
+            energy: self.energy,
+            age: 0.0,
+            trail: vec![position],
+            outcome: Outcome::Flying,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.shots.clear();
+    }
+
+    pub fn step(
+        &mut self,
+        atom: &Atom,
+        center: glm::Vec3,
+        true_scale: f32,
+        dt: f32,
+    ) -> Vec<glm::Vec3> {
+        // one simulation step of every shot still flying, where any touched the nucleus
+        let contact = nucleus::radius(atom.protons + atom.neutrons)
+            * scale::nucleus_factor(atom, true_scale)
+            + nucleus::NUCLEON_RADIUS;
+        let leave = atom.outer_radius() + APPROACH + 5.0;
+        let mut stopped = Vec::new();
+        for shot in &mut self.shots {
+            shot.previous = shot.position;
+            if shot.outcome != Outcome::Flying {
+                continue;
+            }
+            shot.age += dt;
+            let k = coupling(atom, shot.energy);
+            let mut left = dt;
+            while left > 0.0 {
+                left -= advance(&mut shot.position, &mut shot.velocity, center, k, left);
+                if glm::distance(&shot.position, &center) < contact {
+                    shot.outcome = Outcome::Stopped;
+                    stopped.push(shot.position);
+                    break;
+                }
+            }
+            if shot
+                .trail
+                .last()
+                .is_none_or(|last| glm::distance(last, &shot.position) >= TRAIL_SPACING)
+            {
+                shot.trail.push(shot.position);
+            }
+            let away = glm::dot(&(shot.position - center), &shot.velocity) > 0.0;
+            if shot.outcome == Outcome::Flying
+                && away
+                && glm::distance(&shot.position, &center) > leave
+            {
+                // off the picture; coasted out of reach the way it came in, on a copy, for
+                // the angle it ends up at
+                let (mut position, mut velocity) = (shot.position, shot.velocity);
+                while glm::distance(&position, &center) < leave * FAR {
+                    advance(&mut position, &mut velocity, center, k, f32::MAX);
+                }
+                let cos = glm::dot(&shot.direction, &glm::normalize(&velocity));
+                shot.outcome = Outcome::Scattered(cos.clamp(-1.0, 1.0).acos().to_degrees());
+            }
+        }
+        stopped
+    }
+
+    pub fn flying(&self, alpha: f32) -> Vec<(glm::Vec3, f32)> {
+        // world position and age of every shot still on its way, blended like everything else
+        self.shots
+            .iter()
+            .filter(|shot| shot.outcome == Outcome::Flying)
+            .map(|shot| (glm::lerp(&shot.previous, &shot.position, alpha), shot.age))
+            .collect()
+    }
+}
+
+fn coupling(atom: &Atom, energy: f32) -> f32 {
+    // k of the k / r^2 outward acceleration, set so a head-on shot turns around at the
+    // closest approach: the kinetic energy at SPEED equals k / d there
+    closest_approach(atom, energy) * units_per_fm(atom) * SPEED * SPEED / 2.0
+}
+
+fn advance(
+    position: &mut glm::Vec3,
+    velocity: &mut glm::Vec3,
+    center: glm::Vec3,
+    k: f32,
+    most: f32,
+) -> f32 {
+    // one velocity verlet step, shorter the closer to the nucleus and never longer than
+    // most; returns the time it took
+    let acceleration = |p: &glm::Vec3| {
+        let r = p - center;
+        let d = glm::length(&r).max(1e-4);
+        r * (k / (d * d * d))
+    };
+    let distance = glm::distance(position, &center);
+    let h = (SUBSTEP * distance / glm::length(velocity).max(1e-4)).clamp(1e-5, most);
+    let a = acceleration(position);
+    *position += *velocity * h + a * (0.5 * h * h);
+    *velocity += (a + acceleration(position)) * (0.5 * h);
+    h
+}
+
+fn units_per_fm(atom: &Atom) -> f32 {
+    nucleus::radius(atom.protons + atom.neutrons) / scale::nucleus_radius_fm(atom)
+}
+
+pub fn closest_approach(atom: &Atom, energy: f32) -> f32 {
+    // fm, head on, where all of the kinetic energy has gone into the coulomb potential
+    COULOMB_MEV_FM * ALPHA_CHARGE * atom.protons as f32 / energy
+}
+
+pub fn rutherford_angle(atom: &Atom, impact: f32, energy: f32) -> f32 {
+    // degrees, tan(theta / 2) = d / 2b
+    2.0 * (closest_approach(atom, energy) / (2.0 * impact))
+        .atan()
+        .to_degrees()
+}
+
+pub struct ScatteringRenderer {
+    program: WatchedProgram, // single color lines, the gizmo shader
+    vao: gl::types::GLuint,  // every trail one after the other, refilled every frame
+    vbo: gl::types::GLuint,
+}
+
+impl ScatteringRenderer {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut vao = 0;
+        let mut vbo = 0;
+        gl::GenVertexArrays(1, &mut vao);
+        gl::GenBuffers(1, &mut vbo);
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        gl::VertexAttribPointer(
+            0,
+            3,
+            gl::FLOAT,
+            gl::FALSE,
+            (3 * mem::size_of::<f32>()) as i32,
+            std::ptr::null(),
+        );
+        gl::EnableVertexAttribArray(0);
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl::BindVertexArray(0);
+        Ok(Self {
+            program: WatchedProgram::new("gizmo", ShaderProgram::gizmo)?,
+            vao,
+            vbo,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn draw(&self, scattering: &Scattering, view: &glm::Mat4, projection: &glm::Mat4) {
+        // one strip per shot, the older ones fainter; blended without depth writes
+        let shots = &scattering.shots;
+        let mut vertices: Vec<f32> = Vec::new();
+        let mut strips = Vec::with_capacity(shots.len());
+        for shot in shots {
+            strips.push(((vertices.len() / 3) as i32, shot.trail.len() as i32 + 1));
+            for p in shot.trail.iter().chain([&shot.position]) {
+                vertices.extend_from_slice(&[p.x, p.y, p.z]);
+            }
+        }
+        if vertices.is_empty() {
+            return;
+        }
+        gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+        gl::BufferData(
+            gl::ARRAY_BUFFER,
+            mem::size_of_val(vertices.as_slice()) as isize,
+            vertices.as_ptr() as *const c_void,
+            gl::STREAM_DRAW,
+        );
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+        self.program.use_program();
+        self.program.set_uniform_mat4("model", &glm::identity());
+        self.program.set_uniform_mat4("view", view);
+        self.program.set_uniform_mat4("projection", projection);
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::DepthMask(gl::FALSE);
+        gl::BindVertexArray(self.vao);
+        // drawn inside the linear scene
+        let c = srgb_to_linear(&ALPHA_TRAIL_COLOR);
+        for (i, &(first, count)) in strips.iter().enumerate() {
+            let age = (strips.len() - 1 - i) as f32 / MAX_SHOTS as f32;
+            self.program
+                .set_uniform_vec4("color", &glm::vec4(c.x, c.y, c.z, 0.9 - 0.7 * age));
+            stats::count(gl::LINE_STRIP, count, 1);
+            gl::DrawArrays(gl::LINE_STRIP, first, count);
+        }
+        gl::BindVertexArray(0);
+        gl::DepthMask(gl::TRUE);
+        gl::Disable(gl::BLEND);
+    }
+}
+
+impl Drop for ScatteringRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+pub fn draw(text: &mut TextRenderer, atom: &Atom, scattering: &Scattering, width: u32) {
+    // queued into the text batch, the caller flushes
+    let grey = glm::vec4(0.8, 0.8, 0.8, 1.0);
+    let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+    let x0 = (width as f32 - PANEL_WIDTH) / 2.0;
+    text.draw_rect(
+        x0,
+        TOP,
+        PANEL_WIDTH,
+        PANEL_HEIGHT,
+        &glm::vec4(0.0, 0.0, 0.0, 0.85),
+    );
+    let element = atom.element();
+    let caption = format!(
+        "alpha particles at {} (Z = {})",
+        element.symbol, atom.protons
+    );
+    text.draw_text(&caption, x0 + 6.0, TOP + 6.0, 1.0, &grey);
+    let settings = format!(
+        "b = {:.0} fm   E = {:.1} MeV   closest head on {:.1} fm",
+        scattering.impact,
+        scattering.energy,
+        closest_approach(atom, scattering.energy)
+    );
+    text.draw_text(&settings, x0 + 6.0, TOP + 22.0, 1.0, &white);
+    // the newest shot that is done, against what rutherford's formula says for it
+    let last = scattering
+        .shots
+        .iter()
+        .rev()
+        .find(|shot| shot.outcome != Outcome::Flying);
+    let result = match last.map(|shot| (shot, shot.outcome)) {
+        Some((shot, Outcome::Scattered(angle))) => format!(
+            "last shot {:.1} deg, rutherford {:.1} deg",
+            angle,
+            rutherford_angle(atom, shot.impact, shot.energy)
+        ),
+        Some((_, Outcome::Stopped)) => "last shot touched the nucleus".to_string(),
+        _ => "Enter fires".to_string(),
+    };
+    text.draw_text(&result, x0 + 6.0, TOP + 38.0, 1.0, &grey);
+}
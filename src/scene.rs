@@ -0,0 +1,1531 @@
+use crate::absorption; // photons fired at an atom
+use crate::arcball::AtomDrag; // turning one atom by hand
+use crate::audio::Sound; // what happened, for the sound effects
+use crate::aufbau::Builder; // electron by electron filling of the main atom
+use crate::camera::Camera; // orbit camera
+use crate::compare::{self, Comparison}; // main atom next to a partner
+use crate::decay::{self, Ejecta, EjectaKind, Flash}; // what flies off a decaying nucleus
+use crate::ecs::{ElectronPlace, ElectronState, Entity, Mesh, NucleonState, Trail, World}; // bohr view objects
+use crate::elements::Element; // what to place
+use crate::field::FieldView; // per atom field lines or potential slice
+use crate::ground::GroundMode; // floor under the atoms
+use crate::history::{Edit, History}; // undo / redo of scene edits
+use crate::material::Material; // surface looks per kind of particle
+use crate::nucleus; // proton / neutron packing
+use crate::orbital::Orbital; // what the orbital view shows
+use crate::particles::ParticleSystem; // glowing effects
+use crate::photon::Photon; // light from de-excitation
+use crate::physics::Particle; // cdf-sampled cloud particles
+use crate::quality::Quality; // mesh detail, trail and particle caps
+use crate::scale; // true scale nucleus
+use crate::scattering::Scattering; // alpha particles fired at the main atom
+use crate::simulation::{Atom, Shell, Simulation, TimeControl, FIXED_DT}; // bohr atom + its clocks
+use crate::slice::ClipPlane; // cross-section
+use crate::stereo::StereoMode; // one picture or two
+use crate::style::{self, MoleculeStyle}; // balls instead of shells
+use crate::theme::Theme; // palette
+use crate::tonemap::ToneMapping; // hdr to screen
+use crate::vsepr::{self, Geometry}; // molecule shapes
+use nalgebra_glm as glm; // transforms
+use serde::{Deserialize, Serialize}; // view / bond order go into save files
+
+// everything that describes "what is on screen" lives here
+// cpu-side only: the renderer turns this into draw calls
+// keeping it separate means the same scene can go to a window or to an image file
+
+// what the renderer is currently drawing
+// Cloud = monte carlo |psi|^2 samples from physics.rs
+// Bohr = animated planetary atom from simulation.rs
+// Orbital = real-orbital point cloud from orbital.rs
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ViewMode {
+    Cloud,
+    Bohr,
+    Orbital,
+}
+
+impl ViewMode {
+    pub fn next(self) -> Self {
+        // V key cycles through these
+        match self {
+            ViewMode::Cloud => ViewMode::Bohr,
+            ViewMode::Bohr => ViewMode::Orbital,
+            ViewMode::Orbital => ViewMode::Cloud,
+        }
+    }
+}
+
+// what is behind the atoms
+// Solid = plain clear color, Gradient = vertical blend drawn on a fullscreen triangle,
+// Skybox = cubemap from six images (gradient again if none could be loaded)
+#[derive(Clone, Copy, PartialEq)]
+pub enum BackgroundMode {
+    Solid,
+    Gradient,
+    Skybox,
+}
+
+impl BackgroundMode {
+    pub fn next(self) -> Self {
+        // B key cycles through these
+        match self {
+            BackgroundMode::Solid => BackgroundMode::Gradient,
+            BackgroundMode::Gradient => BackgroundMode::Skybox,
+            BackgroundMode::Skybox => BackgroundMode::Solid,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BackgroundMode::Solid => "solid",
+            BackgroundMode::Gradient => "gradient",
+            BackgroundMode::Skybox => "skybox",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            BackgroundMode::Solid,
+            BackgroundMode::Gradient,
+            BackgroundMode::Skybox,
+        ]
+        .into_iter()
+        .find(|mode| mode.name() == name)
+    }
+}
+
+// how meshes are rasterized, independent of the view mode
+// Wireframe / Points are for inspecting the sphere tessellation
+#[derive(Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    Solid,
+    Wireframe,
+    Points,
+}
+
+impl RenderMode {
+    pub fn next(self) -> Self {
+        // W key cycles through these
+        match self {
+            RenderMode::Solid => RenderMode::Wireframe,
+            RenderMode::Wireframe => RenderMode::Points,
+            RenderMode::Points => RenderMode::Solid,
+        }
+    }
+}
+
+// --- bohr view entities, see ecs.rs ---
+
+// something the user can click on in the bohr view
+// indices point into Scene::atoms and into that atom's nucleons / shells
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EntityId {
+    Nucleon {
+        atom: usize,
+        index: usize, // into nucleus::packed for that atom
+    },
+    Electron {
+        atom: usize,
+        shell: usize, // index into Atom::shells, n = shell + 1
+        index: usize,
+    },
+}
+
+// which entities belong to which part of an atom
+// so the animation system can push the simulation state into them every frame
+#[derive(Default)] // empty placeholder until the next rebuild
+struct AtomEntities {
+    root: Entity,                       // the atom's model transform, position and rotation
+    nucleus: Entity,                    // scaled down in true scale mode
+    nucleons: Vec<(Entity, glm::Vec3)>, // with their packed places, thermal jitter goes on top
+    shells: Vec<Entity>,
+    electrons: Vec<Vec<Entity>>, // [shell][electron]
+    jumps: Vec<Entity>,          // electrons between shells, same order as Atom::jumps
+    revision: u32,               // Atom::revision these entities were built for
+}
+
+impl AtomEntities {
+    fn build(
+        world: &mut World,
+        atom: &Atom,
+        atom_index: usize,
+        model: glm::Mat4,
+        display: &DisplaySettings,
+    ) -> Self {
+        // atom -> nucleus -> nucleons
+        //      -> shell (tilted orbit plane) -> electrons
+        //      -> jumping electrons (no shell of their own while in flight)
+        let root = world.spawn(None, model);
+        let sphere = |world: &mut World, entity, radius, material| {
+            world.meshes.insert(entity, Mesh::Sphere { radius });
+            let id = world.library.add(material);
+            world.materials.insert(entity, id);
+        };
+
+        // nucleus: every proton (red) and neutron (blue) is its own little sphere
+        // packing is cached per element, see nucleus.rs
+        let nucleus = world.spawn(Some(root), glm::identity());
+        let mut nucleons = Vec::new();
+        for (index, nucleon) in nucleus::packed(atom.protons, atom.neutrons)
+            .into_iter()
+            .enumerate()
+        {
+            let material = if nucleon.is_proton {
+                Material::proton(display.theme.proton)
+            } else {
+                Material::neutron(display.theme.neutron)
+            };
+            let entity = world.spawn(Some(nucleus), glm::translation(&nucleon.position));
+            sphere(world, entity, nucleus::NUCLEON_RADIUS, material);
+            world.nucleons.insert(
+                entity,
+                NucleonState {
+                    atom: atom_index,
+                    index,
+                    proton: nucleon.is_proton,
+                },
+            );
+            nucleons.push((entity, nucleon.position));
+        }
+
+        let mut shells = Vec::new();
+        let mut electrons = Vec::new();
+        for (shell_index, shell) in atom.shells.iter().enumerate() {
+            let shell_entity = world.spawn(Some(root), shell.plane_rotation());
+            let material = Material::electron(display.theme.electron(shell.n));
+
+            // electrons: small spheres riding their shells
+            let electron_entities = shell
+                .electrons
+                .iter()
+                .enumerate()
+                .map(|(index, electron)| {
+                    let entity = world.spawn(
+                        Some(shell_entity),
+                        glm::translation(&shell.in_plane_position(electron)),
+                    );
+                    sphere(world, entity, display.electron_scale, material);
+                    world.electrons.insert(
+                        entity,
+                        ElectronState {
+                            atom: atom_index,
+                            place: ElectronPlace::Shell {
+                                shell: shell_index,
+                                index,
+                            },
+                        },
+                    );
+                    world.trails.insert(entity, Trail { n: shell.n });
+                    entity
+                })
+                .collect();
+
+            shells.push(shell_entity);
+            electrons.push(electron_entities);
+        }
+
+        let jumps = atom
+            .jumps
+            .iter()
+            .map(|jump| {
+                // colored for where it is going
+                let material = Material::electron(display.theme.electron(jump.to as u32 + 1));
+                let entity = world.spawn(Some(root), glm::translation(&atom.jump_position(jump)));
+                sphere(world, entity, display.electron_scale, material);
+                world.electrons.insert(
+                    entity,
+                    ElectronState {
+                        atom: atom_index,
+                        place: ElectronPlace::Jumping,
+                    },
+                );
+                entity
+            })
+            .collect();
+
+        Self {
+            root,
+            nucleus,
+            nucleons,
+            shells,
+            electrons,
+            jumps,
+            revision: atom.revision,
+        }
+    }
+
+    fn place(&self, world: &mut World, model: glm::Mat4) {
+        // the atom moved or turned outside a step, see World::place
+        world.place(self.root, model);
+    }
+
+    fn animate(&self, world: &mut World, atom: &Atom, model: glm::Mat4, display: &DisplaySettings) {
+        // animation system: copy simulation state (and display knobs) into the transforms
+        world.set_local(self.root, model);
+        // a display knob, not simulation state: shown as set even while paused
+        let factor = scale::nucleus_factor(atom, display.true_scale);
+        world.place(
+            self.nucleus,
+            glm::scaling(&glm::vec3(factor, factor, factor)),
+        );
+        for (&(entity, packed), jitter) in self.nucleons.iter().zip(&atom.nucleon_jitter) {
+            world.set_local(entity, glm::translation(&(packed + jitter)));
+        }
+        for (shell, (&shell_entity, electron_entities)) in atom
+            .shells
+            .iter()
+            .zip(self.shells.iter().zip(&self.electrons))
+        {
+            world.set_local(shell_entity, shell.plane_rotation());
+            world.set_visible(shell_entity, display.shell_visible(shell.n));
+
+            for (electron, &entity) in shell.electrons.iter().zip(electron_entities) {
+                world.set_local(entity, glm::translation(&shell.in_plane_position(electron)));
+            }
+        }
+
+        for (jump, &entity) in atom.jumps.iter().zip(&self.jumps) {
+            world.set_local(entity, glm::translation(&atom.jump_position(jump)));
+        }
+        for &entity in self.electrons.iter().flatten().chain(&self.jumps) {
+            if let Some(Mesh::Sphere { radius }) = world.meshes.get_mut(entity) {
+                *radius = display.electron_scale;
+            }
+        }
+    }
+}
+
+// max shells any element in the table uses (n = 1..=7)
+pub const MAX_SHELLS: usize = 7;
+
+pub struct DisplaySettings {
+    // knobs that change how things look, not what is simulated
+    pub electron_scale: f32,                // electron sphere radius
+    pub shells_visible: [bool; MAX_SHELLS], // index 0 = n=1
+    pub antialiasing: bool,                 // msaa when available, fxaa otherwise
+    pub bloom: bool,                        // glow around electrons, photons and sparks
+    pub bloom_threshold: f32,               // brightness where the glow starts
+    pub bloom_intensity: f32,               // how strongly the glow is added back
+    pub ssao: bool,                         // ambient occlusion, see ssao.rs
+    pub ssao_strength: f32,                 // 0 = no darkening, 1 = fully blocked goes black
+    pub ssao_radius: f32,                   // reach for occluders, times the distance
+    pub shadows: bool,                      // key light shadows, see shadow.rs
+    pub dof: bool,                          // depth of field, see dof.rs
+    pub dof_aperture: f32,                  // 0 = all sharp, 1 = blurs quickly off focus
+    pub tone_mapping: ToneMapping,          // hdr down to the screen, see tonemap.rs
+    pub exposure: f32,                      // brightness before the tone mapping, 1 = as lit
+    pub ground: GroundMode,                 // a floor under the atoms, see ground.rs
+    pub ground_height: f32,                 // from the lowest shell, below it is negative
+    pub ground_fade: f32,                   // world units around the target until it is gone
+    pub trail_length: usize,                // points per electron trail, < 2 = no trails
+    pub periodic_table: bool,               // element picker overlay open
+    pub editor: bool,                       // molecule editor palette, see molecule.rs
+    pub spectrum: bool,                     // emission spectrum bar along the bottom
+    pub levels: bool,                       // energy level sidebar, see levels.rs
+    pub spin: bool,                         // spin arrow through every electron (bohr view)
+    pub guides: bool,                       // shell rings + corner axes, see gizmo.rs
+    pub cloud_opacity: f32, // probability cloud samples, 1 = opaque, below = sorted blending
+    pub background_mode: BackgroundMode,
+    pub theme: Theme, // every color, nucleons and electrons are baked in when entities are built
+    pub clip: ClipPlane, // cross-section through clouds and atoms, see slice.rs
+    pub density_slice: bool, // |psi|^2 on the cut plane in a corner, cloud and orbital views
+    pub radial_plot: bool, // P(r) chart of the same orbital, see radial.rs
+    pub textures: bool, // charge marks / noise on nucleons and electrons, see texture.rs
+    pub outline_width: f32, // selection outline in pixels, the color is theme.highlight
+    pub ui_scale: f32, // hud, overlays and control panel, on top of content_scale
+    pub content_scale: f32, // the display's scale (2 on retina, 1.5 at 150%), from the window
+    pub profiler: bool, // gpu time per render pass in the hud, see profiler.rs
+    pub true_scale: f32, // 0 = didactic nucleus, 1 = real size against the shells, see scale.rs
+    pub magnetic: bool, // orbit field loops + net moment arrow (bohr view), see magnetic.rs
+    pub stereo: StereoMode, // anaglyph or side by side, see stereo.rs
+    pub eye_separation: f32, // distance between the eyes, times the distance to the target
+    pub quality: Quality, // low .. ultra, see quality.rs
+    pub molecule_style: MoleculeStyle, // shells, or balls and sticks, see style.rs
+}
+
+impl DisplaySettings {
+    pub fn hud_scale(&self) -> f32 {
+        // framebuffer pixels per hud pixel: overlays are laid out for a plain 1x screen
+        // and this blows them up to the same size on every display
+        self.ui_scale * self.content_scale
+    }
+
+    pub fn shell_visible(&self, n: u32) -> bool {
+        self.shells_visible
+            .get(n as usize - 1)
+            .copied()
+            .unwrap_or(true)
+    }
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            electron_scale: 0.2,
+            shells_visible: [true; MAX_SHELLS],
+            antialiasing: true,
+            bloom: true,
+            bloom_threshold: 1.0,
+            bloom_intensity: 0.8,
+            ssao: true,
+            ssao_strength: 0.8,
+            ssao_radius: 0.05,
+            shadows: true,
+            dof: false,
+            dof_aperture: 0.3,
+            tone_mapping: ToneMapping::Aces,
+            exposure: 1.0,
+            ground: GroundMode::Off,
+            ground_height: 0.0,
+            ground_fade: 80.0,
+            trail_length: 90,
+            periodic_table: false,
+            editor: false,
+            spectrum: true,
+            levels: false,
+            spin: false,
+            guides: false,
+            cloud_opacity: 1.0,
+            background_mode: BackgroundMode::Solid,
+            theme: Theme::default(),
+            clip: ClipPlane::default(),
+            density_slice: false,
+            radial_plot: false,
+            textures: false,
+            outline_width: 3.0,
+            ui_scale: 1.0,
+            content_scale: 1.0,
+            profiler: false,
+            true_scale: 0.0,
+            magnetic: false,
+            stereo: StereoMode::Off,
+            eye_separation: 0.03,
+            quality: Quality::High,
+            molecule_style: MoleculeStyle::Shells,
+        }
+    }
+}
+
+pub struct SceneAtom {
+    // one atom placed somewhere in the world
+    pub atom: Atom,
+    pub position: glm::Vec3,
+    pub orientation: glm::Quat, // turned by hand, see arcball.rs
+    pub field: FieldView,       // see field.rs
+    entities: AtomEntities,
+}
+
+impl SceneAtom {
+    pub fn model(&self) -> glm::Mat4 {
+        // atom space -> world, what everything it carries hangs from
+        glm::translation(&self.position) * glm::quat_to_mat4(&self.orientation)
+    }
+
+    fn place(&self, world: &mut World) {
+        // position or orientation changed outside a step, show it as it is now
+        self.entities.place(world, self.model());
+    }
+
+    pub fn to_world(&self, local: &glm::Vec3) -> glm::Vec3 {
+        // a point of the atom (relative to its nucleus) in the world
+        self.position + glm::quat_rotate_vec3(&self.orientation, local)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BondOrder {
+    Single,
+    Double,
+    Triple,
+}
+
+impl BondOrder {
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        // smiles-style bond characters
+        match symbol {
+            '-' => Some(BondOrder::Single),
+            '=' => Some(BondOrder::Double),
+            '#' => Some(BondOrder::Triple),
+            _ => None,
+        }
+    }
+
+    pub fn count(self) -> usize {
+        // how many sticks to draw
+        match self {
+            BondOrder::Single => 1,
+            BondOrder::Double => 2,
+            BondOrder::Triple => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Bond {
+    // connects two atoms by index into Scene::atoms
+    pub a: usize,
+    pub b: usize,
+    pub order: BondOrder,
+}
+
+pub struct Scene {
+    pub atoms: Vec<SceneAtom>,
+    pub bonds: Vec<Bond>,
+    pub camera: Camera,
+    pub view_mode: ViewMode,
+    pub render_mode: RenderMode,
+    pub cloud_particles: Vec<Particle>, // static, sampled once at startup
+    pub world: World,                   // bohr view objects, see ecs.rs
+    pub time: TimeControl,              // pause / step / speed of the simulation
+    pub simulation: Simulation,         // fixed timestep accumulator, see update
+    pub display: DisplaySettings,
+    pub selected: Option<EntityId>, // clicked particle, outlined + described in the hud
+    pub photons: Vec<Photon>,       // in flight, world space
+    pub ejecta: Vec<Ejecta>,        // alpha / beta particles from decays, world space
+    pub flashes: Vec<Flash>,        // recent decays, newest last
+    pub particles: ParticleSystem,  // sparks and streaks, world space
+    pub repulsion: bool, // electrons spread over their shell spheres by pushing each other apart
+    pub vibration: bool, // nucleons move under their own forces, see vibration.rs
+    pub temperature: f32, // kelvin, nucleons and electrons shake with it, see thermal.rs
+    pub lamp_wavelength: f32, // nm, the light fire_photon sends at an atom, see absorption.rs
+    pub orbital: Orbital, // n, l, m of the orbital view, the renderer samples again on change
+    pub builder: Option<Builder>, // builder mode, the main atom filled electron by electron
+    pub comparison: Option<Comparison>, // comparison mode, which atom stands next to the main one
+    pub scattering: Option<Scattering>, // scattering mode, alpha particles fired at the main atom
+    pub sounds: Vec<Sound>, // what happened during the last update, see audio.rs
+    pub grabbed: Option<AtomDrag>, // atom being turned with the right mouse button
+    pub history: History, // what Ctrl+Z takes back, see history.rs
+    pub geometry: Option<Geometry>, // bonded atoms slide into their vsepr shape, None = off
+}
+
+impl Scene {
+    pub fn new(camera: Camera, cloud_particles: Vec<Particle>) -> Self {
+        // empty scene, atoms come in through add_atom
+        Self {
+            atoms: Vec::new(),
+            bonds: Vec::new(),
+            camera,
+            view_mode: ViewMode::Cloud,
+            render_mode: RenderMode::Solid,
+            cloud_particles,
+            world: World::new(),
+            time: TimeControl::new(),
+            simulation: Simulation::new(),
+            display: DisplaySettings::default(),
+            selected: None,
+            photons: Vec::new(),
+            ejecta: Vec::new(),
+            flashes: Vec::new(),
+            particles: ParticleSystem::new(),
+            repulsion: false,
+            vibration: false,
+            temperature: 0.0,
+            lamp_wavelength: 121.6, // lyman alpha, what lifts hydrogen's electron to n=2
+            orbital: Orbital::new(2, 1, 0),
+            builder: None,
+            comparison: None,
+            scattering: None,
+            sounds: Vec::new(),
+            grabbed: None,
+            history: History::default(),
+            geometry: Some(Geometry::new()),
+        }
+    }
+
+    pub fn add_atom(&mut self, element: &Element, position: glm::Vec3) -> usize {
+        // neutral ground-state atom of this element at a world position
+        // returns its index in self.atoms
+        let atom = Atom::from_atomic_number(element.atomic_number)
+            .expect("element table entry with invalid atomic number");
+        let entities = AtomEntities::build(
+            &mut self.world,
+            &atom,
+            self.atoms.len(),
+            glm::translation(&position),
+            &self.display,
+        );
+
+        self.atoms.push(SceneAtom {
+            atom,
+            position,
+            orientation: glm::quat_identity(),
+            field: FieldView::Off,
+            entities,
+        });
+        self.atoms.len() - 1
+    }
+
+    pub fn place_atom(
+        &mut self,
+        element: &Element,
+        mass_number: Option<u32>,
+        position: glm::Vec3,
+    ) -> usize {
+        // add_atom as an edit the user made, so it can be undone; one edit with its isotope
+        let index = self.add_atom(element, position);
+        if let Some(atom) = mass_number.and_then(|a| Atom::from_isotope(element.atomic_number, a)) {
+            self.atoms[index].atom = atom;
+            self.rebuild_world();
+        }
+        self.history.record(Edit::AddAtom {
+            index,
+            atom: self.atoms[index].atom.clone(),
+            position,
+        });
+        index
+    }
+
+    pub fn set_element(&mut self, index: usize, element: &Element) {
+        // swap the atom at index for a fresh one of another element, same position
+        let before = self.atoms[index].atom.clone();
+        self.atoms[index].atom = Atom::from_atomic_number(element.atomic_number)
+            .expect("element table entry with invalid atomic number");
+        self.record_atom(index, before, "element change");
+        self.restart_builder(index);
+        self.arrange_comparison();
+        self.rebuild_world();
+    }
+
+    pub fn set_isotope(&mut self, index: usize, mass_number: u32) -> bool {
+        // same element, A - Z neutrons, the nucleus gets repacked on rebuild
+        // false (and nothing changes) if that nucleus cannot exist
+        let Some(atom) = Atom::from_isotope(self.atoms[index].atom.protons, mass_number) else {
+            return false;
+        };
+        let before = std::mem::replace(&mut self.atoms[index].atom, atom);
+        self.record_atom(index, before, "isotope change");
+        self.restart_builder(index);
+        self.rebuild_world();
+        true
+    }
+
+    pub fn replace_atom(&mut self, index: usize, atom: Atom) {
+        // put back an atom as it was before or after an edit, see history.rs
+        self.atoms[index].atom = atom;
+        self.arrange_comparison();
+        self.rebuild_world();
+    }
+
+    pub fn insert_atom(
+        &mut self,
+        index: usize,
+        atom: Atom,
+        position: glm::Vec3,
+        orientation: glm::Quat,
+        bonds: &[Bond],
+    ) {
+        // an atom back in the place it was removed from, the ones after it move up one
+        // bonds are numbered with it already in place
+        for bond in &mut self.bonds {
+            bond.a += (bond.a >= index) as usize;
+            bond.b += (bond.b >= index) as usize;
+        }
+        self.atoms.insert(
+            index,
+            SceneAtom {
+                atom,
+                position,
+                orientation,
+                field: FieldView::Off,
+                entities: AtomEntities::default(),
+            },
+        );
+        self.bonds.extend_from_slice(bonds);
+        self.renumbered();
+    }
+
+    pub fn remove_atom(&mut self, index: usize) -> (Atom, glm::Vec3, glm::Quat, Vec<Bond>) {
+        // takes the atom and its bonds out, the ones after it move down one
+        let scene_atom = self.atoms.remove(index);
+        let (bonds, kept) = self
+            .bonds
+            .iter()
+            .partition(|bond| bond.a == index || bond.b == index);
+        self.bonds = kept;
+        for bond in &mut self.bonds {
+            bond.a -= (bond.a > index) as usize;
+            bond.b -= (bond.b > index) as usize;
+        }
+        self.renumbered();
+        (
+            scene_atom.atom,
+            scene_atom.position,
+            scene_atom.orientation,
+            bonds,
+        )
+    }
+
+    fn renumbered(&mut self) {
+        // photons and ejecta know their atoms by index, which has just changed under them
+        self.photons.clear();
+        self.ejecta.clear();
+        self.grabbed = None;
+        self.rebuild_world();
+    }
+
+    pub fn delete_atom(&mut self, index: usize) -> bool {
+        // remove_atom as an edit the user made; the main atom stays, builder, comparison and
+        // scattering all hang off it, and nothing goes while comparison mode knows its partner
+        // by number
+        if index == 0 || index >= self.atoms.len() || self.comparison.is_some() {
+            return false;
+        }
+        let (atom, position, orientation, bonds) = self.remove_atom(index);
+        self.history.record(Edit::RemoveAtom {
+            index,
+            atom,
+            position,
+            orientation,
+            bonds,
+        });
+        true
+    }
+
+    fn record_atom(&mut self, index: usize, before: Atom, what: &'static str) {
+        // the atom at index has just changed from before to what it is now
+        // not while the builder fills the main atom, it steps through that one itself
+        if index == 0 && self.builder.is_some() {
+            return;
+        }
+        self.history.record(Edit::Atom {
+            index,
+            before,
+            after: self.atoms[index].atom.clone(),
+            what,
+        });
+    }
+
+    fn rebuild_world(&mut self) {
+        // entities are append-only, so any change in structure means starting over
+        self.world = World::new();
+        for (atom_index, scene_atom) in self.atoms.iter_mut().enumerate() {
+            scene_atom.entities = AtomEntities::build(
+                &mut self.world,
+                &scene_atom.atom,
+                atom_index,
+                scene_atom.model(),
+                &self.display,
+            );
+        }
+        // old nucleon / electron indices may not exist in the new atom
+        self.selected = None;
+    }
+
+    pub fn restore(&mut self, atoms: Vec<(Atom, glm::Vec3, glm::Quat)>, bonds: Vec<Bond>) {
+        // swap in a whole saved arrangement, see save.rs
+        // whatever was still flying around belonged to the old atoms
+        self.atoms = atoms
+            .into_iter()
+            .map(|(atom, position, orientation)| SceneAtom {
+                atom,
+                position,
+                orientation,
+                field: FieldView::Off,
+                entities: AtomEntities::default(),
+            })
+            .collect();
+        self.bonds = bonds;
+        self.history.clear(); // its edits were made to other atoms
+        self.builder = None; // the saved atoms come with all their electrons
+        self.comparison = None;
+        self.grabbed = None;
+        if let Some(scattering) = &mut self.scattering {
+            scattering.clear(); // they were aimed at the old main atom
+        }
+        self.photons.clear();
+        self.ejecta.clear();
+        self.flashes.clear();
+        self.particles = ParticleSystem::new();
+        self.rebuild_world();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        // nucleon and electron colors live in their materials, so they are rebuilt
+        self.display.theme = theme;
+        self.rebuild_world();
+    }
+
+    pub fn set_repulsion(&mut self, on: bool) {
+        // switching off puts every electron back on its ring, evenly spaced like on a fresh atom
+        self.repulsion = on;
+        if !on {
+            for scene_atom in &mut self.atoms {
+                scene_atom.atom.shells.iter_mut().for_each(Shell::spread);
+            }
+        }
+    }
+
+    pub fn add_bond(&mut self, a: usize, b: usize, order: BondOrder) {
+        // ball-and-stick bond between two nuclei
+        assert!(
+            a < self.atoms.len() && b < self.atoms.len() && a != b,
+            "bond between invalid atoms {} and {}",
+            a,
+            b
+        );
+        self.bonds.push(Bond { a, b, order });
+    }
+
+    pub fn remove_bond(&mut self, a: usize, b: usize) -> Option<Bond> {
+        // the bond between these two, whichever way round it was made
+        let index = self
+            .bonds
+            .iter()
+            .position(|bond| (bond.a, bond.b) == (a, b) || (bond.a, bond.b) == (b, a))?;
+        Some(self.bonds.remove(index))
+    }
+
+    pub fn set_bond_order(&mut self, a: usize, b: usize, order: BondOrder) {
+        if let Some(bond) = self
+            .bonds
+            .iter_mut()
+            .find(|bond| (bond.a, bond.b) == (a, b) || (bond.a, bond.b) == (b, a))
+        {
+            bond.order = order;
+        }
+    }
+
+    pub fn raise_bond(&mut self, a: usize, b: usize) -> Option<BondOrder> {
+        // single -> double -> triple -> single on the bond between these two, as an edit
+        // None if they are not bonded
+        let bond = self
+            .bonds
+            .iter()
+            .find(|bond| (bond.a, bond.b) == (a, b) || (bond.a, bond.b) == (b, a))?;
+        let before = bond.order;
+        let after = match before {
+            BondOrder::Single => BondOrder::Double,
+            BondOrder::Double => BondOrder::Triple,
+            BondOrder::Triple => BondOrder::Single,
+        };
+        self.set_bond_order(a, b, after);
+        self.history.record(Edit::BondOrder {
+            a,
+            b,
+            before,
+            after,
+        });
+        Some(after)
+    }
+
+    pub fn connect(&mut self, a: usize, b: usize, order: BondOrder) -> bool {
+        // add_bond as an edit the user made; false if the two are not atoms or already bonded
+        let count = self.atoms.len();
+        let bonded = self
+            .bonds
+            .iter()
+            .any(|bond| (bond.a, bond.b) == (a, b) || (bond.a, bond.b) == (b, a));
+        if a >= count || b >= count || a == b || bonded {
+            return false;
+        }
+        self.add_bond(a, b, order);
+        self.history.record(Edit::AddBond(Bond { a, b, order }));
+        true
+    }
+
+    pub fn disconnect(&mut self, a: usize, b: usize) -> bool {
+        let Some(bond) = self.remove_bond(a, b) else {
+            return false;
+        };
+        self.history.record(Edit::RemoveBond(bond));
+        true
+    }
+
+    pub fn center(&self) -> glm::Vec3 {
+        // average atom position, what the camera should look at
+        if self.atoms.is_empty() {
+            return glm::vec3(0.0, 0.0, 0.0);
+        }
+        let sum = self
+            .atoms
+            .iter()
+            .fold(glm::vec3(0.0, 0.0, 0.0), |acc, a| acc + a.position);
+        sum / self.atoms.len() as f32
+    }
+
+    pub fn electron_positions(&self) -> Vec<(u32, glm::Vec3)> {
+        // world position of everything with a trail (the electrons on shells), tagged with
+        // its shell's n, blended like the spheres so the trail ends right at the electron
+        self.world
+            .trails
+            .iter()
+            .map(|(entity, trail)| (trail.n, self.world.position(entity)))
+            .collect()
+    }
+
+    pub fn pick(&self, x: f32, y: f32) -> Option<EntityId> {
+        // ray cast from the camera through (x, y) in normalized device coordinates
+        // (-1..1, y up), nearest sphere hit wins
+        // only the bohr view has pickable particles
+        if self.view_mode != ViewMode::Bohr {
+            return None;
+        }
+
+        let (origin, dir) = self.cursor_ray(x, y);
+        let style = self.display.molecule_style;
+        if style != MoleculeStyle::Shells {
+            // the balls stand in for the particles, a hit selects the atom's first nucleon
+            return style::balls(style, &self.atoms, &self.bonds)
+                .iter()
+                .filter_map(|ball| Some((ray_hit(&origin, &dir, &ball.center, ball.radius)?, ball)))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, ball)| EntityId::Nucleon {
+                    atom: ball.atom,
+                    index: 0,
+                });
+        }
+        let mut nearest: Option<(f32, EntityId)> = None;
+        self.for_each_entity(|entity, center, radius| {
+            let Some(t) = ray_hit(&origin, &dir, &center, radius) else {
+                return;
+            };
+            if nearest.is_none_or(|(best, _)| t < best) {
+                nearest = Some((t, entity));
+            }
+        });
+        nearest.map(|(_, entity)| entity)
+    }
+
+    fn cursor_ray(&self, x: f32, y: f32) -> (glm::Vec3, glm::Vec3) {
+        // origin and unit direction through (x, y) in normalized device coordinates
+        // unproject the cursor on the near and far plane, works for ortho too
+        let inverse =
+            glm::inverse(&(self.camera.get_projection_matrix() * self.camera.get_view_matrix()));
+        let unproject = |z: f32| {
+            let p = inverse * glm::vec4(x, y, z, 1.0);
+            p.xyz() / p.w
+        };
+        let origin = unproject(-1.0);
+        (origin, glm::normalize(&(unproject(1.0) - origin)))
+    }
+
+    pub fn cursor_point(&self, x: f32, y: f32) -> glm::Vec3 {
+        // where the ray through (x, y) crosses the plane through the camera target facing the
+        // camera, somewhere to put things down
+        let (origin, dir) = self.cursor_ray(x, y);
+        let target = self.camera.target;
+        let facing = glm::normalize(&(target - self.camera.get_position()));
+        let along = glm::dot(&dir, &facing);
+        if along.abs() < 1e-6 {
+            return target;
+        }
+        origin + dir * (glm::dot(&(target - origin), &facing) / along)
+    }
+
+    pub fn atom_at(&self, x: f32, y: f32) -> Option<usize> {
+        // the nearest atom whose outermost shell the ray through (x, y) passes, in any view
+        let (origin, dir) = self.cursor_ray(x, y);
+        self.atoms
+            .iter()
+            .enumerate()
+            .filter_map(|(index, scene_atom)| {
+                let to_center = scene_atom.position - origin;
+                let along = glm::dot(&to_center, &dir);
+                let miss_sq = glm::dot(&to_center, &to_center) - along * along;
+                let radius = scene_atom.atom.outer_radius();
+                (miss_sq <= radius * radius && along > 0.0).then_some((along, index))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, index)| index)
+    }
+
+    pub fn grab_atom(&mut self, ndc: glm::Vec2, cursor: glm::Vec2, window: glm::Vec2) {
+        // right mouse button down: the atom under the cursor, or the selected / main one when
+        // the press misses every atom (one atom filling the screen is mostly outside its shells)
+        if self.atoms.is_empty() {
+            return;
+        }
+        let index = self
+            .atom_at(ndc.x, ndc.y)
+            .unwrap_or_else(|| self.target_atom());
+        let scene_atom = &self.atoms[index];
+        self.grabbed = Some(AtomDrag::new(
+            index,
+            &scene_atom.position,
+            scene_atom.orientation,
+            &self.camera,
+            cursor,
+            window,
+        ));
+    }
+
+    pub fn drag_atom(&mut self, cursor: glm::Vec2) {
+        // window units, like the press; nothing while no atom is held
+        if let Some(drag) = &self.grabbed {
+            if let Some(scene_atom) = self.atoms.get_mut(drag.atom) {
+                // placed, not blended: a rotation lerped against the last step's comes out
+                // sheared, and while paused it would only ever show part of the drag
+                scene_atom.orientation = drag.orientation(cursor);
+                scene_atom.place(&mut self.world);
+            }
+        }
+    }
+
+    pub fn release_atom(&mut self) {
+        self.grabbed = None;
+    }
+
+    pub fn reset_rotation(&mut self) {
+        // the selected atom (or the main one) back the way it was built
+        let index = self.target_atom();
+        if let Some(scene_atom) = self.atoms.get_mut(index) {
+            scene_atom.orientation = glm::quat_identity();
+            scene_atom.place(&mut self.world);
+        }
+    }
+
+    pub fn spin(&self, id: EntityId) -> Option<bool> {
+        // spin up or down of an electron, None for nucleons (and electrons that are gone)
+        let EntityId::Electron { atom, shell, index } = id else {
+            return None;
+        };
+        let shell = self.atoms.get(atom)?.atom.shells.get(shell)?;
+        shell.electrons.get(index).map(|electron| electron.spin_up)
+    }
+
+    pub fn entity_sphere(&self, id: EntityId) -> Option<(glm::Vec3, f32)> {
+        // world center and radius of a pickable sphere, None if it is hidden or gone
+        let mut found = None;
+        self.for_each_entity(|entity, center, radius| {
+            if entity == id {
+                found = Some((center, radius));
+            }
+        });
+        found
+    }
+
+    fn for_each_entity(&self, mut visit: impl FnMut(EntityId, glm::Vec3, f32)) {
+        // every shown sphere that can be picked, in world space
+        for sphere in self.world.spheres() {
+            if let Some(id) = self.world.pick_id(sphere.entity) {
+                let center = sphere.world.column(3).xyz();
+                let radius = sphere.radius * glm::length(&sphere.world.column(0).xyz());
+                visit(id, center, radius);
+            }
+        }
+    }
+
+    pub fn shape(&self, index: usize) -> Option<String> {
+        // vsepr name of the atom at index, when it is a center of two or more bonds
+        let bonds: Vec<&Bond> = self
+            .bonds
+            .iter()
+            .filter(|bond| bond.a == index || bond.b == index)
+            .collect();
+        if bonds.len() < 2 {
+            return None;
+        }
+        let electrons = bonds.iter().map(|bond| bond.order.count()).sum();
+        let lone_pairs = vsepr::lone_pair_count(&self.atoms[index], electrons);
+        Some(format!(
+            "{} (AX{}E{})",
+            vsepr::shape(bonds.len(), lone_pairs),
+            bonds.len(),
+            lone_pairs
+        ))
+    }
+
+    pub fn describe(&self, id: EntityId) -> Vec<String> {
+        // hud lines for a selected particle
+        match id {
+            EntityId::Nucleon {
+                atom: atom_index,
+                index,
+            } => {
+                let atom = &self.atoms[atom_index].atom;
+                let is_proton = nucleus::packed(atom.protons, atom.neutrons)
+                    .get(index)
+                    .is_some_and(|nucleon| nucleon.is_proton);
+                let (name, charge, mass) = if is_proton {
+                    ("proton", "+1 e", 1.00728)
+                } else {
+                    ("neutron", "0", 1.00866)
+                };
+                let mut lines = vec![
+                    format!("{} in {}", name, atom.isotope().name()),
+                    format!("charge {}  mass {:.5} u", charge, mass),
+                    format!(
+                        "binding {:.2} MeV/nucleon",
+                        atom.binding_energy_per_nucleon()
+                    ),
+                    atom.isotope().describe(),
+                ];
+                lines.extend(self.shape(atom_index));
+                lines
+            }
+            EntityId::Electron { atom, shell, .. } => {
+                let atom = &self.atoms[atom].atom;
+                vec![
+                    format!("electron in {}", atom.element().symbol),
+                    format!("charge -1 e  shell n={}", atom.shells[shell].n),
+                    format!("energy {:.2} eV", atom.electron_energy(shell)),
+                    match self.spin(id) {
+                        Some(true) => "spin up, m_s = +1/2".to_string(),
+                        _ => "spin down, m_s = -1/2".to_string(),
+                    },
+                ]
+            }
+        }
+    }
+
+    pub fn excite(&mut self) -> bool {
+        // the selected electron goes up one shell, or without a selection
+        // the main atom's outermost one does
+        match self.selected {
+            Some(EntityId::Electron { atom, shell, index }) => {
+                self.atoms[atom].atom.excite((shell, index), shell + 1)
+            }
+            _ => self
+                .atoms
+                .first_mut()
+                .is_some_and(|scene_atom| scene_atom.atom.excite_outermost()),
+        }
+    }
+
+    pub fn ionize(&mut self) -> bool {
+        // the atom of the selected particle loses an electron, the main atom without a selection
+        self.change_target(Atom::ionize, "ionization")
+    }
+
+    pub fn add_electron(&mut self) -> bool {
+        self.change_target(Atom::add_electron, "added electron")
+    }
+
+    fn change_target(&mut self, change: fn(&mut Atom) -> bool, what: &'static str) -> bool {
+        // recorded for undo when it did something
+        let index = self.target_atom();
+        let Some(scene_atom) = self.atoms.get_mut(index) else {
+            return false;
+        };
+        let before = scene_atom.atom.clone();
+        if !change(&mut scene_atom.atom) {
+            return false;
+        }
+        self.record_atom(index, before, what);
+        true
+    }
+
+    pub fn cycle_field(&mut self) -> Option<FieldView> {
+        // off -> lines -> slice -> off on the selected particle's atom, the main one without
+        // a selection; the bohr view is the one with point charges to take the field of
+        let index = self.target_atom();
+        let scene_atom = self.atoms.get_mut(index)?;
+        scene_atom.field = scene_atom.field.next();
+        if scene_atom.field != FieldView::Off {
+            self.view_mode = ViewMode::Bohr;
+        }
+        Some(scene_atom.field)
+    }
+
+    pub fn toggle_scattering(&mut self) -> bool {
+        // true if it is on now, the trails go with it when it is turned off
+        if self.scattering.take().is_some() {
+            return false;
+        }
+        self.scattering = Some(Scattering::new());
+        self.view_mode = ViewMode::Bohr;
+        true
+    }
+
+    pub fn fire_alpha(&mut self) -> bool {
+        // one shot across the screen at the main atom, false outside scattering mode
+        let (Some(scattering), Some(main_atom)) = (&mut self.scattering, self.atoms.first()) else {
+            return false;
+        };
+        // the camera's right and up in the world are the first two rows of its view matrix
+        let view = self.camera.get_view_matrix();
+        let right = glm::vec3(view[(0, 0)], view[(0, 1)], view[(0, 2)]);
+        let up = glm::vec3(view[(1, 0)], view[(1, 1)], view[(1, 2)]);
+        scattering.fire(&main_atom.atom, main_atom.position, &right, &up);
+        true
+    }
+
+    pub fn fire_photon(&mut self) -> bool {
+        // light of lamp_wavelength at the selected atom (the main one without a selection),
+        // across the screen like the alpha particles; false without atoms
+        let target = self.target_atom();
+        let Some(scene_atom) = self.atoms.get(target) else {
+            return false;
+        };
+        let view = self.camera.get_view_matrix();
+        let right = glm::vec3(view[(0, 0)], view[(0, 1)], view[(0, 2)]);
+        self.photons.push(absorption::fire(
+            &scene_atom.atom,
+            scene_atom.position,
+            target,
+            &right,
+            self.lamp_wavelength,
+        ));
+        true
+    }
+
+    pub fn toggle_builder(&mut self) -> bool {
+        // into builder mode with a bare main atom, or out of it with the ground state back
+        // true if it is on now
+        let Some(main_atom) = self.atoms.first_mut() else {
+            return false;
+        };
+        // edits to the main atom from before cannot be undone into builder mode or out of it
+        self.history.clear();
+        if self.builder.take().is_some() {
+            main_atom.atom.set_electron_count(main_atom.atom.protons);
+            return false;
+        }
+        self.builder = Some(Builder::new());
+        main_atom.atom.set_electron_count(0);
+        self.view_mode = ViewMode::Bohr;
+        true
+    }
+
+    fn restart_builder(&mut self, index: usize) {
+        // a new main atom in builder mode starts over from its bare nucleus
+        if index == 0 && self.builder.is_some() {
+            self.builder = Some(Builder::new());
+            self.atoms[0].atom.set_electron_count(0);
+        }
+    }
+
+    pub fn toggle_comparison(&mut self) -> bool {
+        // main atom and partner side by side (see compare.rs), or back to the scene as it was
+        // true if it is on now
+        // the partner coming and going renumbers the atoms, so the edits before are forgotten
+        self.history.clear();
+        if let Some(comparison) = self.comparison.take() {
+            if comparison.added {
+                // atoms added while comparing sit after the partner, their bonds move down
+                self.remove_atom(comparison.partner);
+            }
+            return false;
+        }
+        let Some(main_atom) = self.atoms.first() else {
+            return false;
+        };
+        let comparison = if self.atoms.len() > 1 {
+            Comparison {
+                partner: 1,
+                added: false,
+            }
+        } else {
+            let element = compare::default_partner(main_atom.atom.element());
+            let partner = Atom::from_atomic_number(element.atomic_number)
+                .expect("element table entry with invalid atomic number");
+            let position = compare::partner_position(&main_atom.atom, main_atom.position, &partner);
+            Comparison {
+                partner: self.add_atom(element, position),
+                added: true,
+            }
+        };
+
+        // both in the middle of the screen, with room to spare
+        let (a, b) = (&self.atoms[0], &self.atoms[comparison.partner]);
+        let center = (a.position + b.position) * 0.5;
+        let extent =
+            glm::distance(&a.position, &b.position) + a.atom.outer_radius() + b.atom.outer_radius();
+        self.camera.fly_to(center, extent.max(30.0), 1.0);
+        self.comparison = Some(comparison);
+        self.view_mode = ViewMode::Bohr;
+        true
+    }
+
+    pub fn set_partner_element(&mut self, element: &Element) -> bool {
+        // false when comparison mode is off
+        let Some(partner) = self
+            .comparison
+            .as_ref()
+            .map(|comparison| comparison.partner)
+        else {
+            return false;
+        };
+        self.set_element(partner, element);
+        true
+    }
+
+    fn arrange_comparison(&mut self) {
+        // a partner brought in for the comparison stays clear of the main atom whatever the two
+        // of them turn into
+        if let Some(Comparison {
+            partner,
+            added: true,
+        }) = self.comparison
+        {
+            let main_atom = &self.atoms[0];
+            self.atoms[partner].position = compare::partner_position(
+                &main_atom.atom,
+                main_atom.position,
+                &self.atoms[partner].atom,
+            );
+            // moved aside outside a step, its entities may not be rebuilt for it
+            self.atoms[partner].place(&mut self.world);
+        }
+    }
+
+    pub fn builder_next(&mut self) -> bool {
+        match (&mut self.builder, self.atoms.first_mut()) {
+            (Some(builder), Some(main_atom)) => builder.next(&mut main_atom.atom),
+            _ => false,
+        }
+    }
+
+    pub fn builder_previous(&mut self) -> bool {
+        match (&mut self.builder, self.atoms.first_mut()) {
+            (Some(builder), Some(main_atom)) => builder.previous(&mut main_atom.atom),
+            _ => false,
+        }
+    }
+
+    pub fn log_stats(&self, fps: f32) {
+        // one line summary of what is alive, for spotting leaks and runaway effects
+        let speed = if self.time.paused {
+            "paused".to_string()
+        } else {
+            format!("x{}", self.time.scale)
+        };
+        let electrons: usize = self
+            .atoms
+            .iter()
+            .map(|scene_atom| scene_atom.atom.electron_count())
+            .sum();
+        log::debug!(
+            "{:.0} fps, time {}, {} atoms, {} electrons, {} photons, {} ejecta, {} particles",
+            fps,
+            speed,
+            self.atoms.len(),
+            electrons,
+            self.photons.len(),
+            self.ejecta.len(),
+            self.particles.iter().count()
+        );
+    }
+
+    pub fn target_atom(&self) -> usize {
+        match self.selected {
+            Some(EntityId::Nucleon { atom, .. }) | Some(EntityId::Electron { atom, .. }) => atom,
+            None => 0,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        // advance everything that moves on its own, once per frame
+        // time controls only affect the simulation, the camera keeps real time
+        // the simulation itself only ever moves in FIXED_DT steps, however long the frame was,
+        // so a run looks the same at 30 and at 240 fps; what is drawn is blended between the
+        // last two steps by how far into the next one the frame is
+        self.sounds.clear();
+        // molecules take their shape on real time, also while paused, so the atoms are
+        // placed where they slid to instead of blended between steps like the electrons
+        if let Some(geometry) = &mut self.geometry {
+            if geometry.relax(&mut self.atoms, &self.bonds, dt) {
+                for scene_atom in &self.atoms {
+                    scene_atom.place(&mut self.world);
+                }
+            }
+        }
+        let steps = self.simulation.advance(self.time.tick(dt));
+        for _ in 0..steps {
+            self.world.save_previous();
+            self.step(FIXED_DT);
+            self.animate();
+        }
+        if steps == 0 {
+            // paused or a very short frame: display changes and ions still have to show up
+            self.animate();
+        }
+        self.world.blend = self.simulation.alpha();
+        self.world.update_transforms();
+
+        self.camera.update(dt);
+    }
+
+    fn animate(&mut self) {
+        // push simulation state into the entities, starting over if electrons changed shells
+        let structure_changed = self
+            .atoms
+            .iter()
+            .any(|scene_atom| scene_atom.entities.revision != scene_atom.atom.revision);
+        if structure_changed {
+            // entity ids (and what they point at) moved around
+            self.rebuild_world();
+        }
+        for scene_atom in &self.atoms {
+            scene_atom.entities.animate(
+                &mut self.world,
+                &scene_atom.atom,
+                scene_atom.model(),
+                &self.display,
+            );
+        }
+    }
+
+    fn step(&mut self, sim_dt: f64) {
+        // one fixed step of everything simulated
+        // atoms take the f64 step, what flies around only lives a few seconds and goes f32
+        let dt = sim_dt as f32;
+        self.particles.limit = self.display.quality.max_particles();
+        for (atom_index, scene_atom) in self.atoms.iter_mut().enumerate() {
+            // jumps that have not moved yet were started since the last step, by a key, a
+            // click or a photon, and the ones going up are heard
+            let lifted = scene_atom
+                .atom
+                .jumps
+                .iter()
+                .filter(|jump| jump.progress == 0.0 && jump.to > jump.from)
+                .count();
+            self.sounds
+                .extend(std::iter::repeat_n(Sound::Transition, lifted));
+
+            let parent = scene_atom.atom.isotope().name();
+            if let Some(mode) = scene_atom.atom.decay(sim_dt) {
+                let label = format!(
+                    "{} -> {} ({})",
+                    parent,
+                    scene_atom.atom.isotope().name(),
+                    mode.name()
+                );
+                log::info!("atom {} decayed: {}", atom_index, label);
+                if let Some(kind) = EjectaKind::from_mode(mode) {
+                    self.ejecta.push(Ejecta::new(
+                        scene_atom.position,
+                        decay::random_direction(),
+                        kind,
+                        atom_index,
+                    ));
+                }
+                self.flashes.push(Flash::new(scene_atom.position, label));
+                self.sounds.push(Sound::Decay);
+                self.particles
+                    .burst(scene_atom.position, 40, 6.0, glm::vec3(1.0, 0.9, 0.6));
+            }
+
+            for emission in
+                scene_atom
+                    .atom
+                    .step(sim_dt, self.repulsion, self.vibration, self.temperature)
+            {
+                // photon leaves radially, away from the nucleus
+                let position = scene_atom.to_world(&emission.position);
+                let outward = if glm::length(&emission.position) > 1e-4 {
+                    glm::normalize(&(position - scene_atom.position))
+                } else {
+                    glm::vec3(0.0, 1.0, 0.0)
+                };
+                let photon = Photon::new(position, outward, emission.wavelength());
+                self.sounds.push(Sound::Emission {
+                    wavelength: photon.wavelength,
+                });
+                self.particles.burst(position, 12, 3.0, photon.color);
+                self.photons.push(photon);
+            }
+        }
+
+        if let (Some(builder), Some(main_atom)) = (&mut self.builder, self.atoms.first_mut()) {
+            if let Some((n, landed)) = builder.step(&mut main_atom.atom, sim_dt) {
+                self.particles.burst(
+                    main_atom.to_world(&landed),
+                    12,
+                    3.0,
+                    self.display.theme.electron(n),
+                );
+            }
+            if let Some((n, position)) = builder.incoming(1.0) {
+                self.particles.streak(
+                    main_atom.to_world(&position),
+                    self.display.electron_scale * 0.8,
+                    self.display.theme.trail(n),
+                );
+            }
+        }
+
+        if let (Some(scattering), Some(main_atom)) = (&mut self.scattering, self.atoms.first()) {
+            let stopped = scattering.step(
+                &main_atom.atom,
+                main_atom.position,
+                self.display.true_scale,
+                dt,
+            );
+            for position in stopped {
+                self.particles
+                    .burst(position, 25, 4.0, glm::vec3(1.0, 0.8, 0.4));
+            }
+        }
+
+        // photons fired at an atom lift an electron if they carry the energy of a jump it can
+        // make, and fly on through it otherwise
+        let atoms = &mut self.atoms;
+        let particles = &mut self.particles;
+        self.photons.retain_mut(|photon| {
+            let Some(target) = photon.target else {
+                return true;
+            };
+            let Some(scene_atom) = atoms.get_mut(target) else {
+                photon.pass(); // the atom went away
+                return true;
+            };
+            if let Some(transition) =
+                absorption::absorb(photon, &scene_atom.atom, scene_atom.position)
+            {
+                if scene_atom.atom.excite(transition.electron, transition.to) {
+                    log::info!(
+                        "atom {} absorbed {:.1} nm: n={} -> n={}",
+                        target,
+                        photon.wavelength,
+                        transition.electron.0 + 1,
+                        transition.to + 1
+                    );
+                    particles.burst(photon.position, 12, 3.0, photon.color);
+                    return false;
+                }
+            }
+            let outward = photon.position - scene_atom.position;
+            if glm::dot(&outward, &photon.velocity) > 0.0 {
+                photon.pass();
+            }
+            true
+        });
+
+        // moving things leave glowing tails (never while paused, there are no steps then)
+        for photon in &mut self.photons {
+            photon.step(dt);
+            self.particles
+                .streak(photon.position, photon.radius() * 0.8, photon.color);
+        }
+        self.photons.retain(Photon::alive);
+
+        for ejecta in &mut self.ejecta {
+            ejecta.step(dt);
+            self.particles
+                .streak(ejecta.position, 0.2, ejecta.trail_color(&self.display));
+        }
+        // a decay product that runs into another nucleus is stopped there in a shower of sparks
+        let atoms = &self.atoms;
+        let particles = &mut self.particles;
+        self.ejecta.retain(|ejecta| {
+            let hit = atoms.iter().enumerate().any(|(index, scene_atom)| {
+                let atom = &scene_atom.atom;
+                index != ejecta.source
+                    && glm::distance(&ejecta.position, &scene_atom.position)
+                        < nucleus::radius(atom.protons + atom.neutrons)
+            });
+            if hit {
+                particles.burst(ejecta.position, 25, 4.0, glm::vec3(1.0, 0.8, 0.4));
+                return false;
+            }
+            ejecta.alive()
+        });
+        for flash in &mut self.flashes {
+            flash.step(dt);
+        }
+        self.flashes.retain(Flash::alive);
+        self.particles.step(dt);
+    }
+}
+
+fn ray_hit(origin: &glm::Vec3, dir: &glm::Vec3, center: &glm::Vec3, radius: f32) -> Option<f32> {
+    // ray / sphere: solve |origin + t dir - center| = radius for the smaller t, ahead only
+    let to_center = center - origin;
+    let along = glm::dot(&to_center, dir);
+    let miss_sq = glm::dot(&to_center, &to_center) - along * along;
+    if miss_sq > radius * radius {
+        return None;
+    }
+    Some(along - (radius * radius - miss_sq).sqrt()).filter(|&t| t > 0.0)
+}
@@ -0,0 +1,211 @@
+use crate::elements::Element;
+use crate::input::Action; // scripts can press any bound key
+use crate::isotopes; // Element("C-14") is parsed like --element
+use crate::scene::{Scene, ViewMode};
+use serde::Deserialize; // script file format
+use std::fmt; // pretty errors
+use std::fs;
+
+// timed demo sequences, so a classroom demo runs the same way every time
+// (atom-sim --script demo.ron, Ctrl+R starts it over)
+// a script is a list of events, each fired once its time has come:
+//
+//   (
+//       repeat_after: Some(12.0), // optional, start over from t=0 after this many seconds
+//       events: [
+//           (at: 0.0, event: Element("H")),
+//           (at: 2.0, event: Element("He")),
+//           (at: 5.0, event: Action(Excite)),
+//           (at: 8.0, event: Orbit(degrees: 180.0, seconds: 3.0)),
+//           (at: 8.0, event: Say("the photon carries the energy difference away")),
+//       ],
+//   )
+//
+// times are real seconds since the script started, pausing the simulation does not stop
+// the script (otherwise a scripted Pause would never be followed by anything)
+
+#[derive(Debug)]
+pub enum ScriptError {
+    Io { path: String, message: String },
+    Parse { path: String, message: String },
+    Invalid { path: String, message: String }, // parsed, but cannot be played
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptError::Io { path, message } => {
+                write!(f, "could not read script '{}': {}", path, message)
+            }
+            ScriptError::Parse { path, message } => {
+                write!(f, "could not parse script '{}': {}", path, message)
+            }
+            ScriptError::Invalid { path, message } => {
+                write!(f, "invalid script '{}': {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptFile {
+    #[serde(default)]
+    repeat_after: Option<f32>,
+    events: Vec<Scheduled>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Scheduled {
+    at: f32, // seconds since the script started
+    event: Event,
+}
+
+#[derive(Deserialize)]
+enum Event {
+    Action(Action),                       // same as pressing its key
+    Element(String),                      // main atom becomes this, "He", "6" or "C-14"
+    View(ViewMode),                       // Cloud, Bohr or Orbital
+    Speed(f32),                           // time scale
+    Orbit { degrees: f32, seconds: f32 }, // camera swings around the target
+    Zoom { radius: f32, seconds: f32 },   // camera flies to this distance
+    Say(String),                          // printed to the terminal, captions for the demo
+}
+
+// a running Orbit event, the camera goal is nudged a bit every frame
+struct Swing {
+    rate: f32,      // rad/s
+    remaining: f32, // seconds
+}
+
+pub struct Script {
+    events: Vec<Scheduled>, // sorted by time
+    repeat_after: Option<f32>,
+    time: f32,
+    next: usize, // first event that has not fired yet
+    swings: Vec<Swing>,
+}
+
+impl Script {
+    pub fn load(path: &str) -> Result<Self, ScriptError> {
+        let source = fs::read_to_string(path).map_err(|err| ScriptError::Io {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+        let file: ScriptFile = ron::from_str(&source).map_err(|err| ScriptError::Parse {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+        validate(&file).map_err(|message| ScriptError::Invalid {
+            path: path.to_string(),
+            message,
+        })?;
+
+        let mut events = file.events;
+        // stable, so events at the same time fire in the order they were written
+        events.sort_by(|a, b| a.at.total_cmp(&b.at));
+        log::info!("loaded {} script events from {}", events.len(), path);
+        Ok(Self {
+            events,
+            repeat_after: file.repeat_after,
+            time: 0.0,
+            next: 0,
+            swings: Vec::new(),
+        })
+    }
+
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+        self.next = 0;
+        self.swings.clear();
+    }
+
+    pub fn update(&mut self, dt: f32, scene: &mut Scene) -> Vec<Action> {
+        // call once per frame with real time
+        // scene-level events happen right here, key actions are handed back for the caller
+        // to run, they may need the window or the recorder
+        if let Some(length) = self.repeat_after {
+            if self.time >= length {
+                self.restart();
+            }
+        }
+        self.time += dt;
+
+        let mut actions = Vec::new();
+        while let Some(scheduled) = self.events.get(self.next) {
+            if scheduled.at > self.time {
+                break;
+            }
+            self.next += 1;
+            match &scheduled.event {
+                Event::Action(action) => actions.push(*action),
+                Event::Element(name) => {
+                    // checked on load
+                    let (element, mass_number) = isotopes::parse(name).unwrap();
+                    set_main_atom(scene, element, mass_number);
+                }
+                Event::View(mode) => scene.view_mode = *mode,
+                Event::Speed(scale) => scene.time.set_scale(*scale),
+                Event::Orbit { degrees, seconds } => self.swings.push(Swing {
+                    rate: degrees.to_radians() / seconds,
+                    remaining: *seconds,
+                }),
+                Event::Zoom { radius, seconds } => {
+                    let target = scene.camera.goal_target;
+                    scene.camera.fly_to(target, *radius, *seconds);
+                }
+                Event::Say(text) => println!("{}", text),
+            }
+        }
+
+        for swing in &mut self.swings {
+            let step = dt.min(swing.remaining);
+            scene.camera.orbit_by(swing.rate * step);
+            swing.remaining -= step;
+        }
+        self.swings.retain(|swing| swing.remaining > 0.0);
+        actions
+    }
+}
+
+pub fn set_main_atom(scene: &mut Scene, element: &Element, mass_number: Option<u32>) {
+    if scene.atoms.is_empty() {
+        return;
+    }
+    scene.set_element(0, element);
+    if let Some(mass_number) = mass_number {
+        scene.set_isotope(0, mass_number);
+    }
+}
+
+fn validate(file: &ScriptFile) -> Result<(), String> {
+    // catch what would misbehave halfway through a demo, with the event number in the message
+    if file.repeat_after.is_some_and(|length| length <= 0.0) {
+        return Err("repeat_after must be positive".to_string());
+    }
+    for (i, scheduled) in file.events.iter().enumerate() {
+        let number = i + 1;
+        if scheduled.at < 0.0 {
+            return Err(format!("event {}: at must not be negative", number));
+        }
+        match &scheduled.event {
+            Event::Element(name) => {
+                isotopes::parse(name).map_err(|err| format!("event {}: {}", number, err))?;
+            }
+            Event::Speed(scale) if *scale <= 0.0 => {
+                return Err(format!("event {}: speed must be positive", number));
+            }
+            Event::Orbit { seconds, .. } | Event::Zoom { seconds, .. } if *seconds <= 0.0 => {
+                return Err(format!("event {}: seconds must be positive", number));
+            }
+            Event::Zoom { radius, .. } if *radius <= 0.0 => {
+                return Err(format!("event {}: radius must be positive", number));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
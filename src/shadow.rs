@@ -0,0 +1,171 @@
+use crate::framebuffer::{self, Framebuffer};
+use crate::material::Shading; // unlit things glow, they cast nothing
+use crate::render::{
+    generate_cylinder, generate_sphere, InstancedRenderer, ShaderError, ShaderProgram, VertexArray,
+    WatchedProgram,
+};
+use crate::renderer::bond_models; // the grey sticks of the shells style
+use crate::scene::Scene;
+use crate::style::{self, MoleculeStyle}; // balls and sticks cast too
+use nalgebra_glm as glm;
+
+// shadows from the key light (Shift+H), bohr view only: atoms shade each other and the
+// ground (Shift+G, see ground.rs) below them, which is what makes their places in 3d readable
+// the key light is a point light, but far enough away to pass for a directional one here:
+//   1. depth only: every opaque sphere and stick into a depth texture, seen from the light
+//      along a fixed direction, orthographic and fitted around the atoms
+//   2. the lit shader looks each fragment up in it, nine comparisons around it (pcf) on
+//      top of the hardware's own blend of four, so the edges come out soft
+// casters are collected again here instead of reusing the renderer's batches, those are
+// culled to the camera and a sphere behind it can still throw its shadow into the picture
+// the shadow only darkens the key light, the fill light and the ambient part stay, so a
+// shadowed side is never black
+// fragments are looked up a texel and a half off their surface along the normal, with a
+// polygon offset in the depth pass on top, against shadow acne on the curved spheres
+
+const UNIT: u32 = 2; // texture unit of the map, surface textures are on 0
+const NORMAL_OFFSET: f32 = 1.5; // texels along the normal
+
+pub struct ShadowMap {
+    program: WatchedProgram,
+    target: Framebuffer,
+    spheres: InstancedRenderer, // every caster sphere, one plain mesh, the shadow is blurred
+    sticks: InstancedRenderer,
+    light_space: glm::Mat4, // world -> the map's clip space, from the last render
+    texel: f32,             // world units per texel of the last render
+    drawn: bool,            // whether the map holds this frame's casters
+}
+
+impl ShadowMap {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        Ok(Self {
+            program: WatchedProgram::new("shadow", ShaderProgram::shadow)?,
+            target: Framebuffer::depth_only(),
+            spheres: InstancedRenderer::new(VertexArray::from_mesh(&generate_sphere(1.0, 16, 12))),
+            sticks: InstancedRenderer::new(VertexArray::from_mesh(&generate_cylinder(
+                1.0, 1.0, 12,
+            ))),
+            light_space: glm::identity(),
+            texel: 0.0,
+            drawn: false,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn render(&mut self, scene: &Scene, light: &glm::Vec3) {
+        // the depth pass, sets up what apply hands to the lit shader
+        self.drawn = false;
+        let Some((center, radius)) = bounds(scene) else {
+            return;
+        };
+        let direction = glm::normalize(&(light - center));
+        let up = if direction.y.abs() > 0.99 {
+            glm::vec3(0.0, 0.0, 1.0)
+        } else {
+            glm::vec3(0.0, 1.0, 0.0)
+        };
+        // along the light every shadow lands on the same spot of the map as its caster, so
+        // the atoms' circle is all it has to cover; depth reaches far behind them for the
+        // ground
+        let view = glm::look_at(&(center + direction * (3.0 * radius)), &center, &up);
+        let projection = glm::ortho(-radius, radius, -radius, radius, radius, 9.0 * radius);
+        self.light_space = projection * view;
+        let size = scene.display.quality.shadow_size();
+        self.texel = 2.0 * radius / size as f32;
+
+        self.spheres.clear();
+        self.sticks.clear();
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        let style = scene.display.molecule_style;
+        if style == MoleculeStyle::Shells {
+            let casting = scene
+                .world
+                .spheres()
+                .filter(|sphere| sphere.material.opacity >= 1.0)
+                .filter(|sphere| sphere.material.shading == Shading::Lit);
+            for sphere in casting {
+                self.spheres.push(&sphere.model, &white);
+            }
+            for bond in &scene.bonds {
+                let (a, b) = (&scene.atoms[bond.a].position, &scene.atoms[bond.b].position);
+                for model in bond_models(a, b, bond.order) {
+                    self.sticks.push(&model, &white);
+                }
+            }
+        }
+        for ball in style::balls(style, &scene.atoms, &scene.bonds) {
+            let r = ball.radius;
+            let model = glm::scale(&glm::translation(&ball.center), &glm::vec3(r, r, r));
+            self.spheres.push(&model, &white);
+        }
+        for (model, _) in style::sticks(style, &scene.atoms, &scene.bonds) {
+            self.sticks.push(&model, &white);
+        }
+        for ejecta in &scene.ejecta {
+            for (center, r, _) in ejecta.spheres(&scene.display, scene.simulation.alpha()) {
+                let model = glm::scale(&glm::translation(&center), &glm::vec3(r, r, r));
+                self.spheres.push(&model, &white);
+            }
+        }
+
+        let previous = framebuffer::current_binding();
+        self.target.resize(size, size);
+        self.target.bind();
+        gl::Clear(gl::DEPTH_BUFFER_BIT);
+        gl::Enable(gl::POLYGON_OFFSET_FILL);
+        gl::PolygonOffset(2.0, 4.0);
+        self.program.use_program();
+        self.program
+            .set_uniform_mat4("lightSpace", &self.light_space);
+        self.spheres.draw();
+        self.sticks.draw();
+        gl::Disable(gl::POLYGON_OFFSET_FILL);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous);
+        check_gl_error!("shadow map");
+        self.drawn = true;
+    }
+
+    pub fn skip(&mut self) {
+        // no shadows this frame, apply switches them off in the shader
+        self.drawn = false;
+    }
+
+    pub unsafe fn apply(&self, program: &WatchedProgram) {
+        // hand the map to the lit shader, before anything is drawn with it
+        program.use_program();
+        // a sampler2DShadow and the sampler2D of the surfaces must never share a unit,
+        // not even while this one goes unused
+        program.set_uniform_i32("shadowMap", UNIT as i32);
+        program.set_uniform_i32("shadows", self.drawn as i32);
+        if self.drawn {
+            program.set_uniform_mat4("lightSpace", &self.light_space);
+            program.set_uniform_f32("normalOffset", NORMAL_OFFSET * self.texel);
+            self.target.bind_depth_texture(UNIT);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+}
+
+pub fn bounds(scene: &Scene) -> Option<(glm::Vec3, f32)> {
+    // a sphere around every atom and its shells, None for an empty scene
+    let first = scene.atoms.first()?;
+    let (mut low, mut high) = (first.position, first.position);
+    for scene_atom in &scene.atoms {
+        let r = scene_atom.atom.outer_radius();
+        let reach = glm::vec3(r, r, r);
+        low = glm::min2(&low, &(scene_atom.position - reach));
+        high = glm::max2(&high, &(scene_atom.position + reach));
+    }
+    let center = (low + high) * 0.5;
+    let radius = scene
+        .atoms
+        .iter()
+        .map(|scene_atom| {
+            glm::distance(&scene_atom.position, &center) + scene_atom.atom.outer_radius()
+        })
+        .fold(1.0, f32::max);
+    Some((center, radius))
+}
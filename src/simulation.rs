@@ -0,0 +1,800 @@
+use crate::decay; // didactic decay clock
+use crate::elements::{self, Element}; // periodic table lookups
+use crate::isotopes::{self, DecayMode, Isotope, Stability}; // neutron counts and half-lives
+use crate::thermal; // temperature jitter
+use crate::vibration::Vibration; // nucleons moving under their own forces
+use nalgebra_glm as glm; // vectors again, they follow us everywhere
+use std::f64::consts::PI; // circles, orbits, the usual
+
+// this is the bohr picture of an atom
+// yes it is wrong
+// yes electrons do not actually orbit like planets
+// but it is the picture everybody draws on the whiteboard
+// so here it is, animated
+//
+// positions, angles and times in here are f64: an f32 phase stepped 120 times a second at
+// 100x speed rounds off enough each step that electrons visibly drift out of their spacing
+// after a while; everything handed to the renderer (plane_rotation, in_plane_position,
+// jump_position, emissions) comes out as f32, that is the only place it gets converted
+// energies stay f32, they are computed fresh from the counts and never accumulate
+
+const SHELL_SPACING: f64 = 3.0;
+// distance between neighbouring shells in world units
+// real bohr radii grow like n^2 which makes outer shells absurdly far away
+// so we space them linearly (didactic scale, not true scale)
+
+const BASE_ANGULAR_VELOCITY: f64 = 2.0;
+// angular velocity of the innermost shell in rad/s
+// outer shells are slower, see Shell::new
+
+const RYDBERG_EV: f32 = 13.606;
+// hydrogen ground state binding energy, E_n = -13.6 eV * Z^2 / n^2 in the bohr model
+
+// semi-empirical mass formula coefficients in MeV (volume, surface, coulomb, asymmetry, pairing)
+const SEMF_VOLUME: f32 = 15.75;
+const SEMF_SURFACE: f32 = 17.8;
+const SEMF_COULOMB: f32 = 0.711;
+const SEMF_ASYMMETRY: f32 = 23.7;
+const SEMF_PAIRING: f32 = 11.18;
+
+// excitation: how long a jump between shells takes, and how long an excited electron
+// stays up before falling back (real lifetimes are nanoseconds, this is for watching)
+const JUMP_DURATION: f64 = 0.6;
+const EXCITED_LIFETIME: f64 = 2.0;
+
+pub const HC_EV_NM: f32 = 1239.84;
+// planck constant * speed of light in eV * nm, so wavelength = HC_EV_NM / energy
+
+// anions go up to this many electrons past neutral (N3-, O2-, Cl- and friends)
+const MAX_EXTRA_ELECTRONS: u32 = 3;
+
+// allowed range of TimeControl::scale
+pub const MIN_TIME_SCALE: f32 = 0.1;
+pub const MAX_TIME_SCALE: f32 = 100.0;
+
+// how much simulated time one single step advances, at scale 1
+const STEP_DT: f64 = 1.0 / 60.0;
+
+// the simulation always advances in steps of exactly this much simulated time
+// frames just decide how many steps to run, so the outcome does not depend on frame rate
+pub const FIXED_DT: f64 = 1.0 / 120.0;
+
+// at 100x speed a slow frame would ask for thousands of steps, and the next frame would be
+// even slower; past this the simulation falls behind instead
+const MAX_STEPS_PER_FRAME: u32 = 240;
+
+// repulsion mode: how fast electrons slide downhill on their shell sphere (per unit force,
+// per second), and how far one may move in a single step when two start out on top of
+// each other and the force is huge (radians on the unit sphere)
+const RELAX_RATE: f64 = 2.0;
+const MAX_RELAX_STEP: f64 = 0.02;
+
+// a little jiggle every step; electrons evenly spaced on the ring are in equilibrium, just
+// an unstable one, and without a nudge they would stay in the plane forever
+const RELAX_NOISE: f64 = 1e-3;
+
+// highest subshell any known element puts electrons in (s, p, d, f)
+pub const MAX_FILLED_L: usize = 3;
+
+pub struct Simulation {
+    // fixed timestep accumulator: collects simulated time, pays it out in FIXED_DT steps
+    // f64 like the rest of the clock, so 1/30 s frames come out as exactly 4 steps and not
+    // 3 then 5
+    accumulator: f64,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self { accumulator: 0.0 }
+    }
+
+    pub fn advance(&mut self, dt: f64) -> u32 {
+        // dt = simulated seconds this frame (after TimeControl), returns how many steps to run
+        self.accumulator += dt;
+        let mut steps = (self.accumulator / FIXED_DT) as u32;
+        if steps > MAX_STEPS_PER_FRAME {
+            log::debug!(
+                "simulation {} steps behind, dropping them",
+                steps - MAX_STEPS_PER_FRAME
+            );
+            steps = MAX_STEPS_PER_FRAME;
+            self.accumulator = FIXED_DT * steps as f64;
+        }
+        self.accumulator -= FIXED_DT * steps as f64;
+        steps
+    }
+
+    pub fn alpha(&self) -> f32 {
+        // how far into the next step we are, 0..1, for blending the last two states
+        (self.accumulator / FIXED_DT) as f32
+    }
+}
+
+pub struct TimeControl {
+    // turns real frame time into simulated time
+    // electrons are slow, nuclear wobble is fast, so the user gets a speed knob
+    pub scale: f32, // simulated seconds per real second, MIN_TIME_SCALE ..= MAX_TIME_SCALE
+    pub paused: bool, // frozen until resumed or stepped
+    pending_steps: u32, // single steps requested while paused
+}
+
+impl TimeControl {
+    pub fn new() -> Self {
+        Self {
+            scale: 1.0,
+            paused: false,
+            pending_steps: 0,
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.pending_steps = 0;
+    }
+
+    pub fn step(&mut self) {
+        // advance exactly one frame's worth of simulated time on the next tick
+        // stepping while running pauses first, otherwise nobody could see the step
+        self.paused = true;
+        self.pending_steps += 1;
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    }
+
+    pub fn tick(&mut self, real_dt: f32) -> f64 {
+        // call once per frame, returns how far the simulation should advance
+        if !self.paused {
+            return real_dt as f64 * self.scale as f64;
+        }
+        if self.pending_steps > 0 {
+            self.pending_steps -= 1;
+            return STEP_DT * self.scale as f64;
+        }
+        0.0
+    }
+}
+
+#[derive(Clone)]
+pub struct Electron {
+    // one electron riding on a shell
+    pub phase: f64,              // angle along the orbit in radians
+    pub direction: glm::DVec3, // unit vector in the untilted shell frame, on the ring unless repulsion moved it
+    pub decay_to: Option<usize>, // excited: shell index it falls back to, None = where it belongs
+    pub lifetime: f64,         // seconds until an excited electron falls back
+    pub spin_up: bool,         // m_s = +1/2, handed out by hund_filling, kept through jumps
+    pub jitter: glm::Vec3, // thermal offset from the orbit, untilted shell frame, see thermal.rs
+}
+
+#[derive(Clone)]
+pub struct Jump {
+    // an electron in flight between two shells, owned by the atom, not by either shell
+    pub from: usize, // shell indices
+    pub to: usize,
+    pub phase: f64,              // keeps orbiting while it moves
+    pub progress: f64,           // 0 = at from, 1 = arrived
+    pub decay_to: Option<usize>, // carried over to the electron once it lands
+    pub spin_up: bool,           // light does not flip spins, it lands with the one it left with
+}
+
+pub struct Emission {
+    // photon given off by a downward jump
+    pub position: glm::Vec3, // atom space, where the electron landed
+    pub energy: f32,         // eV
+}
+
+impl Emission {
+    pub fn wavelength(&self) -> f32 {
+        // nm, E = hc / lambda
+        HC_EV_NM / self.energy
+    }
+}
+
+#[derive(Clone)]
+pub struct Shell {
+    // one bohr shell
+    // all electrons on it share radius, speed and orbit plane
+    pub n: u32,      // principal quantum number of this shell (1 = K, 2 = L, ...)
+    pub radius: f64, // orbit radius
+    pub angular_velocity: f64, // rad/s
+    pub tilt: f64,   // rotation of the orbit plane around x, so shells dont all overlap
+    pub electrons: Vec<Electron>,
+}
+
+impl Shell {
+    pub fn new(n: u32, electron_count: u32) -> Self {
+        // bohr model says omega ~ 1/n^3
+        // keep that ratio so inner electrons visibly whip around faster
+        let radius = SHELL_SPACING * n as f64;
+        let angular_velocity = BASE_ANGULAR_VELOCITY / (n * n * n) as f64;
+
+        // tilt every shell a bit differently
+        // golden-ish angle so neighbouring shells never line up
+        let tilt = (n as f64 * 0.618 * PI) % PI;
+
+        // spread electrons evenly around the ring, spins as the ground state has them
+        let filling = hund_filling(n, electron_count as usize);
+        let electrons = (0..electron_count as usize)
+            .map(|i| {
+                let phase = 2.0 * PI * i as f64 / electron_count as f64;
+                Electron {
+                    phase,
+                    direction: ring_direction(phase),
+                    decay_to: None,
+                    lifetime: 0.0,
+                    spin_up: filling.get(i).map_or(i % 2 == 0, |&(_, _, up)| up),
+                    jitter: glm::vec3(0.0, 0.0, 0.0),
+                }
+            })
+            .collect();
+
+        Self {
+            n,
+            radius,
+            angular_velocity,
+            tilt,
+            electrons,
+        }
+    }
+
+    pub fn electron_count(&self) -> usize {
+        self.electrons.len()
+    }
+
+    pub fn spins(&self) -> (usize, usize) {
+        // electrons with spin up, spin down
+        let up = self.electrons.iter().filter(|e| e.spin_up).count();
+        (up, self.electrons.len() - up)
+    }
+
+    pub fn plane_rotation(&self) -> glm::Mat4 {
+        // orientation of the orbit plane relative to the atom
+        // orbits live in the xz plane, tilted around x
+        glm::rotation(self.tilt, &glm::vec3(1.0, 0.0, 0.0)).cast()
+    }
+
+    pub fn in_plane_position(&self, electron: &Electron) -> glm::Vec3 {
+        // where an electron sits on the untilted circle (or sphere, with repulsion on)
+        // combine with plane_rotation to get atom-space position
+        // a warm atom's electrons wander off it a little, see thermal.rs
+        (electron.direction * self.radius).cast() + electron.jitter
+    }
+
+    fn orbit(&mut self, dt: f64, repulsion: bool) {
+        // everybody moves along at the shell's angular velocity
+        // with repulsion the whole arrangement turns about the shell axis, so the spacing the
+        // electrons found stays as it is
+        for electron in &mut self.electrons {
+            electron.phase = (electron.phase + self.angular_velocity * dt) % (2.0 * PI);
+            if !repulsion {
+                electron.direction = ring_direction(electron.phase);
+                continue;
+            }
+            let d = electron.direction;
+            let horizontal = (d.x * d.x + d.z * d.z).sqrt();
+            electron.direction = glm::vec3(
+                horizontal * electron.phase.cos(),
+                d.y,
+                horizontal * electron.phase.sin(),
+            );
+        }
+    }
+
+    fn relax(&mut self, dt: f64) {
+        // thomson problem: electrons on a sphere push each other apart with 1/r^2 forces
+        // each one slides along the sphere in the direction of the force it feels (overdamped,
+        // no momentum), so they settle into the arrangement with the least energy:
+        // 2 opposite, 3 a triangle, 4 a tetrahedron, 6 an octahedron, ...
+        // forces are computed on the unit sphere, the radius only scales the picture
+        let directions: Vec<glm::DVec3> = self.electrons.iter().map(|e| e.direction).collect();
+        for (i, electron) in self.electrons.iter_mut().enumerate() {
+            let u = directions[i];
+            let mut force = glm::vec3(0.0, 0.0, 0.0);
+            for (j, other) in directions.iter().enumerate() {
+                let apart = u - other;
+                let distance = glm::length(&apart);
+                if j != i && distance > 1e-4 {
+                    force += apart / (distance * distance * distance);
+                }
+            }
+            let noise = glm::vec3(
+                rand::random::<f64>() - 0.5,
+                rand::random::<f64>() - 0.5,
+                rand::random::<f64>() - 0.5,
+            ) * RELAX_NOISE;
+            // only the part along the sphere moves anything, the rest is held by the shell
+            let tangent = force - u * glm::dot(&force, &u);
+            let mut step = tangent * RELAX_RATE * dt;
+            if glm::length(&step) > MAX_RELAX_STEP {
+                step = glm::normalize(&step) * MAX_RELAX_STEP;
+            }
+            electron.direction = glm::normalize(&(u + step + noise));
+            electron.phase = electron.direction.z.atan2(electron.direction.x);
+        }
+    }
+
+    pub fn spread(&mut self) {
+        // back onto the ring, evenly spaced again, in the order they are around it now
+        // indices stay put, a selected electron stays selected
+        let count = self.electrons.len();
+        let Some(start) = self.electrons.first().map(|e| e.phase) else {
+            return;
+        };
+        let mut order: Vec<usize> = (0..count).collect();
+        let around = |e: &Electron| (e.phase - start).rem_euclid(2.0 * PI);
+        order.sort_by(|&a, &b| around(&self.electrons[a]).total_cmp(&around(&self.electrons[b])));
+        for (rank, index) in order.into_iter().enumerate() {
+            let electron = &mut self.electrons[index];
+            electron.phase = start + 2.0 * PI * rank as f64 / count as f64;
+            electron.direction = ring_direction(electron.phase);
+        }
+    }
+}
+
+pub fn hund_filling(n: u32, count: usize) -> Vec<(usize, usize, bool)> {
+    // ground state of `count` electrons on shell n: (l, orbital within the subshell, spin up)
+    // of each, in filling order; subshells fill s, p, d, f in turn, and inside one subshell
+    // every orbital gets a spin up electron before any gets its spin down partner (hund),
+    // which is the only way to fit two in one orbital (pauli)
+    // stops when s to f are full, electrons past that (piled up by excitation) are not in it
+    let mut filling = Vec::with_capacity(count);
+    let mut remaining = count;
+    for l in 0..(n as usize).min(MAX_FILLED_L + 1) {
+        let orbitals = 2 * l + 1;
+        let here = remaining.min(2 * orbitals);
+        remaining -= here;
+        filling.extend((0..here).map(|i| (l, i % orbitals, i < orbitals)));
+    }
+    filling
+}
+
+pub fn ring_direction(phase: f64) -> glm::DVec3 {
+    // unit vector to the point at this angle on the untilted ring
+    glm::vec3(phase.cos(), 0.0, phase.sin())
+}
+
+#[derive(Clone)]
+pub struct Atom {
+    pub protons: u32,                   // atomic number Z
+    pub neutrons: u32,                  // N, so A = Z + N
+    pub shells: Vec<Shell>,             // innermost first
+    pub jumps: Vec<Jump>,               // electrons currently between shells
+    pub revision: u32, // bumped whenever electrons change shells, the scene rebuilds on it
+    pub nucleon_jitter: Vec<glm::Vec3>, // thermal offsets from the packed places, see thermal.rs
+    pub vibration: Option<Vibration>, // nucleon dynamics while switched on, see vibration.rs
+}
+
+impl Atom {
+    pub fn new(protons: u32, neutrons: u32, shell_occupancy: &[u32]) -> Self {
+        // shell_occupancy is the classic 2-8-8 style list
+        // index 0 = n=1 shell
+        let shells = shell_occupancy
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| Shell::new(i as u32 + 1, count))
+            .collect();
+
+        Self {
+            protons,
+            neutrons,
+            shells,
+            jumps: Vec::new(),
+            revision: 0,
+            nucleon_jitter: vec![glm::vec3(0.0, 0.0, 0.0); (protons + neutrons) as usize],
+            vibration: None,
+        }
+    }
+
+    pub fn from_atomic_number(z: u32) -> Option<Self> {
+        // neutral atom in its ground state, straight from the table
+        let element = elements::by_atomic_number(z)?;
+
+        Some(Self::new(z, element.neutron_count(), element.shells))
+    }
+
+    pub fn from_isotope(z: u32, mass_number: u32) -> Option<Self> {
+        // like from_atomic_number, but with A - Z neutrons instead of the usual count
+        // electrons do not care, only the nucleus changes
+        let element = elements::by_atomic_number(z)?;
+        let isotope = isotopes::isotope(z, mass_number)?;
+
+        Some(Self::new(z, isotope.neutrons(), element.shells))
+    }
+
+    pub fn isotope(&self) -> Isotope {
+        // atoms are only ever built from valid (Z, A) pairs
+        isotopes::isotope(self.protons, self.protons + self.neutrons)
+            .expect("atom with an impossible nucleus")
+    }
+
+    pub fn element(&self) -> &'static Element {
+        // which element this is depends only on Z
+        // electrons can come and go, protons define identity
+        elements::by_atomic_number(self.protons).expect("atom with invalid atomic number")
+    }
+
+    pub fn decay(&mut self, dt: f64) -> Option<DecayMode> {
+        // unstable nuclei roll the dice every step, chance from the half-life on the
+        // didactic clock (see decay.rs), 1 - e^(-ln2 dt / T)
+        // on a decay the nucleus becomes the daughter and the electrons its ground state
+        // (the daughter really starts out as an ion, it just settles down instantly here)
+        let Stability::Unstable { half_life, mode } = self.isotope().stability else {
+            return None;
+        };
+        let chance =
+            1.0 - (-std::f64::consts::LN_2 * dt / decay::screen_half_life(half_life) as f64).exp();
+        if dt <= 0.0 || rand::random::<f64>() >= chance {
+            return None;
+        }
+
+        let (protons, neutrons) = match mode {
+            DecayMode::Alpha => (self.protons - 2, self.neutrons - 2),
+            DecayMode::BetaMinus => (self.protons + 1, self.neutrons - 1),
+            DecayMode::BetaPlus | DecayMode::ElectronCapture => {
+                (self.protons - 1, self.neutrons + 1)
+            }
+        };
+        let shells = elements::by_atomic_number(protons)?.shells;
+        let revision = self.revision + 1;
+        *self = Self::new(protons, neutrons, shells);
+        self.revision = revision;
+        Some(mode)
+    }
+
+    pub fn step(
+        &mut self,
+        dt: f64,
+        repulsion: bool,
+        vibrating: bool,
+        temperature: f32,
+    ) -> Vec<Emission> {
+        // advance every electron along its orbit, and every jump along its way
+        // dt in seconds, so speed does not depend on frame rate
+        // repulsion: electrons on a shell also push each other around its sphere, see relax
+        // vibrating: nucleons move under their own forces, see vibration.rs
+        // temperature: kelvin, how much nucleons and electrons shake, see thermal.rs
+        // (nucleons only while not vibrating, the dynamics move them on their own then)
+        // returns the photons emitted by electrons that fell to a lower shell this step
+
+        if !vibrating {
+            self.vibration = None;
+        } else if self.vibration.is_none() {
+            self.vibration = Some(Vibration::new(self.protons, self.neutrons));
+        }
+        match &mut self.vibration {
+            Some(vibration) => {
+                vibration.step(dt);
+                vibration.offsets(&mut self.nucleon_jitter);
+            }
+            // the jitter is noise around a place, nothing adds up in it, f32 is plenty
+            None => {
+                for offset in &mut self.nucleon_jitter {
+                    thermal::shake_nucleon(offset, temperature, dt as f32);
+                }
+            }
+        }
+
+        let mut decaying = Vec::new();
+        for (shell_index, shell) in self.shells.iter_mut().enumerate() {
+            shell.orbit(dt, repulsion);
+            if repulsion {
+                shell.relax(dt);
+            }
+            for (index, electron) in shell.electrons.iter_mut().enumerate() {
+                thermal::shake_electron(
+                    &mut electron.jitter,
+                    temperature,
+                    shell.radius as f32,
+                    dt as f32,
+                );
+                if electron.decay_to.is_some() {
+                    electron.lifetime -= dt;
+                    if electron.lifetime <= 0.0 {
+                        decaying.push((shell_index, index));
+                    }
+                }
+            }
+        }
+        // back to front so the indices of the ones still to go stay valid
+        for &(shell, index) in decaying.iter().rev() {
+            let electron = self.shells[shell].electrons.remove(index);
+            self.jumps.push(Jump {
+                from: shell,
+                to: electron.decay_to.unwrap(),
+                phase: electron.phase,
+                progress: 0.0,
+                decay_to: None,
+                spin_up: electron.spin_up,
+            });
+            self.revision += 1;
+        }
+
+        let mut emissions = Vec::new();
+        let mut i = 0;
+        while i < self.jumps.len() {
+            let jump = &mut self.jumps[i];
+            let velocity = self.shells[jump.from].angular_velocity
+                + (self.shells[jump.to].angular_velocity - self.shells[jump.from].angular_velocity)
+                    * jump.progress;
+            jump.phase = (jump.phase + velocity * dt) % (2.0 * PI);
+            jump.progress += dt / JUMP_DURATION;
+
+            if jump.progress < 1.0 {
+                i += 1;
+                continue;
+            }
+
+            let jump = self.jumps.remove(i);
+            let electron = Electron {
+                phase: jump.phase,
+                direction: ring_direction(jump.phase),
+                decay_to: jump.decay_to,
+                lifetime: EXCITED_LIFETIME,
+                spin_up: jump.spin_up,
+                jitter: glm::vec3(0.0, 0.0, 0.0),
+            };
+            let to = &self.shells[jump.to];
+            let local = to.in_plane_position(&electron);
+            let landed = (to.plane_rotation() * glm::vec4(local.x, local.y, local.z, 1.0)).xyz();
+            self.shells[jump.to].electrons.push(electron);
+
+            if jump.to < jump.from {
+                // the energy difference leaves as light
+                // measured after landing, so screening reflects the new arrangement
+                let energy = self.electron_energy(jump.to) - self.electron_energy(jump.from);
+                emissions.push(Emission {
+                    position: landed,
+                    energy: -energy,
+                });
+            }
+            self.revision += 1;
+        }
+
+        emissions
+    }
+
+    pub fn excite(&mut self, electron: (usize, usize), to_shell: usize) -> bool {
+        // send electron (shell index, index in that shell) up to shell index to_shell
+        // it falls back on its own after EXCITED_LIFETIME, emitting a photon
+        // missing outer shells are created empty on the way
+        // returns false if there is no such electron or the target is not higher up
+        let (shell, index) = electron;
+        if shell >= self.shells.len()
+            || index >= self.shells[shell].electrons.len()
+            || to_shell <= shell
+        {
+            return false;
+        }
+
+        while self.shells.len() <= to_shell {
+            let n = self.shells.len() as u32 + 1;
+            self.shells.push(Shell::new(n, 0));
+        }
+
+        let electron = self.shells[shell].electrons.remove(index);
+        self.jumps.push(Jump {
+            from: shell,
+            to: to_shell,
+            phase: electron.phase,
+            progress: 0.0,
+            // an electron excited twice still wants to go all the way home
+            decay_to: Some(electron.decay_to.unwrap_or(shell)),
+            spin_up: electron.spin_up,
+        });
+        log::debug!(
+            "{}: electron excited from n={} to n={}",
+            self.element().symbol,
+            shell + 1,
+            to_shell + 1
+        );
+        self.revision += 1;
+        true
+    }
+
+    pub fn excite_outermost(&mut self) -> bool {
+        // key binding version: outermost ground-state electron goes up one shell
+        let Some(shell) = self
+            .shells
+            .iter()
+            .rposition(|shell| shell.electrons.iter().any(|e| e.decay_to.is_none()))
+        else {
+            return false;
+        };
+        let index = self.shells[shell]
+            .electrons
+            .iter()
+            .position(|e| e.decay_to.is_none())
+            .unwrap();
+        self.excite((shell, index), shell + 1)
+    }
+
+    pub fn jump_position(&self, jump: &Jump) -> glm::Vec3 {
+        // atom-space position of an electron in flight
+        // radius and orbit plane both blend from the old shell to the new one
+        // smoothstep so it eases off one ring and onto the other
+        let t = jump.progress.clamp(0.0, 1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        let (from, to) = (&self.shells[jump.from], &self.shells[jump.to]);
+        let radius = from.radius + (to.radius - from.radius) * t;
+        let tilt = from.tilt + (to.tilt - from.tilt) * t;
+        let local = glm::vec4(
+            radius * jump.phase.cos(),
+            0.0,
+            radius * jump.phase.sin(),
+            1.0,
+        );
+        (glm::rotation(tilt, &glm::vec3(1.0, 0.0, 0.0)) * local)
+            .xyz()
+            .cast()
+    }
+
+    pub fn electron_positions(&self) -> Vec<glm::Vec3> {
+        // atom space, everything on a shell and everything between shells
+        let on_shells = self.shells.iter().flat_map(|shell| {
+            let rotation = shell.plane_rotation();
+            shell.electrons.iter().map(move |electron| {
+                let local = shell.in_plane_position(electron);
+                (rotation * glm::vec4(local.x, local.y, local.z, 1.0)).xyz()
+            })
+        });
+        let jumping = self.jumps.iter().map(|jump| self.jump_position(jump));
+        on_shells.chain(jumping).collect()
+    }
+
+    pub fn electron_count(&self) -> usize {
+        self.shells
+            .iter()
+            .map(|shell| shell.electron_count())
+            .sum::<usize>()
+            + self.jumps.len()
+    }
+
+    pub fn charge(&self) -> i32 {
+        // net charge in units of e, protons minus electrons
+        self.protons as i32 - self.electron_count() as i32
+    }
+
+    pub fn ion_label(&self) -> Option<String> {
+        // "Na+", "Fe3+", "O2-", None for a neutral atom
+        let charge = self.charge();
+        let sign = if charge > 0 { '+' } else { '-' };
+        match charge.unsigned_abs() {
+            0 => None,
+            1 => Some(format!("{}{}", self.element().symbol, sign)),
+            n => Some(format!("{}{}{}", self.element().symbol, n, sign)),
+        }
+    }
+
+    pub fn ionize(&mut self) -> bool {
+        // knock out one electron, false if the nucleus is already bare
+        let count = self.electron_count() as u32;
+        count > 0 && self.set_electron_count(count - 1)
+    }
+
+    pub fn add_electron(&mut self) -> bool {
+        // capture one more electron, false past MAX_EXTRA_ELECTRONS
+        let count = self.electron_count() as u32;
+        count < self.protons + MAX_EXTRA_ELECTRONS && self.set_electron_count(count + 1)
+    }
+
+    pub fn land_electron(&mut self, n: u32, phase: f64) {
+        // one more electron on shell n, at that angle along the ring, with the spin the ground
+        // state gives it there; the builder's way in (see aufbau.rs), shells below are made
+        // if missing and the ring is spaced out evenly again
+        while self.shells.len() < n as usize {
+            self.shells
+                .push(Shell::new(self.shells.len() as u32 + 1, 0));
+        }
+        let shell = &mut self.shells[n as usize - 1];
+        let count = shell.electron_count();
+        let spin_up = hund_filling(n, count + 1)
+            .get(count)
+            .map_or(count.is_multiple_of(2), |&(_, _, up)| up);
+        shell.electrons.push(Electron {
+            phase,
+            direction: ring_direction(phase),
+            decay_to: None,
+            lifetime: 0.0,
+            spin_up,
+            jitter: glm::vec3(0.0, 0.0, 0.0),
+        });
+        shell.spread();
+        self.revision += 1;
+    }
+
+    pub fn remove_electron(&mut self, n: u32) -> bool {
+        // and out again: the last one put on shell n leaves, empty outer shells go with it
+        let Some(shell) = self.shells.get_mut((n as usize).wrapping_sub(1)) else {
+            return false;
+        };
+        if shell.electrons.pop().is_none() {
+            return false;
+        }
+        shell.spread();
+        while self
+            .shells
+            .last()
+            .is_some_and(|shell| shell.electrons.is_empty())
+        {
+            self.shells.pop();
+        }
+        self.revision += 1;
+        true
+    }
+
+    pub fn set_electron_count(&mut self, count: u32) -> bool {
+        // shells rebalance to the ground state of the neutral atom with that many electrons
+        // (Na+ gets neon's 2-8, Cl- argon's 2-8-8), any excitation is dropped on the way
+        let occupancy: &[u32] = if count == 0 {
+            &[]
+        } else {
+            match elements::by_atomic_number(count) {
+                Some(element) => element.shells,
+                None => return false,
+            }
+        };
+        self.shells = occupancy
+            .iter()
+            .enumerate()
+            .map(|(i, &electrons)| Shell::new(i as u32 + 1, electrons))
+            .collect();
+        self.jumps.clear();
+        self.revision += 1;
+        log::debug!(
+            "{} now has {} electrons, charge {:+}",
+            self.element().symbol,
+            count,
+            self.charge()
+        );
+        true
+    }
+
+    pub fn electron_energy(&self, shell: usize) -> f32 {
+        // bohr energy of an electron in shell index `shell`, in eV
+        // every electron further in screens one unit of nuclear charge (crude, but the
+        // outer electron of sodium sees Z_eff = 1 instead of 11, which is the right idea)
+        // shells the atom does not have yet count as empty ones, for lifting electrons there
+        let inner: usize = self
+            .shells
+            .iter()
+            .take(shell)
+            .map(|shell| shell.electron_count())
+            .sum();
+        let z_eff = (self.protons as f32 - inner as f32).max(1.0);
+        let n = shell as f32 + 1.0;
+        -RYDBERG_EV * z_eff * z_eff / (n * n)
+    }
+
+    pub fn binding_energy_per_nucleon(&self) -> f32 {
+        // weizsacker formula, in MeV, what it costs on average to pull one nucleon out
+        // meaningless for hydrogen-1 (a lone proton is not bound to anything)
+        let z = self.protons as f32;
+        let n = self.neutrons as f32;
+        let a = z + n;
+        if self.protons + self.neutrons < 2 {
+            return 0.0;
+        }
+
+        let pairing = match (self.protons % 2, self.neutrons % 2) {
+            (0, 0) => SEMF_PAIRING / a.sqrt(),
+            (1, 1) => -SEMF_PAIRING / a.sqrt(),
+            _ => 0.0,
+        };
+        let binding = SEMF_VOLUME * a
+            - SEMF_SURFACE * a.powf(2.0 / 3.0)
+            - SEMF_COULOMB * z * (z - 1.0) / a.cbrt()
+            - SEMF_ASYMMETRY * (n - z).powi(2) / a
+            + pairing;
+        (binding / a).max(0.0)
+    }
+
+    pub fn outer_radius(&self) -> f32 {
+        // radius of the outermost occupied shell, used for spacing atoms apart
+        self.shells
+            .iter()
+            .rev()
+            .find(|shell| shell.electron_count() > 0)
+            .map_or(0.0, |shell| shell.radius as f32)
+    }
+}
@@ -0,0 +1,298 @@
+use crate::camera::Camera; // P turns the plane toward the viewer
+use crate::orbital::{self, Orbital, NEGATIVE_COLOR, POSITIVE_COLOR};
+use crate::physics; // the complex cloud's |psi|^2
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::stats;
+use crate::text::TextRenderer; // caption under the slice
+use nalgebra_glm as glm;
+
+// cross-sections: a plane through the scene that cuts away everything on its front side,
+// so the inside of a probability cloud or a nucleus can be looked at
+// (P toggles it, the control panel moves it)
+// the cut happens in the vertex shaders with gl_ClipDistance, so a sphere the plane goes
+// through is sliced open rather than dropped
+//
+// optionally (Shift+P) a density map of the cut goes in a corner: |psi|^2 evaluated on a
+// grid across the plane, the same orbital as the view, brighter = more likely
+
+// grid points per side of the density map
+const SLICE_RESOLUTION: usize = 128;
+
+// on screen, in pixels
+const SLICE_SIZE: f32 = 256.0;
+const MARGIN: f32 = 10.0;
+
+// kept clear along the bottom for the spectrum bar
+const SPECTRUM_SPACE: f32 = 60.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct ClipPlane {
+    pub enabled: bool,
+    // direction of the normal in degrees, measured like the camera's angles
+    pub azimuth: f32,   // around the vertical axis, 0 = +x
+    pub elevation: f32, // down from straight up, 90 = level
+    pub offset: f32,    // distance of the plane from the origin along the normal
+}
+
+impl Default for ClipPlane {
+    fn default() -> Self {
+        // facing the default camera, through the nucleus
+        Self {
+            enabled: false,
+            azimuth: 0.0,
+            elevation: 90.0,
+            offset: 0.0,
+        }
+    }
+}
+
+impl ClipPlane {
+    pub fn normal(&self) -> glm::Vec3 {
+        // points at the side that is cut away
+        let (azimuth, elevation) = (self.azimuth.to_radians(), self.elevation.to_radians());
+        glm::vec3(
+            elevation.sin() * azimuth.cos(),
+            elevation.cos(),
+            elevation.sin() * azimuth.sin(),
+        )
+    }
+
+    pub fn equation(&self) -> glm::Vec4 {
+        // what the shaders get: dot(equation, (p, 1)) >= 0 is kept
+        let normal = self.normal();
+        glm::vec4(-normal.x, -normal.y, -normal.z, self.offset)
+    }
+
+    pub fn face(&mut self, camera: &Camera) {
+        // turn the cut side toward the camera, so the cut face is what it sees
+        let toward = camera.get_position() - camera.target;
+        if glm::length(&toward) < 1e-4 {
+            return;
+        }
+        let toward = glm::normalize(&toward);
+        self.elevation = toward.y.clamp(-1.0, 1.0).acos().to_degrees();
+        self.azimuth = toward.z.atan2(toward.x).to_degrees().rem_euclid(360.0);
+    }
+
+    fn axes(&self) -> (glm::Vec3, glm::Vec3) {
+        // two directions along the plane, for laying out the density grid
+        // "up" on the map is world up where that makes sense
+        let normal = self.normal();
+        let up = if normal.y.abs() > 0.99 {
+            glm::vec3(0.0, 0.0, 1.0)
+        } else {
+            glm::vec3(0.0, 1.0, 0.0)
+        };
+        let right = glm::normalize(&glm::cross(&up, &normal));
+        (right, glm::cross(&normal, &right))
+    }
+}
+
+// which |psi|^2 the map shows, follows the view mode
+#[derive(Clone, Copy, PartialEq)]
+pub enum SliceSource {
+    Real(Orbital),          // orbital view, the sign of psi colors the lobes
+    Complex(i32, i32, i32), // cloud view, physics.rs has no phase to show
+}
+
+impl SliceSource {
+    fn label(&self) -> String {
+        // ascii only, the hud font has no superscripts
+        match self {
+            SliceSource::Real(orbital) => format!(
+                "slice n={} l={} {}",
+                orbital.n,
+                orbital.l,
+                if orbital.subshell {
+                    "all m".to_string()
+                } else {
+                    format!("m={}", orbital.m)
+                }
+            ),
+            SliceSource::Complex(n, l, m) => format!("slice n={} l={} m={}", n, l, m),
+        }
+    }
+
+    pub fn quantum_numbers(&self) -> (i32, i32) {
+        // n and l, all the radial part depends on
+        match self {
+            SliceSource::Real(orbital) => (orbital.n, orbital.l),
+            SliceSource::Complex(n, l, _) => (*n, *l),
+        }
+    }
+
+    fn psi_squared(&self, p: &glm::DVec3) -> (f64, bool) {
+        // density at a point and whether psi is negative there
+        let r = glm::length(p);
+        let theta = if r > 0.0 {
+            (p.y / r).clamp(-1.0, 1.0).acos()
+        } else {
+            0.0
+        };
+        let phi = p.z.atan2(p.x);
+        match *self {
+            SliceSource::Real(orbital) if orbital.subshell => {
+                let density = (-orbital.l..=orbital.l)
+                    .map(|m| orbital::real_wavefunction(orbital.n, orbital.l, m, r, theta, phi))
+                    .map(|psi| psi * psi)
+                    .sum();
+                (density, false)
+            }
+            SliceSource::Real(orbital) => {
+                let psi =
+                    orbital::real_wavefunction(orbital.n, orbital.l, orbital.m, r, theta, phi);
+                (psi * psi, psi < 0.0)
+            }
+            SliceSource::Complex(n, l, m) => {
+                // |e^(i m phi)| = 1, what is left does not depend on phi
+                let psi = physics::radial_wavefunction(n, l, r)
+                    * physics::associated_legendre(l, m.abs(), theta.cos());
+                (psi * psi, false)
+            }
+        }
+    }
+}
+
+pub struct DensitySlice {
+    program: WatchedProgram,
+    empty_vao: gl::types::GLuint,
+    texture: gl::types::GLuint,
+    computed: Option<(SliceSource, ClipPlane)>, // what the texture shows, None = nothing yet
+}
+
+impl DensitySlice {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        let mut texture = 0;
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+        Ok(Self {
+            program: WatchedProgram::new("slice", ShaderProgram::slice)?,
+            empty_vao,
+            texture,
+            computed: None,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn draw(
+        &mut self,
+        source: SliceSource,
+        plane: &ClipPlane,
+        text: &mut TextRenderer,
+        width: u32,
+        height: u32,
+    ) {
+        // bottom right corner, above the spectrum bar; the caption is queued into the hud
+        // text batch, the caller flushes
+        if self.computed != Some((source, *plane)) {
+            self.upload(&source, plane);
+            self.computed = Some((source, *plane));
+        }
+        let x = width as f32 - MARGIN - SLICE_SIZE;
+        let y = height as f32 - SPECTRUM_SPACE - SLICE_SIZE;
+
+        let projection = glm::ortho(0.0, width as f32, height as f32, 0.0, -1.0, 1.0);
+        self.program.use_program();
+        self.program.set_uniform_mat4("projection", &projection);
+        self.program
+            .set_uniform_vec4("rect", &glm::vec4(x, y, SLICE_SIZE, SLICE_SIZE));
+        self.program.set_uniform_i32("density", 0);
+        gl::Disable(gl::DEPTH_TEST);
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, self.texture);
+        gl::BindVertexArray(self.empty_vao);
+        stats::count(gl::TRIANGLE_STRIP, 4, 1);
+        gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+        gl::BindVertexArray(0);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+        gl::Enable(gl::DEPTH_TEST);
+
+        text.draw_text(
+            &source.label(),
+            x,
+            y - 20.0,
+            2.0,
+            &glm::vec4(1.0, 1.0, 1.0, 1.0),
+        );
+    }
+
+    unsafe fn upload(&self, source: &SliceSource, plane: &ClipPlane) {
+        let pixels = density_image(source, plane);
+        gl::BindTexture(gl::TEXTURE_2D, self.texture);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA8 as i32, // overlay, srgb values go straight to the screen
+            SLICE_RESOLUTION as i32,
+            SLICE_RESOLUTION as i32,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_ptr() as *const _,
+        );
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+    }
+}
+
+impl Drop for DensitySlice {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture);
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
+
+fn density_image(source: &SliceSource, plane: &ClipPlane) -> Vec<u8> {
+    // rgba rows, bottom row first like gl wants it
+    // most of the density of shell n sits within 2 n^2 bohr radii of the nucleus
+    let n = source.quantum_numbers().0.max(1) as f64;
+    let half_size = 2.0 * n * n + 2.0;
+    let (right, up) = plane.axes();
+    let center = plane.normal() * plane.offset;
+    let to_f64 = |v: glm::Vec3| glm::vec3(v.x as f64, v.y as f64, v.z as f64);
+    let (right, up, center) = (to_f64(right), to_f64(up), to_f64(center));
+
+    let mut values = Vec::with_capacity(SLICE_RESOLUTION * SLICE_RESOLUTION);
+    for row in 0..SLICE_RESOLUTION {
+        for column in 0..SLICE_RESOLUTION {
+            let along = |i: usize| (i as f64 + 0.5) / SLICE_RESOLUTION as f64 * 2.0 - 1.0;
+            let p = center + right * (along(column) * half_size) + up * (along(row) * half_size);
+            values.push(source.psi_squared(&p));
+        }
+    }
+
+    // scaled to the brightest point of this slice, square root so the faint tails show
+    let max = values.iter().map(|v| v.0).fold(0.0, f64::max);
+    let mut pixels = Vec::with_capacity(values.len() * 4);
+    for (density, negative) in values {
+        let brightness = if max > 0.0 {
+            (density / max).sqrt() as f32
+        } else {
+            0.0
+        };
+        let color = if negative {
+            NEGATIVE_COLOR
+        } else {
+            POSITIVE_COLOR
+        };
+        let rgb = color.xyz() * brightness;
+        pixels.extend(
+            [rgb.x, rgb.y, rgb.z]
+                .iter()
+                .map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8),
+        );
+        pixels.push(255);
+    }
+    pixels
+}
@@ -0,0 +1,496 @@
+use crate::decay::random_direction; // side chains start out in any direction
+use crate::elements::{self, Element};
+use crate::import::MAX_ATOMS; // same limit as molecule files
+use crate::scene::{Bond, BondOrder, Scene, ViewMode};
+use crate::simulation::Atom;
+use crate::vsepr::{self, Geometry}; // the shape comes from the vsepr relaxation
+use nalgebra_glm as glm;
+use std::collections::HashMap;
+use std::fmt; // pretty errors
+
+// SMILES, a molecule typed as one line (smiles O=C=O in the console, or the control panel),
+// replacing whatever was in the scene
+// what is understood:
+//   atoms     B C N O P S F Cl Br I bare, filled up with hydrogens to their usual valence;
+//             anything else in brackets, [Na+], [13CH4], [NH4+], [O-2], which get exactly
+//             the hydrogens written; chirality (@) and atom classes (:1) are read and ignored
+//   bonds     - = #, / and \ count as single, nothing written between two atoms is single
+//   branches  C(C)C, as deep as wanted
+//   rings     C1CCCCC1, the digit closes a bond back to the atom that opened it, %10 and up
+//             for two digits; the bond symbol may sit on either end
+//   .         separates molecules, [Na+].[Cl-]
+// aromatic lowercase atoms (c1ccccc1) and : bonds are not, they would need a kekulé pass to
+// know where the double bonds go; the error says to write C1=CC=CC=C1 instead
+// SMILES has no coordinates: every atom starts a bond length from the one it hangs off,
+// roughly away from where that one came from, and molecule shapes (vsepr.rs) are switched
+// on to fold it into its real geometry over the next seconds, rings pulled shut included
+
+#[derive(Debug)]
+pub enum SmilesError {
+    Parse {
+        smiles: String,
+        column: usize, // 1-based, in characters
+        message: String,
+    },
+    Invalid {
+        smiles: String,
+        message: String,
+    }, // parsed, but nothing the scene can show
+}
+
+impl fmt::Display for SmilesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SmilesError::Parse {
+                smiles,
+                column,
+                message,
+            } => write!(
+                f,
+                "could not parse SMILES '{}' at character {}: {}",
+                smiles, column, message
+            ),
+            SmilesError::Invalid { smiles, message } => {
+                write!(f, "invalid SMILES '{}': {}", smiles, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SmilesError {}
+
+struct Parsed {
+    element: &'static Element,
+    mass_number: Option<u32>,
+    charge: i32,
+    hydrogens: Option<u32>, // None for bare atoms, they get the implicit ones
+    parent: Option<usize>,  // the atom it was written after, None starts a molecule
+}
+
+pub fn open(scene: &mut Scene, smiles: &str) -> Result<usize, SmilesError> {
+    // the molecule instead of the scene's atoms, returns how many there are (hydrogens too)
+    let invalid = |message: String| SmilesError::Invalid {
+        smiles: smiles.to_string(),
+        message,
+    };
+    let (parsed, mut bonds) = parse(smiles).map_err(|(column, message)| SmilesError::Parse {
+        smiles: smiles.to_string(),
+        column,
+        message,
+    })?;
+    if parsed.is_empty() {
+        return Err(invalid("no atoms in it".to_string()));
+    }
+
+    // the heavy atoms in the order written, then every hydrogen after the atom it sits on
+    let mut atoms = Vec::with_capacity(parsed.len());
+    let mut parents: Vec<Option<usize>> = Vec::with_capacity(parsed.len());
+    for record in &parsed {
+        let mut atom = match record.mass_number {
+            Some(mass_number) => Atom::from_isotope(record.element.atomic_number, mass_number),
+            None => Atom::from_atomic_number(record.element.atomic_number),
+        }
+        .ok_or_else(|| match record.mass_number {
+            Some(mass_number) => format!(
+                "{}-{} is not a known isotope",
+                record.element.symbol, mass_number
+            ),
+            None => format!("no atom for {}", record.element.symbol),
+        })
+        .map_err(invalid)?;
+        if record.charge != 0 {
+            let electrons = record.element.atomic_number as i32 - record.charge;
+            if electrons < 0 || !atom.set_electron_count(electrons as u32) {
+                return Err(invalid(format!(
+                    "{} cannot have a charge of {:+}",
+                    record.element.symbol, record.charge
+                )));
+            }
+        }
+        atoms.push(atom);
+        parents.push(record.parent);
+    }
+    let hydrogen = Atom::from_atomic_number(1).expect("hydrogen is element 1");
+    for (index, record) in parsed.iter().enumerate() {
+        for _ in 0..hydrogen_count(record, index, &bonds) {
+            bonds.push(Bond {
+                a: index,
+                b: atoms.len(),
+                order: BondOrder::Single,
+            });
+            atoms.push(hydrogen.clone());
+            parents.push(Some(index));
+        }
+    }
+    if atoms.len() > MAX_ATOMS {
+        return Err(invalid(format!(
+            "{} atoms with the hydrogens, at most {} can be simulated",
+            atoms.len(),
+            MAX_ATOMS
+        )));
+    }
+
+    let positions = layout(&atoms, &parents);
+    let extent = positions
+        .iter()
+        .zip(&atoms)
+        .map(|(position, atom)| glm::length(position) + atom.outer_radius())
+        .fold(0.0, f32::max);
+    let count = atoms.len();
+    scene.restore(
+        atoms
+            .into_iter()
+            .zip(positions)
+            .map(|(atom, position)| (atom, position, glm::quat_identity()))
+            .collect(),
+        bonds,
+    );
+    scene.geometry = Some(Geometry::new());
+    scene.view_mode = ViewMode::Bohr;
+    scene
+        .camera
+        .fly_to(glm::vec3(0.0, 0.0, 0.0), (2.0 * extent).max(30.0), 1.0);
+    log::info!("built {} atoms from {}", count, smiles);
+    Ok(count)
+}
+
+fn parse(smiles: &str) -> Result<(Vec<Parsed>, Vec<Bond>), (usize, String)> {
+    // errors carry the 1-based column they were found at
+    let chars: Vec<char> = smiles.trim().chars().collect();
+    let mut atoms: Vec<Parsed> = Vec::new();
+    let mut bonds: Vec<Bond> = Vec::new();
+    let mut previous: Option<usize> = None; // the atom the next one bonds to
+    let mut bond: Option<(BondOrder, usize)> = None; // written since that atom, and where
+    let mut branches: Vec<(Option<usize>, usize)> = Vec::new(); // atom to go back to, '(' at
+    let mut rings: HashMap<u32, (usize, Option<BondOrder>, usize)> = HashMap::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let c = chars[i];
+        i += 1;
+        match c {
+            '(' => {
+                if previous.is_none() {
+                    return Err((column, "a branch needs an atom before it".to_string()));
+                }
+                if bond.is_some() {
+                    return Err((column, "the bond goes inside the branch".to_string()));
+                }
+                branches.push((previous, column));
+            }
+            ')' => {
+                let Some((start, _)) = branches.pop() else {
+                    return Err((column, "')' without a '('".to_string()));
+                };
+                if bond.is_some() {
+                    return Err((column, "a bond with no atom after it".to_string()));
+                }
+                previous = start;
+            }
+            '-' | '=' | '#' | '/' | '\\' => {
+                if previous.is_none() {
+                    return Err((column, "a bond with no atom before it".to_string()));
+                }
+                if bond.is_some() {
+                    return Err((column, "two bonds in a row".to_string()));
+                }
+                // / and \ only say which side of a double bond things are on
+                bond = Some((
+                    BondOrder::from_symbol(c).unwrap_or(BondOrder::Single),
+                    column,
+                ));
+            }
+            ':' | '$' => {
+                return Err((
+                    column,
+                    "aromatic ':' and quadruple '$' bonds are not supported".to_string(),
+                ))
+            }
+            '.' => {
+                if bond.is_some() {
+                    return Err((column, "a bond with no atom after it".to_string()));
+                }
+                previous = None;
+            }
+            '0'..='9' | '%' => {
+                let number = if c == '%' {
+                    let digits: String = chars[i..].iter().take(2).collect();
+                    i += digits.len();
+                    digits
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|_| digits.len() == 2)
+                        .ok_or_else(|| (column, "'%' takes two digits".to_string()))?
+                } else {
+                    c.to_digit(10).unwrap_or(0)
+                };
+                let Some(here) = previous else {
+                    return Err((column, "a ring number needs an atom before it".to_string()));
+                };
+                let order = bond.take().map(|(order, _)| order);
+                match rings.remove(&number) {
+                    None => {
+                        rings.insert(number, (here, order, column));
+                    }
+                    Some((there, opened_with, _)) => {
+                        let order = match (opened_with, order) {
+                            (Some(a), Some(b)) if a != b => {
+                                return Err((
+                                    column,
+                                    format!(
+                                        "ring bond {} has a different order at each end",
+                                        number
+                                    ),
+                                ))
+                            }
+                            (Some(order), _) | (None, Some(order)) => order,
+                            (None, None) => BondOrder::Single,
+                        };
+                        if there == here || bonded(&bonds, there, here) {
+                            return Err((
+                                column,
+                                format!("ring bond {} closes onto an atom already bonded", number),
+                            ));
+                        }
+                        bonds.push(Bond {
+                            a: there,
+                            b: here,
+                            order,
+                        });
+                    }
+                }
+            }
+            '[' => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|&c| c == ']')
+                    .map(|offset| i + offset)
+                    .ok_or_else(|| (column, "'[' never closed".to_string()))?;
+                let inside: String = chars[i..end].iter().collect();
+                let parsed = bracket(&inside, previous).map_err(|message| (column, message))?;
+                i = end + 1;
+                add_atom(&mut atoms, &mut bonds, &mut previous, &mut bond, parsed);
+            }
+            _ if c.is_ascii_alphabetic() => {
+                // the organic subset, two letters first so Cl is not C and l
+                let two: String = chars[i - 1..(i + 1).min(chars.len())].iter().collect();
+                let symbol = if two == "Cl" || two == "Br" {
+                    i += 1;
+                    two
+                } else if "BCNOPSFI".contains(c) {
+                    c.to_string()
+                } else if "bcnops".contains(c) {
+                    return Err((column, aromatic(c)));
+                } else {
+                    return Err((
+                        column,
+                        format!(
+                            "'{}' needs brackets, only B C N O P S F Cl Br I go without",
+                            c
+                        ),
+                    ));
+                };
+                let element = elements::by_symbol(&symbol).expect("organic subset symbols");
+                let parsed = Parsed {
+                    element,
+                    mass_number: None,
+                    charge: 0,
+                    hydrogens: None,
+                    parent: previous,
+                };
+                add_atom(&mut atoms, &mut bonds, &mut previous, &mut bond, parsed);
+            }
+            _ => return Err((column, format!("'{}' does not belong in SMILES", c))),
+        }
+    }
+
+    if let Some((_, column)) = bond {
+        return Err((column, "a bond with no atom after it".to_string()));
+    }
+    if let Some(&(_, column)) = branches.last() {
+        return Err((column, "'(' never closed".to_string()));
+    }
+    if let Some((number, &(_, _, column))) = rings.iter().min_by_key(|(_, ring)| ring.2) {
+        return Err((column, format!("ring bond {} never closed", number)));
+    }
+    Ok((atoms, bonds))
+}
+
+fn add_atom(
+    atoms: &mut Vec<Parsed>,
+    bonds: &mut Vec<Bond>,
+    previous: &mut Option<usize>,
+    bond: &mut Option<(BondOrder, usize)>,
+    parsed: Parsed,
+) {
+    // the new atom bonds to the one before it with whatever bond was written between them
+    let index = atoms.len();
+    if let Some(before) = *previous {
+        bonds.push(Bond {
+            a: before,
+            b: index,
+            order: bond.take().map_or(BondOrder::Single, |(order, _)| order),
+        });
+    }
+    atoms.push(parsed);
+    *previous = Some(index);
+}
+
+fn bracket(inside: &str, parent: Option<usize>) -> Result<Parsed, String> {
+    // [isotope symbol chirality hydrogens charge :class], everything but the symbol optional
+    let chars: Vec<char> = inside.chars().collect();
+    let mut i = 0;
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while *i < chars.len() && chars[*i].is_ascii_digit() {
+            *i += 1;
+        }
+        chars[start..*i]
+            .iter()
+            .collect::<String>()
+            .parse::<u32>()
+            .ok()
+    };
+
+    let mass_number = digits(&mut i);
+    let Some(&first) = chars.get(i) else {
+        return Err(format!("no element in [{}]", inside));
+    };
+    if first.is_ascii_lowercase() {
+        return Err(aromatic(first));
+    }
+    // the longest symbol that is an element, written exactly like it
+    let exact = |symbol: &str| elements::by_symbol(symbol).filter(|e| e.symbol == symbol);
+    let element = match chars.get(i + 1).filter(|c| c.is_ascii_lowercase()) {
+        Some(&second) if exact(&format!("{}{}", first, second)).is_some() => {
+            i += 2;
+            exact(&format!("{}{}", first, second))
+        }
+        _ => {
+            i += 1;
+            exact(&first.to_string())
+        }
+    }
+    .ok_or_else(|| format!("no element in [{}]", inside))?;
+
+    let chiral = chars.get(i) == Some(&'@');
+    while chars.get(i) == Some(&'@') {
+        i += 1;
+    }
+    if chiral
+        && chars
+            .get(i)
+            .is_some_and(|c| c.is_ascii_uppercase() && *c != 'H')
+    {
+        i += 2; // @TH1, @SP2 and friends
+        digits(&mut i);
+    }
+    let hydrogens = if chars.get(i) == Some(&'H') {
+        i += 1;
+        Some(digits(&mut i).unwrap_or(1))
+    } else {
+        Some(0)
+    };
+    let mut charge = 0;
+    if let Some(&sign) = chars.get(i).filter(|c| **c == '+' || **c == '-') {
+        let unit = if sign == '+' { 1 } else { -1 };
+        i += 1;
+        charge = match digits(&mut i) {
+            Some(size) => unit * size as i32,
+            None => {
+                // ++ and -- are old ways of writing +2 and -2
+                let mut size = 1;
+                while chars.get(i) == Some(&sign) {
+                    size += 1;
+                    i += 1;
+                }
+                unit * size
+            }
+        };
+    }
+    if chars.get(i) == Some(&':') {
+        i += 1;
+        digits(&mut i);
+    }
+    if i < chars.len() {
+        return Err(format!("'{}' does not belong in [{}]", chars[i], inside));
+    }
+    Ok(Parsed {
+        element,
+        mass_number,
+        charge,
+        hydrogens,
+        parent,
+    })
+}
+
+fn aromatic(c: char) -> String {
+    format!(
+        "aromatic atoms like '{}' are not supported, write the rings with double bonds, \
+         benzene as C1=CC=CC=C1",
+        c
+    )
+}
+
+fn bonded(bonds: &[Bond], a: usize, b: usize) -> bool {
+    bonds
+        .iter()
+        .any(|bond| (bond.a, bond.b) == (a, b) || (bond.a, bond.b) == (b, a))
+}
+
+fn hydrogen_count(parsed: &Parsed, index: usize, bonds: &[Bond]) -> u32 {
+    // bracket atoms have theirs written, bare ones what their lowest usual valence that
+    // fits the bonds leaves over: C 4, N 3 or 5, O 2, S 2, 4 or 6, halogens 1
+    if let Some(hydrogens) = parsed.hydrogens {
+        return hydrogens;
+    }
+    let used: u32 = bonds
+        .iter()
+        .filter(|bond| bond.a == index || bond.b == index)
+        .map(|bond| bond.order.count() as u32)
+        .sum();
+    let valences: &[u32] = match parsed.element.symbol {
+        "B" => &[3],
+        "C" => &[4],
+        "N" | "P" => &[3, 5],
+        "O" => &[2],
+        "S" => &[2, 4, 6],
+        _ => &[1], // F, Cl, Br, I
+    };
+    valences
+        .iter()
+        .find(|&&valence| valence >= used)
+        .map_or(0, |valence| valence - used)
+}
+
+fn layout(atoms: &[Atom], parents: &[Option<usize>]) -> Vec<glm::Vec3> {
+    // a rough start for the relaxation, centered on the origin: every atom a bond length from
+    // its parent, carrying on the way the parent came with some wobble so chains do not run
+    // dead straight; separate molecules start side by side
+    const WOBBLE: f32 = 0.8;
+    let mut positions: Vec<glm::Vec3> = Vec::with_capacity(atoms.len());
+    for (index, atom) in atoms.iter().enumerate() {
+        let position = match parents[index] {
+            Some(parent) => {
+                let from = &positions[parent];
+                let onward = parents[parent]
+                    .map(|grandparent| glm::normalize(&(from - positions[grandparent])))
+                    .unwrap_or_else(random_direction);
+                let direction = glm::normalize(&(onward + random_direction() * WOBBLE));
+                from + direction * vsepr::bond_length(&atoms[parent], atom)
+            }
+            None if index == 0 => glm::vec3(0.0, 0.0, 0.0),
+            None => {
+                let right = positions.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+                glm::vec3(right + 2.0 * vsepr::bond_length(atom, atom), 0.0, 0.0)
+            }
+        };
+        positions.push(position);
+    }
+    let middle = positions
+        .iter()
+        .fold(glm::vec3(0.0, 0.0, 0.0), |sum, p| sum + p)
+        / positions.len() as f32;
+    positions.iter().map(|p| p - middle).collect()
+}
@@ -0,0 +1,164 @@
+use crate::elements::Element;
+use crate::photon::wavelength_color; // same colors as the photons
+use crate::text::TextRenderer; // bar is drawn with the hud text batch
+use nalgebra_glm as glm;
+
+// emission spectrum of the main atom, drawn as a bar along the bottom of the screen
+// hydrogen: rydberg formula, lyman / balmer / paschen
+// a handful of elements with famous lines: measured wavelengths from the lamp tables
+// everything else: the outermost electron treated as if it orbited a +1 core
+// (hydrogen-like with Z_eff = 1, starting at its own shell), which only gets the idea right
+
+const RYDBERG_INFINITY: f64 = 1.097_373_157e7; // 1/m
+const ELECTRON_MASS_U: f64 = 5.485_799e-4; // electron mass in u, for the reduced mass
+
+// visible range shown on the bar
+const VISIBLE_MIN: f32 = 380.0;
+const VISIBLE_MAX: f32 = 780.0;
+
+// strongest visible lines in nm (neutral atoms, nist / lamp catalogues)
+#[rustfmt::skip]
+const MEASURED_LINES: &[(u32, &[f32])] = &[
+    (2, &[447.1, 471.3, 492.2, 501.6, 587.6, 667.8, 706.5]),                  // He
+    (3, &[460.3, 610.4, 670.8]),                                               // Li
+    (10, &[585.2, 588.2, 603.0, 607.4, 616.4, 621.7, 626.6, 633.4, 640.2, 650.7, 692.9, 703.2]), // Ne
+    (11, &[498.3, 568.8, 589.0, 589.6]),                                       // Na
+    (12, &[516.7, 517.3, 518.4]),                                              // Mg
+    (18, &[696.5, 706.7, 738.4, 750.4, 763.5, 772.4]),                         // Ar
+    (19, &[404.4, 404.7, 766.5, 769.9]),                                       // K
+    (20, &[422.7, 616.2]),                                                     // Ca
+    (26, &[438.4, 440.5, 527.0, 532.8]),                                       // Fe
+    (29, &[510.6, 515.3, 521.8]),                                              // Cu
+    (30, &[468.0, 472.2, 481.1, 636.2]),                                       // Zn
+    (36, &[557.0, 587.1]),                                                     // Kr
+    (37, &[420.2, 421.6, 780.0]),                                              // Rb
+    (38, &[460.7]),                                                            // Sr
+    (48, &[467.8, 480.0, 508.6, 643.8]),                                       // Cd
+    (49, &[410.2, 451.1]),                                                     // In
+    (54, &[462.4, 467.1]),                                                     // Xe
+    (55, &[455.5, 459.3]),                                                     // Cs
+    (56, &[553.5]),                                                            // Ba
+    (80, &[404.7, 435.8, 546.1, 577.0, 579.1]),                                // Hg
+    (81, &[535.0]),                                                            // Tl
+];
+
+pub enum Source {
+    Rydberg,
+    Measured,
+    Estimate,
+}
+
+pub struct Spectrum {
+    pub lines: Vec<f32>, // wavelengths in nm, any range
+    pub source: Source,
+}
+
+pub fn emission_lines(element: &Element) -> Spectrum {
+    if element.atomic_number == 1 {
+        return Spectrum {
+            lines: rydberg_lines(1.0, element.atomic_mass, 1),
+            source: Source::Rydberg,
+        };
+    }
+
+    if let Some((_, lines)) = MEASURED_LINES
+        .iter()
+        .find(|(z, _)| *z == element.atomic_number)
+    {
+        return Spectrum {
+            lines: lines.to_vec(),
+            source: Source::Measured,
+        };
+    }
+
+    // valence electron on shell n sees the rest of the atom as a +1 charge
+    let valence = element.shells.len() as u32;
+    Spectrum {
+        lines: rydberg_lines(1.0, element.atomic_mass, valence),
+        source: Source::Estimate,
+    }
+}
+
+fn rydberg_lines(z: f64, mass: f64, lowest: u32) -> Vec<f32> {
+    // 1/lambda = R_M Z^2 (1/n_lower^2 - 1/n_upper^2)
+    // R_M is R_infinity corrected for a nucleus that is not infinitely heavy
+    // three series starting at n = lowest, six lines each
+    let rydberg = RYDBERG_INFINITY / (1.0 + ELECTRON_MASS_U / mass);
+    let mut lines = Vec::new();
+    for lower in lowest..lowest + 3 {
+        for upper in lower + 1..=lower + 6 {
+            let inverse =
+                rydberg * z * z * (1.0 / (lower * lower) as f64 - 1.0 / (upper * upper) as f64);
+            lines.push((1e9 / inverse) as f32);
+        }
+    }
+    lines
+}
+
+pub fn draw(text: &mut TextRenderer, element: &Element, width: u32, height: u32) {
+    // bar along the bottom: faint rainbow for orientation, bright lines on top, nm ticks
+    // queued into the text batch, the caller flushes
+    let spectrum = emission_lines(element);
+    let margin = 10.0;
+    let bar_height = 40.0;
+    let x0 = margin;
+    let bar_width = width as f32 - 2.0 * margin;
+    let y0 = height as f32 - margin - bar_height;
+    let to_x = |nm: f32| x0 + (nm - VISIBLE_MIN) / (VISIBLE_MAX - VISIBLE_MIN) * bar_width;
+
+    text.draw_rect(
+        x0,
+        y0,
+        bar_width,
+        bar_height,
+        &glm::vec4(0.0, 0.0, 0.0, 0.85),
+    );
+
+    // 2 px slices of the continuous spectrum at 20% brightness
+    let steps = (bar_width / 2.0) as usize;
+    for i in 0..steps {
+        let nm = VISIBLE_MIN + (VISIBLE_MAX - VISIBLE_MIN) * i as f32 / steps as f32;
+        let c = wavelength_color(nm) * 0.2;
+        text.draw_rect(
+            to_x(nm),
+            y0,
+            2.0,
+            bar_height,
+            &glm::vec4(c.x, c.y, c.z, 1.0),
+        );
+    }
+
+    let mut hidden = 0;
+    for &nm in &spectrum.lines {
+        if !(VISIBLE_MIN..=VISIBLE_MAX).contains(&nm) {
+            hidden += 1;
+            continue;
+        }
+        let c = wavelength_color(nm);
+        text.draw_rect(
+            to_x(nm) - 1.0,
+            y0,
+            3.0,
+            bar_height,
+            &glm::vec4(c.x, c.y, c.z, 1.0),
+        );
+    }
+
+    let grey = glm::vec4(0.8, 0.8, 0.8, 1.0);
+    for nm in [400.0, 500.0, 600.0, 700.0] {
+        let label = format!("{:.0}", nm);
+        let label_x = to_x(nm) - TextRenderer::text_width(&label, 1.0) * 0.5;
+        text.draw_text(&label, label_x, y0 + bar_height - 9.0, 1.0, &grey);
+    }
+
+    let source = match spectrum.source {
+        Source::Rydberg => "rydberg formula",
+        Source::Measured => "measured lines",
+        Source::Estimate => "hydrogen-like estimate",
+    };
+    let caption = format!(
+        "{} emission spectrum, {} ({} lines outside the visible range)",
+        element.symbol, source, hidden
+    );
+    text.draw_text(&caption, x0, y0 - 18.0, 2.0, &grey);
+}
@@ -0,0 +1,124 @@
+use crate::render::{
+    generate_quad, InstancedRenderer, ShaderError, ShaderProgram, VertexArray, WatchedProgram,
+};
+use nalgebra_glm as glm;
+
+// far away electrons and photons as camera-facing quads instead of sphere meshes
+// a ball a couple of pixels across looks the same either way, but even the coarsest
+// sphere level is 40 triangles and a quad is 2; with many atoms on screen most electrons
+// end up this small, so the switch happens on its own whenever a sphere shrinks below
+// SPRITE_RADIUS on screen (zooming in turns them back into spheres)
+// the quads sample a small generated texture: a lit ball with a soft round edge
+
+// projected radius in pixels below which a sphere becomes a sprite
+pub const SPRITE_RADIUS: f32 = 3.0;
+
+const TEXTURE_SIZE: usize = 32;
+
+pub struct SpriteRenderer {
+    program: WatchedProgram,
+    texture: gl::types::GLuint,
+    shaded: InstancedRenderer,  // lit like the spheres: electrons
+    glowing: InstancedRenderer, // flat color: photons
+}
+
+impl SpriteRenderer {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let pixels = sprite_image();
+        let mut texture = 0;
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA8 as i32, // shading factors, not colors, so no srgb
+            TEXTURE_SIZE as i32,
+            TEXTURE_SIZE as i32,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_ptr() as *const _,
+        );
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+
+        let quad = generate_quad();
+        Ok(Self {
+            program: WatchedProgram::new("sprite", ShaderProgram::sprite)?,
+            texture,
+            shaded: InstancedRenderer::new(VertexArray::from_mesh(&quad)),
+            glowing: InstancedRenderer::new(VertexArray::from_mesh(&quad)),
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub fn clear(&mut self) {
+        self.shaded.clear();
+        self.glowing.clear();
+    }
+
+    pub fn push(&mut self, model: &glm::Mat4, color: &glm::Vec4, shaded: bool) {
+        // model: the unit sphere transform the sphere would have been drawn with
+        if shaded {
+            self.shaded.push(model, color);
+        } else {
+            self.glowing.push(model, color);
+        }
+    }
+
+    pub unsafe fn draw(&mut self) {
+        // after the opaque spheres and photons: depth tested and written like them, the soft
+        // edge blends over whatever is already behind it
+        if self.shaded.instance_count() + self.glowing.instance_count() == 0 {
+            return;
+        }
+        // camera and cross-section come from the shared Frame block
+        self.program.use_program();
+        self.program.set_uniform_i32("sprite", 0);
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, self.texture);
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        for (batch, shaded) in [(&mut self.shaded, true), (&mut self.glowing, false)] {
+            self.program.set_uniform_i32("shaded", shaded as i32);
+            batch.draw();
+        }
+        gl::Disable(gl::BLEND);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+    }
+}
+
+impl Drop for SpriteRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
+
+fn sprite_image() -> Vec<u8> {
+    // rgba, r = diffuse light on a unit ball seen head on, a = coverage
+    // light from above, in front of the ball, with some ambient so the far side does not
+    // go black; at a few pixels nobody can tell where the light really comes from
+    let light = glm::normalize(&glm::vec3(-0.4, 0.5, 0.8));
+    let mut pixels = Vec::with_capacity(TEXTURE_SIZE * TEXTURE_SIZE * 4);
+    for row in 0..TEXTURE_SIZE {
+        for column in 0..TEXTURE_SIZE {
+            let along = |i: usize| (i as f32 + 0.5) / TEXTURE_SIZE as f32 * 2.0 - 1.0;
+            let (x, y) = (along(column), along(row));
+            let d = (x * x + y * y).sqrt();
+            let normal = glm::vec3(x, y, (1.0 - d * d).max(0.0).sqrt());
+            let diffuse = 0.25 + 0.75 * glm::dot(&normal, &light).max(0.0);
+            // soft rim over the outer fifth instead of a hard pixelated circle
+            let coverage = ((1.0 - d) / 0.2).clamp(0.0, 1.0);
+            pixels.extend_from_slice(&[(diffuse * 255.0) as u8, 0, 0, (coverage * 255.0) as u8]);
+        }
+    }
+    pixels
+}
@@ -0,0 +1,139 @@
+use crate::framebuffer::{self, ColorFormat, Framebuffer};
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::stats;
+use nalgebra_glm as glm;
+
+// ambient occlusion (Shift+O): contact shading where surfaces crowd each other, nucleons
+// packed in a nucleus, the balls of a space-filling molecule, so depth reads at a glance
+// the scene is drawn into a target whose depth is a texture, then:
+//   1. occlusion: per pixel, view space position and normal back from the depth buffer,
+//      then points over the hemisphere above it, the share of them inside geometry
+//   2. blur: separable and depth aware, horizontal then vertical, hides the noise
+//   3. composite: the frame times the occlusion into whatever was bound before begin()
+// the reach grows with the distance from the camera, so it looks the same zoomed in or out
+// samples per pixel follow the render quality; the cost is three fullscreen passes, weak
+// gpus switch it off
+// same begin / finish shape as Bloom, and nested inside it: bloom's target gets the
+// shaded frame, the glow is not darkened; the composite writes the scene's depth along,
+// depth of field reads it from there (see dof.rs)
+
+pub struct Ssao {
+    occlusion: WatchedProgram,
+    blur: WatchedProgram,
+    composite: WatchedProgram,
+    empty_vao: gl::types::GLuint, // fullscreen triangle, generated in the vertex shader
+    scene: Framebuffer,           // full resolution hdr frame, depth readable
+    ping: Framebuffer,            // occlusion, the blur goes back and forth between these
+    pong: Framebuffer,
+    previous: gl::types::GLuint, // framebuffer to composite into, remembered in begin()
+}
+
+impl Ssao {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        Ok(Self {
+            occlusion: WatchedProgram::new("ssao", ShaderProgram::ssao)?,
+            blur: WatchedProgram::new("ssao_blur", ShaderProgram::ssao_blur)?,
+            composite: WatchedProgram::new("ssao_composite", ShaderProgram::ssao_composite)?,
+            empty_vao,
+            scene: Framebuffer::with_depth_texture(ColorFormat::Rgba16F),
+            ping: Framebuffer::new(ColorFormat::R8, false),
+            pong: Framebuffer::new(ColorFormat::R8, false),
+            previous: 0,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.occlusion.poll();
+        self.blur.poll();
+        self.composite.poll();
+    }
+
+    pub unsafe fn begin(&mut self, width: u32, height: u32) {
+        // redirect drawing into the target with the depth texture
+        self.previous = framebuffer::current_binding();
+        self.scene.resize(width, height);
+        self.ping.resize(width, height);
+        self.pong.resize(width, height);
+        self.scene.bind();
+    }
+
+    pub unsafe fn finish(
+        &mut self,
+        projection: &glm::Mat4,
+        strength: f32,
+        radius: f32,
+        samples: i32,
+    ) {
+        gl::Disable(gl::DEPTH_TEST);
+        gl::BindVertexArray(self.empty_vao);
+        let inverse = glm::inverse(projection);
+        let (width, height) = self.scene.size();
+        let texel = glm::vec2(1.0 / width as f32, 1.0 / height as f32);
+
+        self.ping.bind();
+        self.occlusion.use_program();
+        self.occlusion.set_uniform_i32("depth", 0);
+        self.occlusion.set_uniform_mat4("projection", projection);
+        self.occlusion
+            .set_uniform_mat4("inverseProjection", &inverse);
+        self.occlusion.set_uniform_vec2("texel", &texel);
+        self.occlusion.set_uniform_f32("radius", radius);
+        self.occlusion.set_uniform_i32("samples", samples);
+        self.scene.bind_depth_texture(0);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+        self.blur.use_program();
+        self.blur.set_uniform_i32("occlusion", 0);
+        self.blur.set_uniform_i32("depth", 1);
+        self.blur.set_uniform_mat4("inverseProjection", &inverse);
+        self.scene.bind_depth_texture(1);
+        self.pong.bind();
+        self.blur
+            .set_uniform_vec2("direction", &glm::vec2(texel.x, 0.0));
+        self.ping.bind_texture(0);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+        self.ping.bind();
+        self.blur
+            .set_uniform_vec2("direction", &glm::vec2(0.0, texel.y));
+        self.pong.bind_texture(0);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous);
+        gl::Viewport(0, 0, width as i32, height as i32);
+        // depth is written through untested, a fullscreen triangle that is never in front
+        gl::Enable(gl::DEPTH_TEST);
+        gl::DepthFunc(gl::ALWAYS);
+        self.composite.use_program();
+        self.composite.set_uniform_i32("scene", 0);
+        self.composite.set_uniform_i32("occlusion", 1);
+        self.composite.set_uniform_i32("depth", 2);
+        self.composite.set_uniform_f32("strength", strength);
+        self.scene.bind_texture(0);
+        self.ping.bind_texture(1);
+        self.scene.bind_depth_texture(2);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::DepthFunc(gl::LESS);
+
+        for unit in [2, 1, 0] {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        gl::BindVertexArray(0);
+        check_gl_error!("ssao");
+    }
+}
+
+impl Drop for Ssao {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// what the gpu was asked to draw, for --benchmark (see benchmark.rs)
+// every gl::Draw* call in the app reports here right next to the call, global counters
+// like the gl state itself, so nothing has to be threaded through the renderer for it
+// egui paints with its own renderer and is not counted
+
+static DRAW_CALLS: AtomicU64 = AtomicU64::new(0);
+static TRIANGLES: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Copy, Default)]
+pub struct DrawStats {
+    pub draw_calls: u64,
+    pub triangles: u64, // lines and points count as calls, not triangles
+}
+
+pub fn count(mode: gl::types::GLenum, vertices: i32, instances: i32) {
+    let per_instance = match mode {
+        gl::TRIANGLES => vertices / 3,
+        gl::TRIANGLE_STRIP | gl::TRIANGLE_FAN => (vertices - 2).max(0),
+        _ => 0,
+    };
+    DRAW_CALLS.fetch_add(1, Ordering::Relaxed);
+    TRIANGLES.fetch_add(per_instance as u64 * instances as u64, Ordering::Relaxed);
+}
+
+// everything since the last take, and start over
+pub fn take() -> DrawStats {
+    DrawStats {
+        draw_calls: DRAW_CALLS.swap(0, Ordering::Relaxed),
+        triangles: TRIANGLES.swap(0, Ordering::Relaxed),
+    }
+}
@@ -0,0 +1,135 @@
+use crate::framebuffer::{self, ColorFormat, Framebuffer}; // one target per eye
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::stats;
+
+// stereo 3d (Shift+V): the scene drawn twice, once from each eye, then put together
+//   anaglyph      red-cyan glasses: the left eye's picture in red, the right's in cyan
+//   side by side  half width each, left eye on the left, for 3d tvs and headsets that
+//                 stretch each half back to the full screen (the picture looks squeezed
+//                 without one)
+// the eyes sit DisplaySettings::eye_separation times the camera distance apart, sideways,
+// and both look through the same window at the target, see Camera::eye; so the target is
+// right on the screen, nearer things stand out of it and further ones sink in
+// everything of the scene pass goes through both eyes (bloom, fxaa, overlays in the
+// world), the hud and the control panel are drawn once on top of the result
+// anaglyph colors are half-color: the left eye gets the brightness of its picture, not its
+// red, or every green and blue thing would be missing on that side and shimmer
+
+pub const MAX_EYE_SEPARATION: f32 = 0.1; // more and the eyes stop fusing the two pictures
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum StereoMode {
+    Off,
+    Anaglyph,
+    SideBySide,
+}
+
+impl StereoMode {
+    pub fn next(self) -> Self {
+        // Shift+V cycles through these
+        match self {
+            StereoMode::Off => StereoMode::Anaglyph,
+            StereoMode::Anaglyph => StereoMode::SideBySide,
+            StereoMode::SideBySide => StereoMode::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StereoMode::Off => "off",
+            StereoMode::Anaglyph => "anaglyph",
+            StereoMode::SideBySide => "side by side",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            StereoMode::Off,
+            StereoMode::Anaglyph,
+            StereoMode::SideBySide,
+        ]
+        .into_iter()
+        .find(|mode| mode.name() == name)
+    }
+
+    pub fn eye_size(self, width: u32, height: u32) -> (u32, u32) {
+        // what each eye is drawn at for a frame of this size
+        match self {
+            StereoMode::SideBySide => (width.div_ceil(2), height),
+            _ => (width, height),
+        }
+    }
+}
+
+pub struct Stereo {
+    program: WatchedProgram,
+    empty_vao: gl::types::GLuint,
+    eyes: [Framebuffer; 2],      // left, right
+    previous: gl::types::GLuint, // framebuffer to compose into, remembered in begin()
+}
+
+impl Stereo {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        Ok(Self {
+            program: WatchedProgram::new("stereo", ShaderProgram::stereo)?,
+            empty_vao,
+            eyes: [
+                Framebuffer::new(ColorFormat::Srgb8, true),
+                Framebuffer::new(ColorFormat::Srgb8, true),
+            ],
+            previous: 0,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn begin(&mut self) {
+        self.previous = framebuffer::current_binding();
+    }
+
+    pub unsafe fn bind_eye(&mut self, eye: usize, width: u32, height: u32) {
+        // 0 = left, 1 = right; draw that eye's frame after this
+        self.eyes[eye].resize(width, height);
+        self.eyes[eye].bind();
+    }
+
+    pub unsafe fn finish(&mut self) {
+        // back to the target of begin()
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous);
+    }
+
+    pub unsafe fn compose(&self, mode: StereoMode, width: u32, height: u32) {
+        // both eyes into whatever is bound now, one fullscreen triangle
+        gl::Viewport(0, 0, width as i32, height as i32);
+        gl::Disable(gl::DEPTH_TEST);
+
+        self.program.use_program();
+        self.program.set_uniform_i32("left", 0);
+        self.program.set_uniform_i32("right", 1);
+        self.program
+            .set_uniform_i32("sideBySide", (mode == StereoMode::SideBySide) as i32);
+        self.eyes[0].bind_texture(0);
+        self.eyes[1].bind_texture(1);
+        gl::BindVertexArray(self.empty_vao);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::BindVertexArray(0);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+
+        gl::Enable(gl::DEPTH_TEST);
+    }
+}
+
+impl Drop for Stereo {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::elements::Element;
+use crate::renderer::stick_models; // the same sticks as the bonds, thicker or thinner
+use crate::scene::{Bond, SceneAtom};
+use nalgebra_glm as glm;
+
+// molecule styles (Ctrl+Shift+B), how the bohr view draws its atoms:
+//   shells          every nucleon and electron, the default
+//   ball-and-stick  one ball per atom at 0.3 of its van der waals radius, sticks
+//                   between them colored half and half by the atoms at their ends
+//   space-filling   balls as big as the van der waals radius, overlapping where bonded,
+//                   the outline a molecule shows to other molecules
+//   wireframe       the bonds only, thin lines colored like the sticks
+// balls are in cpk colors from elements.rs, whatever the theme
+// the shells are far bigger than real atoms (see import.rs), so the real radii are brought
+// to the scene's size: world units per angstrom from its bonds, the distance between two
+// bonded nuclei against their covalent radii added up; without bonds the outer shells set
+// it, a lone atom's ball fills its shells
+// nucleons, electrons and their trails are not drawn in the other styles, photons, decay
+// products and the overlays are; clicking a ball selects its atom
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MoleculeStyle {
+    Shells,
+    BallAndStick,
+    SpaceFilling,
+    Wireframe,
+}
+
+impl MoleculeStyle {
+    pub fn next(self) -> Self {
+        // Ctrl+Shift+B cycles through these
+        match self {
+            MoleculeStyle::Shells => MoleculeStyle::BallAndStick,
+            MoleculeStyle::BallAndStick => MoleculeStyle::SpaceFilling,
+            MoleculeStyle::SpaceFilling => MoleculeStyle::Wireframe,
+            MoleculeStyle::Wireframe => MoleculeStyle::Shells,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MoleculeStyle::Shells => "shells",
+            MoleculeStyle::BallAndStick => "ball-and-stick",
+            MoleculeStyle::SpaceFilling => "space-filling",
+            MoleculeStyle::Wireframe => "wireframe",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            MoleculeStyle::Shells,
+            MoleculeStyle::BallAndStick,
+            MoleculeStyle::SpaceFilling,
+            MoleculeStyle::Wireframe,
+        ]
+        .into_iter()
+        .find(|style| style.name() == name)
+    }
+}
+
+const BALL: f32 = 0.3; // ball-and-stick ball, times the van der waals radius
+const STICK: f32 = 0.12; // angstrom, ball-and-stick stick radius
+const WIRE: f32 = 0.04; // angstrom, wireframe line radius
+const DOT: f32 = 0.15; // wireframe atom without bonds, times the van der waals radius
+const SPACING: f32 = 2.5; // between the sticks of a double or triple bond, times their radius
+const FALLBACK_RADIUS: u32 = 200; // pm, van der waals radius past lawrencium
+
+pub struct Ball {
+    pub atom: usize, // index into Scene::atoms
+    pub center: glm::Vec3,
+    pub radius: f32,
+    pub color: glm::Vec4, // srgb like every instance color
+}
+
+pub fn balls(style: MoleculeStyle, atoms: &[SceneAtom], bonds: &[Bond]) -> Vec<Ball> {
+    // one per atom, none for shells
+    if style == MoleculeStyle::Shells {
+        return Vec::new();
+    }
+    let scale = world_per_angstrom(atoms, bonds);
+    atoms
+        .iter()
+        .enumerate()
+        .map(|(index, scene_atom)| {
+            let element = scene_atom.atom.element();
+            let size = van_der_waals(element);
+            let radius = match style {
+                MoleculeStyle::BallAndStick => BALL * size,
+                MoleculeStyle::SpaceFilling => size,
+                // a round joint where the lines meet
+                _ if bonds.iter().any(|bond| bond.a == index || bond.b == index) => WIRE,
+                _ => DOT * size,
+            };
+            let c = element.cpk_color();
+            Ball {
+                atom: index,
+                center: scene_atom.position,
+                radius: radius * scale,
+                color: glm::vec4(c.x, c.y, c.z, 1.0),
+            }
+        })
+        .collect()
+}
+
+pub fn sticks(
+    style: MoleculeStyle,
+    atoms: &[SceneAtom],
+    bonds: &[Bond],
+) -> Vec<(glm::Mat4, glm::Vec4)> {
+    // unit cylinder transforms with their colors, each bond split at its middle
+    let radius = match style {
+        MoleculeStyle::BallAndStick => STICK,
+        MoleculeStyle::Wireframe => WIRE,
+        _ => return Vec::new(),
+    } * world_per_angstrom(atoms, bonds);
+    let mut sticks = Vec::new();
+    for bond in bonds {
+        let (a, b) = (&atoms[bond.a], &atoms[bond.b]);
+        let middle = (a.position + b.position) * 0.5;
+        for (from, to, end) in [(&a.position, &middle, a), (&middle, &b.position, b)] {
+            let c = end.atom.element().cpk_color();
+            let color = glm::vec4(c.x, c.y, c.z, 1.0);
+            for model in stick_models(from, to, bond.order, radius, radius * SPACING) {
+                sticks.push((model, color));
+            }
+        }
+    }
+    sticks
+}
+
+fn van_der_waals(element: &Element) -> f32 {
+    // angstrom
+    element.van_der_waals_radius().unwrap_or(FALLBACK_RADIUS) as f32 / 100.0
+}
+
+fn world_per_angstrom(atoms: &[SceneAtom], bonds: &[Bond]) -> f32 {
+    // how big an angstrom is in this scene, averaged over the bonds
+    let ratios: Vec<f32> = bonds
+        .iter()
+        .filter_map(|bond| {
+            let (a, b) = (&atoms[bond.a], &atoms[bond.b]);
+            let real = a.atom.element().covalent_radius()? + b.atom.element().covalent_radius()?;
+            Some(glm::distance(&a.position, &b.position) / (real as f32 / 100.0))
+        })
+        .collect();
+    if !ratios.is_empty() {
+        return ratios.iter().sum::<f32>() / ratios.len() as f32;
+    }
+    if atoms.is_empty() {
+        return 1.0;
+    }
+    atoms
+        .iter()
+        .map(|scene_atom| scene_atom.atom.outer_radius() / van_der_waals(scene_atom.atom.element()))
+        .sum::<f32>()
+        / atoms.len() as f32
+}
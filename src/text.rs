@@ -0,0 +1,418 @@
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram}; // text gets its own shader preset
+use crate::stats;
+use nalgebra_glm as glm;
+use std::mem; // buffer sizes
+use std::os::raw::c_void; // raw pointer casting
+
+// on-screen text: element symbol, shell occupancy, fps
+// no font files, no font crates, just a tiny 5x7 bitmap font baked into the binary
+// every glyph becomes one textured quad, all text of a frame is one draw call
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+// glyphs sit in 6x8 cells in the atlas, the extra column / row is letter spacing
+const CELL_WIDTH: usize = 6;
+const CELL_HEIGHT: usize = 8;
+const ATLAS_COLUMNS: usize = 16;
+const ATLAS_ROWS: usize = 6; // 16 * 6 = 96 slots for the 95 printable ascii chars
+
+// the one leftover atlas slot is filled solid, rectangles sample it
+const SOLID_SLOT: usize = 95;
+
+const FIRST_CHAR: u8 = b' ';
+const LAST_CHAR: u8 = b'~';
+
+// printable ascii from ' ' to '~'
+// one byte per row, top row first, bit 4 = leftmost pixel
+#[rustfmt::skip]
+const FONT: [[u8; GLYPH_HEIGHT]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // '!'
+    [0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // '#'
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // '$'
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // '%'
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // '&'
+    [0x04, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // '('
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // ')'
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // '*'
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x04, 0x04, 0x08], // ','
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // '.'
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // '/'
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // '0'
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // '1'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // '2'
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // '3'
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // '4'
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // '5'
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // '6'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // '7'
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // '8'
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ';'
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // '<'
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // '='
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // '>'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // '?'
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // '@'
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'A'
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // 'B'
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // 'C'
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // 'D'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // 'E'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // 'F'
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // 'G'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'H'
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'I'
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // 'J'
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // 'K'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // 'L'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // 'M'
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // 'N'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'O'
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // 'P'
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // 'Q'
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // 'R'
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // 'S'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // 'T'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'U'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'V'
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // 'W'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // 'X'
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // 'Y'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // 'Z'
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // '['
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // '\\'
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ']'
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // '_'
+    [0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // 'a'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // 'b'
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // 'c'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // 'd'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // 'e'
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // 'f'
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'g'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // 'h'
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // 'i'
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // 'j'
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // 'k'
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'l'
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // 'm'
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // 'n'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // 'o'
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // 'p'
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // 'q'
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // 'r'
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // 's'
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // 't'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // 'u'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'v'
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // 'w'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // 'x'
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'y'
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // 'z'
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // '{'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // '|'
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // '}'
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // '~'
+];
+
+// per vertex: position xy (pixels) + atlas uv + rgba
+const VERTEX_FLOATS: usize = 8;
+
+pub struct TextRenderer {
+    program: WatchedProgram,
+    texture: gl::types::GLuint, // the font atlas
+    vao: gl::types::GLuint,
+    vbo: gl::types::GLuint,
+    vertices: Vec<f32>, // quads queued by draw_text since the last flush
+}
+
+impl TextRenderer {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let program = WatchedProgram::new("text", ShaderProgram::text)?;
+
+        // unpack the bit rows into a single channel atlas image
+        let atlas_width = ATLAS_COLUMNS * CELL_WIDTH;
+        let atlas_height = ATLAS_ROWS * CELL_HEIGHT;
+        let mut pixels = vec![0u8; atlas_width * atlas_height];
+
+        for (index, glyph) in FONT.iter().enumerate() {
+            let cell_x = (index % ATLAS_COLUMNS) * CELL_WIDTH;
+            let cell_y = (index / ATLAS_COLUMNS) * CELL_HEIGHT;
+            for (row, bits) in glyph.iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                        pixels[(cell_y + row) * atlas_width + cell_x + column] = 255;
+                    }
+                }
+            }
+        }
+
+        let solid_x = (SOLID_SLOT % ATLAS_COLUMNS) * CELL_WIDTH;
+        let solid_y = (SOLID_SLOT / ATLAS_COLUMNS) * CELL_HEIGHT;
+        for row in solid_y..solid_y + CELL_HEIGHT {
+            pixels[row * atlas_width + solid_x..][..CELL_WIDTH].fill(255);
+        }
+
+        let mut texture = 0;
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        // one byte per texel, rows are tightly packed (gl assumes 4 byte row alignment by default)
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::R8 as i32,
+            atlas_width as i32,
+            atlas_height as i32,
+            0,
+            gl::RED,
+            gl::UNSIGNED_BYTE,
+            pixels.as_ptr() as *const c_void,
+        );
+        // nearest keeps pixel fonts crisp when scaled up
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+
+        // dynamic vbo, refilled every flush
+        let mut vao = 0;
+        let mut vbo = 0;
+        gl::GenVertexArrays(1, &mut vao);
+        gl::GenBuffers(1, &mut vbo);
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+        let stride = (VERTEX_FLOATS * mem::size_of::<f32>()) as i32;
+        let mut offset = 0;
+        for (location, size) in [2, 2, 4].into_iter().enumerate() {
+            gl::VertexAttribPointer(
+                location as u32,
+                size,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (offset * mem::size_of::<f32>()) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(location as u32);
+            offset += size as usize;
+        }
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl::BindVertexArray(0);
+
+        Ok(Self {
+            program,
+            texture,
+            vao,
+            vbo,
+            vertices: Vec::new(),
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub fn draw_text(&mut self, text: &str, x: f32, y: f32, scale: f32, color: &glm::Vec4) {
+        // queue text with its top-left corner at (x, y) in pixels, origin = top-left of the window
+        // scale 1 = 5x7 pixel glyphs, '\n' starts a new line
+        // nothing hits the screen until flush()
+        let atlas_width = (ATLAS_COLUMNS * CELL_WIDTH) as f32;
+        let atlas_height = (ATLAS_ROWS * CELL_HEIGHT) as f32;
+        let (w, h) = (GLYPH_WIDTH as f32 * scale, GLYPH_HEIGHT as f32 * scale);
+
+        let mut pen_x = x;
+        let mut pen_y = y;
+        for c in plain(text).chars() {
+            if c == '\n' {
+                pen_x = x;
+                pen_y += CELL_HEIGHT as f32 * scale;
+                continue;
+            }
+
+            // anything outside printable ascii and plain() shows up as '?'
+            let code = match u8::try_from(c) {
+                Ok(code) if (FIRST_CHAR..=LAST_CHAR).contains(&code) => code,
+                _ => b'?',
+            };
+
+            if code != b' ' {
+                let index = (code - FIRST_CHAR) as usize;
+                let u0 = ((index % ATLAS_COLUMNS) * CELL_WIDTH) as f32 / atlas_width;
+                let v0 = ((index / ATLAS_COLUMNS) * CELL_HEIGHT) as f32 / atlas_height;
+                let u1 = u0 + GLYPH_WIDTH as f32 / atlas_width;
+                let v1 = v0 + GLYPH_HEIGHT as f32 / atlas_height;
+                let (x0, y0, x1, y1) = (pen_x, pen_y, pen_x + w, pen_y + h);
+
+                self.push_quad([x0, y0, x1, y1], [u0, v0, u1, v1], color);
+            }
+
+            pen_x += CELL_WIDTH as f32 * scale;
+        }
+    }
+
+    pub fn draw_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: &glm::Vec4) {
+        // filled rectangle in the same pixel coordinates as draw_text
+        // goes into the same batch, so queue backgrounds before the text on them
+        // every corner samples the middle of the solid slot, nearest filtering does the rest
+        let atlas_width = (ATLAS_COLUMNS * CELL_WIDTH) as f32;
+        let atlas_height = (ATLAS_ROWS * CELL_HEIGHT) as f32;
+        let u = ((SOLID_SLOT % ATLAS_COLUMNS) * CELL_WIDTH + CELL_WIDTH / 2) as f32 / atlas_width;
+        let v =
+            ((SOLID_SLOT / ATLAS_COLUMNS) * CELL_HEIGHT + CELL_HEIGHT / 2) as f32 / atlas_height;
+        self.push_quad([x, y, x + width, y + height], [u, v, u, v], color);
+    }
+
+    pub fn text_width(text: &str, scale: f32) -> f32 {
+        // width in pixels of the longest line, without the trailing letter spacing
+        let longest = plain(text)
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        (longest * CELL_WIDTH).saturating_sub(CELL_WIDTH - GLYPH_WIDTH) as f32 * scale
+    }
+
+    fn push_quad(
+        &mut self,
+        [x0, y0, x1, y1]: [f32; 4],
+        [u0, v0, u1, v1]: [f32; 4],
+        color: &glm::Vec4,
+    ) {
+        // two triangles, atlas row 0 (v0) is the top of the glyph
+        for (px, py, u, v) in [
+            (x0, y0, u0, v0),
+            (x0, y1, u0, v1),
+            (x1, y1, u1, v1),
+            (x0, y0, u0, v0),
+            (x1, y1, u1, v1),
+            (x1, y0, u1, v0),
+        ] {
+            self.vertices.extend_from_slice(&[px, py, u, v]);
+            self.vertices.extend_from_slice(color.as_slice());
+        }
+    }
+
+    pub unsafe fn flush(&mut self, width: u32, height: u32) {
+        // draw everything queued since the last flush on top of the frame, then forget it
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        // y down so text coordinates match window / mouse coordinates
+        let projection = glm::ortho(0.0, width as f32, height as f32, 0.0, -1.0, 1.0);
+
+        self.program.use_program();
+        self.program.set_uniform_mat4("projection", &projection);
+        self.program.set_uniform_i32("glyphs", 0);
+
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, self.texture);
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+        gl::BufferData(
+            gl::ARRAY_BUFFER,
+            mem::size_of_val(self.vertices.as_slice()) as isize,
+            self.vertices.as_ptr() as *const c_void,
+            gl::STREAM_DRAW,
+        );
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+        // overlay: always on top, blended, leave depth alone
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+
+        gl::BindVertexArray(self.vao);
+        let vertices = (self.vertices.len() / VERTEX_FLOATS) as i32;
+        stats::count(gl::TRIANGLES, vertices, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, vertices);
+        gl::BindVertexArray(0);
+
+        gl::Disable(gl::BLEND);
+        gl::Enable(gl::DEPTH_TEST);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+
+        self.vertices.clear();
+    }
+}
+
+impl Drop for TextRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture);
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+        }
+    }
+}
+
+pub fn hud_size(framebuffer: u32, scale: f32) -> u32 {
+    // hud pixels are framebuffer pixels blown up by DisplaySettings::hud_scale, the overlays
+    // lay out in a screen that much smaller and flush() stretches it over the real one
+    ((framebuffer as f32 / scale).round() as u32).max(1)
+}
+
+// accented latin letters as the plain ones, the font has no room for them: a translated
+// "Sélénium" still reads "Selenium" on the hud and not "S?l?nium"; ß becomes ss
+const ACCENTED: [(&str, char); 30] = [
+    ("ÀÁÂÃÄÅĀĂĄ", 'A'),
+    ("àáâãäåāăą", 'a'),
+    ("ÇĆČ", 'C'),
+    ("çćč", 'c'),
+    ("ĎĐ", 'D'),
+    ("ďđ", 'd'),
+    ("ÈÉÊËĒĖĘĚ", 'E'),
+    ("èéêëēėęě", 'e'),
+    ("ĞĢ", 'G'),
+    ("ğģ", 'g'),
+    ("ÌÍÎÏĪĮİ", 'I'),
+    ("ìíîïīįı", 'i'),
+    ("ĹĻĽŁ", 'L'),
+    ("ĺļľł", 'l'),
+    ("ÑŃŅŇ", 'N'),
+    ("ñńņň", 'n'),
+    ("ÒÓÔÕÖØŌŐ", 'O'),
+    ("òóôõöøōő", 'o'),
+    ("ŔŘ", 'R'),
+    ("ŕř", 'r'),
+    ("ŚŞŠ", 'S'),
+    ("śşš", 's'),
+    ("ŢŤ", 'T'),
+    ("ţť", 't'),
+    ("ÙÚÛÜŪŮŰŲ", 'U'),
+    ("ùúûüūůűų", 'u'),
+    ("ÝŸ", 'Y'),
+    ("ýÿ", 'y'),
+    ("ŹŻŽ", 'Z'),
+    ("źżž", 'z'),
+];
+
+fn plain(text: &str) -> std::borrow::Cow<'_, str> {
+    if text.is_ascii() {
+        return text.into();
+    }
+    let mut plain = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            'ß' => plain.push_str("ss"),
+            c => plain.push(
+                ACCENTED
+                    .iter()
+                    .find(|(accented, _)| accented.contains(c))
+                    .map_or(c, |&(_, base)| base),
+            ),
+        }
+    }
+    plain.into()
+}
@@ -0,0 +1,256 @@
+use crate::material::SurfaceTexture;
+use image::RgbaImage;
+use std::fmt; // pretty errors
+
+// 2d textures: loaded from image files or generated, uploaded once, sampled by the lit
+// shader through the sphere uvs (see generate_sphere)
+// image is built with png support only, other formats report as unsupported
+
+#[derive(Debug)]
+pub enum TextureError {
+    Io { path: String, message: String },
+    Parse { path: String, message: String }, // not an image, or a format that is not built in
+    Invalid { path: String, message: String }, // decodes, but cannot be a texture
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::Io { path, message } => {
+                write!(f, "could not read texture '{}': {}", path, message)
+            }
+            TextureError::Parse { path, message } => {
+                write!(f, "could not decode texture '{}': {}", path, message)
+            }
+            TextureError::Invalid { path, message } => {
+                write!(f, "invalid texture '{}': {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Filter {
+    Nearest,   // blocky up close, for pixel art and lookup tables
+    Linear,    // smooth, but shimmers when shrunk without mipmaps
+    Trilinear, // linear between mipmap levels too, needs mipmaps
+}
+
+impl Filter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        // textures.filter in atomsim.toml
+        match name {
+            "nearest" => Some(Filter::Nearest),
+            "linear" => Some(Filter::Linear),
+            "trilinear" => Some(Filter::Trilinear),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TextureOptions {
+    pub filter: Filter,
+    pub mipmaps: bool,
+    pub repeat: bool, // wrap around at the edges instead of clamping, sphere uvs go all the way round
+    pub srgb: bool,   // color images are srgb, data (noise, masks) is not
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            filter: Filter::Trilinear,
+            mipmaps: true,
+            repeat: true,
+            srgb: true,
+        }
+    }
+}
+
+pub struct Texture2D {
+    id: gl::types::GLuint,
+}
+
+impl Texture2D {
+    pub unsafe fn load(path: &str, options: &TextureOptions) -> Result<Self, TextureError> {
+        let reader = image::ImageReader::open(path).map_err(|err| TextureError::Io {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+        let parse = |err: &dyn std::error::Error| TextureError::Parse {
+            path: path.to_string(),
+            message: err.to_string(),
+        };
+        let image = reader
+            .with_guessed_format()
+            .map_err(|err| parse(&err))?
+            .decode()
+            .map_err(|err| parse(&err))?
+            .to_rgba8();
+        if image.width() == 0 || image.height() == 0 {
+            return Err(TextureError::Invalid {
+                path: path.to_string(),
+                message: "image is empty".to_string(),
+            });
+        }
+        log::info!(
+            "loaded texture {} ({}x{})",
+            path,
+            image.width(),
+            image.height()
+        );
+        Ok(Self::from_image(&image, options))
+    }
+
+    pub unsafe fn from_image(image: &RgbaImage, options: &TextureOptions) -> Self {
+        let mut id = 0;
+        gl::GenTextures(1, &mut id);
+        gl::BindTexture(gl::TEXTURE_2D, id);
+        let format = if options.srgb {
+            gl::SRGB8_ALPHA8 // sampling hands back linear
+        } else {
+            gl::RGBA8
+        };
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            format as i32,
+            image.width() as i32,
+            image.height() as i32,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            image.as_raw().as_ptr() as *const _,
+        );
+        if options.mipmaps {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        }
+        // trilinear without mipmaps would sample levels that do not exist (= black)
+        let (min, mag) = match (options.filter, options.mipmaps) {
+            (Filter::Nearest, _) => (gl::NEAREST, gl::NEAREST),
+            (Filter::Trilinear, true) => (gl::LINEAR_MIPMAP_LINEAR, gl::LINEAR),
+            (Filter::Linear, true) => (gl::LINEAR_MIPMAP_NEAREST, gl::LINEAR),
+            (_, false) => (gl::LINEAR, gl::LINEAR),
+        };
+        let wrap = if options.repeat {
+            gl::REPEAT
+        } else {
+            gl::CLAMP_TO_EDGE
+        };
+        for (parameter, value) in [
+            (gl::TEXTURE_MIN_FILTER, min),
+            (gl::TEXTURE_MAG_FILTER, mag),
+            (gl::TEXTURE_WRAP_S, wrap),
+            (gl::TEXTURE_WRAP_T, wrap),
+        ] {
+            gl::TexParameteri(gl::TEXTURE_2D, parameter, value as i32);
+        }
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+        check_gl_error!("texture upload");
+        Self { id }
+    }
+
+    pub unsafe fn bind(&self, unit: u32) {
+        gl::ActiveTexture(gl::TEXTURE0 + unit);
+        gl::BindTexture(gl::TEXTURE_2D, self.id);
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+// image files that replace the generated surface textures, from [textures] in atomsim.toml
+pub struct TextureFiles<'a> {
+    pub filter: Filter,
+    pub proton: Option<&'a str>,
+    pub neutron: Option<&'a str>,
+    pub electron: Option<&'a str>,
+}
+
+// the textures materials can ask for, see SurfaceTexture
+pub struct SurfaceTextures {
+    plus: Texture2D,
+    fuzz: Texture2D,
+    minus: Texture2D,
+}
+
+impl SurfaceTextures {
+    pub unsafe fn new(files: &TextureFiles) -> Self {
+        let options = TextureOptions {
+            filter: files.filter,
+            ..TextureOptions::default()
+        };
+        // a file that does not load is not fatal, the generated texture stands in
+        let load = |file: Option<&str>, generated: fn() -> RgbaImage| {
+            let loaded = file.and_then(|path| match Texture2D::load(path, &options) {
+                Ok(texture) => Some(texture),
+                Err(err) => {
+                    log::warn!("{}\nusing the built-in texture", err);
+                    None
+                }
+            });
+            loaded.unwrap_or_else(|| Texture2D::from_image(&generated(), &options))
+        };
+        Self {
+            plus: load(files.proton, || charge_marks(true)),
+            fuzz: load(files.neutron, fuzz),
+            minus: load(files.electron, || charge_marks(false)),
+        }
+    }
+
+    pub fn get(&self, which: SurfaceTexture) -> &Texture2D {
+        match which {
+            SurfaceTexture::Plus => &self.plus,
+            SurfaceTexture::Fuzz => &self.fuzz,
+            SurfaceTexture::Minus => &self.minus,
+        }
+    }
+}
+
+// generated ones are 2:1 like the sphere's uv grid, so the marks come out round on it
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 128;
+
+fn charge_marks(plus: bool) -> RgbaImage {
+    // + or - signs , four around the equator and two rows of them, white with dark marks
+    // so it tints whatever color the material has
+    // each sign sits in a 64 x 64 cell
+    RgbaImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        let (cx, cy) = ((x % 64) as i32 - 32, (y % 64) as i32 - 32);
+        let bar = |along: i32, across: i32| along.abs() <= 18 && across.abs() <= 5;
+        let mark = bar(cx, cy) || (plus && bar(cy, cx));
+        let v = if mark { 60 } else { 255 };
+        image::Rgba([v, v, v, 255])
+    })
+}
+
+fn fuzz() -> RgbaImage {
+    // value noise, bright with darker blotches, for a soft, not quite solid nucleon
+    // a fixed seed, so every run (and screenshot) looks the same
+    const CELLS: u32 = 16;
+    let lattice = |i: u32, j: u32| {
+        // integer hash of the lattice point, wrapping in x so the seam does not show
+        let mut h = (i % CELLS).wrapping_mul(374_761_393) ^ j.wrapping_mul(668_265_263);
+        h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+        (h ^ (h >> 16)) as f32 / u32::MAX as f32
+    };
+    RgbaImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        let fx = x as f32 / WIDTH as f32 * CELLS as f32;
+        let fy = y as f32 / HEIGHT as f32 * (CELLS / 2) as f32;
+        let (i, j) = (fx as u32, fy as u32);
+        let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (smooth(fx.fract()), smooth(fy.fract()));
+        let top = lattice(i, j) + (lattice(i + 1, j) - lattice(i, j)) * tx;
+        let bottom = lattice(i, j + 1) + (lattice(i + 1, j + 1) - lattice(i, j + 1)) * tx;
+        let noise = top + (bottom - top) * ty;
+        let v = (180.0 + 75.0 * noise) as u8;
+        image::Rgba([v, v, v, 255])
+    })
+}
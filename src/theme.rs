@@ -0,0 +1,135 @@
+use crate::scene::MAX_SHELLS;
+use nalgebra_glm as glm;
+
+// every color the scene is drawn with, in one place, so a palette can be swapped as a whole
+// picked with colors.theme in atomsim.toml (single colors can still be overridden there)
+// or with K / the control panel while running; all srgb, see the Color section in the readme
+//   classic    - the colors this app always had: grey backdrop, red / blue nucleus
+//   dark       - near black background, brighter particles for projectors and dim rooms
+//   colorblind - okabe-ito palette, hues stay apart for every common color vision deficiency
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub proton: glm::Vec3,
+    pub neutron: glm::Vec3,
+    pub electrons: [glm::Vec3; MAX_SHELLS], // electron spheres, index 0 = n=1
+    pub trails: [glm::Vec3; MAX_SHELLS],    // trails, guide rings and exported shell rings
+    pub background: glm::Vec3,              // solid background / clear color
+    pub gradient_top: glm::Vec3,            // gradient background, top and bottom edge
+    pub gradient_bottom: glm::Vec3,
+    pub highlight: glm::Vec3, // selection outline, current element in the periodic table
+}
+
+pub const PRESETS: [&str; 3] = ["classic", "dark", "colorblind"];
+
+impl Theme {
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Self::classic()),
+            "dark" => Some(Self::dark()),
+            "colorblind" => Some(Self::colorblind()),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        // K key cycles through the presets
+        let index = PRESETS
+            .iter()
+            .position(|&name| name == self.name)
+            .unwrap_or(0);
+        Self::preset(PRESETS[(index + 1) % PRESETS.len()]).unwrap()
+    }
+
+    pub fn electron(&self, n: u32) -> glm::Vec3 {
+        self.electrons[shell_index(n)]
+    }
+
+    pub fn trail(&self, n: u32) -> glm::Vec3 {
+        self.trails[shell_index(n)]
+    }
+
+    fn classic() -> Self {
+        Self {
+            name: "classic",
+            proton: glm::vec3(0.9, 0.2, 0.2),
+            neutron: glm::vec3(0.3, 0.4, 0.9),
+            electrons: [glm::vec3(0.2, 0.6, 1.0); MAX_SHELLS],
+            // inner shells warm, outer shells cool
+            trails: [
+                glm::vec3(1.0, 0.4, 0.3),
+                glm::vec3(1.0, 0.7, 0.2),
+                glm::vec3(0.9, 1.0, 0.3),
+                glm::vec3(0.4, 1.0, 0.5),
+                glm::vec3(0.3, 0.9, 1.0),
+                glm::vec3(0.4, 0.5, 1.0),
+                glm::vec3(0.8, 0.4, 1.0),
+            ],
+            background: glm::vec3(0.3, 0.3, 0.3),
+            gradient_top: glm::vec3(0.12, 0.14, 0.22),
+            gradient_bottom: glm::vec3(0.02, 0.02, 0.04),
+            highlight: glm::vec3(1.0, 0.9, 0.3),
+        }
+    }
+
+    fn dark() -> Self {
+        // electrons share their trail's color, so a shell reads as one thing
+        let shells = [
+            glm::vec3(1.0, 0.35, 0.55),
+            glm::vec3(1.0, 0.6, 0.2),
+            glm::vec3(1.0, 0.95, 0.3),
+            glm::vec3(0.4, 1.0, 0.5),
+            glm::vec3(0.2, 0.9, 1.0),
+            glm::vec3(0.45, 0.5, 1.0),
+            glm::vec3(0.85, 0.4, 1.0),
+        ];
+        Self {
+            name: "dark",
+            proton: glm::vec3(1.0, 0.3, 0.35),
+            neutron: glm::vec3(0.35, 0.5, 1.0),
+            electrons: shells,
+            trails: shells,
+            background: glm::vec3(0.02, 0.02, 0.03),
+            gradient_top: glm::vec3(0.05, 0.06, 0.1),
+            gradient_bottom: glm::vec3(0.0, 0.0, 0.0),
+            highlight: glm::vec3(0.2, 1.0, 0.9),
+        }
+    }
+
+    fn colorblind() -> Self {
+        // okabe-ito: orange, sky blue, bluish green, yellow, blue, vermillion, reddish purple
+        let shells = [
+            glm::vec3(0.902, 0.624, 0.0),
+            glm::vec3(0.337, 0.706, 0.914),
+            glm::vec3(0.0, 0.620, 0.451),
+            glm::vec3(0.941, 0.894, 0.259),
+            glm::vec3(0.0, 0.447, 0.698),
+            glm::vec3(0.835, 0.369, 0.0),
+            glm::vec3(0.8, 0.475, 0.655),
+        ];
+        Self {
+            name: "colorblind",
+            // vermillion / blue is the pair that stays distinct for everyone
+            proton: glm::vec3(0.835, 0.369, 0.0),
+            neutron: glm::vec3(0.0, 0.447, 0.698),
+            electrons: shells,
+            trails: shells,
+            background: glm::vec3(0.15, 0.15, 0.15),
+            gradient_top: glm::vec3(0.2, 0.2, 0.2),
+            gradient_bottom: glm::vec3(0.05, 0.05, 0.05),
+            highlight: glm::vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+fn shell_index(n: u32) -> usize {
+    // heavier atoms than the palette has colors for keep the outermost one
+    (n as usize - 1).min(MAX_SHELLS - 1)
+}
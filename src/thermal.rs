@@ -0,0 +1,58 @@
+use nalgebra_glm as glm;
+
+// temperature (control panel slider, kelvin): a hot atom shakes, a cold one sits still
+// every nucleon gets a small random offset from its packed place and every electron one from
+// its orbit, both wandering the way a particle in a warm bath does: pulled back towards
+// where it belongs and kicked around at random (an ornstein-uhlenbeck process)
+// by equipartition the mean squared offset grows with kT, so the size goes with sqrt(T)
+// the scale is made up so the slider's range shows something: really a nucleus does not
+// stir below 1e10 K and an electron leaves its ground state long before it jiggles like this
+// at 0 K everything settles back in place over a few moments
+
+pub const MAX_TEMPERATURE: f32 = 5000.0;
+const NUCLEON_AMPLITUDE: f32 = 0.1; // world units at MAX_TEMPERATURE, a nucleon radius is 0.25
+const NUCLEON_TIME: f32 = 0.05; // seconds to forget a kick, a fast shiver
+const ELECTRON_AMPLITUDE: f32 = 0.06; // of the shell radius at MAX_TEMPERATURE
+const ELECTRON_TIME: f32 = 0.4; // slower, a drift about the orbit
+
+fn nucleon_spread(temperature: f32) -> f32 {
+    NUCLEON_AMPLITUDE * (temperature / MAX_TEMPERATURE).max(0.0).sqrt()
+}
+
+fn electron_spread(temperature: f32, shell_radius: f32) -> f32 {
+    ELECTRON_AMPLITUDE * shell_radius * (temperature / MAX_TEMPERATURE).max(0.0).sqrt()
+}
+
+pub fn shake_nucleon(offset: &mut glm::Vec3, temperature: f32, dt: f32) {
+    wander(offset, nucleon_spread(temperature), NUCLEON_TIME, dt);
+}
+
+pub fn shake_electron(offset: &mut glm::Vec3, temperature: f32, shell_radius: f32, dt: f32) {
+    wander(
+        offset,
+        electron_spread(temperature, shell_radius),
+        ELECTRON_TIME,
+        dt,
+    );
+}
+
+fn wander(offset: &mut glm::Vec3, spread: f32, time: f32, dt: f32) {
+    // exact ornstein-uhlenbeck step, whatever dt is: the offset keeps e^(-dt / time) of itself
+    // and gets just enough noise to stay about spread off per axis, however long it runs
+    let keep = (-dt / time).exp();
+    *offset = *offset * keep + gaussian() * (spread * (1.0 - keep * keep).sqrt());
+}
+
+fn gaussian() -> glm::Vec3 {
+    // three standard normals, box-muller twice over
+    let pair = || {
+        let u = 1.0 - rand::random::<f32>(); // (0, 1], ln(0) is no good
+        let v = rand::random::<f32>();
+        let r = (-2.0 * u.ln()).sqrt();
+        let angle = 2.0 * std::f32::consts::PI * v;
+        (r * angle.cos(), r * angle.sin())
+    };
+    let (x, y) = pair();
+    let (z, _) = pair();
+    glm::vec3(x, y, z)
+}
@@ -0,0 +1,57 @@
+use std::time::Instant; // monotonic clock, never jumps backwards
+
+// how long the last frame took, so everything that moves can scale by it
+// with vsync on a 144 hz screen frames are short, on a laptop without vsync they are chaotic
+// either way electrons and the camera should move at the same real speed
+
+// a frame longer than this (window dragged, debugger pause, ...) counts as this long
+// otherwise the simulation jumps a huge step and everything teleports
+const MAX_DT: f32 = 0.25;
+
+// weight of the newest frame in the fps average
+// small = steady readable number, large = reacts fast
+const FPS_SMOOTHING: f32 = 0.05;
+
+pub struct FrameTimer {
+    last: Instant,
+    dt: f32,  // seconds, last frame, already clamped
+    fps: f32, // exponentially smoothed, 0 until the first tick
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            last: now,
+            dt: 0.0,
+            fps: 0.0,
+        }
+    }
+
+    pub fn tick(&mut self) -> f32 {
+        // call exactly once per frame, returns the new dt
+        let now = Instant::now();
+        let raw = now.duration_since(self.last).as_secs_f32();
+        self.last = now;
+
+        if raw > 0.0 {
+            // fps uses the unclamped time, a stall should show up as a low number
+            self.fps = if self.fps == 0.0 {
+                1.0 / raw
+            } else {
+                self.fps * (1.0 - FPS_SMOOTHING) + FPS_SMOOTHING / raw
+            };
+        }
+
+        self.dt = raw.min(MAX_DT);
+        self.dt
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+}
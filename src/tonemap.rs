@@ -0,0 +1,109 @@
+use crate::framebuffer::{self, ColorFormat, Framebuffer};
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::stats;
+
+// hdr output (Ctrl+T): the frame stays half float all the way through bloom, then one pass
+// brings it down to what the screen can show, times the exposure first:
+//   off       cut off at 1.0 per channel, as it always was: bright electrons and photons
+//             and the glow around them flatten out to white
+//   reinhard  c / (1 + luminance), gentle, keeps hue, never quite reaches white
+//   aces      the filmic curve, more contrast, brights roll off smoothly into white
+// same begin / finish shape as Bloom, nested outside it, so the glow is added before the
+// squeeze; off skips the pass altogether
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ToneMapping {
+    Off,
+    Reinhard,
+    Aces,
+}
+
+impl ToneMapping {
+    pub fn next(self) -> Self {
+        // Ctrl+T cycles through these
+        match self {
+            ToneMapping::Off => ToneMapping::Reinhard,
+            ToneMapping::Reinhard => ToneMapping::Aces,
+            ToneMapping::Aces => ToneMapping::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ToneMapping::Off => "off",
+            ToneMapping::Reinhard => "reinhard",
+            ToneMapping::Aces => "aces",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [ToneMapping::Off, ToneMapping::Reinhard, ToneMapping::Aces]
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
+}
+
+pub struct ToneMap {
+    program: WatchedProgram,
+    empty_vao: gl::types::GLuint, // fullscreen triangle, generated in the vertex shader
+    scene: Framebuffer,           // full resolution hdr frame
+    previous: gl::types::GLuint,  // framebuffer to map into, remembered in begin()
+}
+
+impl ToneMap {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        let mut empty_vao = 0;
+        gl::GenVertexArrays(1, &mut empty_vao);
+        Ok(Self {
+            program: WatchedProgram::new("tonemap", ShaderProgram::tonemap)?,
+            empty_vao,
+            scene: Framebuffer::new(ColorFormat::Rgba16F, true),
+            previous: 0,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn begin(&mut self, width: u32, height: u32) {
+        // redirect drawing into the hdr target
+        self.previous = framebuffer::current_binding();
+        self.scene.resize(width, height);
+        self.scene.bind();
+    }
+
+    pub unsafe fn finish(&mut self, mode: ToneMapping, exposure: f32) {
+        // back to the original target at full size
+        let (width, height) = self.scene.size();
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous);
+        gl::Viewport(0, 0, width as i32, height as i32);
+        gl::Disable(gl::DEPTH_TEST);
+        self.program.use_program();
+        self.program.set_uniform_i32("scene", 0);
+        self.program.set_uniform_f32("exposure", exposure);
+        self.program.set_uniform_i32(
+            "mode",
+            match mode {
+                ToneMapping::Aces => 2,
+                _ => 1,
+            },
+        );
+        self.scene.bind_texture(0);
+        gl::BindVertexArray(self.empty_vao);
+        stats::count(gl::TRIANGLES, 3, 1);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::BindVertexArray(0);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+        gl::Enable(gl::DEPTH_TEST);
+        check_gl_error!("tone mapping");
+    }
+}
+
+impl Drop for ToneMap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.empty_vao);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use nalgebra_glm as glm;
+
+// the other way to turn the camera: a quaternion instead of azimuth / elevation
+// picked with camera.mode = "trackball" in atomsim.toml
+// the orbit camera has to stop just short of the poles (its up vector is always world y,
+// looking straight down along it has no sideways any more), which makes a top down look at
+// a flat orbital awkward; here dragging always turns around the axes of the screen as it
+// is right now, so the camera goes over the top and keeps going, and drags that do not
+// line up with the screen axes leave it rolled a little (free roll, nothing pulls it back
+// level)
+// the orientation maps camera space to world space: x = screen right, y = screen up,
+// z = from the target toward the camera (opengl cameras look down their -z)
+
+pub struct TrackballCamera {
+    pub orientation: glm::Quat,
+    pub goal_orientation: glm::Quat, // same easing as the orbit goals, see Camera::update
+}
+
+impl TrackballCamera {
+    // the orientation the orbit camera has at these angles, so switching or loading an
+    // older save file starts from the same view
+    pub fn from_angles(azimuth: f32, elevation: f32) -> Self {
+        let orientation = angles_to_quat(azimuth, elevation);
+        Self {
+            orientation,
+            goal_orientation: orientation,
+        }
+    }
+
+    pub fn set(&mut self, orientation: glm::Quat) {
+        self.orientation = glm::quat_normalize(&orientation);
+        self.goal_orientation = self.orientation;
+    }
+
+    pub fn update(&mut self, k: f32) {
+        // k = fraction of the way to the goal this frame, from Camera::update
+        self.orientation = glm::quat_normalize(&glm::quat_slerp(
+            &self.orientation,
+            &self.goal_orientation,
+            k,
+        ));
+    }
+
+    // mouse moved by dx, dy pixels while orbiting, speed in radians per pixel
+    // signs match the orbit camera: dragging right swings the camera right around the
+    // target, dragging down lifts it
+    pub fn drag(&mut self, dx: f32, dy: f32, speed: f32) {
+        let yaw = glm::quat_angle_axis(-dx * speed, &glm::vec3(0.0, 1.0, 0.0));
+        let pitch = glm::quat_angle_axis(-dy * speed, &glm::vec3(1.0, 0.0, 0.0));
+        // both axes in camera space, so the right-hand side
+        self.goal_orientation = glm::quat_normalize(&(self.goal_orientation * yaw * pitch));
+    }
+
+    // turn around the world vertical instead, for scripted camera swings
+    pub fn spin(&mut self, angle: f32) {
+        let turn = glm::quat_angle_axis(-angle, &glm::vec3(0.0, 1.0, 0.0));
+        self.goal_orientation = glm::quat_normalize(&(turn * self.goal_orientation));
+    }
+
+    pub fn axis(&self, local: glm::Vec3) -> glm::Vec3 {
+        // a camera space direction in world space
+        glm::quat_rotate_vec3(&self.orientation, &local)
+    }
+
+    // azimuth and elevation of the camera's current direction from the target, what an
+    // orbit camera needs to look from the same side (roll is lost)
+    pub fn angles(&self) -> (f32, f32) {
+        quat_to_angles(&self.orientation)
+    }
+
+    // same for where it is easing to
+    pub fn goal_angles(&self) -> (f32, f32) {
+        quat_to_angles(&self.goal_orientation)
+    }
+}
+
+fn quat_to_angles(orientation: &glm::Quat) -> (f32, f32) {
+    let back = glm::quat_rotate_vec3(orientation, &glm::vec3(0.0, 0.0, 1.0));
+    (back.z.atan2(back.x), back.y.clamp(-1.0, 1.0).acos())
+}
+
+fn angles_to_quat(azimuth: f32, elevation: f32) -> glm::Quat {
+    // the frame Camera::screen_axes builds for the orbit camera, as a rotation
+    let elevation = elevation.clamp(0.01, std::f32::consts::PI - 0.01);
+    let back = glm::vec3(
+        elevation.sin() * azimuth.cos(),
+        elevation.cos(),
+        elevation.sin() * azimuth.sin(),
+    );
+    let right = glm::normalize(&glm::cross(&glm::vec3(0.0, 1.0, 0.0), &back));
+    let up = glm::cross(&back, &right);
+    glm::mat3_to_quat(&glm::mat3(
+        right.x, up.x, back.x, //
+        right.y, up.y, back.y, //
+        right.z, up.z, back.z,
+    ))
+}
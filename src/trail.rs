@@ -0,0 +1,204 @@
+use crate::render::{ShaderError, ShaderProgram, WatchedProgram};
+use crate::scene::Scene;
+use crate::stats;
+use nalgebra_glm as glm;
+use std::mem; // buffer sizes
+use std::os::raw::c_void; // raw pointer casting
+
+// fading line behind every electron, so you can see the orbit it is tracing
+// each trail is a ring buffer living directly in a vbo:
+// one new position per frame overwrites the oldest one, nothing else is uploaded
+// the vertex shader works out the age of each point from its slot
+
+pub struct Trail {
+    vao: gl::types::GLuint,
+    vbo: gl::types::GLuint,
+    capacity: usize,         // max points kept
+    head: usize,             // next slot to write
+    count: usize,            // slots filled so far, capped at capacity
+    last: Option<glm::Vec3>, // last recorded position, to skip duplicates while paused
+}
+
+impl Trail {
+    pub unsafe fn new(capacity: usize) -> Self {
+        // capacity + 1 slots: the extra one mirrors slot 0
+        // so the strip can run from the oldest point across the wrap without a gap
+        let mut vao = 0;
+        let mut vbo = 0;
+        gl::GenVertexArrays(1, &mut vao);
+        gl::GenBuffers(1, &mut vbo);
+
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        gl::BufferData(
+            gl::ARRAY_BUFFER,
+            ((capacity + 1) * 3 * mem::size_of::<f32>()) as isize,
+            std::ptr::null(),
+            gl::DYNAMIC_DRAW,
+        );
+        gl::VertexAttribPointer(
+            0,
+            3,
+            gl::FLOAT,
+            gl::FALSE,
+            (3 * mem::size_of::<f32>()) as i32,
+            std::ptr::null(),
+        );
+        gl::EnableVertexAttribArray(0);
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl::BindVertexArray(0);
+
+        Self {
+            vao,
+            vbo,
+            capacity,
+            head: 0,
+            count: 0,
+            last: None,
+        }
+    }
+
+    pub unsafe fn push(&mut self, position: glm::Vec3) {
+        // record one point, overwriting the oldest once full
+        if self.last == Some(position) {
+            return;
+        }
+        self.last = Some(position);
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+        let write = |slot: usize| {
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                (slot * 3 * mem::size_of::<f32>()) as isize,
+                (3 * mem::size_of::<f32>()) as isize,
+                position.as_ptr() as *const c_void,
+            );
+        };
+        write(self.head);
+        if self.head == 0 {
+            write(self.capacity);
+        }
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+        self.head = (self.head + 1) % self.capacity;
+        self.count = (self.count + 1).min(self.capacity);
+    }
+
+    unsafe fn draw(&self, program: &ShaderProgram) {
+        if self.count < 2 {
+            return;
+        }
+
+        program.set_uniform_i32("head", self.head as i32);
+        program.set_uniform_i32("count", self.count as i32);
+        program.set_uniform_i32("capacity", self.capacity as i32);
+
+        gl::BindVertexArray(self.vao);
+        if self.count < self.capacity {
+            // not wrapped yet: slots 0..head in order
+            stats::count(gl::LINE_STRIP, self.head as i32, 1);
+            gl::DrawArrays(gl::LINE_STRIP, 0, self.head as i32);
+        } else {
+            // oldest part head..=capacity (the last slot is slot 0 again), then 0..head
+            stats::count(gl::LINE_STRIP, (self.capacity - self.head + 1) as i32, 1);
+            gl::DrawArrays(
+                gl::LINE_STRIP,
+                self.head as i32,
+                (self.capacity - self.head + 1) as i32,
+            );
+            if self.head > 1 {
+                stats::count(gl::LINE_STRIP, self.head as i32, 1);
+                gl::DrawArrays(gl::LINE_STRIP, 0, self.head as i32);
+            }
+        }
+        gl::BindVertexArray(0);
+    }
+}
+
+impl Drop for Trail {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+        }
+    }
+}
+
+pub struct Trails {
+    // one trail per electron in the scene
+    program: WatchedProgram,
+    trails: Vec<(u32, Trail)>, // (shell n, trail), same order as Scene::electron_positions
+    length: usize,             // capacity the current trails were built with
+}
+
+impl Trails {
+    pub unsafe fn new() -> Result<Self, ShaderError> {
+        Ok(Self {
+            program: WatchedProgram::new("trail", ShaderProgram::trail)?,
+            trails: Vec::new(),
+            length: 0,
+        })
+    }
+
+    pub unsafe fn poll_shaders(&mut self) {
+        self.program.poll();
+    }
+
+    pub unsafe fn record(&mut self, scene: &Scene) {
+        // append this frame's electron positions
+        // electrons appearing/disappearing (element switch) or a new length = start over
+        // (the quality going down shortens them too, see Quality::max_trail)
+        let electrons = scene.electron_positions();
+        let length = scene
+            .display
+            .trail_length
+            .min(scene.display.quality.max_trail());
+
+        let layout_changed = electrons.len() != self.trails.len()
+            || electrons
+                .iter()
+                .zip(&self.trails)
+                .any(|((n, _), (trail_n, _))| n != trail_n);
+
+        if layout_changed || length != self.length {
+            self.length = length;
+            self.trails = if length < 2 {
+                Vec::new()
+            } else {
+                electrons
+                    .iter()
+                    .map(|&(n, _)| (n, Trail::new(length)))
+                    .collect()
+            };
+        }
+
+        for ((_, position), (_, trail)) in electrons.into_iter().zip(&mut self.trails) {
+            trail.push(position);
+        }
+    }
+
+    pub unsafe fn draw(&self, scene: &Scene) {
+        if self.trails.is_empty() {
+            return;
+        }
+
+        self.program.use_program(); // camera from the shared Frame block
+
+        // translucent lines: blend, but dont write depth or they would clip each other
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::DepthMask(gl::FALSE);
+
+        for (n, trail) in &self.trails {
+            if !scene.display.shell_visible(*n) {
+                continue;
+            }
+            self.program
+                .set_uniform_vec3("color", &scene.display.theme.trail(*n));
+            trail.draw(&self.program);
+        }
+
+        gl::DepthMask(gl::TRUE);
+        gl::Disable(gl::BLEND);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::quality::Quality; // sphere detail
+use crate::render::{generate_sphere, InstancedRenderer, VertexArray};
+use nalgebra_glm as glm;
+
+// alpha blended spheres, drawn after everything opaque
+// "over" blending depends on order: a far translucent sphere drawn after a near one paints over
+// it, and with depth writes on it would not show up behind it at all
+// so translucent things are not drawn where they are produced, they are queued here,
+// sorted back to front by view-space depth every frame and drawn in that order,
+// depth tested against the opaque scene but without writing depth
+// (additive things, like particles and the orbital points, do not care about order and skip this)
+
+struct Item {
+    model: glm::Mat4,
+    color: glm::Vec4,
+    depth: f32, // view-space z of the center, more negative = further away
+}
+
+pub struct TransparentPass {
+    items: Vec<Item>,
+    spheres: InstancedRenderer,
+}
+
+impl TransparentPass {
+    pub unsafe fn new(quality: Quality) -> Self {
+        let (sectors, stacks) = quality.tessellation(24, 16);
+        Self {
+            items: Vec::new(),
+            spheres: InstancedRenderer::new(VertexArray::from_mesh(&generate_sphere(
+                1.0, sectors, stacks,
+            ))),
+        }
+    }
+
+    pub fn push(&mut self, model: glm::Mat4, color: glm::Vec4, view: &glm::Mat4) {
+        // unit sphere instance, placed by model, queued until flush
+        let center = model.column(3);
+        let depth = (view * glm::vec4(center.x, center.y, center.z, 1.0)).z;
+        self.items.push(Item {
+            model,
+            color,
+            depth,
+        });
+    }
+
+    pub unsafe fn flush(&mut self) {
+        // draw and forget everything queued, with whatever program the caller set up
+        if self.items.is_empty() {
+            return;
+        }
+        // instances are rasterized in buffer order, so sorting the buffer sorts the draw
+        self.items.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+        self.spheres.clear();
+        for item in self.items.drain(..) {
+            self.spheres.push(&item.model, &item.color);
+        }
+
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::DepthMask(gl::FALSE);
+        // front faces only, the far half of a sphere would otherwise blend over its near half
+        gl::Enable(gl::CULL_FACE);
+        gl::CullFace(gl::BACK);
+        self.spheres.draw();
+        gl::Disable(gl::CULL_FACE);
+        gl::DepthMask(gl::TRUE);
+        gl::Disable(gl::BLEND);
+    }
+}
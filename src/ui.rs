@@ -0,0 +1,721 @@
+use crate::absorption; // lamp wavelength range and the lines it can hit
+use crate::elements; // element picker contents
+use crate::field::FieldView; // per atom field view
+use crate::ground::GroundMode; // ground radio buttons
+use crate::locale::{fill, text as t}; // every label in the configured language
+use crate::orbital::MAX_N; // quantum number sliders
+use crate::quality::Quality; // quality radio buttons
+use crate::scattering; // slider ranges
+use crate::scene::{BackgroundMode, Scene, MAX_SHELLS}; // what the panel edits
+use crate::simulation::{MAX_TIME_SCALE, MIN_TIME_SCALE};
+use crate::smiles; // molecule from a SMILES field
+use crate::stereo::{StereoMode, MAX_EYE_SEPARATION}; // stereo mode + eye slider
+use crate::style::MoleculeStyle; // molecule style radio buttons
+use crate::theme::{Theme, PRESETS}; // theme picker
+use crate::thermal::MAX_TEMPERATURE; // temperature slider
+use crate::tonemap::ToneMapping; // tone mapping radio buttons
+use crate::vsepr::Geometry; // molecule shapes checkbox
+use glfw::{Action, Key, Modifiers, MouseButton};
+use nalgebra_glm as glm;
+use std::sync::Arc;
+
+// optional control panel (cargo run --features ui)
+// egui does the widgets, egui_glow draws them with its own little gl renderer
+// glfw has no official egui backend, so translating input is done by hand below
+// only the events the panel actually needs are forwarded: pointer, buttons, scroll, and
+// typing plus the editing keys while a text field has the focus (the SMILES one)
+
+pub struct Ui {
+    ctx: egui::Context,
+    painter: egui_glow::Painter,
+    events: Vec<egui::Event>, // collected since the last frame
+    pointer: egui::Pos2,      // last cursor position, glfw button events dont carry one
+    modifiers: egui::Modifiers,
+    points_per_unit: f32, // glfw window units to egui points, from the last frame
+    smiles: String,       // what is typed into the SMILES field, kept between frames
+}
+
+impl Ui {
+    pub fn new(window: &mut glfw::Window) -> Result<Self, egui_glow::PainterError> {
+        // egui_glow talks to gl through glow, which needs its own function pointers
+        // same context as the gl crate uses, just loaded a second time
+        let gl = unsafe {
+            glow::Context::from_loader_function(|symbol| {
+                window.get_proc_address(symbol) as *const _
+            })
+        };
+        let painter = egui_glow::Painter::new(Arc::new(gl), "", None, false)?;
+
+        Ok(Self {
+            ctx: egui::Context::default(),
+            painter,
+            events: Vec::new(),
+            pointer: egui::Pos2::ZERO,
+            modifiers: egui::Modifiers::NONE,
+            points_per_unit: 1.0,
+            smiles: String::new(),
+        })
+    }
+
+    pub fn handle_event(&mut self, event: &glfw::WindowEvent) -> bool {
+        // feed one window event to egui
+        // returns true if the panel swallowed it and the camera should not see it
+        // only presses and scrolls are swallowed: releases always reach the camera
+        // so a drag that started on the atom can never get stuck
+        let over_ui = self.ctx.wants_pointer_input() || self.ctx.is_pointer_over_area();
+        // keys only go to a focused text field, the rest of the time they are shortcuts
+        let typing = self.ctx.wants_keyboard_input();
+
+        match event {
+            glfw::WindowEvent::CursorPos(x, y) => {
+                self.pointer = egui::pos2(*x as f32, *y as f32) * self.points_per_unit;
+                self.events.push(egui::Event::PointerMoved(self.pointer));
+                false
+            }
+            glfw::WindowEvent::MouseButton(button, action, mods) => {
+                self.modifiers = to_egui_modifiers(*mods);
+                let button = match button {
+                    MouseButton::Button1 => egui::PointerButton::Primary,
+                    MouseButton::Button2 => egui::PointerButton::Secondary,
+                    MouseButton::Button3 => egui::PointerButton::Middle,
+                    _ => return false,
+                };
+                self.events.push(egui::Event::PointerButton {
+                    pos: self.pointer,
+                    button,
+                    pressed: *action == Action::Press,
+                    modifiers: self.modifiers,
+                });
+                over_ui && *action == Action::Press
+            }
+            glfw::WindowEvent::Scroll(x, y) => {
+                self.events.push(egui::Event::MouseWheel {
+                    unit: egui::MouseWheelUnit::Line,
+                    delta: egui::vec2(*x as f32, *y as f32),
+                    modifiers: self.modifiers,
+                });
+                over_ui
+            }
+            glfw::WindowEvent::Char(c) if typing => {
+                self.events.push(egui::Event::Text(c.to_string()));
+                true
+            }
+            glfw::WindowEvent::Key(key, _, action, mods) if typing => {
+                self.modifiers = to_egui_modifiers(*mods);
+                if let Some(key) = to_egui_key(*key) {
+                    self.events.push(egui::Event::Key {
+                        key,
+                        physical_key: None,
+                        pressed: *action != Action::Release,
+                        repeat: *action == Action::Repeat,
+                        modifiers: self.modifiers,
+                    });
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn frame(&mut self, window: &glfw::Window, scene: &mut Scene, time: f64) {
+        // build the panel, apply whatever the user changed, draw it on top of the frame
+        let (win_width, _) = window.get_size();
+        let (fb_width, fb_height) = window.get_framebuffer_size();
+        // a point is a hud pixel (see DisplaySettings::hud_scale), so the panel grows with
+        // the hud; glfw cursor coords are in window units, which are not framebuffer pixels
+        // on every platform (macos retina), hence the extra ratio for the pointer
+        let pixels_per_point = scene.display.hud_scale();
+        self.points_per_unit = fb_width as f32 / win_width.max(1) as f32 / pixels_per_point;
+
+        let mut raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(fb_width as f32, fb_height as f32) / pixels_per_point,
+            )),
+            time: Some(time),
+            modifiers: self.modifiers,
+            events: std::mem::take(&mut self.events),
+            ..Default::default()
+        };
+        raw_input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(pixels_per_point);
+
+        let output = self
+            .ctx
+            .run(raw_input, |ctx| control_panel(ctx, scene, &mut self.smiles));
+
+        let primitives = self.ctx.tessellate(output.shapes, output.pixels_per_point);
+        self.painter.paint_and_update_textures(
+            [fb_width as u32, fb_height as u32],
+            output.pixels_per_point,
+            &primitives,
+            &output.textures_delta,
+        );
+
+        // the painter leaves blending + scissor on and depth off, put back what the renderer expects
+        unsafe {
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::Disable(gl::BLEND);
+            gl::Enable(gl::DEPTH_TEST);
+        }
+    }
+}
+
+impl Drop for Ui {
+    fn drop(&mut self) {
+        // painter owns gl objects and insists on being told explicitly
+        self.painter.destroy();
+    }
+}
+
+fn control_panel(ctx: &egui::Context, scene: &mut Scene, smiles: &mut String) {
+    egui::Window::new(t("Controls"))
+        .default_pos([10.0, 120.0])
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.add(
+                egui::Slider::new(&mut scene.time.scale, MIN_TIME_SCALE..=MAX_TIME_SCALE)
+                    .logarithmic(true)
+                    .text(t("simulation speed")),
+            );
+            ui.horizontal(|ui| {
+                let label = if scene.time.paused {
+                    t("resume")
+                } else {
+                    t("pause")
+                };
+                if ui.button(label).clicked() {
+                    scene.time.toggle_pause();
+                }
+                if ui.button(t("step")).clicked() {
+                    scene.time.step();
+                }
+            });
+            ui.horizontal(|ui| {
+                let label = if scene.builder.is_some() {
+                    t("leave builder")
+                } else {
+                    t("build electrons")
+                };
+                if ui.button(label).clicked() {
+                    scene.toggle_builder();
+                }
+                if scene.builder.is_some() {
+                    if ui.button(t("previous")).clicked() {
+                        scene.builder_previous();
+                    }
+                    if ui.button(t("next")).clicked() {
+                        scene.builder_next();
+                    }
+                }
+            });
+            ui.add(
+                egui::Slider::new(&mut scene.display.electron_scale, 0.05..=1.0)
+                    .text(t("electron size")),
+            );
+            ui.add(
+                egui::Slider::new(&mut scene.display.true_scale, 0.0..=1.0)
+                    .text(t("true scale (0 = didactic)")),
+            )
+            .on_hover_text(t(
+                "1 = the real proportion, a nucleus 10000 to 100000 times smaller than its atom",
+            ));
+
+            ui.horizontal(|ui| {
+                ui.label(t("quality"));
+                for quality in [Quality::Low, Quality::Medium, Quality::High, Quality::Ultra] {
+                    ui.radio_value(&mut scene.display.quality, quality, t(quality.name()));
+                }
+            })
+            .response
+            .on_hover_text(t(
+                "sphere detail, trail length, sparks and antialiasing together",
+            ));
+            // the longest trail the quality allows, see Quality::max_trail
+            let max_trail = scene.display.quality.max_trail();
+            ui.add(
+                egui::Slider::new(&mut scene.display.trail_length, 0..=max_trail)
+                    .text(t("trail length")),
+            );
+            ui.checkbox(&mut scene.display.antialiasing, t("antialiasing"));
+            ui.horizontal(|ui| {
+                ui.label(t("background"));
+                for mode in [
+                    BackgroundMode::Solid,
+                    BackgroundMode::Gradient,
+                    BackgroundMode::Skybox,
+                ] {
+                    ui.radio_value(&mut scene.display.background_mode, mode, t(mode.name()));
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(t("stereo"));
+                for mode in [
+                    StereoMode::Off,
+                    StereoMode::Anaglyph,
+                    StereoMode::SideBySide,
+                ] {
+                    ui.radio_value(&mut scene.display.stereo, mode, t(mode.name()));
+                }
+            });
+            ui.add_enabled(
+                scene.display.stereo != StereoMode::Off,
+                egui::Slider::new(&mut scene.display.eye_separation, 0.0..=MAX_EYE_SEPARATION)
+                    .text(t("eye separation")),
+            );
+            let current = scene.display.theme.name;
+            let mut selected = current;
+            egui::ComboBox::from_label(t("theme"))
+                .selected_text(current)
+                .show_ui(ui, |ui| {
+                    for name in PRESETS {
+                        ui.selectable_value(&mut selected, name, name);
+                    }
+                });
+            if selected != current {
+                // a whole palette swap, single colors from atomsim.toml do not survive it
+                scene.set_theme(Theme::preset(selected).unwrap());
+            }
+            ui.horizontal(|ui| {
+                // the highlight is drawn fresh every frame, no rebuild needed like the
+                // nucleon and electron colors
+                let highlight = &mut scene.display.theme.highlight;
+                let mut rgb = [highlight.x, highlight.y, highlight.z];
+                if ui.color_edit_button_rgb(&mut rgb).changed() {
+                    *highlight = glm::vec3(rgb[0], rgb[1], rgb[2]);
+                }
+                ui.add(
+                    egui::Slider::new(&mut scene.display.outline_width, 1.0..=10.0)
+                        .text(t("selection outline")),
+                );
+            });
+            // hud, overlays and this panel, on top of the monitor's own scale
+            ui.add(egui::Slider::new(&mut scene.display.ui_scale, 0.5..=3.0).text(t("ui scale")));
+            ui.checkbox(&mut scene.display.bloom, t("bloom"));
+            ui.add_enabled(
+                scene.display.bloom,
+                egui::Slider::new(&mut scene.display.bloom_threshold, 0.1..=3.0)
+                    .text(t("bloom threshold")),
+            );
+            ui.add_enabled(
+                scene.display.bloom,
+                egui::Slider::new(&mut scene.display.bloom_intensity, 0.0..=3.0)
+                    .text(t("bloom intensity")),
+            );
+            ui.checkbox(&mut scene.display.ssao, t("ambient occlusion"));
+            ui.add_enabled(
+                scene.display.ssao,
+                egui::Slider::new(&mut scene.display.ssao_strength, 0.0..=1.0)
+                    .text(t("occlusion strength")),
+            );
+            ui.add_enabled(
+                scene.display.ssao,
+                egui::Slider::new(&mut scene.display.ssao_radius, 0.01..=0.2)
+                    .text(t("occlusion radius")),
+            );
+            ui.checkbox(&mut scene.display.shadows, t("shadows"));
+            ui.checkbox(&mut scene.display.dof, t("depth of field"));
+            ui.add_enabled(
+                scene.display.dof,
+                egui::Slider::new(&mut scene.display.dof_aperture, 0.0..=1.0).text(t("aperture")),
+            )
+            .on_hover_text(t("sharp at the selected atom, or the camera target"));
+            ui.horizontal(|ui| {
+                ui.label(t("tone mapping"));
+                for mode in [ToneMapping::Off, ToneMapping::Reinhard, ToneMapping::Aces] {
+                    ui.radio_value(&mut scene.display.tone_mapping, mode, t(mode.name()));
+                }
+            });
+            ui.add_enabled(
+                scene.display.tone_mapping != ToneMapping::Off,
+                egui::Slider::new(&mut scene.display.exposure, 0.1..=4.0)
+                    .logarithmic(true)
+                    .text(t("exposure")),
+            );
+            ui.horizontal(|ui| {
+                ui.label(t("ground"));
+                for mode in [GroundMode::Off, GroundMode::Plane, GroundMode::Grid] {
+                    ui.radio_value(&mut scene.display.ground, mode, t(mode.name()));
+                }
+            });
+            let ground = scene.display.ground != GroundMode::Off;
+            ui.add_enabled(
+                ground,
+                egui::Slider::new(&mut scene.display.ground_height, -30.0..=30.0)
+                    .text(t("ground height")),
+            );
+            ui.add_enabled(
+                ground,
+                egui::Slider::new(&mut scene.display.ground_fade, 10.0..=300.0)
+                    .text(t("ground fade")),
+            );
+            ui.checkbox(&mut scene.display.periodic_table, t("periodic table"));
+            ui.checkbox(&mut scene.display.editor, t("molecule editor"));
+            let mut shapes = scene.geometry.is_some();
+            if ui
+                .checkbox(&mut shapes, t("molecule shapes"))
+                .on_hover_text(t("bonded atoms slide into their VSEPR geometry"))
+                .changed()
+            {
+                scene.geometry = shapes.then(Geometry::new);
+            }
+            ui.horizontal(|ui| {
+                ui.label(t("molecule style"));
+                for style in [
+                    MoleculeStyle::Shells,
+                    MoleculeStyle::BallAndStick,
+                    MoleculeStyle::SpaceFilling,
+                    MoleculeStyle::Wireframe,
+                ] {
+                    ui.radio_value(&mut scene.display.molecule_style, style, t(style.name()));
+                }
+            })
+            .response
+            .on_hover_text(t(
+                "one ball per atom in cpk colors instead of nucleons and electrons",
+            ));
+            ui.horizontal(|ui| {
+                ui.label("SMILES");
+                let field = ui.add(
+                    egui::TextEdit::singleline(smiles)
+                        .hint_text("CC(=O)O")
+                        .desired_width(120.0),
+                );
+                let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button(t("build")).clicked() || entered {
+                    // a bad string is reported like a bad file, the scene stays as it was
+                    match smiles::open(scene, smiles.trim()) {
+                        Ok(count) => println!("Built {} atoms from {}", count, smiles.trim()),
+                        Err(err) => log::error!("{}", err),
+                    }
+                }
+            })
+            .response
+            .on_hover_text(t("a molecule written as SMILES replaces the scene"));
+            ui.checkbox(&mut scene.display.spectrum, t("emission spectrum"));
+            ui.checkbox(&mut scene.display.levels, t("energy levels"));
+            ui.checkbox(&mut scene.display.profiler, t("gpu profiler"));
+            ui.checkbox(&mut scene.display.guides, t("shell rings + axes"));
+            ui.checkbox(&mut scene.display.magnetic, t("magnetic moment"));
+            ui.add(
+                egui::Slider::new(&mut scene.display.cloud_opacity, 0.05..=1.0)
+                    .text(t("cloud opacity")),
+            );
+
+            let projection = &mut scene.camera.projection;
+            ui.checkbox(&mut projection.orthographic, t("orthographic"));
+            ui.add_enabled(
+                !projection.orthographic,
+                egui::Slider::new(&mut projection.fov, 20.0..=90.0).text(t("field of view")),
+            );
+            ui.add(
+                egui::Slider::new(&mut projection.near, 0.01..=5.0)
+                    .logarithmic(true)
+                    .text(t("near plane")),
+            );
+            ui.add(
+                egui::Slider::new(&mut projection.far, 50.0..=1000.0)
+                    .logarithmic(true)
+                    .text(t("far plane")),
+            );
+
+            // only offer shells that some atom in the scene actually has
+            let shell_count = scene
+                .atoms
+                .iter()
+                .map(|scene_atom| scene_atom.atom.shells.len())
+                .max()
+                .unwrap_or(0)
+                .min(MAX_SHELLS);
+            ui.horizontal(|ui| {
+                ui.label(t("shells"));
+                for n in 0..shell_count {
+                    ui.checkbox(&mut scene.display.shells_visible[n], format!("{}", n + 1));
+                }
+            });
+            let mut repulsion = scene.repulsion;
+            if ui
+                .checkbox(&mut repulsion, t("electron repulsion"))
+                .on_hover_text(t(
+                    "electrons push each other apart over their shell spheres",
+                ))
+                .changed()
+            {
+                scene.set_repulsion(repulsion);
+            }
+            ui.checkbox(&mut scene.vibration, t("nucleon dynamics"))
+                .on_hover_text(t(
+                "protons and neutrons pull together and protons push apart, the nucleus wobbles",
+            ));
+            ui.add(
+                egui::Slider::new(&mut scene.temperature, 0.0..=MAX_TEMPERATURE)
+                    .suffix(" K")
+                    .text(t("temperature")),
+            )
+            .on_hover_text(t("a warm atom shakes, at 0 K everything sits still"));
+            ui.checkbox(&mut scene.display.spin, t("electron spins"));
+            ui.checkbox(&mut scene.display.textures, t("surface textures"));
+            // pauli in numbers: how the main atom's electrons pair up, shell by shell
+            if let Some(main_atom) = scene.atoms.first() {
+                for shell in &main_atom.atom.shells {
+                    let (up, down) = shell.spins();
+                    ui.label(fill(
+                        "n={}  {}↑ {}↓  {} paired, {} unpaired",
+                        &[
+                            &shell.n,
+                            &up,
+                            &down,
+                            &(2 * up.min(down)),
+                            &up.abs_diff(down),
+                        ],
+                    ));
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.label(t("trail colors"));
+                for color in scene.display.theme.trails.iter_mut().take(shell_count) {
+                    let mut rgb = [color.x, color.y, color.z];
+                    if ui.color_edit_button_rgb(&mut rgb).changed() {
+                        *color = glm::vec3(rgb[0], rgb[1], rgb[2]);
+                    }
+                }
+            });
+
+            // element picker for the main atom (the one at the origin)
+            if let Some(main_atom) = scene.atoms.first() {
+                let current = main_atom.atom.element();
+                let mut selected = current.atomic_number;
+                egui::ComboBox::from_label(t("element"))
+                    .selected_text(format!("{} ({})", current.local_name(), current.symbol))
+                    .show_ui(ui, |ui| {
+                        for element in elements::ELEMENTS.iter() {
+                            ui.selectable_value(
+                                &mut selected,
+                                element.atomic_number,
+                                format!(
+                                    "{:>3} {} ({})",
+                                    element.atomic_number,
+                                    element.local_name(),
+                                    element.symbol
+                                ),
+                            );
+                        }
+                    });
+
+                if selected != current.atomic_number {
+                    let element = elements::by_atomic_number(selected).unwrap();
+                    scene.set_element(0, element);
+                }
+            }
+
+            // comparison mode and the partner's element, picked the same way
+            let partner = scene
+                .comparison
+                .as_ref()
+                .map(|comparison| comparison.partner);
+            ui.horizontal(|ui| {
+                let label = if partner.is_some() {
+                    t("stop comparing")
+                } else {
+                    t("compare")
+                };
+                if ui.button(label).clicked() {
+                    scene.toggle_comparison();
+                }
+                let Some(partner) = partner else {
+                    return;
+                };
+                let current = scene.atoms[partner].atom.element();
+                let mut selected = current.atomic_number;
+                egui::ComboBox::from_label(t("with"))
+                    .selected_text(format!("{} ({})", current.local_name(), current.symbol))
+                    .show_ui(ui, |ui| {
+                        for element in elements::ELEMENTS.iter() {
+                            ui.selectable_value(
+                                &mut selected,
+                                element.atomic_number,
+                                format!(
+                                    "{:>3} {} ({})",
+                                    element.atomic_number,
+                                    element.local_name(),
+                                    element.symbol
+                                ),
+                            );
+                        }
+                    });
+                if selected != current.atomic_number {
+                    scene.set_partner_element(elements::by_atomic_number(selected).unwrap());
+                }
+            });
+
+            // scattering mode, the next shot's settings and the trigger
+            ui.horizontal(|ui| {
+                let label = if scene.scattering.is_some() {
+                    t("stop scattering")
+                } else {
+                    t("alpha scattering")
+                };
+                if ui.button(label).clicked() {
+                    scene.toggle_scattering();
+                }
+                if scene.scattering.is_some() {
+                    if ui.button(t("fire")).clicked() {
+                        scene.fire_alpha();
+                    }
+                    if let Some(scattering) = &mut scene.scattering {
+                        if ui.button(t("clear")).clicked() {
+                            scattering.clear();
+                        }
+                    }
+                }
+            });
+            if let Some(scattering) = &mut scene.scattering {
+                ui.add(
+                    egui::Slider::new(
+                        &mut scattering.impact,
+                        scattering::MIN_IMPACT..=scattering::MAX_IMPACT,
+                    )
+                    .text(t("impact parameter (fm)")),
+                )
+                .on_hover_text(t("how far the alpha would miss the nucleus going straight"));
+                ui.add(
+                    egui::Slider::new(
+                        &mut scattering.energy,
+                        scattering::MIN_ENERGY..=scattering::MAX_ENERGY,
+                    )
+                    .logarithmic(true)
+                    .text(t("energy (MeV)")),
+                );
+            }
+
+            // light fired at the selected atom, tuned by hand or straight onto its lines
+            let target = scene.target_atom();
+            if let Some(scene_atom) = scene.atoms.get(target) {
+                let atom = &scene_atom.atom;
+                ui.add(
+                    egui::Slider::new(
+                        &mut scene.lamp_wavelength,
+                        absorption::MIN_WAVELENGTH..=absorption::MAX_WAVELENGTH,
+                    )
+                    .logarithmic(true)
+                    .text(t("photon (nm)")),
+                );
+                let wavelength = scene.lamp_wavelength;
+                // both happen below, the atom is still borrowed here
+                let mut fire = false;
+                let mut line = None;
+                ui.horizontal(|ui| {
+                    fire = ui.button(t("fire photon")).clicked();
+                    if ui.button(t("previous line")).clicked() {
+                        line = absorption::next_line(atom, wavelength, false);
+                    }
+                    if ui.button(t("next line")).clicked() {
+                        line = absorption::next_line(atom, wavelength, true);
+                    }
+                });
+                ui.label(match absorption::matching(atom, wavelength) {
+                    Some(transition) => fill(
+                        "{} absorbs it, n={} -> n={}",
+                        &[
+                            &atom.element().symbol,
+                            &(transition.electron.0 + 1),
+                            &(transition.to + 1),
+                        ],
+                    ),
+                    None => fill("passes through {}", &[&atom.element().symbol]),
+                });
+                if let Some(line) = line {
+                    scene.lamp_wavelength = line;
+                }
+                if fire {
+                    scene.fire_photon();
+                }
+            }
+
+            // field view of the selected atom (the main one without a selection)
+            let target = scene.target_atom();
+            if let Some(scene_atom) = scene.atoms.get_mut(target) {
+                ui.horizontal(|ui| {
+                    ui.label(fill("field of {}", &[&scene_atom.atom.element().symbol]));
+                    for view in [FieldView::Off, FieldView::Lines, FieldView::Slice] {
+                        ui.radio_value(&mut scene_atom.field, view, t(view.name()));
+                    }
+                });
+            }
+
+            // quantum numbers of the orbital view, the sliders only ever offer valid values
+            // (l and m are clamped after each one, n may just have shrunk under them)
+            // written back only when touched, a startup orbital past MAX_N stays as it is
+            let mut picked = scene.orbital.clamped();
+            ui.label(fill("orbital {}", &[&scene.orbital.name()]));
+            let mut changed = ui
+                .add(egui::Slider::new(&mut picked.n, 1..=MAX_N).text("n"))
+                .changed();
+            picked = picked.clamped();
+            changed |= ui
+                .add_enabled(
+                    picked.n > 1,
+                    egui::Slider::new(&mut picked.l, 0..=picked.n - 1).text("l"),
+                )
+                .changed();
+            picked = picked.clamped();
+            changed |= ui
+                .add_enabled(
+                    picked.l > 0 && !picked.subshell,
+                    egui::Slider::new(&mut picked.m, -picked.l..=picked.l).text("m"),
+                )
+                .changed();
+            changed |= ui
+                .checkbox(&mut picked.subshell, t("full subshell"))
+                .on_hover_text(t("all m of this l added up, a round cloud"))
+                .changed();
+            if changed {
+                scene.orbital = picked;
+            }
+
+            // cross-section plane, the sliders stay usable while it is off so it can be set up
+            // before cutting
+            let clip = &mut scene.display.clip;
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut clip.enabled, t("cross-section"));
+                if ui.button(t("face camera")).clicked() {
+                    clip.face(&scene.camera);
+                }
+            });
+            ui.add(egui::Slider::new(&mut clip.azimuth, 0.0..=360.0).text(t("plane azimuth")));
+            ui.add(egui::Slider::new(&mut clip.elevation, 0.0..=180.0).text(t("plane elevation")));
+            ui.add(egui::Slider::new(&mut clip.offset, -30.0..=30.0).text(t("plane offset")));
+            ui.checkbox(&mut scene.display.density_slice, t("density slice"));
+            ui.checkbox(&mut scene.display.radial_plot, t("radial distribution"));
+        });
+}
+
+fn to_egui_key(key: Key) -> Option<egui::Key> {
+    // the keys a single line text field edits with
+    Some(match key {
+        Key::Backspace => egui::Key::Backspace,
+        Key::Delete => egui::Key::Delete,
+        Key::Enter | Key::KpEnter => egui::Key::Enter,
+        Key::Escape => egui::Key::Escape,
+        Key::Left => egui::Key::ArrowLeft,
+        Key::Right => egui::Key::ArrowRight,
+        Key::Home => egui::Key::Home,
+        Key::End => egui::Key::End,
+        Key::A => egui::Key::A, // Ctrl+A selects everything
+        _ => return None,
+    })
+}
+
+fn to_egui_modifiers(mods: Modifiers) -> egui::Modifiers {
+    egui::Modifiers {
+        alt: mods.contains(Modifiers::Alt),
+        ctrl: mods.contains(Modifiers::Control),
+        shift: mods.contains(Modifiers::Shift),
+        // no mac_cmd / command split on this side, ctrl is the command key
+        mac_cmd: false,
+        command: mods.contains(Modifiers::Control),
+    }
+}
@@ -0,0 +1,193 @@
+use crate::nucleus::{self, NUCLEON_RADIUS}; // where the nucleons start out
+use lazy_static::lazy_static; // settled shape cache
+use nalgebra_glm as glm;
+use std::sync::Mutex;
+
+// nucleon dynamics (Shift+D): instead of sitting in their packed places, the nucleons of a
+// nucleus move under the forces between them, so the nucleus wobbles and breathes on its own
+//   strong force  a morse potential between every pair, stiff when two overlap, attractive
+//                 out to nearly two nucleon widths past touching and nothing further away
+//   coulomb       protons push each other apart, k / r^2, at every distance
+// integrated with velocity verlet, which keeps the energy of the starting kick sloshing
+// around as vibration instead of letting it leak away or build up, so the jiggle neither
+// dies down nor blows up
+// the ratio is roughly the real one, coulomb at touching is a twentieth of the well depth;
+// the stiffness is made up so the wobble is slow enough to watch, a real nucleus rings at
+// 1e22 Hz
+// O(A^2) per step like the packing, uranium's 238 nucleons are 28000 pairs
+
+const WELL_DEPTH: f64 = 4.0; // morse D, energy of one bonded pair
+const WELL_WIDTH: f64 = 6.0; // morse a, per world unit, higher = shorter range and stiffer
+const COULOMB: f64 = 0.1; // k of the proton-proton push
+const RANGE: f64 = 5.0 / WELL_WIDTH; // past touching, where the morse force is cut off
+const KICK: f64 = 0.5; // world units per second, random start so symmetric nuclei move too
+
+// the packing is only roughly where these forces balance, and let go from there the extra
+// energy melts the nucleus (uranium boils off nucleons), so it first settles with friction,
+// once per (Z, N) like the packing, a third of a second for uranium
+const SETTLE_STEPS: usize = 600;
+const SETTLE_DT: f64 = 1.0 / 120.0;
+const SETTLE_DAMPING: f64 = 0.98; // of the velocity kept per settling step
+
+#[derive(Clone)]
+pub struct Vibration {
+    rest: Vec<glm::DVec3>, // the packed places, see nucleus.rs
+    positions: Vec<glm::DVec3>,
+    velocities: Vec<glm::DVec3>,
+    forces: Vec<glm::DVec3>, // at the current positions, verlet reuses them for the next half
+    protons: Vec<bool>,
+}
+
+impl Vibration {
+    pub fn new(protons: u32, neutrons: u32) -> Self {
+        // same nucleon order as nucleus::packed, so offsets line up with the ecs entities
+        let packed = nucleus::packed(protons, neutrons);
+        let rest: Vec<glm::DVec3> = packed
+            .iter()
+            .map(|nucleon| nucleon.position.cast())
+            .collect();
+        let is_proton: Vec<bool> = packed.iter().map(|nucleon| nucleon.is_proton).collect();
+        let positions = settled(protons, neutrons, &rest, &is_proton);
+        let mut velocities: Vec<glm::DVec3> = positions
+            .iter()
+            .map(|_| {
+                glm::vec3(
+                    rand::random::<f64>() - 0.5,
+                    rand::random::<f64>() - 0.5,
+                    rand::random::<f64>() - 0.5,
+                ) * (2.0 * KICK)
+            })
+            .collect();
+        still(&positions, &mut velocities);
+        Self {
+            forces: forces(&positions, &is_proton),
+            positions,
+            velocities,
+            rest,
+            protons: is_proton,
+        }
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        // velocity verlet, unit masses: half kick, drift, new forces, half kick
+        for (velocity, force) in self.velocities.iter_mut().zip(&self.forces) {
+            *velocity += force * (0.5 * dt);
+        }
+        for (position, velocity) in self.positions.iter_mut().zip(&self.velocities) {
+            *position += velocity * dt;
+        }
+        self.forces = forces(&self.positions, &self.protons);
+        for (velocity, force) in self.velocities.iter_mut().zip(&self.forces) {
+            *velocity += force * (0.5 * dt);
+        }
+    }
+
+    pub fn offsets(&self, out: &mut [glm::Vec3]) {
+        // where every nucleon is against its packed place, what the renderer adds on top
+        for ((offset, position), rest) in out.iter_mut().zip(&self.positions).zip(&self.rest) {
+            *offset = (position - rest).cast();
+        }
+    }
+}
+
+fn settled(
+    protons: u32,
+    neutrons: u32,
+    rest: &[glm::DVec3],
+    is_proton: &[bool],
+) -> Vec<glm::DVec3> {
+    // where the packed nucleus comes to a standstill under these forces
+    lazy_static! {
+        static ref SETTLED_CACHE: Mutex<Vec<(u32, u32, Vec<glm::DVec3>)>> = Mutex::new(Vec::new());
+    }
+
+    let mut cache = SETTLED_CACHE.lock().unwrap();
+    if let Some((_, _, positions)) = cache
+        .iter()
+        .find(|(z, n, _)| *z == protons && *n == neutrons)
+    {
+        return positions.clone();
+    }
+
+    let mut vibration = Vibration {
+        rest: Vec::new(), // only positions matter here
+        positions: rest.to_vec(),
+        velocities: vec![glm::vec3(0.0, 0.0, 0.0); rest.len()],
+        forces: forces(rest, is_proton),
+        protons: is_proton.to_vec(),
+    };
+    for _ in 0..SETTLE_STEPS {
+        vibration.step(SETTLE_DT);
+        for velocity in &mut vibration.velocities {
+            *velocity *= SETTLE_DAMPING;
+        }
+    }
+    cache.push((protons, neutrons, vibration.positions.clone()));
+    vibration.positions
+}
+
+fn forces(positions: &[glm::DVec3], protons: &[bool]) -> Vec<glm::DVec3> {
+    // every pair once, pushed onto both
+    let touching = 2.0 * NUCLEON_RADIUS as f64;
+    let cutoff = touching + RANGE;
+    let mut forces = vec![glm::vec3(0.0, 0.0, 0.0); positions.len()];
+    for i in 0..positions.len() {
+        for j in i + 1..positions.len() {
+            let apart = positions[i] - positions[j];
+            let r = glm::length(&apart).max(1e-3);
+            // along apart, so positive pushes the two away from each other
+            let mut push = 0.0;
+            if r < cutoff {
+                // shifted so it fades to nothing at the cutoff instead of dropping off a step,
+                // which would leak energy in or out every time a pair crossed it
+                push += morse(r - touching) - morse(RANGE);
+            }
+            if protons[i] && protons[j] {
+                push += COULOMB / (r * r);
+            }
+            let force = apart * (push / r);
+            forces[i] += force;
+            forces[j] -= force;
+        }
+    }
+    forces
+}
+
+fn morse(stretch: f64) -> f64 {
+    // -dV/dr of the morse potential D ((1 - e^(-a x))^2 - 1), x = distance past touching
+    let e = (-WELL_WIDTH * stretch).exp();
+    2.0 * WELL_DEPTH * WELL_WIDTH * e * (e - 1.0)
+}
+
+fn still(positions: &[glm::DVec3], velocities: &mut [glm::DVec3]) {
+    // take out any drift and spin the random start has, the nucleus should stay where it is
+    // and not slowly turn away from its atom's packed places
+    let count = positions.len().max(1) as f64;
+    let drift = velocities
+        .iter()
+        .fold(glm::vec3(0.0, 0.0, 0.0), |sum, v| sum + v)
+        / count;
+    velocities.iter_mut().for_each(|v| *v -= drift);
+    if positions.len() < 3 {
+        // two points cannot spin about the line between them, and the rest barely matters
+        return;
+    }
+    let center = positions
+        .iter()
+        .fold(glm::vec3(0.0, 0.0, 0.0), |sum, p| sum + p)
+        / count;
+    let mut momentum = glm::vec3(0.0, 0.0, 0.0);
+    let mut inertia = glm::DMat3::zeros();
+    for (p, v) in positions.iter().zip(velocities.iter()) {
+        let r = p - center;
+        momentum += glm::cross(&r, v);
+        inertia += glm::DMat3::identity() * glm::dot(&r, &r) - r * r.transpose();
+    }
+    let Some(inverse) = inertia.try_inverse() else {
+        return;
+    };
+    let spin = inverse * momentum;
+    for (p, v) in positions.iter().zip(velocities.iter_mut()) {
+        *v -= glm::cross(&spin, &(p - center));
+    }
+}
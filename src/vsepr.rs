@@ -0,0 +1,223 @@
+use crate::decay::random_direction; // lone pairs start anywhere
+use crate::molecule; // which elements have a main-group valence
+use crate::scene::{Bond, SceneAtom};
+use crate::simulation::Atom; // shell sizes set the bond lengths
+use nalgebra_glm as glm;
+
+// molecule shapes (Shift+B): bonded atoms drift into the geometry VSEPR gives them
+// every atom with two or more bonds is a center; its electron domains, one per bonded
+// neighbour (a double bond is still one) and one per lone pair, push each other apart over
+// the sphere around it, which is all VSEPR says, and the arrangement they settle in is the
+// familiar one: two domains linear, three trigonal planar, four tetrahedral, five trigonal
+// bipyramidal, six octahedral; lone pairs push a little harder, so water bends to about
+// 104 degrees instead of 109.5
+// bonds are springs to the length the atoms were placed at by main.rs (shells touching plus
+// a gap), atoms that are not bonded but both in a molecule are kept from overlapping
+// it runs on real time like the camera, a frame at a time, so the atoms can be watched
+// sliding into place; once nothing moves any more it costs a pass over the bonds per frame
+// lone pairs are counted from the outer electrons the element has (minus its charge) that
+// its bonds do not use, two per pair; transition metals get none
+
+const GAP: f32 = 2.0; // between the shells of two bonded atoms, the spacing main.rs uses
+const STIFFNESS: f32 = 3.0; // per second, how fast a bond finds its length
+const BENDING: f32 = 2.0; // how hard the domains around a center spread out
+const LONE_PAIR: f32 = 1.2; // a lone pair pushes this much harder than a bond
+const KICK: f32 = 0.05; // world units, shakes a new molecule out of a flat start
+const SETTLED: f32 = 0.01; // world units per second, when nothing moves faster it stops
+const MAX_STEP: f32 = 0.5; // world units per frame, a wild start does not fling atoms away
+
+pub struct Geometry {
+    lone_pairs: Vec<Vec<glm::Vec3>>, // per atom, unit directions from its nucleus
+    bonds: Vec<(usize, usize, usize)>, // a, b, order as last seen, to notice edits
+}
+
+impl Geometry {
+    pub fn new() -> Self {
+        Self {
+            lone_pairs: Vec::new(),
+            bonds: Vec::new(),
+        }
+    }
+
+    pub fn relax(&mut self, atoms: &mut [SceneAtom], bonds: &[Bond], dt: f32) -> bool {
+        // one frame of sliding towards the shape, false when no atom moved
+        if bonds.is_empty() || dt <= 0.0 {
+            return false;
+        }
+        let count = atoms.len();
+        let mut neighbours = vec![Vec::new(); count];
+        let mut bond_counts = vec![0; count];
+        for bond in bonds {
+            neighbours[bond.a].push(bond.b);
+            neighbours[bond.b].push(bond.a);
+            bond_counts[bond.a] += bond.order.count();
+            bond_counts[bond.b] += bond.order.count();
+        }
+
+        // a new or changed bond: every atom in a molecule gets a little shake, flat layouts
+        // (everything placed on the screen plane) are balanced but not stable, and this
+        // tips them into the third dimension
+        let seen: Vec<(usize, usize, usize)> = bonds
+            .iter()
+            .map(|bond| (bond.a, bond.b, bond.order.count()))
+            .collect();
+        let kicked = seen != self.bonds || self.lone_pairs.len() != count;
+        if kicked {
+            self.bonds = seen;
+            self.lone_pairs.resize(count, Vec::new());
+            let kicks: Vec<glm::Vec3> = (0..count)
+                .map(|index| {
+                    if neighbours[index].is_empty() {
+                        glm::vec3(0.0, 0.0, 0.0)
+                    } else {
+                        random_direction() * KICK
+                    }
+                })
+                .collect();
+            let mean = kicks
+                .iter()
+                .fold(glm::vec3(0.0, 0.0, 0.0), |sum, k| sum + k)
+                / count as f32;
+            for (index, scene_atom) in atoms.iter_mut().enumerate() {
+                if !neighbours[index].is_empty() {
+                    scene_atom.position += kicks[index] - mean;
+                }
+            }
+        }
+        for (index, scene_atom) in atoms.iter().enumerate() {
+            let wanted = lone_pair_count(scene_atom, bond_counts[index]);
+            let pairs = &mut self.lone_pairs[index];
+            if pairs.len() != wanted {
+                *pairs = (0..wanted).map(|_| random_direction()).collect();
+            }
+        }
+
+        let mut forces = vec![glm::vec3(0.0, 0.0, 0.0); count];
+        for bond in bonds {
+            let (a, b) = (&atoms[bond.a], &atoms[bond.b]);
+            let apart = b.position - a.position;
+            let length = glm::length(&apart).max(1e-4);
+            let rest = bond_length(&a.atom, &b.atom);
+            let force = apart * (STIFFNESS * (length - rest) / length);
+            forces[bond.a] += force;
+            forces[bond.b] -= force;
+        }
+
+        for center in 0..count {
+            if neighbours[center].len() < 2 {
+                continue; // one neighbour has no angle to keep
+            }
+            let origin = atoms[center].position;
+            let mut domains: Vec<(glm::Vec3, f32)> = neighbours[center]
+                .iter()
+                .map(|&other| (glm::normalize(&(atoms[other].position - origin)), 1.0))
+                .collect();
+            domains.extend(self.lone_pairs[center].iter().map(|d| (*d, LONE_PAIR)));
+            let pushes = spread(&domains);
+            for (slot, &other) in neighbours[center].iter().enumerate() {
+                // pushed sideways at its end of the bond, the center takes the other side
+                let reach = glm::distance(&atoms[other].position, &origin);
+                let force = pushes[slot] * (BENDING * reach);
+                forces[other] += force;
+                forces[center] -= force;
+            }
+            let first_pair = neighbours[center].len();
+            for (pair, push) in self.lone_pairs[center]
+                .iter_mut()
+                .zip(&pushes[first_pair..])
+            {
+                *pair = glm::normalize(&(*pair + push * (BENDING * dt)));
+            }
+        }
+
+        // atoms of molecules that are not bonded to each other keep their shells apart
+        for a in 0..count {
+            for b in a + 1..count {
+                if neighbours[a].is_empty()
+                    || neighbours[b].is_empty()
+                    || neighbours[a].contains(&b)
+                {
+                    continue;
+                }
+                let apart = atoms[b].position - atoms[a].position;
+                let length = glm::length(&apart).max(1e-4);
+                let closest = bond_length(&atoms[a].atom, &atoms[b].atom);
+                if length < closest {
+                    let force = apart * (STIFFNESS * (length - closest) / length);
+                    forces[a] += force;
+                    forces[b] -= force;
+                }
+            }
+        }
+
+        let fastest = forces.iter().map(glm::length).fold(0.0, f32::max);
+        if fastest < SETTLED {
+            return kicked;
+        }
+        for (scene_atom, force) in atoms.iter_mut().zip(&forces) {
+            // overdamped: the force is the speed, nothing overshoots or keeps swinging
+            let step = force * dt;
+            let length = glm::length(&step);
+            scene_atom.position += if length > MAX_STEP {
+                step * (MAX_STEP / length)
+            } else {
+                step
+            };
+        }
+        true
+    }
+}
+
+pub fn shape(neighbours: usize, lone_pairs: usize) -> String {
+    // the textbook name for a center with this many bonded atoms and lone pairs
+    let name = match (neighbours, lone_pairs) {
+        (2, 0) | (2, 3) => "linear",
+        (3, 0) => "trigonal planar",
+        (2, 1) | (2, 2) => "bent",
+        (4, 0) => "tetrahedral",
+        (3, 1) => "trigonal pyramidal",
+        (5, 0) => "trigonal bipyramidal",
+        (4, 1) => "seesaw",
+        (3, 2) => "T-shaped",
+        (6, 0) => "octahedral",
+        (5, 1) => "square pyramidal",
+        (4, 2) => "square planar",
+        _ => return format!("AX{}E{}", neighbours, lone_pairs),
+    };
+    name.to_string()
+}
+
+pub fn lone_pair_count(scene_atom: &SceneAtom, bonds: usize) -> usize {
+    // outer electrons the bonds leave over, in pairs
+    let atom = &scene_atom.atom;
+    let element = atom.element();
+    if molecule::valence(element).is_none() {
+        return 0;
+    }
+    let outer = element.shells.last().copied().unwrap_or(0) as i32 - atom.charge();
+    (outer - bonds as i32).max(0) as usize / 2
+}
+
+pub fn bond_length(a: &Atom, b: &Atom) -> f32 {
+    // world units between two bonded nuclei
+    a.outer_radius() + b.outer_radius() + GAP
+}
+
+fn spread(domains: &[(glm::Vec3, f32)]) -> Vec<glm::Vec3> {
+    // every domain pushed away from the others, 1 / d^2 like charges, only along the sphere
+    domains
+        .iter()
+        .enumerate()
+        .map(|(i, (direction, weight))| {
+            let away = domains.iter().enumerate().filter(|(j, _)| *j != i).fold(
+                glm::vec3(0.0, 0.0, 0.0),
+                |sum, (_, (other, other_weight))| {
+                    let apart = direction - other;
+                    let distance = glm::length(&apart).max(1e-3);
+                    sum + apart * (weight * other_weight / (distance * distance * distance))
+                },
+            );
+            away - direction * glm::dot(&away, direction)
+        })
+        .collect()
+}
@@ -0,0 +1,219 @@
+use crate::camera::Projection; // aspect follows the framebuffer
+use glfw::Context; // make_current, swap_buffers
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+// the glfw window and its opengl context, everything main used to do by hand:
+// hints, creation, vsync, which events to listen to, loading the gl functions
+// after new() the context is current and gl calls work
+// derefs to glfw::Window, so cursor / size queries go straight through
+
+const TITLE: &str = "Atom Simulator";
+
+pub struct WindowSettings {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub visible: bool, // headless runs still need a context, just no window on screen
+    pub samples: u32,  // msaa samples asked for, 0 = none
+    pub debug_context: bool, // needed for GL_DEBUG_OUTPUT, see gl_debug.rs
+    pub vsync: bool,
+}
+
+#[derive(Debug)]
+pub enum WindowError {
+    Init(glfw::InitError),
+    Create, // glfw does not say why here, glfw_error has already logged it
+}
+
+impl fmt::Display for WindowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WindowError::Init(err) => write!(f, "could not initialize glfw: {}", err),
+            WindowError::Create => write!(f, "could not create the window / opengl context"),
+        }
+    }
+}
+
+impl std::error::Error for WindowError {}
+
+pub struct Window {
+    glfw: glfw::Glfw,
+    handle: glfw::PWindow,
+    events: glfw::GlfwReceiver<(f64, glfw::WindowEvent)>,
+    vsync: bool,
+    windowed: (i32, i32, u32, u32), // position and size to go back to when leaving fullscreen
+}
+
+impl Window {
+    pub fn new(settings: &WindowSettings) -> Result<Self, WindowError> {
+        let mut glfw = glfw::init(glfw_error).map_err(WindowError::Init)?;
+        if !settings.visible {
+            glfw.window_hint(glfw::WindowHint::Visible(false));
+        }
+        if settings.samples > 0 {
+            glfw.window_hint(glfw::WindowHint::Samples(Some(settings.samples)));
+        }
+        // linear shading needs the window to encode to srgb, see Renderer::render
+        glfw.window_hint(glfw::WindowHint::SRgbCapable(true));
+        if settings.debug_context {
+            glfw.window_hint(glfw::WindowHint::OpenGlDebugContext(true));
+        }
+        // on scaled displays (windows, x11 with a dpi set) ask for e.g. 1920x1080 at 150%
+        // when 1280x720 is configured, so the window is as big as it would be at 100%;
+        // macos does this on its own, there the framebuffer is the one that grows
+        glfw.window_hint(glfw::WindowHint::ScaleToMonitor(true));
+        let (handle, events) = glfw
+            .with_primary_monitor(|glfw, monitor| {
+                // fullscreen takes the closest video mode the monitor has to the requested size
+                let mode = match monitor {
+                    Some(monitor) if settings.fullscreen => glfw::WindowMode::FullScreen(monitor),
+                    _ => glfw::WindowMode::Windowed,
+                };
+                glfw.create_window(settings.width, settings.height, TITLE, mode)
+            })
+            .ok_or(WindowError::Create)?;
+
+        let mut window = Self {
+            glfw,
+            handle,
+            events,
+            vsync: settings.vsync,
+            // only used when starting fullscreen, otherwise overwritten on the way in
+            windowed: (100, 100, settings.width, settings.height),
+        };
+        window.handle.make_current();
+        window.apply_vsync();
+        window.handle.set_key_polling(true);
+        window.handle.set_char_polling(true); // typed text for the console
+        window.handle.set_cursor_pos_polling(true);
+        window.handle.set_mouse_button_polling(true);
+        window.handle.set_scroll_polling(true);
+        window.handle.set_framebuffer_size_polling(true);
+        window.handle.set_drag_and_drop_polling(true); // molecule files, see import.rs
+        gl::load_with(|symbol| window.handle.get_proc_address(symbol) as *const _);
+        Ok(window)
+    }
+
+    pub fn content_scale(&self) -> f32 {
+        // how much bigger than a 96 dpi screen this one is, see DisplaySettings::hud_scale
+        // x and y are the same on every display that matters
+        let (x, _) = self.handle.get_content_scale();
+        if x > 0.0 {
+            x
+        } else {
+            1.0
+        }
+    }
+
+    pub fn pixel_ratio(&self) -> (f64, f64) {
+        // framebuffer pixels per window unit: cursor coords are in window units, on retina
+        // screens the framebuffer has twice as many pixels
+        let (win_width, win_height) = self.handle.get_size();
+        let (fb_width, fb_height) = self.handle.get_framebuffer_size();
+        (
+            fb_width as f64 / win_width.max(1) as f64,
+            fb_height as f64 / win_height.max(1) as f64,
+        )
+    }
+
+    fn apply_vsync(&mut self) {
+        self.glfw.set_swap_interval(if self.vsync {
+            glfw::SwapInterval::Sync(1)
+        } else {
+            glfw::SwapInterval::None
+        });
+    }
+
+    pub fn poll_events(
+        &mut self,
+        projection: &mut Projection,
+    ) -> std::vec::IntoIter<glfw::WindowEvent> {
+        // everything that happened since the last frame
+        // resizes are dealt with here (viewport + aspect) and still passed on,
+        // collected first so the caller can use the window while going through them
+        self.glfw.poll_events();
+        let events: Vec<glfw::WindowEvent> = glfw::flush_messages(&self.events)
+            .map(|(_, event)| event)
+            .collect();
+        for event in &events {
+            if let glfw::WindowEvent::FramebufferSize(width, height) = event {
+                unsafe { gl::Viewport(0, 0, *width, *height) };
+                projection.resize(*width, *height);
+            }
+        }
+        events.into_iter()
+    }
+
+    pub fn swap_buffers(&mut self) {
+        self.handle.swap_buffers();
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.handle
+            .with_window_mode(|mode| matches!(mode, glfw::WindowMode::FullScreen(_)))
+    }
+
+    pub fn toggle_fullscreen(&mut self) {
+        // windowed -> primary monitor at its current video mode -> back where the window was
+        // the framebuffer size event this causes comes through poll_events like any resize
+        if self.is_fullscreen() {
+            let (x, y, width, height) = self.windowed;
+            self.handle
+                .set_monitor(glfw::WindowMode::Windowed, x, y, width, height, None);
+        } else {
+            let (x, y) = self.handle.get_pos();
+            let (width, height) = self.handle.get_size();
+            self.windowed = (x, y, width.max(1) as u32, height.max(1) as u32);
+            let handle = &mut self.handle;
+            self.glfw.with_primary_monitor(|_, monitor| {
+                let Some(monitor) = monitor else {
+                    log::warn!("no monitor to go fullscreen on");
+                    return;
+                };
+                let Some(mode) = monitor.get_video_mode() else {
+                    log::warn!("primary monitor has no video mode, staying windowed");
+                    return;
+                };
+                handle.set_monitor(
+                    glfw::WindowMode::FullScreen(monitor),
+                    0,
+                    0,
+                    mode.width,
+                    mode.height,
+                    Some(mode.refresh_rate),
+                );
+            });
+        }
+        // some platforms drop the swap interval when the monitor changes
+        self.apply_vsync();
+        log::info!(
+            "{}",
+            if self.is_fullscreen() {
+                "fullscreen"
+            } else {
+                "windowed"
+            }
+        );
+    }
+}
+
+fn glfw_error(_: glfw::Error, description: String) {
+    // glfw reports problems through a callback, log them instead of panicking inside it
+    // the call that failed then returns None / Err and is handled where it happened
+    log::error!("glfw: {}", description);
+}
+
+impl Deref for Window {
+    type Target = glfw::Window;
+
+    fn deref(&self) -> &glfw::Window {
+        &self.handle
+    }
+}
+
+impl DerefMut for Window {
+    fn deref_mut(&mut self) -> &mut glfw::Window {
+        &mut self.handle
+    }
+}